@@ -0,0 +1,89 @@
+//! Module for the ammunition of the ranged weapons.
+//!
+//! Arrows and bolts are [Stackable](super::Stackable) items with
+//! [Ammunition], which are worn in the [EquipmentSlot::Quiver]. Every
+//! shot of a ranged weapon takes a single piece off the quivered stack,
+//! see [nock]. Once the shot has landed, the piece either breaks or is
+//! left on the target tile to be picked up again, see [land].
+
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+use specs::world::EntitiesRes;
+
+use super::{config, entity_factory};
+use super::{Ammunition, AmmunitionKind, EquipmentSlot, Equipped, Loot, Position};
+
+/// Returns the ammunition of the passed `kind` worn in the quiver
+/// of the `owner`, or `None` if the quiver is empty or holds
+/// another kind of ammunition.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the `owner` and its items.
+/// * `owner`: The [Entity] wearing the quiver.
+/// * `kind`: The [AmmunitionKind] fired by the owner's weapon.
+///
+pub fn quivered(ecs: &World, owner: Entity, kind: AmmunitionKind) -> Option<Entity> {
+    let entities = ecs.entities();
+    let equipped = ecs.read_storage::<Equipped>();
+    let ammunition = ecs.read_storage::<Ammunition>();
+
+    (&entities, &equipped, &ammunition)
+        .join()
+        .find(|(_, worn, ammunition)| {
+            worn.owner == owner && worn.slot == EquipmentSlot::Quiver && ammunition.kind == kind
+        })
+        .map(|(entity, _, _)| entity)
+}
+
+/// Takes a single piece of the ammunition of the passed `kind` out of
+/// the quiver of the `owner` for a shot and returns it. Returns `None`
+/// if the owner has run out of fitting ammunition.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the `owner` and its items.
+/// * `owner`: The [Entity] taking the shot.
+/// * `kind`: The [AmmunitionKind] fired by the owner's weapon.
+///
+/// # Notes
+/// * The returned piece is in flight, it's neither in the owner's
+///   backpack nor on the map, until it has [land]ed.
+/// * The last piece of a stack empties the quiver.
+///
+pub fn nock(ecs: &mut World, owner: Entity, kind: AmmunitionKind) -> Option<Entity> {
+    let quiver = quivered(ecs, owner, kind)?;
+    let piece = entity_factory::split_stack(ecs, quiver);
+
+    ecs.write_storage::<Loot>().remove(piece);
+    ecs.write_storage::<Equipped>().remove(piece);
+
+    Some(piece)
+}
+
+/// Lets the passed `piece` of ammunition, that has been shot, land on the
+/// `target` tile. By the [config::AMMUNITION_RECOVERY_CHANCE] the piece
+/// stays intact and is left on the tile, otherwise it breaks. Returns
+/// `true` if the piece can be recovered.
+///
+/// # Arguments
+/// * `rng`: The [RandomNumberGenerator] rolling the recovery.
+/// * `entities`: All entities of the `ecs`.
+/// * `positions`: The [Position] storage of the `ecs`.
+/// * `piece`: The piece of ammunition returned by [nock].
+/// * `target`: The [Position] the shot has landed on.
+///
+pub fn land(
+    rng: &mut RandomNumberGenerator,
+    entities: &EntitiesRes,
+    positions: &mut WriteStorage<Position>,
+    piece: Entity,
+    target: Position,
+) -> bool {
+    if rng.roll_dice(1, 100) <= config::AMMUNITION_RECOVERY_CHANCE
+        && positions.insert(piece, target).is_ok()
+    {
+        return true;
+    }
+
+    let _ = entities.delete(piece);
+    false
+}
@@ -0,0 +1,182 @@
+//! Module for validating the content assets of the game on startup.
+//!
+//! Content is referenced by name throughout the game, e.g. spawn table
+//! entries name the entities to create and items name the scripts they
+//! run. Instead of failing on first use deep inside of a system, all
+//! references are checked once on startup and every missing or unreadable
+//! asset is reported to the player at once.
+
+use std::collections::HashSet;
+
+use rltk::{console, VirtualKeyCode};
+use specs::prelude::*;
+
+use super::exceptions::GameError;
+use super::game_config::GameConfig;
+use super::localization;
+use super::raws::Raws;
+use super::rng::{self, Dice};
+use super::scripting::ScriptEngine;
+use super::spawn_table::SpawnTables;
+use super::{
+    entity_factory, register_components, DialogInterface, DialogOption, Position, Scripted,
+};
+
+/// Checks all content assets registered with the passed `ecs`
+/// and returns an error for every missing or unreadable asset.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [ScriptEngine], [Raws],
+///   [SpawnTables] and [GameConfig] are registered.
+///
+/// # Notes
+/// * Every spawn table entry is created once in a scratch [World],
+///   so the assets its entity references can be checked as well.
+/// * The dice notations of the spawn tables are parsed, so
+///   malformed amounts are reported as well.
+/// * The base monsters and drops of the unique monsters are checked.
+///
+pub fn validate(ecs: &World) -> Vec<GameError> {
+    let script_engine = ecs.fetch::<ScriptEngine>();
+    let spawn_tables = ecs.fetch::<SpawnTables>();
+
+    let raws = ecs.fetch::<Raws>();
+
+    let mut errors = script_engine.failures().to_vec();
+    errors.extend(raws.failures().iter().cloned());
+    errors.extend(ecs.fetch::<GameConfig>().failures().iter().cloned());
+    errors.extend(localization::failures());
+
+    let mut scratch = World::new();
+    register_components(&mut scratch);
+    rng::register(&mut scratch, None);
+    scratch.insert((*raws).clone());
+
+    let tables = [
+        &spawn_tables.monsters,
+        &spawn_tables.items,
+        &spawn_tables.treasure,
+    ];
+
+    let notations = tables.iter().flat_map(|table| {
+        std::iter::once(&table.per_room).chain(table.entries.iter().map(|entry| &entry.group))
+    });
+
+    for notation in notations {
+        if let Err(error) = Dice::parse(notation) {
+            errors.push(error);
+        }
+    }
+
+    let mut checked = HashSet::new();
+
+    let entries = spawn_tables
+        .monsters
+        .entries
+        .iter()
+        .chain(spawn_tables.items.entries.iter())
+        .chain(spawn_tables.treasure.entries.iter());
+
+    for entry in entries {
+        if !checked.insert(entry.name.as_str()) {
+            continue;
+        }
+
+        let position = Position { x: 0, y: 0 };
+
+        let entity = match entity_factory::new_named(&mut scratch, &entry.name, position) {
+            Some(entity) => entity,
+            None => {
+                errors.push(GameError::Asset {
+                    asset: entry.name.clone(),
+                    message: "Unknown entity in the spawn tables".to_string(),
+                });
+                continue;
+            }
+        };
+
+        if let Some(scripted) = scratch.read_storage::<Scripted>().get(entity) {
+            if !script_engine.is_loaded(&scripted.script) {
+                errors.push(GameError::Script {
+                    script: scripted.script.clone(),
+                    message: format!("Referenced by '{}', but not loaded", entry.name),
+                });
+            }
+        }
+    }
+
+    // Unique monsters aren't part of the spawn tables, but reference other definitions
+    for name in raws.unique_names() {
+        let unique = match raws.unique(&name) {
+            Some(unique) => unique,
+            None => continue,
+        };
+
+        if raws.monster(&unique.base).is_none() {
+            errors.push(GameError::Asset {
+                asset: name.clone(),
+                message: format!("Unknown base monster '{}'", unique.base),
+            });
+        }
+
+        for item in unique.drops.iter().filter(|item| raws.item(item).is_none()) {
+            errors.push(GameError::Asset {
+                asset: name.clone(),
+                message: format!("Unknown drop '{}'", item),
+            });
+        }
+    }
+
+    errors
+}
+
+/// Validates the content assets of the passed `ecs` and registers a
+/// [DialogInterface] listing all problems, if any were found. The player
+/// can either continue without the affected content or quit the game.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the assets are registered.
+///
+/// # See also
+/// * [validate]
+///
+pub fn register(ecs: &mut World) {
+    let errors = validate(ecs);
+
+    if errors.is_empty() {
+        return;
+    }
+
+    for error in errors.iter() {
+        console::log(error.to_string());
+    }
+
+    let details = errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    let message = localization::tr_with("dialog.assets.message", &[&errors.len(), &details]);
+
+    DialogInterface::register_dialog(
+        ecs,
+        localization::tr("dialog.assets.title"),
+        Some(message),
+        vec![
+            DialogOption {
+                description: localization::tr("dialog.assets.continue"),
+                key: VirtualKeyCode::C,
+                args: vec![],
+                callback: Box::new(|_, _, _| ()),
+            },
+            DialogOption {
+                description: localization::tr("dialog.assets.quit"),
+                key: VirtualKeyCode::Q,
+                args: vec![],
+                callback: Box::new(|_, ctx, _| ctx.quit()),
+            },
+        ],
+        false,
+    );
+}
@@ -0,0 +1,85 @@
+//! Module for the themed biomes of the dungeon.
+//!
+//! Every level of the dungeon belongs to a [Biome], which is picked by the
+//! depth band the level lies in. The biome decides the colors of the floor
+//! and the walls, the [MapGenerator] creating the layout of the level and
+//! which entries of the spawn tables are available on it.
+
+use serde::{Deserialize, Serialize};
+
+use super::map_builders::MapGenerator;
+use super::swatch::{self, Pallet};
+use super::{config, localization};
+
+/// Enum describing all biomes of the dungeon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Biome {
+    /// Rooms and corridors of an old burial site.
+    Crypt,
+
+    /// Natural caves below the crypt.
+    Caverns,
+
+    /// Overgrown caverns lit by glowing fungi.
+    FungalForest,
+}
+
+impl Biome {
+    /// Returns the biome of the level at the passed `depth`,
+    /// or `None` for the town, which belongs to no biome.
+    ///
+    /// # Arguments
+    /// * `depth`: The depth of the level.
+    ///
+    pub fn for_depth(depth: i32) -> Option<Self> {
+        if depth <= config::TOWN_DEPTH {
+            None
+        } else if depth >= config::FUNGAL_FOREST_MIN_DEPTH {
+            Some(Biome::FungalForest)
+        } else if depth >= config::CAVERNS_MIN_DEPTH {
+            Some(Biome::Caverns)
+        } else {
+            Some(Biome::Crypt)
+        }
+    }
+
+    /// Returns the localized name of the biome.
+    pub fn name(&self) -> String {
+        let id = match self {
+            Biome::Crypt => "biome.crypt",
+            Biome::Caverns => "biome.caverns",
+            Biome::FungalForest => "biome.fungal_forest",
+        };
+
+        localization::tr(id)
+    }
+
+    /// Returns the [Pallet] of the floor tiles of the biome.
+    pub fn floor(&self) -> &'static Pallet {
+        match self {
+            Biome::Crypt => &swatch::CRYPT_FLOOR,
+            Biome::Caverns => &swatch::CAVERNS_FLOOR,
+            Biome::FungalForest => &swatch::FUNGAL_FOREST_FLOOR,
+        }
+    }
+
+    /// Returns the [Pallet] of the wall tiles of the biome.
+    pub fn wall(&self) -> &'static Pallet {
+        match self {
+            Biome::Crypt => &swatch::CRYPT_WALL,
+            Biome::Caverns => &swatch::CAVERNS_WALL,
+            Biome::FungalForest => &swatch::FUNGAL_FOREST_WALL,
+        }
+    }
+
+    /// Returns the [MapGenerator] creating the levels of the biome,
+    /// if the generator isn't fixed through the `GameConfig`.
+    pub fn generator(&self) -> MapGenerator {
+        match self {
+            Biome::Crypt => MapGenerator::Rooms,
+            Biome::Caverns => MapGenerator::Cave,
+            Biome::FungalForest => MapGenerator::Wfc,
+        }
+    }
+}
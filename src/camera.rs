@@ -0,0 +1,122 @@
+//! Module for the camera, which decides the part of the map on the screen.
+//!
+//! Maps can be larger than the window, so only the slice of the map inside
+//! of the viewport above the message log is drawn. The [Camera] is centered
+//! on the player and stops at the borders of the map, so nothing outside of
+//! the map is shown. Maps smaller than the viewport are drawn from its top
+//! left corner.
+//!
+//! Everything drawn onto the map is translated from world to screen
+//! coordinates through the [Camera], and the tiles selected with the
+//! mouse from screen to world coordinates.
+
+use rltk::Point;
+use specs::prelude::*;
+
+use super::game_config::GameConfig;
+use super::Map;
+
+/// The slice of the map shown in the viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Camera {
+    /// The position on the map shown in the
+    /// top left corner of the viewport.
+    pub origin: Point,
+
+    /// The width of the viewport in tiles.
+    pub width: i32,
+
+    /// The height of the viewport in tiles.
+    pub height: i32,
+}
+
+impl Camera {
+    /// Creates the [Camera] of the current frame, centered
+    /// on the player's position on the [Map].
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which the [Map], the player's
+    ///   [Point] and the [GameConfig] are registered.
+    ///
+    pub fn new(ecs: &World) -> Self {
+        let game_config = ecs.fetch::<GameConfig>();
+        let map = ecs.fetch::<Map>();
+        let player_position = ecs.fetch::<Point>();
+
+        Camera::centered_on(
+            *player_position,
+            Point::new(map.width, map.height),
+            Point::new(game_config.viewport_width(), game_config.viewport_height()),
+        )
+    }
+
+    /// Creates a [Camera] centered on the passed `center`, that
+    /// is kept within the borders of the map.
+    ///
+    /// # Arguments
+    /// * `center`: The position on the map to center on.
+    /// * `map_size`: The width and height of the map.
+    /// * `viewport`: The width and height of the viewport.
+    ///
+    pub fn centered_on(center: Point, map_size: Point, viewport: Point) -> Self {
+        let axis_origin = |center: i32, map_size: i32, viewport_size: i32| {
+            if map_size <= viewport_size {
+                0
+            } else {
+                (center - viewport_size / 2).clamp(0, map_size - viewport_size)
+            }
+        };
+
+        Camera {
+            origin: Point::new(
+                axis_origin(center.x, map_size.x, viewport.x),
+                axis_origin(center.y, map_size.y, viewport.y),
+            ),
+            width: viewport.x,
+            height: viewport.y,
+        }
+    }
+
+    /// Returns the screen coordinates of the passed
+    /// `position` on the map, if it's in the viewport.
+    ///
+    /// # Arguments
+    /// * `position`: The position in world coordinates.
+    ///
+    pub fn to_screen(self, position: Point) -> Option<Point> {
+        let screen = position - self.origin;
+
+        if self.is_in_viewport(screen) {
+            Some(screen)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the position on the map shown at the passed
+    /// screen `point`, if the point is in the viewport.
+    ///
+    /// # Arguments
+    /// * `point`: The position in screen coordinates, e.g. of the mouse.
+    ///
+    /// # Notes
+    /// * The position may still lie outside of the [Map],
+    ///   if the map is smaller than the viewport.
+    ///
+    pub fn to_world(self, point: Point) -> Option<Point> {
+        if self.is_in_viewport(point) {
+            Some(point + self.origin)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the passed screen `point` lies in the viewport.
+    ///
+    /// # Arguments
+    /// * `point`: The position in screen coordinates.
+    ///
+    fn is_in_viewport(self, point: Point) -> bool {
+        point.x >= 0 && point.y >= 0 && point.x < self.width && point.y < self.height
+    }
+}
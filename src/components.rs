@@ -1,14 +1,35 @@
 //! List of all components used in the game.
 
+use std::convert::Infallible;
+use std::ops::Deref;
+
 use rltk::{FontCharType, Point, RGB};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
+use specs::saveload::ConvertSaveload;
+use specs::storage::MaskedStorage;
+use specs::world::EntitiesRes;
 use specs_derive::*;
 
-use super::{exceptions, GameLog};
+use super::exceptions::{GameError, GameResult};
+use super::fov::FovAlgorithm;
+use super::rng::Dice;
+use super::{
+    config, publish_event, pythagoras_distance, DropEvent, EquipEvent, GameLog, LogCategory,
+    PickupEvent,
+};
+use super::{localization, saveload};
 
 /// Component to describe the position
 /// of a game entity in the game.
-#[derive(Component, Copy, Clone, PartialEq)]
+///
+/// # Notes
+/// * The storage is flagged, so the spatial index of
+///   the `Map` only updates entities that moved.
+///
+#[derive(Component, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[storage(FlaggedStorage)]
 pub struct Position {
     /// X coordinate of the entity.
     pub x: i32,
@@ -89,7 +110,7 @@ impl Position {
 
 /// Component to describe the render
 /// information of an entity.
-#[derive(Component)]
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Renderable {
     /// Font symbol of the entity.
     pub symbol: FontCharType,
@@ -105,11 +126,17 @@ pub struct Renderable {
 }
 
 /// Component for the player entity.
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Player {}
 
+/// Component marking an entity, that sneaks around. Sneaking entities
+/// make only half the noise, see [config::PLAYER_NOISE_RADIUS], but
+/// also only act in every other round.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Sneaking {}
+
 /// Component for the field of view implementation.
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct FOV {
     /// Positions in the FOV.
     pub content: Vec<rltk::Point>,
@@ -117,6 +144,13 @@ pub struct FOV {
     /// Range of the FOV.
     pub range: i32,
 
+    /// The algorithm the [FOV] was calculated with. Kept in
+    /// sync with the [crate::settings::Settings] by the view
+    /// system, so the player and the monsters always see
+    /// each other through the same algorithm.
+    #[serde(default)]
+    pub algorithm: FovAlgorithm,
+
     /// Flag indicating if the
     /// [FOV] should be updated.
     pub is_dirty: bool,
@@ -146,26 +180,335 @@ impl FOV {
     }
 }
 
+/// Component for entities, that only see within a cone
+/// in the direction they are facing. Entities without
+/// a [Facing] see in all directions.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Facing {
+    /// The direction the entity is facing, as the offset
+    /// of the adjacent tile in that direction.
+    pub direction: Point,
+}
+
+impl Facing {
+    /// The eight directions an entity can
+    /// face, clockwise starting with north.
+    pub const DIRECTIONS: [Point; 8] = [
+        Point { x: 0, y: -1 },
+        Point { x: 1, y: -1 },
+        Point { x: 1, y: 0 },
+        Point { x: 1, y: 1 },
+        Point { x: 0, y: 1 },
+        Point { x: -1, y: 1 },
+        Point { x: -1, y: 0 },
+        Point { x: -1, y: -1 },
+    ];
+
+    /// Turns the [Facing] in the direction from the position `from`
+    /// to the position `to`. Returns `true` if the direction changed.
+    ///
+    /// # Arguments
+    /// * `from`: The position of the entity.
+    /// * `to`: The position the entity should face.
+    ///
+    pub fn turn_towards(&mut self, from: Point, to: Point) -> bool {
+        let direction = Point::new((to.x - from.x).signum(), (to.y - from.y).signum());
+
+        if direction == Point::zero() || direction == self.direction {
+            return false;
+        }
+
+        self.direction = direction;
+        true
+    }
+
+    /// Returns `true` if the `target` lies within the vision cone of an
+    /// entity at the `origin`, see [config::VISION_CONE_ANGLE]. The
+    /// `origin` itself is always within the cone.
+    ///
+    /// # Arguments
+    /// * `origin`: The position of the entity.
+    /// * `target`: The position to check.
+    ///
+    pub fn is_in_cone(&self, origin: Point, target: Point) -> bool {
+        if origin == target {
+            return true;
+        }
+
+        let (dx, dy) = ((target.x - origin.x) as f32, (target.y - origin.y) as f32);
+        let (fx, fy) = (self.direction.x as f32, self.direction.y as f32);
+
+        let cos = (dx * fx + dy * fy) / (dx.hypot(dy) * fx.hypot(fy));
+
+        cos >= (config::VISION_CONE_ANGLE.to_radians() / 2.0).cos()
+    }
+}
+
+/// Component for entities emitting light, e.g. torches or glowing
+/// monsters, which lights the tiles around them, see the `LightingSystem`.
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LightSource {
+    /// The number of tiles the light reaches.
+    pub range: i32,
+
+    /// The color of the light at full intensity.
+    pub color: RGB,
+}
+
 /// Component for the monsters.
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Monster {}
 
+/// Component marking the boss of the dungeon,
+/// whose defeat wins the game.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Boss {}
+
+/// Component marking a unique monster, a named and stronger variant of
+/// another monster, that appears at most once per run. Its definition
+/// is looked up in the `Raws` by its [Name].
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Unique {}
+
+/// Component marking a monster, that was spawned on a level above its
+/// usual depth. Defeating it leaves a hoard of treasure behind, see
+/// [SpawnTable::out_of_depth](super::spawn_table::SpawnTable::out_of_depth).
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct OutOfDepth {
+    /// The depth the monster and its hoard are picked for.
+    pub depth: i32,
+}
+
+/// Component for entities, that aren't hostile to the player.
+/// The player talks to them instead of attacking them.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Friendly {
+    /// The id of the text the entity greets the player with,
+    /// which receives the name of the entity as `{0}`.
+    pub greeting: String,
+}
+
+/// Component for NPCs, that trade items with the player, see [ForSale].
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Shopkeeper {
+    /// Flag indicating whether or not the shopkeeper has caught
+    /// the player stealing and refuses to trade with the player.
+    pub robbed: bool,
+}
+
+/// Component describing how persuasive an entity is. Shopkeepers
+/// grant entities with a higher charisma better prices, see
+/// [PriceModifiers](super::valuation::PriceModifiers).
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Charisma {
+    /// The charisma of the entity, starting at `0`.
+    pub value: i32,
+}
+
+/// Component holding the gold an entity carries.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Purse {
+    /// The amount of gold in the purse.
+    pub gold: i32,
+}
+
+/// Component assigning an entity to a faction, whose relations
+/// to the other factions decide, whom the entity attacks,
+/// see [FactionRelations](super::faction::FactionRelations).
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Faction {
+    /// The name of the faction.
+    pub name: String,
+}
+
+impl Faction {
+    /// Creates a new [Faction] with the passed `name`.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the faction.
+    ///
+    pub fn new(name: &str) -> Self {
+        Faction {
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Enum describing the behavior states of a monster's [AI].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Behavior {
+    /// The monster waits until it notices the player.
+    #[default]
+    Idle,
+
+    /// The monster hunts and attacks the player.
+    Chase,
+
+    /// The monster has lost its morale and runs from
+    /// the player, see [config::FLEE_HP_FRACTION].
+    Flee,
+
+    /// The monster wanders around until it notices the player.
+    Patrol,
+
+    /// The monster guards its lair until it notices the player and
+    /// flies into a rage below [config::BOSS_ENRAGE_HP_FRACTION].
+    Boss,
+
+    /// The entity strolls around its home and never attacks,
+    /// e.g. the NPCs of the town.
+    Wander,
+}
+
+/// Enum describing how aware a monster is of the player,
+/// ordered from the least to the most aware.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Awareness {
+    /// The monster hasn't noticed the player.
+    #[default]
+    Unaware,
+
+    /// The monster has heard or glimpsed the player and turns
+    /// towards it, but doesn't hunt it yet.
+    Suspicious,
+
+    /// The monster has noticed the player.
+    Alert,
+}
+
+/// Component holding the behavior state of a monster,
+/// which is advanced by the `MonsterAI` every turn.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct AI {
+    /// The current behavior.
+    pub behavior: Behavior,
+
+    /// The behavior the monster returns to,
+    /// once it has lost sight of the player.
+    pub default_behavior: Behavior,
+
+    /// The tile a patrolling monster walks to, if it has chosen one.
+    #[serde(default)]
+    pub patrol_target: Option<(i32, i32)>,
+
+    /// The phase of monsters with multiple phases, e.g.
+    /// the boss, starting at `0` for the first phase.
+    #[serde(default)]
+    pub phase: i32,
+
+    /// Flag indicating whether or not the monster loses its morale early,
+    /// see [config::COWARDLY_FLEE_HP_FRACTION].
+    #[serde(default)]
+    pub cowardly: bool,
+
+    /// The id of the pack the monster belongs to, shared by all
+    /// monsters spawned together in the same room or region of
+    /// a level, or `None` if it was spawned alone.
+    #[serde(default)]
+    pub pack: Option<usize>,
+
+    /// The tile a wandering entity stays close to, see
+    /// [config::WANDER_RADIUS], or `None` if it roams freely.
+    #[serde(default)]
+    pub home: Option<(i32, i32)>,
+
+    /// How aware the monster is of the player.
+    #[serde(default)]
+    pub awareness: Awareness,
+}
+
+impl AI {
+    /// Creates a new [AI] starting in and returning
+    /// to the passed `behavior`.
+    ///
+    /// # Arguments
+    /// * `behavior`: The default [Behavior] of the monster.
+    ///
+    pub fn new(behavior: Behavior) -> Self {
+        AI {
+            behavior,
+            default_behavior: behavior,
+            patrol_target: None,
+            phase: 0,
+            cowardly: false,
+            pack: None,
+            home: None,
+            awareness: Awareness::Unaware,
+        }
+    }
+}
+
 /// Component to name entities
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Name {
     /// The name of the entity
     pub name: String,
 }
 
+impl Name {
+    /// Returns the name of the entity translated
+    /// into the selected language.
+    pub fn display(&self) -> String {
+        localization::entity_name(&self.name)
+    }
+}
+
 /// Component that designates a an associated
 /// entity as blocking, meaning it can't be walked
 /// over.
-#[derive(Component, Debug)]
+///
+/// # Notes
+/// * The storage is flagged, so the blocked tiles of
+///   the `Map` are updated when it's added or removed.
+///
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+#[storage(FlaggedStorage)]
 pub struct Collision {}
 
+/// Component that designates an associated
+/// entity as opaque, meaning it blocks the
+/// sight through its tile.
+///
+/// # Notes
+/// * The storage is flagged, so the opaque tiles of
+///   the `Map` are updated when it's added or removed.
+///
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+#[storage(FlaggedStorage)]
+pub struct Opaque {}
+
+/// Component for the doors of a level. Closed doors have
+/// [Collision] and are [Opaque], open doors have neither.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Door {
+    /// Flag indicating whether or not the door is open.
+    pub open: bool,
+
+    /// Flag indicating whether or not the door is locked,
+    /// locked doors only open with their [Key].
+    #[serde(default)]
+    pub locked: bool,
+}
+
+/// Component for the keys unlocking a locked [Door], which
+/// is identified by the depth and coordinates of its tile.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Key {
+    /// The depth of the level containing the door.
+    pub depth: i32,
+
+    /// X coordinate of the door.
+    pub x: i32,
+
+    /// Y coordinate of the door.
+    pub y: i32,
+}
+
 /// Component describing the
 /// combat stats of an entity.
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Statistics {
     /// Maximum hp of the entity.
     pub hp_max: i32,
@@ -180,55 +523,553 @@ pub struct Statistics {
     pub defense: i32,
 }
 
-/// Component designating an entity which
-/// attacks in melee range.
-#[derive(Component, Debug, Clone)]
-pub struct MeleeAttack {
-    /// The target entity of the attack.
-    pub target: Entity,
+impl Statistics {
+    /// Returns the weight the entity carries without being slowed down,
+    /// which grows with its power, see [config::CARRY_CAPACITY_PER_POWER].
+    pub fn carry_capacity(&self) -> i32 {
+        config::BASE_CARRY_CAPACITY + self.power.max(0) * config::CARRY_CAPACITY_PER_POWER
+    }
+
+    /// Returns the weight beyond which the entity can't pick up
+    /// any more items, see [config::MAX_LOAD_PERCENT].
+    pub fn max_load(&self) -> i32 {
+        self.carry_capacity() * config::MAX_LOAD_PERCENT / 100
+    }
 }
 
-/// Component keeping track of
-/// the damage an entity receives
-/// in a turn.
-#[derive(Component, Debug)]
-pub struct DamageCounter {
-    /// The amount of damage the entity has taken
-    /// this turn as a vector.
-    pub damage_values: Vec<i32>,
+/// Component tracking the experience of an entity,
+/// which levels up once it has gathered enough xp.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Experience {
+    /// The current level, starting at `1`.
+    pub level: i32,
+
+    /// The xp gathered since the last level up.
+    pub xp: i32,
 }
 
-impl DamageCounter {
-    /// Adds the passed damage `amount` to the damage values of the `target` and writes them
-    /// into the associated `ecs` `store`.
+impl Experience {
+    /// Returns the xp needed to reach the next level.
+    pub fn xp_to_next_level(&self) -> i32 {
+        self.level * config::XP_PER_LEVEL
+    }
+
+    /// Adds the `amount` of xp and returns the number of levels gained.
     ///
     /// # Arguments
-    /// * `store`: The store in which the [DamageCounter] component should be saved.
-    /// * `target`: The [Entity] taking the damage.
-    /// * `amount`: The number of damage the [Entity] has taken.
+    /// * `amount`: The gained xp.
     ///
-    pub fn add_damage_taken(store: &mut WriteStorage<DamageCounter>, target: Entity, amount: i32) {
-        if let Some(damage_counter) = store.get_mut(target) {
-            damage_counter.damage_values.push(amount)
-        } else {
-            let damage_counter = DamageCounter {
-                damage_values: vec![amount],
-            };
+    /// # Notes
+    /// * Surplus xp is carried over to the next level, so a
+    ///   large `amount` can grant several levels at once.
+    ///
+    pub fn gain(&mut self, amount: i32) -> i32 {
+        let mut levels = 0;
+
+        self.xp += amount.max(0);
+
+        while self.xp >= self.xp_to_next_level() {
+            self.xp -= self.xp_to_next_level();
+            self.level += 1;
+            levels += 1;
+        }
+
+        levels
+    }
+}
+
+/// Component describing the mana of an
+/// entity, which is spent on casting spells.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Mana {
+    /// Maximum mp of the entity.
+    pub mp_max: i32,
+
+    /// Current mp of the entity.
+    pub mp: i32,
+}
+
+/// Component listing the spells an entity can cast.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Spellbook {
+    /// The names of the spells, e.g. `"Magic Missile"`.
+    pub spells: Vec<String>,
+}
+
+/// Component used for communication with the
+/// SpellCastSystem to indicate, that an
+/// [Entity] wants to cast a spell.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct CastSpell {
+    /// The name of the spell the [Entity] wants to cast.
+    pub spell: String,
+
+    /// The targeted tile, if the spell has a range.
+    pub target: Option<Point>,
+}
+
+impl CastSpell {
+    /// Adds a request to the passed `ecs`, that the `caster` [Entity]
+    /// wants to cast the `spell`, optionally on the given `target`.
+    ///
+    /// # Arguments
+    /// * `ecs`: The overarching `ecs` to write to.
+    /// * `caster`: The [Entity] that wants to cast the `spell`.
+    /// * `spell`: The name of the spell.
+    /// * `target`: The targeted [Point] for spells with a range, [None] otherwise.
+    ///
+    /// # Errors
+    /// * If the request couldn't be inserted, e.g. because the `caster` has
+    ///   already been deleted.
+    ///
+    pub fn cast(
+        ecs: &World,
+        caster: &Entity,
+        spell: &str,
+        target: Option<Point>,
+    ) -> GameResult<()> {
+        let cast = CastSpell {
+            spell: spell.to_string(),
+            target,
+        };
+
+        ecs.write_storage::<CastSpell>()
+            .insert(*caster, cast)
+            .map(|_| ())
+            .map_err(|_| GameError::component_insertion::<CastSpell>(*caster))
+    }
+}
+
+/// Component describing the xp granted
+/// to the player for defeating an entity.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct ExperienceReward {
+    /// The granted xp.
+    pub xp: i32,
+}
+
+/// Enum describing all kinds of status effects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusEffectKind {
+    /// Deals the potency of the effect as damage every turn.
+    Poison,
+
+    /// Scrambles the direction the entity moves in.
+    Confusion,
+
+    /// The entity skips its turns.
+    Stun,
+
+    /// The entity only sees the tiles next to it,
+    /// see [config::BLIND_VISION_RANGE].
+    Blindness,
+
+    /// The entity is hidden like an [Invisible] one.
+    Invisibility,
+
+    /// The entity sees [Invisible] entities.
+    SeeInvisible,
+}
+
+impl StatusEffectKind {
+    /// Returns the kind with the passed `name`, e.g. `"poison"`,
+    /// or `None` if no kind has the `name`.
+    ///
+    /// # Arguments
+    /// * `name`: The lowercase name of the kind.
+    ///
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "poison" => Some(StatusEffectKind::Poison),
+            "confusion" => Some(StatusEffectKind::Confusion),
+            "stun" => Some(StatusEffectKind::Stun),
+            "blindness" => Some(StatusEffectKind::Blindness),
+            "invisibility" => Some(StatusEffectKind::Invisibility),
+            "see_invisible" => Some(StatusEffectKind::SeeInvisible),
+            _ => None,
+        }
+    }
+
+    /// Returns the lowercase name of the kind, which is also
+    /// part of the ids of its texts, e.g. `status.poison.applied`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusEffectKind::Poison => "poison",
+            StatusEffectKind::Confusion => "confusion",
+            StatusEffectKind::Stun => "stun",
+            StatusEffectKind::Blindness => "blindness",
+            StatusEffectKind::Invisibility => "invisibility",
+            StatusEffectKind::SeeInvisible => "see_invisible",
+        }
+    }
+}
+
+/// A status effect and its remaining duration.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffect {
+    /// The kind of the effect.
+    pub kind: StatusEffectKind,
 
-            let on_error_message = exceptions::get_add_damage_amount_error_message(&target, amount);
+    /// The remaining turns of the effect.
+    pub duration: i32,
 
-            store
-                .insert(target, damage_counter)
-                .expect(&on_error_message);
+    /// The strength of the effect, e.g. the damage per turn of poison.
+    #[serde(default)]
+    pub potency: i32,
+
+    /// The entity, that inflicted the effect, credited with the
+    /// damage of poison. Never part of the definitions in the raws.
+    #[serde(skip)]
+    pub inflicter: Option<Entity>,
+}
+
+/// Component holding the status effects an entity suffers from.
+/// An entity suffers from every [StatusEffectKind] at most once.
+///
+/// # Notes
+/// * The effects are applied and ticked by the `StatusEffectSystem`,
+///   other systems only check them through [StatusEffects::has].
+///
+#[derive(Component, Debug, Clone, Default)]
+pub struct StatusEffects {
+    /// The active effects.
+    pub effects: Vec<StatusEffect>,
+}
+
+/// The serialized form of a [StatusEffect], with the
+/// inflicter replaced by its marker `M`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StatusEffectData<M> {
+    /// The kind of the effect.
+    pub kind: StatusEffectKind,
+
+    /// The remaining turns of the effect.
+    pub duration: i32,
+
+    /// The strength of the effect.
+    #[serde(default)]
+    pub potency: i32,
+
+    /// The marker of the inflicter, if it's still alive.
+    #[serde(default)]
+    pub inflicter: Option<M>,
+}
+
+/// The serialized form of the [StatusEffects], with the
+/// inflicters replaced by their markers `M`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StatusEffectsData<M> {
+    /// The active effects.
+    pub effects: Vec<StatusEffectData<M>>,
+}
+
+impl<M> ConvertSaveload<M> for StatusEffects
+where
+    M: Serialize + DeserializeOwned,
+{
+    type Data = StatusEffectsData<M>;
+    type Error = Infallible;
+
+    fn convert_from<F>(data: Self::Data, mut ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        let effects = data
+            .effects
+            .into_iter()
+            .map(|effect| StatusEffect {
+                kind: effect.kind,
+                duration: effect.duration,
+                potency: effect.potency,
+                inflicter: effect.inflicter.and_then(&mut ids),
+            })
+            .collect();
+
+        Ok(StatusEffects { effects })
+    }
+
+    fn convert_into<F>(&self, mut ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        let effects = self
+            .effects
+            .iter()
+            .map(|effect| StatusEffectData {
+                kind: effect.kind,
+                duration: effect.duration,
+                potency: effect.potency,
+                inflicter: effect.inflicter.and_then(&mut ids),
+            })
+            .collect();
+
+        Ok(StatusEffectsData { effects })
+    }
+}
+
+impl StatusEffects {
+    /// Returns `true` if the entity suffers from the
+    /// passed `kind` of effect, `false` otherwise.
+    ///
+    /// # Arguments
+    /// * `kind`: The [StatusEffectKind] to check.
+    ///
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Adds the passed `effect`. An active effect of the same kind is
+    /// kept, but takes over the longer duration and higher potency.
+    /// The latest inflicter is credited with the effect.
+    ///
+    /// # Arguments
+    /// * `effect`: The [StatusEffect] to add.
+    ///
+    pub fn apply(&mut self, effect: StatusEffect) -> &Self {
+        match self
+            .effects
+            .iter_mut()
+            .find(|active| active.kind == effect.kind)
+        {
+            Some(active) => {
+                active.duration = i32::max(active.duration, effect.duration);
+                active.potency = i32::max(active.potency, effect.potency);
+                active.inflicter = effect.inflicter.or(active.inflicter);
+            }
+            None => self.effects.push(effect),
         }
+        self
+    }
+
+    /// Removes the effect of the passed `kind`. Returns `true`
+    /// if the entity suffered from the effect, `false` otherwise.
+    ///
+    /// # Arguments
+    /// * `kind`: The [StatusEffectKind] to cure.
+    ///
+    pub fn cure(&mut self, kind: StatusEffectKind) -> bool {
+        let count = self.effects.len();
+        self.effects.retain(|effect| effect.kind != kind);
+
+        self.effects.len() != count
     }
 }
 
+/// Component for entities, whose melee attacks
+/// inflict a [StatusEffect] on their target.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InflictsStatus {
+    /// The inflicted effect.
+    pub effect: StatusEffect,
+
+    /// The chance in percent of an attack, that deals
+    /// damage, to inflict the effect.
+    pub chance: i32,
+}
+
+/// Component marking an entity, that can't be seen by others, unless
+/// they stand right next to it or see invisible, see [Invisible::is_hidden].
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Invisible {}
+
+impl Invisible {
+    /// Returns `true` if the `target` is hidden from the `viewer`, i.e. it's
+    /// [Invisible] or suffers from [StatusEffectKind::Invisibility], the
+    /// `viewer` doesn't see invisible and they don't stand next to each
+    /// other, `false` otherwise.
+    ///
+    /// # Arguments
+    /// * `invisibles`: The [Invisible] storage of the `ecs`.
+    /// * `status_effects`: The [StatusEffects] storage of the `ecs`.
+    /// * `viewer`: The [Entity] looking at the `target`.
+    /// * `viewer_position`: The position of the `viewer`.
+    /// * `target`: The [Entity] being looked at.
+    /// * `target_position`: The position of the `target`.
+    ///
+    /// # Notes
+    /// * Entities always see themselves.
+    /// * Whether the `target` is in the `viewer`'s [FOV] at all isn't checked.
+    ///
+    pub fn is_hidden<I, S>(
+        invisibles: &Storage<'_, Invisible, I>,
+        status_effects: &Storage<'_, StatusEffects, S>,
+        viewer: Entity,
+        viewer_position: Point,
+        target: Entity,
+        target_position: Point,
+    ) -> bool
+    where
+        I: Deref<Target = MaskedStorage<Invisible>>,
+        S: Deref<Target = MaskedStorage<StatusEffects>>,
+    {
+        let has = |entity: Entity, kind: StatusEffectKind| {
+            status_effects
+                .get(entity)
+                .is_some_and(|effects| effects.has(kind))
+        };
+
+        viewer != target
+            && (invisibles.contains(target) || has(target, StatusEffectKind::Invisibility))
+            && !has(viewer, StatusEffectKind::SeeInvisible)
+            && pythagoras_distance(&viewer_position, &target_position) >= 1.5
+    }
+}
+
+/// Component for entities, that heal every turn, e.g. trolls.
+/// Fire stops the regeneration for a while, see [Regenerates::scorch].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Regenerates {
+    /// The hp restored every turn.
+    pub per_turn: i32,
+
+    /// The remaining turns, during which the
+    /// regeneration is stopped by fire.
+    #[serde(default)]
+    pub scorched: i32,
+}
+
+impl Regenerates {
+    /// Stops the regeneration for [config::FIRE_REGENERATION_PAUSE]
+    /// turns, after the entity has been hit by fire.
+    pub fn scorch(&mut self) {
+        self.scorched = config::FIRE_REGENERATION_PAUSE;
+    }
+}
+
+/// Component for temporary visual effects, e.g. the slash mark
+/// of an attack. The entity is drawn with its [Renderable] on
+/// the `position` until its lifetime has passed.
+///
+/// # Notes
+/// * Particles have no [Position], so they are neither part of
+///   the spatial index of the map nor of the save game.
+///
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ParticleLifetime {
+    /// The tile the particle is displayed on.
+    pub position: Point,
+
+    /// The remaining lifetime in milliseconds.
+    pub lifetime_ms: f32,
+}
+
 /// Component marking an entity as an item
 /// e.g. potions, equipment, scrolls, etc.
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Item {}
 
+/// Component holding the worth of an [Item], from which its
+/// prices in the shops and its share of the score are derived,
+/// see the [valuation](super::valuation) module.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Value {
+    /// The price of the item without any modifiers.
+    pub base_price: i32,
+}
+
+/// Component for [Item]s, that stack with the identical items in a
+/// backpack, e.g. potions. A stack is a single entity holding the
+/// amount of its items.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Stackable {
+    /// The amount of items in the stack.
+    pub quantity: i32,
+}
+
+impl Stackable {
+    /// Returns the passed display `name` of the stack
+    /// with its quantity, if it holds more than one item.
+    ///
+    /// # Arguments
+    /// * `name`: The display name of the items in the stack.
+    ///
+    pub fn label(&self, name: String) -> String {
+        if self.quantity > 1 {
+            localization::tr_with("inventory.stack", &[&name, &self.quantity])
+        } else {
+            name
+        }
+    }
+
+    /// Returns the stack in the backpack of the passed `owner`, that
+    /// the `item` joins once the owner takes it, or `None` if the item
+    /// isn't [Stackable] or the owner carries no identical items.
+    ///
+    /// # Arguments
+    /// * `item`: The item taken by the `owner`.
+    /// * `owner`: The [Entity] taking the `item`.
+    /// * `entities`: All entities of the `ecs`.
+    /// * `backpack`: The [Loot] storage of the `ecs`.
+    /// * `names`: The [Name] storage of the `ecs`.
+    /// * `stackables`: The [Stackable] storage of the `ecs`.
+    ///
+    pub fn find_stack<L, N, S>(
+        item: Entity,
+        owner: Entity,
+        entities: &EntitiesRes,
+        backpack: &Storage<'_, Loot, L>,
+        names: &Storage<'_, Name, N>,
+        stackables: &Storage<'_, Stackable, S>,
+    ) -> Option<Entity>
+    where
+        L: Deref<Target = MaskedStorage<Loot>>,
+        N: Deref<Target = MaskedStorage<Name>>,
+        S: Deref<Target = MaskedStorage<Stackable>>,
+    {
+        let name = match (stackables.contains(item), names.get(item)) {
+            (true, Some(name)) => &name.name,
+            _ => return None,
+        };
+
+        (entities, backpack, names, stackables)
+            .join()
+            .find(|(other, loot, other_name, _)| {
+                *other != item && loot.owner == owner && other_name.name == *name
+            })
+            .map(|(other, _, _, _)| other)
+    }
+}
+
+/// Component holding the weight of an [Item], which burdens the
+/// entity carrying it, see [Statistics::carry_capacity].
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Weight {
+    /// The weight of a single item.
+    pub value: i32,
+}
+
+impl Weight {
+    /// Returns the total weight of the items in the backpack of the
+    /// passed `owner`. Every item of a [Stackable] stack counts.
+    ///
+    /// # Arguments
+    /// * `owner`: The [Entity] carrying the items.
+    /// * `backpack`: The [Loot] storage of the `ecs`.
+    /// * `weights`: The [Weight] storage of the `ecs`.
+    /// * `stackables`: The [Stackable] storage of the `ecs`.
+    ///
+    pub fn carried<L, W, S>(
+        owner: Entity,
+        backpack: &Storage<'_, Loot, L>,
+        weights: &Storage<'_, Weight, W>,
+        stackables: &Storage<'_, Stackable, S>,
+    ) -> i32
+    where
+        L: Deref<Target = MaskedStorage<Loot>>,
+        W: Deref<Target = MaskedStorage<Weight>>,
+        S: Deref<Target = MaskedStorage<Stackable>>,
+    {
+        (backpack, weights, stackables.maybe())
+            .join()
+            .filter(|(loot, _, _)| loot.owner == owner)
+            .map(|(_, weight, stackable)| {
+                weight.value * stackable.map_or(1, |stackable| stackable.quantity)
+            })
+            .sum()
+    }
+}
+
 impl Item {
     /// Picks up the first [Item] [Entity] at the [Position] of the `collector` [Entity]
     /// and adds it to the [Loot] of the `collector` and sends a corresponding message to the
@@ -262,33 +1103,89 @@ impl Item {
         }
 
         let out_name: String = match collector_name {
-            None => "Some one".to_string(),
-            Some(name_plate) => (*name_plate.name).to_string(),
+            None => localization::tr("name.someone"),
+            Some(name_plate) => name_plate.display(),
         };
 
         match picked_item {
             None => {
-                let message = format!(
-                    "{} tried to pick up an item, but there is nothing on the ground.",
-                    out_name
-                );
-                game_log.messages_push(&message);
+                let message = localization::tr_with("item.nothing_to_pick_up", &[&out_name]);
+                game_log.push(&message, LogCategory::Item);
             }
             Some(picked_item) => {
-                let mut pickups = ecs.write_storage::<PickupItem>();
-                let pickup = PickupItem {
+                let pickup = PickupEvent {
                     collector: *collector,
                     item: picked_item,
                 };
 
-                let on_error_message =
-                    exceptions::get_pick_up_item_error_message(collector, &picked_item);
-
-                pickups.insert(*collector, pickup).expect(&on_error_message);
+                publish_event(ecs, pickup);
             }
         };
     }
 
+    /// Adds a request to the passed `ecs`, that the `user` [Entity] wants to
+    /// use the supplied `item` [Entity], optionally on the given `target`.
+    ///
+    /// # Arguments
+    /// * `ecs`: The overarching `ecs` to write to.
+    /// * `user`: The [Entity] that wants to use the `item`.
+    /// * `item`: The `item` [Entity] the `user` wants to use.
+    /// * `target`: The targeted [Point] for [Ranged] items, [None] otherwise.
+    ///
+    /// # Errors
+    /// * If the request couldn't be inserted, e.g. because the `user` has
+    ///   already been deleted.
+    ///
+    pub fn use_item(
+        ecs: &World,
+        user: &Entity,
+        item: &Entity,
+        target: Option<Point>,
+    ) -> GameResult<()> {
+        let mut usage_intent = ecs.write_storage::<UseItem>();
+
+        let usage = UseItem {
+            item: *item,
+            target,
+        };
+
+        usage_intent
+            .insert(*user, usage)
+            .map(|_| ())
+            .map_err(|_| GameError::component_insertion::<UseItem>(*user))
+    }
+
+    /// Puts the passed `item` right into the backpack of the `owner`,
+    /// e.g. a bought item. [Stackable] items join the stack of identical
+    /// items the `owner` already carries.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which both the `owner` and `item` are stored.
+    /// * `owner`: The [Entity] receiving the `item`.
+    /// * `item`: The [Item] without a [Position].
+    ///
+    pub fn put_into_backpack(ecs: &World, owner: &Entity, item: Entity) {
+        let entities = ecs.entities();
+        let names = ecs.read_storage::<Name>();
+        let mut backpack = ecs.write_storage::<Loot>();
+        let mut stackables = ecs.write_storage::<Stackable>();
+
+        let stack = Stackable::find_stack(item, *owner, &entities, &backpack, &names, &stackables);
+        let quantity = stackables
+            .get(item)
+            .map_or(1, |stackable| stackable.quantity);
+
+        match stack.and_then(|stack| stackables.get_mut(stack)) {
+            Some(stackable) => {
+                stackable.quantity += quantity;
+                let _ = entities.delete(item);
+            }
+            None => {
+                let _ = backpack.insert(item, Loot { owner: *owner });
+            }
+        }
+    }
+
     /// Drops an [Item] [Entity] from the inventory of the `owner`
     /// [Entity].
     ///
@@ -298,81 +1195,525 @@ impl Item {
     /// * `item`: The [Item] that the `owner` wants to drop.
     ///
     pub fn drop_item(ecs: &World, owner: &Entity, item: &Entity) {
-        let mut drop_intent = ecs.write_storage::<DropItem>();
+        let drop_item = DropEvent {
+            owner: *owner,
+            item: *item,
+        };
+
+        publish_event(ecs, drop_item);
+    }
 
-        let drop_item = DropItem { item: *item };
+    /// Equips the [Equippable] `item` of the `owner` [Entity], or
+    /// takes it off, if the `owner` is already wearing it.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which both the `owner` and `item` are stored.
+    /// * `owner`: The `owner` [Entity] of the `item`.
+    /// * `item`: The [Item] that the `owner` wants to put on or take off.
+    /// * `off_hand`: Flag indicating whether or not a one-handed
+    ///   melee weapon should be wielded in the off hand.
+    ///
+    pub fn equip_item(ecs: &World, owner: &Entity, item: &Entity, off_hand: bool) {
+        let equip_item = EquipEvent {
+            owner: *owner,
+            item: *item,
+            off_hand,
+        };
 
-        let on_error_message = exceptions::get_drop_item_error_message(owner, item);
-        drop_intent
-            .insert(*owner, drop_item)
-            .expect(&on_error_message);
+        publish_event(ecs, equip_item);
     }
 }
 
-/// Component describing a drinkable potion
-/// that heals the players hp.
-#[derive(Component, Debug)]
-pub struct Potion {
-    /// The amount of health, the [Potion]
-    /// restores for the [Entity] that drinks it.
-    pub healing_amount: i32,
+/// Component referencing the content script, that
+/// defines the behavior of an [Entity], e.g. the
+/// effect of an [Item] when it's used.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Scripted {
+    /// The name of the script.
+    pub script: String,
 }
 
-impl Potion {
-    /// Adds a request to the passed `ecs`, that the `user` [Entity] wants to
-    /// drink the supplied `potion` [Entity].
+/// Component marking an [Item], that has to be used
+/// on a target tile within the given range.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Ranged {
+    /// The maximum distance between the user
+    /// and the targeted tile.
+    pub range: i32,
+}
+
+/// Component marking a [Ranged] [Item], whose effect hits
+/// every entity within the `radius` around the targeted tile.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct AreaOfEffect {
+    /// The maximum distance between the targeted
+    /// tile and the tiles hit by the effect.
+    pub radius: i32,
+}
+
+impl AreaOfEffect {
+    /// Returns `true` if the `tile` is hit by an effect
+    /// centered on the `target`, `false` otherwise.
     ///
     /// # Arguments
-    /// * `ecs`: The overarching `ecs` to write to.
-    /// * `user`: The [Entity] that wants to drink the `potion`.
-    /// * `potion`: The `potion` [Entity] the `user` wants to drink.
+    /// * `target`: The targeted tile.
+    /// * `tile`: The tile to check.
     ///
-    pub fn drink(ecs: &World, user: &Entity, potion: &Entity) {
-        let mut usage_intent = ecs.write_storage::<UsePotion>();
-
-        let usage = UsePotion { potion: *potion };
-
-        let error_message = exceptions::get_drink_potion_error_message(user, potion);
-
-        usage_intent.insert(*user, usage).expect(&error_message);
+    pub fn covers(&self, target: &Point, tile: &Point) -> bool {
+        pythagoras_distance(target, tile) <= self.radius as f32
     }
 }
 
 /// Component marking an [Entity] as collected,
 /// meaning it is in the inventory of a owning [Entity].
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Loot {
     /// The owner of the collected loot.
     pub owner: Entity,
 }
 
-/// Component used for communication with the ItemCollectionSystem
-/// to indicate, that an [Entity] wants to pickup an [Item].
-#[derive(Component, Debug)]
-pub struct PickupItem {
-    /// The [Entity] wanting to pick up the item.
-    pub collector: Entity,
+/// The serialized form of the [Loot], with the
+/// [Entity] replaced by its marker `M`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LootData<M> {
+    /// The marker of the owner.
+    pub owner: M,
+}
 
-    /// The [Item] the `collector` wants to pick up.
-    pub item: Entity,
+impl<M> ConvertSaveload<M> for Loot
+where
+    M: Serialize + DeserializeOwned,
+{
+    type Data = LootData<M>;
+    type Error = Infallible;
+
+    fn convert_from<F>(data: Self::Data, ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        Ok(Loot {
+            owner: Entity::convert_from(data.owner, ids)?,
+        })
+    }
+
+    fn convert_into<F>(&self, ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        Ok(LootData {
+            owner: self.owner.convert_into(ids)?,
+        })
+    }
+}
+
+/// Component marking an [Item] as part of the stock of a [Shopkeeper].
+/// Items on display lie in the shop, the ones sold to the shopkeeper
+/// have no [Position]. Picking up an item on display without paying
+/// for it is theft.
+#[derive(Component, Debug, Clone)]
+pub struct ForSale {
+    /// The [Shopkeeper] selling the item.
+    pub shopkeeper: Entity,
+}
+
+/// The serialized form of the [ForSale], with the
+/// [Entity] replaced by its marker `M`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ForSaleData<M> {
+    /// The marker of the shopkeeper.
+    pub shopkeeper: M,
+}
+
+impl<M> ConvertSaveload<M> for ForSale
+where
+    M: Serialize + DeserializeOwned,
+{
+    type Data = ForSaleData<M>;
+    type Error = Infallible;
+
+    fn convert_from<F>(data: Self::Data, ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        Ok(ForSale {
+            shopkeeper: Entity::convert_from(data.shopkeeper, ids)?,
+        })
+    }
+
+    fn convert_into<F>(&self, ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        Ok(ForSaleData {
+            shopkeeper: self.shopkeeper.convert_into(ids)?,
+        })
+    }
+}
+
+/// Component for the containers of a level, e.g. chests. The [Item]s
+/// in a container have neither a [Position] nor are they [Loot].
+#[derive(Component, Debug, Clone)]
+pub struct Container {
+    /// The items in the container.
+    pub contents: Vec<Entity>,
+}
+
+/// The serialized form of the [Container], with the
+/// [Entity]s replaced by their markers `M`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ContainerData<M> {
+    /// The markers of the items.
+    pub contents: Vec<M>,
+}
+
+impl<M> ConvertSaveload<M> for Container
+where
+    M: Serialize + DeserializeOwned,
+{
+    type Data = ContainerData<M>;
+    type Error = Infallible;
+
+    fn convert_from<F>(data: Self::Data, mut ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        let contents = data
+            .contents
+            .into_iter()
+            .map(|item| Entity::convert_from(item, &mut ids))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Container { contents })
+    }
+
+    fn convert_into<F>(&self, mut ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        let contents = self
+            .contents
+            .iter()
+            .map(|item| item.convert_into(&mut ids))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ContainerData { contents })
+    }
+}
+
+/// Enum describing the slots an [Equippable] item is worn in.
+/// Every [Entity] can wear only one item per slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EquipmentSlot {
+    /// Weapons held in the main hand.
+    Melee,
+
+    /// Shields held in the off hand, or a second one-handed
+    /// melee weapon, see [Equippable::two_handed].
+    Shield,
+
+    /// Armor worn on the body.
+    Armor,
+
+    /// Ammunition carried in the quiver, see [Ammunition].
+    Quiver,
+
+    /// Ranged weapons held ready next to the
+    /// melee weapon, see [RangedWeapon].
+    Ranged,
+}
+
+/// Enum describing the kinds of [Ammunition],
+/// every ranged weapon fires only one of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmmunitionKind {
+    /// Arrows shot by bows.
+    Arrow,
+
+    /// Bolts shot by crossbows.
+    Bolt,
+}
+
+/// Component marking an [Item] as ammunition of the given `kind`,
+/// which is worn in the [EquipmentSlot::Quiver] and used up by the
+/// shots of a ranged weapon, see the [ammunition](super::ammunition) module.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Ammunition {
+    /// The kind of the ammunition.
+    pub kind: AmmunitionKind,
+}
+
+/// Component marking an [Item], that can be worn in
+/// its `slot` and improves the stats of its wearer.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Equippable {
+    /// The slot the item is worn in.
+    pub slot: EquipmentSlot,
+
+    /// The power added to the wearer's [Statistics].
+    pub power_bonus: i32,
+
+    /// The defense added to the wearer's [Statistics].
+    pub defense_bonus: i32,
+
+    /// Flag indicating whether or not the item needs both hands,
+    /// which leaves no room for anything in the off hand.
+    #[serde(default)]
+    pub two_handed: bool,
+
+    /// The chance in percent to block a melee attack, e.g. of a shield.
+    #[serde(default)]
+    pub block_chance: i32,
+
+    /// The damage dice of a melee weapon, e.g. `1d8+2`,
+    /// `None` for items, that aren't wielded as weapon.
+    #[serde(default)]
+    pub damage: Option<Dice>,
+}
+
+impl Equippable {
+    /// Returns `true` if wearing the item in the passed `slot` requires
+    /// taking off the `other` item, which is worn in the `other_slot`.
+    /// Besides the item in the same slot, two-handed weapons displace
+    /// the off hand and anything in the off hand displaces them.
+    ///
+    /// # Arguments
+    /// * `slot`: The slot the item is about to be worn in.
+    /// * `other`: The [Equippable] of the worn item.
+    /// * `other_slot`: The slot the `other` item is worn in.
+    ///
+    pub fn displaces(
+        &self,
+        slot: EquipmentSlot,
+        other: &Equippable,
+        other_slot: EquipmentSlot,
+    ) -> bool {
+        other_slot == slot
+            || (self.two_handed && other_slot == EquipmentSlot::Shield)
+            || (other.two_handed && slot == EquipmentSlot::Shield)
+    }
+
+    /// Returns `true` if the item is a one-handed melee weapon,
+    /// which can be wielded in the off hand as well.
+    pub fn is_off_hand_weapon(&self) -> bool {
+        self.slot == EquipmentSlot::Melee && !self.two_handed
+    }
+}
+
+/// Component marking an [Equippable] [Item] as worn by its owner.
+#[derive(Component, Debug, Clone)]
+pub struct Equipped {
+    /// The entity wearing the item.
+    pub owner: Entity,
+
+    /// The slot the item is worn in.
+    pub slot: EquipmentSlot,
+}
+
+/// The serialized form of the [Equipped], with the
+/// [Entity] replaced by its marker `M`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EquippedData<M> {
+    /// The marker of the owner.
+    pub owner: M,
+
+    /// The slot the item is worn in.
+    pub slot: EquipmentSlot,
+}
+
+impl<M> ConvertSaveload<M> for Equipped
+where
+    M: Serialize + DeserializeOwned,
+{
+    type Data = EquippedData<M>;
+    type Error = Infallible;
+
+    fn convert_from<F>(data: Self::Data, ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        Ok(Equipped {
+            owner: Entity::convert_from(data.owner, ids)?,
+            slot: data.slot,
+        })
+    }
+
+    fn convert_into<F>(&self, ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        Ok(EquippedData {
+            owner: self.owner.convert_into(ids)?,
+            slot: self.slot,
+        })
+    }
+}
+
+/// Component marking an [Equippable] [Item] as a ranged weapon, which
+/// its wearer fires at targets within its `range`, see the
+/// [RangedCombatSystem](super::RangedCombatSystem).
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct RangedWeapon {
+    /// The maximum distance between the shooter and its target.
+    pub range: i32,
+
+    /// The damage of a hit before the defense of the target.
+    pub power: i32,
+
+    /// The kind of [Ammunition] the weapon fires, `None` for
+    /// weapons, that don't need any, e.g. slings.
+    pub ammunition: Option<AmmunitionKind>,
+}
+
+impl RangedWeapon {
+    /// Returns the ranged weapon worn by the passed `owner`,
+    /// or `None` if the owner has none at hand.
+    ///
+    /// # Arguments
+    /// * `owner`: The [Entity] wearing the weapon.
+    /// * `equipped`: The [Equipped] storage of the `ecs`.
+    /// * `weapons`: The [RangedWeapon] storage of the `ecs`.
+    ///
+    pub fn wielded<'a>(
+        owner: Entity,
+        equipped: &'a ReadStorage<Equipped>,
+        weapons: &'a ReadStorage<RangedWeapon>,
+    ) -> Option<&'a RangedWeapon> {
+        (equipped, weapons)
+            .join()
+            .find(|(worn, _)| worn.owner == owner)
+            .map(|(_, weapon)| weapon)
+    }
+}
+
+/// The bonuses all [Equipped] items of an [Entity] add to its [Statistics].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EquipmentBonus {
+    /// The sum of the power bonuses.
+    pub power: i32,
+
+    /// The sum of the defense bonuses.
+    pub defense: i32,
+
+    /// The sum of the block chances in percent.
+    pub block_chance: i32,
+
+    /// The damage dice of the weapon wielded in the main
+    /// hand, `None` if the owner fights unarmed.
+    pub damage: Option<Dice>,
+
+    /// The power bonus of the weapon wielded in the off hand, `None`
+    /// if there is none. It only counts for the off hand's own attack
+    /// and isn't part of the `power`.
+    pub off_hand_power: Option<i32>,
+
+    /// The damage dice of the weapon wielded in the off hand.
+    pub off_hand_damage: Option<Dice>,
+}
+
+impl EquipmentBonus {
+    /// Returns the bonuses of all items worn by the `owner`.
+    ///
+    /// # Arguments
+    /// * `owner`: The [Entity] wearing the items.
+    /// * `equipped`: The [Equipped] storage of the `ecs`.
+    /// * `equippables`: The [Equippable] storage of the `ecs`.
+    ///
+    pub fn of(
+        owner: Entity,
+        equipped: &ReadStorage<Equipped>,
+        equippables: &ReadStorage<Equippable>,
+    ) -> EquipmentBonus {
+        (equipped, equippables)
+            .join()
+            .filter(|(worn, _)| worn.owner == owner)
+            .fold(EquipmentBonus::default(), |bonus, (worn, equippable)| {
+                let is_off_hand_weapon =
+                    worn.slot == EquipmentSlot::Shield && equippable.slot == EquipmentSlot::Melee;
+
+                if is_off_hand_weapon {
+                    EquipmentBonus {
+                        off_hand_power: Some(equippable.power_bonus),
+                        off_hand_damage: equippable.damage,
+                        defense: bonus.defense + equippable.defense_bonus,
+                        block_chance: bonus.block_chance + equippable.block_chance,
+                        ..bonus
+                    }
+                } else {
+                    EquipmentBonus {
+                        power: bonus.power + equippable.power_bonus,
+                        damage: equippable.damage.or(bonus.damage),
+                        defense: bonus.defense + equippable.defense_bonus,
+                        block_chance: bonus.block_chance + equippable.block_chance,
+                        ..bonus
+                    }
+                }
+            })
+    }
+}
+
+/// Component marking an [Item] as cursed. Cursed items can't be taken
+/// off or dropped once they are [Equipped], until the curse is removed.
+///
+/// # Notes
+/// * The curse is hidden from the player, until the item is worn.
+///
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Cursed {
+    /// Flag indicating whether or not the player knows of the curse.
+    pub revealed: bool,
 }
 
 /// Component used for communication with the
-/// ItemDropSystem to indicate, that an [Entity]
-/// wants to drop a collected [Item].
-#[derive(Component, Debug)]
-pub struct DropItem {
-    /// Reference to the [Item] entity to drop.
+/// ItemUseSystem to indicate, that an
+/// [Entity] wants to use an [Item].
+#[derive(Component, Debug, Clone)]
+pub struct UseItem {
+    /// The [Item] the [Entity] wants to use.
     pub item: Entity,
+
+    /// The targeted tile, if the [Item] is [Ranged].
+    pub target: Option<Point>,
 }
 
-/// Component used for communication with the
-/// PotionDrinkSystem to indicate, that an
-/// [Entity] wants to drink a [Potion].
-#[derive(Component, Debug)]
-pub struct UsePotion {
-    /// The [Potion] the [Entity] wants to consume.
-    pub potion: Entity,
+/// The serialized form of the [UseItem], with the
+/// [Entity] replaced by its marker `M`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UseItemData<M> {
+    /// The marker of the [Item].
+    pub item: M,
+
+    /// The targeted tile, if the [Item] is [Ranged].
+    pub target: Option<Point>,
+}
+
+impl<M> ConvertSaveload<M> for UseItem
+where
+    M: Serialize + DeserializeOwned,
+{
+    type Data = UseItemData<M>;
+    type Error = Infallible;
+
+    fn convert_from<F>(data: Self::Data, ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        Ok(UseItem {
+            item: Entity::convert_from(data.item, ids)?,
+            target: data.target,
+        })
+    }
+
+    fn convert_into<F>(&self, ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        Ok(UseItemData {
+            item: self.item.convert_into(ids)?,
+            target: self.target,
+        })
+    }
 }
 
 /// Shorthand function to register all needed
@@ -383,19 +1724,54 @@ pub struct UsePotion {
 ///
 pub fn register_components(ecs: &mut World) {
     ecs.register::<FOV>();
+    ecs.register::<Facing>();
     ecs.register::<Name>();
     ecs.register::<Item>();
+    ecs.register::<Value>();
     ecs.register::<Loot>();
+    ecs.register::<ForSale>();
     ecs.register::<Player>();
-    ecs.register::<Potion>();
+    ecs.register::<Sneaking>();
+    ecs.register::<Scripted>();
     ecs.register::<Monster>();
+    ecs.register::<Boss>();
+    ecs.register::<OutOfDepth>();
+    ecs.register::<Unique>();
+    ecs.register::<Friendly>();
+    ecs.register::<Shopkeeper>();
+    ecs.register::<Purse>();
+    ecs.register::<Charisma>();
+    ecs.register::<Faction>();
+    ecs.register::<AI>();
     ecs.register::<Position>();
-    ecs.register::<DropItem>();
     ecs.register::<Collision>();
-    ecs.register::<UsePotion>();
+    ecs.register::<Opaque>();
+    ecs.register::<Door>();
+    ecs.register::<Key>();
+    ecs.register::<Container>();
+    ecs.register::<Stackable>();
+    ecs.register::<Weight>();
+    ecs.register::<Ammunition>();
+    ecs.register::<RangedWeapon>();
+    ecs.register::<Ranged>();
+    ecs.register::<AreaOfEffect>();
+    ecs.register::<Equippable>();
+    ecs.register::<Equipped>();
+    ecs.register::<Cursed>();
+    ecs.register::<UseItem>();
     ecs.register::<Renderable>();
+    ecs.register::<LightSource>();
     ecs.register::<Statistics>();
-    ecs.register::<PickupItem>();
-    ecs.register::<MeleeAttack>();
-    ecs.register::<DamageCounter>();
+    ecs.register::<Experience>();
+    ecs.register::<ExperienceReward>();
+    ecs.register::<Mana>();
+    ecs.register::<Spellbook>();
+    ecs.register::<CastSpell>();
+    ecs.register::<StatusEffects>();
+    ecs.register::<InflictsStatus>();
+    ecs.register::<Regenerates>();
+    ecs.register::<Invisible>();
+    ecs.register::<ParticleLifetime>();
+
+    saveload::register(ecs);
 }
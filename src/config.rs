@@ -1,7 +1,14 @@
 //! Global game settings.
+//!
+//! The values of the window and the map generation are the defaults of
+//! the [GameConfig](super::game_config::GameConfig), which overrides them
+//! through the [CONFIG_FILE].
 
 use rltk::console;
 
+use super::fov::FovAlgorithm;
+use super::map_builders::MapGenerator;
+
 /// The current version of the game.
 pub const GAME_VERSION: &'static str = "v0.2.8";
 
@@ -33,11 +40,387 @@ pub const MIN_ROOM_SIZE: i32 = 6;
 /// the map.
 pub const MAX_ROOM_SIZE: i32 = 10;
 
-/// The maximum amount of monsters, that can be spawned in a single room of the game.
-pub const MAX_MONSTERS_PER_ROOM: i32 = 4;
+/// The algorithm creating the layout of the maps.
+pub const MAP_GENERATOR: MapGenerator = MapGenerator::Random;
+
+/// The minimum width and height of a leaf of the binary space
+/// partition of [MapGenerator::Bsp], every leaf contains one room.
+pub const BSP_MIN_LEAF_SIZE: i32 = MAX_ROOM_SIZE + 2;
+
+/// The chance in percent of a tile being a wall before
+/// the caves of [MapGenerator::Cave] are smoothed.
+pub const CAVE_WALL_CHANCE: i32 = 45;
+
+/// The amount of smoothing iterations of the cellular
+/// automaton of [MapGenerator::Cave].
+pub const CAVE_SMOOTHING_ITERATIONS: i32 = 12;
+
+/// The minimum share of the map in percent, that the connected
+/// part of a cave of [MapGenerator::Cave] has to cover.
+pub const CAVE_MIN_FLOOR_PERCENT: usize = 40;
+
+/// The maximum amount of caves generated while
+/// looking for one with enough floor tiles.
+pub const CAVE_MAX_ATTEMPTS: i32 = 10;
+
+/// The minimum percentage of the map, that the connected
+/// part of a layout of [MapGenerator::Wfc] has to cover.
+pub const WFC_MIN_FLOOR_PERCENT: usize = 35;
+
+/// The maximum amount of wave function collapse layouts generated,
+/// before falling back to rooms and corridors.
+pub const WFC_MAX_ATTEMPTS: i32 = 5;
+
+/// The depth of the town on the surface, in which the
+/// game starts and from which the dungeon is entered.
+pub const TOWN_DEPTH: i32 = 0;
+
+/// The depth of the last level of the dungeon, which has
+/// no stairs, but the arena of the boss instead.
+pub const FINAL_DEPTH: i32 = 5;
+
+/// The first depth of the caverns, the levels
+/// above belong to the crypt.
+pub const CAVERNS_MIN_DEPTH: i32 = 3;
+
+/// The first depth of the fungal forest.
+pub const FUNGAL_FOREST_MIN_DEPTH: i32 = 4;
+
+/// The side length of the square regions, that maps
+/// without rooms are populated by, one at a time.
+pub const SPAWN_REGION_SIZE: i32 = 16;
+
+/// The minimum amount of floor tiles of a region of
+/// a map without rooms, to spawn entities in it.
+pub const MIN_SPAWN_REGION_TILES: usize = 24;
+
+/// The maximum amount of monsters spawned in a single
+/// room or region, regardless of the spawn tables.
+pub const MAX_MONSTERS_PER_ROOM: usize = 6;
+
+/// The maximum amount of items spawned in a single
+/// room or region, regardless of the spawn tables.
+pub const MAX_ITEMS_PER_ROOM: usize = 4;
+
+/// The directory in which save data is stored on native
+/// targets. The web build uses the browser's local storage.
+pub const SAVE_DIRECTORY: &str = "saves";
+
+/// The maximum amount of entries the message log keeps,
+/// older entries are dropped.
+pub const MAX_LOG_ENTRIES: usize = 100;
+
+/// The maximum amount of tiles the `click-to-move` path finding
+/// explores, before the target is treated as unreachable.
+pub const MAX_PATHING_EXPANSIONS: usize = 1024;
+
+/// The default field of view algorithm, can
+/// be changed through the player's settings.
+pub const DEFAULT_FOV_ALGORITHM: FovAlgorithm = FovAlgorithm::Rltk;
+
+/// The opening angle in degrees of the vision cone of entities
+/// with a [Facing](super::Facing). Tiles outside the cone are
+/// not part of their field of view.
+pub const VISION_CONE_ANGLE: f32 = 120.0;
+
+/// The damage multiplier of attacks on targets, that can't see
+/// their attacker, and of the player's attacks on suspicious monsters.
+pub const SNEAK_ATTACK_MULTIPLIER: i32 = 2;
+
+/// The damage multiplier of the player's attacks on
+/// monsters, that haven't noticed the player.
+pub const UNAWARE_SNEAK_ATTACK_MULTIPLIER: i32 = 3;
+
+/// The distance up to which monsters hear the player. Sneaking
+/// players only make noise up to half the distance.
+pub const PLAYER_NOISE_RADIUS: f32 = 6.0;
+
+/// The chance in percent, that a doorway of
+/// a room is closed off by a door.
+pub const DOOR_CHANCE: i32 = 60;
+
+/// The chance in percent, that a room other than the
+/// player's starting room is a treasure room with a chest.
+pub const TREASURE_ROOM_CHANCE: i32 = 15;
+
+/// The chance in percent, that a door is locked and
+/// its [Key](super::Key) is placed in another room.
+pub const LOCKED_DOOR_CHANCE: i32 = 20;
+
+/// The value a roll of a d20 plus the power of the basher has
+/// to reach to break a door.
+pub const DOOR_BASH_DIFFICULTY: i32 = 18;
+
+/// The power a monster needs to bash in doors,
+/// weaker monsters are stopped by closed doors.
+pub const DOOR_BASH_MIN_POWER: i32 = 4;
+
+/// The distance up to which monsters hear the player bashing a door.
+pub const DOOR_BASH_NOISE_RADIUS: f32 = 12.0;
+
+/// The fraction of their maximum hp, below which
+/// monsters lose their morale and flee from the player.
+pub const FLEE_HP_FRACTION: f32 = 0.25;
+
+/// The maximum distance from the player up to which fleeing
+/// monsters plan their escape. Tiles further away are
+/// considered safe.
+pub const FLEE_SEARCH_DEPTH: f32 = 20.0;
+
+/// The factor by which the distances to the player are inverted in
+/// the flee map. Values above `1` let fleeing monsters accept a few
+/// steps towards the player to reach a safer part of the map.
+pub const FLEE_INVERSION_FACTOR: f32 = 1.2;
+
+/// The fraction of their maximum hp, below which
+/// cowardly monsters already flee from the player.
+pub const COWARDLY_FLEE_HP_FRACTION: f32 = 0.75;
+
+/// The maximum distance on each axis from a patrolling
+/// monster to the next tile it walks to.
+pub const PATROL_RADIUS: i32 = 8;
+
+/// The maximum distance on each axis a wandering
+/// NPC strays from the tile it was created on.
+pub const WANDER_RADIUS: i32 = 3;
+
+/// The chance in percent of a wandering NPC to take a step in its turn.
+pub const WANDER_STEP_CHANCE: i32 = 30;
+
+/// The range of the field of view of the NPCs.
+pub const NPC_VISION_RANGE: i32 = 6;
+
+/// The maximum distance on each axis from a shopkeeper
+/// to the items it puts on display.
+pub const SHOP_DISPLAY_RADIUS: i32 = 2;
+
+/// The share in percent of an item's price, that
+/// the shopkeepers pay for an item sold to them.
+pub const SELL_PRICE_PERCENT: i32 = 50;
+
+/// The share in percent of an item's price, that unidentified
+/// items are worth, since nobody knows what they are.
+pub const UNIDENTIFIED_PRICE_PERCENT: i32 = 40;
+
+/// The surcharge in percent on the prices of the shopkeepers for
+/// every level of depth, on which they trade.
+pub const DEPTH_PRICE_PERCENT: i32 = 10;
+
+/// The discount in percent on buying and the bonus on selling
+/// the player haggles out for every point of charisma.
+pub const CHARISMA_PRICE_PERCENT: i32 = 5;
+
+/// The maximum discount and bonus in percent granted for the
+/// charisma, so items can never be sold for more than they cost.
+pub const MAX_CHARISMA_PRICE_PERCENT: i32 = 30;
+
+/// The points of the score for every level of depth reached.
+pub const SCORE_PER_DEPTH: i32 = 100;
+
+/// The points of the score for every defeated monster.
+pub const SCORE_PER_KILL: i32 = 10;
+
+/// The gold the player starts a new run with.
+pub const STARTING_GOLD: i32 = 40;
+
+/// The fraction of its maximum hp, below which the boss
+/// enters its second phase and flies into a rage.
+pub const BOSS_ENRAGE_HP_FRACTION: f32 = 0.5;
+
+/// The attack power the boss gains, once it's enraged.
+pub const BOSS_ENRAGE_POWER: i32 = 3;
+
+/// The xp needed per level to reach the next level,
+/// e.g. level `2` needs `2 * XP_PER_LEVEL` xp.
+pub const XP_PER_LEVEL: i32 = 50;
+
+/// The maximum hp gained on every level up.
+pub const LEVEL_UP_HP: i32 = 5;
+
+/// The attack power gained on every level up.
+pub const LEVEL_UP_POWER: i32 = 1;
+
+/// The charisma gained on every level up.
+pub const LEVEL_UP_CHARISMA: i32 = 1;
+
+/// The weight every entity can carry without being slowed,
+/// regardless of its power.
+pub const BASE_CARRY_CAPACITY: i32 = 20;
+
+/// The carrying capacity gained for every point of power.
+pub const CARRY_CAPACITY_PER_POWER: i32 = 4;
+
+/// The maximum load in percent of the carrying capacity, beyond
+/// which no further items can be picked up.
+pub const MAX_LOAD_PERCENT: i32 = 150;
+
+/// The power by which the extra attack of a weapon
+/// wielded in the off hand is reduced.
+pub const DUAL_WIELD_PENALTY: i32 = 2;
+
+/// The armor class of a target without any defense, which the
+/// d20 roll of a melee attack plus the attacker's power has to reach.
+pub const BASE_ARMOR_CLASS: i32 = 10;
+
+/// The sides of the single die rolled for the
+/// damage of a melee attack without a weapon.
+pub const UNARMED_DAMAGE_SIDES: i32 = 4;
+
+/// The chance in percent, that a shot arrow or bolt stays intact
+/// and can be picked up again from the tile it landed on.
+pub const AMMUNITION_RECOVERY_CHANCE: i32 = 50;
+
+/// The number of turns between two regeneration ticks of the player.
+pub const REGENERATION_INTERVAL: u64 = 10;
+
+/// The hp restored on every regeneration tick.
+pub const REGENERATION_HP: i32 = 1;
+
+/// The mp restored on every regeneration tick.
+pub const REGENERATION_MP: i32 = 1;
+
+/// The light level of the tiles in the player's field of
+/// view, that aren't lit by any [LightSource](super::LightSource).
+pub const AMBIENT_LIGHT: f32 = 0.4;
+
+/// The range of the torch carried by the player.
+pub const PLAYER_LIGHT_RANGE: i32 = 8;
+
+/// The color of the torch carried by the player.
+pub const PLAYER_LIGHT_COLOR: (u8, u8, u8) = (255, 214, 170);
+
+/// The faction of the player, see [FactionRelations](super::faction::FactionRelations).
+pub const PLAYER_FACTION: &str = "player";
+
+/// The faction of the friendly NPCs of the town.
+pub const NPC_FACTION: &str = "townsfolk";
+
+/// The faction of monsters, whose definition doesn't name one.
+pub const DEFAULT_MONSTER_FACTION: &str = "monsters";
+
+/// The number of pools of water and lava and chasms
+/// scattered over every level of the dungeon.
+pub const TERRAIN_FEATURES_PER_LEVEL: i32 = 4;
+
+/// The number of heaps of rubble scattered over
+/// every level of the dungeon.
+pub const RUBBLE_PER_LEVEL: i32 = 6;
+
+/// The depth from which on lava and chasms appear,
+/// shallower levels only contain deep water.
+pub const HAZARD_MIN_DEPTH: i32 = 2;
+
+/// The damage dealt every turn to entities standing in lava.
+pub const LAVA_DAMAGE: i32 = 5;
+
+/// The amount of turns a tile burns, once it has caught fire.
+pub const FIRE_DURATION: i32 = 5;
+
+/// The chance in percent, that the fire on a burning
+/// tile spreads to a flammable neighbor every turn.
+pub const FIRE_SPREAD_CHANCE: i32 = 40;
+
+/// The damage dealt every turn to entities standing in fire.
+pub const FIRE_DAMAGE: i32 = 3;
+
+/// The chance in percent, that a level of the dungeon is
+/// haunted by a unique monster, that hasn't appeared yet.
+pub const UNIQUE_SPAWN_CHANCE: i32 = 25;
+
+/// The minimum distance of a unique monster to the player's start.
+pub const UNIQUE_MIN_START_DISTANCE: f32 = 12.0;
+
+/// The range of the field of view of blinded entities.
+pub const BLIND_VISION_RANGE: i32 = 1;
+
+/// The amount of turns the regeneration of an entity
+/// with `Regenerates` stops, after it has been hit by fire.
+pub const FIRE_REGENERATION_PAUSE: i32 = 3;
+
+/// The damage the player takes when falling into a chasm.
+pub const CHASM_FALL_DAMAGE: i32 = 3;
+
+/// The number of items listed on a single page of the
+/// inventory screen, at most one per letter of the alphabet.
+pub const INVENTORY_PAGE_SIZE: usize = 20;
+
+/// The time in milliseconds a particle, e.g. the
+/// slash mark of an attack, stays on the screen.
+pub const PARTICLE_LIFETIME_MS: f32 = 250.0;
+
+/// The time in milliseconds the particles of an
+/// explosion stay on the screen at its center.
+pub const EXPLOSION_LIFETIME_MS: f32 = 500.0;
+
+/// The default frame rate cap of the game, can
+/// be changed through the player's settings.
+pub const DEFAULT_FPS_CAP: u32 = 60;
+
+/// The frame rate cap while the game is idle.
+pub const IDLE_FPS_CAP: u32 = 5;
+
+/// The time without any input in seconds,
+/// after which the game is idle.
+pub const IDLE_TIMEOUT_SECONDS: u64 = 30;
+
+/// The draw layer of the map. Every layer is the z-order its
+/// [DrawBatch] is submitted with, higher layers are drawn on top.
+/// The gaps between the layers leave room for every command of
+/// a batch, since each command is ordered by its layer plus its
+/// index in the batch.
+///
+/// [DrawBatch]: rltk::DrawBatch
+pub const LAYER_MAP: usize = 0;
+
+/// The draw layer of all entities on the map.
+pub const LAYER_ENTITIES: usize = 100_000;
+
+/// The draw layer of the particles, on top of the entities.
+pub const LAYER_PARTICLES: usize = 150_000;
+
+/// The draw layer of the menus and the base ui,
+/// i.e. the message log and the status information.
+pub const LAYER_UI: usize = 200_000;
+
+/// The draw layer of overlays on top of the map and
+/// ui, e.g. the targeting range or performance overlay.
+pub const LAYER_OVERLAY: usize = 300_000;
+
+/// The draw layer of the tooltips.
+pub const LAYER_TOOLTIP: usize = 400_000;
+
+/// The draw layer of dialogs, the top most layer.
+pub const LAYER_DIALOG: usize = 500_000;
+
+/// The time in milliseconds a touch has to be held on
+/// the same tile, before it's handled as long press.
+#[cfg(target_arch = "wasm32")]
+pub const LONG_PRESS_MILLIS: u64 = 500;
+
+/// The amount of recent log messages
+/// written to a crash report.
+pub const CRASH_LOG_ENTRIES: usize = 20;
+
+/// The amount of recent durations the performance
+/// diagnostics average over.
+pub const DIAGNOSTICS_SAMPLES: usize = 60;
+
+/// The directory from which content scripts are loaded on
+/// native targets, in addition to the embedded scripts.
+pub const SCRIPT_DIRECTORY: &str = "scripts";
+
+/// The directory from which entity definitions are loaded
+/// on native targets, in addition to the embedded ones.
+pub const RAW_DIRECTORY: &str = "raws";
+
+/// The directory from which language files are loaded on
+/// native targets, in addition to the embedded languages.
+pub const LANGUAGE_DIRECTORY: &str = "lang";
 
-/// The maximum amount of items, that can be spawned in a single room of the game.
-pub const MAX_ITEMS_PER_ROOM: i32 = 2;
+/// The file next to the game, from which the
+/// [GameConfig](super::game_config::GameConfig) is read on native targets.
+#[cfg(not(target_arch = "wasm32"))]
+pub const CONFIG_FILE: &str = "config.toml";
 
 /// Prints the games logo, copyright notice and current
 /// version to the console.
@@ -0,0 +1,158 @@
+//! Module for the containers of the dungeon, e.g. chests.
+//!
+//! Chests are placed in the treasure rooms while a level is built, see
+//! the [ChestBuilder](super::map_builders::ChestBuilder). They are filled
+//! with the items of the treasure [SpawnTable](super::spawn_table::SpawnTable).
+//! Bumping into a [Container] lists its contents in a [DialogInterface],
+//! from which the player takes single items or everything at once. The
+//! taken items are picked up like the ones lying on the floor, see [PickupEvent].
+
+use rltk::VirtualKeyCode;
+use specs::prelude::*;
+
+use super::identification::Identification;
+use super::localization;
+use super::{
+    i32_to_alpha_key, publish_event, Container, DialogArgs, DialogInterface, DialogOption, GameLog,
+    LogCategory, Name, PickupEvent,
+};
+
+/// The maximum amount of items listed in the dialog of a container,
+/// one for every letter of the alphabet.
+const MAX_LISTED_ITEMS: usize = 26;
+
+/// Opens the passed `container` for the player, listing its contents
+/// through a [DialogInterface]. Empty containers are only reported
+/// to the [GameLog].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the dialog should be registered.
+/// * `container`: The [Container] the player has bumped into.
+///
+/// # Notes
+/// * Every item is selected through the letter of its position in
+///   the container, all items are taken through the space key.
+///
+pub fn open(ecs: &mut World, container: Entity) {
+    let (title, items) = {
+        let identification = ecs.fetch::<Identification>();
+        let names = ecs.read_storage::<Name>();
+        let containers = ecs.read_storage::<Container>();
+
+        let items = containers
+            .get(container)
+            .map(|container| {
+                container
+                    .contents
+                    .iter()
+                    .filter_map(|item| names.get(*item).map(|name| (*item, name)))
+                    .map(|(item, name)| (item, identification.display(name)))
+                    .take(MAX_LISTED_ITEMS)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        (
+            names.get(container).map(Name::display).unwrap_or_default(),
+            items,
+        )
+    };
+
+    if items.is_empty() {
+        ecs.write_resource::<GameLog>().push(
+            &localization::tr_with("container.empty", &[&title]),
+            LogCategory::Item,
+        );
+        return;
+    }
+
+    let mut options = items
+        .into_iter()
+        .enumerate()
+        .map(|(index, (item, name))| DialogOption {
+            description: name,
+            key: i32_to_alpha_key(index as i32),
+            args: vec![Box::new(container), Box::new(item)],
+            callback: Box::new(|world, _, args| take(world, args)),
+        })
+        .collect::<Vec<_>>();
+
+    options.push(DialogOption {
+        description: localization::tr("container.take_all"),
+        key: VirtualKeyCode::Space,
+        args: vec![Box::new(container)],
+        callback: Box::new(|world, _, args| take_all(world, args)),
+    });
+
+    DialogInterface::register_dialog(
+        ecs,
+        title,
+        Some(localization::tr("container.contents")),
+        options,
+        true,
+    );
+}
+
+/// Callback of the [DialogOption] of a single item, which takes
+/// the item out of the container and hands it to the player.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the container.
+/// * `args`: The [Entity] of the container, followed by the [Entity] of the item.
+///
+fn take(ecs: &World, args: &DialogArgs) {
+    let mut entities = args.iter().filter_map(|arg| arg.downcast_ref::<Entity>());
+
+    if let (Some(container), Some(item)) = (entities.next(), entities.next()) {
+        transfer(ecs, *container, |contents| {
+            let index = contents.iter().position(|content| content == item);
+            index
+                .map(|index| vec![contents.remove(index)])
+                .unwrap_or_default()
+        });
+    }
+}
+
+/// Callback of the take all [DialogOption], which empties the
+/// container and hands all of its items to the player.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the container.
+/// * `args`: The [Entity] of the container.
+///
+fn take_all(ecs: &World, args: &DialogArgs) {
+    if let Some(container) = args.first().and_then(|arg| arg.downcast_ref::<Entity>()) {
+        transfer(ecs, *container, std::mem::take);
+    }
+}
+
+/// Removes the items selected by `select` from the contents of the
+/// passed `container` and publishes a [PickupEvent] of the player
+/// for every one of them.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the container.
+/// * `container`: The [Container] the items are taken from.
+/// * `select`: Removes the taken items from the contents and returns them.
+///
+fn transfer<F>(ecs: &World, container: Entity, select: F)
+where
+    F: FnOnce(&mut Vec<Entity>) -> Vec<Entity>,
+{
+    let player = *ecs.fetch::<Entity>();
+
+    let taken = match ecs.write_storage::<Container>().get_mut(container) {
+        Some(container) => select(&mut container.contents),
+        None => return,
+    };
+
+    for item in taken {
+        publish_event(
+            ecs,
+            PickupEvent {
+                collector: player,
+                item,
+            },
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Module for reporting crashes of the game.
+//!
+//! A panic hook writes a crash report through the [storage] module
+//! before the game exits. Since the `ecs` can't be accessed from the
+//! hook, the state relevant for reproducing the crash is copied into a
+//! [CrashSnapshot] every turn, see [update_snapshot]. The report
+//! contains the panic message, the seed of the `rng` handler, the
+//! turn, the player's state and the most recent log messages, so it
+//! can be attached to a bug report.
+//!
+//! The snapshot holds the serialized game of the latest turn as well,
+//! which the hook writes into the save game as an emergency save. The
+//! game can't be serialized in the hook itself, since the panic may have
+//! left the `ecs` in an inconsistent state and the hook has no access
+//! to it. Both the report and the emergency save are best-effort, if
+//! the snapshot is locked by the panicking thread they are skipped.
+
+use std::fmt::Write;
+use std::panic::{self, PanicHookInfo};
+use std::sync::Mutex;
+
+use chrono::Utc;
+use rltk::console;
+use specs::prelude::*;
+
+use super::rng::RngSeed;
+use super::{config, saveload, storage, GameLog, Map, Position, Statistics, TurnCounter};
+
+/// The state of the game copied from the `ecs`,
+/// which is written to the crash report.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CrashSnapshot {
+    /// The seed of the `rng` handler.
+    pub seed: Option<u64>,
+
+    /// The current turn.
+    pub turn: u64,
+
+    /// The depth of the current map.
+    pub depth: i32,
+
+    /// The position of the player as `(x, y)`.
+    pub player_position: Option<(i32, i32)>,
+
+    /// The health of the player as `(hp, hp_max)`.
+    pub player_health: Option<(i32, i32)>,
+
+    /// The most recent log messages from
+    /// the oldest to the newest.
+    pub recent_log: Vec<String>,
+
+    /// The serialized game, written as an emergency save,
+    /// or `None` if the game couldn't be serialized.
+    pub save_game: Option<String>,
+}
+
+impl CrashSnapshot {
+    /// Copies the crash relevant state from the passed `ecs`.
+    /// Missing resources or components are left empty.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] of the running game.
+    ///
+    /// # Notes
+    /// * The game isn't serialized, see [CrashSnapshot::save_game].
+    ///
+    pub fn capture(ecs: &World) -> Self {
+        let player = ecs.try_fetch::<Entity>().map(|player| *player);
+        let positions = ecs.read_storage::<Position>();
+        let statistics = ecs.read_storage::<Statistics>();
+
+        let mut recent_log: Vec<String> = ecs
+            .try_fetch::<GameLog>()
+            .map(|game_log| {
+                game_log
+                    .iter_recent(config::CRASH_LOG_ENTRIES)
+                    .map(|entry| format!("{:>4} > {}", entry.turn, entry.text))
+                    .collect()
+            })
+            .unwrap_or_default();
+        recent_log.reverse();
+
+        CrashSnapshot {
+            seed: ecs.try_fetch::<RngSeed>().map(|seed| seed.seed),
+            turn: ecs
+                .try_fetch::<TurnCounter>()
+                .map_or(0, |counter| counter.turn),
+            depth: ecs.try_fetch::<Map>().map_or(0, |map| map.depth),
+            player_position: player
+                .and_then(|player| positions.get(player))
+                .map(|position| (position.x, position.y)),
+            player_health: player
+                .and_then(|player| statistics.get(player))
+                .map(|statistic| (statistic.hp, statistic.hp_max)),
+            recent_log,
+            save_game: None,
+        }
+    }
+}
+
+/// The snapshot written by the panic hook.
+static SNAPSHOT: Mutex<Option<CrashSnapshot>> = Mutex::new(None);
+
+/// Replaces the snapshot written to crash reports with
+/// the current state of the passed `ecs`, including the
+/// serialized game for the emergency save.
+///
+/// # Arguments
+/// * `ecs`: The [World] of the running game.
+///
+/// # Notes
+/// * Errors of the serialization are reported to the console
+///   and leave the snapshot without an emergency save.
+///
+pub fn update_snapshot(ecs: &mut World) {
+    let save_game = saveload::serialize_game(ecs)
+        .inspect_err(|error| console::log(format!("Unable to snapshot the game: {}", error)))
+        .ok();
+
+    let snapshot = CrashSnapshot {
+        save_game,
+        ..CrashSnapshot::capture(ecs)
+    };
+
+    *SNAPSHOT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(snapshot);
+}
+
+/// Installs the panic hook, which writes a crash report, prints
+/// its location and writes the emergency save before the default
+/// hook reports the panic itself.
+///
+/// # Notes
+/// * Should be called as early as possible, so panics
+///   during the bootstrapping are reported as well.
+///
+pub fn install_hook() {
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let key = format!("crash_{}.txt", Utc::now().format("%Y%m%d_%H%M%S"));
+
+        match storage::write(&key, &create_report(info)) {
+            Ok(()) => console::log(format!(
+                "The game crashed, a report has been written to: {}",
+                storage::location(&key)
+            )),
+            Err(error) => console::log(format!(
+                "The game crashed and the report couldn't be written: {}",
+                error
+            )),
+        }
+
+        write_emergency_save();
+
+        default_hook(info);
+    }));
+}
+
+/// Writes the serialized game of the latest [CrashSnapshot] into the
+/// save game, so the run can be continued after the crash.
+///
+/// # Notes
+/// * Nothing is written if the snapshot is locked or holds no
+///   serialized game. Errors are ignored, since the game is
+///   about to exit anyway.
+///
+fn write_emergency_save() {
+    let save_game = SNAPSHOT.try_lock().ok().and_then(|snapshot| {
+        snapshot
+            .as_ref()
+            .and_then(|latest| latest.save_game.clone())
+    });
+
+    if let Some(data) = save_game {
+        if saveload::write_save_game(&data).is_ok() {
+            console::log("The game of the latest turn has been saved.");
+        }
+    }
+}
+
+/// Creates the text of the crash report for the panic
+/// described by `info` and the latest [CrashSnapshot].
+///
+/// # Arguments
+/// * `info`: The information about the panic.
+///
+/// # Notes
+/// * The snapshot is skipped if its lock is held, e.g. because
+///   the panic occurred while it was updated.
+///
+fn create_report(info: &PanicHookInfo) -> String {
+    let mut report = String::new();
+
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic".to_string());
+
+    let _ = writeln!(report, "{} {}", config::GAME_NAME, config::GAME_VERSION);
+    let _ = writeln!(report, "Panic: {}", message);
+
+    if let Some(location) = info.location() {
+        let _ = writeln!(report, "Location: {}", location);
+    }
+
+    let snapshot = SNAPSHOT
+        .try_lock()
+        .ok()
+        .and_then(|snapshot| snapshot.clone());
+
+    let snapshot = match snapshot {
+        Some(snapshot) => snapshot,
+        None => {
+            let _ = writeln!(report, "No game state available.");
+            return report;
+        }
+    };
+
+    let seed = snapshot
+        .seed
+        .map_or("unknown".to_string(), |seed| seed.to_string());
+
+    let _ = writeln!(report, "Seed: {}", seed);
+    let _ = writeln!(report, "Turn: {}", snapshot.turn);
+    let _ = writeln!(report, "Depth: {}", snapshot.depth);
+
+    if let Some((x, y)) = snapshot.player_position {
+        let _ = writeln!(report, "Player position: {}, {}", x, y);
+    }
+
+    if let Some((hp, hp_max)) = snapshot.player_health {
+        let _ = writeln!(report, "Player health: {} / {}", hp, hp_max);
+    }
+
+    let _ = writeln!(report, "Recent log:");
+
+    for line in snapshot.recent_log.iter() {
+        let _ = writeln!(report, "{}", line);
+    }
+
+    report
+}
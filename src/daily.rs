@@ -0,0 +1,251 @@
+//! Module for the daily challenge.
+//!
+//! The seed of a daily run is derived from the current date in UTC,
+//! so every player explores the same dungeon on the same day. A seed
+//! passed on the command line is ignored for daily runs. Once a daily
+//! run ends, its result is recorded on the [DailyScoreboard], which is
+//! stored apart from the save game through the [storage] module.
+//!
+//! Only the first finished daily run of a date is recorded. Whether it
+//! has been recorded is kept in the [storage] as well, since a loaded
+//! save game would otherwise allow another try at the same run.
+
+use chrono::Utc;
+use rltk::console;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use super::{rng, storage, valuation};
+use super::{Map, RunStatistics, TurnCounter};
+
+/// The key under which the [DailyScoreboard] is stored.
+const SCOREBOARD_KEY: &str = "daily_scores.json";
+
+/// The key under which the state of the latest daily run is stored,
+/// either `attempted <date>` or `recorded <date>`.
+const ATTEMPT_KEY: &str = "daily_attempt.txt";
+
+/// The maximum amount of results kept on the [DailyScoreboard],
+/// the oldest results are dropped first.
+const MAX_SCORES: usize = 100;
+
+/// Resource marking the current run as the daily challenge of a date.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyRun {
+    /// The date of the challenge, e.g. `2024-05-17`.
+    pub date: String,
+
+    /// The best score of the date on the [DailyScoreboard], once
+    /// the run has been recorded, or `None` while it's running.
+    #[serde(default)]
+    pub best_score: Option<i32>,
+}
+
+/// The result of a finished daily run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyScore {
+    /// The date of the challenge.
+    pub date: String,
+
+    /// The score of the run, see [valuation::run_score].
+    pub score: i32,
+
+    /// The depth reached in the run.
+    pub depth: i32,
+
+    /// The amount of turns the run lasted.
+    pub turns: u64,
+
+    /// The amount of defeated monsters.
+    pub kills: u32,
+}
+
+/// The results of all recorded daily runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyScoreboard {
+    /// The recorded results, from the oldest to the latest.
+    #[serde(default)]
+    pub scores: Vec<DailyScore>,
+}
+
+impl DailyScoreboard {
+    /// Reads the stored [DailyScoreboard], or returns an empty
+    /// scoreboard if none is stored or it can't be read.
+    ///
+    /// # Notes
+    /// * Errors are reported to the console.
+    ///
+    pub fn load() -> Self {
+        let data = match storage::read(SCOREBOARD_KEY) {
+            Ok(Some(data)) => data,
+            Ok(None) => return DailyScoreboard::default(),
+            Err(error) => {
+                console::log(error.to_string());
+                return DailyScoreboard::default();
+            }
+        };
+
+        serde_json::from_str(&data).unwrap_or_else(|err| {
+            console::log(format!("Unable to parse the daily scoreboard: {}", err));
+            DailyScoreboard::default()
+        })
+    }
+
+    /// Stores the [DailyScoreboard], so it's kept between sessions.
+    ///
+    /// # Notes
+    /// * Errors are reported to the console.
+    ///
+    pub fn save(&self) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|data| storage::write(SCOREBOARD_KEY, &data).map_err(|err| err.to_string()));
+
+        if let Err(message) = result {
+            console::log(format!("Unable to save the daily scoreboard: {}", message));
+        }
+    }
+
+    /// Adds the passed `score` to the scoreboard. Once the scoreboard holds
+    /// more than [MAX_SCORES] results, the oldest ones are dropped.
+    ///
+    /// # Arguments
+    /// * `score`: The result of the finished daily run.
+    ///
+    pub fn push(&mut self, score: DailyScore) {
+        self.scores.push(score);
+
+        if self.scores.len() > MAX_SCORES {
+            let excess = self.scores.len() - MAX_SCORES;
+            self.scores.drain(..excess);
+        }
+    }
+
+    /// Returns the best result recorded for the passed `date`, or
+    /// `None` if no run of the date has been recorded yet.
+    ///
+    /// # Arguments
+    /// * `date`: The date of the challenge.
+    ///
+    pub fn best(&self, date: &str) -> Option<&DailyScore> {
+        self.scores
+            .iter()
+            .filter(|score| score.date == date)
+            .max_by_key(|score| score.score)
+    }
+}
+
+/// Returns the current date in UTC, which identifies the daily challenge.
+pub fn today() -> String {
+    Utc::now().format("%Y-%m-%d").to_string()
+}
+
+/// Returns the seed of the daily challenge of the passed `date`.
+///
+/// # Arguments
+/// * `date`: The date of the challenge, see [today].
+///
+/// # Notes
+/// * The seed is the FNV-1a hash of the date, so it's identical
+///   on every platform and in every build of the game.
+///
+pub fn seed(date: &str) -> u64 {
+    date.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Marks the run, that is about to start in the passed `ecs`, as the daily
+/// challenge of today and reseeds the `rng` handler with its seed. Returns
+/// the seed of the run.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the new run is started.
+///
+/// # Notes
+/// * Must be called before the first level is built.
+/// * The date is stored as attempted, unless a run of the
+///   date has already been recorded, see [record].
+///
+pub fn start(ecs: &mut World) -> u64 {
+    let date = today();
+    let seed = rng::reseed_with(ecs, seed(&date));
+
+    if !has_state("recorded", &date) {
+        store_state("attempted", &date);
+    }
+
+    ecs.insert(DailyRun {
+        date,
+        best_score: None,
+    });
+
+    seed
+}
+
+/// Records the result of the current run on the [DailyScoreboard], if it's
+/// a daily run, whose date has been attempted, but not recorded yet.
+/// Other runs are ignored.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the finished run.
+///
+/// # Notes
+/// * Runs, in which the wizard mode has been used, are never recorded.
+///
+pub fn record(ecs: &mut World) {
+    if ecs.fetch::<RunStatistics>().wizard {
+        return;
+    }
+
+    let date = match ecs.try_fetch::<DailyRun>() {
+        Some(daily_run) => daily_run.date.clone(),
+        None => return,
+    };
+
+    if !has_state("attempted", &date) {
+        return;
+    }
+
+    let score = DailyScore {
+        date: date.clone(),
+        score: valuation::run_score(ecs),
+        depth: ecs.fetch::<Map>().depth,
+        turns: ecs.fetch::<TurnCounter>().turn,
+        kills: ecs.fetch::<RunStatistics>().kills,
+    };
+
+    let mut scoreboard = DailyScoreboard::load();
+    scoreboard.push(score);
+    scoreboard.save();
+    store_state("recorded", &date);
+
+    let best_score = scoreboard.best(&date).map(|best| best.score);
+    ecs.write_resource::<DailyRun>().best_score = best_score;
+}
+
+/// Returns `true` if the stored state of the latest daily run
+/// is the passed `state` of the `date`, `false` otherwise.
+///
+/// # Arguments
+/// * `state`: The state to check, `attempted` or `recorded`.
+/// * `date`: The date of the challenge.
+///
+fn has_state(state: &str, date: &str) -> bool {
+    matches!(storage::read(ATTEMPT_KEY), Ok(Some(data)) if data == format!("{} {}", state, date))
+}
+
+/// Stores the passed `state` of the daily run of the `date`.
+///
+/// # Arguments
+/// * `state`: The state to store, `attempted` or `recorded`.
+/// * `date`: The date of the challenge.
+///
+/// # Notes
+/// * Errors are reported to the console.
+///
+fn store_state(state: &str, date: &str) {
+    if let Err(error) = storage::write(ATTEMPT_KEY, &format!("{} {}", state, date)) {
+        console::log(format!("Unable to save the daily attempt: {}", error));
+    }
+}
@@ -1,12 +1,75 @@
 //! Module for all pod structures
 
-use super::config;
+use std::collections::{HashMap, VecDeque};
+
+use rltk::{console, Point, RGB};
+use serde::{Deserialize, Serialize};
+use specs::Entity;
+
+use super::{config, exceptions::GameError, localization, pythagoras_distance, swatch};
+
+/// Enum describing the categories
+/// of the [GameLog]'s entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogCategory {
+    /// General information and flavour text.
+    General,
+
+    /// Attacks, damage and deaths.
+    Combat,
+
+    /// Picking up, using and dropping items.
+    Item,
+
+    /// Recoverable errors of the game.
+    Error,
+}
+
+impl LogCategory {
+    /// Returns the default text color of the category.
+    pub fn color(&self) -> RGB {
+        let (fg, _) = match self {
+            LogCategory::General => swatch::LOG_GENERAL.colors(),
+            LogCategory::Combat => swatch::LOG_COMBAT.colors(),
+            LogCategory::Item => swatch::LOG_ITEM.colors(),
+            LogCategory::Error => swatch::LOG_ERROR.colors(),
+        };
+
+        fg
+    }
+}
+
+/// A single entry of the [GameLog].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// The message of the entry.
+    pub text: String,
+
+    /// The turn in which the entry was logged.
+    pub turn: u64,
+
+    /// The category of the entry.
+    pub category: LogCategory,
+
+    /// The color the entry is displayed in.
+    pub color: RGB,
+}
 
 /// Struct storing the games message stream.
+///
+/// # Notes
+/// * The stream is a ring buffer holding at most
+///   [config::MAX_LOG_ENTRIES] entries. When it is full,
+///   the oldest entry is dropped for every new one.
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameLog {
-    /// [Vec] containing the message
-    /// stream of the game.
-    pub messages: Vec<String>,
+    /// The entries of the stream,
+    /// from the oldest to the newest.
+    entries: VecDeque<LogEntry>,
+
+    /// The turn assigned to new entries.
+    turn: u64,
 }
 
 impl GameLog {
@@ -14,74 +77,164 @@ impl GameLog {
     /// it with the games name, version and an
     /// introductory message.
     pub fn new() -> Self {
-        GameLog {
-            messages: vec![
-                format!("{} {}", config::GAME_NAME, config::GAME_VERSION),
-                "You entered the dungeon...".to_string(),
-            ],
-        }
+        let mut game_log = Self::new_empty();
+
+        game_log.messages_push(&format!("{} {}", config::GAME_NAME, config::GAME_VERSION));
+        game_log.messages_push(&localization::tr("log.intro"));
+
+        game_log
     }
 
     /// Creates a new [GameLog] with an empty
     /// message stream.
     pub fn new_empty() -> Self {
         GameLog {
-            messages: Vec::new(),
+            entries: VecDeque::with_capacity(config::MAX_LOG_ENTRIES),
+            turn: 0,
         }
     }
 
-    /// Pushes the passed `message` to the [GameLog]'s message stream.
+    /// Sets the turn, that is assigned to all following entries.
+    ///
+    /// # Arguments
+    /// * `turn`: The number of the current turn.
+    ///
+    pub fn set_turn(&mut self, turn: u64) {
+        self.turn = turn;
+    }
+
+    /// Pushes a new entry with the `text` to the [GameLog]'s message
+    /// stream, displayed in the default color of its `category`.
+    ///
+    /// # Arguments
+    /// * `text`: The message of the entry.
+    /// * `category`: The [LogCategory] of the entry.
+    ///
+    pub fn push(&mut self, text: &str, category: LogCategory) {
+        self.push_colored(text, category, category.color());
+    }
+
+    /// Pushes a new entry with the `text` to the [GameLog]'s
+    /// message stream, displayed in the passed `color`.
+    ///
+    /// # Arguments
+    /// * `text`: The message of the entry.
+    /// * `category`: The [LogCategory] of the entry.
+    /// * `color`: The color the entry is displayed in.
+    ///
+    pub fn push_colored(&mut self, text: &str, category: LogCategory, color: RGB) {
+        if self.entries.len() >= config::MAX_LOG_ENTRIES {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(LogEntry {
+            text: text.to_string(),
+            turn: self.turn,
+            category,
+            color,
+        });
+    }
+
+    /// Pushes the passed `message` to the [GameLog]'s message
+    /// stream as [LogCategory::General] entry.
     ///
     /// # Arguments
     /// * `message`: The message to add to the stream.
     ///
     pub fn messages_push(&mut self, message: &str) {
-        self.messages.push(message.to_string());
+        self.push(message, LogCategory::General);
     }
 
-    /// Removes the passed `message` from the [GameLog]'s message
-    /// stream.
+    /// Reports the passed `error` by pushing its description to the
+    /// [GameLog]'s message stream and the console, so the game can
+    /// continue instead of crashing.
     ///
     /// # Arguments
-    /// * `message`: The `message` to remove.
+    /// * `error`: The [GameError] that occurred.
     ///
-    pub fn messages_remove(&mut self, message: &String) {
-        self.messages.retain(|element| element != message);
+    pub fn push_error(&mut self, error: &GameError) {
+        console::log(format!("Error: {}", error));
+        self.push(
+            &localization::tr_with("log.error", &[error]),
+            LogCategory::Error,
+        );
     }
 
     /// Removes all messages from the [GameLog]'s stream.
     pub fn clear(&mut self) {
-        self.messages.clear();
+        self.entries.clear();
+    }
+
+    /// Returns the amount of entries in the [GameLog]'s stream.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the [GameLog]'s stream has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
     }
 
-    /// Iterates through all messages in the [GameLog]'s stream
-    /// and executes the passed `block` with them.
+    /// Returns an iterator over all entries of the [GameLog]'s
+    /// stream from the oldest to the newest, e.g. for a
+    /// scrollback viewer.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LogEntry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    /// Returns an iterator over the newest `count` entries of the
+    /// [GameLog]'s stream from the newest to the oldest, e.g. for
+    /// the message box of the HUD.
     ///
     /// # Arguments
-    /// * `block`: The lambda to execute for each message.
+    /// * `count`: The maximum amount of entries to return.
     ///
-    pub fn messages_for_each_rev<F>(&mut self, mut block: F)
-    where
-        F: FnMut(&mut String),
-    {
-        for message in self.messages.iter_mut().rev() {
-            block(message)
-        }
+    pub fn iter_recent(&self, count: usize) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter().rev().take(count)
+    }
+
+    /// Returns an iterator over all entries of the passed
+    /// `category` from the oldest to the newest.
+    ///
+    /// # Arguments
+    /// * `category`: The [LogCategory] of the entries.
+    ///
+    pub fn iter_category(&self, category: LogCategory) -> impl Iterator<Item = &LogEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.category == category)
     }
 }
 
 /// Struct to store the players `click-to-move` path
 /// calculate through A*.
+///
+/// # Notes
+/// * The target of the current path and the last unreachable
+///   target are cached, so repeated clicks on the same tile
+///   don't start a new search.
+///
 pub struct PlayerPathing {
     /// [Vec] containing the pathing information for players
     /// `click-to-move` movement.
     steps: Vec<usize>,
+
+    /// Index of the tile the current path leads to.
+    target: Option<usize>,
+
+    /// The `(start, target)` indices of the last
+    /// search, that didn't find a path.
+    unreachable: Option<(usize, usize)>,
 }
 
 impl PlayerPathing {
     /// Creates a new [PlayerPathing] struct.
     pub fn new() -> Self {
-        PlayerPathing { steps: Vec::new() }
+        PlayerPathing {
+            steps: Vec::new(),
+            target: None,
+            unreachable: None,
+        }
     }
 
     /// Removes the first available entry form the player's
@@ -89,7 +242,13 @@ impl PlayerPathing {
     /// element in the pathing [Vec], the [Option] will contain
     /// [None].
     pub fn pop(&mut self) -> Option<usize> {
-        self.steps.pop()
+        let step = self.steps.pop();
+
+        if self.steps.is_empty() {
+            self.target = None;
+        }
+
+        step
     }
 
     /// Overrides the current pathing [Vec] with the new
@@ -97,15 +256,364 @@ impl PlayerPathing {
     ///
     /// # Arguments
     /// * `steps`: The new pathing information.
+    /// * `target`: Index of the tile the `steps` lead to.
     ///
-    pub fn update(&mut self, steps: &mut Vec<usize>) {
+    pub fn update(&mut self, steps: &mut Vec<usize>, target: usize) {
         self.steps.clear();
         self.steps.append(steps);
+        self.target = Some(target);
+        self.unreachable = None;
+    }
+
+    /// Returns `true` if the current path already leads to the `target`
+    /// or if the last search from the `start` to the `target` has failed.
+    ///
+    /// # Arguments
+    /// * `start`: Index of the tile the search would start at.
+    /// * `target`: Index of the tile the search would lead to.
+    ///
+    pub fn is_cached(&self, start: usize, target: usize) -> bool {
+        self.target == Some(target) || self.unreachable == Some((start, target))
+    }
+
+    /// Remembers that no path from the `start` to the `target` exists.
+    ///
+    /// # Arguments
+    /// * `start`: Index of the tile the search started at.
+    /// * `target`: Index of the tile the search should have lead to.
+    ///
+    pub fn mark_unreachable(&mut self, start: usize, target: usize) {
+        self.unreachable = Some((start, target));
     }
 
     /// Removes all pathing information from the
     /// [PlayerPathing]'s struct [Vec].
     pub fn clear(&mut self) {
         self.steps.clear();
+        self.target = None;
+        self.unreachable = None;
+    }
+
+    /// Returns `true` if the player has no
+    /// remaining steps to walk.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl Default for PlayerPathing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Resource tracking whether the player is resting, which skips
+/// turns without further input until the player has recovered.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PlayerResting {
+    /// Flag indicating if the player is resting.
+    active: bool,
+}
+
+impl PlayerResting {
+    /// Starts resting, the player skips turns from now on.
+    pub fn start(&mut self) {
+        self.active = true;
+    }
+
+    /// Stops resting, the player acts through input again.
+    pub fn stop(&mut self) {
+        self.active = false;
+    }
+
+    /// Returns `true` if the player is resting.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+/// Resource tracking the direction the player is running in, which
+/// moves the player without further input until something of interest
+/// is reached, e.g. a junction, an item or a monster.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PlayerRunning {
+    /// The `(x, y)` delta of every step, if the player is running.
+    direction: Option<(i32, i32)>,
+
+    /// The number of walkable tiles around the tile of the last
+    /// step, used to detect junctions and room entrances.
+    exits: Option<usize>,
+}
+
+impl PlayerRunning {
+    /// Starts running in the passed `direction`.
+    ///
+    /// # Arguments
+    /// * `direction`: The `(x, y)` delta of every step.
+    ///
+    pub fn start(&mut self, direction: (i32, i32)) {
+        self.direction = Some(direction);
+        self.exits = None;
+    }
+
+    /// Stops running, the player moves through input again.
+    pub fn stop(&mut self) {
+        self.direction = None;
+        self.exits = None;
+    }
+
+    /// Returns the direction the player is running in,
+    /// or `None` if the player isn't running.
+    pub fn direction(&self) -> Option<(i32, i32)> {
+        self.direction
+    }
+
+    /// Remembers the number of `exits` of the current tile and
+    /// returns `true` if it differs from the one of the last tile.
+    ///
+    /// # Arguments
+    /// * `exits`: The number of walkable tiles around the current tile.
+    ///
+    pub fn update_exits(&mut self, exits: usize) -> bool {
+        let changed = self.exits.is_some_and(|previous| previous != exits);
+        self.exits = Some(exits);
+        changed
+    }
+}
+
+/// Resource counting the turns, that have
+/// passed since the player entered the dungeon.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TurnCounter {
+    /// The number of the current turn, starting at `0`.
+    pub turn: u64,
+}
+
+impl TurnCounter {
+    /// Ends the current turn and starts the next one.
+    pub fn advance(&mut self) -> &Self {
+        self.turn += 1;
+        self
+    }
+}
+
+/// Resource ordering the entities acting in the current round. The
+/// player acts first, followed by the monsters, see the `TurnQueueSystem`.
+///
+/// # Notes
+/// * Entities are removed from the front of the queue once they have
+///   acted, so the front is always the entity whose turn is next.
+///
+#[derive(Debug, Default, Clone)]
+pub struct TurnQueue {
+    /// The number of the current round, starting at `1`
+    /// with the first round.
+    round: u64,
+
+    /// The entities, that haven't acted in the current round yet.
+    queue: VecDeque<Entity>,
+}
+
+impl TurnQueue {
+    /// Starts the next round, in which the entities act in the passed `order`.
+    ///
+    /// # Arguments
+    /// * `order`: The entities acting in the round, in the order of their turns.
+    ///
+    pub fn start_round(&mut self, order: Vec<Entity>) {
+        self.round += 1;
+        self.queue = order.into();
+    }
+
+    /// Returns the number of the current round.
+    pub fn round(&self) -> u64 {
+        self.round
+    }
+
+    /// Returns the entity whose turn is next, or
+    /// `None` if all entities have acted in the round.
+    pub fn current(&self) -> Option<Entity> {
+        self.queue.front().copied()
+    }
+
+    /// Returns `true` if it's the turn of the passed `entity`.
+    ///
+    /// # Arguments
+    /// * `entity`: The [Entity] to check.
+    ///
+    pub fn is_turn_of(&self, entity: Entity) -> bool {
+        self.current() == Some(entity)
+    }
+
+    /// Ends the turn of the passed `entity`, if it's its turn.
+    ///
+    /// # Arguments
+    /// * `entity`: The [Entity] whose turn is over.
+    ///
+    pub fn end_turn(&mut self, entity: Entity) {
+        if self.is_turn_of(entity) {
+            self.queue.pop_front();
+        }
+    }
+
+    /// Removes the entity whose turn is next from the queue and returns
+    /// it, or returns `None` if all entities have acted in the round.
+    pub fn pop(&mut self) -> Option<Entity> {
+        self.queue.pop_front()
+    }
+
+    /// Returns the entities, that haven't acted in
+    /// the current round yet, in the order of their turns.
+    pub fn upcoming(&self) -> impl Iterator<Item = &Entity> {
+        self.queue.iter()
+    }
+
+    /// Returns `true` if all entities have acted in the round.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// Resource remembering the entity, that has dealt the last damage to
+/// each entity in the current turn, so the defeat of an entity can
+/// be credited to its slayer, see the `DamageSystem`.
+///
+/// # Notes
+/// * The sources are forgotten, once the defeated entities are removed.
+///
+#[derive(Debug, Default, Clone)]
+pub struct DamageSources {
+    /// The source of the last damage taken by each damaged entity.
+    sources: HashMap<Entity, Entity>,
+}
+
+impl DamageSources {
+    /// Remembers the `source` as the last entity, that has damaged the `target`.
+    ///
+    /// # Arguments
+    /// * `target`: The damaged [Entity].
+    /// * `source`: The [Entity] dealing the damage.
+    ///
+    pub fn record(&mut self, target: Entity, source: Entity) {
+        self.sources.insert(target, source);
     }
+
+    /// Returns the entity, that has last damaged the `target` in the
+    /// current turn, or `None` if the damage had no known source.
+    ///
+    /// # Arguments
+    /// * `target`: The damaged [Entity].
+    ///
+    pub fn source_of(&self, target: Entity) -> Option<Entity> {
+        self.sources.get(&target).copied()
+    }
+
+    /// Forgets the sources of all damage.
+    pub fn clear(&mut self) {
+        self.sources.clear();
+    }
+}
+
+/// Resource collecting the loud noises made by the player since the
+/// last monster turn, e.g. by bashing a door. Monsters within the
+/// radius of a noise hear the player, see the `MonsterAI`.
+#[derive(Debug, Default, Clone)]
+pub struct Noises {
+    /// The origin and radius of every noise.
+    noises: Vec<(Point, f32)>,
+}
+
+impl Noises {
+    /// Makes a noise, that can be heard up to the `radius` around the `origin`.
+    ///
+    /// # Arguments
+    /// * `origin`: The tile the noise is made on.
+    /// * `radius`: The distance up to which the noise can be heard.
+    ///
+    pub fn emit(&mut self, origin: Point, radius: f32) {
+        self.noises.push((origin, radius));
+    }
+
+    /// Returns `true` if any of the noises can be heard on the passed `tile`.
+    ///
+    /// # Arguments
+    /// * `tile`: The tile of the listener.
+    ///
+    pub fn is_heard_at(&self, tile: &Point) -> bool {
+        self.noises
+            .iter()
+            .any(|(origin, radius)| pythagoras_distance(origin, tile) <= *radius)
+    }
+
+    /// Forgets all noises, once the monsters have heard them.
+    pub fn clear(&mut self) {
+        self.noises.clear();
+    }
+}
+
+/// Resource tracking the statistics of the current level, which are
+/// shown in the status bar and summarized once the player descends.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelStatistics {
+    /// The depth of the current level.
+    pub depth: i32,
+
+    /// The turn in which the player entered the level.
+    pub entered_turn: u64,
+
+    /// The number of monsters defeated on the level.
+    pub kills: u32,
+}
+
+impl LevelStatistics {
+    /// Creates the statistics of a newly entered level.
+    ///
+    /// # Arguments
+    /// * `depth`: The depth of the level.
+    /// * `turn`: The turn in which the player entered the level.
+    ///
+    pub fn new(depth: i32, turn: u64) -> Self {
+        LevelStatistics {
+            depth,
+            entered_turn: turn,
+            kills: 0,
+        }
+    }
+
+    /// Returns the number of turns the player has spent on the level.
+    ///
+    /// # Arguments
+    /// * `turn`: The current turn.
+    ///
+    pub fn turns_spent(&self, turn: u64) -> u64 {
+        turn.saturating_sub(self.entered_turn)
+    }
+}
+
+/// Resource tracking the statistics of the current run, which
+/// are summarized on the game over screen. The statistics are
+/// updated by the combat systems.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunStatistics {
+    /// The number of monsters defeated in the run.
+    pub kills: u32,
+
+    /// The damage the player has dealt in melee.
+    pub damage_dealt: i32,
+
+    /// The damage the player has taken in melee.
+    pub damage_taken: i32,
+
+    /// The names of the unique monsters, that have appeared in the run.
+    #[serde(default)]
+    pub uniques_spawned: Vec<String>,
+
+    /// The names of the unique monsters the player has defeated.
+    #[serde(default)]
+    pub uniques_slain: Vec<String>,
+
+    /// Flag indicating whether or not cheats of the wizard mode
+    /// have been used in the run, which is then no longer scored.
+    #[serde(default)]
+    pub wizard: bool,
 }
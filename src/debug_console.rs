@@ -0,0 +1,437 @@
+//! Module for the debug console of the wizard mode.
+//!
+//! While the [wizard] mode is enabled, the tilde key opens the console
+//! overlay, into which commands are typed to test content without
+//! recompiling the game, e.g. `spawn goblin 10 12`. Every submitted line
+//! is parsed into a [ConsoleCommand], which is executed against the
+//! [World]. Like all cheats of the wizard mode, the console isn't
+//! available in a [DailyRun] and the run is no longer scored once a
+//! command has been executed.
+
+use std::collections::VecDeque;
+
+use rltk::Point;
+use specs::prelude::*;
+
+use super::daily::DailyRun;
+use super::exceptions::{GameError, GameResult};
+use super::raws::Raws;
+use super::{config, entity_factory, localization, wizard};
+use super::{GameLog, Item, LogCategory, Map, Position, RunState, RunStatistics, Statistics};
+
+/// The maximum amount of lines kept in the output of the console.
+pub const MAX_OUTPUT_LINES: usize = 8;
+
+/// The maximum amount of characters of a typed command.
+pub const MAX_INPUT_LENGTH: usize = 60;
+
+/// Resource holding the typed input and the output of the debug console.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebugConsole {
+    /// The command the player is typing.
+    pub input: String,
+
+    /// The most recent lines of output, from
+    /// the oldest to the latest line.
+    pub output: VecDeque<String>,
+}
+
+impl DebugConsole {
+    /// Adds the passed `line` to the output, dropping the oldest
+    /// line once more than [MAX_OUTPUT_LINES] are kept.
+    ///
+    /// # Arguments
+    /// * `line`: The line to print.
+    ///
+    pub fn print(&mut self, line: String) {
+        self.output.push_back(line);
+
+        if self.output.len() > MAX_OUTPUT_LINES {
+            self.output.pop_front();
+        }
+    }
+}
+
+/// Enum describing the commands of the debug console.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleCommand {
+    /// Spawns the entity with the `name` on the `position`,
+    /// or next to the player if no position is given,
+    /// e.g. `spawn goblin 10 12`.
+    Spawn {
+        /// The name of the entity in the [Raws].
+        name: String,
+
+        /// The tile of the entity.
+        position: Option<Point>,
+    },
+
+    /// Puts the item with the `name` into the player's
+    /// backpack, e.g. `give potion`.
+    Give {
+        /// The name of the item in the [Raws].
+        name: String,
+    },
+
+    /// Builds the level of the `depth` and places the
+    /// player on it, e.g. `setdepth 3`.
+    SetDepth {
+        /// The depth of the level.
+        depth: i32,
+    },
+
+    /// Fully heals the player.
+    Heal,
+
+    /// Lists all commands.
+    Help,
+}
+
+impl ConsoleCommand {
+    /// Parses the passed `line` typed into the console and returns
+    /// the [ConsoleCommand]. The typed names are looked up in the
+    /// `raws`, see [resolve_name].
+    ///
+    /// # Arguments
+    /// * `line`: The typed line, e.g. `spawn goblin 10 12`.
+    /// * `raws`: The [Raws] defining the entities.
+    ///
+    /// # Notes
+    /// * The command and the names are case insensitive.
+    /// * Names may contain spaces, e.g. `give health potion`.
+    /// * Only depths from the town to the [config::FINAL_DEPTH]
+    ///   can be set, as no level lies below the final one.
+    ///
+    /// # Errors
+    /// * If the command is unknown, its arguments are malformed
+    ///   or no entity matches the typed name.
+    ///
+    pub fn parse(line: &str, raws: &Raws) -> GameResult<Self> {
+        let error = |message: &str| GameError::ConsoleCommand {
+            command: line.trim().to_string(),
+            message: message.to_string(),
+        };
+
+        let mut words = line.split_whitespace();
+        let keyword = words
+            .next()
+            .ok_or_else(|| error("The command is empty"))?
+            .to_ascii_lowercase();
+        let arguments = words.collect::<Vec<_>>();
+
+        match keyword.as_str() {
+            "spawn" => {
+                // The coordinates are optional and follow the name
+                let (name, position) = match arguments.as_slice() {
+                    [name @ .., x, y] if !name.is_empty() => {
+                        match (x.parse::<i32>(), y.parse::<i32>()) {
+                            (Ok(x), Ok(y)) => (name.join(" "), Some(Point::new(x, y))),
+                            _ => (arguments.join(" "), None),
+                        }
+                    }
+                    _ => (arguments.join(" "), None),
+                };
+
+                let mut names = raws.monster_names();
+                names.extend(raws.unique_names());
+                names.extend(raws.item_names());
+                names.extend(raws.npc_names());
+
+                let name = resolve_name(&names, &name)
+                    .ok_or_else(|| error("No entity matches the name"))?;
+
+                Ok(ConsoleCommand::Spawn { name, position })
+            }
+            "give" => {
+                let name = resolve_name(&raws.item_names(), &arguments.join(" "))
+                    .ok_or_else(|| error("No item matches the name"))?;
+
+                Ok(ConsoleCommand::Give { name })
+            }
+            "setdepth" => match arguments.as_slice() {
+                [depth] => match depth.parse::<i32>() {
+                    Ok(depth) if (config::TOWN_DEPTH..=config::FINAL_DEPTH).contains(&depth) => {
+                        Ok(ConsoleCommand::SetDepth { depth })
+                    }
+                    Ok(_) => Err(error("The depth lies outside of the dungeon")),
+                    Err(_) => Err(error("The depth is not a number")),
+                },
+                _ => Err(error("Expected a single depth")),
+            },
+            "heal" => Ok(ConsoleCommand::Heal),
+            "help" => Ok(ConsoleCommand::Help),
+            _ => Err(error("Unknown command, type 'help' to list all commands")),
+        }
+    }
+
+    /// Executes the command against the passed `ecs`, prints its outcome
+    /// to the [DebugConsole] and returns the next [RunState].
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] the command is executed on.
+    /// * `line`: The typed line of the command, used for errors.
+    ///
+    /// # Errors
+    /// * If the tile to spawn an entity on is out of the map or blocked.
+    ///
+    fn execute(self, ecs: &mut World, line: &str) -> GameResult<RunState> {
+        let error = |message: &str| GameError::ConsoleCommand {
+            command: line.trim().to_string(),
+            message: message.to_string(),
+        };
+
+        let output = match self {
+            ConsoleCommand::Spawn { name, position } => {
+                let tile = match position {
+                    Some(position) => {
+                        let map = ecs.fetch::<Map>();
+
+                        if !map.check_idx(position.x, position.y) {
+                            return Err(GameError::MapOutOfBounds {
+                                x: position.x,
+                                y: position.y,
+                            });
+                        }
+
+                        if map.is_tile_blocked(position.x, position.y) {
+                            return Err(error("The tile is blocked"));
+                        }
+
+                        position
+                    }
+                    None => wizard::free_tile_next_to_player(ecs)
+                        .ok_or_else(|| error("All tiles around the player are blocked"))?,
+                };
+
+                entity_factory::new_named(ecs, &name, Position::new_from_tuple((tile.x, tile.y)))
+                    .ok_or_else(|| error("The entity couldn't be created"))?;
+
+                localization::tr_with(
+                    "console.spawned",
+                    &[&localization::entity_name(&name), &tile.x, &tile.y],
+                )
+            }
+            ConsoleCommand::Give { name } => {
+                let player = *ecs.fetch::<Entity>();
+                let player_position = *ecs.fetch::<Point>();
+
+                let position = Position::new_from_tuple((player_position.x, player_position.y));
+                let item = entity_factory::new_named(ecs, &name, position)
+                    .ok_or_else(|| error("The item couldn't be created"))?;
+
+                ecs.write_storage::<Position>().remove(item);
+                Item::put_into_backpack(ecs, &player, item);
+
+                localization::tr_with("console.given", &[&localization::entity_name(&name)])
+            }
+            ConsoleCommand::SetDepth { depth } => {
+                // The next level is built one below the depth of the current map
+                ecs.write_resource::<Map>().depth = depth - 1;
+                ecs.write_resource::<RunStatistics>().wizard = true;
+                ecs.write_resource::<GameLog>().push(
+                    &localization::tr_with("console.depth", &[&depth]),
+                    LogCategory::General,
+                );
+
+                return Ok(RunState::NextLevel);
+            }
+            ConsoleCommand::Heal => {
+                let player = *ecs.fetch::<Entity>();
+
+                if let Some(statistics) = ecs.write_storage::<Statistics>().get_mut(player) {
+                    statistics.hp = statistics.hp_max;
+                }
+
+                localization::tr("console.healed")
+            }
+            ConsoleCommand::Help => {
+                let mut console = ecs.fetch_mut::<DebugConsole>();
+
+                for id in [
+                    "console.help.spawn",
+                    "console.help.give",
+                    "console.help.setdepth",
+                    "console.help.heal",
+                ] {
+                    console.print(localization::tr(id));
+                }
+
+                return Ok(RunState::DebugConsole);
+            }
+        };
+
+        ecs.write_resource::<RunStatistics>().wizard = true;
+        ecs.fetch_mut::<DebugConsole>().print(output);
+
+        Ok(RunState::DebugConsole)
+    }
+}
+
+/// Returns the name of the `names`, that matches the `typed` name, or
+/// `None` if none matches. Names, that are equal to the typed name
+/// ignoring the case, are preferred over names merely containing it.
+///
+/// # Arguments
+/// * `names`: The names of the entities in alphabetical order.
+/// * `typed`: The name typed into the console, e.g. `potion`.
+///
+fn resolve_name(names: &[String], typed: &str) -> Option<String> {
+    let typed = typed.trim().to_lowercase();
+
+    if typed.is_empty() {
+        return None;
+    }
+
+    names
+        .iter()
+        .find(|name| name.to_lowercase() == typed)
+        .or_else(|| {
+            names
+                .iter()
+                .find(|name| name.to_lowercase().contains(&typed))
+        })
+        .cloned()
+}
+
+/// Opens the debug console and returns the next [RunState]. Does
+/// nothing if the wizard mode is disabled. In a [DailyRun] the
+/// player is only told, that cheating isn't allowed.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [DebugConsole] is registered.
+///
+pub fn open(ecs: &mut World) -> RunState {
+    if !wizard::is_enabled(ecs) {
+        return RunState::AwaitingInput;
+    }
+
+    if ecs.has_value::<DailyRun>() {
+        ecs.write_resource::<GameLog>()
+            .push(&localization::tr("wizard.scored_run"), LogCategory::General);
+        return RunState::AwaitingInput;
+    }
+
+    ecs.entry::<DebugConsole>()
+        .or_insert_with(DebugConsole::default);
+
+    RunState::DebugConsole
+}
+
+/// Parses and executes the command typed into the [DebugConsole] and
+/// returns the next [RunState]. Errors are printed to the console.
+///
+/// # Arguments
+/// * `ecs`: The [World] the command is executed on.
+///
+pub fn submit(ecs: &mut World) -> RunState {
+    let line = {
+        let mut console = ecs.fetch_mut::<DebugConsole>();
+        let line = std::mem::take(&mut console.input);
+
+        console.print(format!("> {}", line));
+        line
+    };
+
+    if line.trim().is_empty() || ecs.has_value::<DailyRun>() {
+        return RunState::DebugConsole;
+    }
+
+    let command = ConsoleCommand::parse(&line, &ecs.fetch::<Raws>());
+
+    match command.and_then(|command| command.execute(ecs, &line)) {
+        Ok(run_state) => run_state,
+        Err(error) => {
+            ecs.fetch_mut::<DebugConsole>().print(error.to_string());
+            RunState::DebugConsole
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shorthand to parse the passed `line` with the embedded [Raws].
+    fn parse(line: &str) -> GameResult<ConsoleCommand> {
+        ConsoleCommand::parse(line, &Raws::load())
+    }
+
+    #[test]
+    fn parse_spawn() {
+        assert_eq!(
+            parse("spawn goblin 10 12").unwrap(),
+            ConsoleCommand::Spawn {
+                name: "Goblin".to_string(),
+                position: Some(Point::new(10, 12)),
+            }
+        );
+        assert_eq!(
+            parse("SPAWN Goblin").unwrap(),
+            ConsoleCommand::Spawn {
+                name: "Goblin".to_string(),
+                position: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_spawn_rejects_unknown_names() {
+        assert!(parse("spawn").is_err());
+        assert!(parse("spawn 10 12").is_err());
+        assert!(parse("spawn dragonlord 10 12").is_err());
+    }
+
+    #[test]
+    fn parse_give() {
+        assert_eq!(
+            parse("give health potion").unwrap(),
+            ConsoleCommand::Give {
+                name: "Health Potion".to_string(),
+            }
+        );
+        assert!(parse("give goblin").is_err());
+    }
+
+    #[test]
+    fn parse_set_depth() {
+        assert_eq!(
+            parse("setdepth 3").unwrap(),
+            ConsoleCommand::SetDepth { depth: 3 }
+        );
+        assert!(parse("setdepth").is_err());
+        assert!(parse("setdepth -1").is_err());
+        assert!(parse("setdepth 6").is_err());
+        assert!(parse("setdepth 2147483648").is_err());
+        assert!(parse("setdepth 1 2").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_empty_and_unknown_commands() {
+        assert!(parse("").is_err());
+        assert!(parse("   ").is_err());
+        assert!(parse("teleport 1 2").is_err());
+        assert_eq!(parse(" heal ").unwrap(), ConsoleCommand::Heal);
+        assert_eq!(parse("Help").unwrap(), ConsoleCommand::Help);
+    }
+
+    #[test]
+    fn resolve_name_prefers_exact_matches() {
+        let names = vec![
+            "Dire Goblin".to_string(),
+            "Goblin".to_string(),
+            "Health Potion".to_string(),
+        ];
+
+        assert_eq!(resolve_name(&names, "goblin"), Some("Goblin".to_string()));
+        assert_eq!(
+            resolve_name(&names, "dire"),
+            Some("Dire Goblin".to_string())
+        );
+        assert_eq!(
+            resolve_name(&names, " POTION "),
+            Some("Health Potion".to_string())
+        );
+        assert_eq!(resolve_name(&names, "orc"), None);
+        assert_eq!(resolve_name(&names, ""), None);
+    }
+}
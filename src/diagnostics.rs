@@ -0,0 +1,212 @@
+//! Module for measuring the performance of the game's systems.
+//!
+//! Every system registered through the [SystemDispatcherBuilder] is timed
+//! on each run and the durations are collected in the [Diagnostics]
+//! resource, next to the duration of every complete dispatch. The collected
+//! [Timing]s are shown in the performance overlay and can be written to the
+//! [GameLog], so hotspots can be measured in real games.
+//!
+//! [SystemDispatcherBuilder]: super::SystemDispatcherBuilder
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use specs::prelude::*;
+
+use super::{config, localization, GameLog, LogCategory};
+
+/// Rolling statistics over the most recent
+/// durations of a measured execution.
+#[derive(Debug, Clone, Default)]
+pub struct Timing {
+    /// The most recent durations, at most
+    /// [config::DIAGNOSTICS_SAMPLES] of them.
+    samples: VecDeque<Duration>,
+
+    /// The sum of all durations in the `samples`.
+    total: Duration,
+
+    /// The longest duration ever recorded.
+    worst: Duration,
+}
+
+impl Timing {
+    /// Adds the `duration` to the samples, dropping the
+    /// oldest sample if the window is full.
+    ///
+    /// # Arguments
+    /// * `duration`: The measured duration.
+    ///
+    pub fn record(&mut self, duration: Duration) {
+        if self.samples.len() >= config::DIAGNOSTICS_SAMPLES {
+            if let Some(oldest) = self.samples.pop_front() {
+                self.total -= oldest;
+            }
+        }
+
+        self.samples.push_back(duration);
+        self.total += duration;
+        self.worst = self.worst.max(duration);
+    }
+
+    /// Returns the average duration of the recent samples,
+    /// or [Duration::ZERO] if nothing has been recorded yet.
+    pub fn average(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+
+        self.total / self.samples.len() as u32
+    }
+
+    /// Returns the longest duration ever recorded.
+    pub fn worst(&self) -> Duration {
+        self.worst
+    }
+}
+
+/// Resource collecting the [Timing]s of all systems and dispatches.
+///
+/// # Notes
+/// * The timings are guarded by [Mutex]es, so systems running in
+///   parallel can record their durations through a shared reference
+///   without serializing each other.
+///
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    /// The timings of all systems by their registered name.
+    systems: Mutex<BTreeMap<String, Timing>>,
+
+    /// The timing of the complete dispatches.
+    frames: Mutex<Timing>,
+
+    /// Flag indicating whether or not the
+    /// performance overlay is displayed.
+    pub show_overlay: bool,
+}
+
+impl Diagnostics {
+    /// Records the `duration` of a single run of the system with the `name`.
+    ///
+    /// # Arguments
+    /// * `name`: The name under which the system is registered.
+    /// * `duration`: The duration of the run.
+    ///
+    pub fn record_system(&self, name: &str, duration: Duration) {
+        let mut systems = lock(&self.systems);
+
+        match systems.get_mut(name) {
+            Some(timing) => timing.record(duration),
+            None => {
+                let mut timing = Timing::default();
+                timing.record(duration);
+                systems.insert(name.to_string(), timing);
+            }
+        }
+    }
+
+    /// Records the `duration` of a dispatch of all systems.
+    ///
+    /// # Arguments
+    /// * `duration`: The duration of the dispatch.
+    ///
+    pub fn record_frame(&self, duration: Duration) {
+        lock(&self.frames).record(duration);
+    }
+
+    /// Returns a copy of the [Timing] of the complete dispatches.
+    pub fn frames(&self) -> Timing {
+        lock(&self.frames).clone()
+    }
+
+    /// Returns copies of the [Timing]s of all systems by their
+    /// name, sorted from the highest to the lowest average.
+    pub fn systems(&self) -> Vec<(String, Timing)> {
+        let mut systems: Vec<(String, Timing)> = lock(&self.systems)
+            .iter()
+            .map(|(name, timing)| (name.clone(), timing.clone()))
+            .collect();
+
+        systems.sort_by_key(|(_, timing)| std::cmp::Reverse(timing.average()));
+        systems
+    }
+
+    /// Returns the translated lines of the performance report,
+    /// starting with the dispatches followed by all systems.
+    pub fn report(&self) -> Vec<String> {
+        let frame = localization::tr("diagnostics.frame");
+
+        std::iter::once((frame, self.frames()))
+            .chain(self.systems())
+            .map(|(name, timing)| {
+                localization::tr_with(
+                    "diagnostics.timing",
+                    &[
+                        &name,
+                        &format_millis(timing.average()),
+                        &format_millis(timing.worst()),
+                    ],
+                )
+            })
+            .collect()
+    }
+}
+
+/// Locks the passed `mutex`, recovering the data if
+/// a system panicked while holding the lock.
+///
+/// # Arguments
+/// * `mutex`: The [Mutex] to lock.
+///
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Formats the `duration` as milliseconds with two decimals.
+///
+/// # Arguments
+/// * `duration`: The duration to format.
+///
+fn format_millis(duration: Duration) -> String {
+    format!("{:.2}", duration.as_secs_f64() * 1000.0)
+}
+
+/// Toggles the performance overlay of the [Diagnostics]
+/// registered with the passed `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Diagnostics] are registered.
+///
+pub fn toggle_overlay(ecs: &World) {
+    let mut diagnostics = ecs.fetch_mut::<Diagnostics>();
+    diagnostics.show_overlay = !diagnostics.show_overlay;
+}
+
+/// Writes the performance report of the [Diagnostics]
+/// registered with the passed `ecs` to the [GameLog].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Diagnostics] and [GameLog] are registered.
+///
+pub fn dump_to_log(ecs: &World) {
+    let report = ecs.fetch::<Diagnostics>().report();
+    let mut game_log = ecs.fetch_mut::<GameLog>();
+
+    game_log.push(&localization::tr("diagnostics.title"), LogCategory::General);
+
+    for line in report.iter() {
+        game_log.push(line, LogCategory::General);
+    }
+}
+
+/// Registers the [Diagnostics] with the passed `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Diagnostics] should be registered.
+///
+pub fn register(ecs: &mut World) {
+    ecs.insert(Diagnostics::default());
+}
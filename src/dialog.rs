@@ -2,10 +2,11 @@
 
 use std::any::Any;
 
-use rltk::{Rltk, VirtualKeyCode};
+use rltk::{ColorPair, DrawBatch, Point, Rect, Rltk, VirtualKeyCode};
 use specs::prelude::*;
 
-use super::{config, swatch, virtual_key_code_to_string};
+use super::game_config::GameConfig;
+use super::{config, localization, submit_batch, swatch, virtual_key_code_to_string};
 
 /// Enum describing all the results
 /// a [DialogInterface] can return when it is shown.
@@ -20,6 +21,10 @@ pub enum DialogResult {
     Waiting,
 }
 
+/// Type alias for the list of type erased arguments,
+/// that are passed to the callback of a [DialogOption].
+pub type DialogArgs = Vec<Box<dyn Any + Send + Sync>>;
+
 /// An option the player can select
 /// on a [DialogInterface].
 pub struct DialogOption {
@@ -32,11 +37,11 @@ pub struct DialogOption {
 
     /// List of optional arguments to pass to the callback
     /// function.
-    pub args: Vec<Box<dyn Any + Send + Sync>>,
+    pub args: DialogArgs,
 
     /// The callback function which is invoked when
     /// the player selects the option.
-    pub callback: Box<fn(&World, &mut Rltk, args: &Vec<Box<dyn Any + Send + Sync>>)>,
+    pub callback: Box<fn(&World, &mut Rltk, args: &DialogArgs)>,
 }
 
 impl DialogOption {
@@ -44,7 +49,7 @@ impl DialogOption {
     /// [DialogInterface]s that are cancelable.
     pub fn create_cancel_option() -> DialogOption {
         DialogOption {
-            description: "Dismiss".to_string(),
+            description: localization::tr("dialog.dismiss"),
             key: VirtualKeyCode::Escape,
             args: Vec::new(),
             callback: Box::new(|_, _, _| ()),
@@ -114,10 +119,11 @@ impl DialogInterface {
         ecs.insert(dialog);
     }
 
-    /// Displays the dialog on the screen.
+    /// Draws the dialog to the [config::LAYER_DIALOG] and
+    /// handles the player's input on it.
     ///
     /// # Arguments
-    /// * `terminal`: Reference to the terminal on which the dialog should be drawn.
+    /// * `terminal`: Reference to the terminal, passed on to the callbacks.
     ///
     pub fn show(&mut self, ecs: &World, terminal: &mut Rltk) -> DialogResult {
         // Calculate the width and height for the dialog
@@ -126,23 +132,34 @@ impl DialogInterface {
             Some(message) => message.len() as f32,
         };
 
-        let width = (config::MAP_WIDTH as f32 / 2.5) as i32;
+        let (viewport_width, viewport_height) = {
+            let game_config = ecs.fetch::<GameConfig>();
+            (game_config.viewport_width(), game_config.viewport_height())
+        };
+
+        let width = (viewport_width as f32 / 2.5) as i32;
         let mut height = (message_length / width as f32).ceil() as i32;
         height += (self.options.len() * 2) as i32 + 3;
 
         // Calculate the x and y coordinate for the dialog
-        let x = (config::MAP_WIDTH / 2) - (width / 2);
-        let y = (config::MAP_HEIGHT / 2) - (height / 2);
+        let x = (viewport_width / 2) - (width / 2);
+        let y = (viewport_height / 2) - (height / 2);
+
+        let mut batch = DrawBatch::new();
 
         let (fg, bg) = swatch::DIALOG_FRAME.colors();
 
         // Draw the dialog's box
-        terminal.draw_box(x, y, width, height, fg, bg);
+        batch.draw_box(Rect::with_size(x, y, width, height), ColorPair::new(fg, bg));
 
         let (fg, bg) = swatch::DIALOG_TITLE.colors();
 
         // Draw the dialog's title
-        terminal.print_color(x + 2, y, fg, bg, &format!("{}", self.title));
+        batch.print_color(
+            Point::new(x + 2, y),
+            self.title.to_string(),
+            ColorPair::new(fg, bg),
+        );
 
         let mut y_position = y + 2;
 
@@ -156,7 +173,7 @@ impl DialogInterface {
                 .collect::<Vec<String>>();
 
             for chunk in message_chunks {
-                terminal.print(x + 2, y_position, chunk);
+                batch.print(Point::new(x + 2, y_position), chunk);
                 y_position += 1;
             }
         }
@@ -164,16 +181,15 @@ impl DialogInterface {
         y_position += 1;
 
         let (fg, bg) = swatch::DIALOG_OPTION.colors();
+        let first_option_row = y_position;
 
         // Draw the dialog's options
         for (_, option) in self.options.iter().enumerate() {
             let key_string = virtual_key_code_to_string(option.key);
-            terminal.print_color(
-                x + 2,
-                y_position,
-                fg,
-                bg,
-                &format!("{} - {}", key_string, option.description),
+            batch.print_color(
+                Point::new(x + 2, y_position),
+                format!("{} - {}", key_string, option.description),
+                ColorPair::new(fg, bg),
             );
 
             y_position += 2;
@@ -184,17 +200,37 @@ impl DialogInterface {
         if self.cancelable {
             let (fg, bg) = swatch::DIALOG_DISMISS_BUTTON.colors();
 
-            terminal.print_color(
-                x + 2,
-                y + height,
-                fg,
-                bg,
-                &format!("{} - {}", "ESCAPE", "Dismiss"),
-            )
+            batch.print_color(
+                Point::new(x + 2, y + height),
+                format!("{} - {}", "ESCAPE", localization::tr("dialog.dismiss")),
+                ColorPair::new(fg, bg),
+            );
         }
 
+        submit_batch(&mut batch, config::LAYER_DIALOG);
+
+        // A tap or click selects the option in its row, anywhere
+        // else it dismisses the dialog, if it's cancelable.
+        let key = if terminal.left_click {
+            let row = terminal.mouse_point().y - first_option_row;
+
+            let tapped_option = if row >= 0 && row % 2 == 0 {
+                self.options.get((row / 2) as usize)
+            } else {
+                None
+            };
+
+            match tapped_option {
+                Some(option) => Some(option.key),
+                None if self.cancelable => Some(VirtualKeyCode::Escape),
+                None => None,
+            }
+        } else {
+            terminal.key
+        };
+
         // Listen for key press event
-        if let Some(key) = terminal.key {
+        if let Some(key) = key {
             let selection = self.options.iter_mut().find(|element| element.key == key);
 
             if let Some(option) = selection {
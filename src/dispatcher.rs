@@ -0,0 +1,182 @@
+//! Module for the scheduling and execution of the game's systems.
+
+use instant::Instant;
+use specs::prelude::*;
+
+use super::diagnostics::Diagnostics;
+
+/// Wrapper around a system, that records the duration
+/// of every run in the [Diagnostics] resource.
+struct TimedSystem<S> {
+    /// The wrapped system.
+    system: S,
+
+    /// The name under which the system is registered.
+    name: String,
+}
+
+impl<'a, S> System<'a> for TimedSystem<S>
+where
+    S: System<'a>,
+    S::SystemData: SystemData<'a>,
+{
+    type SystemData = (S::SystemData, Read<'a, Diagnostics>);
+
+    fn setup(&mut self, world: &mut World) {
+        <Read<Diagnostics> as SystemData>::setup(world);
+        self.system.setup(world);
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (data, diagnostics) = data;
+        let start = Instant::now();
+
+        self.system.run(data);
+
+        diagnostics.record_system(&self.name, start.elapsed());
+    }
+}
+
+/// Builder to register all systems of the game with their
+/// dependencies and create a [SystemDispatcher] from them.
+///
+/// # Notes
+/// * On native targets the systems are scheduled through a
+///   [specs::Dispatcher], which executes independent systems
+///   in parallel.
+/// * The `wasm32` target doesn't support threads, so the systems
+///   are executed one after another in the order of their registration.
+///   Dependencies therefore always have to be registered first.
+///
+pub struct SystemDispatcherBuilder {
+    /// The wrapped [DispatcherBuilder].
+    #[cfg(not(target_arch = "wasm32"))]
+    builder: DispatcherBuilder<'static, 'static>,
+
+    /// The registered systems in the order of their registration.
+    #[cfg(target_arch = "wasm32")]
+    systems: Vec<Box<dyn for<'a> RunNow<'a>>>,
+}
+
+impl SystemDispatcherBuilder {
+    /// Creates a new [SystemDispatcherBuilder] without any systems.
+    pub fn new() -> Self {
+        SystemDispatcherBuilder {
+            #[cfg(not(target_arch = "wasm32"))]
+            builder: DispatcherBuilder::new(),
+
+            #[cfg(target_arch = "wasm32")]
+            systems: Vec::new(),
+        }
+    }
+
+    /// Registers the passed `system` under the given `name`. The `system`
+    /// is only executed after all systems named in `dependencies` have run.
+    ///
+    /// # Arguments
+    /// * `system`: The system to register.
+    /// * `name`: The unique name of the system, used to reference it as a dependency.
+    /// * `dependencies`: The names of the systems that must run before the `system`.
+    ///
+    /// # Notes
+    /// * The duration of every run of the `system` is
+    ///   recorded in the [Diagnostics] under its `name`.
+    ///
+    /// # Panics
+    /// * If the `name` is already taken or a dependency isn't registered yet.
+    ///
+    pub fn with<S>(mut self, system: S, name: &str, dependencies: &[&str]) -> Self
+    where
+        S: for<'a> System<'a> + Send + 'static,
+        for<'a> <S as System<'a>>::SystemData: SystemData<'a>,
+    {
+        let system = TimedSystem {
+            system,
+            name: name.to_string(),
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.builder.add(system, name, dependencies);
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = dependencies;
+            self.systems.push(Box::new(system));
+        }
+
+        self
+    }
+
+    /// Consumes the builder and creates the [SystemDispatcher]
+    /// with all registered systems.
+    pub fn build(self) -> SystemDispatcher {
+        SystemDispatcher {
+            #[cfg(not(target_arch = "wasm32"))]
+            dispatcher: self.builder.build(),
+
+            #[cfg(target_arch = "wasm32")]
+            systems: self.systems,
+        }
+    }
+}
+
+impl Default for SystemDispatcherBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Executes all systems registered through
+/// a [SystemDispatcherBuilder].
+pub struct SystemDispatcher {
+    /// The wrapped [Dispatcher].
+    #[cfg(not(target_arch = "wasm32"))]
+    dispatcher: Dispatcher<'static, 'static>,
+
+    /// The systems in the order of their execution.
+    #[cfg(target_arch = "wasm32")]
+    systems: Vec<Box<dyn for<'a> RunNow<'a>>>,
+}
+
+impl SystemDispatcher {
+    /// Sets up the passed `ecs` for the execution of the systems by
+    /// registering all storages and default resources they require.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] the systems will be executed on.
+    ///
+    pub fn setup(&mut self, ecs: &mut World) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.dispatcher.setup(ecs);
+
+        #[cfg(target_arch = "wasm32")]
+        for system in self.systems.iter_mut() {
+            system.setup(ecs);
+        }
+    }
+
+    /// Executes all systems once on the passed `ecs` and records
+    /// the duration of the dispatch in the [Diagnostics].
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] the systems should be executed on.
+    ///
+    /// # Panics
+    /// * If any resource of the `ecs` is still borrowed.
+    ///
+    pub fn dispatch(&mut self, ecs: &World) {
+        let start = Instant::now();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.dispatcher.dispatch(ecs);
+
+        #[cfg(target_arch = "wasm32")]
+        for system in self.systems.iter_mut() {
+            system.run_now(ecs);
+        }
+
+        if let Some(diagnostics) = ecs.try_fetch::<Diagnostics>() {
+            diagnostics.record_frame(start.elapsed());
+        }
+    }
+}
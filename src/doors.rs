@@ -0,0 +1,256 @@
+//! Module for the doors closing off the rooms of the dungeon.
+//!
+//! Doors are placed in the doorways of the rooms while a level is built,
+//! see the [DoorBuilder](super::map_builders::DoorBuilder). Closed doors
+//! have [Collision] and are [Opaque], the player opens them by bumping
+//! into them. The player and strong monsters, see
+//! [config::DOOR_BASH_MIN_POWER], can also bash doors in with a strength
+//! check, which leaves a [TileType::BROKEN_DOOR] behind. Bashing a door
+//! is loud and alerts the monsters around the player, see [Noises].
+//!
+//! Some doors are locked, they only open for a player carrying their
+//! [Key], which is placed somewhere else on the level, see [bump].
+
+use rltk::{Point, RandomNumberGenerator, VirtualKeyCode};
+use specs::prelude::*;
+use specs::world::EntitiesRes;
+
+use super::{config, localization, swatch};
+use super::{
+    Collision, DialogArgs, DialogInterface, DialogOption, Door, Facing, GameLog, Key, LogCategory,
+    Loot, Map, Name, Noises, Opaque, Position, Renderable, Statistics, TileType, FOV,
+};
+
+/// Returns `true` if a basher with the passed `power` breaks a door, i.e.
+/// a roll of a d20 plus the `power` reaches [config::DOOR_BASH_DIFFICULTY].
+///
+/// # Arguments
+/// * `rng`: The [RandomNumberGenerator] rolling the check.
+/// * `power`: The power of the basher.
+///
+pub fn bash_check(rng: &mut RandomNumberGenerator, power: i32) -> bool {
+    rng.roll_dice(1, 20) + power >= config::DOOR_BASH_DIFFICULTY
+}
+
+/// Breaks the passed `door` on the tile at the `x` and `y` coordinates.
+/// The door is deleted and the tile turned into a [TileType::BROKEN_DOOR].
+///
+/// # Arguments
+/// * `map`: The [Map] containing the door.
+/// * `entities`: All entities of the `ecs`.
+/// * `door`: The [Door] to break.
+/// * `x`: X coordinate of the door.
+/// * `y`: Y coordinate of the door.
+///
+/// # Notes
+/// * The [FOV]s seeing through the doorway have to be marked as dirty
+///   by the caller, see [mark_fovs_as_dirty].
+///
+pub fn break_door(map: &mut Map, entities: &EntitiesRes, door: Entity, x: i32, y: i32) {
+    let _ = entities.delete(door);
+    map.set_tile(x, y, TileType::BROKEN_DOOR);
+}
+
+/// Marks the [FOV]s of all entities as dirty, e.g. after
+/// a door has been opened or broken.
+///
+/// # Arguments
+/// * `fovs`: The [FOV] storage of the `ecs`.
+///
+pub fn mark_fovs_as_dirty(fovs: &mut WriteStorage<FOV>) {
+    for fov in fovs.join() {
+        fov.mark_as_dirty();
+    }
+}
+
+/// Opens the passed closed `door`, so it can be walked
+/// and seen through.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the door.
+/// * `door`: The [Door] to open.
+///
+pub fn open(ecs: &World, door: Entity) {
+    if let Some(door_state) = ecs.write_storage::<Door>().get_mut(door) {
+        door_state.open = true;
+    }
+
+    if let Some(renderable) = ecs.write_storage::<Renderable>().get_mut(door) {
+        renderable.symbol = rltk::to_cp437('/');
+    }
+
+    ecs.write_storage::<Collision>().remove(door);
+    ecs.write_storage::<Opaque>().remove(door);
+    mark_fovs_as_dirty(&mut ecs.write_storage::<FOV>());
+}
+
+/// Handles the player bumping into the passed closed `door`. Unlocked
+/// doors are opened right away. For a locked door the player is offered
+/// to unlock it through a [DialogInterface], if the player carries its
+/// [Key], otherwise the player is told, that the door is locked.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the door.
+/// * `door`: The closed [Door] the player has bumped into.
+///
+pub fn bump(ecs: &mut World, door: Entity) {
+    let is_locked = ecs
+        .read_storage::<Door>()
+        .get(door)
+        .is_some_and(|door| door.locked);
+
+    if !is_locked {
+        open(ecs, door);
+        return;
+    }
+
+    let key = {
+        let player = *ecs.fetch::<Entity>();
+        let depth = ecs.fetch::<Map>().depth;
+        let entities = ecs.entities();
+        let positions = ecs.read_storage::<Position>();
+        let keys = ecs.read_storage::<Key>();
+        let backpack = ecs.read_storage::<Loot>();
+
+        positions.get(door).and_then(|position| {
+            (&entities, &keys, &backpack)
+                .join()
+                .find(|(_, key, loot)| {
+                    loot.owner == player
+                        && key.depth == depth
+                        && key.x == position.x
+                        && key.y == position.y
+                })
+                .map(|(key, _, _)| key)
+        })
+    };
+
+    let key = match key {
+        Some(key) => key,
+        None => {
+            ecs.write_resource::<GameLog>()
+                .push(&localization::tr("door.locked"), LogCategory::General);
+            return;
+        }
+    };
+
+    let name = ecs
+        .read_storage::<Name>()
+        .get(door)
+        .map(Name::display)
+        .unwrap_or_default();
+
+    DialogInterface::register_dialog(
+        ecs,
+        name,
+        Some(localization::tr("door.unlock_prompt")),
+        vec![DialogOption {
+            description: localization::tr("door.unlock"),
+            key: VirtualKeyCode::U,
+            args: vec![Box::new(door), Box::new(key)],
+            callback: Box::new(|world, _, args| unlock(world, args)),
+        }],
+        true,
+    );
+}
+
+/// Callback of the unlock [DialogOption], which unlocks and opens
+/// the door. The key is used up in the process.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the door and the key.
+/// * `args`: The [Entity] of the door, followed by the [Entity] of the key.
+///
+fn unlock(ecs: &World, args: &DialogArgs) {
+    let mut entities = args.iter().filter_map(|arg| arg.downcast_ref::<Entity>());
+
+    let (door, key) = match (entities.next(), entities.next()) {
+        (Some(door), Some(key)) => (*door, *key),
+        _ => return,
+    };
+
+    if let Some(door_state) = ecs.write_storage::<Door>().get_mut(door) {
+        door_state.locked = false;
+    }
+
+    if let Some(renderable) = ecs.write_storage::<Renderable>().get_mut(door) {
+        renderable.fg = swatch::DOOR.colors().0;
+    }
+
+    let _ = ecs.entities().delete(key);
+    open(ecs, door);
+
+    ecs.write_resource::<GameLog>()
+        .push(&localization::tr("door.unlocked"), LogCategory::General);
+}
+
+/// Lets the player bash the closed door next to it. Returns `true`
+/// if there was a door to bash, which takes the player's turn.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the doors.
+///
+/// # Notes
+/// * If the player stands next to several closed doors, the first
+///   one in the order of the [Facing::DIRECTIONS] is bashed.
+/// * The noise of the bash can be heard up to the
+///   [config::DOOR_BASH_NOISE_RADIUS], whether or not it succeeds.
+///
+pub fn bash_adjacent(ecs: &mut World) -> bool {
+    let player = *ecs.fetch::<Entity>();
+    let origin = *ecs.fetch::<Point>();
+
+    let target = {
+        let map = ecs.fetch::<Map>();
+        let doors = ecs.read_storage::<Door>();
+
+        Facing::DIRECTIONS
+            .iter()
+            .map(|direction| (origin.x + direction.x, origin.y + direction.y))
+            .filter(|&(x, y)| map.check_idx(x, y))
+            .find_map(|(x, y)| {
+                map.tile_contents_get(x, y)
+                    .find(|entity| doors.get(*entity).is_some_and(|door| !door.open))
+                    .map(|door| (door, x, y))
+            })
+    };
+
+    let (door, x, y) = match target {
+        Some(target) => target,
+        None => {
+            ecs.write_resource::<GameLog>().push(
+                &localization::tr("door.nothing_to_bash"),
+                LogCategory::General,
+            );
+            return false;
+        }
+    };
+
+    let power = ecs
+        .read_storage::<Statistics>()
+        .get(player)
+        .map_or(0, |statistic| statistic.power);
+
+    ecs.write_resource::<Noises>()
+        .emit(Point::new(x, y), config::DOOR_BASH_NOISE_RADIUS);
+
+    if !bash_check(&mut ecs.write_resource::<RandomNumberGenerator>(), power) {
+        ecs.write_resource::<GameLog>()
+            .push(&localization::tr("door.bash_failed"), LogCategory::General);
+        return true;
+    }
+
+    break_door(
+        &mut ecs.write_resource::<Map>(),
+        &ecs.entities(),
+        door,
+        x,
+        y,
+    );
+    mark_fovs_as_dirty(&mut ecs.write_storage::<FOV>());
+
+    ecs.write_resource::<GameLog>()
+        .push(&localization::tr("door.bashed"), LogCategory::General);
+
+    true
+}
@@ -1,9 +1,17 @@
 //! Factory to create entities
 
+use rltk::RGB;
 use specs::prelude::*;
+use specs::saveload::MarkedBuilder;
 
+use super::fov::FovAlgorithm;
+use super::raws::{ItemRaw, MonsterRaw, NpcRaw, Raws, UniqueRaw};
+use super::saveload::SaveMarker;
 use super::{
-    rng, swatch, Collision, Item, Monster, Name, Player, Position, Potion, Renderable, Statistics,
+    config, rng, swatch, Ammunition, AreaOfEffect, Boss, Charisma, Collision, Container, Cursed,
+    Door, Experience, ExperienceReward, Facing, Faction, Friendly, Invisible, Item, Key,
+    LightSource, Loot, Mana, Monster, Name, Opaque, Player, Position, Purse, Ranged, Regenerates,
+    Renderable, Scripted, Shopkeeper, Spellbook, Stackable, Statistics, Unique, Value, Weight, AI,
     FOV,
 };
 
@@ -29,10 +37,16 @@ pub fn new_player(position: &Position, ecs: &mut World) -> Entity {
             order: 0,
         })
         .with(Player {})
+        .with(Faction::new(config::PLAYER_FACTION))
+        .with(LightSource {
+            range: config::PLAYER_LIGHT_RANGE,
+            color: RGB::named(config::PLAYER_LIGHT_COLOR),
+        })
         .with(FOV {
             content: Vec::new(),
             range: 8,
             is_dirty: true,
+            algorithm: FovAlgorithm::default(),
         })
         .with(Name {
             name: "Rouge".to_string(),
@@ -43,140 +57,417 @@ pub fn new_player(position: &Position, ecs: &mut World) -> Entity {
             power: 5,
             defense: 3,
         })
+        .with(Experience { level: 1, xp: 0 })
+        .with(Mana { mp_max: 10, mp: 10 })
+        .with(Purse {
+            gold: config::STARTING_GOLD,
+        })
+        .with(Charisma { value: 0 })
+        .with(Spellbook {
+            spells: vec!["Magic Missile".to_string(), "Mend".to_string()],
+        })
+        .marked::<SaveMarker>()
         .build()
 }
 
-/// Creates a new goblin entity through the `ecs`, puts it at
-/// the passed `position` and returns it.
+/// Creates the entity with the passed `name` at the `position` in the
+/// `ecs` and returns it. Used to spawn the entries of the spawn tables.
 ///
 /// # Arguments
-/// * `position`: The x and y coordinates at which the goblin should be placed at.
-/// * `ecs`: The `ecs` through which the goblin should be created.
-/// * `suffix`: Optional suffix that can be added to the monsters name.
+/// * `ecs`: The [World] in which the entity should be created.
+/// * `name`: The name of the entity, e.g. `"Goblin"`.
+/// * `position`: The [Position] at which the entity should be placed.
 ///
-pub fn new_goblin(ecs: &mut World, position: Position, suffix: Option<String>) -> Entity {
-    let name = Name {
-        name: format!("{}{}", "Goblin", suffix.unwrap_or(String::default())),
+/// # Notes
+/// * The entity is created from its definition in the [Raws].
+/// * Returns `None` if no entity with the `name` is defined.
+/// * Whether a unique monster has already appeared in the run isn't
+///   checked, see [spawn_unique](super::spawn_controller::spawn_unique).
+///
+pub fn new_named(ecs: &mut World, name: &str, position: Position) -> Option<Entity> {
+    let (monster, item, npc, unique) = {
+        let raws = ecs.fetch::<Raws>();
+        (
+            raws.monster(name).cloned(),
+            raws.item(name).cloned(),
+            raws.npc(name).cloned(),
+            raws.unique(name).cloned(),
+        )
     };
 
-    let (fg, bg) = swatch::GOBLIN.colors();
-    let renderable = Renderable {
-        symbol: rltk::to_cp437('o'),
-        fg,
-        bg,
-        order: 1,
-    };
+    match (monster, item, npc, unique) {
+        (Some(monster), _, _, _) => Some(new_monster(ecs, &monster, position)),
+        (None, Some(item), _, _) => Some(new_item(ecs, &item, position)),
+        (None, None, Some(npc), _) => Some(new_npc(ecs, &npc, position)),
+        (None, None, None, Some(unique)) => new_unique(ecs, &unique, position),
+        (None, None, None, None) => None,
+    }
+}
+
+/// Creates a new unique monster from the passed definition at the
+/// `position` in the `ecs` and returns it. The unique is created
+/// as its base monster, whose name, look and stats are replaced.
+///
+/// # Arguments
+/// * `ecs`: The [World] the unique should be added to.
+/// * `raw`: The [UniqueRaw] definition of the unique.
+/// * `position`: The [Position] of the unique in the world.
+///
+/// # Notes
+/// * Returns `None` if the base monster isn't defined.
+///
+fn new_unique(ecs: &mut World, raw: &UniqueRaw, position: Position) -> Option<Entity> {
+    let base = ecs.fetch::<Raws>().monster(&raw.base).cloned()?;
+    let unique = new_monster(ecs, &base, position);
+
+    let _ = ecs.write_storage::<Name>().insert(
+        unique,
+        Name {
+            name: raw.name.clone(),
+        },
+    );
+    let _ = ecs
+        .write_storage::<Renderable>()
+        .insert(unique, raw.to_renderable(&base.renderable));
+    let _ = ecs.write_storage::<Unique>().insert(unique, Unique {});
 
-    let statistic = Statistics {
-        hp_max: 10,
-        hp: 10,
-        power: 2,
-        defense: 1,
+    if let Some(statistic) = ecs.write_storage::<Statistics>().get_mut(unique) {
+        statistic.hp_max += raw.bonus.hp;
+        statistic.hp = statistic.hp_max;
+        statistic.power += raw.bonus.power;
+        statistic.defense += raw.bonus.defense;
+    }
+
+    if let Some(reward) = ecs.write_storage::<ExperienceReward>().get_mut(unique) {
+        reward.xp += raw.xp;
+    }
+
+    Some(unique)
+}
+
+/// Creates a new monster from the passed definition at
+/// the `position` in the `ecs` and returns it.
+///
+/// # Arguments
+/// * `ecs`: The [World] the monster should be added to.
+/// * `raw`: The [MonsterRaw] definition of the monster.
+/// * `position`: The [Position] of the monster in the world.
+///
+/// # Notes
+/// * The monster faces a random direction.
+///
+fn new_monster(ecs: &mut World, raw: &MonsterRaw, position: Position) -> Entity {
+    let direction = rng::range(ecs, 0, Facing::DIRECTIONS.len() as i32);
+    let facing = Facing {
+        direction: Facing::DIRECTIONS[direction as usize],
     };
 
-    new_monster(ecs, name, renderable, statistic, position)
+    let mut builder = ecs
+        .create_entity()
+        .with(position)
+        .with(raw.renderable.to_renderable())
+        .with(Name {
+            name: raw.name.clone(),
+        })
+        .with(Statistics {
+            hp_max: raw.stats.hp,
+            hp: raw.stats.hp,
+            power: raw.stats.power,
+            defense: raw.stats.defense,
+        })
+        .with(ExperienceReward { xp: raw.xp })
+        .with(FOV {
+            content: Vec::new(),
+            range: raw.vision_range,
+            is_dirty: true,
+            algorithm: FovAlgorithm::default(),
+        })
+        .with(facing)
+        .with(Monster {})
+        .with(Faction::new(&raw.faction))
+        .with(AI {
+            cowardly: raw.cowardly,
+            ..AI::new(raw.behavior)
+        })
+        .with(Collision {});
+
+    if let Some(inflicts) = raw.inflicts {
+        builder = builder.with(inflicts);
+    }
+
+    if let Some(per_turn) = raw.regenerates {
+        builder = builder.with(Regenerates {
+            per_turn,
+            scorched: 0,
+        });
+    }
+
+    if raw.invisible {
+        builder = builder.with(Invisible {});
+    }
+
+    if raw.boss {
+        builder = builder.with(Boss {});
+    }
+
+    if let Some(light) = &raw.light {
+        builder = builder.with(light.to_light_source());
+    }
+
+    builder.marked::<SaveMarker>().build()
 }
 
-/// Creates a new gremlin entity through the `ecs`, puts it at
-/// the passed `position` and returns it.
+/// Creates a new item from the passed definition at
+/// the `position` in the `ecs` and returns it.
 ///
 /// # Arguments
-/// * `position`: The x and y coordinates at which the gremlin should be placed at.
-/// * `ecs`: The `ecs` through which the gremlin should be created.
-/// * `suffix`: Optional suffix that can be added to the monsters name.
+/// * `ecs`: The [World] the item should be added to.
+/// * `raw`: The [ItemRaw] definition of the item.
+/// * `position`: The [Position] of the item in the world.
 ///
-pub fn new_gremlin(ecs: &mut World, position: Position, suffix: Option<String>) -> Entity {
-    let (fg, bg) = swatch::GREMLIN.colors();
+fn new_item(ecs: &mut World, raw: &ItemRaw, position: Position) -> Entity {
+    let mut builder = ecs
+        .create_entity()
+        .with(position)
+        .with(raw.renderable.to_renderable())
+        .with(Name {
+            name: raw.name.clone(),
+        })
+        .with(Item {});
+
+    if let Some(script) = &raw.script {
+        builder = builder.with(Scripted {
+            script: script.clone(),
+        });
+    }
+
+    if let Some(range) = raw.range {
+        builder = builder.with(Ranged { range });
+    }
+
+    if let Some(radius) = raw.radius {
+        builder = builder.with(AreaOfEffect { radius });
+    }
+
+    if let Some(equippable) = &raw.equippable {
+        builder = builder.with(equippable.to_equippable());
+    }
+
+    if raw.cursed {
+        builder = builder.with(Cursed { revealed: false });
+    }
+
+    if raw.price > 0 {
+        builder = builder.with(Value {
+            base_price: raw.price,
+        });
+    }
 
-    let name = Name {
-        name: format!("{}{}", "Gremlin", suffix.unwrap_or(String::default())),
+    if raw.stackable {
+        builder = builder.with(Stackable {
+            quantity: raw.quantity.max(1),
+        });
+    }
+
+    if let Some(kind) = raw.ammunition {
+        builder = builder.with(Ammunition { kind });
+    }
+
+    if let Some(weapon) = &raw.ranged_weapon {
+        builder = builder.with(weapon.to_ranged_weapon());
+    }
+
+    if raw.weight > 0 {
+        builder = builder.with(Weight { value: raw.weight });
+    }
+
+    if let Some(light) = &raw.light {
+        builder = builder.with(light.to_light_source());
+    }
+
+    builder.marked::<SaveMarker>().build()
+}
+
+/// Takes a single item off the passed `stack` and returns it. The item is
+/// created anew from its definition and belongs to the owner of the stack,
+/// it has no [Position]. Returns the `stack` itself, if it holds only a
+/// single item.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the stack.
+/// * `stack`: The [Stackable] item to split.
+///
+pub fn split_stack(ecs: &mut World, stack: Entity) -> Entity {
+    let name = {
+        let stackables = ecs.read_storage::<Stackable>();
+        let names = ecs.read_storage::<Name>();
+
+        match (stackables.get(stack), names.get(stack)) {
+            (Some(stackable), Some(name)) if stackable.quantity > 1 => name.name.clone(),
+            _ => return stack,
+        }
     };
 
-    let renderable = Renderable {
-        symbol: rltk::to_cp437('g'),
-        fg,
-        bg,
-        order: 1,
+    let item = match new_named(ecs, &name, Position { x: 0, y: 0 }) {
+        Some(item) => item,
+        None => return stack,
     };
 
-    let statistic = Statistics {
-        hp_max: 16,
-        hp: 16,
-        power: 4,
-        defense: 2,
+    ecs.write_storage::<Position>().remove(item);
+
+    // Items created as a bundle start with more than one item
+    if let Some(stackable) = ecs.write_storage::<Stackable>().get_mut(item) {
+        stackable.quantity = 1;
+    }
+
+    let owner = ecs.read_storage::<Loot>().get(stack).map(|loot| loot.owner);
+
+    if let Some(owner) = owner {
+        let _ = ecs.write_storage::<Loot>().insert(item, Loot { owner });
+    }
+
+    if let Some(stackable) = ecs.write_storage::<Stackable>().get_mut(stack) {
+        stackable.quantity -= 1;
+    }
+
+    item
+}
+
+/// Creates a new closed door at the passed `position`
+/// in the `ecs` and returns it.
+///
+/// # Arguments
+/// * `ecs`: The [World] the door should be added to.
+/// * `position`: The [Position] of the door in the world.
+/// * `locked`: Flag indicating whether or not the door is locked.
+///
+pub fn new_door(ecs: &mut World, position: Position, locked: bool) -> Entity {
+    let (fg, bg) = if locked {
+        swatch::LOCKED_DOOR.colors()
+    } else {
+        swatch::DOOR.colors()
     };
 
-    new_monster(ecs, name, renderable, statistic, position)
+    ecs.create_entity()
+        .with(position)
+        .with(Renderable {
+            symbol: rltk::to_cp437('+'),
+            fg,
+            bg,
+            order: 3,
+        })
+        .with(Name {
+            name: "Door".to_string(),
+        })
+        .with(Door {
+            open: false,
+            locked,
+        })
+        .with(Collision {})
+        .with(Opaque {})
+        .marked::<SaveMarker>()
+        .build()
 }
 
-/// Creates a new [Potion] entity at the supplied `position` in the passed `ecs`.
+/// Creates a new chest holding the passed `contents` at the
+/// `position` in the `ecs` and returns it.
 ///
 /// # Arguments
-/// * `ecs`: The [World] in which the `potion` should be created.
-/// * `position`: The [Position] at which the potion should be placed.
+/// * `ecs`: The [World] the chest should be added to.
+/// * `position`: The [Position] of the chest in the world.
+/// * `contents`: The items in the chest, which must
+///   have neither a [Position] nor an owner.
 ///
-pub fn new_health_potion(ecs: &mut World, position: Position) -> Entity {
-    let (fg, bg) = swatch::HEALTH_POTION.colors();
+pub fn new_chest(ecs: &mut World, position: Position, contents: Vec<Entity>) -> Entity {
+    let (fg, bg) = swatch::CHEST.colors();
 
     ecs.create_entity()
         .with(position)
         .with(Renderable {
-            symbol: rltk::to_cp437('!'),
+            symbol: rltk::to_cp437('≡'),
             fg,
             bg,
-            order: 2,
+            order: 3,
         })
         .with(Name {
-            name: "Health Potion".to_string(),
+            name: "Chest".to_string(),
         })
-        .with(Item {})
-        .with(Potion { healing_amount: 8 })
+        .with(Container { contents })
+        .with(Collision {})
+        .marked::<SaveMarker>()
         .build()
 }
 
-/// Creates a random monster in the `ecs` at the passed `position`.
+/// Creates a new key at the passed `position` in the `ecs`, which
+/// unlocks the door at the `door` position on the `depth`, and returns it.
+///
+/// # Arguments
+/// * `ecs`: The [World] the key should be added to.
+/// * `position`: The [Position] of the key in the world.
+/// * `depth`: The depth of the level containing the door.
+/// * `door`: The [Position] of the door.
 ///
-/// * Arguments
-/// * `ecs`: The [World] in which the monster should be created.
-/// * `position`: The [Position] at which the monster should be placed.
+/// # Notes
+/// * The key is created from the `"Key"` item definition in the [Raws].
+/// * Returns `None` if the item isn't defined.
 ///
-pub fn random_monster(ecs: &mut World, position: Position) -> Entity {
-    let creator = [new_goblin, new_gremlin];
-    let upper_bound = creator.len() as i32;
+pub fn new_key(ecs: &mut World, position: Position, depth: i32, door: Position) -> Option<Entity> {
+    let key = new_named(ecs, "Key", position)?;
 
-    let index = rng::range(ecs, 0, upper_bound) as usize;
+    let _ = ecs.write_storage::<Key>().insert(
+        key,
+        Key {
+            depth,
+            x: door.x,
+            y: door.y,
+        },
+    );
 
-    (creator[index])(ecs, position, None)
+    Some(key)
 }
 
-/// Creates a new monster in the passed `ecs` and attaches the supplied
-/// `name`, `renderable`, `statistic` and `position` components.
+/// Creates a new friendly NPC from the passed definition at
+/// the `position` in the `ecs` and returns it.
 ///
 /// # Arguments
-/// * `ecs`: The [World] the monster should be added to.
-/// * `name`: The [Name] of the monster.
-/// * `renderable`: The [Renderable] information of the monster.
-/// * `statistic`: The [Statistic] data of the monster for battle.
-/// * `position`: The [Position] of the monster in the world.
+/// * `ecs`: The [World] the NPC should be added to.
+/// * `raw`: The [NpcRaw] definition of the NPC.
+/// * `position`: The [Position] of the NPC in the world.
 ///
-fn new_monster(
-    ecs: &mut World,
-    name: Name,
-    renderable: Renderable,
-    statistic: Statistics,
-    position: Position,
-) -> Entity {
-    ecs.create_entity()
+/// # Notes
+/// * NPCs aren't [Monster]s and have no [Statistics], so they can't
+///   be fought. Their [AI] lets them stroll around the tile they
+///   were created on, the player talks to them by bumping into them.
+/// * NPCs with a shop become [Shopkeeper]s, their stock is put on
+///   display by the town, see [crate::shop::stock_shop].
+///
+fn new_npc(ecs: &mut World, raw: &NpcRaw, position: Position) -> Entity {
+    let mut builder = ecs
+        .create_entity()
         .with(position)
-        .with(renderable)
-        .with(name)
-        .with(statistic)
         .with(FOV {
             content: Vec::new(),
-            range: 8,
+            range: config::NPC_VISION_RANGE,
             is_dirty: true,
+            algorithm: FovAlgorithm::default(),
         })
-        .with(Monster {})
-        .with(Collision {})
-        .build()
+        .with(AI {
+            home: Some((position.x, position.y)),
+            ..AI::new(raw.behavior)
+        })
+        .with(raw.renderable.to_renderable())
+        .with(Name {
+            name: raw.name.clone(),
+        })
+        .with(Friendly {
+            greeting: raw.greeting.clone(),
+        })
+        .with(Faction::new(config::NPC_FACTION))
+        .with(Collision {});
+
+    if !raw.shop.is_empty() {
+        builder = builder.with(Shopkeeper { robbed: false });
+    }
+
+    builder.marked::<SaveMarker>().build()
 }
@@ -0,0 +1,203 @@
+//! Module for the rules of wearing equipment.
+//!
+//! Every [EquipmentSlot] holds a single item. Two-handed weapons occupy
+//! the off hand as well, so they can't be wielded together with a shield.
+//! One-handed melee weapons can be wielded in the off hand instead of a
+//! shield, which grants an extra attack at a penalty, see the
+//! [MeleeCombatSystem](super::MeleeCombatSystem). Before the player puts
+//! on an item, that takes off items in other slots, or picks the hand for
+//! a second weapon, the choice is explained through a [DialogInterface].
+
+use rltk::VirtualKeyCode;
+use specs::prelude::*;
+
+use super::identification::Identification;
+use super::localization;
+use super::{
+    DialogArgs, DialogInterface, DialogOption, EquipmentSlot, Equippable, Equipped, Item, Name,
+    RunState,
+};
+
+/// Requests the player to put on or take off the passed `item` and returns
+/// the next [RunState]. Items, that take off items worn in another slot,
+/// and second one-handed weapons are only put on, once the player has
+/// confirmed it in a [DialogInterface].
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the item.
+/// * `item`: The item selected in the inventory.
+///
+pub fn equip(ecs: &mut World, item: Entity) -> RunState {
+    let player = *ecs.fetch::<Entity>();
+
+    let (title, equippable, main_hand, off_hand) = {
+        let identification = ecs.fetch::<Identification>();
+        let entities = ecs.entities();
+        let names = ecs.read_storage::<Name>();
+        let equipped = ecs.read_storage::<Equipped>();
+        let equippables = ecs.read_storage::<Equippable>();
+
+        // Worn items are taken off right away
+        let equippable = match equippables.get(item) {
+            Some(equippable) if !equipped.contains(item) => equippable,
+            _ => {
+                Item::equip_item(ecs, &player, &item, false);
+                return RunState::PlayerTurn;
+            }
+        };
+
+        // The worn items, that are taken off, if the item is worn in the passed slot
+        let displaced = |slot: EquipmentSlot| {
+            (&entities, &equipped, &equippables, &names)
+                .join()
+                .filter(|(_, worn, other, _)| {
+                    worn.owner == player && equippable.displaces(slot, other, worn.slot)
+                })
+                .map(|(_, worn, _, name)| (worn.slot, identification.display(name)))
+                .collect::<Vec<_>>()
+        };
+
+        let wields_weapon = (&equipped, &equippables).join().any(|(worn, other)| {
+            worn.owner == player && worn.slot == EquipmentSlot::Melee && other.is_off_hand_weapon()
+        });
+
+        // Only a second one-handed weapon can go into the off hand
+        let off_hand = (equippable.is_off_hand_weapon() && wields_weapon)
+            .then(|| displaced(EquipmentSlot::Shield));
+
+        (
+            names
+                .get(item)
+                .map(|name| identification.display(name))
+                .unwrap_or_default(),
+            equippable.clone(),
+            displaced(equippable.slot),
+            off_hand,
+        )
+    };
+
+    if let Some(off_hand) = off_hand {
+        let hand = |id: &str, displaced: &[(EquipmentSlot, String)]| {
+            if displaced.is_empty() {
+                localization::tr(id)
+            } else {
+                localization::tr_with(
+                    "equip.replacing",
+                    &[&localization::tr(id), &list(displaced)],
+                )
+            }
+        };
+
+        DialogInterface::register_dialog(
+            ecs,
+            title,
+            Some(localization::tr("equip.choose_hand")),
+            vec![
+                equip_option(
+                    hand("equip.main_hand", &main_hand),
+                    VirtualKeyCode::M,
+                    item,
+                    false,
+                ),
+                equip_option(
+                    hand("equip.off_hand", &off_hand),
+                    VirtualKeyCode::O,
+                    item,
+                    true,
+                ),
+            ],
+            true,
+        );
+
+        return RunState::Dialog;
+    }
+
+    // Items worn in other slots are only taken off with the player's consent
+    let conflicts = main_hand
+        .into_iter()
+        .filter(|(slot, _)| *slot != equippable.slot)
+        .collect::<Vec<_>>();
+
+    if conflicts.is_empty() {
+        Item::equip_item(ecs, &player, &item, false);
+        return RunState::PlayerTurn;
+    }
+
+    let reason = if equippable.two_handed {
+        "equip.two_handed"
+    } else {
+        "equip.needs_off_hand"
+    };
+
+    DialogInterface::register_dialog(
+        ecs,
+        title.clone(),
+        Some(localization::tr_with(reason, &[&title, &list(&conflicts)])),
+        vec![equip_option(
+            localization::tr("equip.confirm"),
+            VirtualKeyCode::E,
+            item,
+            false,
+        )],
+        true,
+    );
+
+    RunState::Dialog
+}
+
+/// Returns the names of the passed `items` as comma separated list.
+///
+/// # Arguments
+/// * `items`: The worn items with their slot.
+///
+fn list(items: &[(EquipmentSlot, String)]) -> String {
+    items
+        .iter()
+        .map(|(_, name)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Creates the [DialogOption] putting on the passed `item`.
+///
+/// # Arguments
+/// * `description`: The description of the option.
+/// * `key`: The key selecting the option.
+/// * `item`: The item to put on.
+/// * `off_hand`: Flag indicating whether or not the item goes into the off hand.
+///
+fn equip_option(
+    description: String,
+    key: VirtualKeyCode,
+    item: Entity,
+    off_hand: bool,
+) -> DialogOption {
+    DialogOption {
+        description,
+        key,
+        args: vec![Box::new(item), Box::new(off_hand)],
+        callback: Box::new(|world, _, args| confirm(world, args)),
+    }
+}
+
+/// Callback of the [DialogOption]s of the equip dialogs, which puts
+/// on the item and ends the player's turn.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the item.
+/// * `args`: The [Entity] of the item, followed by the off hand flag.
+///
+fn confirm(ecs: &World, args: &DialogArgs) {
+    let item = args.first().and_then(|arg| arg.downcast_ref::<Entity>());
+    let off_hand = args
+        .get(1)
+        .and_then(|arg| arg.downcast_ref::<bool>())
+        .is_some_and(|off_hand| *off_hand);
+
+    if let Some(item) = item {
+        let player = *ecs.fetch::<Entity>();
+
+        Item::equip_item(ecs, &player, item, off_hand);
+        *ecs.write_resource::<RunState>() = RunState::PlayerTurn;
+    }
+}
@@ -0,0 +1,158 @@
+//! List of all events used in the game.
+//!
+//! Events are published through the [EventChannel] resource of their
+//! type and read by the systems, that registered a [ReaderId] for the
+//! channel during their setup. Unlike one-shot components an [Entity]
+//! can publish any number of events per turn.
+
+use rltk::Point;
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use super::{StatusEffect, StatusEffectKind};
+
+/// Event describing the intent of an
+/// [Entity] to attack another [Entity]
+/// in melee range.
+#[derive(Debug, Clone)]
+pub struct MeleeAttackEvent {
+    /// The attacking entity.
+    pub attacker: Entity,
+
+    /// The target entity of the attack.
+    pub target: Entity,
+}
+
+/// Event describing an [Entity] firing its
+/// ranged weapon at a target tile.
+#[derive(Debug, Clone)]
+pub struct RangedAttackEvent {
+    /// The shooting entity.
+    pub attacker: Entity,
+
+    /// The tile the shot is aimed at.
+    pub target: Point,
+
+    /// The piece of ammunition in flight, `None`
+    /// if the weapon doesn't need any.
+    pub ammunition: Option<Entity>,
+}
+
+/// Event describing the damage an
+/// [Entity] receives in a turn.
+#[derive(Debug, Clone)]
+pub struct DamageEvent {
+    /// The entity taking the damage.
+    pub target: Entity,
+
+    /// The amount of damage the `target` takes.
+    pub amount: i32,
+
+    /// The entity dealing the damage, `None` if it's unknown.
+    pub source: Option<Entity>,
+}
+
+/// Event describing a [StatusEffect]
+/// inflicted on an [Entity].
+#[derive(Debug, Clone)]
+pub struct StatusEffectEvent {
+    /// The entity suffering from the effect.
+    pub target: Entity,
+
+    /// The inflicted effect.
+    pub effect: StatusEffect,
+}
+
+/// Event describing the cure of an
+/// [Entity] from a kind of [StatusEffect].
+#[derive(Debug, Clone)]
+pub struct CureEvent {
+    /// The entity being cured.
+    pub target: Entity,
+
+    /// The kind of effect the `target` is cured from.
+    pub kind: StatusEffectKind,
+}
+
+/// Event describing a fire-damage effect, that sets the
+/// flammable tiles within the `radius` around the `target` on fire.
+#[derive(Debug, Clone)]
+pub struct IgniteEvent {
+    /// The tile at the center of the effect.
+    pub target: Point,
+
+    /// The maximum distance between the `target`
+    /// and the tiles set on fire.
+    pub radius: i32,
+}
+
+/// Event describing the intent of an
+/// [Entity] to pick up an item.
+#[derive(Debug, Clone)]
+pub struct PickupEvent {
+    /// The entity wanting to pick up the item.
+    pub collector: Entity,
+
+    /// The item the `collector` wants to pick up.
+    pub item: Entity,
+}
+
+/// Event describing the intent of an
+/// [Entity] to drop a collected item.
+#[derive(Debug, Clone)]
+pub struct DropEvent {
+    /// The entity owning the item.
+    pub owner: Entity,
+
+    /// The item the `owner` wants to drop.
+    pub item: Entity,
+}
+
+/// Event describing the intent of an [Entity]
+/// to put on or take off a collected item.
+#[derive(Debug, Clone)]
+pub struct EquipEvent {
+    /// The entity owning the item.
+    pub owner: Entity,
+
+    /// The item the `owner` wants to put on or take off.
+    pub item: Entity,
+
+    /// Flag indicating whether or not a one-handed melee weapon
+    /// is wielded in the off hand instead of the main hand.
+    pub off_hand: bool,
+}
+
+/// Publishes the passed `event` through the [EventChannel] of
+/// its type stored in the `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [EventChannel] is stored.
+/// * `event`: The event to publish.
+///
+/// # Panics
+/// * If no [EventChannel] for the type of the `event` has been
+///   registered, meaning no system reads the events.
+///
+pub fn publish_event<E>(ecs: &World, event: E)
+where
+    E: Send + Sync + 'static,
+{
+    ecs.fetch_mut::<EventChannel<E>>().single_write(event);
+}
+
+/// Registers a new [ReaderId] for the [EventChannel] of the type `E`
+/// in the `ecs` and returns it. If the channel doesn't exist yet, it's
+/// created. Used by the systems to subscribe to events during their setup.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [EventChannel] is stored.
+///
+pub fn register_event_reader<E>(ecs: &mut World) -> ReaderId<E>
+where
+    E: Send + Sync + 'static,
+{
+    ecs.entry::<EventChannel<E>>()
+        .or_insert_with(EventChannel::new)
+        .register_reader()
+}
@@ -1,76 +1,203 @@
 //! Module for custom exceptions and error messages.
 
-use specs::Entity;
-
-/// Returns the `error message` for the `DamageSystem`, when the storing of
-/// any damage `amount` for the `target` [Entity].
-///
-/// # Arguments
-/// * `target`: The [Entity] for which the damage `amount` couldn't be added.
-/// * `amount`: The `amount` of damage, that should have been added to the `target` [Entity].
-///
-pub fn get_add_damage_amount_error_message(target: &Entity, amount: i32) -> String {
-    format!(
-        "Damage amount {} couldn't be stored in the ecs for entity with id {}",
-        amount,
-        target.id()
-    )
+use std::any::type_name;
+use std::fmt;
+use std::ops::Deref;
+
+use specs::storage::MaskedStorage;
+use specs::{Component, Entity, Storage};
+
+/// Enum describing all recoverable errors, that can occur
+/// while the game is running. Instead of crashing the game,
+/// these errors are reported to the player through the `GameLog`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameError {
+    /// The coordinates lie outside of the map.
+    MapOutOfBounds {
+        /// X coordinate of the position.
+        x: i32,
+
+        /// Y coordinate of the position.
+        y: i32,
+    },
+
+    /// A required component is missing on an [Entity].
+    MissingComponent {
+        /// The [Entity] without the component.
+        entity: Entity,
+
+        /// The name of the missing component.
+        component: &'static str,
+    },
+
+    /// A component couldn't be inserted for an [Entity],
+    /// e.g. because the [Entity] has already been deleted.
+    ComponentInsertion {
+        /// The [Entity] the component should have been inserted for.
+        entity: Entity,
+
+        /// The name of the component.
+        component: &'static str,
+    },
+
+    /// One or more entities couldn't be deleted from the `ecs`.
+    EntityDeletion {
+        /// The [Entity] that couldn't be deleted.
+        entity: Entity,
+    },
+
+    /// Reading or writing the save data failed.
+    SaveIO {
+        /// Description of the failure.
+        message: String,
+    },
+
+    /// A content script couldn't be executed.
+    Script {
+        /// The name of the script.
+        script: String,
+
+        /// Description of the failure.
+        message: String,
+    },
+
+    /// A dice notation couldn't be parsed.
+    DiceNotation {
+        /// The malformed notation.
+        notation: String,
+
+        /// Description of the failure.
+        message: String,
+    },
+
+    /// A content asset is missing or unreadable.
+    Asset {
+        /// The name or path of the asset.
+        asset: String,
+
+        /// Description of the failure.
+        message: String,
+    },
+
+    /// A command line argument is unknown or malformed.
+    Argument {
+        /// The argument as passed on the command line.
+        argument: String,
+
+        /// Description of the failure.
+        message: String,
+    },
+
+    /// A command typed into the debug console is unknown,
+    /// malformed or couldn't be executed.
+    ConsoleCommand {
+        /// The command as typed into the console.
+        command: String,
+
+        /// Description of the failure.
+        message: String,
+    },
+}
+
+impl GameError {
+    /// Creates a new [GameError::MissingComponent] for the component
+    /// of type `T`, that is missing on the passed `entity`.
+    ///
+    /// # Arguments
+    /// * `entity`: The [Entity] without the component.
+    ///
+    pub fn missing_component<T>(entity: Entity) -> Self {
+        GameError::MissingComponent {
+            entity,
+            component: short_type_name::<T>(),
+        }
+    }
+
+    /// Creates a new [GameError::ComponentInsertion] for the component
+    /// of type `T`, that couldn't be inserted for the passed `entity`.
+    ///
+    /// # Arguments
+    /// * `entity`: The [Entity] the component should have been inserted for.
+    ///
+    pub fn component_insertion<T>(entity: Entity) -> Self {
+        GameError::ComponentInsertion {
+            entity,
+            component: short_type_name::<T>(),
+        }
+    }
 }
 
-/// Returns the `error message`for the `ItemCollectionSystem`, when the picking up of
-/// an item [Entity] has failed for the passed `collector` [Entity].
-///
-/// # Arguments
-/// * `collector`: The [Entity] that wants to pick up the `item`.
-/// * `item`: The item [Entity], which the `collector` is trying to pickup.
-///
-pub fn get_pick_up_item_error_message(collector: &Entity, item: &Entity) -> String {
-    format!(
-        "Unable to pick up item with id {} and add it to inventory of collector with id {}!",
-        item.id(),
-        collector.id()
-    )
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::MapOutOfBounds { x, y } => {
+                write!(f, "Position ({}, {}) is out of the map's bounds!", x, y)
+            }
+            GameError::MissingComponent { entity, component } => write!(
+                f,
+                "Entity with id {} has no {} component!",
+                entity.id(),
+                component
+            ),
+            GameError::ComponentInsertion { entity, component } => write!(
+                f,
+                "Unable to insert {} component for entity with id {}!",
+                component,
+                entity.id()
+            ),
+            GameError::EntityDeletion { entity } => {
+                write!(f, "Unable to delete entity with id {}!", entity.id())
+            }
+            GameError::SaveIO { message } => write!(f, "Save data error: {}", message),
+            GameError::Script { script, message } => {
+                write!(f, "Script '{}' failed: {}", script, message)
+            }
+            GameError::DiceNotation { notation, message } => {
+                write!(f, "Invalid dice '{}': {}", notation, message)
+            }
+            GameError::Asset { asset, message } => {
+                write!(f, "Asset '{}' is unusable: {}", asset, message)
+            }
+            GameError::Argument { argument, message } => {
+                write!(f, "Invalid argument '{}': {}", argument, message)
+            }
+            GameError::ConsoleCommand { command, message } => {
+                write!(f, "Command '{}' failed: {}", command, message)
+            }
+        }
+    }
 }
 
-/// Returns the error message for the `DropItemSystem`, when the droping of an
-/// item [Entity] fails for the passed `owner` entity.
-///
-/// # Arguments
-/// * `owner`: The current owning [Entity], which is trying to drop the `item` [Entity].
-/// * `item`: The item [Entity] the `owner` is trying to drop.
-///
-pub fn get_drop_item_error_message(owner: &Entity, item: &Entity) -> String {
-    format!(
-        "Unable to drop item with id {} from inventory of owner with id {}!",
-        owner.id(),
-        item.id()
-    )
+impl std::error::Error for GameError {}
+
+/// Shorthand for a [Result] with a [GameError].
+pub type GameResult<T> = Result<T, GameError>;
+
+/// Extension for component storages to fetch the component of an
+/// [Entity], that is required for further processing, as [GameResult].
+pub trait RequireComponent<T> {
+    /// Returns the component of the passed `entity`, or a
+    /// [GameError::MissingComponent] if the `entity` has none.
+    ///
+    /// # Arguments
+    /// * `entity`: The [Entity] whose component should be returned.
+    ///
+    fn require(&self, entity: Entity) -> GameResult<&T>;
 }
 
-/// Returns the error message for the `UsePotion` systen, when the insertion
-/// of a use potion request failes.
-/// 
-/// # Arguments
-/// * `user`: The [Entity] that wants to drink the `potion`.
-/// * `potion`: The `potion` [Entity] the `user` wants to drink.
-/// 
-pub fn get_drink_potion_error_message(user: &Entity, potion: &Entity) -> String {
-    format!(
-        "Unable to insert use potion request for user with id {} and potion with id {}",
-        user.id(),
-        potion.id()
-    )
+impl<'e, T, D> RequireComponent<T> for Storage<'e, T, D>
+where
+    T: Component,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    fn require(&self, entity: Entity) -> GameResult<&T> {
+        self.get(entity)
+            .ok_or_else(|| GameError::missing_component::<T>(entity))
+    }
 }
 
-/// Returns the error message for `MeleeCombatSystem` system, used when the
-/// adding of a melee attack from a monster against the player fails.
-/// 
-/// #A Arguments
-/// * `monster`: The [Entity] attacking the player.
-/// 
-pub fn get_add_melee_damage_error_message(monster: &Entity) -> String {
-    format!(
-        "Adding melee attack from monster with id {} against player failed!",
-        monster.id()
-    )
+/// Returns the name of the type `T` without its module path.
+fn short_type_name<T>() -> &'static str {
+    let name = type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
 }
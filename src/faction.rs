@@ -0,0 +1,126 @@
+//! Module for the relations between the factions of the game.
+//!
+//! The relations are defined in `data/factions.json`, which is embedded
+//! into the binary. Every entity with a [Faction] belongs to one faction,
+//! the members of a faction are always allied with each other. Relations
+//! between two factions apply in both directions, pairs of factions
+//! without a defined relation fall back to the default relation.
+
+use serde::Deserialize;
+use specs::prelude::*;
+
+use super::Faction;
+
+/// The embedded faction relation definitions.
+const FACTIONS: &str = include_str!("../data/factions.json");
+
+/// Enum describing how two factions treat each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    /// The factions fight on the same side and never attack each other.
+    Allied,
+
+    /// The factions ignore each other, unless they are attacked.
+    Neutral,
+
+    /// The factions attack each other on sight.
+    Hostile,
+}
+
+/// The relation between a single pair of factions.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct RelationEntry {
+    /// The names of the two factions.
+    factions: [String; 2],
+
+    /// The relation of the factions to each other.
+    relation: Relation,
+}
+
+/// Resource holding the hostility matrix of all factions.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FactionRelations {
+    /// The relation between two different factions without an entry.
+    default: Relation,
+
+    /// The relations between pairs of factions.
+    relations: Vec<RelationEntry>,
+}
+
+impl FactionRelations {
+    /// Parses the faction relations embedded into the binary.
+    ///
+    /// # Panics
+    /// * If the embedded definitions are malformed.
+    ///
+    pub fn load() -> Self {
+        serde_json::from_str(FACTIONS).expect("The embedded faction relations are malformed!")
+    }
+
+    /// Returns the [Relation] between the factions with the passed names.
+    ///
+    /// # Arguments
+    /// * `first`: The name of the first faction.
+    /// * `second`: The name of the second faction.
+    ///
+    pub fn relation(&self, first: &str, second: &str) -> Relation {
+        if first == second {
+            return Relation::Allied;
+        }
+
+        self.relations
+            .iter()
+            .find(|entry| {
+                let [a, b] = &entry.factions;
+                (a == first && b == second) || (a == second && b == first)
+            })
+            .map_or(self.default, |entry| entry.relation)
+    }
+
+    /// Returns the [Relation] between the [Faction]s of the passed
+    /// entities. Entities without a [Faction] are treated as
+    /// belonging to a faction without any defined relation.
+    ///
+    /// # Arguments
+    /// * `factions`: The [Faction] storage of the `ecs`.
+    /// * `first`: The first [Entity].
+    /// * `second`: The second [Entity].
+    ///
+    pub fn relation_between(
+        &self,
+        factions: &ReadStorage<Faction>,
+        first: Entity,
+        second: Entity,
+    ) -> Relation {
+        match (factions.get(first), factions.get(second)) {
+            (Some(first), Some(second)) => self.relation(&first.name, &second.name),
+            _ => self.default,
+        }
+    }
+
+    /// Returns `true` if the passed entities are hostile to each other.
+    ///
+    /// # Arguments
+    /// * `factions`: The [Faction] storage of the `ecs`.
+    /// * `first`: The first [Entity].
+    /// * `second`: The second [Entity].
+    ///
+    pub fn is_hostile(
+        &self,
+        factions: &ReadStorage<Faction>,
+        first: Entity,
+        second: Entity,
+    ) -> bool {
+        self.relation_between(factions, first, second) == Relation::Hostile
+    }
+}
+
+/// Registers the [FactionRelations] with the passed `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [FactionRelations] should be registered.
+///
+pub fn register(ecs: &mut World) {
+    ecs.insert(FactionRelations::load());
+}
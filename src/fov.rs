@@ -0,0 +1,342 @@
+//! Module for the field of view algorithms.
+//!
+//! Every algorithm implements the [FieldOfView] trait and is selected through
+//! the [FovAlgorithm] of the player's settings. The default algorithm of
+//! `rltk` is cheap, but casts its rays only to the border of the view, so
+//! walls seen along diagonals can show gaps and tiles visible from one
+//! position don't always see that position back. The symmetric shadowcasting
+//! and permissive algorithms avoid these artifacts at a slightly higher cost.
+
+use rltk::{field_of_view, BaseMap, Point};
+use serde::{Deserialize, Serialize};
+
+use super::{config, localization, pythagoras_distance, Map};
+
+/// An algorithm calculating the tiles visible from a position.
+pub trait FieldOfView {
+    /// Returns all tiles within the `range` of the `origin`, that
+    /// are visible from it, including the `origin` itself.
+    ///
+    /// # Arguments
+    /// * `origin`: The position of the viewer.
+    /// * `range`: The maximum distance of a visible tile.
+    /// * `map`: The [Map] providing the opaque tiles.
+    ///
+    /// # Notes
+    /// * The result may contain tiles outside of the `map`.
+    ///
+    fn compute(&self, origin: Point, range: i32, map: &Map) -> Vec<Point>;
+}
+
+/// Enum describing all selectable field of view algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FovAlgorithm {
+    /// The shadowcasting of [rltk::field_of_view].
+    Rltk,
+
+    /// Symmetric shadowcasting, every tile visible from a position
+    /// sees that position back, see [SymmetricShadowcasting].
+    SymmetricShadowcasting,
+
+    /// Permissive line of sight, see [Permissive].
+    Permissive,
+}
+
+impl Default for FovAlgorithm {
+    fn default() -> Self {
+        config::DEFAULT_FOV_ALGORITHM
+    }
+}
+
+impl FovAlgorithm {
+    /// All selectable algorithms, in the order they are cycled through.
+    const ALL: [FovAlgorithm; 3] = [
+        FovAlgorithm::Rltk,
+        FovAlgorithm::SymmetricShadowcasting,
+        FovAlgorithm::Permissive,
+    ];
+
+    /// Returns the algorithm following this one in the order of the options menu.
+    pub fn next(&self) -> FovAlgorithm {
+        let index = FovAlgorithm::ALL
+            .iter()
+            .position(|algorithm| algorithm == self)
+            .unwrap_or(0);
+
+        FovAlgorithm::ALL[(index + 1) % FovAlgorithm::ALL.len()]
+    }
+
+    /// Returns the localized name of the algorithm.
+    pub fn name(&self) -> String {
+        let id = match self {
+            FovAlgorithm::Rltk => "fov.rltk",
+            FovAlgorithm::SymmetricShadowcasting => "fov.symmetric_shadowcasting",
+            FovAlgorithm::Permissive => "fov.permissive",
+        };
+
+        localization::tr(id)
+    }
+
+    /// Returns the implementation of the algorithm.
+    pub fn field_of_view(&self) -> &'static dyn FieldOfView {
+        match self {
+            FovAlgorithm::Rltk => &RltkFieldOfView,
+            FovAlgorithm::SymmetricShadowcasting => &SymmetricShadowcasting,
+            FovAlgorithm::Permissive => &Permissive,
+        }
+    }
+}
+
+/// The field of view of `rltk`, see [rltk::field_of_view].
+pub struct RltkFieldOfView;
+
+impl FieldOfView for RltkFieldOfView {
+    fn compute(&self, origin: Point, range: i32, map: &Map) -> Vec<Point> {
+        field_of_view(origin, range, map)
+    }
+}
+
+/// Symmetric shadowcasting as described by Albert Ford.
+///
+/// # Notes
+/// * The view is scanned row by row in each of the four cardinal
+///   quadrants. Floor tiles are only visible if their center lies
+///   within the unblocked slopes, which makes the view symmetric,
+///   while walls are visible if any part of them is.
+///
+pub struct SymmetricShadowcasting;
+
+/// A row of a quadrant scanned by the [SymmetricShadowcasting].
+#[derive(Debug, Clone, Copy)]
+struct Row {
+    /// Distance of the row from the origin.
+    depth: i32,
+
+    /// The slope of the first unblocked column.
+    start_slope: f64,
+
+    /// The slope of the last unblocked column.
+    end_slope: f64,
+}
+
+impl Row {
+    /// Returns the first column of the row, that lies within its slopes.
+    fn min_col(&self) -> i32 {
+        (self.depth as f64 * self.start_slope + 0.5).floor() as i32
+    }
+
+    /// Returns the last column of the row, that lies within its slopes.
+    fn max_col(&self) -> i32 {
+        (self.depth as f64 * self.end_slope - 0.5).ceil() as i32
+    }
+
+    /// Returns `true` if the center of the `col` lies within the slopes of the row.
+    ///
+    /// # Arguments
+    /// * `col`: The column to check.
+    ///
+    fn is_symmetric(&self, col: i32) -> bool {
+        let col = col as f64;
+        let depth = self.depth as f64;
+
+        col >= depth * self.start_slope && col <= depth * self.end_slope
+    }
+
+    /// Returns the row behind this row with the same slopes.
+    fn next(&self) -> Row {
+        Row {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+}
+
+/// Returns the slope of the left edge of the `col` in a row at the `depth`.
+///
+/// # Arguments
+/// * `depth`: The distance of the row from the origin.
+/// * `col`: The column in the row.
+///
+fn slope(depth: i32, col: i32) -> f64 {
+    (2 * col - 1) as f64 / (2 * depth) as f64
+}
+
+impl FieldOfView for SymmetricShadowcasting {
+    fn compute(&self, origin: Point, range: i32, map: &Map) -> Vec<Point> {
+        let mut visible = vec![origin];
+
+        // Transforms a (depth, col) pair of a quadrant into map coordinates
+        let quadrants: [fn(Point, i32, i32) -> Point; 4] = [
+            |origin, depth, col| Point::new(origin.x + col, origin.y - depth),
+            |origin, depth, col| Point::new(origin.x + depth, origin.y + col),
+            |origin, depth, col| Point::new(origin.x + col, origin.y + depth),
+            |origin, depth, col| Point::new(origin.x - depth, origin.y + col),
+        ];
+
+        for transform in quadrants.iter() {
+            let mut rows = vec![Row {
+                depth: 1,
+                start_slope: -1.0,
+                end_slope: 1.0,
+            }];
+
+            while let Some(mut row) = rows.pop() {
+                if row.depth > range {
+                    continue;
+                }
+
+                let mut previous_is_wall: Option<bool> = None;
+
+                for col in row.min_col()..=row.max_col() {
+                    let tile = transform(origin, row.depth, col);
+                    let is_wall = is_opaque(map, tile);
+
+                    if (is_wall || row.is_symmetric(col)) && is_in_range(origin, tile, range) {
+                        visible.push(tile);
+                    }
+
+                    if previous_is_wall == Some(true) && !is_wall {
+                        row.start_slope = slope(row.depth, col);
+                    }
+
+                    if previous_is_wall == Some(false) && is_wall {
+                        let mut next = row.next();
+                        next.end_slope = slope(row.depth, col);
+                        rows.push(next);
+                    }
+
+                    previous_is_wall = Some(is_wall);
+                }
+
+                if previous_is_wall == Some(false) {
+                    rows.push(row.next());
+                }
+            }
+        }
+
+        visible.sort_by_key(|point| (point.y, point.x));
+        visible.dedup();
+        visible
+    }
+}
+
+/// Permissive line of sight.
+///
+/// # Notes
+/// * A tile is visible if a line from the center of the origin reaches
+///   its center or a point close to one of its corners without crossing
+///   an opaque tile. Walls seen along diagonals and through the corners
+///   of doorways are shown completely, at the cost of five line checks
+///   per tile in range.
+///
+pub struct Permissive;
+
+/// The offsets of the points checked for every tile by
+/// the [Permissive] algorithm, relative to its top left corner.
+const PERMISSIVE_TARGETS: [(f64, f64); 5] = [
+    (0.5, 0.5),
+    (0.05, 0.05),
+    (0.95, 0.05),
+    (0.05, 0.95),
+    (0.95, 0.95),
+];
+
+impl FieldOfView for Permissive {
+    fn compute(&self, origin: Point, range: i32, map: &Map) -> Vec<Point> {
+        let mut visible = Vec::new();
+
+        for y in (origin.y - range)..=(origin.y + range) {
+            for x in (origin.x - range)..=(origin.x + range) {
+                let tile = Point::new(x, y);
+
+                if !is_in_range(origin, tile, range) {
+                    continue;
+                }
+
+                let is_visible = PERMISSIVE_TARGETS.iter().any(|(offset_x, offset_y)| {
+                    is_line_clear(
+                        map,
+                        origin,
+                        tile,
+                        (x as f64 + offset_x, y as f64 + offset_y),
+                    )
+                });
+
+                if is_visible {
+                    visible.push(tile);
+                }
+            }
+        }
+
+        visible
+    }
+}
+
+/// Returns `true` if the line from the center of the `origin` to the
+/// `target` point inside the `tile` doesn't cross an opaque tile. The
+/// `origin` and the `tile` itself are never blocking.
+///
+/// # Arguments
+/// * `map`: The [Map] providing the opaque tiles.
+/// * `origin`: The tile the line starts in.
+/// * `tile`: The tile containing the `target`.
+/// * `target`: The end point of the line in map coordinates.
+///
+fn is_line_clear(map: &Map, origin: Point, tile: Point, target: (f64, f64)) -> bool {
+    let start = (origin.x as f64 + 0.5, origin.y as f64 + 0.5);
+    let delta = (target.0 - start.0, target.1 - start.1);
+
+    // Walk all tiles crossed by the line, by always stepping
+    // over the grid line, that the line reaches first
+    let step = (delta.0.signum() as i32, delta.1.signum() as i32);
+    let t_delta = (1.0 / delta.0.abs(), 1.0 / delta.1.abs());
+
+    // The line starts in the center, so the first grid lines are half a tile away
+    let mut t_max = (0.5 * t_delta.0, 0.5 * t_delta.1);
+    let mut current = origin;
+
+    // Every step moves one tile closer to the target tile
+    let steps = (tile.x - origin.x).abs() + (tile.y - origin.y).abs();
+
+    for _ in 0..steps {
+        if current != origin && is_opaque(map, current) {
+            return false;
+        }
+
+        if t_max.0 < t_max.1 {
+            current.x += step.0;
+            t_max.0 += t_delta.0;
+        } else {
+            current.y += step.1;
+            t_max.1 += t_delta.1;
+        }
+    }
+
+    current == tile
+}
+
+/// Returns `true` if the `tile` is opaque or outside of the `map`.
+///
+/// # Arguments
+/// * `map`: The [Map] containing the tile.
+/// * `tile`: The position of the tile.
+///
+fn is_opaque(map: &Map, tile: Point) -> bool {
+    if tile.x < 0 || tile.x >= map.width || tile.y < 0 || tile.y >= map.height {
+        return true;
+    }
+
+    map.is_opaque(map.coordinates_to_idx(tile.x, tile.y))
+}
+
+/// Returns `true` if the `tile` lies within the `range` of the `origin`.
+///
+/// # Arguments
+/// * `origin`: The position of the viewer.
+/// * `tile`: The position of the tile.
+/// * `range`: The maximum distance.
+///
+fn is_in_range(origin: Point, tile: Point, range: i32) -> bool {
+    pythagoras_distance(&origin, &tile) <= range as f32
+}
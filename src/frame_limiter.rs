@@ -0,0 +1,128 @@
+//! Module for limiting the work done per frame.
+//!
+//! The game is turn based, so nothing changes on screen while the player
+//! doesn't interact with it. The [FrameLimiter] tracks the player's input
+//! and the focus of the window. Once no input has arrived for
+//! [config::IDLE_TIMEOUT_SECONDS] or the window lost its focus, the game
+//! is idle and the frame rate is lowered to [config::IDLE_FPS_CAP] on native
+//! targets. Frames without input, that only wait for it, aren't rendered
+//! again, as long as nothing else changed on screen, see
+//! [FrameLimiter::has_input].
+
+use instant::{Duration, Instant};
+use rltk::{BEvent, Rltk, INPUT};
+
+use super::config;
+
+/// Tracks the player's input to decide whether
+/// or not the game is idle.
+pub struct FrameLimiter {
+    /// The time of the last input.
+    last_input: Instant,
+
+    /// The last known mouse position.
+    last_mouse_position: (i32, i32),
+
+    /// Flag indicating whether or not the window has the focus.
+    focused: bool,
+
+    /// Flag indicating whether or not the game was idle
+    /// during the last frame.
+    idle: bool,
+
+    /// Flag indicating whether or not the player
+    /// interacted with the game in the last frame.
+    has_input: bool,
+}
+
+impl FrameLimiter {
+    /// Creates a new active [FrameLimiter].
+    pub fn new() -> Self {
+        FrameLimiter {
+            last_input: Instant::now(),
+            last_mouse_position: (0, 0),
+            focused: true,
+            idle: false,
+            has_input: true,
+        }
+    }
+
+    /// Returns `true` if the player interacted with the game during
+    /// the frame processed by the last [FrameLimiter::update], e.g.
+    /// pressed a key or moved the mouse.
+    pub fn has_input(&self) -> bool {
+        self.has_input
+    }
+
+    /// Processes the input of the current frame and lowers the frame
+    /// rate, once the game is idle.
+    ///
+    /// # Arguments
+    /// * `ctx`: The [Rltk] context of the current frame.
+    /// * `fps_cap`: The frame rate while the game is active,
+    ///   `0` for an uncapped frame rate.
+    ///
+    /// # Notes
+    /// * Drains the input event queue of `rltk`.
+    /// * Adjusts the frame rate when the game becomes idle or active.
+    ///
+    pub fn update(&mut self, ctx: &Rltk, fps_cap: u32) {
+        let mut has_input = ctx.key.is_some() || ctx.left_click;
+
+        INPUT.lock().for_each_message(|event| match event {
+            BEvent::Focused { focused } => {
+                self.focused = focused;
+                has_input = true;
+            }
+            BEvent::CloseRequested | BEvent::Resized { .. } => has_input = true,
+            _ => {}
+        });
+
+        let mouse_position = ctx.mouse_pos();
+
+        if mouse_position != self.last_mouse_position {
+            self.last_mouse_position = mouse_position;
+            has_input = true;
+        }
+
+        if has_input {
+            self.last_input = Instant::now();
+        }
+
+        self.has_input = has_input;
+
+        let timeout = Duration::from_secs(config::IDLE_TIMEOUT_SECONDS);
+        let idle = !has_input && (!self.focused || self.last_input.elapsed() >= timeout);
+
+        if idle != self.idle {
+            self.idle = idle;
+            self.apply_fps_cap(fps_cap);
+        }
+    }
+
+    /// Applies the frame rate of the current state to the
+    /// backend. Only native targets support a frame rate cap.
+    ///
+    /// # Arguments
+    /// * `fps_cap`: The frame rate while the game is active,
+    ///   `0` for an uncapped frame rate.
+    ///
+    fn apply_fps_cap(&self, fps_cap: u32) {
+        let fps_cap = if self.idle {
+            config::IDLE_FPS_CAP
+        } else {
+            fps_cap
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            rltk::BACKEND.lock().frame_sleep_time = match fps_cap {
+                0 => None,
+                fps => Some(1000 / fps as u64),
+            };
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        let _ = fps_cap;
+    }
+}
@@ -1,7 +1,7 @@
 //! Collection of helper functions
 
 use chrono::{Timelike, Utc};
-use rltk::{DistanceAlg, Point, VirtualKeyCode};
+use rltk::{console, DistanceAlg, DrawBatch, Point, VirtualKeyCode};
 
 /// Calculates the distance between the `start` and `end` point
 /// using [DistanceAlg::Pythagoras] and returns the result.
@@ -59,10 +59,61 @@ pub fn virtual_key_code_to_string(key: VirtualKeyCode) -> &'static str {
         VirtualKeyCode::Y => "Y",
         VirtualKeyCode::Z => "Z",
         VirtualKeyCode::Escape => "Escape",
+        VirtualKeyCode::Space => "Space",
         _ => "Key",
     }
 }
 
+/// Converts the passed [VirtualKeyCode] to the [char] it would type,
+/// if it is a letter or the space key. Otherwise [None] is returned.
+///
+/// # Arguments
+/// * `key`: The [VirtualKeyCode] to convert.
+/// * `uppercase`: Flag indicating whether or not letters should be uppercase.
+///
+pub fn virtual_key_code_to_char(key: VirtualKeyCode, uppercase: bool) -> Option<char> {
+    if key == VirtualKeyCode::Space {
+        return Some(' ');
+    }
+
+    let key_string = virtual_key_code_to_string(key);
+
+    if key_string.len() != 1 {
+        return None;
+    }
+
+    key_string.chars().next().map(|character| {
+        if uppercase {
+            character.to_ascii_uppercase()
+        } else {
+            character.to_ascii_lowercase()
+        }
+    })
+}
+
+/// Converts the passed [VirtualKeyCode] to the digit it would type,
+/// if it is a number key of the main keyboard or the numpad.
+/// Otherwise [None] is returned.
+///
+/// # Arguments
+/// * `key`: The [VirtualKeyCode] to convert.
+///
+pub fn virtual_key_code_to_digit(key: VirtualKeyCode) -> Option<char> {
+    match key {
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Some('0'),
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Some('1'),
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Some('2'),
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Some('3'),
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Some('4'),
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Some('5'),
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Some('6'),
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Some('7'),
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Some('8'),
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Some('9'),
+        _ => None,
+    }
+}
+
 /// Converts the passed [i32] `value` to a corresponding
 /// alpha [VirtualKeyCode].
 ///
@@ -107,3 +158,20 @@ pub fn i32_to_alpha_key(value: i32) -> VirtualKeyCode {
         ),
     }
 }
+
+/// Submits the passed `batch` with the z-order of the `layer`, so
+/// it is rendered with all other batches at the end of the frame.
+///
+/// # Arguments
+/// * `batch`: The [DrawBatch] to submit.
+/// * `layer`: The draw layer, e.g. [config::LAYER_UI].
+///
+/// # Notes
+/// * Errors are reported to the console.
+///
+/// [config::LAYER_UI]: super::config::LAYER_UI
+pub fn submit_batch(batch: &mut DrawBatch, layer: usize) {
+    if let Err(error) = batch.submit(layer) {
+        console::log(format!("Unable to submit a draw batch: {:?}", error));
+    }
+}
@@ -0,0 +1,337 @@
+//! Module for the configuration file of the game.
+//!
+//! The [GameConfig] holds the values of the [config] module, that control
+//! the size of the window and the generation of the maps. On native targets
+//! they can be overridden through the [config::CONFIG_FILE] next to the
+//! game, so players and testers can tweak the generation without rebuilding
+//! the game, e.g.
+//!
+//! ```toml
+//! map_width = 100
+//! map_height = 50
+//! map_generator = "cave"
+//! max_monsters_per_room = 2
+//! ```
+//!
+//! Every value missing in the file keeps its default. Values outside of
+//! their valid range are clamped, so the maps can always be generated.
+//! Maps larger than the window are scrolled through the `camera`. The web
+//! build always uses the defaults.
+
+use std::fmt::Display;
+
+use rltk::console;
+use serde::Deserialize;
+use specs::prelude::*;
+
+use super::config;
+use super::exceptions::GameError;
+use super::map_builders::MapGenerator;
+
+/// The minimum width of the window, needed to fit the status bar.
+const MIN_WINDOW_WIDTH: i32 = 80;
+
+/// The amount of rows of the message log below the map.
+const MESSAGE_LOG_HEIGHT: i32 = 10;
+
+/// The minimum width of a map, needed to fit the arena of the boss.
+const MIN_MAP_WIDTH: i32 = 40;
+
+/// The minimum height of a map, needed to fit the arena of the boss.
+const MIN_MAP_HEIGHT: i32 = 20;
+
+/// The maximum width and height of the window and the maps.
+const MAX_SIZE: i32 = 250;
+
+/// The smallest room, that still has a floor tile surrounded by floor.
+const MIN_ROOM_SIZE: i32 = 3;
+
+/// Resource holding the configuration of the window and the map generation.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// The width of the game's window.
+    pub window_width: i32,
+
+    /// The height of the game's window, including
+    /// the message log below the viewport.
+    pub window_height: i32,
+
+    /// The width of the in-game map.
+    pub map_width: i32,
+
+    /// The height of the in-game map.
+    pub map_height: i32,
+
+    /// The maximum amount of rooms the map can display.
+    pub max_rooms: i32,
+
+    /// The minimum size of a room on the map.
+    pub min_room_size: i32,
+
+    /// The maximum size of a room on the map.
+    pub max_room_size: i32,
+
+    /// The algorithm creating the layout of the maps.
+    pub map_generator: MapGenerator,
+
+    /// The minimum width and height of a leaf of the
+    /// binary space partition of [MapGenerator::Bsp].
+    pub bsp_min_leaf_size: i32,
+
+    /// The chance in percent of a tile being a wall before
+    /// the caves of [MapGenerator::Cave] are smoothed.
+    pub cave_wall_chance: i32,
+
+    /// The amount of smoothing iterations of the cellular
+    /// automaton of [MapGenerator::Cave].
+    pub cave_smoothing_iterations: i32,
+
+    /// The minimum share of the map in percent, that the connected
+    /// part of a cave of [MapGenerator::Cave] has to cover.
+    pub cave_min_floor_percent: usize,
+
+    /// The maximum amount of caves generated while
+    /// looking for one with enough floor tiles.
+    pub cave_max_attempts: i32,
+
+    /// The side length of the square regions, that maps
+    /// without rooms are populated by, one at a time.
+    pub spawn_region_size: i32,
+
+    /// The minimum amount of floor tiles of a region of
+    /// a map without rooms, to spawn entities in it.
+    pub min_spawn_region_tiles: usize,
+
+    /// The maximum amount of monsters spawned in a single
+    /// room or region, regardless of the spawn tables.
+    pub max_monsters_per_room: usize,
+
+    /// The maximum amount of items spawned in a single
+    /// room or region, regardless of the spawn tables.
+    pub max_items_per_room: usize,
+
+    /// The errors of the configuration file, i.e. if it
+    /// couldn't be read or parsed or had invalid values.
+    #[serde(skip)]
+    failures: Vec<GameError>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            window_width: config::WINDOW_WIDTH,
+            window_height: config::WINDOW_HEIGHT,
+            map_width: config::MAP_WIDTH,
+            map_height: config::MAP_HEIGHT,
+            max_rooms: config::MAX_ROOMS,
+            min_room_size: config::MIN_ROOM_SIZE,
+            max_room_size: config::MAX_ROOM_SIZE,
+            map_generator: config::MAP_GENERATOR,
+            bsp_min_leaf_size: config::BSP_MIN_LEAF_SIZE,
+            cave_wall_chance: config::CAVE_WALL_CHANCE,
+            cave_smoothing_iterations: config::CAVE_SMOOTHING_ITERATIONS,
+            cave_min_floor_percent: config::CAVE_MIN_FLOOR_PERCENT,
+            cave_max_attempts: config::CAVE_MAX_ATTEMPTS,
+            spawn_region_size: config::SPAWN_REGION_SIZE,
+            min_spawn_region_tiles: config::MIN_SPAWN_REGION_TILES,
+            max_monsters_per_room: config::MAX_MONSTERS_PER_ROOM,
+            max_items_per_room: config::MAX_ITEMS_PER_ROOM,
+            failures: Vec::new(),
+        }
+    }
+}
+
+impl GameConfig {
+    /// Reads the [config::CONFIG_FILE], or returns the default
+    /// [GameConfig] if there is none or it can't be read.
+    ///
+    /// # Notes
+    /// * Errors are reported to the console and kept
+    ///   in the [GameConfig::failures].
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        match std::fs::read_to_string(config::CONFIG_FILE) {
+            Ok(source) => GameConfig::parse(&source),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => GameConfig::default(),
+            Err(err) => GameConfig::default().with_failure(err.to_string()),
+        }
+    }
+
+    /// Returns the default [GameConfig], since the
+    /// web build has no configuration file.
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        GameConfig::default()
+    }
+
+    /// Parses the TOML `source` of a configuration file and
+    /// clamps its values to their valid ranges.
+    ///
+    /// # Arguments
+    /// * `source`: The content of the configuration file.
+    ///
+    /// # Notes
+    /// * Returns the default [GameConfig] if the `source` is malformed.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn parse(source: &str) -> Self {
+        match toml::from_str::<GameConfig>(source) {
+            Ok(game_config) => game_config.clamped(),
+            Err(err) => GameConfig::default().with_failure(err.to_string()),
+        }
+    }
+
+    /// Reports the passed `message` about the configuration file
+    /// to the console and adds it to the [GameConfig::failures].
+    ///
+    /// # Arguments
+    /// * `message`: The description of the error.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    fn with_failure(mut self, message: String) -> Self {
+        console::log(format!(
+            "Unable to load '{}': {}",
+            config::CONFIG_FILE,
+            message
+        ));
+
+        self.failures.push(GameError::Asset {
+            asset: config::CONFIG_FILE.to_string(),
+            message,
+        });
+
+        self
+    }
+
+    /// Clamps all values to the ranges, in which the window can
+    /// display the ui and the maps can be generated.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn clamped(mut self) -> Self {
+        self.map_width = self.clamp("map_width", self.map_width, MIN_MAP_WIDTH, MAX_SIZE);
+        self.map_height = self.clamp("map_height", self.map_height, MIN_MAP_HEIGHT, MAX_SIZE);
+
+        self.window_width = self.clamp(
+            "window_width",
+            self.window_width,
+            MIN_WINDOW_WIDTH,
+            MAX_SIZE,
+        );
+        self.window_height = self.clamp(
+            "window_height",
+            self.window_height,
+            MIN_MAP_HEIGHT + MESSAGE_LOG_HEIGHT,
+            MAX_SIZE,
+        );
+
+        // Rooms have to fit into the map with a wall on every side
+        let map_size = self.map_width.min(self.map_height);
+
+        self.max_rooms = self.clamp("max_rooms", self.max_rooms, 1, MAX_SIZE);
+        self.min_room_size = self.clamp(
+            "min_room_size",
+            self.min_room_size,
+            MIN_ROOM_SIZE,
+            map_size / 2,
+        );
+        self.max_room_size = self.clamp(
+            "max_room_size",
+            self.max_room_size,
+            self.min_room_size + 1,
+            map_size - 3,
+        );
+        self.bsp_min_leaf_size = self.clamp(
+            "bsp_min_leaf_size",
+            self.bsp_min_leaf_size,
+            self.min_room_size + 1,
+            map_size,
+        );
+
+        self.cave_wall_chance = self.clamp("cave_wall_chance", self.cave_wall_chance, 0, 100);
+        self.cave_smoothing_iterations = self.clamp(
+            "cave_smoothing_iterations",
+            self.cave_smoothing_iterations,
+            0,
+            100,
+        );
+        self.cave_min_floor_percent = self.clamp(
+            "cave_min_floor_percent",
+            self.cave_min_floor_percent,
+            0,
+            100,
+        );
+        self.cave_max_attempts = self.clamp("cave_max_attempts", self.cave_max_attempts, 1, 100);
+
+        self.spawn_region_size =
+            self.clamp("spawn_region_size", self.spawn_region_size, 1, MAX_SIZE);
+
+        self
+    }
+
+    /// Returns the `value` of the `key` clamped to the range from
+    /// `min` to `max` and reports values outside of it.
+    ///
+    /// # Arguments
+    /// * `key`: The key of the value in the configuration file.
+    /// * `value`: The value read from the configuration file.
+    /// * `min`: The smallest valid value.
+    /// * `max`: The largest valid value.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    fn clamp<T: PartialOrd + Copy + Display>(&mut self, key: &str, value: T, min: T, max: T) -> T {
+        let clamped = if value < min {
+            min
+        } else if value > max {
+            max
+        } else {
+            return value;
+        };
+
+        let message = format!(
+            "'{}' has to be between {} and {}, using {} instead of {}",
+            key, min, max, clamped, value
+        );
+
+        console::log(format!(
+            "Invalid value in '{}': {}",
+            config::CONFIG_FILE,
+            message
+        ));
+
+        self.failures.push(GameError::Asset {
+            asset: config::CONFIG_FILE.to_string(),
+            message,
+        });
+
+        clamped
+    }
+
+    /// Returns the width of the viewport, in which the
+    /// part of the map around the player is shown.
+    pub fn viewport_width(&self) -> i32 {
+        self.window_width
+    }
+
+    /// Returns the height of the viewport, in which the part of
+    /// the map around the player is shown, above the message log.
+    pub fn viewport_height(&self) -> i32 {
+        self.window_height - MESSAGE_LOG_HEIGHT
+    }
+
+    /// Returns the errors of the configuration file, i.e. if it
+    /// couldn't be read or parsed or had invalid values.
+    pub fn failures(&self) -> &[GameError] {
+        &self.failures
+    }
+}
+
+/// Registers the passed `game_config` with the `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [GameConfig] should be registered.
+/// * `game_config`: The [GameConfig] to register.
+///
+pub fn register(ecs: &mut World, game_config: GameConfig) {
+    ecs.insert(game_config);
+}
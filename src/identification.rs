@@ -0,0 +1,173 @@
+//! Module for the identification of items.
+//!
+//! Potions and scrolls are unknown at the start of every run. Instead of
+//! their name, they are displayed with an appearance, e.g. a "Swirly Potion"
+//! or a "Scroll labeled ZELGO MER", that is assigned randomly to every item
+//! definition with an `appearance` in the raws, when a new run is started.
+//! An item is identified for the rest of the run once an item of its kind
+//! has been used, or by reading an Identify Scroll.
+//!
+//! All names of items shown to the player are resolved through the
+//! [Identification], see [Identification::display].
+
+use std::collections::{HashMap, HashSet};
+
+use rltk::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use super::localization;
+use super::raws::Raws;
+use super::Name;
+
+/// The appearances of the unidentified items by the
+/// kind of appearance of their definitions.
+const APPEARANCES: &[(&str, &[&str])] = &[
+    (
+        "potion",
+        &[
+            "swirly", "bubbling", "murky", "glowing", "fizzy", "smoky", "golden", "inky",
+        ],
+    ),
+    (
+        "scroll",
+        &[
+            "zelgo_mer",
+            "xixaxa",
+            "elbib_yloh",
+            "verr_yed",
+            "kirje",
+            "foobie_bletch",
+            "nr_9",
+            "tharr",
+        ],
+    ),
+];
+
+/// The appearance of an unidentified item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Appearance {
+    /// The kind of the appearance, e.g. `potion`.
+    kind: String,
+
+    /// The variant of the `kind`, e.g. `swirly`.
+    variant: String,
+}
+
+impl Appearance {
+    /// Returns the translated name of the appearance, e.g. "Swirly Potion".
+    fn display(&self) -> String {
+        let variant = localization::tr(&format!("appearance.{}.{}", self.kind, self.variant));
+        localization::tr_with(&format!("appearance.{}", self.kind), &[&variant])
+    }
+}
+
+/// Resource tracking the appearances of the unidentified
+/// items and the items identified during the current run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Identification {
+    /// The appearances of the unidentified items by their names.
+    appearances: HashMap<String, Appearance>,
+
+    /// The names of the items identified during the run.
+    identified: HashSet<String>,
+}
+
+impl Identification {
+    /// Creates a new [Identification], that assigns a random
+    /// appearance to every item definition with an `appearance`.
+    ///
+    /// # Arguments
+    /// * `raws`: The [Raws] with the item definitions.
+    /// * `rng`: The [RandomNumberGenerator] shuffling the appearances.
+    ///
+    /// # Notes
+    /// * Items of the same kind never share an appearance. Items
+    ///   left without one, once all are taken, are always identified.
+    ///
+    pub fn new(raws: &Raws, rng: &mut RandomNumberGenerator) -> Self {
+        let mut available: HashMap<&str, Vec<&str>> = APPEARANCES
+            .iter()
+            .map(|(kind, variants)| (*kind, variants.to_vec()))
+            .collect();
+
+        let mut appearances = HashMap::new();
+
+        // The names are sorted, so the same seed assigns the same appearances
+        for name in raws.item_names() {
+            let kind = match raws.item(&name).and_then(|item| item.appearance.as_ref()) {
+                Some(kind) => kind,
+                None => continue,
+            };
+
+            let variants = match available.get_mut(kind.as_str()) {
+                Some(variants) if !variants.is_empty() => variants,
+                _ => continue,
+            };
+
+            let variant = variants.remove(rng.range(0, variants.len()));
+
+            appearances.insert(
+                name.clone(),
+                Appearance {
+                    kind: kind.clone(),
+                    variant: variant.to_string(),
+                },
+            );
+        }
+
+        Identification {
+            appearances,
+            identified: HashSet::new(),
+        }
+    }
+
+    /// Returns `true` if the item with the passed `name` is known
+    /// to the player, i.e. it has been identified or has no appearance.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the item definition, e.g. `"Health Potion"`.
+    ///
+    pub fn is_identified(&self, name: &str) -> bool {
+        !self.appearances.contains_key(name) || self.identified.contains(name)
+    }
+
+    /// Identifies the item with the passed `name` for the rest of the run.
+    /// Returns `true` if the item hasn't been known before.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the item definition, e.g. `"Health Potion"`.
+    ///
+    pub fn identify(&mut self, name: &str) -> bool {
+        !self.is_identified(name) && self.identified.insert(name.to_string())
+    }
+
+    /// Returns the translated name of the entity with the passed [Name]
+    /// as known to the player, i.e. the appearance of unidentified items.
+    ///
+    /// # Arguments
+    /// * `name`: The [Name] of the entity.
+    ///
+    pub fn display(&self, name: &Name) -> String {
+        match self.appearances.get(&name.name) {
+            Some(appearance) if !self.identified.contains(&name.name) => appearance.display(),
+            _ => name.display(),
+        }
+    }
+}
+
+/// Registers a new [Identification] for the next run with the `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Raws] and the
+///   [RandomNumberGenerator] are registered.
+///
+pub fn register(ecs: &mut World) {
+    let identification = {
+        let raws = ecs.fetch::<Raws>();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        Identification::new(&raws, &mut rng)
+    };
+
+    ecs.insert(identification);
+}
@@ -0,0 +1,463 @@
+//! Module for the player's key bindings.
+//!
+//! The [KeyBindings] map every [KeyAction] of the running game to the
+//! keys triggering it. They are stored through the [storage] module as
+//! a json object of action names to key names, e.g. `"move_north": ["W",
+//! "Up"]`, so players on other keyboard layouts can edit the file or
+//! rebind the keys from the options menu. Actions missing in the stored
+//! file keep their default keys.
+
+use std::collections::BTreeMap;
+
+use rltk::{console, VirtualKeyCode};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use super::{localization, storage};
+
+/// The key under which the [KeyBindings] are stored.
+const KEY_BINDINGS_KEY: &str = "keybindings.json";
+
+/// The keys, that can be bound to actions, with
+/// their names used in the stored file and the ui.
+const BINDABLE_KEYS: [(VirtualKeyCode, &str); 67] = [
+    (VirtualKeyCode::A, "A"),
+    (VirtualKeyCode::B, "B"),
+    (VirtualKeyCode::C, "C"),
+    (VirtualKeyCode::D, "D"),
+    (VirtualKeyCode::E, "E"),
+    (VirtualKeyCode::F, "F"),
+    (VirtualKeyCode::G, "G"),
+    (VirtualKeyCode::H, "H"),
+    (VirtualKeyCode::I, "I"),
+    (VirtualKeyCode::J, "J"),
+    (VirtualKeyCode::K, "K"),
+    (VirtualKeyCode::L, "L"),
+    (VirtualKeyCode::M, "M"),
+    (VirtualKeyCode::N, "N"),
+    (VirtualKeyCode::O, "O"),
+    (VirtualKeyCode::P, "P"),
+    (VirtualKeyCode::Q, "Q"),
+    (VirtualKeyCode::R, "R"),
+    (VirtualKeyCode::S, "S"),
+    (VirtualKeyCode::T, "T"),
+    (VirtualKeyCode::U, "U"),
+    (VirtualKeyCode::V, "V"),
+    (VirtualKeyCode::W, "W"),
+    (VirtualKeyCode::X, "X"),
+    (VirtualKeyCode::Y, "Y"),
+    (VirtualKeyCode::Z, "Z"),
+    (VirtualKeyCode::Key0, "0"),
+    (VirtualKeyCode::Key1, "1"),
+    (VirtualKeyCode::Key2, "2"),
+    (VirtualKeyCode::Key3, "3"),
+    (VirtualKeyCode::Key4, "4"),
+    (VirtualKeyCode::Key5, "5"),
+    (VirtualKeyCode::Key6, "6"),
+    (VirtualKeyCode::Key7, "7"),
+    (VirtualKeyCode::Key8, "8"),
+    (VirtualKeyCode::Key9, "9"),
+    (VirtualKeyCode::Numpad0, "Numpad0"),
+    (VirtualKeyCode::Numpad1, "Numpad1"),
+    (VirtualKeyCode::Numpad2, "Numpad2"),
+    (VirtualKeyCode::Numpad3, "Numpad3"),
+    (VirtualKeyCode::Numpad4, "Numpad4"),
+    (VirtualKeyCode::Numpad5, "Numpad5"),
+    (VirtualKeyCode::Numpad6, "Numpad6"),
+    (VirtualKeyCode::Numpad7, "Numpad7"),
+    (VirtualKeyCode::Numpad8, "Numpad8"),
+    (VirtualKeyCode::Numpad9, "Numpad9"),
+    (VirtualKeyCode::Up, "Up"),
+    (VirtualKeyCode::Down, "Down"),
+    (VirtualKeyCode::Left, "Left"),
+    (VirtualKeyCode::Right, "Right"),
+    (VirtualKeyCode::Space, "Space"),
+    (VirtualKeyCode::Tab, "Tab"),
+    (VirtualKeyCode::Escape, "Escape"),
+    (VirtualKeyCode::Period, "Period"),
+    (VirtualKeyCode::Comma, "Comma"),
+    (VirtualKeyCode::Semicolon, "Semicolon"),
+    (VirtualKeyCode::Slash, "Slash"),
+    (VirtualKeyCode::Minus, "Minus"),
+    (VirtualKeyCode::F1, "F1"),
+    (VirtualKeyCode::F2, "F2"),
+    (VirtualKeyCode::F3, "F3"),
+    (VirtualKeyCode::F4, "F4"),
+    (VirtualKeyCode::F5, "F5"),
+    (VirtualKeyCode::F6, "F6"),
+    (VirtualKeyCode::F7, "F7"),
+    (VirtualKeyCode::F8, "F8"),
+    (VirtualKeyCode::F9, "F9"),
+];
+
+/// Returns the name of the passed `key`, or
+/// `None` if the `key` can't be bound.
+///
+/// # Arguments
+/// * `key`: The key whose name should be returned.
+///
+pub fn key_name(key: VirtualKeyCode) -> Option<&'static str> {
+    BINDABLE_KEYS
+        .iter()
+        .find(|(bindable, _)| *bindable == key)
+        .map(|(_, name)| *name)
+}
+
+/// Returns the key with the passed `name`, or
+/// `None` if no bindable key has the `name`.
+///
+/// # Arguments
+/// * `name`: The name of the key, e.g. `Numpad8`.
+///
+fn key_by_name(name: &str) -> Option<VirtualKeyCode> {
+    BINDABLE_KEYS
+        .iter()
+        .find(|(_, bindable)| bindable.eq_ignore_ascii_case(name))
+        .map(|(key, _)| *key)
+}
+
+/// Enum describing the actions of the running
+/// game, that are triggered by a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAction {
+    /// Moves the player one tile up.
+    MoveNorth,
+
+    /// Moves the player one tile down.
+    MoveSouth,
+
+    /// Moves the player one tile to the left.
+    MoveWest,
+
+    /// Moves the player one tile to the right.
+    MoveEast,
+
+    /// Moves the player one tile up and to the left.
+    MoveNorthWest,
+
+    /// Moves the player one tile up and to the right.
+    MoveNorthEast,
+
+    /// Moves the player one tile down and to the left.
+    MoveSouthWest,
+
+    /// Moves the player one tile down and to the right.
+    MoveSouthEast,
+
+    /// Waits for a turn.
+    Wait,
+
+    /// Rests until the player has recovered.
+    Rest,
+
+    /// Descends the stairs the player stands on.
+    Descend,
+
+    /// Picks up the item the player stands on.
+    PickUp,
+
+    /// Starts or stops sneaking.
+    Sneak,
+
+    /// Bashes the closed door next to the player.
+    Bash,
+
+    /// Fires the player's ranged weapon.
+    Fire,
+
+    /// Opens the inventory.
+    Inventory,
+
+    /// Opens the spell menu.
+    CastSpell,
+
+    /// Opens the message log history.
+    LogHistory,
+
+    /// Opens the quest journal.
+    QuestJournal,
+
+    /// Opens the pause dialog.
+    Pause,
+
+    /// Toggles the performance overlay.
+    ToggleDiagnostics,
+
+    /// Writes the performance timings to the log.
+    DumpDiagnostics,
+}
+
+impl KeyAction {
+    /// All actions in the order of their display.
+    pub const ALL: [KeyAction; 22] = [
+        KeyAction::MoveNorth,
+        KeyAction::MoveSouth,
+        KeyAction::MoveWest,
+        KeyAction::MoveEast,
+        KeyAction::MoveNorthWest,
+        KeyAction::MoveNorthEast,
+        KeyAction::MoveSouthWest,
+        KeyAction::MoveSouthEast,
+        KeyAction::Wait,
+        KeyAction::Rest,
+        KeyAction::Descend,
+        KeyAction::PickUp,
+        KeyAction::Sneak,
+        KeyAction::Bash,
+        KeyAction::Fire,
+        KeyAction::Inventory,
+        KeyAction::CastSpell,
+        KeyAction::LogHistory,
+        KeyAction::QuestJournal,
+        KeyAction::Pause,
+        KeyAction::ToggleDiagnostics,
+        KeyAction::DumpDiagnostics,
+    ];
+
+    /// Returns the keys bound to the action by default.
+    fn default_keys(&self) -> Vec<VirtualKeyCode> {
+        match self {
+            KeyAction::MoveNorth => vec![
+                VirtualKeyCode::W,
+                VirtualKeyCode::Up,
+                VirtualKeyCode::Numpad8,
+                VirtualKeyCode::K,
+            ],
+            KeyAction::MoveSouth => vec![
+                VirtualKeyCode::S,
+                VirtualKeyCode::Down,
+                VirtualKeyCode::Numpad2,
+                VirtualKeyCode::J,
+            ],
+            KeyAction::MoveWest => vec![
+                VirtualKeyCode::A,
+                VirtualKeyCode::Left,
+                VirtualKeyCode::Numpad4,
+                VirtualKeyCode::H,
+            ],
+            KeyAction::MoveEast => vec![
+                VirtualKeyCode::D,
+                VirtualKeyCode::Right,
+                VirtualKeyCode::Numpad6,
+                VirtualKeyCode::L,
+            ],
+            KeyAction::MoveNorthWest => vec![VirtualKeyCode::Q, VirtualKeyCode::Numpad7],
+            KeyAction::MoveNorthEast => vec![VirtualKeyCode::E, VirtualKeyCode::Numpad9],
+            KeyAction::MoveSouthWest => vec![VirtualKeyCode::Y, VirtualKeyCode::Numpad1],
+            KeyAction::MoveSouthEast => vec![VirtualKeyCode::X, VirtualKeyCode::Numpad3],
+            KeyAction::Wait => vec![VirtualKeyCode::Space, VirtualKeyCode::Numpad5],
+            KeyAction::Rest => vec![VirtualKeyCode::R],
+            KeyAction::Descend => vec![VirtualKeyCode::Period],
+            KeyAction::PickUp => vec![VirtualKeyCode::G],
+            KeyAction::Sneak => vec![VirtualKeyCode::C],
+            KeyAction::Bash => vec![VirtualKeyCode::B],
+            KeyAction::Fire => vec![VirtualKeyCode::F],
+            KeyAction::Inventory => vec![VirtualKeyCode::I],
+            KeyAction::CastSpell => vec![VirtualKeyCode::Z],
+            KeyAction::LogHistory => vec![VirtualKeyCode::P],
+            KeyAction::QuestJournal => vec![VirtualKeyCode::O],
+            KeyAction::Pause => vec![VirtualKeyCode::Escape],
+            KeyAction::ToggleDiagnostics => vec![VirtualKeyCode::F3],
+            KeyAction::DumpDiagnostics => vec![VirtualKeyCode::F4],
+        }
+    }
+
+    /// Returns the translated description of the action.
+    pub fn description(&self) -> String {
+        let id = match self {
+            KeyAction::MoveNorth => "keys.move_north",
+            KeyAction::MoveSouth => "keys.move_south",
+            KeyAction::MoveWest => "keys.move_west",
+            KeyAction::MoveEast => "keys.move_east",
+            KeyAction::MoveNorthWest => "keys.move_north_west",
+            KeyAction::MoveNorthEast => "keys.move_north_east",
+            KeyAction::MoveSouthWest => "keys.move_south_west",
+            KeyAction::MoveSouthEast => "keys.move_south_east",
+            KeyAction::Wait => "keys.wait",
+            KeyAction::Rest => "keys.rest",
+            KeyAction::Descend => "keys.descend",
+            KeyAction::PickUp => "keys.pick_up",
+            KeyAction::Sneak => "keys.sneak",
+            KeyAction::Bash => "keys.bash",
+            KeyAction::Fire => "keys.fire",
+            KeyAction::Inventory => "keys.inventory",
+            KeyAction::CastSpell => "keys.cast_spell",
+            KeyAction::LogHistory => "keys.log_history",
+            KeyAction::QuestJournal => "keys.quest_journal",
+            KeyAction::Pause => "keys.pause",
+            KeyAction::ToggleDiagnostics => "keys.toggle_diagnostics",
+            KeyAction::DumpDiagnostics => "keys.dump_diagnostics",
+        };
+
+        localization::tr(id)
+    }
+
+    /// Returns the `(x, y)` delta of the movement actions,
+    /// or `None` if the action doesn't move the player.
+    pub fn direction(&self) -> Option<(i32, i32)> {
+        match self {
+            KeyAction::MoveNorth => Some((0, -1)),
+            KeyAction::MoveSouth => Some((0, 1)),
+            KeyAction::MoveWest => Some((-1, 0)),
+            KeyAction::MoveEast => Some((1, 0)),
+            KeyAction::MoveNorthWest => Some((-1, -1)),
+            KeyAction::MoveNorthEast => Some((1, -1)),
+            KeyAction::MoveSouthWest => Some((-1, 1)),
+            KeyAction::MoveSouthEast => Some((1, 1)),
+            _ => None,
+        }
+    }
+}
+
+/// Resource mapping every [KeyAction] to the keys triggering it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    /// The keys bound to the actions.
+    bindings: BTreeMap<KeyAction, Vec<VirtualKeyCode>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            bindings: KeyAction::ALL
+                .iter()
+                .map(|action| (*action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Reads the stored [KeyBindings], or returns the default
+    /// [KeyBindings] if none are stored or they can't be read.
+    ///
+    /// # Notes
+    /// * Errors and unknown key names are reported to the console.
+    ///
+    pub fn load() -> Self {
+        let data = match storage::read(KEY_BINDINGS_KEY) {
+            Ok(Some(data)) => data,
+            Ok(None) => return KeyBindings::default(),
+            Err(error) => {
+                console::log(error.to_string());
+                return KeyBindings::default();
+            }
+        };
+
+        let stored = match serde_json::from_str::<BTreeMap<KeyAction, Vec<String>>>(&data) {
+            Ok(stored) => stored,
+            Err(err) => {
+                console::log(format!("Unable to parse the key bindings: {}", err));
+                return KeyBindings::default();
+            }
+        };
+
+        let mut key_bindings = KeyBindings::default();
+
+        for (action, names) in stored {
+            let keys = names
+                .iter()
+                .filter_map(|name| {
+                    let key = key_by_name(name);
+
+                    if key.is_none() {
+                        console::log(format!("Unknown key '{}' in the key bindings", name));
+                    }
+
+                    key
+                })
+                .collect();
+
+            key_bindings.bindings.insert(action, keys);
+        }
+
+        key_bindings
+    }
+
+    /// Stores the [KeyBindings], so they are kept between sessions.
+    ///
+    /// # Notes
+    /// * Errors are reported to the console.
+    ///
+    pub fn save(&self) {
+        let stored: BTreeMap<KeyAction, Vec<&str>> = self
+            .bindings
+            .iter()
+            .map(|(action, keys)| {
+                (
+                    *action,
+                    keys.iter().filter_map(|key| key_name(*key)).collect(),
+                )
+            })
+            .collect();
+
+        let result = serde_json::to_string_pretty(&stored)
+            .map_err(|err| err.to_string())
+            .and_then(|data| {
+                storage::write(KEY_BINDINGS_KEY, &data).map_err(|err| err.to_string())
+            });
+
+        if let Err(message) = result {
+            console::log(format!("Unable to save the key bindings: {}", message));
+        }
+    }
+
+    /// Returns the action bound to the passed `key`, if there is one.
+    ///
+    /// # Arguments
+    /// * `key`: The pressed key.
+    ///
+    pub fn action(&self, key: VirtualKeyCode) -> Option<KeyAction> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(action, _)| *action)
+    }
+
+    /// Returns the keys bound to the passed `action`.
+    ///
+    /// # Arguments
+    /// * `action`: The [KeyAction] whose keys should be returned.
+    ///
+    pub fn keys(&self, action: KeyAction) -> &[VirtualKeyCode] {
+        self.bindings
+            .get(&action)
+            .map_or(&[], |keys| keys.as_slice())
+    }
+
+    /// Binds the passed `key` to the `action` as its first key. The
+    /// `key` is removed from all other actions, so every key triggers
+    /// a single action.
+    ///
+    /// # Arguments
+    /// * `action`: The [KeyAction] the `key` should trigger.
+    /// * `key`: The key to bind.
+    ///
+    pub fn bind(&mut self, action: KeyAction, key: VirtualKeyCode) {
+        for keys in self.bindings.values_mut() {
+            keys.retain(|bound| *bound != key);
+        }
+
+        self.bindings.entry(action).or_default().insert(0, key);
+    }
+
+    /// Removes all keys bound to the passed `action`.
+    ///
+    /// # Arguments
+    /// * `action`: The [KeyAction] whose keys should be removed.
+    ///
+    pub fn clear(&mut self, action: KeyAction) {
+        self.bindings.insert(action, Vec::new());
+    }
+}
+
+/// Registers the passed `key_bindings` with the `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [KeyBindings] should be registered.
+/// * `key_bindings`: The [KeyBindings] to register.
+///
+pub fn register(ecs: &mut World, key_bindings: KeyBindings) {
+    ecs.insert(key_bindings);
+}
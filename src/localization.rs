@@ -0,0 +1,325 @@
+//! Module for the translation of all player facing texts.
+//!
+//! Texts are referenced by message ids, e.g. `combat.hit`, and looked up in
+//! the table of the selected language. Each language is a flat JSON object
+//! mapping message ids to texts, which may contain numbered placeholders
+//! like `{0}` that are replaced by the arguments of [tr_with].
+//!
+//! The languages shipped with the game are embedded into the binary. On
+//! native targets every `<code>.json` file in the [config::LANGUAGE_DIRECTORY]
+//! is loaded on startup as well, so community translations can be added or
+//! extended without recompiling the game. Files for an embedded language
+//! only replace the texts they define.
+//!
+//! Missing texts fall back to the [DEFAULT_LANGUAGE] and, if they are missing
+//! there as well, to the message id itself, so an incomplete translation
+//! never hides information from the player.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{OnceLock, RwLock, RwLockReadGuard};
+
+use rltk::console;
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::config;
+use super::exceptions::{GameError, GameResult};
+
+/// Code of the language used when no other language is
+/// selected and for all texts missing in a translation.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// File extension of the language files.
+#[cfg(not(target_arch = "wasm32"))]
+pub const LANGUAGE_EXTENSION: &str = "json";
+
+/// Message id of the name of a language in its own table.
+const LANGUAGE_NAME: &str = "language.name";
+
+/// The languages embedded into the binary as `(code, source)` pairs.
+const EMBEDDED_LANGUAGES: &[(&str, &str)] = &[
+    ("en", include_str!("../data/lang/en.json")),
+    ("de", include_str!("../data/lang/de.json")),
+];
+
+/// All loaded language tables and the selected language.
+struct Catalog {
+    /// The texts of every language by their message ids,
+    /// keyed by the code of the language.
+    languages: HashMap<String, HashMap<String, String>>,
+
+    /// Code of the selected language.
+    selected: String,
+
+    /// The errors of all language files, that
+    /// couldn't be read or parsed.
+    failures: Vec<GameError>,
+}
+
+impl Catalog {
+    /// Creates a new [Catalog] with the embedded languages and
+    /// the language files found in the [config::LANGUAGE_DIRECTORY]
+    /// on native targets.
+    ///
+    /// # Panics
+    /// * If an embedded language is malformed.
+    ///
+    fn new() -> Self {
+        let mut catalog = Catalog {
+            languages: HashMap::new(),
+            selected: DEFAULT_LANGUAGE.to_string(),
+            failures: Vec::new(),
+        };
+
+        for (code, source) in EMBEDDED_LANGUAGES.iter() {
+            let texts = serde_json::from_str(source).expect("An embedded language is malformed!");
+            catalog.merge(code, texts);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        catalog.load_directory(config::LANGUAGE_DIRECTORY);
+
+        catalog
+    }
+
+    /// Adds the `texts` to the table of the language with the passed
+    /// `code`, replacing already existing texts with the same id.
+    ///
+    /// # Arguments
+    /// * `code`: The code of the language, e.g. `en`.
+    /// * `texts`: The texts by their message ids.
+    ///
+    fn merge(&mut self, code: &str, texts: HashMap<String, String>) {
+        self.languages
+            .entry(code.to_string())
+            .or_default()
+            .extend(texts);
+    }
+
+    /// Loads all language files with the [LANGUAGE_EXTENSION] from the
+    /// passed `directory`. The file name without extension is used as
+    /// the code of the language. Does nothing if the `directory` doesn't
+    /// exist.
+    ///
+    /// # Arguments
+    /// * `directory`: The directory containing the language files.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_directory(&mut self, directory: &str) {
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            let is_language = path
+                .extension()
+                .is_some_and(|extension| extension == LANGUAGE_EXTENSION);
+
+            let code = path.file_stem().and_then(|stem| stem.to_str());
+
+            if let (true, Some(code)) = (is_language, code) {
+                let texts = std::fs::read_to_string(&path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|source| {
+                        serde_json::from_str(&source).map_err(|err| err.to_string())
+                    });
+
+                match texts {
+                    Ok(texts) => self.merge(code, texts),
+                    Err(message) => {
+                        console::log(format!("Unable to load '{}': {}", path.display(), message));
+                        self.failures.push(GameError::Asset {
+                            asset: path.display().to_string(),
+                            message,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the text of the message `id` in the selected language,
+    /// falling back to the [DEFAULT_LANGUAGE] and the `id` itself.
+    ///
+    /// # Arguments
+    /// * `id`: The message id of the text.
+    ///
+    fn lookup<'a>(&'a self, id: &'a str) -> &'a str {
+        self.find(&self.selected, id)
+            .or_else(|| self.find(DEFAULT_LANGUAGE, id))
+            .unwrap_or(id)
+    }
+
+    /// Returns the text of the message `id` in the language with
+    /// the passed `code`, if the language defines it.
+    ///
+    /// # Arguments
+    /// * `code`: The code of the language.
+    /// * `id`: The message id of the text.
+    ///
+    fn find(&self, code: &str, id: &str) -> Option<&str> {
+        self.languages
+            .get(code)
+            .and_then(|texts| texts.get(id))
+            .map(|text| text.as_str())
+    }
+}
+
+/// Returns the lock guarding the global [Catalog],
+/// which is loaded on first access.
+fn catalog() -> &'static RwLock<Catalog> {
+    static CATALOG: OnceLock<RwLock<Catalog>> = OnceLock::new();
+    CATALOG.get_or_init(|| RwLock::new(Catalog::new()))
+}
+
+/// Locks the global [Catalog] for reading.
+fn read_catalog() -> RwLockReadGuard<'static, Catalog> {
+    catalog()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Returns the text of the message `id` in the selected language.
+///
+/// # Arguments
+/// * `id`: The message id of the text, e.g. `log.intro`.
+///
+/// # Notes
+/// * Missing texts fall back to the [DEFAULT_LANGUAGE]
+///   and then to the `id` itself.
+///
+pub fn tr(id: &str) -> String {
+    read_catalog().lookup(id).to_string()
+}
+
+/// Returns the text of the message `id` in the selected language
+/// with its numbered placeholders replaced by the `args`, i.e. `{0}`
+/// is replaced by the first argument, `{1}` by the second and so on.
+///
+/// # Arguments
+/// * `id`: The message id of the text, e.g. `combat.hit`.
+/// * `args`: The values inserted into the placeholders.
+///
+/// # See also
+/// * [tr]
+///
+pub fn tr_with(id: &str, args: &[&dyn Display]) -> String {
+    args.iter().enumerate().fold(tr(id), |text, (index, arg)| {
+        text.replace(&format!("{{{}}}", index), &arg.to_string())
+    })
+}
+
+/// Returns the translated display name of an entity, that is named
+/// `name` in the `entity_factory`, or the `name` itself if no
+/// translation exists, e.g. for names chosen by the player.
+///
+/// # Arguments
+/// * `name`: The name of the entity.
+///
+pub fn entity_name(name: &str) -> String {
+    let id = format!("entity.{}", name);
+    let catalog = read_catalog();
+
+    catalog
+        .find(&catalog.selected, &id)
+        .or_else(|| catalog.find(DEFAULT_LANGUAGE, &id))
+        .unwrap_or(name)
+        .to_string()
+}
+
+/// Returns the translated description of an entity, that is named
+/// `name` in the `entity_factory`, or `None` if it has none.
+///
+/// # Arguments
+/// * `name`: The name of the entity.
+///
+pub fn entity_description(name: &str) -> Option<String> {
+    let id = format!("description.{}", name);
+    let catalog = read_catalog();
+
+    catalog
+        .find(&catalog.selected, &id)
+        .or_else(|| catalog.find(DEFAULT_LANGUAGE, &id))
+        .map(|description| description.to_string())
+}
+
+/// Selects the language with the passed `code` for all following texts.
+///
+/// # Arguments
+/// * `code`: The code of the language, e.g. `de`.
+///
+/// # Errors
+/// * If no language with the `code` is loaded.
+///
+pub fn set_language(code: &str) -> GameResult<()> {
+    let mut catalog = catalog()
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    if !catalog.languages.contains_key(code) {
+        return Err(GameError::Asset {
+            asset: code.to_string(),
+            message: "Unknown language".to_string(),
+        });
+    }
+
+    catalog.selected = code.to_string();
+    Ok(())
+}
+
+/// Returns the code of the selected language.
+pub fn language() -> String {
+    read_catalog().selected.clone()
+}
+
+/// Returns the codes of all loaded languages,
+/// starting with the [DEFAULT_LANGUAGE].
+pub fn languages() -> Vec<String> {
+    let mut codes: Vec<String> = read_catalog().languages.keys().cloned().collect();
+
+    codes.sort_by_key(|code| (code != DEFAULT_LANGUAGE, code.clone()));
+    codes
+}
+
+/// Returns the name of the language with the passed `code` in
+/// that language, or the `code` itself if it doesn't define one.
+///
+/// # Arguments
+/// * `code`: The code of the language.
+///
+pub fn language_name(code: &str) -> String {
+    read_catalog()
+        .find(code, LANGUAGE_NAME)
+        .unwrap_or(code)
+        .to_string()
+}
+
+/// Selects the language following the selected one in the
+/// order of [languages] and returns its code.
+pub fn cycle_language() -> String {
+    let codes = languages();
+    let selected = language();
+
+    let next = codes
+        .iter()
+        .position(|code| *code == selected)
+        .map_or(0, |index| (index + 1) % codes.len());
+
+    let code = codes[next].clone();
+
+    if let Err(error) = set_language(&code) {
+        console::log(error.to_string());
+    }
+
+    code
+}
+
+/// Returns the errors of all language files, that
+/// couldn't be read or parsed while loading them.
+pub fn failures() -> Vec<GameError> {
+    read_catalog().failures.clone()
+}
@@ -3,16 +3,50 @@
 
 //! D&D and NetHack inspired dungeon crawler written in rust.
 
-use rltk::RltkBuilder;
-use specs::prelude::*;
+use rltk::{console, RltkBuilder};
 
+use exceptions::{GameError, GameResult};
+
+mod ammunition;
+mod assets;
+mod biome;
+mod camera;
 mod config;
+mod containers;
+mod crash;
+mod daily;
+mod debug_console;
+mod diagnostics;
+mod doors;
 mod entity_factory;
+mod equipment;
 mod exceptions;
+mod faction;
+mod fov;
+mod frame_limiter;
+mod game_config;
+mod identification;
+mod keybindings;
+mod localization;
+mod map_builders;
+mod particles;
+mod pathing;
+mod quests;
+mod raws;
 mod rng;
+mod saveload;
+mod scripting;
+mod settings;
+mod shop;
+mod spatial_index;
 mod spawn_controller;
+mod spawn_table;
+mod storage;
 mod swatch;
+mod touch;
 mod ui_controller;
+mod valuation;
+mod wizard;
 
 mod state;
 pub use state::*;
@@ -20,6 +54,9 @@ pub use state::*;
 mod components;
 pub use components::*;
 
+mod events;
+pub use events::*;
+
 mod player;
 pub use player::*;
 
@@ -44,65 +81,231 @@ pub use dialog::*;
 mod data;
 pub use data::*;
 
+mod dispatcher;
+pub use dispatcher::*;
+
 mod scribbles;
 pub use scribbles::*;
 
+#[cfg(test)]
+mod test_support;
+
+/// The usage of the game's command line arguments.
+const USAGE: &str = "Usage: b_ruge [--seed <number>] [--fullscreen] [--wizard] [--help]
+
+Options:
+    --seed <number>  Starts every run with the seed, for reproducible runs
+    --fullscreen     Opens the game in fullscreen mode
+    --wizard         Enables the cheats of the wizard mode, opened with CTRL + W,
+                     and the debug console, opened with the tilde key
+    --help           Prints this help and exits";
+
+/// The options passed to the game on the command line.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Arguments {
+    /// The seed every run starts with, or `None`
+    /// if every run should get a new seed.
+    seed: Option<u64>,
+
+    /// Flag indicating whether or not the game
+    /// should be opened in fullscreen mode.
+    fullscreen: bool,
+
+    /// Flag indicating whether or not the
+    /// wizard mode should be enabled.
+    wizard: bool,
+
+    /// Flag indicating whether or not only the
+    /// [USAGE] should be printed.
+    help: bool,
+}
+
+impl Arguments {
+    /// Parses the passed command line `args`, without the
+    /// name of the executable, and returns the [Arguments].
+    ///
+    /// # Arguments
+    /// * `args`: The command line arguments, e.g. `--seed 42`.
+    ///
+    /// # Errors
+    /// * If an argument is unknown or the seed is missing
+    ///   or isn't a number.
+    ///
+    fn parse<I>(args: I) -> GameResult<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut arguments = Arguments::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = args.next().ok_or_else(|| GameError::Argument {
+                        argument: arg.clone(),
+                        message: "The seed is missing".to_string(),
+                    })?;
+                    let seed = value.parse().map_err(|_| GameError::Argument {
+                        argument: arg.clone(),
+                        message: format!("'{}' is not a valid seed", value),
+                    })?;
+
+                    arguments.seed = Some(seed);
+                }
+                "--fullscreen" => arguments.fullscreen = true,
+                "--wizard" => arguments.wizard = true,
+                "--help" | "-h" => arguments.help = true,
+                _ => {
+                    return Err(GameError::Argument {
+                        argument: arg,
+                        message: "Unknown argument".to_string(),
+                    })
+                }
+            }
+        }
+
+        Ok(arguments)
+    }
+}
+
 /// Bootstraps the game, registers components, initiates systems,
 /// creates entities and starts the rendering. After the bootstrapping
 /// it calls the [rltk::main_loop] to display the game window.
 fn main() -> rltk::BError {
+    // Parse the command line, e.g. to start with a fixed seed
+    let arguments = Arguments::parse(std::env::args().skip(1)).inspect_err(|error| {
+        console::log(format!("{}\n\n{}", error, USAGE));
+    })?;
+
+    if arguments.help {
+        console::log(USAGE);
+        return Ok(());
+    }
+
     config::log_starting_message();
 
-    // Create a new terminal
-    let mut terminal = RltkBuilder::simple(config::WINDOW_WIDTH, config::WINDOW_HEIGHT)?
+    // Write a crash report if the game panics
+    crash::install_hook();
+
+    // Load the player's settings and the configuration file
+    let settings = settings::Settings::load();
+    let game_config = game_config::GameConfig::load();
+
+    // Create a new terminal, the input events are
+    // needed to detect whether the window has the focus
+    let mut builder = RltkBuilder::simple(game_config.window_width, game_config.window_height)?
         .with_title(config::GAME_NAME)
-        .with_fullscreen(false)
-        .build()?;
+        .with_fullscreen(arguments.fullscreen)
+        .with_advanced_input(true);
 
-    // Enable scan lines for the nostalgic feel.
-    // TODO: Need to find a possibility to insert custom shaders.
-    terminal.with_post_scanlines(true);
+    if settings.fps_cap > 0 {
+        builder = builder.with_fps_cap(settings.fps_cap as f32);
+    }
+
+    let mut terminal = builder.build()?;
+
+    // Apply the visual settings of the player
+    settings.apply(&mut terminal);
+
+    // Handle touch input on the canvas of the web build
+    touch::install();
 
     // Create the initial game state
-    let mut game_state = State { ecs: World::new() };
+    let mut game_state = State::new();
 
-    // Register random number generator
-    rng::register(&mut game_state.ecs);
+    // Register random number generator with the seed of the command line
+    rng::register(&mut game_state.ecs, arguments.seed);
 
-    // Register components
-    register_components(&mut game_state.ecs);
+    // Register the script engine with the content scripts
+    scripting::register(&mut game_state.ecs);
 
-    // Create the game map
-    let map = Map::new(&mut game_state.ecs, config::MAP_WIDTH, config::MAP_HEIGHT);
+    // Register the configuration of the window and the map generation
+    game_config::register(&mut game_state.ecs, game_config);
 
-    // Apply the monster creation to all rooms expect for the first.
-    // The rng is used to choose a random monster to place
-    map.rooms_for_each_skip(1, |_, room| {
-        spawn_controller::spawn_in_room(&mut game_state.ecs, room);
-    });
+    // Register the player's settings
+    settings::register(&mut game_state.ecs, settings);
+
+    // Register the player's key bindings
+    keybindings::register(&mut game_state.ecs, keybindings::KeyBindings::load());
+
+    // Register the performance diagnostics
+    diagnostics::register(&mut game_state.ecs);
 
-    // The player is placed in the center of the first room
-    let player_position = map.rooms[0].center();
+    // Register the wizard mode, if it was enabled on the command line
+    wizard::register(&mut game_state.ecs, arguments.wizard);
 
-    // Create the player
-    let player_entity = entity_factory::new_player(&player_position, &mut game_state.ecs);
+    // Register the entity definitions, the spawn tables and the faction relations
+    raws::register(&mut game_state.ecs);
+    spawn_table::register(&mut game_state.ecs);
+    faction::register(&mut game_state.ecs);
 
-    // Create the games message logger
-    let game_log = GameLog::new();
+    // Register the appearances of the unidentified items
+    identification::register(&mut game_state.ecs);
 
-    // Create the player pathing object
-    let player_pathing = PlayerPathing::new();
+    // Register components
+    register_components(&mut game_state.ecs);
 
-    // Insert the game resources into the ecs
-    game_state.ecs.insert(map);
-    game_state.ecs.insert(player_entity);
-    game_state.ecs.insert(player_position.to_point());
-    game_state.ecs.insert(game_log);
-    game_state.ecs.insert(player_pathing);
+    // The game starts in the main menu, from which
+    // a new game is set up or the saved game is loaded
+    game_state.ecs.insert(RunState::MainMenu {
+        selection: ui_controller::MainMenuSelection::NewGame,
+    });
 
-    // Set the initial processing state of the game
-    game_state.ecs.insert(ProcessingState::Internal);
+    // Report missing or unreadable assets before the game starts
+    assets::register(&mut game_state.ecs);
 
     // Start the main loop
     rltk::main_loop(terminal, game_state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shorthand to parse the passed `args`.
+    fn parse(args: &[&str]) -> GameResult<Arguments> {
+        Arguments::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_without_arguments() {
+        assert_eq!(parse(&[]).unwrap(), Arguments::default());
+    }
+
+    #[test]
+    fn parse_all_arguments() {
+        let arguments = parse(&["--seed", "42", "--fullscreen", "--wizard", "-h"]).unwrap();
+
+        assert_eq!(
+            arguments,
+            Arguments {
+                seed: Some(42),
+                fullscreen: true,
+                wizard: true,
+                help: true,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_keeps_last_seed() {
+        assert_eq!(
+            parse(&["--seed", "1", "--seed", "2"]).unwrap().seed,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_seeds() {
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "abc"]).is_err());
+        assert!(parse(&["--seed", "-1"]).is_err());
+        assert!(parse(&["--seed", "18446744073709551616"]).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_unknown_arguments() {
+        assert!(parse(&["--unknown"]).is_err());
+        assert!(parse(&["42"]).is_err());
+    }
+}
@@ -1,26 +1,126 @@
 //! Game map implementation.
 
 use std::cmp::{max, min};
+use std::collections::VecDeque;
 
-use rltk::{console, Algorithm2D, BaseMap, Point, Rltk, SmallVec};
+use rltk::{
+    console, Algorithm2D, BaseMap, ColorPair, DrawBatch, Point, RandomNumberGenerator, SmallVec,
+    RGB,
+};
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
-use super::{config, pythagoras_distance, rng, Rectangle, TileFactory};
+use super::biome::Biome;
+use super::camera::Camera;
+use super::exceptions::{GameError, GameResult};
+use super::localization;
+use super::spatial_index::{SpatialIndex, TileEntities};
+use super::{
+    config, pythagoras_distance, submit_batch, swatch, Position, Rectangle, Renderable, TileFactory,
+};
 
 /// Enum describing all available tile
 /// types of the game.
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum TileType {
     /// Any floor, walkable.
     FLOOR,
     /// Any wall, not walkable.
     WALL,
+    /// Stairs leading down to the next
+    /// level of the dungeon, walkable.
+    DOWNSTAIRS,
+    /// Deep water, walkable, but entities
+    /// wading through it act more slowly.
+    DEEP_WATER,
+    /// Molten rock, walkable, but it burns
+    /// the entities standing in it.
+    LAVA,
+    /// A chasm, walkable, but the entities entering
+    /// it fall down to the next level of the dungeon.
+    CHASM,
+    /// A paved road, walkable and faster
+    /// to travel along than the floor.
+    ROAD,
+    /// Debris covering the floor, walkable,
+    /// but slow to clamber over.
+    RUBBLE,
+    /// A bridge leading across deep water or a chasm, walkable.
+    BRIDGE,
+    /// The remains of a bashed in door, walkable.
+    BROKEN_DOOR,
+}
+
+impl TileType {
+    /// Returns the translated human readable description
+    /// of the [TileType], e.g. for tooltips.
+    pub fn description(&self) -> String {
+        let id = match self {
+            TileType::FLOOR => "tile.floor",
+            TileType::WALL => "tile.wall",
+            TileType::DOWNSTAIRS => "tile.downstairs",
+            TileType::DEEP_WATER => "tile.deep_water",
+            TileType::LAVA => "tile.lava",
+            TileType::CHASM => "tile.chasm",
+            TileType::ROAD => "tile.road",
+            TileType::RUBBLE => "tile.rubble",
+            TileType::BRIDGE => "tile.bridge",
+            TileType::BROKEN_DOOR => "tile.broken_door",
+        };
+
+        localization::tr(id)
+    }
+
+    /// Returns `true` if entities can enter the tile.
+    pub fn is_walkable(&self) -> bool {
+        *self != TileType::WALL
+    }
+
+    /// Returns `true` if paths may lead across the tile. Chasms are
+    /// walkable, but never part of a path, since entering them
+    /// drops the entity down to the next level.
+    pub fn is_pathable(&self) -> bool {
+        self.is_walkable() && *self != TileType::CHASM
+    }
+
+    /// Returns `true` if fire catches on the tile, i.e. for wooden
+    /// tiles like the remains of a door, see [Map::ignite].
+    pub fn is_flammable(&self) -> bool {
+        *self == TileType::BROKEN_DOOR
+    }
+
+    /// Returns the tile left behind, once the fire on
+    /// the tile has burnt out. Burnt tiles never catch fire again.
+    pub fn burnt(&self) -> TileType {
+        match self {
+            TileType::BROKEN_DOOR => TileType::FLOOR,
+            tile => *tile,
+        }
+    }
+
+    /// Returns the factor by which the cost of entering
+    /// the tile is multiplied in the pathfinding.
+    pub fn movement_cost(&self) -> f32 {
+        match self {
+            TileType::ROAD => 0.5,
+            TileType::DEEP_WATER => 2.0,
+            TileType::RUBBLE => 3.0,
+            TileType::LAVA => 8.0,
+            _ => 1.0,
+        }
+    }
 }
 
 /// Struct representing the map of
 /// a level in the game world.
 /// A tile is represented by a [TileType].
-#[derive(Default, Debug, Clone)]
+///
+/// # Notes
+/// * The caches of the map, i.e. the [Map::tile_contents] and
+///   the render information, aren't serialized and have to be
+///   restored through [Map::restore_caches] after loading.
+///
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Map {
     /// Width of the map in tiles.
     pub width: i32,
@@ -28,12 +128,21 @@ pub struct Map {
     /// Height of the map in tiles.
     pub height: i32,
 
+    /// The level of the dungeon the
+    /// map represents, starting at `1`.
+    pub depth: i32,
+
+    /// The [Biome] the map belongs to, `None` for the town.
+    #[serde(default)]
+    pub biome: Option<Biome>,
+
     /// Vector containing all tiles in the map
     /// represented by a [TileType].
     pub tiles: Vec<TileType>,
 
     /// Vector containing all rooms on the map.
     /// Each room is represented by a [Rectangle].
+    /// Empty for maps without rooms, e.g. caves.
     pub rooms: Vec<Rectangle>,
 
     /// Vector containing all tiles
@@ -51,104 +160,167 @@ pub struct Map {
     /// at the position is walkable or not.
     pub blocked_tiles: Vec<bool>,
 
-    /// Vector over all tiles containing
-    /// a list of entities which are on a
-    /// given tile.
-    pub tile_contents: Vec<Vec<Entity>>,
+    /// Vector containing a bool flag
+    /// for each tile of the map, which
+    /// indicates whether or not an entity
+    /// on the tile blocks the sight through it.
+    #[serde(skip)]
+    pub opaque_tiles: Vec<bool>,
+
+    /// Index of the entities, which
+    /// are on the tiles of the map.
+    #[serde(skip)]
+    pub tile_contents: SpatialIndex,
+
+    /// The rendered [Renderable] of every tile, cached
+    /// between frames. `None` for unexplored tiles.
+    #[serde(skip)]
+    pub render_cache: Vec<Option<Renderable>>,
+
+    /// Indices of all tiles whose entry in the
+    /// [Map::render_cache] is outdated.
+    #[serde(skip)]
+    pub dirty_tiles: Vec<usize>,
+
+    /// The color of the light falling onto every tile,
+    /// black for unlit tiles, see the `LightingSystem`.
+    #[serde(skip)]
+    pub light_levels: Vec<RGB>,
+
+    /// The [TileType::movement_cost] of every tile,
+    /// cached for the pathfinding.
+    #[serde(skip)]
+    pub tile_costs: Vec<f32>,
+
+    /// The fire overlay of the map, holding the remaining
+    /// turns every tile keeps burning, `0` for tiles, that
+    /// aren't on fire, see the `FireSpreadSystem`.
+    #[serde(default)]
+    pub fire: Vec<i32>,
+
+    /// Flag indicating whether or not the player is blinded,
+    /// which suppresses the coloring of the tiles in the fov.
+    #[serde(skip)]
+    pub player_blind: bool,
+
+    /// Flag indicating whether or not every tile is drawn as
+    /// explored, toggled in the wizard mode to inspect the
+    /// generated levels.
+    #[serde(skip)]
+    pub revealed: bool,
 }
 
 impl Map {
     /// Creates a new map with the given `width`
-    /// and `height`.
-    ///
-    /// Adds rooms of random width and height
-    /// at random positions to the map. Rooms
-    /// are connected through vertical and
-    /// horizontal intersections.
+    /// and `height`, that is filled with walls.
     ///
-    /// Every room is represented through a
-    /// [Rectangle].
+    /// The layout of the map is created by the
+    /// `MapBuilder`s of a `BuilderChain`.
     ///
     /// # Arguments
     /// * `width`: The width of the new map.
     /// * `height`: The height of the new map.
+    /// * `depth`: The level of the dungeon the new map represents.
     ///
-    pub fn new(ecs: &mut World, width: i32, height: i32) -> Self {
-        // Create the base map struct
-        let mut map = Map {
+    pub fn new(width: i32, height: i32, depth: i32) -> Self {
+        Map {
             width,
             height,
+            depth,
+            biome: Biome::for_depth(depth),
             tiles: vec![TileType::WALL; width as usize * height as usize],
             rooms: Vec::new(),
             explored_tiles: vec![false; width as usize * height as usize],
             tiles_in_fov: vec![false; width as usize * height as usize],
             blocked_tiles: vec![false; width as usize * height as usize],
-            tile_contents: vec![Vec::new(); width as usize * height as usize],
-        };
+            opaque_tiles: vec![false; width as usize * height as usize],
+            tile_contents: SpatialIndex::new(width as usize * height as usize),
+            render_cache: vec![None; width as usize * height as usize],
+            dirty_tiles: Vec::new(),
+            light_levels: vec![RGB::new(); width as usize * height as usize],
+            tile_costs: vec![1.0; width as usize * height as usize],
+            fire: vec![0; width as usize * height as usize],
+            player_blind: false,
+            revealed: false,
+        }
+    }
+
+    /// Returns the [Position] the player starts the level at, i.e. the
+    /// center of the first room or, for maps without rooms, the walkable
+    /// tile closest to the center of the map.
+    pub fn player_start(&self) -> Position {
+        if let Some(first_room) = self.rooms.first() {
+            return first_room.center();
+        }
 
-        // Create as many rooms as defined in the [GAME_CONFIG]
-        for _ in 0..config::MAX_ROOMS {
-            // Calc the [Rectangle] width and height args
-            let room_width = rng::range(ecs, config::MIN_ROOM_SIZE, config::MAX_ROOM_SIZE);
-            let room_height = rng::range(ecs, config::MIN_ROOM_SIZE, config::MAX_ROOM_SIZE);
+        let center = Point::new(self.width / 2, self.height / 2);
 
-            // Calc the x and y position of the top left corner of the [Rectangle].
-            let x = rng::roll_dice(ecs, 1, width - room_width - 1) - 1;
-            let y = rng::roll_dice(ecs, 1, height - room_height - 1) - 1;
+        (0..self.tiles.len())
+            .filter(|idx| self.tiles[*idx] != TileType::WALL)
+            .map(|idx| Position::new_from_tuple(self.idx_to_coordinates(idx)))
+            .min_by_key(|position| (position.x - center.x).pow(2) + (position.y - center.y).pow(2))
+            .unwrap_or(Position {
+                x: center.x,
+                y: center.y,
+            })
+    }
 
-            // Create the new room
-            let room = Rectangle::new(x, y, room_width, room_height);
+    /// Returns a random tile, that isn't blocked by a wall or an
+    /// entity, or `None` if every tile of the map is blocked.
+    ///
+    /// # Arguments
+    /// * `rng`: The [RandomNumberGenerator] choosing the tile.
+    ///
+    pub fn random_walkable_tile(&self, rng: &mut RandomNumberGenerator) -> Option<Point> {
+        let walkable_tiles = (0..self.blocked_tiles.len())
+            .filter(|idx| !self.blocked_tiles[*idx])
+            .collect::<Vec<_>>();
 
-            // Check if the new room overlaps with any of the existing rooms.
-            let mut can_place = true;
+        if walkable_tiles.is_empty() {
+            return None;
+        }
 
-            for existing_room in map.rooms.iter() {
-                if room.overlaps(existing_room) {
-                    can_place = false;
-                    break;
-                }
-            }
+        let idx = walkable_tiles[rng.range(0, walkable_tiles.len())];
+        let (x, y) = self.idx_to_coordinates(idx);
+
+        Some(Point::new(x, y))
+    }
+
+    /// Returns the walking distance of every tile from the tile
+    /// at the passed `x` and `y` coordinates, or `None` for tiles,
+    /// that can't be reached through horizontal and vertical steps.
+    ///
+    /// # Arguments
+    /// * `x`: The x coordinate of the start tile.
+    /// * `y`: The y coordinate of the start tile.
+    ///
+    pub fn flood_fill(&self, x: i32, y: i32) -> Vec<Option<i32>> {
+        let mut distances = vec![None; self.tiles.len()];
+        let mut open = VecDeque::new();
+
+        if self.check_idx(x, y) && self.get_tile(x, y).is_pathable() {
+            distances[self.coordinates_to_idx(x, y)] = Some(0);
+            open.push_back((x, y, 0));
+        }
 
-            if can_place {
-                // Draw the room
-                map.draw_room(&room);
-
-                // Create the intersections between the new and the previous room.
-                if !map.rooms.is_empty() {
-                    let new_room_center = room.center();
-                    let previous_room_center = map.rooms[map.rooms.len() - 1].center();
-
-                    if rng::range(ecs, 0, 2) == 1 {
-                        map.draw_horizontal_intersection(
-                            previous_room_center.x,
-                            new_room_center.x,
-                            previous_room_center.y,
-                        );
-                        map.draw_vertical_intersection(
-                            previous_room_center.y,
-                            new_room_center.y,
-                            new_room_center.x,
-                        );
-                    } else {
-                        map.draw_vertical_intersection(
-                            previous_room_center.y,
-                            new_room_center.y,
-                            previous_room_center.x,
-                        );
-                        map.draw_horizontal_intersection(
-                            previous_room_center.x,
-                            new_room_center.x,
-                            new_room_center.y,
-                        );
-                    }
+        while let Some((x, y, distance)) = open.pop_front() {
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                .iter()
+                .copied()
+            {
+                if !self.check_idx(nx, ny) || !self.get_tile(nx, ny).is_pathable() {
+                    continue;
                 }
 
-                // Add room to the map.
-                map.rooms.push(room);
+                let idx = self.coordinates_to_idx(nx, ny);
+                if distances[idx].is_none() {
+                    distances[idx] = Some(distance + 1);
+                    open.push_back((nx, ny, distance + 1));
+                }
             }
         }
 
-        map
+        distances
     }
 
     /// Gets the [TileType] stored at the given `x`
@@ -177,6 +349,12 @@ impl Map {
     pub fn set_tile(&mut self, x: i32, y: i32, tile: TileType) -> &Self {
         let idx = self.coordinates_to_idx(x, y);
         self.tiles[idx] = tile;
+        self.tile_costs[idx] = tile.movement_cost();
+
+        if self.explored_tiles[idx] {
+            self.dirty_tiles.push(idx);
+        }
+
         self
     }
 
@@ -206,7 +384,12 @@ impl Map {
     ///
     pub fn set_explored_tile(&mut self, x: i32, y: i32, explored: bool) -> &Self {
         let idx = self.coordinates_to_idx(x, y);
-        self.explored_tiles[idx] = explored;
+
+        if self.explored_tiles[idx] != explored {
+            self.explored_tiles[idx] = explored;
+            self.dirty_tiles.push(idx);
+        }
+
         self
     }
 
@@ -236,14 +419,84 @@ impl Map {
     ///
     pub fn set_tile_in_fov(&mut self, x: i32, y: i32, is_in_fov: bool) -> &Self {
         let idx = self.coordinates_to_idx(x, y);
-        self.tiles_in_fov[idx] = is_in_fov;
+
+        if self.tiles_in_fov[idx] != is_in_fov {
+            self.tiles_in_fov[idx] = is_in_fov;
+            self.dirty_tiles.push(idx);
+        }
+
+        self
+    }
+
+    /// Sets the flag indicating whether or not the player is blinded
+    /// and marks all explored tiles as dirty, if it has changed.
+    ///
+    /// # Arguments
+    /// * `blind`: Flag that indicates if the player is blinded.
+    ///
+    pub fn set_player_blind(&mut self, blind: bool) -> &Self {
+        if self.player_blind != blind {
+            self.player_blind = blind;
+
+            for (idx, explored) in self.explored_tiles.iter().enumerate() {
+                if *explored {
+                    self.dirty_tiles.push(idx);
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Sets the flag indicating whether or not every tile is drawn as
+    /// explored and marks all unexplored tiles as dirty, if it has changed.
+    ///
+    /// # Arguments
+    /// * `revealed`: Flag that indicates if the whole map is revealed.
+    ///
+    pub fn set_revealed(&mut self, revealed: bool) -> &Self {
+        if self.revealed != revealed {
+            self.revealed = revealed;
+
+            for (idx, explored) in self.explored_tiles.iter().enumerate() {
+                if !*explored {
+                    self.dirty_tiles.push(idx);
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Replaces the [Map::light_levels] with the passed `light_levels`
+    /// and marks all tiles, whose light has changed, as dirty.
+    ///
+    /// # Arguments
+    /// * `light_levels`: The color of the light falling onto every tile.
+    ///
+    pub fn set_light_levels(&mut self, light_levels: Vec<RGB>) -> &Self {
+        for (idx, (old, new)) in self
+            .light_levels
+            .iter()
+            .zip(light_levels.iter())
+            .enumerate()
+        {
+            if old != new {
+                self.dirty_tiles.push(idx);
+            }
+        }
+
+        self.light_levels = light_levels;
         self
     }
 
     /// Resets all fov flags back to false.
     pub fn reset_tiles_in_fov(&mut self) -> &Self {
-        for tile in self.tiles_in_fov.iter_mut() {
-            *tile = false;
+        for (idx, tile) in self.tiles_in_fov.iter_mut().enumerate() {
+            if *tile {
+                *tile = false;
+                self.dirty_tiles.push(idx);
+            }
         }
         self
     }
@@ -259,6 +512,37 @@ impl Map {
         self.blocked_tiles[self.coordinates_to_idx(x, y)]
     }
 
+    /// Returns `true` if the tile at the passed `x` and `y`
+    /// position is on fire, `false` otherwise.
+    ///
+    /// # Arguments
+    /// * `x`: X position of the tile to check.
+    /// * `y`: Y position of the tile to check.
+    ///
+    pub fn is_burning(&self, x: i32, y: i32) -> bool {
+        self.fire[self.coordinates_to_idx(x, y)] > 0
+    }
+
+    /// Sets the tile at the passed `x` and `y` position on fire for
+    /// [config::FIRE_DURATION] turns. Returns `true` if the tile has
+    /// caught fire, `false` if it's out of bounds, already burning or
+    /// not [TileType::is_flammable].
+    ///
+    /// # Arguments
+    /// * `x`: X position of the tile to ignite.
+    /// * `y`: Y position of the tile to ignite.
+    ///
+    pub fn ignite(&mut self, x: i32, y: i32) -> bool {
+        if !self.check_idx(x, y) || !self.get_tile(x, y).is_flammable() || self.is_burning(x, y) {
+            return false;
+        }
+
+        let idx = self.coordinates_to_idx(x, y);
+        self.fire[idx] = config::FIRE_DURATION;
+
+        true
+    }
+
     /// Sets the the tile at the given `x` and `y` to the value of `blocked` to
     /// indicate whether or not the tile can be walked into or not.
     ///
@@ -273,6 +557,20 @@ impl Map {
         self
     }
 
+    /// Sets the the tile at the given `x` and `y` to the value of `opaque` to
+    /// indicate whether or not an entity on the tile blocks the sight through it.
+    ///
+    /// # Arguments
+    /// * `x`: X position of the tile to modify.
+    /// * `y`: Y position of the tile to modify.
+    /// * `opaque`: Flag that indicates whether or not the tile is opaque.
+    ///
+    pub fn set_tile_is_opaque(&mut self, x: i32, y: i32, opaque: bool) -> &Self {
+        let idx = self.coordinates_to_idx(x, y);
+        self.opaque_tiles[idx] = opaque;
+        self
+    }
+
     /// Returns `true` if the the tile at the supplied `x`
     /// and `y` position is walkable, `false` otherwise.
     ///
@@ -291,15 +589,88 @@ impl Map {
         }
     }
 
-    /// Returns the list of [Entity]s which are currently on the tile
+    /// Returns `true` if the terrain of the tile at the supplied `x`
+    /// and `y` position is walkable, ignoring all entities on it.
+    ///
+    /// # Arguments
+    /// * `x`: The x coordinate of the tile.
+    /// * `y`: The y coordinate of the tile.
+    ///
+    pub fn is_terrain_walkable(&self, x: i32, y: i32) -> bool {
+        self.check_idx(x, y) && self.get_tile(x, y).is_walkable()
+    }
+
+    /// Returns the indices of all neighbouring tiles of the tile at the
+    /// passed `idx`, that are walkable according to `is_walkable`,
+    /// together with the cost of moving there, which is scaled by
+    /// the cost of the target tile in the [Map::tile_costs]. Chasms
+    /// are never exits.
+    ///
+    /// # Arguments
+    /// * `idx`: The index of the tile whose exits should be returned.
+    /// * `is_walkable`: Returns `true` if the tile at the passed
+    ///   `x` and `y` position can be walked into.
+    ///
+    pub fn exits_where<F>(&self, idx: usize, is_walkable: F) -> SmallVec<[(usize, f32); 10]>
+    where
+        F: Fn(i32, i32) -> bool,
+    {
+        let mut walkable_tiles = SmallVec::new();
+
+        let (x, y) = self.idx_to_coordinates(idx);
+        let width = self.width as usize;
+
+        // Chasms are never part of a path, all other tiles
+        // scale the cost of the step by their movement cost
+        let mut push = |target: usize, distance: f32| {
+            if self.tiles[target].is_pathable() {
+                walkable_tiles.push((target, distance * self.tile_costs[target]));
+            }
+        };
+
+        // Check tiles in cardinal directions
+        if is_walkable(x - 1, y) {
+            push(idx - 1, 1.0);
+        }
+        if is_walkable(x + 1, y) {
+            push(idx + 1, 1.0);
+        }
+        if is_walkable(x, y - 1) {
+            push(idx - width, 1.0);
+        }
+        if is_walkable(x, y + 1) {
+            push(idx + width, 1.0);
+        }
+
+        // Check tiles in diagonal directions
+        if is_walkable(x - 1, y - 1) {
+            push((idx - width) - 1, 1.45);
+        }
+
+        if is_walkable(x + 1, y - 1) {
+            push((idx - width) + 1, 1.45);
+        }
+
+        if is_walkable(x - 1, y + 1) {
+            push((idx + width) - 1, 1.45);
+        }
+
+        if is_walkable(x + 1, y + 1) {
+            push((idx + width) + 1, 1.45);
+        }
+
+        walkable_tiles
+    }
+
+    /// Returns the [Entity]s which are currently on the tile
     /// at the given `x` and `y` position.
     ///
     /// # Arguments
     /// * `x`: X position of the tile whos content should be returned.
     /// * `y`: Y position of the tile whos content should be returned.
     ///
-    pub fn tile_contents_get(&self, x: i32, y: i32) -> &Vec<Entity> {
-        &self.tile_contents[self.coordinates_to_idx(x, y)]
+    pub fn tile_contents_get(&self, x: i32, y: i32) -> TileEntities<'_> {
+        self.tile_contents.get(self.coordinates_to_idx(x, y))
     }
 
     /// Adds the passed `entity` to the contents of the tile at given
@@ -312,7 +683,7 @@ impl Map {
     ///
     pub fn tile_contents_push(&mut self, x: i32, y: i32, entity: Entity) -> &Self {
         let idx = self.coordinates_to_idx(x, y);
-        self.tile_contents[idx].push(entity);
+        self.tile_contents.insert(idx, entity);
         self
     }
 
@@ -327,15 +698,13 @@ impl Map {
     ///
     pub fn tile_contents_remove(&mut self, x: i32, y: i32, entity: Entity) -> &Self {
         let idx = self.coordinates_to_idx(x, y);
-        self.tile_contents[idx].retain(|element| *element != entity);
+        self.tile_contents.remove(idx, entity);
         self
     }
 
     /// Clears the contents of all tiles on the map.
     pub fn clear_tile_contents(&mut self) -> &Self {
-        for contents in self.tile_contents.iter_mut() {
-            contents.clear();
-        }
+        self.tile_contents.clear();
 
         self
     }
@@ -379,28 +748,21 @@ impl Map {
     }
 
     /// Checks if the given coordinate is within the bounds of the
-    /// map. Returns a [Result], which contains the map index at the
-    /// given coordinate. Otherwise a appropriate error message is returned.
+    /// map. Returns a [GameResult], which contains the map index at the
+    /// given coordinate. Otherwise a [GameError::MapOutOfBounds] is returned.
     ///
     /// # Arguments
     /// * `x`: X coordinate of the position.
     /// * `y`: Y coordinate of the position.
     ///
-    pub fn check_idx_result(&self, x: i32, y: i32) -> Result<usize, String> {
-        // Get map idx of the position
-        let idx = self.coordinates_to_idx(x, y);
-
-        // Return the idx if it is in bounds
-        if idx > 0 && idx < self.width as usize * self.height as usize {
-            return Ok(idx);
+    pub fn check_idx_result(&self, x: i32, y: i32) -> GameResult<usize> {
+        // Return the idx if the position is in bounds
+        if x >= 0 && x < self.width && y >= 0 && y < self.height {
+            return Ok(self.coordinates_to_idx(x, y));
         }
 
-        // Return an error if the idx is out of bounds
-        let err = format!(
-            "Index {} is out of bounds in map {} * {}!",
-            idx, self.width, self.height
-        );
-        Err(err)
+        // Return an error if the position is out of bounds
+        Err(GameError::MapOutOfBounds { x, y })
     }
 
     /// Returns `true` if the passed `x` and `y`
@@ -451,15 +813,54 @@ impl Map {
         }
     }
 
+    /// Restores the caches of a deserialized map. The [Map::tile_contents]
+    /// are emptied, the [Map::blocked_tiles] only contain the walls and all
+    /// tiles are rendered anew in the next frame.
+    ///
+    /// # Notes
+    /// * The entities are added to the [Map::tile_contents] again
+    ///   by the `MapDexSystem`, once the systems are executed.
+    ///
+    pub fn restore_caches(&mut self) -> &Self {
+        let tile_count = self.tiles.len();
+
+        self.tile_contents = SpatialIndex::new(tile_count);
+        self.render_cache = vec![None; tile_count];
+        self.dirty_tiles = (0..tile_count).collect();
+        self.light_levels = vec![RGB::new(); tile_count];
+        self.opaque_tiles = vec![false; tile_count];
+        // Saves without the fire overlay have no burning tiles
+        self.fire.resize(tile_count, 0);
+        self.refresh_blocked_tiles();
+        self.refresh_tile_costs();
+
+        self
+    }
+
+    /// Refreshes the [Map::tile_costs] vector.
+    pub fn refresh_tile_costs(&mut self) -> &Self {
+        self.tile_costs = self.tiles.iter().map(TileType::movement_cost).collect();
+
+        self
+    }
+
     /// Refreshes the [Map::blocked_tiles] vector.
     pub fn refresh_blocked_tiles(&mut self) -> &Self {
         for (idx, tile) in self.tiles.iter_mut().enumerate() {
-            self.blocked_tiles[idx] = *tile == TileType::WALL;
+            self.blocked_tiles[idx] = !tile.is_walkable();
         }
 
         self
     }
 
+    /// Clears the [Map::opaque_tiles] vector, so only the
+    /// walls block the sight until the entities are indexed again.
+    pub fn clear_opaque_tiles(&mut self) -> &Self {
+        self.opaque_tiles.fill(false);
+
+        self
+    }
+
     /// Draws the passed room on the map by changing the
     /// [TileType] of the positions included in the [Rectangle]
     /// to [TileType::FLOOR].
@@ -469,10 +870,8 @@ impl Map {
     ///
     pub fn draw_room(&mut self, room: &Rectangle) -> &Self {
         // Iterate the room coordinates and set the positions to a floor tile
-        for x in room.left + 1..=room.right {
-            for y in room.top + 1..=room.bottom {
-                self.set_tile(x, y, TileType::FLOOR);
-            }
+        for position in room.iter_interior() {
+            self.set_tile(position.x, position.y, TileType::FLOOR);
         }
         self
     }
@@ -495,31 +894,60 @@ impl Map {
         self
     }
 
-    /// Uses the passed [Rltk] context to draw
-    /// the map on the screen.
+    /// Draws the part of the map in the viewport of the
+    /// passed `camera` to the [config::LAYER_MAP].
     ///
     /// # Arguments
-    /// * `ctx`: The [Rltk] context to draw the map with.
+    /// * `camera`: The [Camera] deciding the visible part of the map.
+    ///
+    /// # Notes
+    /// * Only the [Map::dirty_tiles] are rendered anew, all other
+    ///   tiles are drawn from the [Map::render_cache].
+    /// * Burning tiles in the fov of the player are overlaid with
+    ///   flames, see [Map::fire].
+    /// * Unexplored tiles are drawn as well, while [Map::revealed] is set.
     ///
-    pub fn draw(&self, ctx: &mut Rltk) -> &Self {
-        // Get starting x and y coordinates.i64
-        let (mut x, mut y) = (0, 0);
+    pub fn draw(&mut self, camera: &Camera) -> &Self {
+        self.refresh_render_cache();
 
-        // Iterate through all tiles
-        for (idx, tile) in self.tiles.iter().enumerate() {
-            if self.explored_tiles[idx] {
-                // Draw the tile
-                self.draw_tile(x, y, tile, ctx);
-            }
+        let mut batch = DrawBatch::new();
+
+        for screen_y in 0..camera.height {
+            for screen_x in 0..camera.width {
+                let screen = Point::new(screen_x, screen_y);
+                let position = screen + camera.origin;
 
-            // Increase x and y coordinate counter
-            x += 1;
+                if !self.check_idx(position.x, position.y) {
+                    continue;
+                }
+
+                let idx = self.coordinates_to_idx(position.x, position.y);
+
+                if let Some(tile) = &self.render_cache[idx] {
+                    batch.set(screen, ColorPair::new(tile.fg, tile.bg), tile.symbol);
+                }
 
-            if x > self.max_x() {
-                x = 0;
-                y += 1;
+                if self.tiles_in_fov[idx] && self.fire[idx] > 0 {
+                    let (fg, bg) = swatch::FIRE.colors();
+                    batch.set(screen, ColorPair::new(fg, bg), rltk::to_cp437('^'));
+                }
             }
         }
+
+        submit_batch(&mut batch, config::LAYER_MAP);
+
+        self
+    }
+
+    /// Renders all [Map::dirty_tiles] into the
+    /// [Map::render_cache] and clears them.
+    pub fn refresh_render_cache(&mut self) -> &Self {
+        let dirty_tiles = std::mem::take(&mut self.dirty_tiles);
+
+        for idx in dirty_tiles {
+            self.render_cache[idx] = self.render_tile(idx);
+        }
+
         self
     }
 
@@ -540,7 +968,7 @@ impl Map {
     /// # See also
     /// * See check_idx for the safety measure to handle indices which are out of bounds.
     ///
-    fn draw_horizontal_intersection(&mut self, start_x: i32, end_x: i32, y: i32) -> &Self {
+    pub fn draw_horizontal_intersection(&mut self, start_x: i32, end_x: i32, y: i32) -> &Self {
         // Iterate from the minimum passed x coordinate to the maximum
         for x in min(start_x, end_x)..=max(start_x, end_x) {
             // If the idx is within bounds, set a floor tile
@@ -573,7 +1001,7 @@ impl Map {
     /// # See also
     /// * See check_idx for the safety measure to handle indices which are out of bounds.
     ///
-    fn draw_vertical_intersection(&mut self, start_y: i32, end_y: i32, x: i32) -> &Self {
+    pub fn draw_vertical_intersection(&mut self, start_y: i32, end_y: i32, x: i32) -> &Self {
         // Iterate from the minimum passed y coordinate to the maximum
         for y in min(start_y, end_y)..=max(start_y, end_y) {
             match self.check_idx_result(x, y) {
@@ -589,33 +1017,54 @@ impl Map {
         self
     }
 
-    /// Draws the passed [TileType] at the given `x` and `y` coordinate.
+    /// Renders the tile at the passed `idx` and returns its [Renderable],
+    /// or `None` if the tile hasn't been explored yet.
     ///
     /// # Arguments
-    /// * `x`: The `x` position of the tile.
-    /// * `y`: The `y` position of the tile.
-    /// * `tile`: The [TileType] which should be set at the passed position.
-    /// * `ctx`: The [Rltk] context, in which the `tile` should be drawn.
+    /// * `idx`: The index of the tile to render.
     ///
     /// # Notes
     ///
     /// The tiles are drawn depending on two factors.
-    /// * If the tile is is in the fov of the player, it is drawn with full color.
+    /// * If the tile is is in the fov of the player, its color is scaled
+    ///   and tinted by the light falling onto it, but never darker than
+    ///   the [config::AMBIENT_LIGHT].
     /// * If the tile is outside of the fov it is drawn in its grayscale counterpart.
+    /// * While the player is blinded, the tiles in the fov
+    ///   are drawn in grayscale as well.
     ///
-    fn draw_tile(&self, x: i32, y: i32, tile: &TileType, ctx: &mut Rltk) -> &Self {
-        let mut tile = match tile {
-            TileType::FLOOR => TileFactory::new_floor(),
-            TileType::WALL => TileFactory::new_wall(),
+    fn render_tile(&self, idx: usize) -> Option<Renderable> {
+        if !self.explored_tiles[idx] && !self.revealed {
+            return None;
+        }
+
+        let mut tile = match self.tiles[idx] {
+            TileType::FLOOR => TileFactory::new_floor(self.biome),
+            TileType::WALL => TileFactory::new_wall(self.biome),
+            TileType::DOWNSTAIRS => TileFactory::new_stairs_down(),
+            TileType::DEEP_WATER => TileFactory::new_deep_water(),
+            TileType::LAVA => TileFactory::new_lava(),
+            TileType::CHASM => TileFactory::new_chasm(),
+            TileType::ROAD => TileFactory::new_road(),
+            TileType::RUBBLE => TileFactory::new_rubble(),
+            TileType::BRIDGE => TileFactory::new_bridge(),
+            TileType::BROKEN_DOOR => TileFactory::new_broken_door(),
         };
 
-        if !self.tiles_in_fov[self.coordinates_to_idx(x, y)] {
+        if self.tiles_in_fov[idx] && !self.player_blind {
+            let light = self.light_levels[idx];
+            let scale = |channel: f32| (config::AMBIENT_LIGHT + channel).min(1.0);
+
+            tile.fg = RGB::from_f32(
+                tile.fg.r * scale(light.r),
+                tile.fg.g * scale(light.g),
+                tile.fg.b * scale(light.b),
+            );
+        } else {
             tile.fg = tile.fg.to_greyscale();
         }
 
-        ctx.set(x, y, tile.fg, tile.bg, tile.symbol);
-
-        self
+        Some(tile)
     }
 }
 
@@ -627,47 +1076,11 @@ impl Algorithm2D for Map {
 
 impl BaseMap for Map {
     fn is_opaque(&self, idx: usize) -> bool {
-        self.tiles[idx] == TileType::WALL
+        self.tiles[idx] == TileType::WALL || self.opaque_tiles[idx]
     }
 
     fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
-        let mut walkable_tiles = SmallVec::new();
-
-        let (x, y) = self.idx_to_coordinates(idx);
-        let width = self.width as usize;
-
-        // Check tiles in cardinal directions
-        if self.is_tile_walkable(x - 1, y) {
-            walkable_tiles.push((idx - 1, 1.0));
-        }
-        if self.is_tile_walkable(x + 1, y) {
-            walkable_tiles.push((idx + 1, 1.0));
-        }
-        if self.is_tile_walkable(x, y - 1) {
-            walkable_tiles.push((idx - width, 1.0));
-        }
-        if self.is_tile_walkable(x, y + 1) {
-            walkable_tiles.push((idx + width, 1.0));
-        }
-
-        // Check tiles in diagonal directions
-        if self.is_tile_walkable(x - 1, y - 1) {
-            walkable_tiles.push(((idx - width) - 1, 1.45));
-        }
-
-        if self.is_tile_walkable(x + 1, y - 1) {
-            walkable_tiles.push(((idx - width) + 1, 1.45));
-        }
-
-        if self.is_tile_walkable(x - 1, y + 1) {
-            walkable_tiles.push(((idx + width) - 1, 1.45));
-        }
-
-        if self.is_tile_walkable(x + 1, y + 1) {
-            walkable_tiles.push(((idx + width) + 1, 1.45));
-        }
-
-        walkable_tiles
+        self.exits_where(idx, |x, y| self.is_tile_walkable(x, y))
     }
 
     fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
@@ -0,0 +1,102 @@
+//! Builder placing the arena of the boss on the final level.
+
+use rltk::console;
+use specs::prelude::*;
+
+use crate::{entity_factory, Map, Position, Rectangle, TileType};
+
+use super::MapBuilder;
+
+/// The name of the boss spawned in the arena.
+const BOSS_NAME: &str = "Goblin King";
+
+/// The prefab of the arena. `#` are walls, `.` are floors and
+/// `B` is the floor the boss is spawned on. The open floor within
+/// the outer two rows and columns forms the room of the arena.
+const ARENA: [&str; 13] = [
+    "###################",
+    "###.###.###.###.###",
+    "##...............##",
+    "#.................#",
+    "##...............##",
+    "#.................#",
+    "##.......B.......##",
+    "#.................#",
+    "##...............##",
+    "#.................#",
+    "##...............##",
+    "###.###.###.###.###",
+    "###################",
+];
+
+/// [MapBuilder] stamping the arena prefab onto the side of the map
+/// opposite to the first room and spawning the boss in it. The arena
+/// is appended as the last room, so it's connected to the other rooms
+/// by a following [CorridorBuilder](super::CorridorBuilder).
+///
+/// # Notes
+/// * Rooms, that overlap with the arena, are filled with walls and
+///   removed, so the builder has to run before rooms are connected.
+/// * The boss is created in the [World] right away.
+///
+pub struct BossRoomBuilder;
+
+impl MapBuilder for BossRoomBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        let width = ARENA[0].len() as i32;
+        let height = ARENA.len() as i32;
+
+        // Place the arena as far away from the player's start as possible
+        let x = match map.rooms.first() {
+            Some(first_room) if first_room.center().x >= map.width / 2 => 1,
+            _ => map.width - width - 1,
+        };
+        let y = (map.height - height) / 2;
+
+        let bounds = Rectangle::new(x, y, width - 1, height - 1);
+
+        // Remove the rooms the arena would cut into
+        let (overlapping, rooms): (Vec<Rectangle>, Vec<Rectangle>) = std::mem::take(&mut map.rooms)
+            .into_iter()
+            .partition(|room| room.expand(1).overlaps(&bounds));
+
+        for room in overlapping.iter() {
+            for position in room.iter_interior() {
+                map.set_tile(position.x, position.y, TileType::WALL);
+            }
+        }
+
+        map.rooms = rooms;
+
+        let mut boss_position = None;
+
+        for (dy, row) in ARENA.iter().enumerate() {
+            for (dx, glyph) in row.chars().enumerate() {
+                let (tile_x, tile_y) = (x + dx as i32, y + dy as i32);
+
+                let tile = match glyph {
+                    '#' => TileType::WALL,
+                    'B' => {
+                        boss_position = Some(Position {
+                            x: tile_x,
+                            y: tile_y,
+                        });
+                        TileType::FLOOR
+                    }
+                    _ => TileType::FLOOR,
+                };
+
+                map.set_tile(tile_x, tile_y, tile);
+            }
+        }
+
+        map.rooms
+            .push(Rectangle::new(x + 1, y + 1, width - 4, height - 4));
+
+        if let Some(position) = boss_position {
+            if entity_factory::new_named(ecs, BOSS_NAME, position).is_none() {
+                console::log(format!("Unknown boss '{}'!", BOSS_NAME));
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Builder placing rooms through binary space partitioning.
+
+use specs::prelude::*;
+
+use crate::game_config::GameConfig;
+use crate::{rng, Map, Rectangle};
+
+use super::MapBuilder;
+
+/// [MapBuilder] splitting the map through binary space partitioning
+/// into leaves and adding a room of random width and height to every
+/// leaf. The rooms are added in the order of the leaves, so consecutive
+/// rooms are neighbours, see [CorridorBuilder](super::CorridorBuilder).
+pub struct BspBuilder;
+
+impl MapBuilder for BspBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        let (min_size, max_size, min_leaf_size) = {
+            let game_config = ecs.fetch::<GameConfig>();
+            (
+                game_config.min_room_size,
+                game_config.max_room_size,
+                game_config.bsp_min_leaf_size,
+            )
+        };
+
+        let mut leaves = Vec::new();
+        let root = Rectangle::new(0, 0, map.width - 1, map.height - 1);
+
+        split_leaf(ecs, root, min_leaf_size, &mut leaves);
+
+        for leaf in leaves {
+            let max_width = max_size.min(leaf.width() - 1);
+            let max_height = max_size.min(leaf.height() - 1);
+
+            let room_width = rng::range(ecs, min_size, max_width + 1);
+            let room_height = rng::range(ecs, min_size, max_height + 1);
+
+            // Keep the room inside the leaf, the right and bottom tiles of the leaf stay walls
+            let x = leaf.left + rng::range(ecs, 0, leaf.width() - room_width);
+            let y = leaf.top + rng::range(ecs, 0, leaf.height() - room_height);
+
+            let room = Rectangle::new(x, y, room_width, room_height);
+            map.draw_room(&room);
+            map.rooms.push(room);
+        }
+    }
+}
+
+/// Recursively splits the `leaf` into two halves along its longer side,
+/// until both sides are shorter than twice the `min_size`, and pushes
+/// the final leaves to the `leaves` from left to right and top to
+/// bottom of the tree.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the `rng` handler is registered.
+/// * `leaf`: The [Rectangle] to split.
+/// * `min_size`: The minimum width and height of a leaf, see
+///   [GameConfig::bsp_min_leaf_size].
+/// * `leaves`: The final leaves of the partition.
+///
+fn split_leaf(ecs: &mut World, leaf: Rectangle, min_size: i32, leaves: &mut Vec<Rectangle>) {
+    let can_split_x = leaf.width() >= min_size * 2;
+    let can_split_y = leaf.height() >= min_size * 2;
+
+    let split_x = match (can_split_x, can_split_y) {
+        (false, false) => {
+            leaves.push(leaf);
+            return;
+        }
+        (true, true) => leaf.width() >= leaf.height(),
+        (can_split_x, _) => can_split_x,
+    };
+
+    let (first, second) = if split_x {
+        let split = rng::range(ecs, min_size, leaf.width() - min_size + 1);
+        (
+            Rectangle::new(leaf.left, leaf.top, split, leaf.height()),
+            Rectangle::new(
+                leaf.left + split,
+                leaf.top,
+                leaf.width() - split,
+                leaf.height(),
+            ),
+        )
+    } else {
+        let split = rng::range(ecs, min_size, leaf.height() - min_size + 1);
+        (
+            Rectangle::new(leaf.left, leaf.top, leaf.width(), split),
+            Rectangle::new(
+                leaf.left,
+                leaf.top + split,
+                leaf.width(),
+                leaf.height() - split,
+            ),
+        )
+    };
+
+    split_leaf(ecs, first, min_size, leaves);
+    split_leaf(ecs, second, min_size, leaves);
+}
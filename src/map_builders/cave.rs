@@ -0,0 +1,104 @@
+//! Builder creating caves through a cellular automaton.
+
+use specs::prelude::*;
+
+use crate::game_config::GameConfig;
+use crate::{rng, Map, TileType};
+
+use super::MapBuilder;
+
+/// [MapBuilder] creating a cave by filling the map with random walls and
+/// smoothing them through a cellular automaton. Tiles, that can't be reached
+/// from the [Map::player_start], are filled with walls afterwards, so the
+/// cave is connected. The map has no rooms.
+///
+/// # Notes
+/// * Caves, whose connected part covers less than the
+///   [GameConfig::cave_min_floor_percent] of the map, are generated
+///   anew, up to [GameConfig::cave_max_attempts] times.
+///
+pub struct CaveBuilder;
+
+impl MapBuilder for CaveBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        let game_config = (*ecs.fetch::<GameConfig>()).clone();
+        let mut distances = Vec::new();
+
+        for _ in 0..game_config.cave_max_attempts {
+            seed_walls(ecs, map, game_config.cave_wall_chance);
+
+            for _ in 0..game_config.cave_smoothing_iterations {
+                smooth_walls(map);
+            }
+
+            let start = map.player_start();
+            distances = map.flood_fill(start.x, start.y);
+
+            let reached = distances
+                .iter()
+                .filter(|distance| distance.is_some())
+                .count();
+            if reached * 100 >= map.tiles.len() * game_config.cave_min_floor_percent {
+                break;
+            }
+        }
+
+        // Fill all unreachable caverns
+        for (idx, distance) in distances.iter().enumerate() {
+            if distance.is_none() {
+                map.tiles[idx] = TileType::WALL;
+            }
+        }
+    }
+}
+
+/// Fills the border of the `map` with walls and every other
+/// tile with a wall by the `wall_chance`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the `rng` handler is registered.
+/// * `map`: The [Map] to fill.
+/// * `wall_chance`: The chance in percent of a tile being a wall.
+///
+fn seed_walls(ecs: &mut World, map: &mut Map, wall_chance: i32) {
+    for idx in 0..map.tiles.len() {
+        let (x, y) = map.idx_to_coordinates(idx);
+        let is_border = x == 0 || y == 0 || x == map.width - 1 || y == map.height - 1;
+
+        map.tiles[idx] = if is_border || rng::range(ecs, 0, 100) < wall_chance {
+            TileType::WALL
+        } else {
+            TileType::FLOOR
+        };
+    }
+}
+
+/// Runs one iteration of the cellular automaton, which turns a tile
+/// into a wall, if more than four of its eight neighbours are walls,
+/// and into a floor, if less than four are. The border of the map is kept.
+///
+/// # Arguments
+/// * `map`: The [Map] to smooth.
+///
+fn smooth_walls(map: &mut Map) {
+    let mut tiles = map.tiles.clone();
+
+    for y in 1..map.height - 1 {
+        for x in 1..map.width - 1 {
+            let neighbouring_walls = (-1..=1)
+                .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                .filter(|&(dx, dy)| (dx, dy) != (0, 0))
+                .filter(|&(dx, dy)| map.get_tile(x + dx, y + dy) == TileType::WALL)
+                .count();
+
+            let idx = map.coordinates_to_idx(x, y);
+            match neighbouring_walls {
+                0..=3 => tiles[idx] = TileType::FLOOR,
+                4 => {}
+                _ => tiles[idx] = TileType::WALL,
+            }
+        }
+    }
+
+    map.tiles = tiles;
+}
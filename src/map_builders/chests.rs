@@ -0,0 +1,68 @@
+//! Builder turning some rooms of a map into treasure rooms.
+
+use specs::prelude::*;
+
+use crate::spawn_table::SpawnTables;
+use crate::{config, entity_factory, rng, Map, Position, TileType};
+
+use super::MapBuilder;
+
+/// [MapBuilder] turning the rooms of the map into treasure rooms by the
+/// [config::TREASURE_ROOM_CHANCE]. Every treasure room gets a chest on a
+/// free floor tile, which is filled with the items rolled from the
+/// treasure table of the [SpawnTables], see [crate::containers].
+///
+/// # Notes
+/// * The first room is reserved for the player and maps
+///   without rooms, e.g. caves, don't get any chests.
+/// * Has to be applied after the [SpawnBuilder](super::SpawnBuilder),
+///   so the chests don't share their tile with another entity.
+///
+pub struct ChestBuilder;
+
+impl MapBuilder for ChestBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        let table = ecs.fetch::<SpawnTables>().treasure.clone();
+
+        let occupied = ecs
+            .read_storage::<Position>()
+            .join()
+            .copied()
+            .collect::<Vec<_>>();
+
+        for room in map.rooms.iter().skip(1) {
+            if rng::range(ecs, 0, 100) >= config::TREASURE_ROOM_CHANCE {
+                continue;
+            }
+
+            let tiles = room
+                .iter_interior()
+                .filter(|tile| map.get_tile(tile.x, tile.y) == TileType::FLOOR)
+                .filter(|tile| !occupied.contains(tile))
+                .collect::<Vec<_>>();
+
+            if tiles.is_empty() {
+                continue;
+            }
+
+            let position = tiles[rng::range(ecs, 0, tiles.len() as i32) as usize];
+
+            let contents = table
+                .roll(ecs, map.depth, map.biome)
+                .iter()
+                .filter_map(|name| entity_factory::new_named(ecs, name, position))
+                .collect::<Vec<_>>();
+
+            // The items are in the chest, not on its tile
+            {
+                let mut positions = ecs.write_storage::<Position>();
+
+                for item in contents.iter() {
+                    positions.remove(*item);
+                }
+            }
+
+            entity_factory::new_chest(ecs, position, contents);
+        }
+    }
+}
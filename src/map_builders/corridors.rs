@@ -0,0 +1,45 @@
+//! Builder connecting the rooms of a map.
+
+use specs::prelude::*;
+
+use crate::{rng, Map};
+
+use super::MapBuilder;
+
+/// [MapBuilder] connecting every room of the map with the previous
+/// room through a horizontal and a vertical intersection, whose order
+/// is picked at random. All rooms are reachable afterwards.
+pub struct CorridorBuilder;
+
+impl MapBuilder for CorridorBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        for idx in 1..map.rooms.len() {
+            let new_room_center = map.rooms[idx].center();
+            let previous_room_center = map.rooms[idx - 1].center();
+
+            if rng::range(ecs, 0, 2) == 1 {
+                map.draw_horizontal_intersection(
+                    previous_room_center.x,
+                    new_room_center.x,
+                    previous_room_center.y,
+                );
+                map.draw_vertical_intersection(
+                    previous_room_center.y,
+                    new_room_center.y,
+                    new_room_center.x,
+                );
+            } else {
+                map.draw_vertical_intersection(
+                    previous_room_center.y,
+                    new_room_center.y,
+                    previous_room_center.x,
+                );
+                map.draw_horizontal_intersection(
+                    previous_room_center.x,
+                    new_room_center.x,
+                    new_room_center.y,
+                );
+            }
+        }
+    }
+}
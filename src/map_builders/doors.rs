@@ -0,0 +1,113 @@
+//! Builder closing off the doorways of the rooms with doors.
+
+use specs::prelude::*;
+
+use crate::{config, entity_factory, rng, Map, Position, Rectangle, TileType};
+
+use super::MapBuilder;
+
+/// [MapBuilder] placing a closed door in the doorways of the rooms
+/// by the [config::DOOR_CHANCE], see [crate::doors]. Doors are locked
+/// by the [config::LOCKED_DOOR_CHANCE], their key is placed on a random
+/// floor tile of another room.
+///
+/// # Notes
+/// * Has to be applied after the rooms have been connected, maps
+///   without rooms, e.g. caves, don't get any doors.
+/// * The doors and keys are created in the [World] right away.
+/// * The key may end up behind its own door, locked doors
+///   can still be bashed in though.
+///
+pub struct DoorBuilder;
+
+impl MapBuilder for DoorBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        let mut doorways = map
+            .rooms
+            .iter()
+            .flat_map(|room| doorways(map, room))
+            .collect::<Vec<_>>();
+
+        // Neighbouring rooms may share a doorway
+        doorways.sort_by_key(|doorway| (doorway.y, doorway.x));
+        doorways.dedup();
+
+        for doorway in doorways {
+            if rng::range(ecs, 0, 100) >= config::DOOR_CHANCE {
+                continue;
+            }
+
+            let key_position = if rng::range(ecs, 0, 100) < config::LOCKED_DOOR_CHANCE {
+                key_position(ecs, map, doorway)
+            } else {
+                None
+            };
+
+            entity_factory::new_door(ecs, doorway, key_position.is_some());
+
+            if let Some(position) = key_position {
+                entity_factory::new_key(ecs, position, map.depth, doorway);
+            }
+        }
+    }
+}
+
+/// Returns a random floor tile of a random room, which doesn't
+/// have the passed `doorway`, to place the key of its door on.
+/// Returns `None` if there is no such room.
+///
+/// # Arguments
+/// * `ecs`: The [World] holding the random number generator.
+/// * `map`: The [Map] containing the rooms.
+/// * `doorway`: The [Position] of the locked door.
+///
+fn key_position(ecs: &mut World, map: &Map, doorway: Position) -> Option<Position> {
+    let rooms = map
+        .rooms
+        .iter()
+        .filter(|room| !doorways(map, room).contains(&doorway))
+        .collect::<Vec<_>>();
+
+    if rooms.is_empty() {
+        return None;
+    }
+
+    let room = rooms[rng::range(ecs, 0, rooms.len() as i32) as usize];
+    let position = Position {
+        x: rng::range(ecs, room.left + 1, room.right + 1),
+        y: rng::range(ecs, room.top + 1, room.bottom + 1),
+    };
+
+    (map.get_tile(position.x, position.y) == TileType::FLOOR).then_some(position)
+}
+
+/// Returns the doorways of the passed `room`, i.e. the floor tiles of its
+/// surrounding walls, which are framed by walls on both sides and lead
+/// into a corridor.
+///
+/// # Arguments
+/// * `map`: The [Map] containing the room.
+/// * `room`: The room whose doorways should be returned.
+///
+fn doorways(map: &Map, room: &Rectangle) -> Vec<Position> {
+    let (left, right) = (room.left, room.right + 1);
+    let (top, bottom) = (room.top, room.bottom + 1);
+
+    let is_floor = |x: i32, y: i32| map.check_idx(x, y) && map.get_tile(x, y) == TileType::FLOOR;
+    let is_wall = |x: i32, y: i32| !map.check_idx(x, y) || map.get_tile(x, y) == TileType::WALL;
+
+    // The tiles of the walls with the direction leading out of the room
+    let horizontal = (left + 1..right).flat_map(|x| [(x, top, 0, -1), (x, bottom, 0, 1)]);
+    let vertical = (top + 1..bottom).flat_map(|y| [(left, y, -1, 0), (right, y, 1, 0)]);
+
+    horizontal
+        .chain(vertical)
+        .filter(|&(x, y, dx, dy)| {
+            is_floor(x, y)
+                && is_floor(x + dx, y + dy)
+                && is_wall(x + dy, y + dx)
+                && is_wall(x - dy, y - dx)
+        })
+        .map(|(x, y, _, _)| Position { x, y })
+        .collect()
+}
@@ -0,0 +1,242 @@
+//! Module containing the generators of the game's maps.
+//!
+//! A map is built by a [BuilderChain], which applies a list of [MapBuilder]s
+//! in order to a [Map] filled with walls. The first builder creates the
+//! layout of the map, e.g. the rooms of the [RoomsBuilder], and the following
+//! builders decorate it, e.g. by connecting the rooms through the
+//! [CorridorBuilder] or populating the map through the [SpawnBuilder]. New
+//! generators and decorators only have to implement [MapBuilder] and can be
+//! combined freely with the existing ones.
+
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::biome::Biome;
+use crate::game_config::GameConfig;
+use crate::{config, rng, Map};
+
+mod rooms;
+pub use rooms::RoomsBuilder;
+
+mod bsp;
+pub use bsp::BspBuilder;
+
+mod cave;
+pub use cave::CaveBuilder;
+
+mod wfc;
+pub use wfc::WfcBuilder;
+
+mod corridors;
+pub use corridors::CorridorBuilder;
+
+mod stairs;
+pub use stairs::StairsBuilder;
+
+mod terrain;
+pub use terrain::TerrainBuilder;
+
+mod doors;
+pub use doors::DoorBuilder;
+
+mod spawner;
+pub use spawner::SpawnBuilder;
+
+mod chests;
+pub use chests::ChestBuilder;
+
+mod boss;
+pub use boss::BossRoomBuilder;
+
+mod town;
+pub use town::TownBuilder;
+
+/// A single step of the generation of a [Map].
+pub trait MapBuilder {
+    /// Applies the step to the passed `map`.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which the `rng` handler is registered.
+    /// * `map`: The [Map] that is built.
+    ///
+    fn build(&mut self, ecs: &mut World, map: &mut Map);
+}
+
+/// Builds a [Map] by applying its [MapBuilder]s in order.
+pub struct BuilderChain {
+    /// The width of the built map.
+    width: i32,
+
+    /// The height of the built map.
+    height: i32,
+
+    /// The level of the dungeon the built map represents.
+    depth: i32,
+
+    /// The builders in the order in which they are applied.
+    builders: Vec<Box<dyn MapBuilder>>,
+}
+
+impl BuilderChain {
+    /// Creates a new [BuilderChain] without any builders.
+    ///
+    /// # Arguments
+    /// * `width`: The width of the built map.
+    /// * `height`: The height of the built map.
+    /// * `depth`: The level of the dungeon the built map represents.
+    ///
+    pub fn new(width: i32, height: i32, depth: i32) -> Self {
+        BuilderChain {
+            width,
+            height,
+            depth,
+            builders: Vec::new(),
+        }
+    }
+
+    /// Appends the passed `builder` to the chain.
+    ///
+    /// # Arguments
+    /// * `builder`: The [MapBuilder] applied after the previous builders.
+    ///
+    pub fn with<B: MapBuilder + 'static>(mut self, builder: B) -> Self {
+        self.builders.push(Box::new(builder));
+        self
+    }
+
+    /// Consumes the chain and builds the [Map] by applying all builders.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which the `rng` handler is registered and
+    ///   the entities of spawning builders are created.
+    ///
+    pub fn build(self, ecs: &mut World) -> Map {
+        let mut map = Map::new(self.width, self.height, self.depth);
+
+        for mut builder in self.builders {
+            builder.build(ecs, &mut map);
+        }
+
+        // Builders may change the tiles directly
+        map.refresh_tile_costs();
+
+        map
+    }
+}
+
+/// Enum describing all algorithms, that can create the layout of a [Map].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapGenerator {
+    /// Rooms at random positions, that don't overlap.
+    Rooms,
+
+    /// Rooms in the leaves of a binary space partition of the map.
+    Bsp,
+
+    /// A single connected cave shaped by a cellular automaton, without rooms.
+    Cave,
+
+    /// An organic layout following the patterns of hand-made samples
+    /// through wave function collapse, without rooms.
+    Wfc,
+
+    /// One of the other generators, picked at random for every map.
+    Random,
+}
+
+impl MapGenerator {
+    /// Returns the generator used for the next map, i.e. a random
+    /// concrete generator for [MapGenerator::Random] and the
+    /// generator itself otherwise.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which the `rng` handler is registered.
+    ///
+    pub fn resolve(self, ecs: &mut World) -> Self {
+        match self {
+            MapGenerator::Random => match rng::range(ecs, 0, 4) {
+                0 => MapGenerator::Rooms,
+                1 => MapGenerator::Bsp,
+                2 => MapGenerator::Cave,
+                _ => MapGenerator::Wfc,
+            },
+            generator => generator,
+        }
+    }
+}
+
+/// Creates the [BuilderChain] of a new level with the size and the
+/// [MapGenerator] set in the [GameConfig]. The map gets stairs to the next
+/// level, pools of water, lava and chasms, heaps of rubble, doors in the
+/// doorways of its rooms, is populated with monsters and items and gets
+/// chests in its treasure rooms.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the `rng` handler
+///   and the [GameConfig] are registered.
+/// * `depth`: The level of the dungeon the map represents.
+///
+/// # Notes
+/// * The town at the [config::TOWN_DEPTH] is built by the [TownBuilder]
+///   and the level at the [config::FINAL_DEPTH] by the [boss_builder]
+///   instead.
+/// * If the [GameConfig] doesn't fix the generator, the [Biome::generator]
+///   of the level's biome creates the layout.
+///
+pub fn random_builder(ecs: &mut World, depth: i32) -> BuilderChain {
+    let (width, height, generator) = {
+        let game_config = ecs.fetch::<GameConfig>();
+        (
+            game_config.map_width,
+            game_config.map_height,
+            game_config.map_generator,
+        )
+    };
+
+    let chain = BuilderChain::new(width, height, depth);
+
+    if depth == config::TOWN_DEPTH {
+        return chain.with(TownBuilder);
+    }
+
+    if depth >= config::FINAL_DEPTH {
+        return boss_builder(chain);
+    }
+
+    // Without a fixed generator the biome of the level picks it
+    let generator = match (generator, Biome::for_depth(depth)) {
+        (MapGenerator::Random, Some(biome)) => biome.generator(),
+        (generator, _) => generator,
+    };
+
+    let chain = match generator.resolve(ecs) {
+        MapGenerator::Rooms | MapGenerator::Random => {
+            chain.with(RoomsBuilder).with(CorridorBuilder)
+        }
+        MapGenerator::Bsp => chain.with(BspBuilder).with(CorridorBuilder),
+        MapGenerator::Cave => chain.with(CaveBuilder),
+        MapGenerator::Wfc => chain.with(WfcBuilder),
+    };
+
+    chain
+        .with(StairsBuilder)
+        .with(TerrainBuilder)
+        .with(DoorBuilder)
+        .with(SpawnBuilder)
+        .with(ChestBuilder)
+}
+
+/// Extends the passed `chain` to build the final level, which consists
+/// of rooms and the arena of the boss in place of the stairs.
+///
+/// # Arguments
+/// * `chain`: The empty [BuilderChain] of the level.
+///
+fn boss_builder(chain: BuilderChain) -> BuilderChain {
+    chain
+        .with(RoomsBuilder)
+        .with(BossRoomBuilder)
+        .with(CorridorBuilder)
+        .with(SpawnBuilder)
+}
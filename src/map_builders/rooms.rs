@@ -0,0 +1,51 @@
+//! Builder placing rooms at random positions.
+
+use specs::prelude::*;
+
+use crate::game_config::GameConfig;
+use crate::{rng, Map, Rectangle};
+
+use super::MapBuilder;
+
+/// [MapBuilder] adding rooms of random width and height at
+/// random positions to the map, as long as they don't overlap.
+/// The rooms aren't connected, see [CorridorBuilder](super::CorridorBuilder).
+pub struct RoomsBuilder;
+
+impl MapBuilder for RoomsBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        let (max_rooms, min_size, max_size) = {
+            let game_config = ecs.fetch::<GameConfig>();
+            (
+                game_config.max_rooms,
+                game_config.min_room_size,
+                game_config.max_room_size,
+            )
+        };
+
+        // Create as many rooms as defined in the [GameConfig]
+        for _ in 0..max_rooms {
+            // Calc the [Rectangle] width and height args
+            let room_width = rng::range(ecs, min_size, max_size);
+            let room_height = rng::range(ecs, min_size, max_size);
+
+            // Calc the x and y position of the top left corner of the [Rectangle].
+            let x = rng::roll_dice(ecs, 1, map.width - room_width - 1) - 1;
+            let y = rng::roll_dice(ecs, 1, map.height - room_height - 1) - 1;
+
+            // Create the new room
+            let room = Rectangle::new(x, y, room_width, room_height);
+
+            // Check if the new room overlaps with any of the existing rooms.
+            let can_place = !map
+                .rooms
+                .iter()
+                .any(|existing_room| room.overlaps(existing_room));
+
+            if can_place {
+                map.draw_room(&room);
+                map.rooms.push(room);
+            }
+        }
+    }
+}
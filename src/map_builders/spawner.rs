@@ -0,0 +1,22 @@
+//! Builder populating a map with monsters and items.
+
+use specs::prelude::*;
+
+use crate::{spawn_controller, Map};
+
+use super::MapBuilder;
+
+/// [MapBuilder] spawning monsters and items on the map,
+/// see [spawn_controller::populate_map].
+///
+/// # Notes
+/// * The entities are created in the [World] right away, so the
+///   builder should be one of the last ones of a chain.
+///
+pub struct SpawnBuilder;
+
+impl MapBuilder for SpawnBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        spawn_controller::populate_map(ecs, map);
+    }
+}
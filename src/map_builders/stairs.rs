@@ -0,0 +1,34 @@
+//! Builder placing the stairs to the next level.
+
+use specs::prelude::*;
+
+use crate::{Map, TileType};
+
+use super::MapBuilder;
+
+/// [MapBuilder] placing the stairs to the next level in the center of
+/// the last room, or for maps without rooms at the reachable tile
+/// farthest from the [Map::player_start].
+pub struct StairsBuilder;
+
+impl MapBuilder for StairsBuilder {
+    fn build(&mut self, _ecs: &mut World, map: &mut Map) {
+        if let Some(last_room) = map.rooms.last() {
+            let stairs_position = last_room.center();
+            map.set_tile(stairs_position.x, stairs_position.y, TileType::DOWNSTAIRS);
+            return;
+        }
+
+        let start = map.player_start();
+
+        if let Some((idx, _)) = map
+            .flood_fill(start.x, start.y)
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, distance)| distance.map(|distance| (idx, distance)))
+            .max_by_key(|(_, distance)| *distance)
+        {
+            map.tiles[idx] = TileType::DOWNSTAIRS;
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Builder scattering water, lava, chasms and rubble over the map.
+
+use rltk::Point;
+use specs::prelude::*;
+
+use crate::{config, rng, Map, Position, TileType};
+
+use super::MapBuilder;
+
+/// The smallest distance between a hazard and the [Map::player_start].
+const START_CLEARANCE: i32 = 3;
+
+/// [MapBuilder] placing [config::TERRAIN_FEATURES_PER_LEVEL] pools
+/// of [TileType::DEEP_WATER] or [TileType::LAVA] and chasms, as well
+/// as [config::RUBBLE_PER_LEVEL] heaps of rubble on the floor of the map.
+///
+/// # Notes
+/// * Lava and chasms only appear from the [config::HAZARD_MIN_DEPTH] on.
+/// * A [TileType::BRIDGE] is laid across water and chasms, that cut off
+///   the stairs from the [Map::player_start]. Features, that still cut
+///   them off, are removed again.
+/// * Has to be applied before the [super::SpawnBuilder],
+///   so no entities are placed in the hazards.
+///
+pub struct TerrainBuilder;
+
+impl MapBuilder for TerrainBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        let start = map.player_start();
+
+        for _ in 0..config::RUBBLE_PER_LEVEL {
+            if let Some(center) = random_floor_tile(ecs, map) {
+                paint_disc(map, center, 1, TileType::RUBBLE, &start);
+            }
+        }
+
+        for _ in 0..config::TERRAIN_FEATURES_PER_LEVEL {
+            let center = match random_floor_tile(ecs, map) {
+                Some(center) => center,
+                None => return,
+            };
+            let radius = rng::range(ecs, 1, 3);
+
+            let tile = if map.depth < config::HAZARD_MIN_DEPTH {
+                TileType::DEEP_WATER
+            } else {
+                match rng::range(ecs, 0, 3) {
+                    0 => TileType::DEEP_WATER,
+                    1 => TileType::LAVA,
+                    _ => TileType::CHASM,
+                }
+            };
+
+            let painted = paint_disc(map, center, radius, tile, &start);
+
+            if is_stairs_reachable(map) {
+                continue;
+            }
+
+            // Lay a bridge through the center row of the feature
+            for (x, y) in painted.iter().filter(|(_, y)| *y == center.y) {
+                map.set_tile(*x, *y, TileType::BRIDGE);
+            }
+
+            if !is_stairs_reachable(map) {
+                for (x, y) in painted {
+                    map.set_tile(x, y, TileType::FLOOR);
+                }
+            }
+        }
+    }
+}
+
+/// Returns a random [TileType::FLOOR] tile of the
+/// `map`, or `None` if the map has no floor.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the `rng` handler is registered.
+/// * `map`: The [Map] to pick the tile from.
+///
+fn random_floor_tile(ecs: &mut World, map: &Map) -> Option<Point> {
+    let floor_tiles = (0..map.tiles.len())
+        .filter(|idx| map.tiles[*idx] == TileType::FLOOR)
+        .collect::<Vec<_>>();
+
+    if floor_tiles.is_empty() {
+        return None;
+    }
+
+    let idx = floor_tiles[rng::range(ecs, 0, floor_tiles.len() as i32) as usize];
+    let (x, y) = map.idx_to_coordinates(idx);
+
+    Some(Point::new(x, y))
+}
+
+/// Changes the [TileType::FLOOR] tiles within the `radius` around the
+/// `center` to the passed `tile` and returns their coordinates. Tiles
+/// close to the `start` of the player are left untouched.
+///
+/// # Arguments
+/// * `map`: The [Map] to paint on.
+/// * `center`: The center of the disc.
+/// * `radius`: The radius of the disc.
+/// * `tile`: The [TileType] to paint.
+/// * `start`: The [Map::player_start].
+///
+fn paint_disc(
+    map: &mut Map,
+    center: Point,
+    radius: i32,
+    tile: TileType,
+    start: &Position,
+) -> Vec<(i32, i32)> {
+    let mut painted = Vec::new();
+
+    for y in center.y - radius..=center.y + radius {
+        for x in center.x - radius..=center.x + radius {
+            let is_inside = (x - center.x).pow(2) + (y - center.y).pow(2) <= radius.pow(2);
+            let is_clear =
+                (x - start.x).abs() > START_CLEARANCE || (y - start.y).abs() > START_CLEARANCE;
+
+            if is_inside && is_clear && map.check_idx(x, y) && map.get_tile(x, y) == TileType::FLOOR
+            {
+                painted.push((x, y));
+                map.set_tile(x, y, tile);
+            }
+        }
+    }
+
+    painted
+}
+
+/// Returns `true` if the stairs can be reached from the
+/// [Map::player_start], or if the map has no stairs.
+///
+/// # Arguments
+/// * `map`: The [Map] to check.
+///
+fn is_stairs_reachable(map: &Map) -> bool {
+    let start = map.player_start();
+    let distances = map.flood_fill(start.x, start.y);
+
+    map.tiles
+        .iter()
+        .zip(distances.iter())
+        .all(|(tile, distance)| *tile != TileType::DOWNSTAIRS || distance.is_some())
+}
@@ -0,0 +1,157 @@
+//! Builder creating the town on the surface.
+
+use rltk::console;
+use specs::prelude::*;
+
+use crate::raws::Raws;
+use crate::{entity_factory, rng, shop, Map, Position, Rectangle, TileType};
+
+use super::MapBuilder;
+
+/// The maximum amount of houses placed in the town.
+const MAX_HOUSES: usize = 10;
+
+/// The amount of random positions tried for the houses.
+const HOUSE_ATTEMPTS: i32 = 50;
+
+/// The minimum width and height of a house including its walls.
+const MIN_HOUSE_SIZE: i32 = 5;
+
+/// The maximum width and height of a house including its walls.
+const MAX_HOUSE_SIZE: i32 = 9;
+
+/// The distance from the center of the map to the edge
+/// of the square, which is kept free of houses.
+const SQUARE_RADIUS: i32 = 6;
+
+/// [MapBuilder] creating the town, an open area with houses around
+/// a central square, on which the player starts. The NPCs defined
+/// in the [Raws] stand on the square and a road leads from it to the
+/// entrance to the dungeon on the eastern edge of the town.
+///
+/// # Notes
+/// * The town has no rooms and no monsters.
+/// * The NPCs are created in the [World] right away, the stock of
+///   the shopkeepers is put on display around them.
+///
+pub struct TownBuilder;
+
+impl MapBuilder for TownBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                map.set_tile(x, y, TileType::FLOOR);
+            }
+        }
+
+        let center = Position {
+            x: map.width / 2,
+            y: map.height / 2,
+        };
+        let entrance = Position {
+            x: map.width - 4,
+            y: map.height / 2,
+        };
+
+        // Areas, that have to stay free of houses
+        let mut reserved = vec![
+            Rectangle::new(
+                center.x - SQUARE_RADIUS,
+                center.y - SQUARE_RADIUS,
+                SQUARE_RADIUS * 2,
+                SQUARE_RADIUS * 2,
+            ),
+            Rectangle::new(entrance.x - 2, entrance.y - 2, 4, 4),
+            Rectangle::new(center.x, center.y, entrance.x - center.x, 0),
+        ];
+
+        TownBuilder::build_houses(ecs, map, &mut reserved);
+
+        for x in center.x..entrance.x {
+            map.set_tile(x, center.y, TileType::ROAD);
+        }
+
+        map.set_tile(entrance.x, entrance.y, TileType::DOWNSTAIRS);
+
+        TownBuilder::spawn_npcs(ecs, map, &center);
+    }
+}
+
+impl TownBuilder {
+    /// Places houses with a door in their southern wall at random
+    /// positions of the `map`, that don't overlap with each other
+    /// or the `reserved` areas.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which the `rng` handler is registered.
+    /// * `map`: The [Map] of the town.
+    /// * `reserved`: The areas to keep free, every house is added to them.
+    ///
+    fn build_houses(ecs: &mut World, map: &mut Map, reserved: &mut Vec<Rectangle>) {
+        let mut houses = 0;
+
+        for _ in 0..HOUSE_ATTEMPTS {
+            if houses >= MAX_HOUSES {
+                break;
+            }
+
+            let width = rng::range(ecs, MIN_HOUSE_SIZE, MAX_HOUSE_SIZE + 1);
+            let height = rng::range(ecs, MIN_HOUSE_SIZE, MAX_HOUSE_SIZE + 1);
+            let x = rng::range(ecs, 2, map.width - width - 2);
+            let y = rng::range(ecs, 2, map.height - height - 2);
+
+            let house = Rectangle::new(x, y, width - 1, height - 1);
+
+            if reserved.iter().any(|area| house.expand(1).overlaps(area)) {
+                continue;
+            }
+
+            for tile_y in house.top..=house.bottom {
+                for tile_x in house.left..=house.right {
+                    map.set_tile(tile_x, tile_y, TileType::WALL);
+                }
+            }
+
+            map.draw_room(&Rectangle::new(x, y, width - 2, height - 2));
+            map.set_tile(house.center().x, house.bottom, TileType::FLOOR);
+
+            reserved.push(house);
+            houses += 1;
+        }
+    }
+
+    /// Creates every NPC defined in the [Raws] at a random free tile
+    /// of the square around the `center` and stocks their shops.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which the NPCs should be created.
+    /// * `map`: The [Map] of the town.
+    /// * `center`: The center of the square, which is kept free for the player.
+    ///
+    fn spawn_npcs(ecs: &mut World, map: &Map, center: &Position) {
+        let names = ecs.fetch::<Raws>().npc_names();
+        let mut occupied = vec![*center];
+
+        for name in names {
+            let position = loop {
+                let position = Position {
+                    x: center.x + rng::range(ecs, -SQUARE_RADIUS + 1, SQUARE_RADIUS),
+                    y: center.y + rng::range(ecs, -SQUARE_RADIUS + 1, SQUARE_RADIUS),
+                };
+
+                if map.get_tile(position.x, position.y) == TileType::FLOOR
+                    && !occupied.contains(&position)
+                {
+                    break position;
+                }
+            };
+
+            occupied.push(position);
+
+            match entity_factory::new_named(ecs, &name, position) {
+                Some(npc) => shop::stock_shop(ecs, map, npc, &position, &mut occupied),
+                None => console::log(format!("Unknown NPC '{}'!", name)),
+            }
+        }
+    }
+}
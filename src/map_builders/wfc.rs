@@ -0,0 +1,300 @@
+//! Builder creating organic layouts through wave function collapse.
+
+use std::collections::BTreeMap;
+
+use rltk::console;
+use specs::prelude::*;
+
+use crate::{config, rng, Map, TileType};
+
+use super::{CorridorBuilder, MapBuilder, RoomsBuilder};
+
+/// The embedded sample maps, separated by blank lines.
+/// `#` marks a wall and every other character a floor.
+const SAMPLES: &str = include_str!("../../data/wfc_samples.txt");
+
+/// The side length of the patterns learned from the samples.
+const PATTERN_SIZE: i32 = 3;
+
+/// The offsets of the neighbours of a cell in the order up, right,
+/// down and left, so `(direction + 2) % 4` is the opposite direction.
+const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// A square of tiles found in the samples.
+struct Pattern {
+    /// The tiles of the pattern row by row, `true` for walls.
+    walls: Vec<bool>,
+
+    /// How often the pattern occurs in the samples.
+    weight: i32,
+}
+
+impl Pattern {
+    /// Returns `true` if the tile at the passed `x` and `y`
+    /// coordinates inside of the pattern is a wall.
+    fn is_wall(&self, x: i32, y: i32) -> bool {
+        self.walls[(y * PATTERN_SIZE + x) as usize]
+    }
+
+    /// Returns `true` if the `other` pattern can be placed at the
+    /// offset `dx` and `dy` from this pattern, i.e. both patterns
+    /// agree on all tiles they overlap.
+    fn agrees(&self, other: &Pattern, dx: i32, dy: i32) -> bool {
+        (0..PATTERN_SIZE)
+            .flat_map(|y| (0..PATTERN_SIZE).map(move |x| (x, y)))
+            .filter(|(x, y)| {
+                (0..PATTERN_SIZE).contains(&(x - dx)) && (0..PATTERN_SIZE).contains(&(y - dy))
+            })
+            .all(|(x, y)| self.is_wall(x, y) == other.is_wall(x - dx, y - dy))
+    }
+}
+
+/// [MapBuilder] creating an organic layout through the overlapping
+/// model of the wave function collapse algorithm. Every square of
+/// [PATTERN_SIZE] tiles in the hand-made samples of `data/wfc_samples.txt`
+/// becomes a pattern, and patterns may only be placed next to each other
+/// where they overlap without contradiction. Tiles, that can't be reached
+/// from the [Map::player_start], are filled with walls afterwards. The map
+/// has no rooms.
+///
+/// # Notes
+/// * Layouts, whose connected part covers less than the
+///   [config::WFC_MIN_FLOOR_PERCENT] of the map, or which run into a
+///   contradiction, are generated anew, up to [config::WFC_MAX_ATTEMPTS]
+///   times. If all attempts fail, the [RoomsBuilder] and the
+///   [CorridorBuilder] build the map instead.
+///
+pub struct WfcBuilder;
+
+impl MapBuilder for WfcBuilder {
+    fn build(&mut self, ecs: &mut World, map: &mut Map) {
+        let patterns = learn_patterns();
+
+        for _ in 0..config::WFC_MAX_ATTEMPTS {
+            if collapse(ecs, map, &patterns) && connect(map) {
+                return;
+            }
+        }
+
+        console::log("Wave function collapse failed, falling back to rooms!");
+
+        map.tiles.fill(TileType::WALL);
+        RoomsBuilder.build(ecs, map);
+        CorridorBuilder.build(ecs, map);
+    }
+}
+
+/// Parses the [SAMPLES] and returns all distinct patterns in them.
+///
+/// # Notes
+/// * The samples wrap around at their edges, so every
+///   pattern has at least one neighbour in every direction.
+///
+fn learn_patterns() -> Vec<Pattern> {
+    let mut counts: BTreeMap<Vec<bool>, i32> = BTreeMap::new();
+
+    for sample in SAMPLES.split("\n\n") {
+        let rows = sample
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.chars().map(|tile| tile == '#').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let height = rows.len() as i32;
+        let width = rows.iter().map(Vec::len).min().unwrap_or(0) as i32;
+
+        for y in 0..height {
+            for x in 0..width {
+                let walls = (0..PATTERN_SIZE)
+                    .flat_map(|dy| (0..PATTERN_SIZE).map(move |dx| (dx, dy)))
+                    .map(|(dx, dy)| rows[((y + dy) % height) as usize][((x + dx) % width) as usize])
+                    .collect();
+
+                *counts.entry(walls).or_default() += 1;
+            }
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|(walls, weight)| Pattern { walls, weight })
+        .collect()
+}
+
+/// Fills the passed `map` with the passed `patterns` through wave function
+/// collapse and returns `true` on success, or `false` if the algorithm ran
+/// into a contradiction.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the `rng` handler is registered.
+/// * `map`: The [Map] to fill.
+/// * `patterns`: The patterns learned from the samples.
+///
+fn collapse(ecs: &mut World, map: &mut Map, patterns: &[Pattern]) -> bool {
+    let count = patterns.len();
+    let width = map.width - PATTERN_SIZE + 1;
+    let height = map.height - PATTERN_SIZE + 1;
+
+    if count == 0 || width <= 0 || height <= 0 {
+        return false;
+    }
+
+    let cells = (width * height) as usize;
+
+    // The patterns, that may be placed in each direction of every pattern
+    let propagator: Vec<Vec<Vec<usize>>> = DIRECTIONS
+        .iter()
+        .map(|(dx, dy)| {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    (0..count)
+                        .filter(|other| pattern.agrees(&patterns[*other], *dx, *dy))
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut possible = vec![true; cells * count];
+    let mut remaining = vec![count; cells];
+
+    // The amount of patterns supporting a pattern of
+    // a cell through its neighbour in every direction
+    let mut support: Vec<[u32; 4]> = (0..cells)
+        .flat_map(|_| {
+            (0..count).map(|pattern| {
+                let mut support = [0; 4];
+                for (direction, amount) in support.iter_mut().enumerate() {
+                    *amount = propagator[(direction + 2) % 4][pattern].len() as u32;
+                }
+                support
+            })
+        })
+        .collect();
+
+    let mut banned: Vec<(usize, usize)> = Vec::new();
+
+    loop {
+        // Observe the undecided cell with the least remaining patterns
+        let cell = match (0..cells)
+            .filter(|cell| remaining[*cell] > 1)
+            .min_by_key(|cell| remaining[*cell])
+        {
+            Some(cell) => cell,
+            None => break,
+        };
+
+        let options = (0..count)
+            .filter(|pattern| possible[cell * count + pattern])
+            .collect::<Vec<_>>();
+        let total_weight: i32 = options
+            .iter()
+            .map(|pattern| patterns[*pattern].weight)
+            .sum();
+
+        let mut roll = rng::range(ecs, 0, total_weight);
+        let mut chosen = options[0];
+        for pattern in options.iter() {
+            if roll < patterns[*pattern].weight {
+                chosen = *pattern;
+                break;
+            }
+            roll -= patterns[*pattern].weight;
+        }
+
+        for pattern in options.into_iter().filter(|pattern| *pattern != chosen) {
+            possible[cell * count + pattern] = false;
+            remaining[cell] -= 1;
+            banned.push((cell, pattern));
+        }
+
+        // Propagate the removed patterns to the neighbouring cells
+        while let Some((cell, pattern)) = banned.pop() {
+            let (x, y) = (cell as i32 % width, cell as i32 / width);
+
+            for (direction, (dx, dy)) in DIRECTIONS.iter().enumerate() {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                    continue;
+                }
+
+                let neighbour = (ny * width + nx) as usize;
+
+                for other in propagator[direction][pattern].iter() {
+                    let idx = neighbour * count + other;
+                    support[idx][direction] = support[idx][direction].saturating_sub(1);
+
+                    if support[idx][direction] == 0 && possible[idx] {
+                        possible[idx] = false;
+                        remaining[neighbour] -= 1;
+
+                        if remaining[neighbour] == 0 {
+                            return false;
+                        }
+
+                        banned.push((neighbour, *other));
+                    }
+                }
+            }
+        }
+    }
+
+    // Every pattern covers the tiles to the right and below of its cell
+    for cell in 0..cells {
+        let pattern = match (0..count).find(|pattern| possible[cell * count + pattern]) {
+            Some(pattern) => &patterns[pattern],
+            None => return false,
+        };
+        let (x, y) = (cell as i32 % width, cell as i32 / width);
+
+        for dy in 0..PATTERN_SIZE {
+            for dx in 0..PATTERN_SIZE {
+                let tile = if pattern.is_wall(dx, dy) {
+                    TileType::WALL
+                } else {
+                    TileType::FLOOR
+                };
+                map.set_tile(x + dx, y + dy, tile);
+            }
+        }
+    }
+
+    true
+}
+
+/// Surrounds the `map` with walls and fills all tiles, that can't be
+/// reached from the [Map::player_start], with walls. Returns `true` if
+/// the reachable part covers at least the [config::WFC_MIN_FLOOR_PERCENT]
+/// of the map.
+///
+/// # Arguments
+/// * `map`: The [Map] to validate.
+///
+fn connect(map: &mut Map) -> bool {
+    for idx in 0..map.tiles.len() {
+        let (x, y) = map.idx_to_coordinates(idx);
+        if x == 0 || y == 0 || x == map.width - 1 || y == map.height - 1 {
+            map.tiles[idx] = TileType::WALL;
+        }
+    }
+
+    let start = map.player_start();
+    let distances = map.flood_fill(start.x, start.y);
+
+    let reached = distances
+        .iter()
+        .filter(|distance| distance.is_some())
+        .count();
+    if reached * 100 < map.tiles.len() * config::WFC_MIN_FLOOR_PERCENT {
+        return false;
+    }
+
+    for (idx, distance) in distances.iter().enumerate() {
+        if distance.is_none() {
+            map.tiles[idx] = TileType::WALL;
+        }
+    }
+
+    true
+}
@@ -0,0 +1,299 @@
+//! Module for temporary visual effects.
+//!
+//! Systems push requests for particles, e.g. the slash mark of an attack,
+//! into the [ParticleBuilder]. Every frame the requests are turned into
+//! entities with a [ParticleLifetime], whose lifetime is reduced by the
+//! duration of the frame, and the particles are drawn on top of the other
+//! entities until their lifetime has passed. While the player has enabled
+//! [Settings::reduced_motion], the requests are discarded instead.
+//!
+//! The particles are a subsystem without systems of their own, their
+//! [register] function only inserts the [ParticleBuilder].
+
+use rltk::{ColorPair, DrawBatch, FontCharType, Point};
+use specs::prelude::*;
+
+use super::camera::Camera;
+use super::settings::Settings;
+use super::swatch::{self, Pallet};
+use super::{
+    config, pythagoras_distance, submit_batch, AreaOfEffect, Map, ParticleLifetime, Renderable,
+    SystemDispatcherBuilder,
+};
+
+/// A particle, that should be created in the next frame.
+struct ParticleRequest {
+    /// The tile of the particle.
+    position: Point,
+
+    /// The colors of the particle.
+    pallet: Pallet,
+
+    /// The character the particle is displayed with.
+    glyph: FontCharType,
+
+    /// The lifetime of the particle in milliseconds.
+    lifetime_ms: f32,
+}
+
+/// Resource collecting the particles requested
+/// by the systems during the current turn.
+#[derive(Default)]
+pub struct ParticleBuilder {
+    /// The particles, that haven't been created yet.
+    requests: Vec<ParticleRequest>,
+}
+
+impl ParticleBuilder {
+    /// Requests a new particle.
+    ///
+    /// # Arguments
+    /// * `position`: The tile the particle is displayed on.
+    /// * `pallet`: The colors of the particle.
+    /// * `glyph`: The character the particle is displayed with.
+    /// * `lifetime_ms`: The time in milliseconds the particle is displayed.
+    ///
+    pub fn request(
+        &mut self,
+        position: Point,
+        pallet: Pallet,
+        glyph: FontCharType,
+        lifetime_ms: f32,
+    ) -> &mut Self {
+        self.requests.push(ParticleRequest {
+            position,
+            pallet,
+            glyph,
+            lifetime_ms,
+        });
+        self
+    }
+
+    /// Requests the slash mark of an attack on the passed `position`.
+    ///
+    /// # Arguments
+    /// * `position`: The tile of the attacked entity.
+    ///
+    pub fn slash(&mut self, position: Point) -> &mut Self {
+        self.request(
+            position,
+            swatch::PARTICLE_SLASH,
+            rltk::to_cp437('/'),
+            config::PARTICLE_LIFETIME_MS,
+        )
+    }
+
+    /// Requests the sparks of a blocked attack on the passed `position`.
+    ///
+    /// # Arguments
+    /// * `position`: The tile of the blocking entity.
+    ///
+    pub fn block(&mut self, position: Point) -> &mut Self {
+        self.request(
+            position,
+            swatch::PARTICLE_BLOCK,
+            rltk::to_cp437('#'),
+            config::PARTICLE_LIFETIME_MS,
+        )
+    }
+
+    /// Requests the sparkle of a healing effect on the passed `position`.
+    ///
+    /// # Arguments
+    /// * `position`: The tile of the healed entity.
+    ///
+    pub fn heal(&mut self, position: Point) -> &mut Self {
+        self.request(
+            position,
+            swatch::PARTICLE_HEAL,
+            rltk::to_cp437('♥'),
+            config::PARTICLE_LIFETIME_MS,
+        )
+    }
+
+    /// Requests the shimmer of a teleport on the passed `position`.
+    ///
+    /// # Arguments
+    /// * `position`: The tile the entity has teleported to.
+    ///
+    pub fn teleport(&mut self, position: Point) -> &mut Self {
+        self.request(
+            position,
+            swatch::PARTICLE_TELEPORT,
+            rltk::to_cp437('*'),
+            config::PARTICLE_LIFETIME_MS,
+        )
+    }
+
+    /// Requests an explosion burst covering all tiles
+    /// within the `radius` around the `center`.
+    ///
+    /// # Arguments
+    /// * `center`: The tile the explosion originates from.
+    /// * `radius`: The radius of the burst in tiles.
+    ///
+    pub fn burst(&mut self, center: Point, radius: i32) -> &mut Self {
+        for y in center.y - radius..=center.y + radius {
+            for x in center.x - radius..=center.x + radius {
+                self.request(
+                    Point::new(x, y),
+                    swatch::PARTICLE_BURST,
+                    rltk::to_cp437('░'),
+                    config::PARTICLE_LIFETIME_MS,
+                );
+            }
+        }
+        self
+    }
+
+    /// Requests the fire of an explosion on all tiles hit by the `area`
+    /// around the `center`. The fire lingers longest at the center, so
+    /// the explosion shrinks towards it.
+    ///
+    /// # Arguments
+    /// * `center`: The tile the explosion originates from.
+    /// * `area`: The [AreaOfEffect] of the explosion.
+    ///
+    pub fn explosion(&mut self, center: Point, area: &AreaOfEffect) -> &mut Self {
+        let radius = area.radius;
+
+        for y in center.y - radius..=center.y + radius {
+            for x in center.x - radius..=center.x + radius {
+                let tile = Point::new(x, y);
+
+                if !area.covers(&center, &tile) {
+                    continue;
+                }
+
+                let falloff = pythagoras_distance(&center, &tile) / (radius as f32 + 1.0);
+                let glyph = if falloff < 0.5 { '▓' } else { '▒' };
+
+                self.request(
+                    tile,
+                    swatch::PARTICLE_EXPLOSION,
+                    rltk::to_cp437(glyph),
+                    config::EXPLOSION_LIFETIME_MS * (1.0 - falloff),
+                );
+            }
+        }
+        self
+    }
+}
+
+/// Inserts the [ParticleBuilder] into the passed `ecs`. The `builder`
+/// is returned untouched, since the particles are updated every frame
+/// instead of every turn, see [update_particles].
+///
+/// # Arguments
+/// * `ecs`: The [World] the particles are created in.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    ecs.insert(ParticleBuilder::default());
+
+    builder
+}
+
+/// Ages all particles of the passed `ecs` by the `frame_time_ms`, removes
+/// the expired ones and creates the particles requested since the last frame.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the particles.
+/// * `frame_time_ms`: The duration of the last frame in milliseconds.
+///
+/// # Notes
+/// * With [Settings::reduced_motion] enabled the requested particles,
+///   e.g. flashes and explosions, are dropped without being created.
+///
+pub fn update_particles(ecs: &mut World, frame_time_ms: f32) {
+    let expired = {
+        let entities = ecs.entities();
+        let mut particles = ecs.write_storage::<ParticleLifetime>();
+
+        (&entities, &mut particles)
+            .join()
+            .filter_map(|(entity, particle)| {
+                particle.lifetime_ms -= frame_time_ms;
+                (particle.lifetime_ms <= 0.0).then_some(entity)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Particles are never referenced by other entities, so a failed deletion can be ignored
+    let _ = ecs.delete_entities(&expired);
+
+    let requests = match ecs.try_fetch_mut::<ParticleBuilder>() {
+        Some(mut builder) => std::mem::take(&mut builder.requests),
+        None => return,
+    };
+
+    let is_motion_reduced = ecs
+        .try_fetch::<Settings>()
+        .is_some_and(|settings| settings.reduced_motion);
+
+    if is_motion_reduced {
+        return;
+    }
+
+    for request in requests {
+        let (fg, bg) = request.pallet.colors();
+
+        ecs.create_entity()
+            .with(ParticleLifetime {
+                position: request.position,
+                lifetime_ms: request.lifetime_ms,
+            })
+            .with(Renderable {
+                symbol: request.glyph,
+                fg,
+                bg,
+                order: 0,
+            })
+            .build();
+    }
+}
+
+/// Returns `true` if any particle is displayed or requested, `false` otherwise.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the particles.
+///
+pub fn has_particles(ecs: &World) -> bool {
+    let is_requested = ecs
+        .try_fetch::<ParticleBuilder>()
+        .is_some_and(|builder| !builder.requests.is_empty());
+
+    is_requested || !ecs.read_storage::<ParticleLifetime>().is_empty()
+}
+
+/// Draws all particles on tiles in the player's field of view.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the particles.
+/// * `camera`: The [Camera] deciding the visible part of the map.
+///
+pub fn draw_particles(ecs: &World, camera: &Camera) {
+    let map = ecs.fetch::<Map>();
+    let particles = ecs.read_storage::<ParticleLifetime>();
+    let renderables = ecs.read_storage::<Renderable>();
+
+    let mut batch = DrawBatch::new();
+
+    for (particle, renderable) in (&particles, &renderables).join() {
+        let Point { x, y } = particle.position;
+
+        if !map.check_idx(x, y) || !map.is_tile_in_fov(x, y) {
+            continue;
+        }
+
+        if let Some(screen) = camera.to_screen(particle.position) {
+            batch.set(
+                screen,
+                ColorPair::new(renderable.fg, renderable.bg),
+                renderable.symbol,
+            );
+        }
+    }
+
+    submit_batch(&mut batch, config::LAYER_PARTICLES);
+}
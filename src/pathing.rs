@@ -0,0 +1,268 @@
+//! Module for path finding with a bounded search budget.
+//!
+//! [rltk::a_star_search] explores up to 65536 tiles before it gives up,
+//! which makes requests for unreachable targets expensive. The search in
+//! this module stops after a configurable amount of expanded tiles, so
+//! such requests fail fast.
+//!
+//! The [TerrainView] of a [Map] ignores entities blocking tiles, so paths
+//! can be planned through tiles, that are only temporarily occupied.
+//!
+//! The [dijkstra_map] of a map holds the cost of reaching every tile from a
+//! set of seeds, e.g. the distance to the player. Inverting such a map and
+//! relaxing it once more yields the [flee_map] of the fleeing monsters.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use rltk::{BaseMap, SmallVec};
+
+use super::{config, Map};
+
+/// Read only view of a [Map] for path finding, that only
+/// considers the terrain of the tiles and ignores all
+/// entities blocking them.
+pub struct TerrainView<'a> {
+    /// The viewed [Map].
+    map: &'a Map,
+}
+
+impl<'a> TerrainView<'a> {
+    /// Creates a new [TerrainView] of the passed `map`.
+    ///
+    /// # Arguments
+    /// * `map`: The [Map] to view.
+    ///
+    pub fn new(map: &'a Map) -> Self {
+        TerrainView { map }
+    }
+}
+
+impl BaseMap for TerrainView<'_> {
+    fn is_opaque(&self, idx: usize) -> bool {
+        self.map.is_opaque(idx)
+    }
+
+    fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
+        self.map
+            .exits_where(idx, |x, y| self.map.is_terrain_walkable(x, y))
+    }
+
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        self.map.get_pathing_distance(idx1, idx2)
+    }
+}
+
+/// A tile in the open list of the search,
+/// ordered by its estimated total cost.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OpenTile {
+    /// Index of the tile.
+    idx: usize,
+
+    /// Cost from the start to the tile plus the
+    /// estimated cost from the tile to the end.
+    estimate: f32,
+}
+
+impl Eq for OpenTile {}
+
+impl Ord for OpenTile {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so the [BinaryHeap] pops the cheapest tile first
+        other
+            .estimate
+            .partial_cmp(&self.estimate)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for OpenTile {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Searches a path from the `start` to the `end` tile with the A*
+/// algorithm and returns the indices of its steps, excluding the
+/// `start` and including the `end` tile.
+///
+/// # Arguments
+/// * `map`: The map to search, providing the exits of each tile and
+///   the estimated distance between two tiles.
+/// * `start`: Index of the tile the path starts at.
+/// * `end`: Index of the tile the path should lead to.
+/// * `max_expansions`: The maximum amount of tiles, whose exits are
+///   explored before the search is aborted.
+///
+/// # Notes
+/// * Returns `None` if the `end` is unreachable or the search
+///   exceeds the `max_expansions`.
+///
+pub fn a_star_search_bounded<M: BaseMap>(
+    map: &M,
+    start: usize,
+    end: usize,
+    max_expansions: usize,
+) -> Option<Vec<usize>> {
+    let mut open = BinaryHeap::new();
+    let mut closed = HashSet::new();
+    let mut costs: HashMap<usize, f32> = HashMap::new();
+    let mut parents: HashMap<usize, usize> = HashMap::new();
+
+    open.push(OpenTile {
+        idx: start,
+        estimate: map.get_pathing_distance(start, end),
+    });
+    costs.insert(start, 0.0);
+
+    while let Some(current) = open.pop() {
+        if current.idx == end {
+            return Some(reconstruct_path(&parents, start, end));
+        }
+
+        if !closed.insert(current.idx) {
+            continue;
+        }
+
+        if closed.len() > max_expansions {
+            return None;
+        }
+
+        let current_cost = costs[&current.idx];
+
+        for (neighbour, step_cost) in map.get_available_exits(current.idx) {
+            let cost = current_cost + step_cost;
+
+            if costs.get(&neighbour).is_some_and(|known| *known <= cost) {
+                continue;
+            }
+
+            costs.insert(neighbour, cost);
+            parents.insert(neighbour, current.idx);
+            open.push(OpenTile {
+                idx: neighbour,
+                estimate: cost + map.get_pathing_distance(neighbour, end),
+            });
+        }
+    }
+
+    None
+}
+
+/// Calculates the cost of reaching every tile of the `map` from the
+/// cheapest of the passed `seeds`, each of which starts with the cost
+/// it's paired with, and returns the costs indexed by tile.
+///
+/// # Arguments
+/// * `map`: The map to search, providing the exits of each tile.
+/// * `size`: The amount of tiles of the `map`.
+/// * `seeds`: The indices of the tiles the search starts at and their costs.
+/// * `max_cost`: The cost up to which tiles are reached.
+///
+/// # Notes
+/// * Tiles, that can't be reached within the `max_cost`, cost [f32::MAX].
+///
+pub fn dijkstra_map<M: BaseMap>(
+    map: &M,
+    size: usize,
+    seeds: &[(usize, f32)],
+    max_cost: f32,
+) -> Vec<f32> {
+    let mut costs = vec![f32::MAX; size];
+    let mut open = BinaryHeap::new();
+
+    for (idx, cost) in seeds.iter().copied() {
+        if cost < costs[idx] {
+            costs[idx] = cost;
+            open.push(OpenTile {
+                idx,
+                estimate: cost,
+            });
+        }
+    }
+
+    while let Some(current) = open.pop() {
+        // Skip outdated entries of tiles, that have been reached cheaper since
+        if current.estimate > costs[current.idx] {
+            continue;
+        }
+
+        for (neighbour, step_cost) in map.get_available_exits(current.idx) {
+            let cost = current.estimate + step_cost;
+
+            if cost > max_cost || cost >= costs[neighbour] {
+                continue;
+            }
+
+            costs[neighbour] = cost;
+            open.push(OpenTile {
+                idx: neighbour,
+                estimate: cost,
+            });
+        }
+    }
+
+    costs
+}
+
+/// Calculates the map fleeing monsters follow to escape from the tile
+/// at the passed `threat` index and returns it indexed by tile. The
+/// distances to the `threat` are multiplied by the negative
+/// [config::FLEE_INVERSION_FACTOR] and relaxed once more, so lower values
+/// are safer and stepping downhill leads away from the threat. Unlike
+/// climbing the plain distances, the monsters don't run into dead ends,
+/// but slip past the threat towards the open parts of the map.
+///
+/// # Arguments
+/// * `map`: The [Map] of the level.
+/// * `threat`: Index of the tile to flee from.
+///
+/// # Notes
+/// * The distances ignore entities blocking tiles and only reach up to
+///   [config::FLEE_SEARCH_DEPTH], all tiles beyond are equally safe.
+///
+pub fn flee_map(map: &Map, threat: usize) -> Vec<f32> {
+    let view = TerrainView::new(map);
+    let size = map.tiles.len();
+
+    let distances = dijkstra_map(&view, size, &[(threat, 0.0)], config::FLEE_SEARCH_DEPTH);
+
+    let seeds = distances
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| map.tiles[*idx].is_pathable())
+        .map(|(idx, distance)| {
+            let distance = distance.min(config::FLEE_SEARCH_DEPTH);
+            (idx, -config::FLEE_INVERSION_FACTOR * distance)
+        })
+        .collect::<Vec<_>>();
+
+    dijkstra_map(&view, size, &seeds, f32::MAX)
+}
+
+/// Follows the `parents` back from the `end` to the `start` tile
+/// and returns the steps of the path in walking order, excluding
+/// the `start` tile.
+///
+/// # Arguments
+/// * `parents`: The tile each tile has been reached from.
+/// * `start`: Index of the tile the path starts at.
+/// * `end`: Index of the tile the path leads to.
+///
+fn reconstruct_path(parents: &HashMap<usize, usize>, start: usize, end: usize) -> Vec<usize> {
+    let mut steps = vec![end];
+    let mut current = end;
+
+    while let Some(parent) = parents.get(&current) {
+        if *parent == start {
+            break;
+        }
+
+        steps.push(*parent);
+        current = *parent;
+    }
+
+    steps.reverse();
+    steps
+}
@@ -1,16 +1,26 @@
 //! Collection of functions for the player.
 
-use std::cmp::{max, min};
-
-use rltk::{a_star_search, Point, Rltk, VirtualKeyCode};
+use rltk::{Point, Rltk, VirtualKeyCode};
 use specs::prelude::*;
 use specs::shred::Fetch;
 
-use crate::{DialogInterface, DialogOption, Loot, Name, Potion};
-
+use super::camera::Camera;
+use super::identification::Identification;
+use super::keybindings::{KeyAction, KeyBindings};
+use super::pathing::{a_star_search_bounded, TerrainView};
+use super::raws::Raws;
+use super::touch::TouchGesture;
+use super::ui_controller::{self, InventoryView, LogHistoryView, MainMenuSelection, TouchButton};
+use super::{
+    ammunition, containers, debug_console, diagnostics, doors, localization, quests, rng, shop,
+    wizard,
+};
 use super::{
-    config, i32_to_alpha_key, Item, Map, MeleeAttack, Player, PlayerPathing, Position,
-    ProcessingState, State, Statistics, FOV,
+    config, i32_to_alpha_key, publish_event, CastSpell, Container, DialogArgs, DialogInterface,
+    DialogOption, Door, Equipped, Facing, Friendly, GameLog, Item, LogCategory, Mana, Map,
+    MeleeAttackEvent, Monster, Name, Player, PlayerPathing, PlayerResting, PlayerRunning, Position,
+    RangedWeapon, RunState, Shopkeeper, Sneaking, Spellbook, State, Statistics, StatusEffectKind,
+    StatusEffects, TileType, TurnQueue, FOV,
 };
 
 /// Moves the [Player] entity through its stored [Position]
@@ -25,52 +35,119 @@ use super::{
 /// # Note
 /// If the coordinate the player tries to move to is out of
 /// bounds or not walkable, the player wont be moved.
+/// A confused player moves in a random direction instead.
+/// Moving into a [Friendly] entity greets it instead of
+/// attacking it and takes the next quest it offers.
+/// Bumping into a [Shopkeeper] offers to trade with it.
+/// Bumping into a closed [Door] opens it, locked
+/// doors only open with their key, see [doors::bump].
+/// Bumping into a [Container] opens it.
 ///  
 fn player_move(delta_x: i32, delta_y: i32, ecs: &mut World) {
-    // Fetch map from ecs
-    let map = ecs.fetch::<Map>();
-    let entities = ecs.entities();
-
-    // Write ecs storages
-    let mut fovs = ecs.write_storage::<FOV>();
-    let players = ecs.write_storage::<Player>();
-    let mut positions = ecs.write_storage::<Position>();
-    let mut melee_attacks = ecs.write_storage::<MeleeAttack>();
-    let mut player_ecs_position = ecs.write_resource::<Point>();
-
-    // Read ecs storages
-    let statistics = ecs.read_storage::<Statistics>();
-
-    for (entity, _, position, fov) in (&entities, &players, &mut positions, &mut fovs).join() {
-        let new_position = Position {
-            x: position.x + delta_x,
-            y: position.y + delta_y,
-        };
+    let (delta_x, delta_y) = if player_has_status(ecs, StatusEffectKind::Confusion) {
+        let direction =
+            Facing::DIRECTIONS[rng::range(ecs, 0, Facing::DIRECTIONS.len() as i32) as usize];
+        (direction.x, direction.y)
+    } else {
+        (delta_x, delta_y)
+    };
+
+    let (shopkeeper, closed_door, container) = {
+        // Fetch map from ecs
+        let map = ecs.fetch::<Map>();
+        let entities = ecs.entities();
+
+        // Write ecs storages
+        let mut fovs = ecs.write_storage::<FOV>();
+        let players = ecs.write_storage::<Player>();
+        let mut positions = ecs.write_storage::<Position>();
+        let mut player_ecs_position = ecs.write_resource::<Point>();
+
+        // Read ecs storages
+        let statistics = ecs.read_storage::<Statistics>();
+        let friendlies = ecs.read_storage::<Friendly>();
+        let shopkeepers = ecs.read_storage::<Shopkeeper>();
+        let names = ecs.read_storage::<Name>();
+        let doors = ecs.read_storage::<Door>();
+        let containers = ecs.read_storage::<Container>();
+
+        let mut shopkeeper = None;
+        let mut closed_door = None;
+        let mut container = None;
+
+        for (entity, _, position, fov) in (&entities, &players, &mut positions, &mut fovs).join() {
+            let new_position = Position {
+                x: position.x + delta_x,
+                y: position.y + delta_y,
+            };
+
+            for target in map.tile_contents_get(new_position.x, new_position.y) {
+                if doors.get(target).is_some_and(|door| !door.open) {
+                    closed_door = Some(target);
+                    continue;
+                }
+
+                if containers.contains(target) {
+                    container = Some(target);
+                    continue;
+                }
+
+                if let Some(friendly) = friendlies.get(target) {
+                    let name = names.get(target).map(|name| name.display());
+
+                    ecs.write_resource::<GameLog>().push(
+                        &localization::tr_with(&friendly.greeting, &[&name.unwrap_or_default()]),
+                        LogCategory::General,
+                    );
+
+                    if let Some(name) = names.get(target) {
+                        quests::offer(ecs, &name.name);
+                    }
+
+                    if shopkeepers.contains(target) {
+                        shopkeeper = Some(target);
+                    }
+                    continue;
+                }
 
-        for target in map.tile_contents_get(new_position.x, new_position.y).iter() {
-            let enemy = statistics.get(*target);
+                let enemy = statistics.get(target);
 
-            if let Some(_) = enemy {
-                let attack = MeleeAttack { target: *target };
+                if enemy.is_some() {
+                    let attack = MeleeAttackEvent {
+                        attacker: entity,
+                        target,
+                    };
 
-                melee_attacks.insert(entity, attack).expect(&format!(
-                    "Adding melee attack from player agianst entity with id {} failed!",
-                    entity.id()
-                ));
+                    publish_event(ecs, attack);
+                }
             }
-        }
 
-        let is_new_position_blocked = map.is_tile_blocked(new_position.x, new_position.y);
+            let is_new_position_blocked = map.is_tile_blocked(new_position.x, new_position.y);
 
-        if !is_new_position_blocked {
-            position.x = min(config::WINDOW_WIDTH - 1, max(0, new_position.x));
-            position.y = min(config::WINDOW_HEIGHT - 1, max(0, new_position.y));
+            if !is_new_position_blocked {
+                position.x = new_position.x.clamp(0, map.width - 1);
+                position.y = new_position.y.clamp(0, map.height - 1);
 
-            player_ecs_position.x = position.x;
-            player_ecs_position.y = position.y;
+                player_ecs_position.x = position.x;
+                player_ecs_position.y = position.y;
 
-            fov.is_dirty = true;
+                fov.is_dirty = true;
+            }
         }
+
+        (shopkeeper, closed_door, container)
+    };
+
+    if let Some(shopkeeper) = shopkeeper {
+        shop::offer_trade(ecs, shopkeeper);
+    }
+
+    if let Some(door) = closed_door {
+        doors::bump(ecs, door);
+    }
+
+    if let Some(container) = container {
+        containers::open(ecs, container);
     }
 }
 
@@ -109,37 +186,53 @@ fn player_move_click(ecs: &mut World) -> Option<(i32, i32)> {
 ///
 /// # Arguments
 /// * `ecs`: The [World] in which all [Entity] structs are stored.
-/// * `ctx`: The [Rltk] context in which the mouse click happned.
+/// * `target`: The tile the player has clicked.
+///
+/// # Notes
+/// * Tiles outside of the player's fov are ignored without a search.
+/// * Clicks on the target of the current path or on a target, that has
+///   just been found unreachable, don't start a new search.
+/// * The search is aborted after [config::MAX_PATHING_EXPANSIONS] tiles.
+/// * The path is planned on the [TerrainView] of the [Map], so tiles
+///   blocked by other entities don't divert it.
 ///
-fn handle_new_click_to_move(ecs: &mut World, ctx: &Rltk) {
+fn handle_new_click_to_move(ecs: &mut World, target: Point) {
     let fovs = ecs.read_storage::<FOV>();
-    let mut map = ecs.write_resource::<Map>();
+    let map = ecs.fetch::<Map>();
     let player = ecs.read_resource::<Entity>();
     let player_ecs_position = ecs.write_resource::<Point>();
     let mut pathing_writer = ecs.write_resource::<PlayerPathing>();
 
-    let mouse_position = ctx.mouse_point();
-
-    let start_idx = map.coordinates_to_idx(player_ecs_position.x, player_ecs_position.y);
-    let end_idx = map.coordinates_to_idx(mouse_position.x, mouse_position.y);
+    let is_visible = fovs.get(*player).is_some_and(|fov| fov.contains(&target));
 
-    let blocked_tiles = map.blocked_tiles.clone();
-    map.refresh_blocked_tiles();
+    if !is_visible || !map.check_idx(target.x, target.y) {
+        return;
+    }
 
-    let mut path = a_star_search(start_idx, end_idx, &mut *map);
+    let start_idx = map.coordinates_to_idx(player_ecs_position.x, player_ecs_position.y);
+    let end_idx = map.coordinates_to_idx(target.x, target.y);
 
-    map.blocked_tiles = blocked_tiles;
+    if start_idx == end_idx || pathing_writer.is_cached(start_idx, end_idx) {
+        return;
+    }
 
-    if let Some(fov) = fovs.get(*player) {
-        if path.success && path.steps.len() > 1 && fov.contains(&mouse_position) {
-            path.steps.remove(0);
-            path.steps.reverse();
-            pathing_writer.update(&mut path.steps);
+    let path = a_star_search_bounded(
+        &TerrainView::new(&map),
+        start_idx,
+        end_idx,
+        config::MAX_PATHING_EXPANSIONS,
+    );
+
+    match path {
+        Some(mut steps) => {
+            steps.reverse();
+            pathing_writer.update(&mut steps, end_idx);
         }
+        None => pathing_writer.mark_unreachable(start_idx, end_idx),
     }
 }
 
-/// Creates a new [PickupItem] request
+/// Publishes a new [PickupEvent]
 /// for the player [Entity].
 ///
 /// # Arguments
@@ -155,68 +248,536 @@ fn pick_up_item(ecs: &mut World) {
     Item::pick_up(ecs, &player);
 }
 
-/// Registers a new [DialogInterface] that contains
-/// the item [Entity] structs the player currently
-/// has in its inventory. If `drop` is true,
-/// the player can drop items from the inventory,
-/// otherwise he uses them.
+/// Starts or stops the player sneaking and reports it to the [GameLog].
 ///
 /// # Arguments
 /// * `ecs`: The [World] in which the player is stored.
-/// * `drop`: Flag indicating whether or not the player
-/// wants to drop items or use them.
 ///
-fn show_inventory(ecs: &mut World, drop: bool) {
-    let mut options: Vec<DialogOption> = Vec::new();
+/// # Notes
+/// * Toggling doesn't take a turn.
+///
+fn toggle_sneaking(ecs: &mut World) -> RunState {
+    let player = *get_player_entity(ecs);
+    let mut sneaking = ecs.write_storage::<Sneaking>();
 
-    {
-        let entities = ecs.entities();
-        let player = get_player_entity(&ecs);
+    let id = if sneaking.remove(player).is_some() {
+        "stealth.sneak_off"
+    } else {
+        let _ = sneaking.insert(player, Sneaking {});
+        "stealth.sneak_on"
+    };
+
+    ecs.write_resource::<GameLog>()
+        .push(&localization::tr(id), LogCategory::General);
+
+    RunState::AwaitingInput
+}
+
+/// Checks if the player stands on stairs leading down. If so,
+/// [RunState::NextLevel] is returned to descend to the next level
+/// of the dungeon. Otherwise a message is sent to the [GameLog] and
+/// the game keeps waiting for input.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player and the [Map] are stored.
+///
+fn try_descend(ecs: &mut World) -> RunState {
+    let map = ecs.fetch::<Map>();
+    let player_position = ecs.fetch::<Point>();
+
+    if map.get_tile(player_position.x, player_position.y) == TileType::DOWNSTAIRS {
+        return RunState::NextLevel;
+    }
+
+    let mut game_log = ecs.fetch_mut::<GameLog>();
+    game_log.messages_push(&localization::tr("log.no_way_down"));
+
+    RunState::AwaitingInput
+}
+
+/// Registers a [DialogInterface] describing the `target` tile, i.e.
+/// the names of all visible entities on it and its terrain. Returns
+/// `false` without a dialog if the tile hasn't been explored yet.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the dialog should be registered.
+/// * `target`: The tile to examine.
+///
+fn show_examine_dialog(ecs: &mut World, target: Point) -> bool {
+    let description = {
+        let map = ecs.fetch::<Map>();
+        let identification = ecs.fetch::<Identification>();
         let names = ecs.read_storage::<Name>();
-        let backpack = ecs.read_storage::<Loot>();
 
-        let mut counter = 0;
+        if !map.check_idx(target.x, target.y) || !map.is_tile_explored(target.x, target.y) {
+            return false;
+        }
 
-        for (entity, _, name) in (&entities, &backpack, &names)
-            .join()
-            .filter(|item| item.1.owner == *player)
-        {
-            options.push(DialogOption {
-                description: name.name.to_string(),
-                key: i32_to_alpha_key(counter),
-                args: vec![Box::new(entity), Box::new(*player), Box::new(drop)],
-                callback: Box::new(|world, _, args| {
-                    let item = *args[0].downcast_ref::<Entity>().unwrap();
-                    let player = *args[1].downcast_ref::<Entity>().unwrap();
-                    let is_dropping_item = *args[2].downcast_ref::<bool>().unwrap();
-
-                    if is_dropping_item {
-                        Item::drop_item(world, &player, &item);
-                    } else {
-                        Potion::drink(world, &player, &item);
-                    }
-                }),
-            });
+        let mut descriptions: Vec<String> = Vec::new();
 
-            counter += 1;
+        if map.is_tile_in_fov(target.x, target.y) {
+            for entity in map.tile_contents_get(target.x, target.y) {
+                if let Some(name) = names.get(entity) {
+                    descriptions.push(identification.display(name));
+                }
+            }
         }
+
+        descriptions.push(map.get_tile(target.x, target.y).description());
+        descriptions.join(", ")
+    };
+
+    DialogInterface::register_dialog(
+        ecs,
+        localization::tr("dialog.examine.title"),
+        Some(description),
+        vec![],
+        true,
+    );
+
+    true
+}
+
+/// Handles the press of a touch `button` and returns the next [RunState].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+/// * `button`: The pressed [TouchButton].
+///
+fn handle_touch_button(ecs: &mut World, button: TouchButton) -> RunState {
+    match button {
+        TouchButton::Inventory => RunState::ShowInventory {
+            view: InventoryView::default(),
+        },
+        TouchButton::Wait => RunState::PlayerTurn,
+        TouchButton::Stairs => try_descend(ecs),
+    }
+}
+
+/// Registers the pause [DialogInterface], from which the player
+/// can save, load, return to the main menu or quit the game.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the dialog should be registered.
+///
+fn show_pause_dialog(ecs: &mut World) {
+    DialogInterface::register_dialog(
+        ecs,
+        localization::tr("dialog.pause.title"),
+        Some(localization::tr("dialog.pause.message")),
+        vec![
+            DialogOption {
+                description: localization::tr("dialog.pause.save"),
+                key: VirtualKeyCode::S,
+                args: vec![],
+                callback: Box::new(|world, _, _| {
+                    *world.write_resource::<RunState>() = RunState::SaveGame;
+                }),
+            },
+            DialogOption {
+                description: localization::tr("dialog.pause.load"),
+                key: VirtualKeyCode::L,
+                args: vec![],
+                callback: Box::new(|world, _, _| {
+                    *world.write_resource::<RunState>() = RunState::LoadGame;
+                }),
+            },
+            DialogOption {
+                description: localization::tr("dialog.pause.menu"),
+                key: VirtualKeyCode::M,
+                args: vec![],
+                callback: Box::new(|world, _, _| {
+                    *world.write_resource::<RunState>() = RunState::MainMenu {
+                        selection: MainMenuSelection::NewGame,
+                    };
+                }),
+            },
+            DialogOption {
+                description: localization::tr("dialog.pause.quit"),
+                key: VirtualKeyCode::Q,
+                args: vec![],
+                callback: Box::new(|_, ctx, _| ctx.quit()),
+            },
+        ],
+        true,
+    );
+}
+
+/// Registers the spell [DialogInterface], listing the spells of the
+/// player's [Spellbook] with their mana costs, and returns the next
+/// [RunState]. If the player knows no spells, the dialog isn't shown.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the dialog should be registered.
+///
+fn show_spell_dialog(ecs: &mut World) -> RunState {
+    let options = {
+        let player = get_player_entity(ecs);
+        let raws = ecs.fetch::<Raws>();
+        let spellbooks = ecs.read_storage::<Spellbook>();
+
+        let spells = spellbooks
+            .get(*player)
+            .map_or_else(Vec::new, |spellbook| spellbook.spells.clone());
+
+        spells
+            .iter()
+            .enumerate()
+            .filter_map(|(index, name)| raws.spell(name).map(|spell| (index, spell)))
+            .map(|(index, spell)| DialogOption {
+                description: localization::tr_with(
+                    "dialog.spells.option",
+                    &[&localization::entity_name(&spell.name), &spell.mana_cost],
+                ),
+                key: i32_to_alpha_key(index as i32),
+                args: vec![Box::new(index)],
+                callback: Box::new(|world, _, args| select_spell(world, args)),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if options.is_empty() {
+        ecs.write_resource::<GameLog>()
+            .push(&localization::tr("spell.none_known"), LogCategory::General);
+        return RunState::AwaitingInput;
+    }
+
+    DialogInterface::register_dialog(
+        ecs,
+        localization::tr("dialog.spells.title"),
+        None,
+        options,
+        true,
+    );
+
+    RunState::Dialog
+}
+
+/// Casts the spell selected in the spell dialog. Spells with a range
+/// require the player to select a target first. The spell isn't cast
+/// and the player keeps the turn, if the player lacks the [Mana].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+/// * `args`: The arguments of the selected option, i.e. the
+///   index of the spell in the player's [Spellbook].
+///
+fn select_spell(ecs: &World, args: &DialogArgs) {
+    let index = match args.first().and_then(|arg| arg.downcast_ref::<usize>()) {
+        Some(index) => *index,
+        None => return,
+    };
+
+    let player = *get_player_entity(ecs);
+
+    let spell = {
+        let raws = ecs.fetch::<Raws>();
+        let spellbooks = ecs.read_storage::<Spellbook>();
+
+        spellbooks
+            .get(player)
+            .and_then(|spellbook| spellbook.spells.get(index))
+            .and_then(|name| raws.spell(name))
+            .cloned()
+    };
+
+    let spell = match spell {
+        Some(spell) => spell,
+        None => return,
+    };
+
+    let mp = ecs
+        .read_storage::<Mana>()
+        .get(player)
+        .map_or(0, |mana| mana.mp);
+
+    if mp < spell.mana_cost {
+        let caster_name = ecs
+            .read_storage::<Name>()
+            .get(player)
+            .map_or_else(|| localization::tr("name.someone"), Name::display);
+
+        ecs.write_resource::<GameLog>().push(
+            &localization::tr_with(
+                "spell.no_mana",
+                &[&caster_name, &localization::entity_name(&spell.name)],
+            ),
+            LogCategory::Combat,
+        );
+        return;
     }
 
-    let message = if options.is_empty() {
-        if drop {
-            "No items to drop...".to_string()
-        } else {
-            "You backpack is empty...".to_string()
+    let next_run_state = match spell.range {
+        Some(range) => RunState::SpellTargeting {
+            range,
+            radius: spell.radius,
+            spell: index,
+        },
+        None => match CastSpell::cast(ecs, &player, &spell.name, None) {
+            Ok(_) => RunState::PlayerTurn,
+            Err(error) => {
+                ecs.write_resource::<GameLog>().push_error(&error);
+                return;
+            }
+        },
+    };
+
+    *ecs.write_resource::<RunState>() = next_run_state;
+}
+
+/// Lets the player aim the wielded [RangedWeapon] and returns the next
+/// [RunState]. Without a ranged weapon at hand or the [Ammunition] it
+/// fires in the quiver, the player is told so and keeps the turn.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+///
+fn start_firing(ecs: &mut World) -> RunState {
+    let player = *ecs.fetch::<Entity>();
+
+    let weapon = {
+        let equipped = ecs.read_storage::<Equipped>();
+        let weapons = ecs.read_storage::<RangedWeapon>();
+
+        RangedWeapon::wielded(player, &equipped, &weapons).cloned()
+    };
+
+    let weapon = match weapon {
+        Some(weapon) => weapon,
+        None => {
+            ecs.write_resource::<GameLog>()
+                .push(&localization::tr("ranged.no_weapon"), LogCategory::Combat);
+            return RunState::AwaitingInput;
         }
+    };
+
+    let has_ammunition = weapon
+        .ammunition
+        .is_none_or(|kind| ammunition::quivered(ecs, player, kind).is_some());
+
+    if !has_ammunition {
+        ecs.write_resource::<GameLog>().push(
+            &localization::tr("ranged.no_ammunition"),
+            LogCategory::Combat,
+        );
+        return RunState::AwaitingInput;
+    }
+
+    RunState::FireTargeting {
+        range: weapon.range,
+    }
+}
+
+/// Starts resting, if the player has not yet recovered
+/// and no monster is in view, and returns the next [RunState].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+///
+fn start_resting(ecs: &mut World) -> RunState {
+    let (id, next_run_state) = if is_monster_in_view(ecs) {
+        ("rest.enemies", RunState::AwaitingInput)
+    } else if is_player_recovered(ecs) {
+        ("rest.healthy", RunState::AwaitingInput)
     } else {
-        if drop {
-            "Select item to drop".to_string()
-        } else {
-            "Select an item to use/equip".to_string()
-        }
+        ecs.write_resource::<PlayerResting>().start();
+        ("rest.start", RunState::PlayerTurn)
+    };
+
+    ecs.write_resource::<GameLog>()
+        .push(&localization::tr(id), LogCategory::General);
+
+    next_run_state
+}
+
+/// Skips the turn of the resting player and returns the next
+/// [RunState]. The rest ends once the player has recovered, a
+/// monster comes into view or a key has been `pressed`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+/// * `pressed`: Flag indicating if a key has been pressed.
+///
+fn continue_resting(ecs: &mut World, pressed: bool) -> RunState {
+    let id = if pressed || is_monster_in_view(ecs) {
+        "rest.interrupted"
+    } else if is_player_recovered(ecs) {
+        "rest.done"
+    } else {
+        return RunState::PlayerTurn;
     };
 
-    DialogInterface::register_dialog(ecs, "Inventory".to_string(), Some(message), options, true);
+    ecs.write_resource::<PlayerResting>().stop();
+    ecs.write_resource::<GameLog>()
+        .push(&localization::tr(id), LogCategory::General);
+
+    RunState::AwaitingInput
+}
+
+/// Starts running in the passed `direction` with the first step
+/// and returns the next [RunState]. A confused player or a player
+/// with a monster in view only takes a single step.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+/// * `direction`: The `(x, y)` delta of every step.
+///
+fn start_running(ecs: &mut World, direction: (i32, i32)) -> RunState {
+    if !player_has_status(ecs, StatusEffectKind::Confusion) && !is_monster_in_view(ecs) {
+        ecs.write_resource::<PlayerRunning>().start(direction);
+    }
+
+    player_move(direction.0, direction.1, ecs);
+
+    RunState::PlayerTurn
+}
+
+/// Takes the next step of the running player and returns the next
+/// [RunState]. The run ends at junctions and room entrances, on
+/// items and stairs, in front of obstacles and hazardous terrain,
+/// once a monster comes into view or if a key has been `pressed`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+/// * `direction`: The `(x, y)` delta of every step.
+/// * `pressed`: Flag indicating if a key has been pressed.
+///
+fn continue_running(ecs: &mut World, direction: (i32, i32), pressed: bool) -> RunState {
+    let (exits, is_interesting, is_blocked) = {
+        let map = ecs.fetch::<Map>();
+        let player_position = ecs.fetch::<Point>();
+        let items = ecs.read_storage::<Item>();
+
+        let exits = Facing::DIRECTIONS
+            .iter()
+            .filter(|offset| {
+                map.is_terrain_walkable(player_position.x + offset.x, player_position.y + offset.y)
+            })
+            .count();
+
+        let is_interesting = map.get_tile(player_position.x, player_position.y)
+            == TileType::DOWNSTAIRS
+            || map
+                .tile_contents_get(player_position.x, player_position.y)
+                .any(|entity| items.contains(entity));
+
+        let (next_x, next_y) = (
+            player_position.x + direction.0,
+            player_position.y + direction.1,
+        );
+        // Runs also end in front of water, lava and chasms
+        let is_blocked = !map.check_idx(next_x, next_y)
+            || map.is_tile_blocked(next_x, next_y)
+            || matches!(
+                map.get_tile(next_x, next_y),
+                TileType::DEEP_WATER | TileType::LAVA | TileType::CHASM
+            );
+
+        (exits, is_interesting, is_blocked)
+    };
+
+    let is_junction = ecs.write_resource::<PlayerRunning>().update_exits(exits);
+
+    if pressed || is_junction || is_interesting || is_blocked || is_monster_in_view(ecs) {
+        ecs.write_resource::<PlayerRunning>().stop();
+        return RunState::AwaitingInput;
+    }
+
+    player_move(direction.0, direction.1, ecs);
+
+    RunState::PlayerTurn
+}
+
+/// Returns `true` if a [Monster] is within the player's [FOV].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+///
+fn is_monster_in_view(ecs: &World) -> bool {
+    let player = get_player_entity(ecs);
+    let fovs = ecs.read_storage::<FOV>();
+    let monsters = ecs.read_storage::<Monster>();
+    let positions = ecs.read_storage::<Position>();
+
+    fovs.get(*player).is_some_and(|fov| {
+        (&monsters, &positions)
+            .join()
+            .any(|(_, position)| fov.contains(&position.to_point()))
+    })
+}
+
+/// Returns `true` if the player's hp and mp are full.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+///
+fn is_player_recovered(ecs: &World) -> bool {
+    let player = get_player_entity(ecs);
+    let healed = ecs
+        .read_storage::<Statistics>()
+        .get(*player)
+        .is_none_or(|statistic| statistic.hp >= statistic.hp_max);
+    let rested = ecs
+        .read_storage::<Mana>()
+        .get(*player)
+        .is_none_or(|mana| mana.mp >= mana.mp_max);
+
+    healed && rested
+}
+
+/// Returns `true` if the player stands on a tile of the passed `tile_type`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player and the [Map] are stored.
+/// * `tile_type`: The [TileType] to check.
+///
+fn is_player_on_tile(ecs: &World, tile_type: TileType) -> bool {
+    let map = ecs.fetch::<Map>();
+    let player_position = ecs.fetch::<Point>();
+
+    map.get_tile(player_position.x, player_position.y) == tile_type
+}
+
+/// Lets the player fall through the [TileType::CHASM] it stands on to the
+/// next level, dealing [config::CHASM_FALL_DAMAGE] on the way down.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+///
+/// # Notes
+/// * The fall never kills the player, at least `1` hp are left.
+///
+fn fall_into_chasm(ecs: &mut World) -> RunState {
+    {
+        let player = get_player_entity(ecs);
+        let mut statistics = ecs.write_storage::<Statistics>();
+
+        if let Some(statistic) = statistics.get_mut(*player) {
+            statistic.hp = i32::max(1, statistic.hp - config::CHASM_FALL_DAMAGE);
+        }
+    }
+
+    ecs.write_resource::<GameLog>().push(
+        &localization::tr_with("terrain.chasm.player_fall", &[&config::CHASM_FALL_DAMAGE]),
+        LogCategory::Combat,
+    );
+
+    RunState::NextLevel
+}
+
+/// Returns `true` if the player suffers from the passed
+/// `kind` of status effect, `false` otherwise.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+/// * `kind`: The [StatusEffectKind] to check.
+///
+fn player_has_status(ecs: &World, kind: StatusEffectKind) -> bool {
+    let player = get_player_entity(ecs);
+
+    ecs.read_storage::<StatusEffects>()
+        .get(*player)
+        .is_some_and(|effects| effects.has(kind))
 }
 
 /// Fetches the player [Entity] from the [World]
@@ -240,90 +801,195 @@ fn get_player_entity(ecs: &World) -> Fetch<Entity> {
 /// * `game_state`: Reference to the current state of the game for `ecs` access.
 /// * `ctx`: Reference to the context of the `ecs` to read the key input.
 ///
-pub fn player_handle_input(game_state: &mut State, ctx: &mut Rltk) -> ProcessingState {
+/// # Notes
+/// * The player only acts on its turn in the [TurnQueue], the monsters
+///   ahead of the player in the current round act first.
+/// * A [Sneaking] player only acts in every other round.
+/// * A stunned player skips the turn without any input.
+/// * A resting player skips turns until recovered, any key interrupts the rest.
+/// * Holding `SHIFT` with a movement key runs in its direction, any key stops the run.
+/// * The pressed key is mapped to its action through the [KeyBindings].
+/// * `CTRL + M` opens the message log history regardless of the [KeyBindings].
+///
+pub fn player_handle_input(game_state: &mut State, ctx: &mut Rltk) -> RunState {
+    if is_player_on_tile(&game_state.ecs, TileType::CHASM) {
+        return fall_into_chasm(&mut game_state.ecs);
+    }
+
+    {
+        let player = get_player_entity(&game_state.ecs);
+        let turn_queue = game_state.ecs.fetch::<TurnQueue>();
+
+        // The player sits out rounds while wading through deep water or sneaking
+        if !turn_queue.is_turn_of(*player) {
+            if is_player_on_tile(&game_state.ecs, TileType::DEEP_WATER) {
+                game_state.ecs.write_resource::<GameLog>().push(
+                    &localization::tr("terrain.water.wading"),
+                    LogCategory::General,
+                );
+            }
+            return RunState::MonsterTurn;
+        }
+    }
+
+    if player_has_status(&game_state.ecs, StatusEffectKind::Stun) {
+        game_state.ecs.write_resource::<PlayerPathing>().clear();
+        game_state.ecs.write_resource::<PlayerRunning>().stop();
+        game_state
+            .ecs
+            .write_resource::<GameLog>()
+            .push(&localization::tr("status.stun.skip"), LogCategory::Combat);
+        return RunState::PlayerTurn;
+    }
+
     if let Some((delta_x, delta_y)) = player_move_click(&mut game_state.ecs) {
         player_move(delta_x, delta_y, &mut game_state.ecs);
-        return ProcessingState::PlayerTurn;
+        return RunState::PlayerTurn;
+    }
+
+    if game_state.ecs.fetch::<PlayerResting>().is_active() {
+        return continue_resting(&mut game_state.ecs, ctx.key.is_some());
     }
 
-    match ctx.key {
-        Some(key) => match key {
+    let running = game_state.ecs.fetch::<PlayerRunning>().direction();
+
+    if let Some(direction) = running {
+        // Releasing or repeating `SHIFT` itself doesn't stop the run
+        let pressed = ctx
+            .key
+            .is_some_and(|key| !matches!(key, VirtualKeyCode::LShift | VirtualKeyCode::RShift));
+        return continue_running(&mut game_state.ecs, direction, pressed);
+    }
+
+    // `CTRL + M` always opens the message log history,
+    // since the key bindings don't support modifiers
+    if ctx.control && ctx.key == Some(VirtualKeyCode::M) {
+        return RunState::LogHistory {
+            view: LogHistoryView::default(),
+        };
+    }
+
+    // `CTRL + W` opens the wizard dialog and the tilde key
+    // the debug console, if the wizard mode is enabled
+    if ctx.control && ctx.key == Some(VirtualKeyCode::W) && wizard::is_enabled(&game_state.ecs) {
+        return wizard::open(&mut game_state.ecs);
+    }
+
+    if ctx.key == Some(VirtualKeyCode::Grave) && wizard::is_enabled(&game_state.ecs) {
+        return debug_console::open(&mut game_state.ecs);
+    }
+
+    let action = ctx
+        .key
+        .map(|key| game_state.ecs.fetch::<KeyBindings>().action(key));
+
+    if let (true, Some(Some(action))) = (ctx.shift, action) {
+        if let Some(direction) = action.direction() {
+            return start_running(&mut game_state.ecs, direction);
+        }
+    }
+
+    match action {
+        Some(Some(action)) => match action {
             // Cardinal directions
-            VirtualKeyCode::W
-            | VirtualKeyCode::Up
-            | VirtualKeyCode::Numpad8
-            | VirtualKeyCode::K => player_move(0, -1, &mut game_state.ecs),
-
-            VirtualKeyCode::A
-            | VirtualKeyCode::Left
-            | VirtualKeyCode::Numpad4
-            | VirtualKeyCode::H => player_move(-1, 0, &mut game_state.ecs),
-
-            VirtualKeyCode::S
-            | VirtualKeyCode::Down
-            | VirtualKeyCode::Numpad2
-            | VirtualKeyCode::J => player_move(0, 1, &mut game_state.ecs),
-
-            VirtualKeyCode::D
-            | VirtualKeyCode::Right
-            | VirtualKeyCode::Numpad6
-            | VirtualKeyCode::L => player_move(1, 0, &mut game_state.ecs),
+            KeyAction::MoveNorth => player_move(0, -1, &mut game_state.ecs),
+            KeyAction::MoveWest => player_move(-1, 0, &mut game_state.ecs),
+            KeyAction::MoveSouth => player_move(0, 1, &mut game_state.ecs),
+            KeyAction::MoveEast => player_move(1, 0, &mut game_state.ecs),
 
             // Diagonal directions
-            VirtualKeyCode::Numpad7 | VirtualKeyCode::Q => player_move(-1, -1, &mut game_state.ecs),
+            KeyAction::MoveNorthWest => player_move(-1, -1, &mut game_state.ecs),
+            KeyAction::MoveNorthEast => player_move(1, -1, &mut game_state.ecs),
+            KeyAction::MoveSouthWest => player_move(-1, 1, &mut game_state.ecs),
+            KeyAction::MoveSouthEast => player_move(1, 1, &mut game_state.ecs),
 
-            VirtualKeyCode::Numpad9 | VirtualKeyCode::E => player_move(1, -1, &mut game_state.ecs),
+            // Wait a turn or until recovered
+            KeyAction::Wait => {}
+            KeyAction::Rest => return start_resting(&mut game_state.ecs),
 
-            VirtualKeyCode::Numpad1 | VirtualKeyCode::Y => player_move(-1, 1, &mut game_state.ecs),
-
-            VirtualKeyCode::Numpad3 | VirtualKeyCode::X => player_move(1, 1, &mut game_state.ecs),
+            // Level interactions
+            KeyAction::Descend => return try_descend(&mut game_state.ecs),
 
             // Inventory interactions
-            VirtualKeyCode::G => pick_up_item(&mut game_state.ecs),
+            KeyAction::PickUp => pick_up_item(&mut game_state.ecs),
 
-            VirtualKeyCode::I => show_inventory(&mut game_state.ecs, ctx.shift),
+            // Stealth
+            KeyAction::Sneak => return toggle_sneaking(&mut game_state.ecs),
+
+            // Doors, bashing takes a turn if there is a door to bash
+            KeyAction::Bash => {
+                if !doors::bash_adjacent(&mut game_state.ecs) {
+                    return RunState::AwaitingInput;
+                }
+            }
+
+            // Ranged weapons
+            KeyAction::Fire => return start_firing(&mut game_state.ecs),
+
+            KeyAction::Inventory => {
+                return RunState::ShowInventory {
+                    view: InventoryView::default(),
+                }
+            }
+
+            // Spells
+            KeyAction::CastSpell => return show_spell_dialog(&mut game_state.ecs),
+
+            // Message log history
+            KeyAction::LogHistory => {
+                return RunState::LogHistory {
+                    view: LogHistoryView::default(),
+                }
+            }
+
+            // Quest journal
+            KeyAction::QuestJournal => return RunState::QuestJournal,
 
             // Menus
-            VirtualKeyCode::Escape => {
-                DialogInterface::register_dialog(
-                    &mut game_state.ecs,
-                    "Pause".to_string(),
-                    Some("What would you like to do in this moment of respite?".to_string()),
-                    vec![
-                        DialogOption {
-                            description: "Save".to_string(),
-                            key: VirtualKeyCode::S,
-                            args: vec![],
-                            callback: Box::new(|_, ctx, _| ctx.quit()),
-                        },
-                        DialogOption {
-                            description: "Load".to_string(),
-                            key: VirtualKeyCode::L,
-                            args: vec![],
-                            callback: Box::new(|_, ctx, _| ctx.quit()),
-                        },
-                        DialogOption {
-                            description: "Quit".to_string(),
-                            key: VirtualKeyCode::Q,
-                            args: vec![],
-                            callback: Box::new(|_, ctx, _| ctx.quit()),
-                        },
-                    ],
-                    true,
-                );
+            KeyAction::Pause => {
+                show_pause_dialog(&mut game_state.ecs);
+                return RunState::Dialog;
+            }
+
+            // Diagnostics, which don't take a turn
+            KeyAction::ToggleDiagnostics => {
+                diagnostics::toggle_overlay(&game_state.ecs);
+                return RunState::AwaitingInput;
             }
 
-            _ => return ProcessingState::WaitingForInput,
+            KeyAction::DumpDiagnostics => {
+                diagnostics::dump_to_log(&game_state.ecs);
+                return RunState::AwaitingInput;
+            }
         },
+        // Keys without an action are ignored
+        Some(None) => return RunState::AwaitingInput,
         // If no keyboard key was pressed, check if the player has clicked
-        // selected a position with the mouse.
+        // selected a position with the mouse or a touch.
         None => {
+            let mouse_point = ctx.mouse_point();
+            let target = Camera::new(&game_state.ecs).to_world(mouse_point);
+
+            if let (Some(TouchGesture::LongPress), Some(target)) =
+                (game_state.touch_gesture(), target)
+            {
+                if show_examine_dialog(&mut game_state.ecs, target) {
+                    return RunState::Dialog;
+                }
+            }
+
             if ctx.left_click {
-                handle_new_click_to_move(&mut game_state.ecs, ctx);
+                if let Some(button) = ui_controller::touch_button_at(&game_state.ecs, mouse_point) {
+                    return handle_touch_button(&mut game_state.ecs, button);
+                }
+
+                if let Some(target) = target {
+                    handle_new_click_to_move(&mut game_state.ecs, target);
+                }
             }
-            return ProcessingState::WaitingForInput;
+            return RunState::AwaitingInput;
         }
     }
 
-    ProcessingState::PlayerTurn
+    RunState::PlayerTurn
 }
@@ -0,0 +1,296 @@
+//! Module for the quests the NPCs of the town hand out.
+//!
+//! Quests are defined in `data/raws.json`, see [QuestRaw]. Talking to an
+//! NPC, that offers quests, hands out the next quest the player hasn't
+//! taken yet and adds it to the [QuestLog]. The combat and item systems
+//! and the descent to the next level report the player's [Deed]s to the
+//! [QuestLog], which advances the matching quests. The rewards of
+//! completed quests are granted at the end of the frame, see [grant_rewards].
+
+use rltk::Point;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use super::raws::{QuestRaw, Raws};
+use super::{entity_factory, localization};
+use super::{DamageSystem, GameLog, Item, LogCategory, Position, Purse};
+
+/// Enum describing the task of a quest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Objective {
+    /// Defeat `count` monsters with the name `target`.
+    Kill {
+        /// The name of the monsters, e.g. `"Goblin"`.
+        target: String,
+
+        /// The number of monsters to defeat.
+        count: u32,
+    },
+
+    /// Pick up `count` items with the name `item`.
+    Fetch {
+        /// The name of the items, e.g. `"Health Potion"`.
+        item: String,
+
+        /// The number of items to pick up.
+        count: u32,
+    },
+
+    /// Descend to the `depth` in the dungeon.
+    ReachDepth {
+        /// The depth to reach.
+        depth: i32,
+    },
+}
+
+impl Objective {
+    /// Returns the progress needed to fulfil the objective.
+    pub fn goal(&self) -> u32 {
+        match self {
+            Objective::Kill { count, .. } | Objective::Fetch { count, .. } => *count,
+            Objective::ReachDepth { .. } => 1,
+        }
+    }
+
+    /// Returns `true` if the passed `deed` advances the objective.
+    ///
+    /// # Arguments
+    /// * `deed`: The [Deed] of the player.
+    ///
+    fn is_advanced_by(&self, deed: &Deed) -> bool {
+        match (self, deed) {
+            (Objective::Kill { target, .. }, Deed::Kill(name)) => target == name,
+            (Objective::Fetch { item, .. }, Deed::Fetch(name)) => item == name,
+            (Objective::ReachDepth { depth }, Deed::ReachDepth(reached)) => reached >= depth,
+            _ => false,
+        }
+    }
+
+    /// Returns the translated description of the objective.
+    pub fn description(&self) -> String {
+        match self {
+            Objective::Kill { target, count } => localization::tr_with(
+                "quest.objective.kill",
+                &[&count, &localization::entity_name(target)],
+            ),
+            Objective::Fetch { item, count } => localization::tr_with(
+                "quest.objective.fetch",
+                &[&count, &localization::entity_name(item)],
+            ),
+            Objective::ReachDepth { depth } => {
+                localization::tr_with("quest.objective.reach_depth", &[&depth])
+            }
+        }
+    }
+}
+
+/// Enum describing the deeds of the player, that advance quests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Deed<'a> {
+    /// The player has defeated a monster with the name.
+    Kill(&'a str),
+
+    /// The player has picked up an item with the name.
+    Fetch(&'a str),
+
+    /// The player has descended to the depth.
+    ReachDepth(i32),
+}
+
+/// Enum describing the states of a quest in the [QuestLog].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestState {
+    /// The objective hasn't been fulfilled yet.
+    Active,
+
+    /// The objective is fulfilled, but the reward hasn't been granted yet.
+    Completed,
+
+    /// The objective is fulfilled and the reward has been granted.
+    Rewarded,
+}
+
+/// A quest the player has taken.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quest {
+    /// The name of the [QuestRaw] of the quest.
+    pub name: String,
+
+    /// The id of the text naming the quest.
+    pub title: String,
+
+    /// The task the player has to fulfil.
+    pub objective: Objective,
+
+    /// The progress towards the [Objective::goal].
+    pub progress: u32,
+
+    /// The state of the quest.
+    pub state: QuestState,
+}
+
+/// Resource holding all quests the player has taken in the current run.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestLog {
+    /// The quests in the order they were taken.
+    quests: Vec<Quest>,
+}
+
+impl QuestLog {
+    /// Returns all quests in the order they were taken.
+    pub fn quests(&self) -> &[Quest] {
+        &self.quests
+    }
+
+    /// Returns `true` if the player has taken the quest with the passed `name`.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the quest.
+    ///
+    pub fn has(&self, name: &str) -> bool {
+        self.quests.iter().any(|quest| quest.name == name)
+    }
+
+    /// Adds the quest of the passed definition as active quest.
+    ///
+    /// # Arguments
+    /// * `raw`: The [QuestRaw] definition of the quest.
+    ///
+    pub fn accept(&mut self, raw: &QuestRaw) {
+        self.quests.push(Quest {
+            name: raw.name.clone(),
+            title: raw.title.clone(),
+            objective: raw.objective.clone(),
+            progress: 0,
+            state: QuestState::Active,
+        });
+    }
+
+    /// Advances all active quests, whose objective matches the passed
+    /// `deed`, and marks them as completed once their goal is reached.
+    ///
+    /// # Arguments
+    /// * `deed`: The [Deed] of the player.
+    ///
+    pub fn record(&mut self, deed: Deed) {
+        let advanced = self.quests.iter_mut().filter(|quest| {
+            quest.state == QuestState::Active && quest.objective.is_advanced_by(&deed)
+        });
+
+        for quest in advanced {
+            quest.progress += 1;
+
+            if quest.progress >= quest.objective.goal() {
+                quest.state = QuestState::Completed;
+            }
+        }
+    }
+
+    /// Marks all completed quests as rewarded and returns their names.
+    fn take_completed(&mut self) -> Vec<String> {
+        self.quests
+            .iter_mut()
+            .filter(|quest| quest.state == QuestState::Completed)
+            .map(|quest| {
+                quest.state = QuestState::Rewarded;
+                quest.name.clone()
+            })
+            .collect()
+    }
+}
+
+/// Hands out the next quest of the NPC with the passed `name`, that
+/// the player hasn't taken yet, and reports it to the [GameLog].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [QuestLog] and the [Raws] are registered.
+/// * `npc`: The name of the NPC the player talks to, e.g. `"Elder"`.
+///
+/// # Notes
+/// * Quests unknown to the [Raws] are skipped.
+///
+pub fn offer(ecs: &World, npc: &str) {
+    let raws = ecs.fetch::<Raws>();
+    let mut quest_log = ecs.write_resource::<QuestLog>();
+
+    let quest = raws.npc(npc).and_then(|raw| {
+        raw.quests
+            .iter()
+            .filter(|name| !quest_log.has(name))
+            .find_map(|name| raws.quest(name))
+    });
+
+    if let Some(quest) = quest {
+        quest_log.accept(quest);
+
+        ecs.write_resource::<GameLog>().push(
+            &localization::tr_with("quest.accepted", &[&localization::tr(&quest.title)]),
+            LogCategory::General,
+        );
+    }
+}
+
+/// Grants the rewards of all quests completed since the last call
+/// to the player. The xp are added to the player's experience, the gold
+/// to the player's [Purse] and the items are created right in the
+/// player's backpack, where they join the stacks of identical items.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [QuestLog] and the player are registered.
+///
+pub fn grant_rewards(ecs: &mut World) {
+    let completed = match ecs.try_fetch_mut::<QuestLog>() {
+        Some(mut quest_log) => quest_log.take_completed(),
+        None => return,
+    };
+
+    for name in completed {
+        let (title, reward) = match ecs.fetch::<Raws>().quest(&name) {
+            Some(raw) => (localization::tr(&raw.title), raw.reward.clone()),
+            None => continue,
+        };
+
+        ecs.write_resource::<GameLog>().push(
+            &localization::tr_with("quest.completed", &[&title]),
+            LogCategory::General,
+        );
+
+        let player = *ecs.fetch::<Entity>();
+        let position = {
+            let point = *ecs.fetch::<Point>();
+            Position::new_from_tuple((point.x, point.y))
+        };
+
+        for item in reward.items.iter() {
+            if let Some(entity) = entity_factory::new_named(ecs, item, position) {
+                ecs.write_storage::<Position>().remove(entity);
+                Item::put_into_backpack(ecs, &player, entity);
+
+                ecs.write_resource::<GameLog>().push(
+                    &localization::tr_with(
+                        "quest.reward_item",
+                        &[&localization::entity_name(item)],
+                    ),
+                    LogCategory::Item,
+                );
+            }
+        }
+
+        if reward.gold > 0 {
+            if let Some(purse) = ecs.write_storage::<Purse>().get_mut(player) {
+                purse.gold += reward.gold;
+            }
+
+            ecs.write_resource::<GameLog>().push(
+                &localization::tr_with("quest.reward_gold", &[&reward.gold]),
+                LogCategory::Item,
+            );
+        }
+
+        if reward.xp > 0 {
+            DamageSystem::grant_experience(ecs, reward.xp);
+        }
+    }
+}
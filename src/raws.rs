@@ -0,0 +1,712 @@
+//! Module for the data driven definitions of the game's entities.
+//!
+//! Monsters, items, NPCs, spells, quests and unique monsters are defined in `data/raws.json`, which is embedded
+//! into the binary. Every definition describes the glyph, colors, stats
+//! and effects of an entity, which is created by its name through the
+//! `entity_factory`. On native targets all definition files in the
+//! [config::RAW_DIRECTORY] are loaded on startup as well and replace
+//! embedded definitions of the same name, so content can be added or
+//! changed without recompiling the game.
+
+use std::collections::HashMap;
+
+#[cfg(not(target_arch = "wasm32"))]
+use rltk::console;
+use rltk::RGB;
+use serde::Deserialize;
+use specs::prelude::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::config;
+use super::exceptions::GameError;
+use super::quests::Objective;
+use super::rng::Dice;
+use super::swatch::{self, Pallet};
+use super::{
+    AmmunitionKind, Behavior, EquipmentSlot, Equippable, InflictsStatus, LightSource, RangedWeapon,
+    Renderable,
+};
+
+/// File extension of the definition files.
+#[cfg(not(target_arch = "wasm32"))]
+pub const RAW_EXTENSION: &str = "json";
+
+/// The embedded entity definitions.
+const EMBEDDED_RAWS: &str = include_str!("../data/raws.json");
+
+/// The render information of a definition.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RenderableRaw {
+    /// The character the entity is displayed with.
+    pub glyph: char,
+
+    /// The foreground color as `[r, g, b]`.
+    pub fg: (u8, u8, u8),
+
+    /// The background color as `[r, g, b]`.
+    #[serde(default = "default_bg")]
+    pub bg: (u8, u8, u8),
+
+    /// Place in the rendering order.
+    #[serde(default)]
+    pub order: i32,
+}
+
+impl RenderableRaw {
+    /// Creates the [Renderable] described by the definition.
+    pub fn to_renderable(&self) -> Renderable {
+        let (fg, bg) = Pallet(self.fg, self.bg).colors();
+
+        Renderable {
+            symbol: rltk::to_cp437(self.glyph),
+            fg,
+            bg,
+            order: self.order,
+        }
+    }
+}
+
+/// The light emitted by the entity of a definition.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LightRaw {
+    /// The number of tiles the light reaches.
+    pub range: i32,
+
+    /// The color of the light as `[r, g, b]`.
+    pub color: (u8, u8, u8),
+}
+
+impl LightRaw {
+    /// Creates the [LightSource] described by the definition.
+    pub fn to_light_source(&self) -> LightSource {
+        LightSource {
+            range: self.range,
+            color: RGB::named(self.color),
+        }
+    }
+}
+
+/// The combat stats of a monster definition.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StatisticsRaw {
+    /// The maximum and initial hp.
+    pub hp: i32,
+
+    /// The attack power.
+    pub power: i32,
+
+    /// The defense capabilities.
+    pub defense: i32,
+}
+
+/// The definition of a monster.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MonsterRaw {
+    /// The name of the monster, which is translated
+    /// through the `entity.<name>` text.
+    pub name: String,
+
+    /// The render information.
+    pub renderable: RenderableRaw,
+
+    /// The combat stats.
+    pub stats: StatisticsRaw,
+
+    /// The range of the monster's field of view.
+    #[serde(default = "default_vision_range")]
+    pub vision_range: i32,
+
+    /// The xp granted to the player for defeating the monster.
+    #[serde(default)]
+    pub xp: i32,
+
+    /// The status effect the monster's attacks inflict, if any.
+    #[serde(default)]
+    pub inflicts: Option<InflictsStatus>,
+
+    /// The hp the monster regenerates every turn, `None` for monsters,
+    /// that don't regenerate, see [Regenerates](super::Regenerates).
+    #[serde(default)]
+    pub regenerates: Option<i32>,
+
+    /// Flag indicating whether or not the monster
+    /// is [Invisible](super::Invisible).
+    #[serde(default)]
+    pub invisible: bool,
+
+    /// The behavior of the monster, until it notices the player.
+    #[serde(default)]
+    pub behavior: Behavior,
+
+    /// Flag indicating whether or not the monster is
+    /// the boss of the dungeon, see [Boss](super::Boss).
+    #[serde(default)]
+    pub boss: bool,
+
+    /// Flag indicating whether or not the monster flees early,
+    /// see [AI::cowardly](super::AI::cowardly).
+    #[serde(default)]
+    pub cowardly: bool,
+
+    /// The light the monster emits, `None` for monsters, that don't glow.
+    #[serde(default)]
+    pub light: Option<LightRaw>,
+
+    /// The faction of the monster, see [Faction](super::Faction).
+    #[serde(default = "default_faction")]
+    pub faction: String,
+}
+
+/// The definition of a unique monster, a named and stronger variant of
+/// another monster, that appears at most once per run.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UniqueRaw {
+    /// The name of the unique, which is translated
+    /// through the `entity.<name>` text.
+    pub name: String,
+
+    /// The name of the monster the unique is based on.
+    pub base: String,
+
+    /// The character the unique is displayed with,
+    /// in the colors of [swatch::UNIQUE_MONSTER].
+    pub glyph: char,
+
+    /// The combat stats added to the ones of the base monster.
+    pub bonus: StatisticsRaw,
+
+    /// The xp granted in addition to the one of the base monster.
+    #[serde(default)]
+    pub xp: i32,
+
+    /// The first depth at which the unique can appear.
+    #[serde(default = "default_min_depth")]
+    pub min_depth: i32,
+
+    /// The names of the items the unique drops, once it's defeated.
+    #[serde(default)]
+    pub drops: Vec<String>,
+}
+
+impl UniqueRaw {
+    /// Creates the [Renderable] of the unique, which keeps
+    /// the render order of the base monster.
+    ///
+    /// # Arguments
+    /// * `base`: The [RenderableRaw] of the base monster.
+    ///
+    pub fn to_renderable(&self, base: &RenderableRaw) -> Renderable {
+        let (fg, bg) = swatch::UNIQUE_MONSTER.colors();
+
+        Renderable {
+            symbol: rltk::to_cp437(self.glyph),
+            fg,
+            bg,
+            order: base.order,
+        }
+    }
+}
+
+/// The equipment stats of an item definition.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EquippableRaw {
+    /// The slot the item is worn in.
+    pub slot: EquipmentSlot,
+
+    /// The power added to the wearer.
+    #[serde(default)]
+    pub power: i32,
+
+    /// The defense added to the wearer.
+    #[serde(default)]
+    pub defense: i32,
+
+    /// Flag indicating whether or not the item needs both hands.
+    #[serde(default)]
+    pub two_handed: bool,
+
+    /// The chance in percent to block a melee attack.
+    #[serde(default)]
+    pub block_chance: i32,
+
+    /// The damage dice of a weapon, e.g. `"1d8+2"`.
+    #[serde(default)]
+    pub damage: Option<Dice>,
+}
+
+impl EquippableRaw {
+    /// Creates the [Equippable] described by the definition.
+    pub fn to_equippable(&self) -> Equippable {
+        Equippable {
+            slot: self.slot,
+            power_bonus: self.power,
+            defense_bonus: self.defense,
+            two_handed: self.two_handed,
+            block_chance: self.block_chance,
+            damage: self.damage,
+        }
+    }
+}
+
+/// The definition of a [RangedWeapon].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RangedWeaponRaw {
+    /// The maximum distance of a shot.
+    pub range: i32,
+
+    /// The damage of a hit.
+    pub power: i32,
+
+    /// The kind of ammunition fired by the
+    /// weapon, `None` if it needs none.
+    #[serde(default)]
+    pub ammunition: Option<AmmunitionKind>,
+}
+
+impl RangedWeaponRaw {
+    /// Creates the [RangedWeapon] described by the definition.
+    pub fn to_ranged_weapon(&self) -> RangedWeapon {
+        RangedWeapon {
+            range: self.range,
+            power: self.power,
+            ammunition: self.ammunition,
+        }
+    }
+}
+
+/// The definition of an item.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ItemRaw {
+    /// The name of the item, which is translated
+    /// through the `entity.<name>` text.
+    pub name: String,
+
+    /// The render information.
+    pub renderable: RenderableRaw,
+
+    /// The name of the script defining the
+    /// effect of the item, if it has one.
+    #[serde(default)]
+    pub script: Option<String>,
+
+    /// The range of items, that have to be used on a
+    /// target tile, `None` for items used on the user.
+    #[serde(default)]
+    pub range: Option<i32>,
+
+    /// The radius around the target tile hit by the
+    /// item, `None` for items hitting a single target.
+    #[serde(default)]
+    pub radius: Option<i32>,
+
+    /// The kind of appearance of the item while it's unidentified,
+    /// e.g. `"potion"`, `None` for items, that are always known.
+    #[serde(default)]
+    pub appearance: Option<String>,
+
+    /// The equipment stats of items, that can be worn,
+    /// `None` for items, that can't be equipped.
+    #[serde(default)]
+    pub equippable: Option<EquippableRaw>,
+
+    /// Flag indicating whether or not the item
+    /// is cursed, see [Cursed](super::Cursed).
+    #[serde(default)]
+    pub cursed: bool,
+
+    /// Flag indicating whether or not identical items stack
+    /// in a backpack, see [Stackable](super::Stackable).
+    #[serde(default)]
+    pub stackable: bool,
+
+    /// The amount of items in a newly created stack, e.g. a bundle of
+    /// arrows. Only used for stackable items, `0` counts as a single item.
+    #[serde(default)]
+    pub quantity: i32,
+
+    /// The kind of ammunition the item is, see [Ammunition](super::Ammunition),
+    /// `None` for items, that aren't shot by ranged weapons.
+    #[serde(default)]
+    pub ammunition: Option<AmmunitionKind>,
+
+    /// The stats of the item as ranged weapon, `None`
+    /// for items, that can't be fired.
+    #[serde(default)]
+    pub ranged_weapon: Option<RangedWeaponRaw>,
+
+    /// The light the item emits while it's lying on
+    /// the ground, `None` for items, that don't glow.
+    #[serde(default)]
+    pub light: Option<LightRaw>,
+
+    /// The base price of the item, see [Value](super::Value),
+    /// `0` for items, that the shopkeepers don't trade.
+    #[serde(default)]
+    pub price: i32,
+
+    /// The weight of the item, see [Weight](super::Weight),
+    /// `0` for items, that don't burden their carrier.
+    #[serde(default)]
+    pub weight: i32,
+}
+
+/// The definition of a friendly non-player character.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NpcRaw {
+    /// The name of the NPC, which is translated
+    /// through the `entity.<name>` text.
+    pub name: String,
+
+    /// The render information.
+    pub renderable: RenderableRaw,
+
+    /// The id of the text the NPC greets the player with.
+    pub greeting: String,
+
+    /// The behavior of the NPC, which never attacks anyone.
+    #[serde(default = "default_npc_behavior")]
+    pub behavior: Behavior,
+
+    /// The names of the quests the NPC hands out, in the order
+    /// they are offered to the player, see [QuestRaw].
+    #[serde(default)]
+    pub quests: Vec<String>,
+
+    /// The names of the items the NPC sells, which makes it a
+    /// [Shopkeeper](super::Shopkeeper). Every item is put on
+    /// display around the NPC, when the NPC is created.
+    #[serde(default)]
+    pub shop: Vec<String>,
+}
+
+/// The definition of a quest handed out by an NPC.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct QuestRaw {
+    /// The unique name of the quest.
+    pub name: String,
+
+    /// The id of the text naming the quest.
+    pub title: String,
+
+    /// The task the player has to fulfil.
+    pub objective: Objective,
+
+    /// The reward granted once the [QuestRaw::objective] is fulfilled.
+    #[serde(default)]
+    pub reward: QuestRewardRaw,
+}
+
+/// The reward of a quest definition.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct QuestRewardRaw {
+    /// The xp granted to the player.
+    #[serde(default)]
+    pub xp: i32,
+
+    /// The gold put into the player's [Purse](super::Purse).
+    #[serde(default)]
+    pub gold: i32,
+
+    /// The names of the items put into the player's backpack.
+    #[serde(default)]
+    pub items: Vec<String>,
+}
+
+/// The definition of a spell.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpellRaw {
+    /// The name of the spell, which is translated
+    /// through the `entity.<name>` text.
+    pub name: String,
+
+    /// The mana spent on casting the spell.
+    pub mana_cost: i32,
+
+    /// The name of the script defining the effect of the spell.
+    pub script: String,
+
+    /// The range of spells, that have to be cast on a
+    /// target tile, `None` for spells cast on the caster.
+    #[serde(default)]
+    pub range: Option<i32>,
+
+    /// The radius around the target tile hit by the
+    /// spell, `None` for spells hitting a single target.
+    #[serde(default)]
+    pub radius: Option<i32>,
+}
+
+/// The content of a single definition file.
+#[derive(Debug, Default, Deserialize)]
+struct RawFile {
+    /// The monster definitions of the file.
+    #[serde(default)]
+    monsters: Vec<MonsterRaw>,
+
+    /// The item definitions of the file.
+    #[serde(default)]
+    items: Vec<ItemRaw>,
+
+    /// The NPC definitions of the file.
+    #[serde(default)]
+    npcs: Vec<NpcRaw>,
+
+    /// The spell definitions of the file.
+    #[serde(default)]
+    spells: Vec<SpellRaw>,
+
+    /// The quest definitions of the file.
+    #[serde(default)]
+    quests: Vec<QuestRaw>,
+
+    /// The unique monster definitions of the file.
+    #[serde(default)]
+    uniques: Vec<UniqueRaw>,
+}
+
+/// Resource holding the definitions of all monsters, items,
+/// NPCs, spells, quests and unique monsters.
+#[derive(Debug, Clone, Default)]
+pub struct Raws {
+    /// The monster definitions by their name.
+    monsters: HashMap<String, MonsterRaw>,
+
+    /// The item definitions by their name.
+    items: HashMap<String, ItemRaw>,
+
+    /// The NPC definitions by their name.
+    npcs: HashMap<String, NpcRaw>,
+
+    /// The spell definitions by their name.
+    spells: HashMap<String, SpellRaw>,
+
+    /// The quest definitions by their name.
+    quests: HashMap<String, QuestRaw>,
+
+    /// The unique monster definitions by their name.
+    uniques: HashMap<String, UniqueRaw>,
+
+    /// The errors of all definition files,
+    /// that couldn't be read or parsed.
+    failures: Vec<GameError>,
+}
+
+impl Raws {
+    /// Parses the embedded definitions and, on native targets, the
+    /// definition files found in the [config::RAW_DIRECTORY].
+    ///
+    /// # Notes
+    /// * Files that can't be read or parsed are skipped, reported
+    ///   to the console and kept in the [Raws::failures].
+    ///
+    /// # Panics
+    /// * If the embedded definitions are malformed.
+    ///
+    pub fn load() -> Self {
+        let mut raws = Raws::default();
+
+        let embedded: RawFile = serde_json::from_str(EMBEDDED_RAWS)
+            .expect("The embedded entity definitions are malformed!");
+        raws.insert(embedded);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        raws.load_directory(config::RAW_DIRECTORY);
+
+        raws
+    }
+
+    /// Adds all definitions of the `file`, replacing any
+    /// definition with the same name.
+    ///
+    /// # Arguments
+    /// * `file`: The parsed definition file.
+    ///
+    fn insert(&mut self, file: RawFile) {
+        for monster in file.monsters {
+            self.monsters.insert(monster.name.clone(), monster);
+        }
+
+        for item in file.items {
+            self.items.insert(item.name.clone(), item);
+        }
+
+        for npc in file.npcs {
+            self.npcs.insert(npc.name.clone(), npc);
+        }
+
+        for spell in file.spells {
+            self.spells.insert(spell.name.clone(), spell);
+        }
+
+        for quest in file.quests {
+            self.quests.insert(quest.name.clone(), quest);
+        }
+
+        for unique in file.uniques {
+            self.uniques.insert(unique.name.clone(), unique);
+        }
+    }
+
+    /// Loads all definition files with the [RAW_EXTENSION] from the
+    /// passed `directory`. Does nothing if the `directory` doesn't exist.
+    ///
+    /// # Arguments
+    /// * `directory`: The directory containing the definition files.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_directory(&mut self, directory: &str) {
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        let paths = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == RAW_EXTENSION)
+            });
+
+        for path in paths {
+            let file = std::fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|data| {
+                    serde_json::from_str::<RawFile>(&data).map_err(|err| err.to_string())
+                });
+
+            match file {
+                Ok(file) => self.insert(file),
+                Err(message) => {
+                    console::log(format!("Unable to load '{}': {}", path.display(), message));
+                    self.failures.push(GameError::Asset {
+                        asset: path.display().to_string(),
+                        message,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Returns the definition of the monster with the passed `name`.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the monster, e.g. `"Goblin"`.
+    ///
+    pub fn monster(&self, name: &str) -> Option<&MonsterRaw> {
+        self.monsters.get(name)
+    }
+
+    /// Returns the definition of the item with the passed `name`.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the item, e.g. `"Health Potion"`.
+    ///
+    pub fn item(&self, name: &str) -> Option<&ItemRaw> {
+        self.items.get(name)
+    }
+
+    /// Returns the definition of the NPC with the passed `name`.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the NPC, e.g. `"Elder"`.
+    ///
+    pub fn npc(&self, name: &str) -> Option<&NpcRaw> {
+        self.npcs.get(name)
+    }
+
+    /// Returns the definition of the spell with the passed `name`.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the spell, e.g. `"Magic Missile"`.
+    ///
+    pub fn spell(&self, name: &str) -> Option<&SpellRaw> {
+        self.spells.get(name)
+    }
+
+    /// Returns the definition of the quest with the passed `name`.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the quest, e.g. `"goblin_cull"`.
+    ///
+    pub fn quest(&self, name: &str) -> Option<&QuestRaw> {
+        self.quests.get(name)
+    }
+
+    /// Returns the definition of the unique monster with the passed `name`.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the unique, e.g. `"Gorbag the Cruel"`.
+    ///
+    pub fn unique(&self, name: &str) -> Option<&UniqueRaw> {
+        self.uniques.get(name)
+    }
+
+    /// Returns the names of all monsters in alphabetical order.
+    pub fn monster_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.monsters.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Returns the names of all items in alphabetical order.
+    pub fn item_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.items.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Returns the names of all NPCs in alphabetical order.
+    pub fn npc_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.npcs.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Returns the names of all unique monsters in alphabetical order.
+    pub fn unique_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.uniques.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Returns the errors of all definition files, that
+    /// couldn't be read or parsed while loading them.
+    pub fn failures(&self) -> &[GameError] {
+        &self.failures
+    }
+}
+
+/// Registers the [Raws] with the passed `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Raws] should be registered.
+///
+pub fn register(ecs: &mut World) {
+    ecs.insert(Raws::load());
+}
+
+/// Default value of [RenderableRaw::bg].
+fn default_bg() -> (u8, u8, u8) {
+    swatch::DEFAULT_BG_COLOR
+}
+
+/// Default value of [MonsterRaw::vision_range].
+fn default_vision_range() -> i32 {
+    8
+}
+
+/// Default value of [NpcRaw::behavior].
+fn default_npc_behavior() -> Behavior {
+    Behavior::Wander
+}
+
+/// Default value of [MonsterRaw::faction].
+fn default_faction() -> String {
+    config::DEFAULT_MONSTER_FACTION.to_string()
+}
+
+/// Default value of [UniqueRaw::min_depth].
+fn default_min_depth() -> i32 {
+    1
+}
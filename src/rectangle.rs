@@ -1,5 +1,8 @@
 //! Rectangle for drawing rooms.
 
+use rltk::{Point, RandomNumberGenerator};
+use serde::{Deserialize, Serialize};
+
 use super::Position;
 
 /// Struct to represent any square
@@ -15,7 +18,11 @@ use super::Position;
 ///                 |           | \
 ///                 #-----------+ <-- (right, bottom)
 ///
-#[derive(Default, Debug, Clone)]
+/// # Notes
+/// * The interior of a rectangle, e.g. the floor of a room,
+///   spans from (`left + 1`, `top + 1`) to (`right`, `bottom`).
+///
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Rectangle {
     /// Left x coordinate of the rectangle.
     pub left: i32,
@@ -50,6 +57,132 @@ impl Rectangle {
         }
     }
 
+    /// Creates a new rectangle like [Rectangle::new], but fixes up
+    /// degenerate sizes. Negative sizes extend the rectangle to the
+    /// left or top of (`x`, `y`) and sizes below `1` are raised to
+    /// `1`, so the rectangle always has an interior.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: Left x coordinate of the rectangle.
+    /// * `y`: Top y coordinate of the rectangle.
+    /// * `width`: Width of the rectangle, can be negative.
+    /// * `height`: Height of the rectangle, can be negative.
+    ///
+    pub fn new_normalized(x: i32, y: i32, width: i32, height: i32) -> Self {
+        let (left, width) = if width < 0 {
+            (x + width, -width)
+        } else {
+            (x, width)
+        };
+        let (top, height) = if height < 0 {
+            (y + height, -height)
+        } else {
+            (y, height)
+        };
+
+        Rectangle::new(left, top, width.max(1), height.max(1))
+    }
+
+    /// Returns the width of the rectangle.
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    /// Returns the height of the rectangle.
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    /// Returns `true` if the passed `point` lies within the
+    /// rectangle, including its border, `false` otherwise.
+    ///
+    /// # Arguments
+    /// * `point`: The [Point] to check.
+    ///
+    pub fn contains(&self, point: &Point) -> bool {
+        point.x >= self.left
+            && point.x <= self.right
+            && point.y >= self.top
+            && point.y <= self.bottom
+    }
+
+    /// Returns an iterator over the [Position]s of all
+    /// tiles in the interior of the rectangle, row by row.
+    pub fn iter_interior(&self) -> impl Iterator<Item = Position> {
+        let (left, right) = (self.left + 1, self.right);
+        let (top, bottom) = (self.top + 1, self.bottom);
+
+        (top..=bottom).flat_map(move |y| (left..=right).map(move |x| Position { x, y }))
+    }
+
+    /// Returns a random [Position] in the interior of the rectangle.
+    ///
+    /// # Arguments
+    /// * `rng`: The [RandomNumberGenerator] to pick the position with.
+    ///
+    pub fn random_point(&self, rng: &mut RandomNumberGenerator) -> Position {
+        let x = self.left + rng.roll_dice(1, i32::max(1, self.width()));
+        let y = self.top + rng.roll_dice(1, i32::max(1, self.height()));
+
+        Position { x, y }
+    }
+
+    /// Returns the [Rectangle] both rectangles have in common,
+    /// or `None` if they don't overlap.
+    ///
+    /// # Arguments
+    /// * `other`: The rectangle to intersect with.
+    ///
+    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+        if !self.overlaps(other) {
+            return None;
+        }
+
+        Some(Rectangle {
+            left: i32::max(self.left, other.left),
+            top: i32::max(self.top, other.top),
+            right: i32::min(self.right, other.right),
+            bottom: i32::min(self.bottom, other.bottom),
+        })
+    }
+
+    /// Returns a copy of the rectangle grown by the
+    /// `amount` of tiles in every direction.
+    ///
+    /// # Arguments
+    /// * `amount`: The amount of tiles to grow by.
+    ///
+    pub fn expand(&self, amount: i32) -> Rectangle {
+        Rectangle {
+            left: self.left - amount,
+            top: self.top - amount,
+            right: self.right + amount,
+            bottom: self.bottom + amount,
+        }
+    }
+
+    /// Returns a copy of the rectangle shrunk by the
+    /// `amount` of tiles in every direction.
+    ///
+    /// # Arguments
+    /// * `amount`: The amount of tiles to shrink by.
+    ///
+    /// # Notes
+    /// * A rectangle can't shrink beyond its center, so the
+    ///   result is never degenerate.
+    ///
+    pub fn shrink(&self, amount: i32) -> Rectangle {
+        let center = self.center();
+
+        Rectangle {
+            left: i32::min(self.left + amount, center.x),
+            top: i32::min(self.top + amount, center.y),
+            right: i32::max(self.right - amount, center.x),
+            bottom: i32::max(self.bottom - amount, center.y),
+        }
+    }
+
     /// Checks if the calling [Rectangle] overlaps with
     /// another [Rectangle]. Returns true if they overlap
     /// and false otherwise.
@@ -1,28 +1,319 @@
 //! Module for random number generation
 
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+
 use chrono::Utc;
 use rltk::{console, RandomNumberGenerator};
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
+use super::exceptions::{GameError, GameResult};
+
+/// A parsed dice expression in the standard dice
+/// notation `NdS+M`, e.g. `2d6+3`.
+///
+/// # Notes
+/// * The dice are (de)serialized through their notation.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Dice {
+    /// The number of dice.
+    pub count: i32,
+
+    /// The amount of sides of each die.
+    pub sides: i32,
+
+    /// The value added to the sum of the dice.
+    pub modifier: i32,
+}
+
+impl Dice {
+    /// Parses the passed dice `notation` and returns the [Dice].
+    ///
+    /// # Arguments
+    /// * `notation`: The dice notation, e.g. `2d6+3`, `d20`, `1d4-1` or `3`.
+    ///
+    /// # Notes
+    /// * The number of dice defaults to `1` if omitted, e.g. `d20`.
+    /// * A plain number is a constant value without any dice.
+    /// * Whitespace and the case of the `d` are ignored.
+    ///
+    /// # Errors
+    /// * If the `notation` is malformed or contains negative
+    ///   dice counts or sides.
+    /// * If the lowest or highest possible result doesn't fit into
+    ///   an [i32], so rolling the dice can't overflow.
+    ///
+    pub fn parse(notation: &str) -> GameResult<Self> {
+        let error = |message: &str| GameError::DiceNotation {
+            notation: notation.to_string(),
+            message: message.to_string(),
+        };
+
+        let expression: String = notation
+            .chars()
+            .filter(|character| !character.is_whitespace())
+            .map(|character| character.to_ascii_lowercase())
+            .collect();
+
+        if expression.is_empty() {
+            return Err(error("The notation is empty"));
+        }
+
+        let (dice, modifier) = match expression.find(['+', '-']) {
+            Some(idx) => expression.split_at(idx),
+            None => (expression.as_str(), ""),
+        };
+
+        let modifier = match modifier {
+            "" => 0,
+            modifier => modifier
+                .parse::<i32>()
+                .map_err(|_| error("The modifier is not a number"))?,
+        };
+
+        let (count, sides) = match dice.split_once('d') {
+            Some(("", sides)) => (1, sides),
+            Some((count, sides)) => (
+                count
+                    .parse::<i32>()
+                    .map_err(|_| error("The number of dice is not a number"))?,
+                sides,
+            ),
+            None => {
+                let constant = match dice {
+                    "" => 0,
+                    dice => dice
+                        .parse::<i32>()
+                        .map_err(|_| error("Expected dice or a number"))?,
+                };
+
+                let constant = constant
+                    .checked_add(modifier)
+                    .ok_or_else(|| error("The value is out of range"))?;
+
+                return Ok(Dice {
+                    count: 0,
+                    sides: 0,
+                    modifier: constant,
+                });
+            }
+        };
+
+        let sides = sides
+            .parse::<i32>()
+            .map_err(|_| error("The sides of the dice are not a number"))?;
+
+        if count < 0 || sides < 1 {
+            return Err(error("Dice need a positive count and at least one side"));
+        }
+
+        let lowest = count.checked_add(modifier);
+        let highest = count
+            .checked_mul(sides)
+            .and_then(|sum| sum.checked_add(modifier));
+
+        if lowest.is_none() || highest.is_none() {
+            return Err(error("The possible results are out of range"));
+        }
+
+        Ok(Dice {
+            count,
+            sides,
+            modifier,
+        })
+    }
+
+    /// Rolls the dice with the passed `rng` and returns the result.
+    ///
+    /// # Arguments
+    /// * `rng`: The [RandomNumberGenerator] rolling the dice.
+    ///
+    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> i32 {
+        if self.count == 0 {
+            return self.modifier;
+        }
+
+        rng.roll_dice(self.count, self.sides) + self.modifier
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.count == 0 {
+            return write!(f, "{}", self.modifier);
+        }
+
+        write!(f, "{}d{}", self.count, self.sides)?;
+
+        match self.modifier {
+            0 => Ok(()),
+            modifier => write!(f, "{:+}", modifier),
+        }
+    }
+}
+
+impl TryFrom<String> for Dice {
+    type Error = GameError;
+
+    fn try_from(notation: String) -> GameResult<Self> {
+        Dice::parse(&notation)
+    }
+}
+
+impl From<Dice> for String {
+    fn from(dice: Dice) -> Self {
+        dice.to_string()
+    }
+}
+
+/// Resource caching the parsed [Dice] by their
+/// notation, so every expression is only parsed once.
+#[derive(Debug, Default)]
+pub struct DiceCache {
+    /// The parsed [Dice] by their notation.
+    dice: HashMap<String, Dice>,
+}
+
+impl DiceCache {
+    /// Returns the [Dice] of the passed `notation`,
+    /// parsing and caching them on the first request.
+    ///
+    /// # Arguments
+    /// * `notation`: The dice notation, e.g. `2d6+3`.
+    ///
+    /// # Errors
+    /// * If the `notation` is malformed, see [Dice::parse].
+    ///
+    pub fn get(&mut self, notation: &str) -> GameResult<Dice> {
+        if let Some(dice) = self.dice.get(notation) {
+            return Ok(*dice);
+        }
+
+        let dice = Dice::parse(notation)?;
+        self.dice.insert(notation.to_string(), dice);
+
+        Ok(dice)
+    }
+}
+
+/// Resource holding the seed the `rng` handler
+/// was created with, e.g. for crash reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RngSeed {
+    /// The seed of the `rng` handler.
+    pub seed: u64,
+
+    /// The seed passed on the command line, with which every run
+    /// starts, or `None` if every run gets a new seed.
+    pub fixed: Option<u64>,
+}
+
+/// Returns a new seed calculated through the
+/// current system time in nanoseconds.
+fn time_seed() -> u64 {
+    Utc::now().timestamp_nanos() as u64
+}
+
 /// Registers a the `rng` handler with the passed `ecs`.
 ///
 /// # Arguments
 /// * `ecs`: The [World] in which the `rng` handler should be registered.
+/// * `seed`: The fixed seed for reproducible runs, e.g. passed on the
+///   command line, or `None` for a new seed.
 ///
 /// Notes
-/// * The seed for the `rng` handler is calculated through the current
-/// system time in nanoseconds.
+/// * Without a fixed `seed` the seed for the `rng` handler is calculated
+///   through the current system time in nanoseconds.
 /// * This action must be performed befor any other function from the module
 /// can be safely called! If no handler is registered all other functions
 /// will panic!
 ///
-pub fn register(ecs: &mut World) {
-    let seed = Utc::now().timestamp_nanos() as u64;
-    let rng = RandomNumberGenerator::seeded(seed);
+pub fn register(ecs: &mut World, seed: Option<u64>) {
+    let rng_seed = RngSeed {
+        seed: seed.unwrap_or_else(time_seed),
+        fixed: seed,
+    };
 
-    console::log(&format!("Game running with seed: {}", seed));
+    console::log(format!("Game running with seed: {}", rng_seed.seed));
 
-    ecs.insert(rng);
+    ecs.insert(RandomNumberGenerator::seeded(rng_seed.seed));
+    ecs.insert(rng_seed);
+    ecs.insert(DiceCache::default());
+}
+
+/// Replaces the `rng` handler of the passed `ecs` for a new run
+/// and returns its seed. Runs started with the same fixed seed
+/// generate the same maps and entities.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the `rng` handler is registered.
+///
+/// # Notes
+/// * Without a fixed seed every run gets a new seed, see [register].
+///
+pub fn reseed(ecs: &mut World) -> u64 {
+    let seed = ecs
+        .try_fetch::<RngSeed>()
+        .and_then(|rng_seed| rng_seed.fixed)
+        .unwrap_or_else(time_seed);
+
+    reseed_with(ecs, seed)
+}
+
+/// Replaces the `rng` handler of the passed `ecs` for a new run with one
+/// seeded by the passed `seed` and returns it. Unlike [reseed], the fixed
+/// seed passed on the command line is ignored, e.g. for the daily challenge.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the `rng` handler is registered.
+/// * `seed`: The seed of the new run.
+///
+/// # Notes
+/// * The fixed seed is kept for the following runs.
+///
+pub fn reseed_with(ecs: &mut World, seed: u64) -> u64 {
+    let fixed = ecs
+        .try_fetch::<RngSeed>()
+        .and_then(|rng_seed| rng_seed.fixed);
+
+    console::log(format!("New run with seed: {}", seed));
+
+    ecs.insert(RandomNumberGenerator::seeded(seed));
+    ecs.insert(RngSeed { seed, fixed });
+
+    seed
+}
+
+/// Rolls the dice of the passed `notation` and returns the result.
+///
+/// # Arguments
+/// * `ecs`: The [World] with which the `rng` handler was registered.
+/// * `notation`: The dice notation, e.g. `2d6+3`, see [Dice::parse].
+///
+/// # Errors
+/// * If the `notation` is malformed.
+///
+/// # Panics
+/// * If no `rng` handler is registered in the passed `ecs`.
+///
+/// # See also
+/// * [register]
+///
+pub fn roll(ecs: &mut World, notation: &str) -> GameResult<i32> {
+    let dice = ecs
+        .entry::<DiceCache>()
+        .or_insert_with(DiceCache::default)
+        .get(notation)?;
+
+    if dice.count == 0 {
+        return Ok(dice.modifier);
+    }
+
+    Ok(roll_dice(ecs, dice.count, dice.sides) + dice.modifier)
 }
 
 /// Rolls dice, using the classic 3d6 type.
@@ -75,3 +366,91 @@ pub fn range(ecs: &mut World, start: i32, end: i32) -> i32 {
 fn is_registered(ecs: &mut World) -> bool {
     ecs.has_value::<RandomNumberGenerator>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shorthand to create the expected [Dice].
+    fn dice(count: i32, sides: i32, modifier: i32) -> Dice {
+        Dice {
+            count,
+            sides,
+            modifier,
+        }
+    }
+
+    #[test]
+    fn parse_full_notation() {
+        assert_eq!(Dice::parse("2d6+3").unwrap(), dice(2, 6, 3));
+        assert_eq!(Dice::parse("1d4-1").unwrap(), dice(1, 4, -1));
+        assert_eq!(Dice::parse(" 3D8 + 2 ").unwrap(), dice(3, 8, 2));
+    }
+
+    #[test]
+    fn parse_implicit_count() {
+        assert_eq!(Dice::parse("d20").unwrap(), dice(1, 20, 0));
+        assert_eq!(Dice::parse("d8-2").unwrap(), dice(1, 8, -2));
+    }
+
+    #[test]
+    fn parse_constants() {
+        assert_eq!(Dice::parse("3").unwrap(), dice(0, 0, 3));
+        assert_eq!(Dice::parse("-5").unwrap(), dice(0, 0, -5));
+        assert_eq!(Dice::parse("4+2").unwrap(), dice(0, 0, 6));
+    }
+
+    #[test]
+    fn parse_splits_modifier_at_first_sign() {
+        assert_eq!(Dice::parse("2d6-3").unwrap(), dice(2, 6, -3));
+        assert!(Dice::parse("2d6+3+1").is_err());
+        assert!(Dice::parse("2d6+-3").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_dice() {
+        assert!(Dice::parse("").is_err());
+        assert!(Dice::parse("2d0").is_err());
+        assert!(Dice::parse("-2d6").is_err());
+        assert!(Dice::parse("2d").is_err());
+        assert!(Dice::parse("xd6").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_overflowing_dice() {
+        assert!(Dice::parse("5+2147483647").is_err());
+        assert!(Dice::parse("-5-2147483647").is_err());
+        assert!(Dice::parse("100000d100000").is_err());
+        assert!(Dice::parse("1d2147483647+1").is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for notation in ["2d6+3", "1d4-1", "1d20", "-5", "0"] {
+            assert_eq!(Dice::parse(notation).unwrap().to_string(), notation);
+        }
+    }
+
+    #[test]
+    fn roll_stays_in_range() {
+        let mut rng = RandomNumberGenerator::seeded(1337);
+        let dice = Dice::parse("2d6+3").unwrap();
+
+        for _ in 0..100 {
+            let result = dice.roll(&mut rng);
+            assert!((5..=15).contains(&result));
+        }
+
+        assert_eq!(Dice::parse("-5").unwrap().roll(&mut rng), -5);
+    }
+
+    #[test]
+    fn roll_notation_through_ecs() {
+        let mut ecs = World::new();
+        ecs.insert(RandomNumberGenerator::seeded(1337));
+
+        assert_eq!(roll(&mut ecs, "7").unwrap(), 7);
+        assert!((1..=20).contains(&roll(&mut ecs, "d20").unwrap()));
+        assert!(roll(&mut ecs, "5+2147483647").is_err());
+    }
+}
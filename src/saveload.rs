@@ -0,0 +1,428 @@
+//! Module for saving and restoring a running game.
+//!
+//! All entities marked with a [SaveMarker] are serialized with their
+//! components through the `saveload` feature of `specs`. The resources,
+//! that are needed to continue the game, i.e. the [Map], the [GameLog],
+//! the [TurnCounter], the [RunStatistics], the [LevelStatistics], the
+//! [Identification] and the [QuestLog], are
+//! attached to a temporary entity through a [SerializationHelper] while
+//! saving, so they are serialized alongside the entities. The save game is stored as json
+//! through the [storage] module, with one section for every component.
+
+use std::convert::Infallible;
+use std::marker::PhantomData;
+
+use rltk::Point;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value};
+use specs::prelude::*;
+use specs::saveload::{
+    ConvertSaveload, DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker,
+    SimpleMarkerAllocator,
+};
+use specs_derive::*;
+
+use super::daily::DailyRun;
+use super::exceptions::{GameError, GameResult};
+use super::identification::Identification;
+use super::quests::QuestLog;
+use super::{
+    storage, Ammunition, AreaOfEffect, Boss, CastSpell, Charisma, Collision, Container, Cursed,
+    Door, Equippable, Equipped, Experience, ExperienceReward, Facing, Faction, ForSale, Friendly,
+    GameLog, InflictsStatus, Invisible, Item, Key, LevelStatistics, LightSource, Loot, Mana, Map,
+    Monster, Name, Noises, Opaque, OutOfDepth, Player, PlayerPathing, PlayerResting, PlayerRunning,
+    Position, Purse, Ranged, RangedWeapon, Regenerates, Renderable, RunStatistics, Scripted,
+    Shopkeeper, Sneaking, Spellbook, Stackable, Statistics, StatusEffects, TurnCounter, TurnQueue,
+    Unique, UseItem, Value, Weight, AI, FOV,
+};
+
+/// The key under which the save game is stored.
+const SAVE_GAME_KEY: &str = "savegame.json";
+
+/// Type marking the entities, that are saved, see [SaveMarker].
+pub struct SerializeMe;
+
+/// The marker of all entities, that are part of the save game.
+/// Every entity has to be created with the marker, see [MarkedBuilder].
+pub type SaveMarker = SimpleMarker<SerializeMe>;
+
+/// Component carrying the resources of the game
+/// into the save game, see the module documentation.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct SerializationHelper {
+    /// The [Map] of the current level.
+    pub map: Map,
+
+    /// The message stream of the game.
+    pub game_log: GameLog,
+
+    /// The turn counter of the game.
+    pub turn_counter: TurnCounter,
+
+    /// The statistics of the current run.
+    #[serde(default)]
+    pub run_statistics: RunStatistics,
+
+    /// The statistics of the current level.
+    #[serde(default)]
+    pub level_statistics: LevelStatistics,
+
+    /// The identified items of the current run.
+    #[serde(default)]
+    pub identification: Identification,
+
+    /// The quests taken in the current run.
+    #[serde(default)]
+    pub quest_log: QuestLog,
+
+    /// The daily challenge of the current run, or `None` for other runs.
+    #[serde(default)]
+    pub daily_run: Option<DailyRun>,
+}
+
+/// A component storage, that is part of the save game.
+trait SavedStorage {
+    /// Returns the name of the section of the save game.
+    fn name(&self) -> &'static str;
+
+    /// Serializes the components of all marked entities.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] containing the components.
+    ///
+    fn save(&self, ecs: &World) -> GameResult<Value>;
+
+    /// Deserializes the `section` into the components of the
+    /// marked entities, creating missing entities on the way.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] the components should be restored in.
+    /// * `section`: The serialized components.
+    ///
+    fn restore(&self, ecs: &World, section: Value) -> GameResult<()>;
+}
+
+/// The [SavedStorage] of the component `C`.
+struct Saved<C> {
+    /// The name of the section of the save game.
+    name: &'static str,
+
+    /// The saved component.
+    component: PhantomData<C>,
+}
+
+/// Creates the [SavedStorage] of the component `C`.
+///
+/// # Arguments
+/// * `name`: The name of the section of the save game.
+///
+fn saved<C>(name: &'static str) -> Box<dyn SavedStorage>
+where
+    C: Component + ConvertSaveload<SaveMarker, Error = Infallible>,
+{
+    Box::new(Saved::<C> {
+        name,
+        component: PhantomData,
+    })
+}
+
+impl<C> SavedStorage for Saved<C>
+where
+    C: Component + ConvertSaveload<SaveMarker, Error = Infallible>,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn save(&self, ecs: &World) -> GameResult<Value> {
+        let entities = ecs.entities();
+        let markers = ecs.read_storage::<SaveMarker>();
+
+        SerializeComponents::<Infallible, SaveMarker>::serialize(
+            &(ecs.read_storage::<C>(),),
+            &entities,
+            &markers,
+            serde_json::value::Serializer,
+        )
+        .map_err(save_error)
+    }
+
+    fn restore(&self, ecs: &World, section: Value) -> GameResult<()> {
+        let entities = ecs.entities();
+        let mut markers = ecs.write_storage::<SaveMarker>();
+        let mut allocator = ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>();
+
+        DeserializeComponents::<Infallible, SaveMarker>::deserialize(
+            &mut (ecs.write_storage::<C>(),),
+            &entities,
+            &mut markers,
+            &mut allocator,
+            section,
+        )
+        .map_err(save_error)
+    }
+}
+
+/// Returns all component storages, that are part of the save game.
+///
+/// # Notes
+/// * Every component registered in `register_components` has to
+///   be listed, otherwise it's lost when the game is loaded. Only
+///   the [ParticleLifetime](super::ParticleLifetime) is left out,
+///   particles are temporary.
+///
+fn saved_storages() -> Vec<Box<dyn SavedStorage>> {
+    vec![
+        saved::<Position>("position"),
+        saved::<Renderable>("renderable"),
+        saved::<LightSource>("light_source"),
+        saved::<Player>("player"),
+        saved::<Sneaking>("sneaking"),
+        saved::<FOV>("fov"),
+        saved::<Facing>("facing"),
+        saved::<Monster>("monster"),
+        saved::<Boss>("boss"),
+        saved::<OutOfDepth>("out_of_depth"),
+        saved::<Unique>("unique"),
+        saved::<Friendly>("friendly"),
+        saved::<Shopkeeper>("shopkeeper"),
+        saved::<Purse>("purse"),
+        saved::<Charisma>("charisma"),
+        saved::<Faction>("faction"),
+        saved::<AI>("ai"),
+        saved::<Name>("name"),
+        saved::<Collision>("collision"),
+        saved::<Opaque>("opaque"),
+        saved::<Door>("door"),
+        saved::<Key>("key"),
+        saved::<Container>("container"),
+        saved::<Stackable>("stackable"),
+        saved::<Weight>("weight"),
+        saved::<Ammunition>("ammunition"),
+        saved::<RangedWeapon>("ranged_weapon"),
+        saved::<Statistics>("statistics"),
+        saved::<Experience>("experience"),
+        saved::<ExperienceReward>("experience_reward"),
+        saved::<Mana>("mana"),
+        saved::<Spellbook>("spellbook"),
+        saved::<CastSpell>("cast_spell"),
+        saved::<StatusEffects>("status_effects"),
+        saved::<InflictsStatus>("inflicts_status"),
+        saved::<Regenerates>("regenerates"),
+        saved::<Invisible>("invisible"),
+        saved::<Item>("item"),
+        saved::<Value>("value"),
+        saved::<Scripted>("scripted"),
+        saved::<Ranged>("ranged"),
+        saved::<AreaOfEffect>("area_of_effect"),
+        saved::<Loot>("loot"),
+        saved::<ForSale>("for_sale"),
+        saved::<Equippable>("equippable"),
+        saved::<Equipped>("equipped"),
+        saved::<Cursed>("cursed"),
+        saved::<UseItem>("use_item"),
+        saved::<SerializationHelper>("serialization_helper"),
+    ]
+}
+
+/// Returns `true` if a save game is stored, `false` otherwise.
+pub fn has_save_game() -> bool {
+    storage::exists(SAVE_GAME_KEY)
+}
+
+/// Saves all marked entities and the resources of the passed `ecs`
+/// into the save game. An existing save game is overwritten.
+///
+/// # Arguments
+/// * `ecs`: The [World] of the running game.
+///
+/// # Errors
+/// * If the world couldn't be serialized or the save
+///   game couldn't be written, see [GameError::SaveIO].
+///
+pub fn save_game(ecs: &mut World) -> GameResult<()> {
+    let data = serialize_game(ecs)?;
+    write_save_game(&data)
+}
+
+/// Writes the passed `data` into the save game.
+/// An existing save game is overwritten.
+///
+/// # Arguments
+/// * `data`: The serialized game, see [serialize_game].
+///
+/// # Errors
+/// * If the save game couldn't be written, see [GameError::SaveIO].
+///
+pub fn write_save_game(data: &str) -> GameResult<()> {
+    storage::write(SAVE_GAME_KEY, data)
+}
+
+/// Serializes all marked entities and the resources of the
+/// passed `ecs` and returns the data of the save game.
+///
+/// # Arguments
+/// * `ecs`: The [World] of the running game.
+///
+/// # Errors
+/// * If the world couldn't be serialized, see [GameError::SaveIO].
+///
+pub fn serialize_game(ecs: &mut World) -> GameResult<String> {
+    let helper = SerializationHelper {
+        map: (*ecs.fetch::<Map>()).clone(),
+        game_log: (*ecs.fetch::<GameLog>()).clone(),
+        turn_counter: *ecs.fetch::<TurnCounter>(),
+        run_statistics: (*ecs.fetch::<RunStatistics>()).clone(),
+        level_statistics: *ecs.fetch::<LevelStatistics>(),
+        identification: (*ecs.fetch::<Identification>()).clone(),
+        quest_log: (*ecs.fetch::<QuestLog>()).clone(),
+        daily_run: ecs
+            .try_fetch::<DailyRun>()
+            .map(|daily_run| (*daily_run).clone()),
+    };
+
+    let helper_entity = ecs
+        .create_entity()
+        .with(helper)
+        .marked::<SaveMarker>()
+        .build();
+
+    let sections = saved_storages()
+        .iter()
+        .map(|storage| Ok((storage.name().to_string(), storage.save(ecs)?)))
+        .collect::<GameResult<JsonMap<String, Value>>>();
+
+    // The helper is only needed during the serialization
+    if let Err(error) = ecs.delete_entity(helper_entity) {
+        return Err(GameError::EntityDeletion {
+            entity: error.entity,
+        });
+    }
+
+    serde_json::to_string(&Value::Object(sections?)).map_err(save_error)
+}
+
+/// Replaces all entities and resources of the passed `ecs`
+/// with the ones stored in the save game.
+///
+/// # Arguments
+/// * `ecs`: The [World] of the running game.
+///
+/// # Errors
+/// * If no save game is stored, it can't be read or it's malformed,
+///   see [GameError::SaveIO]. The world may be incomplete, if the
+///   error occurred after the entities have been removed.
+///
+/// # Notes
+/// * The caches of the [Map] are restored by its next update,
+///   so the systems have to be executed once after loading.
+///
+pub fn load_game(ecs: &mut World) -> GameResult<()> {
+    let data = storage::read(SAVE_GAME_KEY)?.ok_or_else(|| GameError::SaveIO {
+        message: "No save game is stored!".to_string(),
+    })?;
+
+    let mut sections = match serde_json::from_str::<Value>(&data).map_err(save_error)? {
+        Value::Object(sections) => sections,
+        _ => return Err(save_error("The save game is malformed!")),
+    };
+
+    ecs.delete_all();
+
+    for storage in saved_storages() {
+        if let Some(section) = sections.remove(storage.name()) {
+            storage.restore(ecs, section)?;
+        }
+    }
+
+    ecs.maintain();
+
+    restore_resources(ecs)
+}
+
+/// Inserts the resources of the [SerializationHelper] and the
+/// player into the passed `ecs` and deletes the helper entity.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the loaded entities.
+///
+/// # Errors
+/// * If the save game contains no helper or player.
+///
+fn restore_resources(ecs: &mut World) -> GameResult<()> {
+    let (helper_entity, mut helper) = {
+        let entities = ecs.entities();
+        let helpers = ecs.read_storage::<SerializationHelper>();
+
+        (&entities, &helpers)
+            .join()
+            .map(|(entity, helper)| (entity, helper.clone()))
+            .next()
+            .ok_or_else(|| save_error("The save game contains no resources!"))?
+    };
+
+    let (player, player_position) = {
+        let entities = ecs.entities();
+        let players = ecs.read_storage::<Player>();
+        let positions = ecs.read_storage::<Position>();
+
+        (&entities, &players, &positions)
+            .join()
+            .map(|(entity, _, position)| (entity, position.to_point()))
+            .next()
+            .ok_or_else(|| save_error("The save game contains no player!"))?
+    };
+
+    helper.map.restore_caches();
+
+    ecs.insert(helper.map);
+    ecs.insert(helper.game_log);
+    ecs.insert(helper.turn_counter);
+    ecs.insert(helper.run_statistics);
+    ecs.insert(helper.level_statistics);
+    ecs.insert(helper.identification);
+    ecs.insert(helper.quest_log);
+
+    match helper.daily_run {
+        Some(daily_run) => ecs.insert(daily_run),
+        None => {
+            ecs.remove::<DailyRun>();
+        }
+    }
+
+    ecs.insert(player);
+    ecs.insert::<Point>(player_position);
+    ecs.insert(PlayerPathing::new());
+    ecs.insert(PlayerResting::default());
+    ecs.insert(PlayerRunning::default());
+    ecs.insert(TurnQueue::default());
+    ecs.insert(Noises::default());
+
+    ecs.delete_entity(helper_entity)
+        .map_err(|error| GameError::EntityDeletion {
+            entity: error.entity,
+        })
+}
+
+/// Registers the [SaveMarker] and the [SerializationHelper]
+/// with the passed `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the components should be registered.
+///
+pub fn register(ecs: &mut World) {
+    ecs.register::<SaveMarker>();
+    ecs.register::<SerializationHelper>();
+    ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+}
+
+/// Wraps the passed `error` into a [GameError::SaveIO].
+///
+/// # Arguments
+/// * `error`: The error, that occurred while saving or loading.
+///
+fn save_error<E: ToString>(error: E) -> GameError {
+    GameError::SaveIO {
+        message: error.to_string(),
+    }
+}
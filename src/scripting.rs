@@ -0,0 +1,482 @@
+//! Module for the content scripts of the game.
+//!
+//! Item effects and spells are written as [rhai] scripts and referenced by name
+//! through the [Scripted] component. Every script defines hook functions,
+//! e.g. `on_use(ctx)`, which receive a [ScriptContext] and request changes
+//! to the game through it. The requested [ScriptEffect]s are applied by the
+//! calling system afterwards, so scripts never access the `ecs` directly.
+//! Player facing texts are looked up with `tr(id)` or `tr(id, [args])`,
+//! see [localization::tr_with].
+//!
+//! The scripts shipped with the game are embedded into the binary. On native
+//! targets scripts in the [config::SCRIPT_DIRECTORY] are loaded on startup
+//! as well and replace embedded scripts of the same name, so mechanics can be
+//! added or changed without recompiling the game.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use rhai::{Array, Engine, Scope, AST};
+use rltk::console;
+use specs::prelude::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::config;
+use super::exceptions::{GameError, GameResult};
+use super::localization;
+use super::{StatusEffect, StatusEffectKind};
+
+/// File extension of the script files.
+#[cfg(not(target_arch = "wasm32"))]
+pub const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Name of the hook, that is called when an item is used.
+pub const HOOK_ON_USE: &str = "on_use";
+
+/// Name of the hook, that is called when a spell is cast.
+pub const HOOK_ON_CAST: &str = "on_cast";
+
+/// The scripts embedded into the binary as `(name, source)` pairs.
+const EMBEDDED_SCRIPTS: &[(&str, &str)] = &[
+    (
+        "health_potion",
+        include_str!("../scripts/health_potion.rhai"),
+    ),
+    (
+        "poison_potion",
+        include_str!("../scripts/poison_potion.rhai"),
+    ),
+    ("antidote", include_str!("../scripts/antidote.rhai")),
+    (
+        "invisibility_potion",
+        include_str!("../scripts/invisibility_potion.rhai"),
+    ),
+    (
+        "see_invisible_potion",
+        include_str!("../scripts/see_invisible_potion.rhai"),
+    ),
+    (
+        "fireball_scroll",
+        include_str!("../scripts/fireball_scroll.rhai"),
+    ),
+    (
+        "confusion_scroll",
+        include_str!("../scripts/confusion_scroll.rhai"),
+    ),
+    ("blink_scroll", include_str!("../scripts/blink_scroll.rhai")),
+    (
+        "teleport_scroll",
+        include_str!("../scripts/teleport_scroll.rhai"),
+    ),
+    (
+        "identify_scroll",
+        include_str!("../scripts/identify_scroll.rhai"),
+    ),
+    (
+        "remove_curse_scroll",
+        include_str!("../scripts/remove_curse_scroll.rhai"),
+    ),
+    (
+        "magic_missile",
+        include_str!("../scripts/magic_missile.rhai"),
+    ),
+    ("mend", include_str!("../scripts/mend.rhai")),
+];
+
+/// Enum describing all changes to the game,
+/// that a script can request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptEffect {
+    /// Restores the given amount of
+    /// health of the user.
+    Heal(i32),
+
+    /// Inflicts the given amount of
+    /// damage on the user, or on every
+    /// entity hit by a targeted item.
+    Damage(i32),
+
+    /// Inflicts the status effect on the user,
+    /// or on every entity hit by a targeted item.
+    Inflict(StatusEffect),
+
+    /// Cures the user, or every entity hit by a targeted
+    /// item, from the status effect of the given kind.
+    Cure(StatusEffectKind),
+
+    /// Sets the flammable tiles under the user, or the
+    /// tiles hit by a targeted item, on fire.
+    Ignite,
+
+    /// Moves the user to the targeted tile, or
+    /// to a random walkable tile of the map, if
+    /// the item isn't used on a target.
+    Teleport,
+
+    /// Identifies all items carried by the user.
+    Identify,
+
+    /// Removes the curses of all items carried by the user.
+    RemoveCurse,
+
+    /// Pushes the message to the `GameLog`.
+    Log(String),
+
+    /// Removes the item from the game.
+    Consume,
+}
+
+/// Data of the [ScriptContext] shared
+/// between all of its copies.
+#[derive(Debug, Default)]
+struct ScriptContextData {
+    /// Name of the entity running the script.
+    user: String,
+
+    /// Name of the item or spell the script belongs to.
+    item: String,
+
+    /// Current hp of the `user`.
+    hp: i32,
+
+    /// Maximum hp of the `user`.
+    hp_max: i32,
+
+    /// The requested effects in the
+    /// order of their request.
+    effects: Vec<ScriptEffect>,
+}
+
+/// Context passed to the hooks of a script, through which the
+/// script reads the state of the game and requests [ScriptEffect]s.
+///
+/// # Notes
+/// * `rhai` passes arguments by value, so all copies of the
+///   context share their data.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ScriptContext {
+    /// The shared data of the context.
+    data: Arc<Mutex<ScriptContextData>>,
+}
+
+impl ScriptContext {
+    /// Creates a new [ScriptContext] for the `user`, running
+    /// the script of the passed `item`.
+    ///
+    /// # Arguments
+    /// * `user`: Name of the entity running the script.
+    /// * `item`: Name of the item the script belongs to.
+    /// * `hp`: Current hp of the `user`.
+    /// * `hp_max`: Maximum hp of the `user`.
+    ///
+    pub fn new(user: &str, item: &str, hp: i32, hp_max: i32) -> Self {
+        let data = ScriptContextData {
+            user: user.to_string(),
+            item: item.to_string(),
+            hp,
+            hp_max,
+            effects: Vec::new(),
+        };
+
+        ScriptContext {
+            data: Arc::new(Mutex::new(data)),
+        }
+    }
+
+    /// Returns all [ScriptEffect]s requested through the
+    /// context in the order of their request.
+    pub fn effects(&self) -> Vec<ScriptEffect> {
+        self.lock().effects.clone()
+    }
+
+    /// Locks the shared data of the context.
+    fn lock(&self) -> std::sync::MutexGuard<'_, ScriptContextData> {
+        self.data
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Requests to heal the user by the `amount` and returns the
+    /// health actually restored, which is capped by the maximum hp.
+    fn heal(&mut self, amount: i64) -> i64 {
+        let mut data = self.lock();
+        let healed = i32::max(0, i32::min(amount as i32, data.hp_max - data.hp));
+
+        data.hp += healed;
+        data.effects.push(ScriptEffect::Heal(healed));
+
+        healed as i64
+    }
+
+    /// Requests to inflict the `amount` of damage on the user.
+    fn damage(&mut self, amount: i64) {
+        let mut data = self.lock();
+
+        data.hp -= amount as i32;
+        data.effects.push(ScriptEffect::Damage(amount as i32));
+    }
+
+    /// Requests to inflict the status effect of the `kind`, e.g. `"poison"`,
+    /// on the user for the `duration` in turns. Returns `false` without
+    /// a request, if no [StatusEffectKind] has the name `kind`.
+    fn inflict(&mut self, kind: &str, duration: i64, potency: i64) -> bool {
+        let kind = match StatusEffectKind::from_name(kind) {
+            Some(kind) => kind,
+            None => return false,
+        };
+
+        self.lock()
+            .effects
+            .push(ScriptEffect::Inflict(StatusEffect {
+                kind,
+                duration: duration as i32,
+                potency: potency as i32,
+                inflicter: None,
+            }));
+
+        true
+    }
+
+    /// Requests to cure the user from the status effect of the `kind`,
+    /// e.g. `"poison"`. Returns `false` without a request, if no
+    /// [StatusEffectKind] has the name `kind`.
+    fn cure(&mut self, kind: &str) -> bool {
+        match StatusEffectKind::from_name(kind) {
+            Some(kind) => {
+                self.lock().effects.push(ScriptEffect::Cure(kind));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Requests to set the tiles hit by the item on fire.
+    fn ignite(&mut self) {
+        self.lock().effects.push(ScriptEffect::Ignite);
+    }
+
+    /// Requests to move the user to the targeted tile, or to
+    /// a random walkable tile, if the item has no target.
+    fn teleport(&mut self) {
+        self.lock().effects.push(ScriptEffect::Teleport);
+    }
+
+    /// Requests to identify all items carried by the user.
+    fn identify(&mut self) {
+        self.lock().effects.push(ScriptEffect::Identify);
+    }
+
+    /// Requests to remove the curses of all items carried by the user.
+    fn remove_curse(&mut self) {
+        self.lock().effects.push(ScriptEffect::RemoveCurse);
+    }
+
+    /// Requests to push the `message` to the game log.
+    fn log(&mut self, message: &str) {
+        self.lock()
+            .effects
+            .push(ScriptEffect::Log(message.to_string()));
+    }
+
+    /// Requests to remove the item from the game.
+    fn consume(&mut self) {
+        self.lock().effects.push(ScriptEffect::Consume);
+    }
+}
+
+/// Resource holding the script engine and all compiled scripts.
+pub struct ScriptEngine {
+    /// The `rhai` engine executing the scripts.
+    engine: Engine,
+
+    /// The compiled scripts by their name.
+    scripts: HashMap<String, AST>,
+
+    /// The errors of all scripts, that couldn't
+    /// be read or compiled.
+    failures: Vec<GameError>,
+}
+
+impl ScriptEngine {
+    /// Creates a new [ScriptEngine], registers the scripting API and
+    /// compiles the embedded scripts as well as the scripts found in
+    /// the [config::SCRIPT_DIRECTORY] on native targets.
+    ///
+    /// # Notes
+    /// * Scripts that fail to compile are skipped, reported to the
+    ///   console and kept in the [ScriptEngine::failures].
+    ///
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+
+        engine
+            .register_type_with_name::<ScriptContext>("Context")
+            .register_get("user", |ctx: &mut ScriptContext| ctx.lock().user.clone())
+            .register_get("item", |ctx: &mut ScriptContext| ctx.lock().item.clone())
+            .register_get("hp", |ctx: &mut ScriptContext| ctx.lock().hp as i64)
+            .register_get("hp_max", |ctx: &mut ScriptContext| ctx.lock().hp_max as i64)
+            .register_fn("heal", ScriptContext::heal)
+            .register_fn("damage", ScriptContext::damage)
+            .register_fn("inflict", ScriptContext::inflict)
+            .register_fn("cure", ScriptContext::cure)
+            .register_fn("ignite", ScriptContext::ignite)
+            .register_fn("teleport", ScriptContext::teleport)
+            .register_fn("identify", ScriptContext::identify)
+            .register_fn("remove_curse", ScriptContext::remove_curse)
+            .register_fn("log", ScriptContext::log)
+            .register_fn("consume", ScriptContext::consume)
+            .register_fn("tr", |id: &str| localization::tr(id))
+            .register_fn("tr", |id: &str, args: Array| {
+                let args: Vec<&dyn std::fmt::Display> = args
+                    .iter()
+                    .map(|arg| arg as &dyn std::fmt::Display)
+                    .collect();
+
+                localization::tr_with(id, &args)
+            });
+
+        let mut script_engine = ScriptEngine {
+            engine,
+            scripts: HashMap::new(),
+            failures: Vec::new(),
+        };
+
+        for (name, source) in EMBEDDED_SCRIPTS.iter() {
+            script_engine.load(name, source);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        script_engine.load_directory(config::SCRIPT_DIRECTORY);
+
+        script_engine
+    }
+
+    /// Compiles the `source` and stores it under the `name`, replacing
+    /// any script with the same name. Returns `true` on success.
+    ///
+    /// # Arguments
+    /// * `name`: The name under which the script is referenced.
+    /// * `source`: The source code of the script.
+    ///
+    pub fn load(&mut self, name: &str, source: &str) -> bool {
+        match self.engine.compile(source) {
+            Ok(ast) => {
+                self.scripts.insert(name.to_string(), ast);
+                true
+            }
+            Err(err) => {
+                console::log(format!("Unable to compile script '{}': {}", name, err));
+                self.failures.push(GameError::Script {
+                    script: name.to_string(),
+                    message: err.to_string(),
+                });
+                false
+            }
+        }
+    }
+
+    /// Loads all scripts with the [SCRIPT_EXTENSION] from the passed
+    /// `directory`. The file name without extension is used as name.
+    /// Does nothing if the `directory` doesn't exist.
+    ///
+    /// # Arguments
+    /// * `directory`: The directory containing the scripts.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_directory(&mut self, directory: &str) {
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            let is_script = path
+                .extension()
+                .is_some_and(|extension| extension == SCRIPT_EXTENSION);
+
+            let name = path.file_stem().and_then(|stem| stem.to_str());
+
+            if let (true, Some(name)) = (is_script, name) {
+                match std::fs::read_to_string(&path) {
+                    Ok(source) => {
+                        self.load(name, &source);
+                    }
+                    Err(err) => {
+                        console::log(format!("Unable to read '{}': {}", path.display(), err));
+                        self.failures.push(GameError::Asset {
+                            asset: path.display().to_string(),
+                            message: err.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if a script with the passed `name` is loaded.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the script.
+    ///
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.scripts.contains_key(name)
+    }
+
+    /// Returns the errors of all scripts, that couldn't be
+    /// read or compiled while loading them.
+    pub fn failures(&self) -> &[GameError] {
+        &self.failures
+    }
+
+    /// Calls the `hook` function of the script with the passed `name`
+    /// with the `context` and returns the requested [ScriptEffect]s.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the script.
+    /// * `hook`: The name of the hook function to call, e.g. [HOOK_ON_USE].
+    /// * `context`: The [ScriptContext] passed to the hook.
+    ///
+    /// # Errors
+    /// * If no script with the `name` is loaded.
+    /// * If the script doesn't define the `hook` or fails during execution.
+    ///
+    pub fn run_hook(
+        &self,
+        name: &str,
+        hook: &str,
+        context: ScriptContext,
+    ) -> GameResult<Vec<ScriptEffect>> {
+        let ast = self.scripts.get(name).ok_or_else(|| GameError::Script {
+            script: name.to_string(),
+            message: "The script is not loaded".to_string(),
+        })?;
+
+        self.engine
+            .call_fn::<()>(&mut Scope::new(), ast, hook, (context.clone(),))
+            .map_err(|err| GameError::Script {
+                script: name.to_string(),
+                message: err.to_string(),
+            })?;
+
+        Ok(context.effects())
+    }
+}
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Registers the [ScriptEngine] with the passed `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [ScriptEngine] should be registered.
+///
+pub fn register(ecs: &mut World) {
+    ecs.insert(ScriptEngine::new());
+}
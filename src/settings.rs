@@ -0,0 +1,132 @@
+//! Module for the player's settings.
+//!
+//! The [Settings] are stored through the [storage] module, so they are
+//! kept between sessions, and are changed from the options menu.
+
+use rltk::{console, Rltk};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use super::fov::FovAlgorithm;
+use super::{config, storage};
+
+/// The key under which the [Settings] are stored.
+const SETTINGS_KEY: &str = "settings.json";
+
+/// Resource holding the player's settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Flag indicating whether or not all purely visual motion, e.g.
+    /// the scanline and screen burn effects or the particles, should
+    /// be disabled. The gameplay is identical in both modes.
+    pub reduced_motion: bool,
+
+    /// The maximum frame rate while the game is active,
+    /// `0` for an uncapped frame rate. Only honored on
+    /// native targets.
+    pub fps_cap: u32,
+
+    /// The algorithm calculating the field of view of all
+    /// entities, e.g. `"symmetric_shadowcasting"`.
+    pub fov_algorithm: FovAlgorithm,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            reduced_motion: false,
+            fps_cap: config::DEFAULT_FPS_CAP,
+            fov_algorithm: FovAlgorithm::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the stored [Settings], or returns the default
+    /// [Settings] if none are stored or they can't be read.
+    ///
+    /// # Notes
+    /// * Errors are reported to the console.
+    ///
+    pub fn load() -> Self {
+        let data = match storage::read(SETTINGS_KEY) {
+            Ok(Some(data)) => data,
+            Ok(None) => return Settings::default(),
+            Err(error) => {
+                console::log(error.to_string());
+                return Settings::default();
+            }
+        };
+
+        serde_json::from_str(&data).unwrap_or_else(|err| {
+            console::log(format!("Unable to parse the settings: {}", err));
+            Settings::default()
+        })
+    }
+
+    /// Stores the [Settings], so they are kept between sessions.
+    ///
+    /// # Notes
+    /// * Errors are reported to the console.
+    ///
+    pub fn save(&self) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|data| storage::write(SETTINGS_KEY, &data).map_err(|err| err.to_string()));
+
+        if let Err(message) = result {
+            console::log(format!("Unable to save the settings: {}", message));
+        }
+    }
+
+    /// Applies the visual settings to the passed terminal `ctx`.
+    ///
+    /// # Arguments
+    /// * `ctx`: The [Rltk] terminal the game is rendered to.
+    ///
+    pub fn apply(&self, ctx: &mut Rltk) {
+        // Enable scan lines for the nostalgic feel, unless motion is reduced.
+        // TODO: Need to find a possibility to insert custom shaders.
+        ctx.post_scanlines = !self.reduced_motion;
+        ctx.post_screenburn = !self.reduced_motion;
+    }
+}
+
+/// Toggles [Settings::reduced_motion] of the [Settings] registered
+/// with the passed `ecs`, stores them and applies them to the `ctx`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Settings] are registered.
+/// * `ctx`: The [Rltk] terminal the game is rendered to.
+///
+pub fn toggle_reduced_motion(ecs: &World, ctx: &mut Rltk) {
+    let mut settings = ecs.fetch_mut::<Settings>();
+
+    settings.reduced_motion = !settings.reduced_motion;
+    settings.save();
+    settings.apply(ctx);
+}
+
+/// Switches [Settings::fov_algorithm] of the [Settings] registered
+/// with the passed `ecs` to the next algorithm and stores them.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Settings] are registered.
+///
+pub fn cycle_fov_algorithm(ecs: &World) {
+    let mut settings = ecs.fetch_mut::<Settings>();
+
+    settings.fov_algorithm = settings.fov_algorithm.next();
+    settings.save();
+}
+
+/// Registers the passed `settings` with the `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Settings] should be registered.
+/// * `settings`: The [Settings] to register.
+///
+pub fn register(ecs: &mut World, settings: Settings) {
+    ecs.insert(settings);
+}
@@ -0,0 +1,333 @@
+//! Module for the trade between the player and the shopkeepers.
+//!
+//! NPCs with a shop in their definition become [Shopkeeper]s. Their stock
+//! is put on display around them, when they are created, every item of
+//! the stock is marked as [ForSale]. Bumping into a shopkeeper offers to
+//! trade with it, the player buys and sells items against the gold in
+//! the player's [Purse] on the shop screen, at the prices of the
+//! [PriceModifiers] of the trade. Picking up an item on display
+//! without paying for it is theft, see the [TheftSystem](super::TheftSystem).
+
+use rltk::VirtualKeyCode;
+use specs::prelude::*;
+
+use super::identification::Identification;
+use super::raws::Raws;
+use super::ui_controller::ShopView;
+use super::valuation::PriceModifiers;
+use super::{config, entity_factory, localization, rng};
+use super::{
+    DialogArgs, DialogInterface, DialogOption, Equipped, ForSale, GameLog, Item, LogCategory, Loot,
+    Map, Name, Position, Purse, RunState, Shopkeeper, TileType, Value,
+};
+
+/// The amount of random tiles tried for every item put on display.
+const DISPLAY_ATTEMPTS: i32 = 20;
+
+/// Creates the stock of the passed `shopkeeper` and puts it on display
+/// on random free floor tiles around the shopkeeper's `position`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the items should be created.
+/// * `map`: The [Map] the shopkeeper stands on.
+/// * `shopkeeper`: The [Shopkeeper] owning the stock.
+/// * `position`: The [Position] of the shopkeeper.
+/// * `occupied`: The tiles, that are already taken, every item is added to them.
+///
+/// # Notes
+/// * Items, for which no free tile is found, are kept by the
+///   shopkeeper, they can still be bought on the shop screen.
+///
+pub fn stock_shop(
+    ecs: &mut World,
+    map: &Map,
+    shopkeeper: Entity,
+    position: &Position,
+    occupied: &mut Vec<Position>,
+) {
+    let stock = {
+        let raws = ecs.fetch::<Raws>();
+        let names = ecs.read_storage::<Name>();
+
+        names
+            .get(shopkeeper)
+            .and_then(|name| raws.npc(&name.name))
+            .map_or_else(Vec::new, |raw| raw.shop.clone())
+    };
+
+    for item_name in stock {
+        let radius = config::SHOP_DISPLAY_RADIUS;
+
+        let display = (0..DISPLAY_ATTEMPTS)
+            .map(|_| Position {
+                x: position.x + rng::range(ecs, -radius, radius + 1),
+                y: position.y + rng::range(ecs, -radius, radius + 1),
+            })
+            .find(|tile| {
+                map.check_idx(tile.x, tile.y)
+                    && map.get_tile(tile.x, tile.y) == TileType::FLOOR
+                    && !occupied.contains(tile)
+            });
+
+        let item = match entity_factory::new_named(ecs, &item_name, display.unwrap_or(*position)) {
+            Some(item) => item,
+            None => continue,
+        };
+
+        match display {
+            Some(tile) => occupied.push(tile),
+            None => {
+                ecs.write_storage::<Position>().remove(item);
+            }
+        }
+
+        let _ = ecs
+            .write_storage::<ForSale>()
+            .insert(item, ForSale { shopkeeper });
+    }
+}
+
+/// Offers the player to trade with the passed `shopkeeper` through a
+/// [DialogInterface]. Shopkeepers, that caught the player stealing,
+/// refuse to trade.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the dialog should be registered.
+/// * `shopkeeper`: The [Shopkeeper] the player has bumped into.
+///
+pub fn offer_trade(ecs: &mut World, shopkeeper: Entity) {
+    let (name, robbed) = {
+        let names = ecs.read_storage::<Name>();
+        let shopkeepers = ecs.read_storage::<Shopkeeper>();
+
+        (
+            names.get(shopkeeper).map(Name::display).unwrap_or_default(),
+            shopkeepers
+                .get(shopkeeper)
+                .is_some_and(|keeper| keeper.robbed),
+        )
+    };
+
+    if robbed {
+        ecs.write_resource::<GameLog>().push(
+            &localization::tr_with("shop.refuse", &[&name]),
+            LogCategory::General,
+        );
+        return;
+    }
+
+    DialogInterface::register_dialog(
+        ecs,
+        name,
+        Some(localization::tr("shop.welcome")),
+        vec![DialogOption {
+            description: localization::tr("shop.trade"),
+            key: VirtualKeyCode::T,
+            args: vec![Box::new(shopkeeper)],
+            callback: Box::new(|world, _, args| open_shop(world, args)),
+        }],
+        true,
+    );
+}
+
+/// Callback of the trade [DialogOption], which opens the shop screen.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [RunState] is stored.
+/// * `args`: The [Entity] of the shopkeeper.
+///
+fn open_shop(ecs: &World, args: &DialogArgs) {
+    if let Some(shopkeeper) = args.first().and_then(|arg| arg.downcast_ref::<Entity>()) {
+        *ecs.write_resource::<RunState>() = RunState::Shop {
+            shopkeeper: *shopkeeper,
+            view: ShopView::default(),
+        };
+    }
+}
+
+/// Sells the passed `item` of the `shopkeeper`'s stock to the player,
+/// if the player can afford it. The item is put into the player's
+/// backpack, where it joins the stack of identical items, and its
+/// price is taken from the player's [Purse].
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the shopkeeper.
+/// * `shopkeeper`: The [Shopkeeper] selling the item.
+/// * `item`: The item the player buys.
+///
+pub fn buy(ecs: &mut World, shopkeeper: Entity, item: Entity) {
+    let player = *ecs.fetch::<Entity>();
+    let modifiers = PriceModifiers::of_player(ecs);
+    let identification = ecs.fetch::<Identification>();
+    let names = ecs.read_storage::<Name>();
+    let values = ecs.read_storage::<Value>();
+    let mut purses = ecs.write_storage::<Purse>();
+    let mut for_sale = ecs.write_storage::<ForSale>();
+    let mut game_log = ecs.write_resource::<GameLog>();
+
+    if for_sale.get(item).map(|sale| sale.shopkeeper) != Some(shopkeeper) {
+        return;
+    }
+
+    let (name, price) = match (names.get(item), values.get(item)) {
+        (Some(name), Some(value)) => (name, modifiers.buy_price(value)),
+        _ => return,
+    };
+
+    let purse = match purses.get_mut(player) {
+        Some(purse) if purse.gold >= price => purse,
+        _ => {
+            game_log.push(&localization::tr("shop.too_expensive"), LogCategory::Item);
+            return;
+        }
+    };
+
+    purse.gold -= price;
+    for_sale.remove(item);
+    ecs.write_storage::<Position>().remove(item);
+    Item::put_into_backpack(ecs, &player, item);
+
+    game_log.push(
+        &localization::tr_with("shop.bought", &[&identification.display(name), &price]),
+        LogCategory::Item,
+    );
+}
+
+/// Sells the passed `item` of the player to the `shopkeeper`, which
+/// adds it to its stock and puts the price into the player's [Purse].
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the shopkeeper.
+/// * `shopkeeper`: The [Shopkeeper] buying the item.
+/// * `item`: The item the player sells.
+///
+/// # Notes
+/// * Only a single item of a stack is sold, see [entity_factory::split_stack].
+///
+pub fn sell(ecs: &mut World, shopkeeper: Entity, item: Entity) {
+    let player = *ecs.fetch::<Entity>();
+
+    // Worn items have to be taken off before they can be sold
+    if ecs.read_storage::<Loot>().get(item).map(|loot| loot.owner) != Some(player)
+        || ecs.read_storage::<Equipped>().contains(item)
+    {
+        return;
+    }
+
+    let offer = {
+        let modifiers = PriceModifiers::of_player(ecs);
+        let identification = ecs.fetch::<Identification>();
+        let names = ecs.read_storage::<Name>();
+        let values = ecs.read_storage::<Value>();
+
+        match (names.get(item), values.get(item)) {
+            (Some(name), Some(value)) => Some((
+                identification.display(name),
+                modifiers.sell_price(value, identification.is_identified(&name.name)),
+            )),
+            _ => None,
+        }
+    };
+
+    let (name, price) = match offer {
+        Some(offer) => offer,
+        None => {
+            ecs.write_resource::<GameLog>()
+                .push(&localization::tr("shop.not_interested"), LogCategory::Item);
+            return;
+        }
+    };
+
+    // The stack is only split once the sale is certain
+    let item = entity_factory::split_stack(ecs, item);
+
+    if let Some(purse) = ecs.write_storage::<Purse>().get_mut(player) {
+        purse.gold += price;
+    }
+
+    ecs.write_storage::<Loot>().remove(item);
+    let _ = ecs
+        .write_storage::<ForSale>()
+        .insert(item, ForSale { shopkeeper });
+
+    ecs.write_resource::<GameLog>().push(
+        &localization::tr_with("shop.sold", &[&name, &price]),
+        LogCategory::Item,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::TestWorldBuilder;
+    use super::super::{EquipmentSlot, Stackable};
+    use super::*;
+
+    /// Returns the items owned by the passed `owner`
+    /// together with the quantity of their stacks.
+    fn inventory(ecs: &World, owner: Entity) -> Vec<(Entity, i32)> {
+        let entities = ecs.entities();
+        let backpack = ecs.read_storage::<Loot>();
+        let stackables = ecs.read_storage::<Stackable>();
+
+        (&entities, &backpack)
+            .join()
+            .filter(|(_, loot)| loot.owner == owner)
+            .map(|(entity, _)| {
+                let quantity = stackables
+                    .get(entity)
+                    .map_or(1, |stackable| stackable.quantity);
+
+                (entity, quantity)
+            })
+            .collect()
+    }
+
+    /// Moves the passed `item` into the backpack of the `owner`
+    /// as a stack of three.
+    fn give_stack(ecs: &mut World, owner: Entity, item: Entity) {
+        ecs.write_storage::<Position>().remove(item);
+        let _ = ecs.write_storage::<Loot>().insert(item, Loot { owner });
+
+        if let Some(stackable) = ecs.write_storage::<Stackable>().get_mut(item) {
+            stackable.quantity = 3;
+        }
+    }
+
+    #[test]
+    fn sell_keeps_equipped_and_foreign_items() {
+        let mut world = TestWorldBuilder::new()
+            .with_player(1, 1)
+            .with_potion(3, 3)
+            .with_potion(4, 4)
+            .build();
+
+        let player = world.player();
+        let shopkeeper = world.ecs.create_entity().build();
+        let (equipped, foreign) = (world.items[0], world.items[1]);
+
+        give_stack(&mut world.ecs, player, equipped);
+        give_stack(&mut world.ecs, shopkeeper, foreign);
+
+        let _ = world.ecs.write_storage::<Equipped>().insert(
+            equipped,
+            Equipped {
+                owner: player,
+                slot: EquipmentSlot::Melee,
+            },
+        );
+
+        let gold = |ecs: &World| ecs.read_storage::<Purse>().get(player).map(|p| p.gold);
+        let gold_before = gold(&world.ecs);
+        let player_items = inventory(&world.ecs, player);
+        let shopkeeper_items = inventory(&world.ecs, shopkeeper);
+
+        sell(&mut world.ecs, shopkeeper, equipped);
+        sell(&mut world.ecs, shopkeeper, foreign);
+        world.ecs.maintain();
+
+        assert_eq!(inventory(&world.ecs, player), player_items);
+        assert_eq!(inventory(&world.ecs, shopkeeper), shopkeeper_items);
+        assert_eq!(gold(&world.ecs), gold_before);
+        assert_eq!(world.ecs.read_storage::<ForSale>().join().count(), 0);
+    }
+}
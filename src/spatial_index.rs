@@ -0,0 +1,155 @@
+//! Module for the spatial index of the entities on a map.
+//!
+//! The [SpatialIndex] stores the entities of all tiles in a single flat
+//! arena of slots, which are chained into one list per tile. Removed
+//! slots are reused by the next insertion and clearing the index keeps
+//! the arena's capacity, so keeping the index up to date doesn't
+//! allocate once the arena has grown to the amount of indexed entities.
+
+use specs::prelude::*;
+
+/// A single entry of the [SpatialIndex] arena.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    /// The indexed [Entity].
+    entity: Entity,
+
+    /// The next slot of the same tile, `None` for the last one.
+    next: Option<usize>,
+}
+
+/// Index of the entities on every tile of a map.
+#[derive(Debug, Clone, Default)]
+pub struct SpatialIndex {
+    /// The first slot of every tile, `None` for empty tiles.
+    heads: Vec<Option<usize>>,
+
+    /// The arena of slots of all tiles.
+    slots: Vec<Slot>,
+
+    /// The slots of the arena, that have been freed
+    /// by removals and can be reused.
+    free: Vec<usize>,
+}
+
+impl SpatialIndex {
+    /// Creates a new empty index for a map of `tile_count` tiles.
+    ///
+    /// # Arguments
+    /// * `tile_count`: The amount of tiles of the map.
+    ///
+    pub fn new(tile_count: usize) -> Self {
+        SpatialIndex {
+            heads: vec![None; tile_count],
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Returns the entities on the tile at the passed
+    /// `idx` in the order in which they were inserted.
+    ///
+    /// # Arguments
+    /// * `idx`: The index of the tile.
+    ///
+    pub fn get(&self, idx: usize) -> TileEntities<'_> {
+        TileEntities {
+            index: self,
+            next: self.heads[idx],
+        }
+    }
+
+    /// Appends the passed `entity` to the tile at the passed `idx`.
+    ///
+    /// # Arguments
+    /// * `idx`: The index of the tile.
+    /// * `entity`: The [Entity] to add.
+    ///
+    pub fn insert(&mut self, idx: usize, entity: Entity) {
+        let slot = Slot { entity, next: None };
+
+        let new = match self.free.pop() {
+            Some(free) => {
+                self.slots[free] = slot;
+                free
+            }
+            None => {
+                self.slots.push(slot);
+                self.slots.len() - 1
+            }
+        };
+
+        match self.last_slot(idx) {
+            Some(last) => self.slots[last].next = Some(new),
+            None => self.heads[idx] = Some(new),
+        }
+    }
+
+    /// Removes the passed `entity` from the tile at the passed `idx`.
+    ///
+    /// # Arguments
+    /// * `idx`: The index of the tile.
+    /// * `entity`: The [Entity] to remove.
+    ///
+    pub fn remove(&mut self, idx: usize, entity: Entity) {
+        let mut previous: Option<usize> = None;
+        let mut current = self.heads[idx];
+
+        while let Some(slot) = current {
+            let next = self.slots[slot].next;
+
+            if self.slots[slot].entity == entity {
+                match previous {
+                    Some(previous) => self.slots[previous].next = next,
+                    None => self.heads[idx] = next,
+                }
+
+                self.free.push(slot);
+            } else {
+                previous = Some(slot);
+            }
+
+            current = next;
+        }
+    }
+
+    /// Removes all entities from the index, but keeps the
+    /// memory of the arena for the next insertions.
+    pub fn clear(&mut self) {
+        self.heads.fill(None);
+        self.slots.clear();
+        self.free.clear();
+    }
+
+    /// Returns the last slot of the tile at the passed
+    /// `idx`, or `None` if the tile is empty.
+    fn last_slot(&self, idx: usize) -> Option<usize> {
+        let mut last = self.heads[idx]?;
+
+        while let Some(next) = self.slots[last].next {
+            last = next;
+        }
+
+        Some(last)
+    }
+}
+
+/// Iterator over the entities on a single tile of a [SpatialIndex].
+pub struct TileEntities<'a> {
+    /// The iterated index.
+    index: &'a SpatialIndex,
+
+    /// The next slot to return.
+    next: Option<usize>,
+}
+
+impl Iterator for TileEntities<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slot = self.index.slots[self.next?];
+        self.next = slot.next;
+
+        Some(slot.entity)
+    }
+}
@@ -1,43 +1,441 @@
 //! Module for spawning monsters, items and general entities.
 
-use super::{config, entity_factory, rng, Position, Rectangle};
+use std::collections::BTreeMap;
+
+use rltk::{console, RandomNumberGenerator};
 use specs::prelude::*;
 
+use super::biome::Biome;
+use super::game_config::GameConfig;
+use super::raws::Raws;
+use super::spawn_table::{SpawnTable, SpawnTables};
+use super::{config, entity_factory, rng};
+use super::{
+    pythagoras_distance, Map, OutOfDepth, Position, Rectangle, RunStatistics, TileType, AI,
+};
+
+/// The part of a [Map] in which entities are spawned.
+enum SpawnArea<'a> {
+    /// The interior of a room.
+    Room(&'a Rectangle),
+
+    /// The floor tiles of a region of a map without rooms.
+    Region(&'a [Position]),
+}
+
+impl SpawnArea<'_> {
+    /// Returns a random [Position] inside of the area.
+    ///
+    /// # Arguments
+    /// * `rng`: The [RandomNumberGenerator] to pick the position with.
+    ///
+    fn random_position(&self, rng: &mut RandomNumberGenerator) -> Position {
+        match self {
+            SpawnArea::Room(room) => room.random_point(rng),
+            SpawnArea::Region(tiles) => tiles[rng.range(0, tiles.len() as i32) as usize],
+        }
+    }
+
+    /// Returns the amount of distinct positions inside of the area.
+    fn capacity(&self) -> usize {
+        match self {
+            SpawnArea::Room(room) => (room.width() * room.height()) as usize,
+            SpawnArea::Region(tiles) => tiles.len(),
+        }
+    }
+}
+
+/// Spawns monsters and items in all rooms of the passed [Map],
+/// except for the first room, which is reserved for the player.
+/// Maps without rooms are populated region by region instead.
+/// The monsters of each room or region form a pack. Afterwards
+/// the map may receive a unique monster.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Entity] structs will be saved.
+/// * `map`: The [Map] whose rooms should be populated.
+///
+/// # See also
+/// * [spawn_in_room]
+/// * [populate_regions]
+/// * [spawn_unique]
+///
+pub fn populate_map(ecs: &mut World, map: &Map) {
+    let tables = (*ecs.fetch::<SpawnTables>()).clone();
+
+    if map.rooms.is_empty() {
+        populate_regions(ecs, &tables, map);
+    } else {
+        map.rooms_for_each_skip(1, |pack, room| {
+            spawn_in_room(ecs, &tables, room, map.depth, map.biome, pack);
+        });
+    }
+
+    spawn_unique(ecs, &tables, map);
+}
+
+/// Splits the passed [Map] into square regions with a side length of
+/// [GameConfig::spawn_region_size] and spawns monsters and items on the floor
+/// tiles of every region, except for the region of the player's start.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Entity] structs will be saved.
+/// * `tables`: The [SpawnTables] from which the entities are picked.
+/// * `map`: The [Map] whose regions should be populated.
+///
+/// # Notes
+/// * Regions with less floor tiles than the
+///   [GameConfig::min_spawn_region_tiles] are skipped.
+///
+/// # See also
+/// * [spawn_in_region]
+///
+fn populate_regions(ecs: &mut World, tables: &SpawnTables, map: &Map) {
+    let (region_size, min_tiles) = {
+        let game_config = ecs.fetch::<GameConfig>();
+        (
+            game_config.spawn_region_size,
+            game_config.min_spawn_region_tiles,
+        )
+    };
+
+    let region_of = |position: &Position| (position.x / region_size, position.y / region_size);
+
+    let start_region = region_of(&map.player_start());
+    let mut regions: BTreeMap<(i32, i32), Vec<Position>> = BTreeMap::new();
+
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        if *tile == TileType::FLOOR {
+            let position = Position::new_from_tuple(map.idx_to_coordinates(idx));
+            regions
+                .entry(region_of(&position))
+                .or_default()
+                .push(position);
+        }
+    }
+
+    let populated = regions
+        .iter()
+        .filter(|(region, tiles)| **region != start_region && tiles.len() >= min_tiles);
+
+    for (pack, (_, tiles)) in populated.enumerate() {
+        spawn_in_region(ecs, tables, tiles, map.depth, map.biome, pack);
+    }
+}
+
 /// Spawns monsters and items in the passed room [Rectangle],
-/// based on the parameters set in the game's [config].
+/// as rolled from the passed [SpawnTables] for the `depth`.
 ///
 /// # Arguments
 /// * `ecs`: The [World] in which the [Entity] structs will be saved.
+/// * `tables`: The [SpawnTables] from which the entities are picked.
 /// * `room`: The room from the [Map] in which the monsters and items
-/// should be spawned.
+///   should be spawned.
+/// * `depth`: The depth of the [Map] the room belongs to.
+/// * `biome`: The [Biome] of the [Map] the room belongs to.
+/// * `pack`: The id of the pack formed by the monsters of the room.
 ///
 /// # See also
-/// * [place_entities_in_room]
+/// * [spawn_from_table]
+/// * [form_pack]
+/// * [spawn_out_of_depth]
 ///
-pub fn spawn_in_room(ecs: &mut World, room: &Rectangle) {
-    let mut monster_spawn_positions: Vec<Position> = Vec::new();
-    let mut item_spawn_positions: Vec<Position> = Vec::new();
+pub fn spawn_in_room(
+    ecs: &mut World,
+    tables: &SpawnTables,
+    room: &Rectangle,
+    depth: i32,
+    biome: Option<Biome>,
+    pack: usize,
+) {
+    let area = SpawnArea::Room(room);
+    let (max_monsters, max_items) = spawn_caps(ecs);
 
-    let monster_amount = rng::roll_dice(ecs, 1, config::MAX_MONSTERS_PER_ROOM + 2) - 3;
-    let item_amount = rng::roll_dice(ecs, 1, config::MAX_ITEMS_PER_ROOM + 2) - 3;
+    let monsters = spawn_from_table(ecs, &tables.monsters, &area, depth, biome, max_monsters);
+    form_pack(ecs, &monsters, pack);
+    spawn_out_of_depth(ecs, &tables.monsters, &area, depth, biome, &monsters);
 
-    // Place monsters
-    place_entities_in_room(ecs, monster_amount, room, &mut monster_spawn_positions);
+    spawn_from_table(ecs, &tables.items, &area, depth, biome, max_items);
+}
 
-    // Place items
-    place_entities_in_room(ecs, item_amount, room, &mut item_spawn_positions);
+/// Spawns monsters and items on the passed floor `tiles` of
+/// a region, as rolled from the passed [SpawnTables] for the `depth`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Entity] structs will be saved.
+/// * `tables`: The [SpawnTables] from which the entities are picked.
+/// * `tiles`: The positions of the floor tiles of the region.
+/// * `depth`: The depth of the [Map] the region belongs to.
+/// * `biome`: The [Biome] of the [Map] the region belongs to.
+/// * `pack`: The id of the pack formed by the monsters of the region.
+///
+/// # See also
+/// * [spawn_from_table]
+/// * [form_pack]
+/// * [spawn_out_of_depth]
+///
+pub fn spawn_in_region(
+    ecs: &mut World,
+    tables: &SpawnTables,
+    tiles: &[Position],
+    depth: i32,
+    biome: Option<Biome>,
+    pack: usize,
+) {
+    let area = SpawnArea::Region(tiles);
+    let (max_monsters, max_items) = spawn_caps(ecs);
+
+    let monsters = spawn_from_table(ecs, &tables.monsters, &area, depth, biome, max_monsters);
+    form_pack(ecs, &monsters, pack);
+    spawn_out_of_depth(ecs, &tables.monsters, &area, depth, biome, &monsters);
+
+    spawn_from_table(ecs, &tables.items, &area, depth, biome, max_items);
+}
 
-    // Create monsters
-    for position in monster_spawn_positions.iter().copied() {
-        entity_factory::random_monster(ecs, position);
+/// Assigns the passed `pack` id to the [AI] of all passed `monsters`,
+/// so they alert each other and surround the player together.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the monsters are stored.
+/// * `monsters`: The monsters spawned together.
+/// * `pack`: The id of the pack, unique within the level.
+///
+/// # Notes
+/// * A single monster doesn't form a pack.
+///
+fn form_pack(ecs: &mut World, monsters: &[Entity], pack: usize) {
+    if monsters.len() < 2 {
+        return;
     }
 
-    // Create items
-    for position in item_spawn_positions.iter().copied() {
-        entity_factory::new_health_potion(ecs, position);
+    let mut ais = ecs.write_storage::<AI>();
+
+    for monster in monsters.iter() {
+        if let Some(ai) = ais.get_mut(*monster) {
+            ai.pack = Some(pack);
+        }
     }
 }
 
+/// Rolls the out of depth chance of the passed [SpawnTable] for the
+/// `depth` and creates the picked entity at a free position inside
+/// of the `area`, marked as [OutOfDepth].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Entity] structs will be saved.
+/// * `table`: The [SpawnTable] from which the entity is picked.
+/// * `area`: The [SpawnArea] in which the entity should be spawned.
+/// * `depth`: The depth of the [Map] the area belongs to.
+/// * `biome`: The [Biome] of the [Map] the area belongs to.
+/// * `occupants`: The entities already spawned in the `area`.
+///
+/// # Notes
+/// * The entity doesn't join the pack of the area.
+/// * Nothing is spawned, if the `area` is already full.
+///
+fn spawn_out_of_depth(
+    ecs: &mut World,
+    table: &SpawnTable,
+    area: &SpawnArea,
+    depth: i32,
+    biome: Option<Biome>,
+    occupants: &[Entity],
+) {
+    let (name, deeper) = match table.roll_out_of_depth(ecs, depth, biome) {
+        Some(spawn) => spawn,
+        None => return,
+    };
+
+    let mut occupied = {
+        let positions = ecs.read_storage::<Position>();
+
+        occupants
+            .iter()
+            .filter_map(|occupant| positions.get(*occupant).copied())
+            .collect::<Vec<_>>()
+    };
+
+    if occupied.len() >= area.capacity() {
+        return;
+    }
+
+    let position = {
+        let positions_before = occupied.len();
+        place_entities_in_area(ecs, 1, area, &mut occupied);
+
+        match occupied.get(positions_before) {
+            Some(position) => *position,
+            None => return,
+        }
+    };
+
+    match entity_factory::new_named(ecs, &name, position) {
+        Some(entity) => {
+            let _ = ecs
+                .write_storage::<OutOfDepth>()
+                .insert(entity, OutOfDepth { depth: deeper });
+        }
+        None => console::log(format!("Unknown entity '{}' in spawn table!", name)),
+    }
+}
+
+/// Spawns a unique monster, that hasn't appeared in the run yet, on a
+/// random free floor tile of the passed [Map] by the
+/// [config::UNIQUE_SPAWN_CHANCE]. The unique is recorded in the
+/// [RunStatistics], so it doesn't appear again.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Entity] structs will be saved.
+/// * `tables`: The [SpawnTables] deciding where the base monsters live.
+/// * `map`: The [Map] the unique should be spawned on.
+///
+/// # Notes
+/// * Only uniques, whose base monster can be spawned on the
+///   map, i.e. at its depth and in its biome, are considered.
+/// * The unique keeps at least [config::UNIQUE_MIN_START_DISTANCE]
+///   away from the player's start.
+///
+pub fn spawn_unique(ecs: &mut World, tables: &SpawnTables, map: &Map) {
+    if rng::range(ecs, 0, 100) >= config::UNIQUE_SPAWN_CHANCE {
+        return;
+    }
+
+    let candidates = {
+        let raws = ecs.fetch::<Raws>();
+        let run_statistics = ecs.fetch::<RunStatistics>();
+
+        raws.unique_names()
+            .into_iter()
+            .filter(|name| !run_statistics.uniques_spawned.contains(name))
+            .filter(|name| {
+                raws.unique(name).is_some_and(|unique| {
+                    map.depth >= unique.min_depth
+                        && tables.monsters.entries.iter().any(|entry| {
+                            entry.name == unique.base && entry.is_available(map.depth, map.biome)
+                        })
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if candidates.is_empty() {
+        return;
+    }
+
+    let occupied = ecs
+        .read_storage::<Position>()
+        .join()
+        .copied()
+        .collect::<Vec<_>>();
+    let start = map.player_start().to_point();
+
+    let tiles = map
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile == TileType::FLOOR)
+        .map(|(idx, _)| Position::new_from_tuple(map.idx_to_coordinates(idx)))
+        .filter(|tile| {
+            pythagoras_distance(&start, &tile.to_point()) >= config::UNIQUE_MIN_START_DISTANCE
+        })
+        .filter(|tile| !occupied.contains(tile))
+        .collect::<Vec<_>>();
+
+    if tiles.is_empty() {
+        return;
+    }
+
+    let name = &candidates[rng::range(ecs, 0, candidates.len() as i32) as usize];
+    let position = tiles[rng::range(ecs, 0, tiles.len() as i32) as usize];
+
+    match entity_factory::new_named(ecs, name, position) {
+        Some(_) => ecs
+            .write_resource::<RunStatistics>()
+            .uniques_spawned
+            .push(name.clone()),
+        None => console::log(format!("Unable to spawn the unique '{}'!", name)),
+    }
+}
+
+/// Creates the items of the treasure [SpawnTable] for the passed
+/// `depth` on the `position`, where an [OutOfDepth] monster has
+/// been defeated.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the items should be created.
+/// * `position`: The [Position] of the defeated monster.
+/// * `depth`: The [OutOfDepth::depth] of the monster.
+///
+pub fn drop_hoard(ecs: &mut World, position: Position, depth: i32) {
+    let table = ecs.fetch::<SpawnTables>().treasure.clone();
+    let biome = ecs.fetch::<Map>().biome;
+
+    for name in table.roll(ecs, depth, biome) {
+        if entity_factory::new_named(ecs, &name, position).is_none() {
+            console::log(format!("Unknown entity '{}' in spawn table!", name));
+        }
+    }
+}
+
+/// Returns the maximum amount of monsters and items
+/// spawned in a single room or region.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [GameConfig] is registered.
+///
+fn spawn_caps(ecs: &World) -> (usize, usize) {
+    let game_config = ecs.fetch::<GameConfig>();
+
+    (
+        game_config.max_monsters_per_room,
+        game_config.max_items_per_room,
+    )
+}
+
+/// Rolls the entities of the passed [SpawnTable] for the `depth`, creates
+/// them at distinct positions inside of the `area` and returns them.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Entity] structs will be saved.
+/// * `table`: The [SpawnTable] from which the entities are picked.
+/// * `area`: The [SpawnArea] in which the entities should be spawned.
+/// * `depth`: The depth of the [Map] the area belongs to.
+/// * `biome`: The [Biome] of the [Map] the area belongs to.
+/// * `max_spawns`: The maximum amount of entities to create.
+///
+/// # Notes
+/// * Entries unknown to the [entity_factory] are skipped and
+///   reported to the console.
+///
+/// # See also
+/// * [place_entities_in_area]
+///
+fn spawn_from_table(
+    ecs: &mut World,
+    table: &SpawnTable,
+    area: &SpawnArea,
+    depth: i32,
+    biome: Option<Biome>,
+    max_spawns: usize,
+) -> Vec<Entity> {
+    let mut spawn_positions: Vec<Position> = Vec::new();
+    let mut entities = Vec::new();
+
+    let mut spawns = table.roll(ecs, depth, biome);
+    spawns.truncate(max_spawns);
+
+    place_entities_in_area(ecs, spawns.len() as i32, area, &mut spawn_positions);
+
+    for (name, position) in spawns.iter().zip(spawn_positions.iter().copied()) {
+        match entity_factory::new_named(ecs, name, position) {
+            Some(entity) => entities.push(entity),
+            None => console::log(format!("Unknown entity '{}' in spawn table!", name)),
+        }
+    }
+
+    entities
+}
+
 /// Convenience function that creates monster or item entities
 /// in accordance to the passed `max_placement` parameter and
 /// the positions which are already occupied by a monster as
@@ -46,24 +444,26 @@ pub fn spawn_in_room(ecs: &mut World, room: &Rectangle) {
 /// # Arguments
 /// * `ecs`: The [World] in which the entities should be stored.
 /// * `max_placements`: Maximum amount of entities that can be placed.
-/// * `room`: Reference to the room [Rectangle] from the [Map], in which
-/// the entities should be placed.
+/// * `area`: Reference to the [SpawnArea] of the [Map], in which
+///   the entities should be placed.
 /// * `container`: [Vec] storing the spawn positions of the monsters.
+///
+/// # Notes
+/// * No more entities than the area has positions are placed.
 ///  
-fn place_entities_in_room(
+fn place_entities_in_area(
     ecs: &mut World,
     max_placements: i32,
-    room: &Rectangle,
+    area: &SpawnArea,
     container: &mut Vec<Position>,
 ) {
+    let max_placements = max_placements.min(area.capacity() as i32);
+
     for _ in 0..max_placements {
         let mut is_placed = false;
 
         while !is_placed {
-            let x = room.left + rng::roll_dice(ecs, 1, i32::abs(room.right - room.left));
-            let y = room.top + rng::roll_dice(ecs, 1, i32::abs(room.bottom - room.top));
-
-            let position = Position { x, y };
+            let position = area.random_position(&mut ecs.write_resource::<RandomNumberGenerator>());
 
             if !container.contains(&position) {
                 container.push(position);
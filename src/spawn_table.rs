@@ -0,0 +1,285 @@
+//! Module for the weighted spawn tables of the game.
+//!
+//! The tables are defined in `data/spawn_tables.json`, which is embedded
+//! into the binary. Every [SpawnTable] defines how many entities a room
+//! receives and from which [SpawnEntry]s they are picked. Amounts are
+//! given in dice notation, e.g. `1d6-3`, see [rng::roll]. Entries are
+//! chosen by their weight among all entries available at the depth of
+//! the map. The weight of an entry changes by its depth modifier on
+//! every level below its minimum depth, so harder monsters and rarer
+//! items become more common on deeper levels. Entries limited to some
+//! [Biome]s are only available on the levels of these biomes.
+//!
+//! Tables with an [OutOfDepthRoll] have a small chance to spawn an
+//! additional entity, that is only available several levels deeper.
+
+use rltk::console;
+use serde::Deserialize;
+use specs::prelude::*;
+
+use super::biome::Biome;
+use super::rng;
+
+/// The embedded spawn table definitions.
+const SPAWN_TABLES: &str = include_str!("../data/spawn_tables.json");
+
+/// Rolls the passed dice `notation` and returns the result, which
+/// is never negative. Malformed notations are reported to the
+/// console and roll `0`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the `rng` handler is registered.
+/// * `notation`: The dice notation to roll, e.g. `1d6-3`.
+///
+fn roll_amount(ecs: &mut World, notation: &str) -> usize {
+    match rng::roll(ecs, notation) {
+        Ok(amount) => amount.max(0) as usize,
+        Err(error) => {
+            console::log(error.to_string());
+            0
+        }
+    }
+}
+
+/// A single entry of a [SpawnTable].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpawnEntry {
+    /// The name of the spawned entity as
+    /// known to the `entity_factory`.
+    pub name: String,
+
+    /// The relative chance of the entry to be picked
+    /// at its `min_depth`.
+    pub weight: i32,
+
+    /// The change of the `weight` on every level below the
+    /// `min_depth`, negative for entries becoming rarer.
+    #[serde(default)]
+    pub depth_modifier: i32,
+
+    /// The first depth at which the entry can be spawned.
+    #[serde(default = "default_min_depth")]
+    pub min_depth: i32,
+
+    /// The last depth at which the entry can be spawned,
+    /// `None` if the entry is available on all deeper levels.
+    #[serde(default)]
+    pub max_depth: Option<i32>,
+
+    /// The dice notation of the amount of entities
+    /// spawned together when the entry is picked.
+    #[serde(default = "default_group")]
+    pub group: String,
+
+    /// The biomes in which the entry can be spawned,
+    /// empty if the entry is available in all biomes.
+    #[serde(default)]
+    pub biomes: Vec<Biome>,
+}
+
+impl SpawnEntry {
+    /// Returns `true` if the entry can be spawned at
+    /// the passed `depth` and in the passed `biome`.
+    ///
+    /// # Arguments
+    /// * `depth`: The depth of the map.
+    /// * `biome`: The [Biome] of the map.
+    ///
+    pub fn is_available(&self, depth: i32, biome: Option<Biome>) -> bool {
+        let in_biome =
+            self.biomes.is_empty() || biome.is_some_and(|biome| self.biomes.contains(&biome));
+
+        in_biome && depth >= self.min_depth && self.max_depth.is_none_or(|max| depth <= max)
+    }
+
+    /// Returns the weight of the entry at the passed `depth` and in the
+    /// passed `biome`, which is `0` if the entry isn't available there.
+    ///
+    /// # Arguments
+    /// * `depth`: The depth of the map.
+    /// * `biome`: The [Biome] of the map.
+    ///
+    pub fn weight_at(&self, depth: i32, biome: Option<Biome>) -> i32 {
+        if !self.is_available(depth, biome) {
+            return 0;
+        }
+
+        i32::max(
+            0,
+            self.weight + self.depth_modifier * (depth - self.min_depth),
+        )
+    }
+}
+
+/// The chance of a [SpawnTable] to spawn an entity of a deeper level.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct OutOfDepthRoll {
+    /// The chance in percent of a single room or
+    /// region to receive an entity out of depth.
+    pub chance: i32,
+
+    /// The amount of levels below the depth of the
+    /// map, at which the entity is picked.
+    pub levels: i32,
+}
+
+/// Table of [SpawnEntry]s from which the entities
+/// of a single room or region are picked.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpawnTable {
+    /// The dice notation of the amount of entities
+    /// spawned in a single room, e.g. `1d6-3`.
+    pub per_room: String,
+
+    /// All entries of the table.
+    pub entries: Vec<SpawnEntry>,
+
+    /// The chance to spawn an entity out of depth, `None`
+    /// if the table only spawns entities of the map's depth.
+    #[serde(default)]
+    pub out_of_depth: Option<OutOfDepthRoll>,
+}
+
+impl SpawnTable {
+    /// Rolls the entities for a single room or region at the passed `depth`
+    /// and returns their names. The amount of entities is capped by the
+    /// `per_room` amount of the table, even if a group would exceed it.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which the `rng` handler is registered.
+    /// * `depth`: The depth of the map the room belongs to.
+    /// * `biome`: The [Biome] of the map the room belongs to.
+    ///
+    /// # Notes
+    /// * Returns an empty [Vec] if no entry is available at the `depth`.
+    ///
+    pub fn roll(&self, ecs: &mut World, depth: i32, biome: Option<Biome>) -> Vec<String> {
+        let available: Vec<(&SpawnEntry, i32)> = self
+            .entries
+            .iter()
+            .map(|entry| (entry, entry.weight_at(depth, biome)))
+            .filter(|(_, weight)| *weight > 0)
+            .collect();
+
+        let total_weight: i32 = available.iter().map(|(_, weight)| weight).sum();
+        let amount = roll_amount(ecs, &self.per_room);
+        let mut spawns = Vec::with_capacity(amount);
+
+        if total_weight <= 0 {
+            return spawns;
+        }
+
+        while spawns.len() < amount {
+            let mut roll = rng::range(ecs, 0, total_weight);
+
+            for (entry, weight) in available.iter() {
+                if roll < *weight {
+                    let group_size = roll_amount(ecs, &entry.group).max(1);
+                    let group_size = group_size.min(amount - spawns.len());
+
+                    spawns.extend(std::iter::repeat_n(entry.name.clone(), group_size));
+                    break;
+                }
+
+                roll -= weight;
+            }
+        }
+
+        spawns
+    }
+
+    /// Rolls the [SpawnTable::out_of_depth] chance of a single room or
+    /// region at the passed `depth`. On success an entry, that isn't
+    /// available at the `depth` yet, is picked by its weight at the
+    /// deeper level. Returns the name of the entry and the depth it
+    /// was picked at, or `None` if no entity is spawned out of depth.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which the `rng` handler is registered.
+    /// * `depth`: The depth of the map the room belongs to.
+    /// * `biome`: The [Biome] of the map the room belongs to.
+    ///
+    pub fn roll_out_of_depth(
+        &self,
+        ecs: &mut World,
+        depth: i32,
+        biome: Option<Biome>,
+    ) -> Option<(String, i32)> {
+        let out_of_depth = self.out_of_depth?;
+
+        if rng::range(ecs, 0, 100) >= out_of_depth.chance {
+            return None;
+        }
+
+        let deeper = depth + out_of_depth.levels;
+
+        let available: Vec<(&SpawnEntry, i32)> = self
+            .entries
+            .iter()
+            .filter(|entry| !entry.is_available(depth, biome))
+            .map(|entry| (entry, entry.weight_at(deeper, biome)))
+            .filter(|(_, weight)| *weight > 0)
+            .collect();
+
+        let total_weight: i32 = available.iter().map(|(_, weight)| weight).sum();
+
+        if total_weight <= 0 {
+            return None;
+        }
+
+        let mut roll = rng::range(ecs, 0, total_weight);
+
+        for (entry, weight) in available {
+            if roll < weight {
+                return Some((entry.name.clone(), deeper));
+            }
+
+            roll -= weight;
+        }
+
+        None
+    }
+}
+
+/// Resource holding all spawn tables of the game.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpawnTables {
+    /// The table for the monsters of a room.
+    pub monsters: SpawnTable,
+
+    /// The table for the items of a room.
+    pub items: SpawnTable,
+
+    /// The table for the items in the chests of the treasure rooms.
+    pub treasure: SpawnTable,
+}
+
+impl SpawnTables {
+    /// Parses the spawn tables embedded into the binary.
+    ///
+    /// # Panics
+    /// * If the embedded definitions are malformed.
+    ///
+    pub fn load() -> Self {
+        serde_json::from_str(SPAWN_TABLES).expect("The embedded spawn tables are malformed!")
+    }
+}
+
+/// Registers the [SpawnTables] with the passed `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [SpawnTables] should be registered.
+///
+pub fn register(ecs: &mut World) {
+    ecs.insert(SpawnTables::load());
+}
+
+/// Default value of [SpawnEntry::min_depth].
+fn default_min_depth() -> i32 {
+    1
+}
+
+/// Default value of [SpawnEntry::group].
+fn default_group() -> String {
+    "1".to_string()
+}
@@ -1,12 +1,38 @@
 //! Game state handling module.
 
-use rltk::{GameState, Rltk};
+use rltk::{console, ColorPair, DrawBatch, GameState, Point, Rltk};
 use specs::prelude::*;
 
+use super::ammunition;
+use super::camera::Camera;
+use super::crash;
+use super::daily::{self, DailyRun};
+use super::debug_console;
+use super::equipment;
+use super::exceptions::GameError;
+use super::frame_limiter::FrameLimiter;
+use super::identification;
+use super::localization;
+use super::particles;
+use super::quests::{self, Deed, QuestLog};
+use super::rng;
+use super::saveload;
+use super::settings::{self, Settings};
+use super::shop;
+use super::touch::{self, TouchGesture};
+use super::ui_controller::{
+    DebugConsoleResult, GameOverSelection, InventoryResult, InventoryView, KeyBindingsResult,
+    KeyBindingsView, LogHistoryResult, LogHistoryView, MainMenuResult, MainMenuSelection,
+    MenuResult, OptionsMenuSelection, QuestJournalResult, ShopResult, ShopView,
+};
+use super::wizard;
 use super::{
-    player_handle_input, ui_controller, DamageSystem, DialogInterface, DialogResult, FOVSystem,
-    ItemCollectionSystem, ItemDropSystem, Map, MapDexSystem, MeleeCombatSystem, MonsterAI,
-    Position, PotionDrinkSystem, Renderable,
+    build_dispatcher, config, entity_factory, map_builders, player_handle_input, publish_event,
+    submit_batch, swatch, ui_controller, AreaOfEffect, CastSpell, DamageSystem, DialogInterface,
+    DialogResult, Equipped, Facing, GameLog, Invisible, Item, LevelStatistics, LogCategory, Loot,
+    Map, Noises, OutOfDepth, PlayerPathing, PlayerResting, PlayerRunning, Position, Ranged,
+    RangedAttackEvent, RangedWeapon, Renderable, RunStatistics, Spellbook, StatusEffects,
+    SystemDispatcher, TurnCounter, TurnQueue, FOV,
 };
 
 /// Struct describing the current state of the game
@@ -16,69 +42,134 @@ pub struct State {
     /// Provides access to resource container [World],
     /// that makes up the `ECS`.
     pub ecs: World,
-}
 
-impl State {
-    /// Execute the systems of the game.
-    fn run_systems(&mut self) {
-        let mut fov_system = FOVSystem {};
-        fov_system.run_now(&self.ecs);
+    /// Executes the systems of the game
+    /// on the `ecs`.
+    dispatcher: SystemDispatcher,
 
-        let mut monster_ai = MonsterAI {};
-        monster_ai.run_now(&self.ecs);
+    /// Decides whether or not a frame
+    /// needs to be rendered.
+    frame_limiter: FrameLimiter,
 
-        let mut map_dex = MapDexSystem {};
-        map_dex.run_now(&self.ecs);
+    /// The touch gesture completed in the current frame.
+    touch_gesture: Option<TouchGesture>,
 
-        let mut melee_combat_system = MeleeCombatSystem {};
-        melee_combat_system.run_now(&self.ecs);
+    /// Flag indicating whether or not the screen changed since
+    /// the last rendered frame, e.g. because systems have run or
+    /// the [RunState] changed.
+    render_dirty: bool,
+}
 
-        let mut damage_system = DamageSystem {};
-        damage_system.run_now(&self.ecs);
+impl State {
+    /// Creates a new [State] with an empty `ecs` and builds the
+    /// [SystemDispatcher] for the game's systems, which is set up
+    /// with the `ecs` right away.
+    pub fn new() -> Self {
+        let mut ecs = World::new();
+        let mut dispatcher = build_dispatcher(&mut ecs);
 
-        let mut item_collection_system = ItemCollectionSystem {};
-        item_collection_system.run_now(&self.ecs);
+        dispatcher.setup(&mut ecs);
 
-        let mut potion_drink_system = PotionDrinkSystem {};
-        potion_drink_system.run_now(&self.ecs);
+        State {
+            ecs,
+            dispatcher,
+            frame_limiter: FrameLimiter::new(),
+            touch_gesture: None,
+            render_dirty: true,
+        }
+    }
 
-        let mut item_drop_system = ItemDropSystem {};
-        item_drop_system.run_now(&self.ecs);
+    /// Returns the touch gesture completed in the current frame.
+    /// Taps are applied to the mouse of the [Rltk] context as well.
+    pub fn touch_gesture(&self) -> Option<TouchGesture> {
+        self.touch_gesture
+    }
 
+    /// Execute the systems of the game.
+    ///
+    /// # Notes
+    /// * Only called when a turn advances or the world has to be
+    ///   refreshed through [RunState::Ticking]. Frames, that only
+    ///   render the game, never execute the systems.
+    ///
+    fn run_systems(&mut self) {
+        self.dispatcher.dispatch(&self.ecs);
         self.ecs.maintain();
     }
 
-    /// Returns the current [ProcessingState] of the
+    /// Returns `true` if the game does nothing in the passed
+    /// `run_state` but waiting for the player's input.
+    ///
+    /// # Arguments
+    /// * `run_state`: The current [RunState] of the game.
+    ///
+    /// # Notes
+    /// * The player walks a `click-to-move` path, rests and runs
+    ///   without further input in [RunState::AwaitingInput].
+    ///
+    fn is_waiting_for_input(&self, run_state: RunState) -> bool {
+        match run_state {
+            RunState::AwaitingInput => {
+                self.ecs
+                    .try_fetch::<PlayerPathing>()
+                    .is_none_or(|pathing| pathing.is_empty())
+                    && self
+                        .ecs
+                        .try_fetch::<PlayerResting>()
+                        .is_none_or(|resting| !resting.is_active())
+                    && self
+                        .ecs
+                        .try_fetch::<PlayerRunning>()
+                        .is_none_or(|running| running.direction().is_none())
+            }
+            RunState::MainMenu { .. }
+            | RunState::OptionsMenu { .. }
+            | RunState::KeyBindings { .. }
+            | RunState::CharacterCreation
+            | RunState::Dialog
+            | RunState::ShowInventory { .. }
+            | RunState::LogHistory { .. }
+            | RunState::QuestJournal
+            | RunState::Shop { .. }
+            | RunState::Targeting { .. }
+            | RunState::SpellTargeting { .. }
+            | RunState::FireTargeting { .. }
+            | RunState::WizardTeleport
+            | RunState::DebugConsole
+            | RunState::GameOver => true,
+            RunState::Ticking
+            | RunState::PlayerTurn
+            | RunState::MonsterTurn
+            | RunState::NextLevel
+            | RunState::Wizard
+            | RunState::SaveGame
+            | RunState::LoadGame => false,
+        }
+    }
+
+    /// Returns the current [RunState] of the
     /// system
     ///
     /// # Note
     /// * If a [DialogInterface] has been registered,
-    /// the function always returns [ProcessingState::Dialog].
-    fn get_processing_state(&self) -> ProcessingState {
-        let has_dialog = self.ecs.has_value::<DialogInterface>();
-
-        let next_processing_state: ProcessingState;
-        {
-            let current_processing_state = *self.ecs.fetch::<ProcessingState>();
-            next_processing_state = if has_dialog {
-                ProcessingState::Dialog
-            } else {
-                current_processing_state
-            }
+    ///   the function always returns [RunState::Dialog].
+    fn get_run_state(&self) -> RunState {
+        if self.ecs.has_value::<DialogInterface>() {
+            return RunState::Dialog;
         }
 
-        return next_processing_state;
+        *self.ecs.fetch::<RunState>()
     }
 
-    /// Updates the saved [ProcessingState] with the passed value,
+    /// Updates the saved [RunState] with the passed value,
     /// by writting it into the `ecs` resource.
     ///
     /// # Arguments
-    /// * `new_processing_state`: The new [ProcessingState] of the system.
+    /// * `new_run_state`: The new [RunState] of the system.
     ///
-    fn set_processing_state(&self, new_processing_state: &ProcessingState) {
-        let mut writter = self.ecs.write_resource::<ProcessingState>();
-        *writter = *new_processing_state;
+    fn set_run_state(&self, new_run_state: &RunState) {
+        let mut writter = self.ecs.write_resource::<RunState>();
+        *writter = *new_run_state;
     }
 
     /// Displays the ui of the game on the screen, this includes
@@ -88,9 +179,21 @@ impl State {
     /// * `ctx`: The context in which the ui should be drawn.
     ///
     fn show_ui(&self, ctx: &mut Rltk) {
-        // Fetch the map from the ecs and draw it
+        // Only the part of the map around the player is visible
+        let camera = Camera::new(&self.ecs);
+
+        // The debug toggles of the wizard mode reveal the whole level
+        let reveal_map = wizard::reveals_map(&self.ecs);
+        let show_all_entities = wizard::shows_all_entities(&self.ecs);
+
+        // Draw the map, the mutable borrow is released right away,
+        // since the particles and tooltips read the map as well
+        {
+            let mut map = self.ecs.fetch_mut::<Map>();
+            map.set_revealed(reveal_map);
+            map.draw(&camera);
+        }
         let map = self.ecs.fetch::<Map>();
-        map.draw(ctx);
 
         // Draw base ui
         ui_controller::draw_ui(&self.ecs, ctx);
@@ -100,27 +203,84 @@ impl State {
         let positions = self.ecs.read_storage::<Position>();
         let renderers = self.ecs.read_storage::<Renderable>();
 
+        // Invisible entities are hidden from the player
+        let player = *self.ecs.fetch::<Entity>();
+        let player_position = *self.ecs.fetch::<Point>();
+        let invisibles = self.ecs.read_storage::<Invisible>();
+        let status_effects = self.ecs.read_storage::<StatusEffects>();
+
+        let is_hidden = |entity: Entity, position: &Position| {
+            !show_all_entities
+                && Invisible::is_hidden(
+                    &invisibles,
+                    &status_effects,
+                    player,
+                    player_position,
+                    entity,
+                    position.to_point(),
+                )
+        };
+
+        // Entities out of the fov are only drawn, while all entities are shown
+        let is_visible = |tile: Point| show_all_entities || map.is_tile_in_fov(tile.x, tile.y);
+
         // Join get all renderables with a position and collect them in a vec for sorting
-        let mut entities = (&positions, &renderers).join().collect::<Vec<_>>();
+        let mut entities = (&self.ecs.entities(), &positions, &renderers)
+            .join()
+            .filter(|(entity, position, _)| !is_hidden(*entity, position))
+            .map(|(_, position, renderable)| (position, renderable))
+            .collect::<Vec<_>>();
 
         // Sort all tuples by the render order set in the renderable
         entities.sort_by(|&first, &second| second.1.order.cmp(&first.1.order));
 
+        let mut batch = DrawBatch::new();
+
+        // Subtly highlight the tile in front of every visible entity with a [Facing]
+        let facings = self.ecs.read_storage::<Facing>();
+
+        for (entity, position, facing) in (&self.ecs.entities(), &positions, &facings).join() {
+            let ahead = position.to_point() + facing.direction;
+
+            if is_hidden(entity, position)
+                || !map.check_idx(ahead.x, ahead.y)
+                || !is_visible(position.to_point())
+                || !is_visible(ahead)
+            {
+                continue;
+            }
+
+            if let Some(screen) = camera.to_screen(ahead) {
+                batch.set_bg(screen, swatch::FACING_INDICATOR);
+            }
+        }
+
         // Render entities
+
         for (position, renderable) in entities.iter() {
-            if map.is_tile_in_fov(position.x, position.y) {
-                ctx.set(
-                    position.x,
-                    position.y,
-                    renderable.fg,
-                    renderable.bg,
+            if !is_visible(position.to_point()) {
+                continue;
+            }
+
+            if let Some(screen) = camera.to_screen(position.to_point()) {
+                batch.set(
+                    screen,
+                    ColorPair::new(renderable.fg, renderable.bg),
                     renderable.symbol,
-                )
+                );
             }
         }
 
+        submit_batch(&mut batch, config::LAYER_ENTITIES);
+
+        // Draw the particles on top of the entities
+        particles::draw_particles(&self.ecs, &camera);
+
+        // Draw the performance overlay, if it's enabled
+        ui_controller::draw_performance_overlay(&self.ecs);
+
         // Draw the tooltip as the top most ui element. (Only dialogs are higer)
-        ui_controller::draw_tooltips(&self.ecs, ctx);
+        ui_controller::draw_tooltips(&self.ecs, ctx, &camera);
     }
 
     /// Fetches the currently saved dialog from the `ecs` and
@@ -136,92 +296,870 @@ impl State {
         let mut dialog = self.ecs.fetch_mut::<DialogInterface>();
         dialog.show(&self.ecs, ctx)
     }
-}
 
-impl GameState for State {
-    /// Gets called every frame of the game.
-    /// Used to  execute render logic, executes systems
-    /// and handle inputs.
+    /// Handles the [RunState]s displaying a full screen menu
+    /// instead of the game and returns the next [RunState].
     ///
     /// # Arguments
-    /// * `ctx`: The [Rltk] context of the `ecs`.
+    /// * `run_state`: The current [RunState] of the game.
+    /// * `ctx`: The [Rltk] context in which the menu should be drawn.
     ///
-    fn tick(&mut self, ctx: &mut Rltk) {
-        // Clear screen
-        ctx.cls();
+    fn tick_menu(&mut self, run_state: RunState, ctx: &mut Rltk) -> RunState {
+        match run_state {
+            RunState::MainMenu { selection } => {
+                match ui_controller::draw_main_menu(&self.ecs, ctx, selection) {
+                    MainMenuResult::NoSelection { selected } => RunState::MainMenu {
+                        selection: selected,
+                    },
+                    MainMenuResult::Selected { selected } => match selected {
+                        MainMenuSelection::NewGame => {
+                            self.new_game(false);
+                            RunState::CharacterCreation
+                        }
+                        MainMenuSelection::DailyChallenge => {
+                            self.new_game(true);
+                            RunState::CharacterCreation
+                        }
+                        MainMenuSelection::LoadGame => self.load_game_from_menu(run_state),
+                        MainMenuSelection::Options => RunState::OptionsMenu {
+                            selection: OptionsMenuSelection::Language,
+                        },
+                        MainMenuSelection::Quit => {
+                            ctx.quit();
+                            run_state
+                        }
+                    },
+                }
+            }
+            RunState::OptionsMenu { selection } => {
+                match ui_controller::draw_options_menu(&self.ecs, ctx, selection) {
+                    MainMenuResult::NoSelection { selected } => RunState::OptionsMenu {
+                        selection: selected,
+                    },
+                    MainMenuResult::Selected { selected } => match selected {
+                        OptionsMenuSelection::Language => {
+                            localization::cycle_language();
+                            run_state
+                        }
+                        OptionsMenuSelection::ReducedMotion => {
+                            settings::toggle_reduced_motion(&self.ecs, ctx);
+                            run_state
+                        }
+                        OptionsMenuSelection::FovAlgorithm => {
+                            settings::cycle_fov_algorithm(&self.ecs);
+                            run_state
+                        }
+                        OptionsMenuSelection::KeyBindings => RunState::KeyBindings {
+                            view: KeyBindingsView::default(),
+                        },
+                        OptionsMenuSelection::Back => RunState::MainMenu {
+                            selection: MainMenuSelection::Options,
+                        },
+                    },
+                }
+            }
+            RunState::KeyBindings { view } => {
+                match ui_controller::draw_key_bindings(&self.ecs, ctx, view) {
+                    KeyBindingsResult::Browsing { view } => RunState::KeyBindings { view },
+                    KeyBindingsResult::Closed => RunState::OptionsMenu {
+                        selection: OptionsMenuSelection::KeyBindings,
+                    },
+                }
+            }
+            // Dialogs registered before a game has started, e.g. the
+            // report of broken assets, are shown on top of the menu
+            RunState::Dialog => {
+                if self.show_dialog(ctx) == DialogResult::Consumed {
+                    self.ecs.remove::<DialogInterface>();
+                }
 
-        let mut show_dialog = false;
+                *self.ecs.fetch::<RunState>()
+            }
+            RunState::CharacterCreation => {
+                match ui_controller::draw_character_creation(&self.ecs, ctx) {
+                    MenuResult::NoResponse => run_state,
+                    MenuResult::Cancel => RunState::MainMenu {
+                        selection: MainMenuSelection::NewGame,
+                    },
+                    MenuResult::Selected(_) => RunState::Ticking,
+                }
+            }
+            RunState::ShowInventory { view } => {
+                match ui_controller::draw_inventory(&self.ecs, ctx, view) {
+                    InventoryResult::Browsing { view } => RunState::ShowInventory { view },
+                    InventoryResult::Closed => RunState::AwaitingInput,
+                    InventoryResult::Use { item } => self.select_item(item, false),
+                    InventoryResult::Drop { item } => self.select_item(item, true),
+                    InventoryResult::Equip { item } => self.equip_item(item),
+                }
+            }
+            RunState::LogHistory { view } => {
+                match ui_controller::draw_log_history(&self.ecs, ctx, view) {
+                    LogHistoryResult::Browsing { view } => RunState::LogHistory { view },
+                    LogHistoryResult::Closed => RunState::AwaitingInput,
+                }
+            }
+            RunState::QuestJournal => match ui_controller::draw_quest_journal(&self.ecs, ctx) {
+                QuestJournalResult::Open => RunState::QuestJournal,
+                QuestJournalResult::Closed => RunState::AwaitingInput,
+            },
+            RunState::Shop { shopkeeper, view } => {
+                match ui_controller::draw_shop(&self.ecs, ctx, shopkeeper, view) {
+                    ShopResult::Browsing { view } => RunState::Shop { shopkeeper, view },
+                    ShopResult::Closed => RunState::AwaitingInput,
+                    ShopResult::Buy { item } => {
+                        shop::buy(&mut self.ecs, shopkeeper, item);
+                        run_state
+                    }
+                    ShopResult::Sell { item } => {
+                        shop::sell(&mut self.ecs, shopkeeper, item);
+                        run_state
+                    }
+                }
+            }
+            RunState::GameOver => match ui_controller::draw_game_over(&self.ecs, ctx) {
+                MenuResult::Selected(GameOverSelection::NewRun) => {
+                    self.new_game(false);
+                    RunState::CharacterCreation
+                }
+                MenuResult::Selected(GameOverSelection::Quit) => {
+                    ctx.quit();
+                    run_state
+                }
+                _ => run_state,
+            },
+            _ => run_state,
+        }
+    }
 
-        let mut next_processing_state = self.get_processing_state();
+    /// Handles the [RunState]s of the running game, renders the
+    /// game and returns the next [RunState].
+    ///
+    /// # Arguments
+    /// * `run_state`: The current [RunState] of the game.
+    /// * `ctx`: The [Rltk] context in which the game should be drawn.
+    ///
+    fn tick_game(&mut self, run_state: RunState, ctx: &mut Rltk) -> RunState {
+        let mut next_run_state = run_state;
 
-        match next_processing_state {
-            ProcessingState::Dialog => {
+        match run_state {
+            RunState::Ticking => {
                 self.run_systems();
-                self.ecs.maintain();
-                show_dialog = true;
+                crash::update_snapshot(&mut self.ecs);
+                next_run_state = RunState::AwaitingInput;
             }
-            ProcessingState::Internal => {
-                self.run_systems();
-                self.ecs.maintain();
-                next_processing_state = ProcessingState::WaitingForInput;
-            }
-            ProcessingState::WaitingForInput => {
-                next_processing_state = player_handle_input(self, ctx);
+            RunState::AwaitingInput => {
+                next_run_state = player_handle_input(self, ctx);
             }
-            ProcessingState::PlayerTurn => {
+            RunState::PlayerTurn => {
                 self.run_systems();
-                self.ecs.maintain();
-                next_processing_state = ProcessingState::MonsterTurn;
+                let player = *self.ecs.fetch::<Entity>();
+                self.ecs.write_resource::<TurnQueue>().end_turn(player);
+                next_run_state = RunState::MonsterTurn;
             }
-            ProcessingState::MonsterTurn => {
+            RunState::MonsterTurn => {
                 self.run_systems();
-                self.ecs.maintain();
-                next_processing_state = ProcessingState::Internal;
+                let turn = self.ecs.write_resource::<TurnCounter>().advance().turn;
+                self.ecs.write_resource::<GameLog>().set_turn(turn);
+                crash::update_snapshot(&mut self.ecs);
+                next_run_state = RunState::AwaitingInput;
+            }
+            RunState::NextLevel => {
+                self.goto_next_level();
+                next_run_state = RunState::Ticking;
+            }
+            RunState::Wizard => {
+                next_run_state = wizard::execute(&mut self.ecs);
             }
+            RunState::SaveGame => {
+                self.save_game();
+                next_run_state = RunState::AwaitingInput;
+            }
+            RunState::LoadGame => {
+                next_run_state = self.load_game();
+            }
+            _ => {}
         }
 
+        // An invincible player survives the damage of the turn
+        wizard::protect_player(&self.ecs);
+
         // Remove all dead/defeated entities from the `ecs`
-        DamageSystem::clean_up(&mut self.ecs);
+        let player_died = DamageSystem::clean_up(&mut self.ecs);
+
+        // Reward the quests completed in this frame
+        if !player_died {
+            quests::grant_rewards(&mut self.ecs);
+        }
+
+        // Age the particles by the duration of the frame
+        particles::update_particles(&mut self.ecs, ctx.frame_time_ms);
 
         // Standard render process
         self.show_ui(ctx);
 
-        // If there is a dialog to display, show it and read the result
-        if show_dialog {
-            if self.show_dialog(ctx) == DialogResult::Consumed {
+        // Draw the overlays, which are displayed on top of the game
+        match run_state {
+            RunState::Dialog if self.show_dialog(ctx) == DialogResult::Consumed => {
                 self.ecs.remove::<DialogInterface>();
-                next_processing_state = ProcessingState::Internal;
+
+                // Dialog options can request a new run state through the `ecs`.
+                let requested_run_state = *self.ecs.fetch::<RunState>();
+                next_run_state = if requested_run_state == RunState::Dialog {
+                    RunState::AwaitingInput
+                } else {
+                    requested_run_state
+                };
+            }
+            RunState::Targeting {
+                range,
+                radius,
+                item,
+            } => {
+                next_run_state =
+                    match ui_controller::draw_player_ranged_targeting(&self.ecs, ctx, range, radius)
+                    {
+                        MenuResult::NoResponse => run_state,
+                        MenuResult::Cancel => RunState::AwaitingInput,
+                        MenuResult::Selected(target) => self.use_item(item, Some(target)),
+                    }
+            }
+            RunState::SpellTargeting {
+                range,
+                radius,
+                spell,
+            } => {
+                next_run_state =
+                    match ui_controller::draw_player_ranged_targeting(&self.ecs, ctx, range, radius)
+                    {
+                        MenuResult::NoResponse => run_state,
+                        MenuResult::Cancel => RunState::AwaitingInput,
+                        MenuResult::Selected(target) => self.cast_spell(spell, target),
+                    }
+            }
+            RunState::FireTargeting { range } => {
+                next_run_state =
+                    match ui_controller::draw_player_ranged_targeting(&self.ecs, ctx, range, None) {
+                        MenuResult::NoResponse => run_state,
+                        MenuResult::Cancel => RunState::AwaitingInput,
+                        MenuResult::Selected(target) => self.fire(target),
+                    }
+            }
+            RunState::WizardTeleport => {
+                next_run_state = match ui_controller::draw_wizard_teleport(&self.ecs, ctx) {
+                    MenuResult::NoResponse => run_state,
+                    MenuResult::Cancel => RunState::AwaitingInput,
+                    MenuResult::Selected(target) => wizard::teleport(&mut self.ecs, target),
+                }
+            }
+            RunState::DebugConsole => {
+                next_run_state = match ui_controller::draw_debug_console(&self.ecs, ctx) {
+                    DebugConsoleResult::Typing => run_state,
+                    DebugConsoleResult::Closed => RunState::AwaitingInput,
+                    DebugConsoleResult::Submitted => debug_console::submit(&mut self.ecs),
+                }
+            }
+            _ => {}
+        }
+
+        if player_died {
+            daily::record(&mut self.ecs);
+            return RunState::GameOver;
+        }
+
+        next_run_state
+    }
+
+    /// Handles the `item` the player has selected in the inventory
+    /// and returns the next [RunState]. If `drop` is `true` the `item`
+    /// is dropped, otherwise it's used. Using [Ranged] items requires
+    /// the player to select a target first, with a preview of the area
+    /// hit by items with an [AreaOfEffect].
+    ///
+    /// # Arguments
+    /// * `item`: The item [Entity] the player has selected.
+    /// * `drop`: Flag indicating whether or not the `item` should be dropped.
+    ///
+    fn select_item(&mut self, item: Entity, drop: bool) -> RunState {
+        let player = *self.ecs.fetch::<Entity>();
+
+        if drop {
+            // Only a single item of a stack is dropped
+            let item = entity_factory::split_stack(&mut self.ecs, item);
+            Item::drop_item(&self.ecs, &player, &item);
+            return RunState::PlayerTurn;
+        }
+
+        if let Some(ranged) = self.ecs.read_storage::<Ranged>().get(item) {
+            let radius = self
+                .ecs
+                .read_storage::<AreaOfEffect>()
+                .get(item)
+                .map(|area| area.radius);
+
+            return RunState::Targeting {
+                range: ranged.range,
+                radius,
+                item,
+            };
+        }
+
+        self.use_item(item, None)
+    }
+
+    /// Requests the player to put on or take off the `item`
+    /// and returns the next [RunState].
+    ///
+    /// # Arguments
+    /// * `item`: The item [Entity] selected in the inventory.
+    ///
+    fn equip_item(&mut self, item: Entity) -> RunState {
+        equipment::equip(&mut self.ecs, item)
+    }
+
+    /// Requests the usage of the `item` by the player, optionally on the
+    /// given `target`, and returns the next [RunState]. If the request fails,
+    /// the error is reported to the [GameLog] and the player keeps their turn.
+    ///
+    /// # Arguments
+    /// * `item`: The item [Entity] the player wants to use.
+    /// * `target`: The targeted [Point] for [Ranged] items, [None] otherwise.
+    ///
+    fn use_item(&mut self, item: Entity, target: Option<Point>) -> RunState {
+        let player = *self.ecs.fetch::<Entity>();
+
+        match Item::use_item(&self.ecs, &player, &item, target) {
+            Ok(_) => RunState::PlayerTurn,
+            Err(error) => {
+                self.ecs.write_resource::<GameLog>().push_error(&error);
+                RunState::AwaitingInput
+            }
+        }
+    }
+
+    /// Requests the player to cast the spell at the `spell` index of their
+    /// [Spellbook] on the `target` and returns the next [RunState]. If the
+    /// request fails, the error is reported to the [GameLog] and the player
+    /// keeps their turn.
+    ///
+    /// # Arguments
+    /// * `spell`: The index of the spell in the player's [Spellbook].
+    /// * `target`: The targeted [Point].
+    ///
+    fn cast_spell(&mut self, spell: usize, target: Point) -> RunState {
+        let player = *self.ecs.fetch::<Entity>();
+
+        let name = self
+            .ecs
+            .read_storage::<Spellbook>()
+            .get(player)
+            .and_then(|spellbook| spellbook.spells.get(spell).cloned());
+
+        let result = match name {
+            Some(name) => CastSpell::cast(&self.ecs, &player, &name, Some(target)),
+            None => return RunState::AwaitingInput,
+        };
+
+        match result {
+            Ok(_) => RunState::PlayerTurn,
+            Err(error) => {
+                self.ecs.write_resource::<GameLog>().push_error(&error);
+                RunState::AwaitingInput
+            }
+        }
+    }
+
+    /// Lets the player fire the wielded [RangedWeapon] at the `target`
+    /// and returns the next [RunState]. Weapons, that need ammunition,
+    /// take a single piece out of the player's quiver for the shot. If
+    /// the quiver has run empty in the meantime, the player keeps the turn.
+    ///
+    /// # Arguments
+    /// * `target`: The targeted [Point].
+    ///
+    fn fire(&mut self, target: Point) -> RunState {
+        let player = *self.ecs.fetch::<Entity>();
+
+        let kind = {
+            let equipped = self.ecs.read_storage::<Equipped>();
+            let weapons = self.ecs.read_storage::<RangedWeapon>();
+
+            match RangedWeapon::wielded(player, &equipped, &weapons) {
+                Some(weapon) => weapon.ammunition,
+                None => return RunState::AwaitingInput,
             }
+        };
+
+        let ammunition = match kind {
+            Some(kind) => match ammunition::nock(&mut self.ecs, player, kind) {
+                Some(piece) => Some(piece),
+                None => {
+                    self.ecs.write_resource::<GameLog>().push(
+                        &localization::tr("ranged.no_ammunition"),
+                        LogCategory::Combat,
+                    );
+                    return RunState::AwaitingInput;
+                }
+            },
+            None => None,
+        };
+
+        publish_event(
+            &self.ecs,
+            RangedAttackEvent {
+                attacker: player,
+                target,
+                ammunition,
+            },
+        );
+
+        RunState::PlayerTurn
+    }
+
+    /// Returns `true` if a game has been started or loaded,
+    /// `false` while the main menu is displayed after the launch.
+    fn is_game_running(&self) -> bool {
+        self.ecs.has_value::<Map>()
+    }
+
+    /// Sets up a new game in the `ecs`, replacing the running game, if
+    /// there is one. The town is generated and the player is created at
+    /// its start, so a new game can be started without relaunching.
+    /// The seed of the run is written to the [GameLog].
+    ///
+    /// # Arguments
+    /// * `daily`: Flag indicating whether or not the run is the daily
+    ///   challenge, which is seeded by the current date, see [daily].
+    ///
+    pub fn new_game(&mut self, daily: bool) {
+        self.ecs.delete_all();
+
+        // Every run starts with a new seed, unless one was passed on the command line.
+        // The daily challenge ignores it, so all players get the same dungeon.
+        let seed = if daily {
+            daily::start(&mut self.ecs)
+        } else {
+            self.ecs.remove::<DailyRun>();
+            rng::reseed(&mut self.ecs)
+        };
+
+        // The cheats of the previous run are turned off
+        wizard::reset(&mut self.ecs);
+
+        // The unidentified items get new appearances in every run
+        identification::register(&mut self.ecs);
+
+        // Create the town, the start of the map is kept free for the player
+        let map =
+            map_builders::random_builder(&mut self.ecs, config::TOWN_DEPTH).build(&mut self.ecs);
+
+        let player_position = map.player_start();
+        let player_entity = entity_factory::new_player(&player_position, &mut self.ecs);
+
+        self.ecs.insert(map);
+        self.ecs.insert(player_entity);
+        self.ecs.insert(player_position.to_point());
+        self.ecs.insert(GameLog::new());
+        self.ecs.insert(PlayerPathing::new());
+        self.ecs.insert(PlayerResting::default());
+        self.ecs.insert(PlayerRunning::default());
+        self.ecs.insert(TurnCounter::default());
+        self.ecs.insert(TurnQueue::default());
+        self.ecs.insert(Noises::default());
+        self.ecs.insert(RunStatistics::default());
+        self.ecs.insert(LevelStatistics::new(config::TOWN_DEPTH, 0));
+        self.ecs.insert(QuestLog::default());
+
+        let seed_message = match self.ecs.try_fetch::<DailyRun>() {
+            Some(daily_run) => localization::tr_with("log.daily_seed", &[&daily_run.date, &seed]),
+            None => localization::tr_with("log.seed", &[&seed]),
+        };
+
+        self.ecs
+            .write_resource::<GameLog>()
+            .messages_push(&seed_message);
+    }
+
+    /// Replaces the running game, if there is one, with the save game
+    /// chosen in the main menu and returns the next [RunState]. If the
+    /// save game can't be loaded, the main menu stays open.
+    ///
+    /// # Arguments
+    /// * `run_state`: The current [RunState] of the main menu.
+    ///
+    fn load_game_from_menu(&mut self, run_state: RunState) -> RunState {
+        if let Err(error) = saveload::load_game(&mut self.ecs) {
+            console::log(format!("Unable to load the save game: {}", error));
+            return run_state;
+        }
+
+        self.ecs
+            .write_resource::<GameLog>()
+            .messages_push(&localization::tr("log.loaded"));
+
+        RunState::Ticking
+    }
+
+    /// Saves the game and reports the outcome to the [GameLog].
+    fn save_game(&mut self) {
+        match saveload::save_game(&mut self.ecs) {
+            Ok(_) => self
+                .ecs
+                .write_resource::<GameLog>()
+                .messages_push(&localization::tr("log.saved")),
+            Err(error) => self.ecs.write_resource::<GameLog>().push_error(&error),
+        };
+    }
+
+    /// Replaces the running game with the save game and returns the
+    /// next [RunState]. If no save game is stored or it can't be loaded,
+    /// the player is informed through the [GameLog] and keeps playing.
+    ///
+    /// # Notes
+    /// * The world is ticked once after loading, so the
+    ///   caches of the loaded [Map] are filled again.
+    ///
+    fn load_game(&mut self) -> RunState {
+        if !saveload::has_save_game() {
+            self.ecs
+                .write_resource::<GameLog>()
+                .messages_push(&localization::tr("log.no_save"));
+            return RunState::AwaitingInput;
+        }
+
+        if let Err(error) = saveload::load_game(&mut self.ecs) {
+            self.ecs.write_resource::<GameLog>().push_error(&error);
+            return RunState::AwaitingInput;
+        }
+
+        self.ecs
+            .write_resource::<GameLog>()
+            .messages_push(&localization::tr("log.loaded"));
+
+        RunState::Ticking
+    }
+
+    /// Returns all entities, that have to be removed from the `ecs`
+    /// when the player leaves the current level. This includes all
+    /// entities except for the player and the items in its inventory.
+    fn entities_to_remove_on_level_change(&self) -> Vec<Entity> {
+        let entities = self.ecs.entities();
+        let player = self.ecs.fetch::<Entity>();
+        let backpack = self.ecs.read_storage::<Loot>();
+
+        entities
+            .join()
+            .filter(|entity| {
+                let is_player = *entity == *player;
+                let is_owned_by_player = backpack
+                    .get(*entity)
+                    .is_some_and(|loot| loot.owner == *player);
+
+                !is_player && !is_owned_by_player
+            })
+            .collect()
+    }
+
+    /// Replaces the current level with a newly generated level of
+    /// the next depth and places the player at its start.
+    fn goto_next_level(&mut self) {
+        // Remove all entities of the current level
+        let entities_to_remove = self.entities_to_remove_on_level_change();
+        if let Err(error) = self.ecs.delete_entities(&entities_to_remove) {
+            self.ecs
+                .write_resource::<GameLog>()
+                .push_error(&GameError::EntityDeletion {
+                    entity: error.entity,
+                });
+        }
+
+        // Generate and populate the next level
+        let (depth, previous_biome) = {
+            let map = self.ecs.fetch::<Map>();
+            (map.depth + 1, map.biome)
+        };
+        let map = map_builders::random_builder(&mut self.ecs, depth).build(&mut self.ecs);
+
+        let player_position = map.player_start();
+        let biome = map.biome;
+        self.ecs.insert(map);
+
+        // Place the player at the start of the level
+        let player = *self.ecs.fetch::<Entity>();
+        let mut positions = self.ecs.write_storage::<Position>();
+        let mut fovs = self.ecs.write_storage::<FOV>();
+
+        if let Some(position) = positions.get_mut(player) {
+            *position = player_position;
+        }
+
+        if let Some(fov) = fovs.get_mut(player) {
+            fov.mark_as_dirty();
+        }
+
+        *self.ecs.write_resource::<Point>() = player_position.to_point();
+        self.ecs.write_resource::<PlayerPathing>().clear();
+        self.ecs.write_resource::<PlayerResting>().stop();
+        self.ecs.write_resource::<PlayerRunning>().stop();
+
+        // Summarize the previous level and start the statistics of the new one
+        let turn = self.ecs.fetch::<TurnCounter>().turn;
+        let previous = std::mem::replace(
+            &mut *self.ecs.write_resource::<LevelStatistics>(),
+            LevelStatistics::new(depth, turn),
+        );
+
+        let mut game_log = self.ecs.write_resource::<GameLog>();
+        game_log.messages_push(&localization::tr_with(
+            "log.level_summary",
+            &[
+                &previous.depth,
+                &previous.turns_spent(turn),
+                &previous.kills,
+            ],
+        ));
+        game_log.messages_push(&localization::tr_with("log.descend", &[&depth]));
+
+        // Monsters out of depth make themselves felt right away
+        let out_of_depth = self.ecs.read_storage::<OutOfDepth>();
+
+        if (&out_of_depth).join().next().is_some() {
+            game_log.push(&localization::tr("log.out_of_depth"), LogCategory::General);
+        }
+
+        self.ecs
+            .write_resource::<QuestLog>()
+            .record(Deed::ReachDepth(depth));
+
+        if let Some(biome) = biome.filter(|biome| previous_biome != Some(*biome)) {
+            game_log.messages_push(&localization::tr_with("biome.enter", &[&biome.name()]));
+        }
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState for State {
+    /// Gets called every frame of the game.
+    /// Used to  execute render logic, executes systems
+    /// and handle inputs.
+    ///
+    /// # Arguments
+    /// * `ctx`: The [Rltk] context of the `ecs`.
+    ///
+    fn tick(&mut self, ctx: &mut Rltk) {
+        let run_state = self.get_run_state();
+
+        let fps_cap = self
+            .ecs
+            .try_fetch::<Settings>()
+            .map_or(config::DEFAULT_FPS_CAP, |settings| settings.fps_cap);
+
+        // Touch input is applied to the mouse before anything reads it
+        self.touch_gesture = touch::apply(ctx);
+
+        self.frame_limiter.update(ctx, fps_cap);
+        let has_input = self.frame_limiter.has_input() || self.touch_gesture.is_some();
+
+        // Nothing changes while the game only waits for input, so the
+        // previous frame is kept on screen. Particles fade over time
+        // though, so frames are rendered until they're gone
+        if !has_input
+            && !self.render_dirty
+            && self.is_waiting_for_input(run_state)
+            && !particles::has_particles(&self.ecs)
+        {
+            return;
+        }
+
+        // Clear screen
+        ctx.cls();
+
+        let next_run_state = match run_state {
+            RunState::MainMenu { .. }
+            | RunState::OptionsMenu { .. }
+            | RunState::KeyBindings { .. }
+            | RunState::CharacterCreation
+            | RunState::ShowInventory { .. }
+            | RunState::LogHistory { .. }
+            | RunState::QuestJournal
+            | RunState::Shop { .. }
+            | RunState::GameOver => self.tick_menu(run_state, ctx),
+            RunState::Dialog if !self.is_game_running() => self.tick_menu(run_state, ctx),
+            _ => self.tick_game(run_state, ctx),
+        };
+
+        // Render all batches submitted during the frame at once
+        if let Err(error) = rltk::render_draw_buffer(ctx) {
+            console::log(format!("Unable to render the draw buffer: {:?}", error));
         }
 
-        // Update the processing state
-        self.set_processing_state(&next_processing_state);
+        // The next frame only has to be rendered, if the game moves on
+        // on its own, i.e. the run state changed or systems will run
+        self.render_dirty =
+            next_run_state != run_state || !self.is_waiting_for_input(next_run_state);
+
+        // Update the run state
+        self.set_run_state(&next_run_state);
     }
 }
 
-/// Enum describing all processing states
+/// Enum describing all states
 /// the game can be in during execution.
 #[derive(PartialEq, Copy, Clone)]
-pub enum ProcessingState {
-    /// Executes all internal systems
-    /// and functions before the game
-    /// passes control to the player.
-    Internal,
+pub enum RunState {
+    /// The main menu is displayed
+    /// and the player selects how
+    /// to proceed.
+    MainMenu {
+        /// The highlighted option of
+        /// the main menu.
+        selection: MainMenuSelection,
+    },
 
-    /// The system is displaying a dialog
-    /// andis waiting for a key press on
-    /// the current dialog.
-    Dialog,
+    /// The options menu is displayed
+    /// and the player changes the
+    /// settings.
+    OptionsMenu {
+        /// The highlighted option of
+        /// the options menu.
+        selection: OptionsMenuSelection,
+    },
+
+    /// The key bindings screen is
+    /// displayed and the player
+    /// rebinds the keys.
+    KeyBindings {
+        /// The state of the key
+        /// bindings screen.
+        view: KeyBindingsView,
+    },
+
+    /// The player creates the character
+    /// before entering the dungeon.
+    CharacterCreation,
+
+    /// Executes all systems once to bring
+    /// the world up to date after it changed
+    /// outside of a turn, e.g. on a new level,
+    /// before the game passes control to the
+    /// player.
+    Ticking,
 
     /// The game is waiting for player
-    /// input.
-    WaitingForInput,
+    /// input. Only renders the game,
+    /// no systems are executed.
+    AwaitingInput,
 
     /// Executes the action
     /// input by the player.
     PlayerTurn,
 
     /// Executes the monsters
-    /// actions.
+    /// actions and ends the turn.
     MonsterTurn,
+
+    /// The system is displaying a dialog
+    /// and is waiting for a key press on
+    /// the current dialog.
+    Dialog,
+
+    /// The inventory screen of the
+    /// player is displayed.
+    ShowInventory {
+        /// The state of the inventory
+        /// screen.
+        view: InventoryView,
+    },
+
+    /// The history of all messages
+    /// of the log is displayed.
+    LogHistory {
+        /// The state of the message
+        /// log history screen.
+        view: LogHistoryView,
+    },
+
+    /// The quests the player has
+    /// taken are displayed.
+    QuestJournal,
+
+    /// The player trades with a shopkeeper.
+    Shop {
+        /// The [Shopkeeper](super::Shopkeeper)
+        /// the player trades with.
+        shopkeeper: Entity,
+
+        /// The state of the shop screen.
+        view: ShopView,
+    },
+
+    /// The player selects the target
+    /// for a [Ranged] item.
+    Targeting {
+        /// The maximum distance of the target.
+        range: i32,
+
+        /// The radius of the area hit around the
+        /// target, if the item has an [AreaOfEffect].
+        radius: Option<i32>,
+
+        /// The item that is used on the target.
+        item: Entity,
+    },
+
+    /// The player selects the target
+    /// for a spell with a range.
+    SpellTargeting {
+        /// The maximum distance of the target.
+        range: i32,
+
+        /// The radius of the area hit around
+        /// the target, if the spell has one.
+        radius: Option<i32>,
+
+        /// The index of the spell in the
+        /// player's [Spellbook].
+        spell: usize,
+    },
+
+    /// The player aims the wielded
+    /// [RangedWeapon] at a target.
+    FireTargeting {
+        /// The range of the weapon.
+        range: i32,
+    },
+
+    /// The player descends to the next
+    /// level of the dungeon.
+    NextLevel,
+
+    /// The command requested through the
+    /// wizard dialog is executed, see
+    /// [WizardCommand](super::wizard::WizardCommand).
+    Wizard,
+
+    /// The player picks the tile to
+    /// teleport to in the wizard mode.
+    WizardTeleport,
+
+    /// The debug console of the wizard
+    /// mode is displayed and the player
+    /// types a command.
+    DebugConsole,
+
+    /// The player has died and the game
+    /// over screen is displayed.
+    GameOver,
+
+    /// The game is saved.
+    SaveGame,
+
+    /// The running game is replaced
+    /// with the saved game.
+    LoadGame,
 }
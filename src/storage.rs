@@ -0,0 +1,152 @@
+//! Module for persisting game data between sessions.
+//!
+//! The data is stored as plain text under a `key`. On native targets
+//! every `key` maps to a file inside the [config::SAVE_DIRECTORY], while
+//! the web assembly build routes all calls to the browser's `localStorage`,
+//! since the `wasm32` target has no access to the file system.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, path::PathBuf};
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::config;
+use super::exceptions::{GameError, GameResult};
+
+/// Writes the passed `data` into the storage under the given `key`.
+/// Existing data under the same `key` is overwritten.
+///
+/// # Arguments
+/// * `key`: The key under which the `data` should be stored.
+/// * `data`: The data to store.
+///
+/// # Errors
+/// * If the storage is not accessible or the `data` couldn't be written.
+///
+pub fn write(key: &str, data: &str) -> GameResult<()> {
+    backend::write(key, data)
+}
+
+/// Reads the data stored under the passed `key` and returns it.
+/// If nothing has been stored for the `key` yet, [None] is returned.
+///
+/// # Arguments
+/// * `key`: The key of the data to read.
+///
+/// # Errors
+/// * If the storage is not accessible or the data couldn't be read.
+///
+pub fn read(key: &str) -> GameResult<Option<String>> {
+    backend::read(key)
+}
+
+/// Returns `true` if data is stored under the passed `key`,
+/// `false` otherwise.
+///
+/// # Arguments
+/// * `key`: The key to check.
+///
+pub fn exists(key: &str) -> bool {
+    matches!(read(key), Ok(Some(_)))
+}
+
+/// Returns a human readable description of the location at which
+/// the data for the passed `key` is stored, e.g. to display it to
+/// the player.
+///
+/// # Arguments
+/// * `key`: The key of the data.
+///
+pub fn location(key: &str) -> String {
+    backend::location(key)
+}
+
+/// File system backend used by all native targets.
+#[cfg(not(target_arch = "wasm32"))]
+mod backend {
+    use super::*;
+
+    /// Maps the passed `key` to the path of its file in the
+    /// [config::SAVE_DIRECTORY].
+    fn path(key: &str) -> PathBuf {
+        PathBuf::from(config::SAVE_DIRECTORY).join(key)
+    }
+
+    pub fn write(key: &str, data: &str) -> GameResult<()> {
+        fs::create_dir_all(config::SAVE_DIRECTORY)
+            .and_then(|_| fs::write(path(key), data))
+            .map_err(|err| GameError::SaveIO {
+                message: format!("Unable to write '{}': {}", location(key), err),
+            })
+    }
+
+    pub fn read(key: &str) -> GameResult<Option<String>> {
+        let path = path(key);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        fs::read_to_string(path)
+            .map(Some)
+            .map_err(|err| GameError::SaveIO {
+                message: format!("Unable to read '{}': {}", location(key), err),
+            })
+    }
+
+    pub fn location(key: &str) -> String {
+        path(key).display().to_string()
+    }
+}
+
+/// Browser `localStorage` backend used by the web assembly build.
+#[cfg(target_arch = "wasm32")]
+mod backend {
+    use web_sys::Storage;
+
+    use super::{GameError, GameResult};
+
+    /// Prefix for all keys written to the `localStorage`, so the
+    /// game's entries don't collide with other data of the domain.
+    const KEY_PREFIX: &str = "b_ruge.";
+
+    /// Creates a [GameError::SaveIO] with the passed `message`.
+    fn save_io_error(message: &str) -> GameError {
+        GameError::SaveIO {
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns the `localStorage` of the current browser window.
+    fn local_storage() -> GameResult<Storage> {
+        web_sys::window()
+            .ok_or_else(|| save_io_error("No browser window available!"))?
+            .local_storage()
+            .map_err(|_| save_io_error("Access to the local storage was denied!"))?
+            .ok_or_else(|| save_io_error("The browser does not provide a local storage!"))
+    }
+
+    /// Maps the passed `key` to the key used in the `localStorage`.
+    fn prefixed(key: &str) -> String {
+        format!("{}{}", KEY_PREFIX, key)
+    }
+
+    pub fn write(key: &str, data: &str) -> GameResult<()> {
+        local_storage()?
+            .set_item(&prefixed(key), data)
+            .map_err(|_| GameError::SaveIO {
+                message: format!("Unable to write '{}', the storage might be full!", key),
+            })
+    }
+
+    pub fn read(key: &str) -> GameResult<Option<String>> {
+        local_storage()?
+            .get_item(&prefixed(key))
+            .map_err(|_| GameError::SaveIO {
+                message: format!("Unable to read '{}' from the local storage!", key),
+            })
+    }
+
+    pub fn location(key: &str) -> String {
+        format!("localStorage['{}']", prefixed(key))
+    }
+}
@@ -31,36 +31,136 @@ impl Pallet {
 /// The player entity's color.
 pub const PLAYER: Pallet = Pallet(rltk::GOLD, DEFAULT_BG_COLOR);
 
-/// The goblin entity's color.
-pub const GOBLIN: Pallet = Pallet((169, 169, 169), DEFAULT_BG_COLOR);
-
-/// The gremlin entity's color.
-pub const GREMLIN: Pallet = Pallet((124, 252, 0), DEFAULT_BG_COLOR);
-
 /// The floor tile's color.
 pub const FLOOR: Pallet = Pallet((141, 163, 153), DEFAULT_BG_COLOR);
 
 /// The wall tile's color.
 pub const WALL: Pallet = Pallet(rltk::GOLDENROD, DEFAULT_BG_COLOR);
 
+/// The floor tile's color in the crypt.
+pub const CRYPT_FLOOR: Pallet = Pallet((128, 128, 140), DEFAULT_BG_COLOR);
+
+/// The wall tile's color in the crypt.
+pub const CRYPT_WALL: Pallet = Pallet((169, 169, 190), DEFAULT_BG_COLOR);
+
+/// The floor tile's color in the caverns.
+pub const CAVERNS_FLOOR: Pallet = Pallet((150, 120, 90), DEFAULT_BG_COLOR);
+
+/// The wall tile's color in the caverns.
+pub const CAVERNS_WALL: Pallet = Pallet((139, 90, 43), DEFAULT_BG_COLOR);
+
+/// The floor tile's color in the fungal forest.
+pub const FUNGAL_FOREST_FLOOR: Pallet = Pallet((120, 160, 110), DEFAULT_BG_COLOR);
+
+/// The wall tile's color in the fungal forest.
+pub const FUNGAL_FOREST_WALL: Pallet = Pallet((186, 85, 211), DEFAULT_BG_COLOR);
+
+/// The stairs tile's color.
+pub const STAIRS_DOWN: Pallet = Pallet(rltk::CYAN, DEFAULT_BG_COLOR);
+
+/// The deep water tile's color.
+pub const DEEP_WATER: Pallet = Pallet((30, 90, 200), DEFAULT_BG_COLOR);
+
+/// The lava tile's color.
+pub const LAVA: Pallet = Pallet(rltk::ORANGE_RED, DEFAULT_BG_COLOR);
+
+/// The chasm tile's color.
+pub const CHASM: Pallet = Pallet((60, 60, 80), DEFAULT_BG_COLOR);
+
+/// The road tile's color.
+pub const ROAD: Pallet = Pallet((181, 166, 130), DEFAULT_BG_COLOR);
+
+/// The rubble tile's color.
+pub const RUBBLE: Pallet = Pallet((120, 110, 100), DEFAULT_BG_COLOR);
+
+/// The bridge tile's color.
+pub const BRIDGE: Pallet = Pallet((139, 69, 19), DEFAULT_BG_COLOR);
+
+/// The broken door tile's color.
+pub const BROKEN_DOOR: Pallet = Pallet((110, 75, 40), DEFAULT_BG_COLOR);
+
+/// The color of the flames on burning tiles.
+pub const FIRE: Pallet = Pallet(rltk::ORANGE, (90, 20, 0));
+
+/// The color of the unique monsters, which sets
+/// them apart from the monsters they're based on.
+pub const UNIQUE_MONSTER: Pallet = Pallet(rltk::MAGENTA, (60, 0, 40));
+
+/// The door entity's color.
+pub const DOOR: Pallet = Pallet((160, 110, 60), DEFAULT_BG_COLOR);
+
+/// The locked door entity's color.
+pub const LOCKED_DOOR: Pallet = Pallet((205, 170, 60), DEFAULT_BG_COLOR);
+
+/// The chest entity's color.
+pub const CHEST: Pallet = Pallet((184, 134, 11), DEFAULT_BG_COLOR);
+
 /// The color for the message box ui.
 pub const MESSAGE_BOX: Pallet = Pallet(rltk::WHITE, DEFAULT_BG_COLOR);
 
+/// The color of general entries in the message log.
+pub const LOG_GENERAL: Pallet = Pallet(rltk::WHITE, DEFAULT_BG_COLOR);
+
+/// The color of combat entries in the message log.
+pub const LOG_COMBAT: Pallet = Pallet(rltk::ORANGE, DEFAULT_BG_COLOR);
+
+/// The color of item entries in the message log.
+pub const LOG_ITEM: Pallet = Pallet(rltk::CYAN, DEFAULT_BG_COLOR);
+
+/// The color of error entries in the message log.
+pub const LOG_ERROR: Pallet = Pallet(rltk::RED, DEFAULT_BG_COLOR);
+
 /// The color for the player's health text on the ui.
 pub const PLAYER_HEALTH_TEXT: Pallet = Pallet(rltk::GOLD, DEFAULT_BG_COLOR);
 
 /// The color for the player's health bar.
 pub const PLAYER_HEALTH_BAR: Pallet = Pallet(rltk::RED, DEFAULT_BG_COLOR);
 
+/// The color for the player's health bar, while the player is poisoned.
+pub const PLAYER_HEALTH_BAR_POISONED: Pallet = Pallet(rltk::LIME_GREEN, DEFAULT_BG_COLOR);
+
+/// The color for the player's mana text.
+pub const PLAYER_MANA_TEXT: Pallet = Pallet(rltk::GOLD, DEFAULT_BG_COLOR);
+
+/// The color for the player's mana bar.
+pub const PLAYER_MANA_BAR: Pallet = Pallet(rltk::ROYAL_BLUE, DEFAULT_BG_COLOR);
+
+/// The color for the depth and turn in the status bar.
+pub const LEVEL_STATUS_TEXT: Pallet = Pallet(rltk::WHITE, DEFAULT_BG_COLOR);
+
+/// The color for the player's level and xp text.
+pub const PLAYER_EXPERIENCE_TEXT: Pallet = Pallet(rltk::GOLD, DEFAULT_BG_COLOR);
+
+/// The color for the player's xp bar.
+pub const PLAYER_EXPERIENCE_BAR: Pallet = Pallet(rltk::MEDIUM_PURPLE, DEFAULT_BG_COLOR);
+
+/// The color for the gold in the player's purse.
+pub const PLAYER_GOLD_TEXT: Pallet = Pallet(rltk::GOLD, DEFAULT_BG_COLOR);
+
+/// The color for the slash marks of attacks.
+pub const PARTICLE_SLASH: Pallet = Pallet(rltk::ORANGE_RED, DEFAULT_BG_COLOR);
+
+/// The color for the sparks of blocked attacks.
+pub const PARTICLE_BLOCK: Pallet = Pallet(rltk::LIGHT_STEEL_BLUE, DEFAULT_BG_COLOR);
+
+/// The color for the sparkles of healing effects.
+pub const PARTICLE_HEAL: Pallet = Pallet(rltk::LIME_GREEN, DEFAULT_BG_COLOR);
+
+/// The color for the shimmer of teleports.
+pub const PARTICLE_TELEPORT: Pallet = Pallet(rltk::LIGHT_CYAN, DEFAULT_BG_COLOR);
+
+/// The color for the bursts of explosions.
+pub const PARTICLE_BURST: Pallet = Pallet(rltk::ORANGE, rltk::DARK_RED);
+
+/// The color for the fire of area of effect explosions.
+pub const PARTICLE_EXPLOSION: Pallet = Pallet(rltk::YELLOW, rltk::ORANGE_RED);
+
 /// The color of the mouse cursor tile.
 pub const MOUSE_CURSOR: U8Color = rltk::GOLD;
 
 /// Color for the tooltips.
 pub const TOOLTIP: Pallet = Pallet(rltk::WHITE, rltk::GOLDENROD);
 
-/// Color pallet for the health potion item.
-pub const HEALTH_POTION: Pallet = Pallet(rltk::CRIMSON, DEFAULT_BG_COLOR);
-
 /// The color pallet for dialog titles.
 pub const DIALOG_TITLE: Pallet = Pallet(rltk::GOLD, DEFAULT_BG_COLOR);
 
@@ -72,3 +172,33 @@ pub const DIALOG_FRAME: Pallet = Pallet(rltk::WHITE, DEFAULT_BG_COLOR);
 
 /// Color pallet for dialog options.
 pub const DIALOG_OPTION: Pallet = Pallet(rltk::GOLDENROD, DEFAULT_BG_COLOR);
+
+/// Color pallet for titles on menu screens.
+pub const MENU_TITLE: Pallet = Pallet(rltk::GOLD, DEFAULT_BG_COLOR);
+
+/// Color pallet for options on menu screens.
+pub const MENU_OPTION: Pallet = Pallet(rltk::WHITE, DEFAULT_BG_COLOR);
+
+/// Color pallet for the highlighted option on menu screens.
+pub const MENU_OPTION_SELECTED: Pallet = Pallet(rltk::GOLDENROD, DEFAULT_BG_COLOR);
+
+/// Color pallet for options on menu screens, that can't be chosen.
+pub const MENU_OPTION_DISABLED: Pallet = Pallet(rltk::GRAY, DEFAULT_BG_COLOR);
+
+/// Background color of the tile a monster is facing.
+pub const FACING_INDICATOR: U8Color = (32, 32, 40);
+
+/// Colors of the touch buttons.
+pub const TOUCH_BUTTON: Pallet = Pallet(rltk::BLACK, rltk::GOLDENROD);
+
+/// Background color of the tiles that can be targeted.
+pub const TARGETING_RANGE: U8Color = rltk::DARK_SLATE;
+
+/// Background color of the mouse cursor on a valid target.
+pub const TARGETING_CURSOR: U8Color = rltk::CYAN;
+
+/// Background color of the tiles hit around the target.
+pub const TARGETING_AREA: U8Color = rltk::DARK_ORANGE;
+
+/// Background color of the mouse cursor on an invalid target.
+pub const TARGETING_CURSOR_INVALID: U8Color = rltk::RED;
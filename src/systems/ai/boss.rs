@@ -0,0 +1,42 @@
+//! Behavior of the boss of the dungeon.
+
+use super::{Action, BehaviorContext, BehaviorHandler};
+use crate::{config, Behavior};
+
+/// Handler of the [Behavior::Boss], which fights in two phases.
+///
+/// # Notes
+/// * In the first phase the boss guards its lair. It only
+///   chases the player, while the player is in sight.
+/// * Once its hp drop below [config::BOSS_ENRAGE_HP_FRACTION],
+///   the boss spends a turn to fly into a rage. In the second
+///   phase it hunts the player, even if the player is out of sight.
+/// * The boss never switches to another behavior, so it can't flee.
+///
+pub struct BossBehavior;
+
+impl BehaviorHandler for BossBehavior {
+    fn transition(&self, _context: &BehaviorContext) -> Option<Behavior> {
+        None
+    }
+
+    fn act(&self, context: &mut BehaviorContext) -> Action {
+        if context.ai.phase == 0 && context.hp_fraction < config::BOSS_ENRAGE_HP_FRACTION {
+            context.ai.phase = 1;
+            return Action::Enrage;
+        }
+
+        if context.ai.phase == 0 && !context.sees_player {
+            return Action::Wait;
+        }
+
+        if context.is_next_to_player() {
+            return Action::Attack;
+        }
+
+        match context.step_towards_player() {
+            Some((x, y)) => Action::Move(x, y),
+            None => Action::Wait,
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Behavior of monsters hunting the player.
+
+use super::{Action, BehaviorContext, BehaviorHandler};
+use crate::Behavior;
+
+/// Handler of the [Behavior::Chase], the monster walks
+/// towards the player and attacks once it's next to it.
+///
+/// # Notes
+/// * Once the player is out of sight of the monster and its
+///   pack, the monster returns to its default behavior.
+/// * Pack members head for different tiles next to the
+///   player, see [BehaviorContext::step_to_flank].
+/// * Monsters, that are strong enough, bash in the closed
+///   doors blocking their way, see [BehaviorContext::can_bash_doors].
+///
+pub struct ChaseBehavior;
+
+impl BehaviorHandler for ChaseBehavior {
+    fn transition(&self, context: &BehaviorContext) -> Option<Behavior> {
+        match context.alerted_behavior() {
+            None => Some(context.ai.default_behavior),
+            Some(Behavior::Chase) => None,
+            behavior => behavior,
+        }
+    }
+
+    fn act(&self, context: &mut BehaviorContext) -> Action {
+        if context.is_next_to_player() {
+            return Action::Attack;
+        }
+
+        let step = match context.ai.pack {
+            Some(_) => context
+                .step_to_flank()
+                .or_else(|| context.step_towards_player()),
+            None => context.step_towards_player(),
+        };
+
+        match step {
+            Some((x, y)) => Action::Move(x, y),
+            None if context.can_bash_doors => match context.step_through_terrain() {
+                Some((x, y)) => Action::Bash(x, y),
+                None => Action::Wait,
+            },
+            None => Action::Wait,
+        }
+    }
+}
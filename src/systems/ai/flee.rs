@@ -0,0 +1,56 @@
+//! Behavior of monsters, that have lost their morale.
+
+use rltk::BaseMap;
+
+use super::{Action, BehaviorContext, BehaviorHandler};
+use crate::Behavior;
+
+/// Handler of the [Behavior::Flee], the monster rolls downhill on the
+/// inverted distance map of the player, see [crate::pathing::flee_map],
+/// so it retreats towards the open parts of the map instead of running
+/// into dead ends.
+///
+/// # Notes
+/// * Cornered monsters wait for the player and fight
+///   back once the player is next to them.
+/// * The monster keeps fleeing, even if the player is out of
+///   sight, until its hp are above [crate::config::FLEE_HP_FRACTION].
+///
+pub struct FleeBehavior;
+
+impl BehaviorHandler for FleeBehavior {
+    fn transition(&self, context: &BehaviorContext) -> Option<Behavior> {
+        if context.is_demoralized() {
+            return None;
+        }
+
+        Some(
+            context
+                .alerted_behavior()
+                .unwrap_or(context.ai.default_behavior),
+        )
+    }
+
+    fn act(&self, context: &mut BehaviorContext) -> Action {
+        let map = context.map;
+        let monster_idx = map.coordinates_to_idx(context.position.x, context.position.y);
+        let flee_map = context.flee_map();
+
+        // Only step to free tiles, that are safer than the current one
+        let exit = map
+            .get_available_exits(monster_idx)
+            .into_iter()
+            .map(|(exit, _)| exit)
+            .filter(|exit| flee_map[*exit] < flee_map[monster_idx])
+            .min_by(|a, b| flee_map[*a].total_cmp(&flee_map[*b]));
+
+        match exit {
+            Some(exit) => {
+                let (x, y) = map.idx_to_coordinates(exit);
+                Action::Move(x, y)
+            }
+            None if context.is_next_to_player() => Action::Attack,
+            None => Action::Wait,
+        }
+    }
+}
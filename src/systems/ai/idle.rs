@@ -0,0 +1,18 @@
+//! Behavior of monsters waiting for the player.
+
+use super::{Action, BehaviorContext, BehaviorHandler};
+use crate::Behavior;
+
+/// Handler of the [Behavior::Idle], the monster
+/// stands still until it notices the player.
+pub struct IdleBehavior;
+
+impl BehaviorHandler for IdleBehavior {
+    fn transition(&self, context: &BehaviorContext) -> Option<Behavior> {
+        context.alerted_behavior()
+    }
+
+    fn act(&self, _context: &mut BehaviorContext) -> Action {
+        Action::Wait
+    }
+}
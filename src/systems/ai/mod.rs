@@ -0,0 +1,753 @@
+//! Systems controlling the behavior of the monsters.
+//!
+//! Every monster has an [AI] holding its current [Behavior]. In each
+//! monster turn the [BehaviorHandler] of the current behavior decides,
+//! whether the monster switches to another behavior, e.g. once it
+//! notices the player, and the handler of the resulting behavior picks
+//! the [Action] of the monster. New behaviors only have to implement
+//! [BehaviorHandler] and be returned by [handler].
+//!
+//! Monsters notice the player gradually, see [Awareness]. They see the
+//! player in their [FOV] and hear it within the player's noise radius,
+//! which is halved while the player is [Sneaking]. Only alert monsters
+//! hunt the player, suspicious ones turn towards it.
+//!
+//! Monsters spawned together form a pack, see [AI::pack]. Once one
+//! member of a pack is alerted, the whole pack is alerted, and
+//! its members spread out to different tiles around the player.
+//!
+//! Strong monsters bash in the closed doors blocking their way to the
+//! player, see [config::DOOR_BASH_MIN_POWER]. Monsters also hear the
+//! [Noises] made during the player's turn, e.g. by bashing a door.
+//!
+//! [Invisible] entities are only seen by monsters standing next to them,
+//! unless the monsters see invisible, see [Invisible::is_hidden].
+//!
+//! Monsters only hunt the player, if their [Faction] is hostile to
+//! the player's. While they aren't busy with the player, they fight
+//! and hunt the entities of hostile factions, see [FactionRelations].
+
+use std::collections::HashSet;
+
+use rltk::{a_star_search, Point, RandomNumberGenerator};
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::faction::FactionRelations;
+use crate::particles::ParticleBuilder;
+use crate::pathing::{self, a_star_search_bounded, TerrainView};
+use crate::{config, doors, localization};
+use crate::{
+    pythagoras_distance, Awareness, Behavior, Door, Facing, Faction, GameLog, Invisible,
+    LogCategory, Map, MeleeAttackEvent, Name, Noises, Position, RunState, Sneaking, Statistics,
+    StatusEffectKind, StatusEffects, SystemDispatcherBuilder, TurnQueue, AI, FOV,
+};
+
+mod idle;
+pub use idle::IdleBehavior;
+
+mod chase;
+pub use chase::ChaseBehavior;
+
+mod flee;
+pub use flee::FleeBehavior;
+
+mod patrol;
+pub use patrol::PatrolBehavior;
+
+mod boss;
+pub use boss::BossBehavior;
+
+mod wander;
+pub use wander::WanderBehavior;
+
+/// Registers the systems of the module with the passed `builder`
+/// and inserts the [Noises] heard by the monsters into the `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    ecs.insert(Noises::default());
+
+    builder.with(MonsterAI {}, "monster_ai", &["fov"])
+}
+
+/// The action a monster takes in its turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// The monster does nothing.
+    Wait,
+
+    /// The monster moves to the neighbouring tile
+    /// with the x and y coordinates.
+    Move(i32, i32),
+
+    /// The monster attacks the player.
+    Attack,
+
+    /// The monster attacks the entity on the neighbouring
+    /// tile with the x and y coordinates.
+    Fight(Entity, i32, i32),
+
+    /// The monster flies into a rage and gains
+    /// [config::BOSS_ENRAGE_POWER] attack power.
+    Enrage,
+
+    /// The monster bashes the closed door on the
+    /// neighbouring tile with the x and y coordinates.
+    Bash(i32, i32),
+}
+
+/// The logic of a single [Behavior].
+pub trait BehaviorHandler {
+    /// Returns the behavior the monster switches to at the
+    /// start of its turn, or `None` to keep the current one.
+    ///
+    /// # Arguments
+    /// * `context`: The [BehaviorContext] of the monster.
+    ///
+    fn transition(&self, context: &BehaviorContext) -> Option<Behavior>;
+
+    /// Returns the [Action] the monster takes in its turn.
+    ///
+    /// # Arguments
+    /// * `context`: The [BehaviorContext] of the monster.
+    ///
+    fn act(&self, context: &mut BehaviorContext) -> Action;
+}
+
+/// Returns the [BehaviorHandler] of the passed `behavior`.
+///
+/// # Arguments
+/// * `behavior`: The [Behavior] whose handler should be returned.
+///
+pub fn handler(behavior: Behavior) -> &'static dyn BehaviorHandler {
+    match behavior {
+        Behavior::Idle => &IdleBehavior,
+        Behavior::Chase => &ChaseBehavior,
+        Behavior::Flee => &FleeBehavior,
+        Behavior::Patrol => &PatrolBehavior,
+        Behavior::Boss => &BossBehavior,
+        Behavior::Wander => &WanderBehavior,
+    }
+}
+
+/// The state of a monster and its surroundings
+/// passed to the [BehaviorHandler]s.
+pub struct BehaviorContext<'a> {
+    /// The [AI] of the monster.
+    pub ai: &'a mut AI,
+
+    /// The position of the monster.
+    pub position: Point,
+
+    /// The position of the player.
+    pub player_position: Point,
+
+    /// Flag indicating whether or not the player is in the [FOV] of the monster.
+    pub sees_player: bool,
+
+    /// Flag indicating whether or not another member
+    /// of the monster's pack is alerted of the player.
+    pub pack_alerted: bool,
+
+    /// The fraction of its maximum hp the monster has left.
+    pub hp_fraction: f32,
+
+    /// Flag indicating whether or not the monster is strong enough to
+    /// bash doors, see [config::DOOR_BASH_MIN_POWER].
+    pub can_bash_doors: bool,
+
+    /// The map of the current level.
+    pub map: &'a Map,
+
+    /// The random number generator of the game.
+    pub rng: &'a mut RandomNumberGenerator,
+
+    /// The flee map of the player's position, shared by all
+    /// monsters of the turn, see [BehaviorContext::flee_map].
+    flee_map: &'a mut Option<Vec<f32>>,
+
+    /// The tiles next to the player, that pack members are already
+    /// heading to in this turn, see [BehaviorContext::step_to_flank].
+    flank_claims: &'a mut Vec<usize>,
+}
+
+impl BehaviorContext<'_> {
+    /// Returns `true` if the player is on a
+    /// neighbouring tile, `false` otherwise.
+    pub fn is_next_to_player(&self) -> bool {
+        pythagoras_distance(&self.position, &self.player_position) < 1.5
+    }
+
+    /// Returns `true` if the hp of the monster are below
+    /// [config::FLEE_HP_FRACTION] of its maximum hp, or below
+    /// [config::COWARDLY_FLEE_HP_FRACTION] for cowardly monsters.
+    pub fn is_demoralized(&self) -> bool {
+        let threshold = if self.ai.cowardly {
+            config::COWARDLY_FLEE_HP_FRACTION
+        } else {
+            config::FLEE_HP_FRACTION
+        };
+
+        self.hp_fraction < threshold
+    }
+
+    /// Returns the behavior of the monster after noticing the player, or
+    /// `None` if neither the monster nor its pack is alerted of the player.
+    pub fn alerted_behavior(&self) -> Option<Behavior> {
+        if self.ai.awareness != Awareness::Alert && !self.pack_alerted {
+            return None;
+        }
+
+        if self.is_demoralized() {
+            Some(Behavior::Flee)
+        } else {
+            Some(Behavior::Chase)
+        }
+    }
+
+    /// Returns the [pathing::flee_map] of the player's position, in which
+    /// lower values are safer. It's calculated once it's first needed
+    /// in the turn.
+    pub fn flee_map(&mut self) -> &[f32] {
+        let map = self.map;
+        let player_position = self.player_position;
+
+        self.flee_map.get_or_insert_with(|| {
+            let player_idx = map.coordinates_to_idx(player_position.x, player_position.y);
+            pathing::flee_map(map, player_idx)
+        })
+    }
+
+    /// Returns the coordinates of the next step on the path of the
+    /// monster to the player, or `None` if no path could be found.
+    pub fn step_towards_player(&self) -> Option<(i32, i32)> {
+        let monster_idx = self
+            .map
+            .coordinates_to_idx(self.position.x, self.position.y);
+        let player_idx = self
+            .map
+            .coordinates_to_idx(self.player_position.x, self.player_position.y);
+
+        let path = a_star_search(monster_idx, player_idx, self.map);
+
+        if path.success && path.steps.len() > 1 {
+            Some(self.map.idx_to_coordinates(path.steps[1]))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the coordinates of the next step on the path of the
+    /// monster to the player, that only considers the terrain, i.e.
+    /// leads through closed doors and other monsters. Returns `None`
+    /// if no path could be found.
+    pub fn step_through_terrain(&self) -> Option<(i32, i32)> {
+        let steps = a_star_search_bounded(
+            &TerrainView::new(self.map),
+            self.map
+                .coordinates_to_idx(self.position.x, self.position.y),
+            self.map
+                .coordinates_to_idx(self.player_position.x, self.player_position.y),
+            config::MAX_PATHING_EXPANSIONS,
+        )?;
+
+        steps.first().map(|idx| self.map.idx_to_coordinates(*idx))
+    }
+
+    /// Returns the coordinates of the next step on the path of the monster
+    /// to the closest free tile next to the player, that no other pack
+    /// member is heading to, and claims that tile for the rest of the turn.
+    /// Returns `None` if no such tile can be reached.
+    ///
+    /// # Notes
+    /// * Spreading out lets a pack surround the player,
+    ///   instead of queueing up in a corridor.
+    ///
+    pub fn step_to_flank(&mut self) -> Option<(i32, i32)> {
+        let map = self.map;
+        let monster_idx = map.coordinates_to_idx(self.position.x, self.position.y);
+
+        let mut candidates = Facing::DIRECTIONS
+            .iter()
+            .map(|direction| {
+                Point::new(
+                    self.player_position.x + direction.x,
+                    self.player_position.y + direction.y,
+                )
+            })
+            .filter(|tile| map.check_idx(tile.x, tile.y) && !map.is_tile_blocked(tile.x, tile.y))
+            .map(|tile| map.coordinates_to_idx(tile.x, tile.y))
+            .filter(|idx| !self.flank_claims.contains(idx))
+            .collect::<Vec<_>>();
+
+        let position = self.position;
+        candidates.sort_by(|a, b| {
+            let distance = |idx: usize| {
+                let (x, y) = map.idx_to_coordinates(idx);
+                pythagoras_distance(&position, &Point::new(x, y))
+            };
+
+            distance(*a).total_cmp(&distance(*b))
+        });
+
+        candidates.into_iter().find_map(|target| {
+            let steps =
+                a_star_search_bounded(map, monster_idx, target, config::MAX_PATHING_EXPANSIONS)?;
+            let step = steps.first()?;
+
+            self.flank_claims.push(target);
+            Some(map.idx_to_coordinates(*step))
+        })
+    }
+}
+
+/// Returns the [Awareness] of a monster after perceiving the player.
+///
+/// # Arguments
+/// * `awareness`: The current [Awareness] of the monster.
+/// * `sees`: Flag indicating whether or not the player is in the [FOV] of the monster.
+/// * `hears`: Flag indicating whether or not the monster is within the player's noise radius.
+/// * `is_sneaking`: Flag indicating whether or not the player is [Sneaking].
+///
+/// # Notes
+/// * Unaware monsters, that glimpse a sneaking player,
+///   only grow suspicious, noticing it takes another look.
+/// * Monsters, that neither see nor hear the player,
+///   calm down by one level every round.
+///
+fn perceive(awareness: Awareness, sees: bool, hears: bool, is_sneaking: bool) -> Awareness {
+    match (sees, hears) {
+        (true, _) if is_sneaking && awareness == Awareness::Unaware => Awareness::Suspicious,
+        (true, _) => Awareness::Alert,
+        (false, true) => awareness.max(Awareness::Suspicious),
+        (false, false) => match awareness {
+            Awareness::Alert => Awareness::Suspicious,
+            _ => Awareness::Unaware,
+        },
+    }
+}
+
+/// Base AI system for all monsters, that dispatches
+/// their turns to the handlers of their [Behavior].
+///
+/// # Notes
+/// * Monsters only hunt the player, once they or another member of
+///   their pack are alerted of it, see [perceive]. Monsters with
+///   a [Facing] turn in the direction they move or attack, so they
+///   can be approached from behind.
+/// * Suspicious monsters, that wait, turn towards the player.
+/// * Monsters hear the [Noises] of the player's turn, which
+///   are forgotten once all monsters have perceived them.
+/// * Monsters bashing a door roll the same check as the
+///   player, see [doors::bash_check].
+/// * Stunned monsters skip their turn, confused monsters
+///   stumble in a random direction instead of acting and
+///   attack any entity in their way.
+/// * The monsters act in the order of the [TurnQueue], entities
+///   without an [AI], [FOV] or [Position] are skipped.
+///
+pub struct MonsterAI {}
+
+impl<'a> System<'a> for MonsterAI {
+    type SystemData = (
+        // Read resources
+        Entities<'a>,                           // Get all entities
+        WriteExpect<'a, Map>,                   // Read the game map from the ecs
+        ReadExpect<'a, Point>,                  // Read the player position from the ecs
+        ReadExpect<'a, Entity>,                 // Read the player entity form the ecs
+        ReadExpect<'a, RunState>,               // Get the current run state of the game
+        WriteExpect<'a, RandomNumberGenerator>, // Roll the random decisions of the monsters
+        WriteExpect<'a, GameLog>,               // Report monsters turning to flee
+        Write<'a, ParticleBuilder>,             // Request the particles of enraged monsters
+        Write<'a, TurnQueue>,                   // Take the turns in the order of the round
+        Write<'a, Noises>,                      // Hear the noises of the player's turn
+        ReadExpect<'a, FactionRelations>,       // Decide whom the monsters attack
+        // Read storages
+        ReadStorage<'a, Name>,          // Get all name components
+        ReadStorage<'a, StatusEffects>, // Get all status effect components
+        ReadStorage<'a, Faction>,       // Get all faction components
+        ReadStorage<'a, Sneaking>,      // Halve the noise of a sneaking player
+        ReadStorage<'a, Door>,          // Find the doors the monsters bash
+        ReadStorage<'a, Invisible>,     // Hide invisible entities from the monsters
+        // Write storages
+        WriteStorage<'a, Statistics>, // Raise the power of enraged monsters
+        WriteStorage<'a, AI>,         // Get all ai components
+        WriteStorage<'a, FOV>,        // Get all fov components
+        WriteStorage<'a, Position>,   // Get all position components
+        WriteStorage<'a, Facing>,     // Get all facing components
+        // Write events
+        Write<'a, EventChannel<MeleeAttackEvent>>, // Publish melee attacks
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        // Get system data
+        let (
+            entities,
+            mut map,
+            player_position,
+            player_entity,
+            run_state,
+            mut rng,
+            mut game_log,
+            mut particle_builder,
+            mut turn_queue,
+            mut noises,
+            relations,
+            names,
+            status_effects,
+            factions,
+            sneaking,
+            doors,
+            invisibles,
+            mut statistics,
+            mut ais,
+            mut fovs,
+            mut positions,
+            mut facings,
+            mut melee_attack_events,
+        ) = data;
+
+        if *run_state != RunState::MonsterTurn {
+            return;
+        }
+
+        // The flee map of the player's position, calculated
+        // once the first monster needs to flee
+        let mut flee_map: Option<Vec<f32>> = None;
+
+        // Hostile monsters perceive the player through their fov and its noise
+        let is_sneaking = sneaking.contains(*player_entity);
+        let noise_radius = if is_sneaking {
+            config::PLAYER_NOISE_RADIUS / 2.0
+        } else {
+            config::PLAYER_NOISE_RADIUS
+        };
+
+        for (entity, ai, fov, position) in (&entities, &mut ais, &fovs, &positions).join() {
+            if !relations.is_hostile(&factions, entity, *player_entity) {
+                ai.awareness = Awareness::Unaware;
+                continue;
+            }
+
+            let point = position.to_point();
+            let sees = fov.content.contains(&*player_position)
+                && !Invisible::is_hidden(
+                    &invisibles,
+                    &status_effects,
+                    entity,
+                    point,
+                    *player_entity,
+                    *player_position,
+                );
+            let hears = pythagoras_distance(&point, &player_position) <= noise_radius
+                || noises.is_heard_at(&point);
+            let awareness = perceive(ai.awareness, sees, hears, is_sneaking);
+
+            // A sneaking player is told, when the monsters in its view catch on
+            let is_in_view = fovs
+                .get(*player_entity)
+                .is_some_and(|player_fov| player_fov.contains(&point));
+
+            if is_sneaking && is_in_view && awareness > ai.awareness {
+                let id = match awareness {
+                    Awareness::Alert => "stealth.alert",
+                    _ => "stealth.suspicious",
+                };
+
+                if let Some(name) = names.get(entity) {
+                    game_log.push(
+                        &localization::tr_with(id, &[&name.display()]),
+                        LogCategory::General,
+                    );
+                }
+            }
+
+            ai.awareness = awareness;
+        }
+
+        noises.clear();
+
+        // The packs with an alerted member alert all of their members
+        let alerted_packs = (&ais)
+            .join()
+            .filter(|ai| ai.awareness == Awareness::Alert)
+            .filter_map(|ai| ai.pack)
+            .collect::<HashSet<_>>();
+
+        for ai in (&mut ais).join() {
+            if ai.pack.is_some_and(|pack| alerted_packs.contains(&pack)) {
+                ai.awareness = Awareness::Alert;
+            }
+        }
+
+        let mut flank_claims: Vec<usize> = Vec::new();
+        let mut doors_broken = false;
+
+        // The positions of all entities, that can be fought, at the start of the turn
+        let combatants = (&entities, &positions, &statistics)
+            .join()
+            .map(|(entity, position, _)| (entity, position.to_point()))
+            .collect::<Vec<_>>();
+
+        // Let all monsters that have an ai and an fov act in the order of the round
+        while let Some(entity) = turn_queue.pop() {
+            let (ai, fov, position) = match (
+                ais.get_mut(entity),
+                fovs.get_mut(entity),
+                positions.get_mut(entity),
+            ) {
+                (Some(ai), Some(fov), Some(position)) => (ai, fov, position),
+                _ => continue,
+            };
+            let facing = facings.get_mut(entity);
+            let is_suspicious = ai.awareness == Awareness::Suspicious;
+
+            let effects = status_effects.get(entity);
+
+            // Stunned monsters skip their turn
+            if effects.is_some_and(|effects| effects.has(StatusEffectKind::Stun)) {
+                continue;
+            }
+
+            let action = if effects.is_some_and(|effects| effects.has(StatusEffectKind::Confusion))
+            {
+                // Confused monsters stumble to a random neighbouring tile
+                // and attack whomever they stumble into, even their allies
+                let direction = Facing::DIRECTIONS[rng.range(0, Facing::DIRECTIONS.len())];
+                let (x, y) = (position.x + direction.x, position.y + direction.y);
+
+                if !map.check_idx(x, y) {
+                    Action::Wait
+                } else if let Some(other) = map
+                    .tile_contents_get(x, y)
+                    .find(|other| statistics.contains(*other))
+                {
+                    Action::Fight(other, x, y)
+                } else if !map.is_tile_blocked(x, y) {
+                    Action::Move(x, y)
+                } else {
+                    Action::Wait
+                }
+            } else {
+                // Monsters, that aren't hostile to the player, ignore it
+                let hostile_to_player = relations.is_hostile(&factions, entity, *player_entity);
+                let pack_alerted =
+                    hostile_to_player && ai.pack.is_some_and(|pack| alerted_packs.contains(&pack));
+                let is_hidden = |other: Entity, point: Point| {
+                    Invisible::is_hidden(
+                        &invisibles,
+                        &status_effects,
+                        entity,
+                        position.to_point(),
+                        other,
+                        point,
+                    )
+                };
+
+                let mut context = BehaviorContext {
+                    ai,
+                    position: position.to_point(),
+                    player_position: *player_position,
+                    sees_player: hostile_to_player
+                        && fov.content.contains(&*player_position)
+                        && !is_hidden(*player_entity, *player_position),
+                    pack_alerted,
+                    hp_fraction: statistics.get(entity).map_or(1.0, |statistic| {
+                        statistic.hp as f32 / statistic.hp_max.max(1) as f32
+                    }),
+                    can_bash_doors: statistics
+                        .get(entity)
+                        .is_some_and(|statistic| statistic.power >= config::DOOR_BASH_MIN_POWER),
+                    map: &map,
+                    rng: &mut rng,
+                    flee_map: &mut flee_map,
+                    flank_claims: &mut flank_claims,
+                };
+
+                if let Some(behavior) = handler(context.ai.behavior).transition(&context) {
+                    if behavior == Behavior::Flee {
+                        if let Some(name) = names.get(entity) {
+                            game_log.push(
+                                &localization::tr_with("combat.flee", &[&name.display()]),
+                                LogCategory::Combat,
+                            );
+                        }
+                    }
+
+                    context.ai.behavior = behavior;
+                }
+
+                let action = handler(context.ai.behavior).act(&mut context);
+                let is_fleeing = context.ai.behavior == Behavior::Flee;
+
+                // Monsters, that aren't busy with the player or fleeing, fight
+                // hostile neighbours or hunt the closest hostile entity in sight
+                match action {
+                    Action::Wait | Action::Move(_, _) if !is_fleeing => {
+                        let origin = position.to_point();
+                        let is_foe = |other: Entity| {
+                            other != entity
+                                && other != *player_entity
+                                && relations.is_hostile(&factions, entity, other)
+                        };
+
+                        let neighbour = Facing::DIRECTIONS
+                            .iter()
+                            .map(|direction| (origin.x + direction.x, origin.y + direction.y))
+                            .filter(|&(x, y)| map.check_idx(x, y))
+                            .flat_map(|(x, y)| {
+                                map.tile_contents_get(x, y).map(move |other| (other, x, y))
+                            })
+                            .find(|&(other, _, _)| statistics.contains(other) && is_foe(other));
+
+                        match neighbour {
+                            Some((other, x, y)) => Action::Fight(other, x, y),
+                            None => combatants
+                                .iter()
+                                .filter(|(other, point)| {
+                                    is_foe(*other)
+                                        && fov.content.contains(point)
+                                        && !is_hidden(*other, *point)
+                                })
+                                .min_by(|(_, a), (_, b)| {
+                                    pythagoras_distance(&origin, a)
+                                        .total_cmp(&pythagoras_distance(&origin, b))
+                                })
+                                .and_then(|(_, target)| {
+                                    a_star_search_bounded(
+                                        &TerrainView::new(&map),
+                                        map.coordinates_to_idx(origin.x, origin.y),
+                                        map.coordinates_to_idx(target.x, target.y),
+                                        config::MAX_PATHING_EXPANSIONS,
+                                    )
+                                })
+                                .and_then(|steps| {
+                                    steps.first().map(|idx| map.idx_to_coordinates(*idx))
+                                })
+                                .filter(|&(x, y)| !map.is_tile_blocked(x, y))
+                                .map_or(action, |(x, y)| Action::Move(x, y)),
+                        }
+                    }
+                    action => action,
+                }
+            };
+
+            match action {
+                Action::Wait => {
+                    // Suspicious monsters look for the source of their suspicion
+                    if let (true, Some(facing)) = (is_suspicious, facing) {
+                        if facing.turn_towards(position.to_point(), *player_position) {
+                            fov.mark_as_dirty();
+                        }
+                    }
+                }
+                Action::Attack => {
+                    if let Some(facing) = facing {
+                        if facing.turn_towards(position.to_point(), *player_position) {
+                            fov.mark_as_dirty();
+                        }
+                    }
+
+                    melee_attack_events.single_write(MeleeAttackEvent {
+                        attacker: entity,
+                        target: *player_entity,
+                    });
+                }
+                Action::Fight(target, x, y) => {
+                    if let Some(facing) = facing {
+                        if facing.turn_towards(position.to_point(), Point::new(x, y)) {
+                            fov.mark_as_dirty();
+                        }
+                    }
+
+                    melee_attack_events.single_write(MeleeAttackEvent {
+                        attacker: entity,
+                        target,
+                    });
+                }
+                Action::Enrage => {
+                    if let Some(statistic) = statistics.get_mut(entity) {
+                        statistic.power += config::BOSS_ENRAGE_POWER;
+                    }
+
+                    if let Some(name) = names.get(entity) {
+                        game_log.push(
+                            &localization::tr_with("combat.enraged", &[&name.display()]),
+                            LogCategory::Combat,
+                        );
+                    }
+
+                    particle_builder.burst(position.to_point(), 1);
+                }
+                Action::Bash(x, y) => {
+                    let door = map
+                        .tile_contents_get(x, y)
+                        .find(|other| doors.get(*other).is_some_and(|door| !door.open));
+
+                    let door = match door {
+                        Some(door) => door,
+                        None => continue,
+                    };
+
+                    if let Some(facing) = facing {
+                        if facing.turn_towards(position.to_point(), Point::new(x, y)) {
+                            fov.mark_as_dirty();
+                        }
+                    }
+
+                    let power = statistics
+                        .get(entity)
+                        .map_or(0, |statistic| statistic.power);
+                    let is_broken = doors::bash_check(&mut rng, power);
+
+                    if map.is_tile_in_fov(x, y) {
+                        let id = if is_broken {
+                            "door.monster_bashed"
+                        } else {
+                            "door.monster_bash_failed"
+                        };
+
+                        if let Some(name) = names.get(entity) {
+                            game_log.push(
+                                &localization::tr_with(id, &[&name.display()]),
+                                LogCategory::Combat,
+                            );
+                        }
+                    }
+
+                    if is_broken {
+                        doors::break_door(&mut map, &entities, door, x, y);
+                        doors_broken = true;
+                    }
+                }
+                Action::Move(x, y) => {
+                    // Unblock old tile for the remaining monsters in the loop
+                    map.set_tile_is_blocked(position.x, position.y, false);
+
+                    // Face the direction the monster moves in
+                    if let Some(facing) = facing {
+                        facing.turn_towards(position.to_point(), Point::new(x, y));
+                    }
+
+                    // Update the monster position
+                    position.update_with_tuple((x, y));
+
+                    // Block the tile the monster has walked to
+                    map.set_tile_is_blocked(x, y, true);
+
+                    // Mark the fov of the monster as dirty so it can be recalculated for the monster
+                    fov.mark_as_dirty();
+                }
+            }
+        }
+
+        // Broken doors open up the view of everyone looking through them
+        if doors_broken {
+            doors::mark_fovs_as_dirty(&mut fovs);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Behavior of monsters wandering through the level.
+
+use super::{Action, BehaviorContext, BehaviorHandler};
+use crate::pathing::a_star_search_bounded;
+use crate::{config, Behavior};
+
+/// The maximum amount of random tiles checked, when
+/// a monster chooses the next tile to walk to.
+const TARGET_ATTEMPTS: i32 = 10;
+
+/// Handler of the [Behavior::Patrol], the monster walks to random
+/// tiles within [config::PATROL_RADIUS] until it notices the player.
+pub struct PatrolBehavior;
+
+impl BehaviorHandler for PatrolBehavior {
+    fn transition(&self, context: &BehaviorContext) -> Option<Behavior> {
+        context.alerted_behavior()
+    }
+
+    fn act(&self, context: &mut BehaviorContext) -> Action {
+        let position = (context.position.x, context.position.y);
+
+        let target = match context
+            .ai
+            .patrol_target
+            .filter(|target| *target != position)
+        {
+            Some(target) => Some(target),
+            None => PatrolBehavior::choose_target(context),
+        };
+
+        let step = target.and_then(|(x, y)| {
+            let map = context.map;
+
+            a_star_search_bounded(
+                map,
+                map.coordinates_to_idx(position.0, position.1),
+                map.coordinates_to_idx(x, y),
+                config::MAX_PATHING_EXPANSIONS,
+            )
+            .and_then(|steps| steps.first().map(|idx| map.idx_to_coordinates(*idx)))
+        });
+
+        match step {
+            Some((x, y)) => {
+                context.ai.patrol_target = target;
+                Action::Move(x, y)
+            }
+            None => {
+                // The target is unreachable, a new one is chosen in the next turn
+                context.ai.patrol_target = None;
+                Action::Wait
+            }
+        }
+    }
+}
+
+impl PatrolBehavior {
+    /// Returns a random free tile within [config::PATROL_RADIUS]
+    /// of the monster, or `None` if none could be found.
+    ///
+    /// # Arguments
+    /// * `context`: The [BehaviorContext] of the monster.
+    ///
+    fn choose_target(context: &mut BehaviorContext) -> Option<(i32, i32)> {
+        let radius = config::PATROL_RADIUS;
+        let (map, position, rng) = (context.map, context.position, &mut context.rng);
+
+        (0..TARGET_ATTEMPTS)
+            .map(|_| {
+                (
+                    position.x + rng.range(-radius, radius + 1),
+                    position.y + rng.range(-radius, radius + 1),
+                )
+            })
+            .find(|&(x, y)| map.check_idx(x, y) && map.is_tile_walkable(x, y))
+    }
+}
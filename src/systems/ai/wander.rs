@@ -0,0 +1,49 @@
+//! Behavior of peaceful entities strolling around.
+
+use super::{Action, BehaviorContext, BehaviorHandler};
+use crate::{config, Behavior, Facing};
+
+/// Handler of the [Behavior::Wander], the entity now and then steps to
+/// a random free neighbouring tile within [config::WANDER_RADIUS] of
+/// its home and never attacks, e.g. the NPCs of the town.
+///
+/// # Notes
+/// * Wandering entities never notice the player, so
+///   they never switch to another behavior.
+/// * Entities without a home wander without limits.
+///
+pub struct WanderBehavior;
+
+impl BehaviorHandler for WanderBehavior {
+    fn transition(&self, _context: &BehaviorContext) -> Option<Behavior> {
+        None
+    }
+
+    fn act(&self, context: &mut BehaviorContext) -> Action {
+        if context.rng.range(0, 100) >= config::WANDER_STEP_CHANCE {
+            return Action::Wait;
+        }
+
+        let direction = Facing::DIRECTIONS[context.rng.range(0, Facing::DIRECTIONS.len())];
+        let (x, y) = (
+            context.position.x + direction.x,
+            context.position.y + direction.y,
+        );
+
+        let map = context.map;
+        let is_near_home = context.ai.home.map_or(true, |(home_x, home_y)| {
+            (x - home_x).abs() <= config::WANDER_RADIUS
+                && (y - home_y).abs() <= config::WANDER_RADIUS
+        });
+
+        if is_near_home
+            && map.check_idx(x, y)
+            && !map.is_tile_blocked(x, y)
+            && map.get_tile(x, y).is_pathable()
+        {
+            Action::Move(x, y)
+        } else {
+            Action::Wait
+        }
+    }
+}
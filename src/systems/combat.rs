@@ -0,0 +1,1001 @@
+//! Systems resolving the combat between entities.
+
+use rltk::{console, RandomNumberGenerator, VirtualKeyCode};
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::ammunition;
+use crate::exceptions::{GameError, GameResult, RequireComponent};
+use crate::faction::{FactionRelations, Relation};
+use crate::particles::ParticleBuilder;
+use crate::quests::{Deed, QuestLog};
+use crate::raws::Raws;
+use crate::rng::Dice;
+use crate::{config, daily, entity_factory, localization, spawn_controller};
+use crate::{
+    register_event_reader, Awareness, Boss, Charisma, DamageEvent, DamageSources, DialogInterface,
+    DialogOption, EquipmentBonus, Equippable, Equipped, Experience, ExperienceReward, Facing,
+    Faction, GameLog, InflictsStatus, LevelStatistics, LogCategory, Map, MeleeAttackEvent, Name,
+    OutOfDepth, Player, Position, RangedAttackEvent, RangedWeapon, RunStatistics, Statistics,
+    StatusEffect, StatusEffectEvent, StatusEffectKind, StatusEffects, SystemDispatcherBuilder,
+    Unique, AI, FOV,
+};
+
+/// Registers the attack systems of the module with the passed `builder`,
+/// the [DamageSystem] is registered through [register_damage].
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder
+        .with(
+            MeleeCombatSystem::default(),
+            "melee_combat",
+            &["monster_ai"],
+        )
+        .with(
+            RangedCombatSystem::default(),
+            "ranged_combat",
+            &["monster_ai"],
+        )
+}
+
+/// Registers the [DamageSystem] with the passed `builder` and inserts
+/// the [DamageSources] into the `ecs`. Registered separately from the
+/// other combat systems, as it handles the [DamageEvent]s of the items,
+/// spells and status effects, whose systems have to be registered first.
+///
+/// # Arguments
+/// * `ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register_damage(
+    ecs: &mut World,
+    builder: SystemDispatcherBuilder,
+) -> SystemDispatcherBuilder {
+    ecs.insert(DamageSources::default());
+
+    builder.with(
+        DamageSystem::default(),
+        "damage",
+        &[
+            "melee_combat",
+            "ranged_combat",
+            "item_use",
+            "spell_cast",
+            "status_effects",
+        ],
+    )
+}
+
+/// System to handle the [MeleeAttackEvent]s
+/// published in the last turn.
+///
+/// # Notes
+/// * Attacks on targets with a [Facing], that can't see their attacker,
+///   are sneak attacks and deal [config::SNEAK_ATTACK_MULTIPLIER] times
+///   the damage. Afterwards the target turns towards its attacker.
+/// * The player's attacks on monsters, that aren't alerted, are sneak
+///   attacks as well. They deal [config::UNAWARE_SNEAK_ATTACK_MULTIPLIER]
+///   times the damage to unaware monsters, which are alerted by them.
+/// * Attackers with [InflictsStatus] inflict their effect by its
+///   chance on the target of every attack, that deals damage.
+/// * The damage dealt and taken by the player is
+///   counted in the [RunStatistics].
+/// * Every strike rolls a d20 to hit the armor class of the target
+///   and the damage dice of the attacker's weapon, see [Equippable::damage].
+/// * The [Equipped] items of the attacker add to its power,
+///   the ones of the target add to its defense.
+/// * Attackers wielding a second weapon in the off hand strike again
+///   with it, reduced by the [config::DUAL_WIELD_PENALTY], unless the
+///   first strike has already slain the target.
+/// * Every strike, that isn't a sneak attack, may be blocked by the
+///   shield of the target, see [EquipmentBonus::block_chance].
+/// * Attacks between entities of allied [Faction]s are ignored,
+///   unless the attacker is confused, see [FactionRelations].
+///
+#[derive(Default)]
+pub struct MeleeCombatSystem {
+    /// Reader for the [MeleeAttackEvent] channel,
+    /// registered during the setup of the system.
+    reader: Option<ReaderId<MeleeAttackEvent>>,
+}
+
+impl<'a> System<'a> for MeleeCombatSystem {
+    type SystemData = (
+        WriteExpect<'a, GameLog>,
+        Read<'a, EventChannel<MeleeAttackEvent>>,
+        Write<'a, EventChannel<DamageEvent>>,
+        Write<'a, EventChannel<StatusEffectEvent>>,
+        Write<'a, ParticleBuilder>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, RunStatistics>,
+        ReadExpect<'a, FactionRelations>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, InflictsStatus>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, StatusEffects>,
+        ReadStorage<'a, Statistics>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Equippable>,
+        WriteStorage<'a, Facing>,
+        WriteStorage<'a, FOV>,
+        WriteStorage<'a, AI>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_event_reader::<MeleeAttackEvent>(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut game_log,
+            melee_attack_events,
+            mut damage_events,
+            mut status_effect_events,
+            mut particle_builder,
+            mut rng,
+            mut run_statistics,
+            relations,
+            names,
+            players,
+            inflicts_status,
+            factions,
+            status_effects,
+            statistics,
+            positions,
+            equipped,
+            equippables,
+            mut facings,
+            mut fovs,
+            mut ais,
+        ) = data;
+
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("MeleeCombatSystem has not been set up!");
+
+        for attack in melee_attack_events.read(reader) {
+            let is_confused = status_effects
+                .get(attack.attacker)
+                .is_some_and(|effects| effects.has(StatusEffectKind::Confusion));
+
+            if !is_confused
+                && relations.relation_between(&factions, attack.attacker, attack.target)
+                    == Relation::Allied
+            {
+                continue;
+            }
+
+            let attacker_position = positions.get(attack.attacker).map(|pos| pos.to_point());
+            let target_position = positions.get(attack.target).map(|pos| pos.to_point());
+
+            // The attacker is unseen if the target only sees within a
+            // vision cone and the attacker is outside of its view
+            let is_unseen = facings.contains(attack.target)
+                && attacker_position.is_some_and(|attacker_position| {
+                    fovs.get(attack.target)
+                        .is_some_and(|fov| !fov.contains(&attacker_position))
+                });
+
+            let awareness = ais
+                .get(attack.target)
+                .filter(|_| players.contains(attack.attacker))
+                .map(|ai| ai.awareness);
+
+            let multiplier = match awareness {
+                Some(Awareness::Unaware) => config::UNAWARE_SNEAK_ATTACK_MULTIPLIER,
+                Some(Awareness::Suspicious) => config::SNEAK_ATTACK_MULTIPLIER,
+                _ if is_unseen => config::SNEAK_ATTACK_MULTIPLIER,
+                _ => 1,
+            };
+
+            let attacker_bonus = EquipmentBonus::of(attack.attacker, &equipped, &equippables);
+            let target_bonus = EquipmentBonus::of(attack.target, &equipped, &equippables);
+            let target_hp = statistics
+                .get(attack.target)
+                .map_or(0, |statistic| statistic.hp);
+
+            // Dual wielders follow up with the weapon in their off hand
+            let strikes =
+                std::iter::once((multiplier, attacker_bonus.power, attacker_bonus.damage)).chain(
+                    attacker_bonus.off_hand_power.map(|power| {
+                        (
+                            1,
+                            power - config::DUAL_WIELD_PENALTY,
+                            attacker_bonus.off_hand_damage,
+                        )
+                    }),
+                );
+
+            let mut dealt = 0;
+
+            for (strike_multiplier, power_bonus, damage_dice) in strikes {
+                // The off hand only strikes targets, that survived the main hand
+                if dealt > 0 && dealt >= target_hp {
+                    break;
+                }
+
+                // Shields only block the attacks, that the target sees coming
+                if target_hp > 0
+                    && strike_multiplier == 1
+                    && rng.range(0, 100) < target_bonus.block_chance
+                {
+                    let display = |entity: Entity| {
+                        names
+                            .get(entity)
+                            .map_or_else(|| localization::tr("name.someone"), Name::display)
+                    };
+
+                    game_log.push(
+                        &localization::tr_with(
+                            "combat.blocked",
+                            &[&display(attack.attacker), &display(attack.target)],
+                        ),
+                        LogCategory::Combat,
+                    );
+
+                    if let Some(target_position) = target_position {
+                        particle_builder.block(target_position);
+                    }
+
+                    continue;
+                }
+
+                let result = MeleeCombatSystem::resolve_attack(
+                    attack,
+                    Strike {
+                        multiplier: strike_multiplier,
+                        power_bonus,
+                        damage: damage_dice,
+                        armor_bonus: target_bonus.defense,
+                    },
+                    &mut rng,
+                    &names,
+                    &statistics,
+                    &mut game_log,
+                    &mut damage_events,
+                );
+
+                match result {
+                    Ok(damage) if damage > 0 => {
+                        // Damage beyond the remaining hp of the target isn't counted
+                        let counted_damage = damage.min(target_hp - dealt);
+                        dealt += damage;
+
+                        if players.contains(attack.attacker) {
+                            run_statistics.damage_dealt += counted_damage;
+                        }
+
+                        if players.contains(attack.target) {
+                            run_statistics.damage_taken += counted_damage;
+                        }
+
+                        if let Some(target_position) = target_position {
+                            particle_builder.slash(target_position);
+                        }
+
+                        // Attacks, that deal damage, may inflict the attacker's status effect
+                        if let Some(inflicts) = inflicts_status.get(attack.attacker) {
+                            if rng.range(0, 100) < inflicts.chance {
+                                status_effect_events.single_write(StatusEffectEvent {
+                                    target: attack.target,
+                                    effect: StatusEffect {
+                                        inflicter: Some(attack.attacker),
+                                        ..inflicts.effect
+                                    },
+                                });
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(error) => game_log.push_error(&error),
+                }
+            }
+
+            // Monsters attacked by the player have noticed it
+            if players.contains(attack.attacker) {
+                if let Some(ai) = ais.get_mut(attack.target) {
+                    ai.awareness = Awareness::Alert;
+                }
+            }
+
+            // The target turns towards the attacker, after noticing the attack
+            if let (Some(facing), Some(from), Some(to)) = (
+                facings.get_mut(attack.target),
+                target_position,
+                attacker_position,
+            ) {
+                if facing.turn_towards(from, to) {
+                    if let Some(fov) = fovs.get_mut(attack.target) {
+                        fov.mark_as_dirty();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A single strike of a melee attack.
+struct Strike {
+    /// The damage multiplier of a sneak attack, `1` for regular attacks.
+    multiplier: i32,
+
+    /// The power bonus of the attacker's equipment.
+    power_bonus: i32,
+
+    /// The damage dice of the striking weapon, `None` for unarmed strikes.
+    damage: Option<Dice>,
+
+    /// The defense bonus of the target's equipment.
+    armor_bonus: i32,
+}
+
+impl MeleeCombatSystem {
+    /// Resolves the passed melee `attack` by rolling whether the `strike`
+    /// hits and how much damage it inflicts on the target, publishing it
+    /// as [DamageEvent] and reporting the outcome to the `game_log`.
+    /// Returns the inflicted damage.
+    ///
+    /// # Arguments
+    /// * `attack`: The [MeleeAttackEvent] to resolve.
+    /// * `strike`: The [Strike] of the attacker.
+    /// * `rng`: The [RandomNumberGenerator] rolling the dice.
+    /// * `names`: The [Name] storage of the `ecs`.
+    /// * `statistics`: The [Statistics] storage of the `ecs`.
+    /// * `game_log`: The [GameLog] to report the outcome to.
+    /// * `damage_events`: The channel to publish the inflicted damage to.
+    ///
+    /// # Notes
+    /// * The strike hits if a d20 plus the attacker's power reaches the
+    ///   armor class of the target, which is [config::BASE_ARMOR_CLASS]
+    ///   plus its defense. A natural 20 always hits, a natural 1 always misses.
+    /// * A hit deals the damage rolled with the weapon's dice, or a single
+    ///   die with [config::UNARMED_DAMAGE_SIDES] without one, plus half
+    ///   of the attacker's power.
+    ///
+    /// # Errors
+    /// * If the attacker or target has no [Name] or [Statistics].
+    ///
+    fn resolve_attack(
+        attack: &MeleeAttackEvent,
+        strike: Strike,
+        rng: &mut RandomNumberGenerator,
+        names: &ReadStorage<Name>,
+        statistics: &ReadStorage<Statistics>,
+        game_log: &mut GameLog,
+        damage_events: &mut EventChannel<DamageEvent>,
+    ) -> GameResult<i32> {
+        let statistic = statistics.require(attack.attacker)?;
+        let target_statistics = statistics.require(attack.target)?;
+
+        if statistic.hp <= 0 || target_statistics.hp <= 0 {
+            return Ok(0);
+        }
+
+        let name = names.require(attack.attacker)?;
+        let target_name = names.require(attack.target)?;
+
+        let power = statistic.power + strike.power_bonus;
+        let armor_class = config::BASE_ARMOR_CLASS + target_statistics.defense + strike.armor_bonus;
+
+        let roll = rng.roll_dice(1, 20);
+        let is_hit = roll == 20 || (roll > 1 && roll + power >= armor_class);
+
+        if !is_hit {
+            game_log.push(
+                &localization::tr_with("combat.miss", &[&name.display(), &target_name.display()]),
+                LogCategory::Combat,
+            );
+            return Ok(0);
+        }
+
+        let dice_roll = match strike.damage {
+            Some(dice) => dice.roll(rng),
+            None => rng.roll_dice(1, config::UNARMED_DAMAGE_SIDES),
+        };
+
+        let is_sneak_attack = strike.multiplier > 1;
+        let damage = i32::max(0, dice_roll + power / 2) * strike.multiplier;
+
+        if damage == 0 {
+            game_log.push(
+                &localization::tr_with(
+                    "combat.no_damage",
+                    &[&name.display(), &target_name.display()],
+                ),
+                LogCategory::Combat,
+            );
+        } else {
+            let id = if is_sneak_attack {
+                "combat.sneak_attack"
+            } else {
+                "combat.hit"
+            };
+
+            game_log.push(
+                &localization::tr_with(id, &[&name.display(), &target_name.display(), &damage]),
+                LogCategory::Combat,
+            );
+            damage_events.single_write(DamageEvent {
+                target: attack.target,
+                amount: damage,
+                source: Some(attack.attacker),
+            });
+        }
+
+        Ok(damage)
+    }
+}
+
+/// System to handle the [RangedAttackEvent]s published in the last
+/// turn. Unlike melee attacks the damage of a shot is determined by the
+/// [RangedWeapon] of the attacker, not by its own power.
+///
+/// # Notes
+/// * The shot hits the first entity with [Statistics] on the target
+///   tile, shots at empty tiles miss.
+/// * The [Equipped] items of the target add to its defense.
+/// * The fired ammunition lands on the target tile, where it may
+///   be recovered, see [ammunition::land].
+/// * Monsters shot by the player have noticed it, the damage dealt
+///   and taken by the player is counted in the [RunStatistics].
+///
+#[derive(Default)]
+pub struct RangedCombatSystem {
+    /// Reader for the [RangedAttackEvent] channel,
+    /// registered during the setup of the system.
+    reader: Option<ReaderId<RangedAttackEvent>>,
+}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        Read<'a, EventChannel<RangedAttackEvent>>,
+        Write<'a, EventChannel<DamageEvent>>,
+        Write<'a, ParticleBuilder>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, RunStatistics>,
+        ReadExpect<'a, Map>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Statistics>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Equippable>,
+        ReadStorage<'a, RangedWeapon>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, AI>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_event_reader::<RangedAttackEvent>(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut game_log,
+            ranged_attack_events,
+            mut damage_events,
+            mut particle_builder,
+            mut rng,
+            mut run_statistics,
+            map,
+            names,
+            players,
+            statistics,
+            equipped,
+            equippables,
+            weapons,
+            mut positions,
+            mut ais,
+        ) = data;
+
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("RangedCombatSystem has not been set up!");
+
+        for attack in ranged_attack_events.read(reader) {
+            let target = Position {
+                x: attack.target.x,
+                y: attack.target.y,
+            };
+
+            let victim = map
+                .tile_contents_get(target.x, target.y)
+                .find(|entity| *entity != attack.attacker && statistics.contains(*entity));
+
+            let power = RangedWeapon::wielded(attack.attacker, &equipped, &weapons)
+                .map_or(0, |weapon| weapon.power);
+
+            let result = match victim {
+                Some(victim) => RangedCombatSystem::resolve_shot(
+                    attack,
+                    victim,
+                    power - EquipmentBonus::of(victim, &equipped, &equippables).defense,
+                    &names,
+                    &statistics,
+                    &mut game_log,
+                    &mut damage_events,
+                ),
+                None => names.require(attack.attacker).map(|name| {
+                    game_log.push(
+                        &localization::tr_with("combat.shot_missed", &[&name.display()]),
+                        LogCategory::Combat,
+                    );
+                    0
+                }),
+            };
+
+            match (result, victim) {
+                (Ok(damage), Some(victim)) if damage > 0 => {
+                    // Damage beyond the remaining hp of the target isn't counted
+                    let counted_damage = statistics
+                        .get(victim)
+                        .map_or(damage, |statistic| damage.min(statistic.hp));
+
+                    if players.contains(attack.attacker) {
+                        run_statistics.damage_dealt += counted_damage;
+                    }
+
+                    if players.contains(victim) {
+                        run_statistics.damage_taken += counted_damage;
+                    }
+
+                    particle_builder.slash(attack.target);
+                }
+                (Ok(_), _) => {}
+                (Err(error), _) => game_log.push_error(&error),
+            }
+
+            // Monsters shot by the player have noticed it
+            if let (true, Some(victim)) = (players.contains(attack.attacker), victim) {
+                if let Some(ai) = ais.get_mut(victim) {
+                    ai.awareness = Awareness::Alert;
+                }
+            }
+
+            if let Some(piece) = attack.ammunition {
+                ammunition::land(&mut rng, &entities, &mut positions, piece, target);
+            }
+        }
+    }
+}
+
+impl RangedCombatSystem {
+    /// Resolves the passed ranged `attack` on the `victim` by publishing
+    /// the damage of the shot as [DamageEvent] and reporting the outcome
+    /// to the `game_log`. Returns the inflicted damage.
+    ///
+    /// # Arguments
+    /// * `attack`: The [RangedAttackEvent] to resolve.
+    /// * `victim`: The entity hit by the shot.
+    /// * `power`: The power of the attacker's [RangedWeapon]
+    ///   reduced by the defense bonus of the victim's equipment.
+    /// * `names`: The [Name] storage of the `ecs`.
+    /// * `statistics`: The [Statistics] storage of the `ecs`.
+    /// * `game_log`: The [GameLog] to report the outcome to.
+    /// * `damage_events`: The channel to publish the inflicted damage to.
+    ///
+    /// # Errors
+    /// * If the attacker or victim has no [Name] or the victim has no [Statistics].
+    ///
+    fn resolve_shot(
+        attack: &RangedAttackEvent,
+        victim: Entity,
+        power: i32,
+        names: &ReadStorage<Name>,
+        statistics: &ReadStorage<Statistics>,
+        game_log: &mut GameLog,
+        damage_events: &mut EventChannel<DamageEvent>,
+    ) -> GameResult<i32> {
+        let victim_statistics = statistics.require(victim)?;
+
+        if victim_statistics.hp <= 0 {
+            return Ok(0);
+        }
+
+        let name = names.require(attack.attacker)?;
+        let victim_name = names.require(victim)?;
+
+        let damage = i32::max(0, power - victim_statistics.defense);
+
+        if damage == 0 {
+            game_log.push(
+                &localization::tr_with(
+                    "combat.no_damage",
+                    &[&name.display(), &victim_name.display()],
+                ),
+                LogCategory::Combat,
+            );
+        } else {
+            game_log.push(
+                &localization::tr_with(
+                    "combat.shot",
+                    &[&name.display(), &victim_name.display(), &damage],
+                ),
+                LogCategory::Combat,
+            );
+            damage_events.single_write(DamageEvent {
+                target: victim,
+                amount: damage,
+                source: Some(attack.attacker),
+            });
+        }
+
+        Ok(damage)
+    }
+}
+
+/// System that takes all the damage inflicted to an entity,
+/// adds up the damage and subtracts it from the entities
+/// health. The source of the damage is remembered in the
+/// [DamageSources] to credit the defeat of the entity.
+#[derive(Default)]
+pub struct DamageSystem {
+    /// Reader for the [DamageEvent] channel,
+    /// registered during the setup of the system.
+    reader: Option<ReaderId<DamageEvent>>,
+}
+
+impl DamageSystem {
+    /// Removes all entities which have been defeated in the last executed turn
+    /// from the `ecs`. Returns `true` if the player has been defeated, `false`
+    /// otherwise.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] from which the defeated entities should be removed.
+    ///
+    /// # Notes
+    /// * The player [Entity] is never removed, so the game over screen can still
+    ///   access its data.
+    /// * The defeat of an entity is credited to the entity, that has dealt
+    ///   the last damage to it, see [DamageSources]. Defeats without a
+    ///   known source, e.g. by lava or fire, are credited
+    ///   to the player.
+    /// * Only entities defeated by the player or an entity allied with the
+    ///   player grant their [ExperienceReward] to the player and count as
+    ///   kills in the [RunStatistics] and the [LevelStatistics]. Entities
+    ///   slain by their foes grant no xp at all.
+    /// * Kills credited to the player advance the quests in the [QuestLog].
+    /// * Defeating the [Boss] wins the game, see [DamageSystem::show_victory_dialog].
+    /// * [OutOfDepth] monsters defeated by the player leave a hoard of
+    ///   treasure behind, see [spawn_controller::drop_hoard].
+    /// * [Unique] monsters always drop the items of their definition,
+    ///   no matter who defeated them. Uniques defeated by the player
+    ///   are listed in the [RunStatistics].
+    ///
+    pub fn clean_up(ecs: &mut World) -> bool {
+        let mut defeated_entities: Vec<Entity> = Vec::new();
+        let mut player_died = false;
+        let mut gained_xp = 0;
+        let mut kills = 0;
+        let mut defeated_boss = None;
+        let mut hoards: Vec<(Position, i32)> = Vec::new();
+        let mut drops: Vec<(Position, String)> = Vec::new();
+        let mut uniques_slain: Vec<String> = Vec::new();
+
+        {
+            let entities = ecs.entities();
+            let names = ecs.read_storage::<Name>();
+            let players = ecs.read_storage::<Player>();
+            let rewards = ecs.read_storage::<ExperienceReward>();
+            let bosses = ecs.read_storage::<Boss>();
+            let out_of_depth = ecs.read_storage::<OutOfDepth>();
+            let positions = ecs.read_storage::<Position>();
+            let uniques = ecs.read_storage::<Unique>();
+            let raws = ecs.fetch::<Raws>();
+            let factions = ecs.read_storage::<Faction>();
+            let relations = ecs.fetch::<FactionRelations>();
+            let mut damage_sources = ecs.write_resource::<DamageSources>();
+            let mut quest_log = ecs.write_resource::<QuestLog>();
+            let mut game_log = ecs.write_resource::<GameLog>();
+            let statistics = ecs.read_storage::<Statistics>();
+
+            for (entity, statistic) in (&entities, &statistics).join() {
+                if statistic.hp < 1 {
+                    let player = players.get(entity);
+
+                    if player.is_some() {
+                        let player_name = names.get(entity).map_or("Player", |name| &name.name);
+                        console::log(format!("{} has died!", player_name));
+                        player_died = true;
+                        continue;
+                    }
+
+                    let monster_name = names.get(entity);
+
+                    if let Some(name) = monster_name {
+                        defeated_entities.push(entity);
+
+                        if bosses.contains(entity) {
+                            defeated_boss = Some(name.display());
+                        }
+
+                        if let (true, Some(position)) =
+                            (uniques.contains(entity), positions.get(entity))
+                        {
+                            let items = raws
+                                .unique(&name.name)
+                                .map_or_else(Vec::new, |unique| unique.drops.clone());
+
+                            drops.extend(items.into_iter().map(|item| (*position, item)));
+                        }
+
+                        let slayer = damage_sources.source_of(entity);
+
+                        let is_credited_to_player = slayer.map_or(true, |slayer| {
+                            factions.get(slayer).is_some_and(|faction| {
+                                relations.relation(&faction.name, config::PLAYER_FACTION)
+                                    == Relation::Allied
+                            })
+                        });
+
+                        if is_credited_to_player {
+                            gained_xp += rewards.get(entity).map_or(0, |reward| reward.xp);
+                            kills += 1;
+                            quest_log.record(Deed::Kill(&name.name));
+
+                            if uniques.contains(entity) {
+                                uniques_slain.push(name.name.clone());
+                            }
+
+                            game_log.push(
+                                &localization::tr_with("combat.died", &[&name.display()]),
+                                LogCategory::Combat,
+                            );
+
+                            if let (Some(monster), Some(position)) =
+                                (out_of_depth.get(entity), positions.get(entity))
+                            {
+                                hoards.push((*position, monster.depth));
+                                game_log.push(
+                                    &localization::tr_with("combat.hoard", &[&name.display()]),
+                                    LogCategory::Item,
+                                );
+                            }
+                        } else {
+                            let slayer_name = slayer
+                                .and_then(|slayer| names.get(slayer))
+                                .map_or_else(|| localization::tr("name.someone"), Name::display);
+
+                            game_log.push(
+                                &localization::tr_with(
+                                    "combat.slain_by",
+                                    &[&name.display(), &slayer_name],
+                                ),
+                                LogCategory::Combat,
+                            );
+                        }
+                    }
+                }
+            }
+
+            damage_sources.clear();
+        }
+
+        {
+            let mut run_statistics = ecs.write_resource::<RunStatistics>();
+            run_statistics.kills += kills;
+            run_statistics.uniques_slain.extend(uniques_slain);
+        }
+        ecs.write_resource::<LevelStatistics>().kills += kills;
+
+        if let Err(error) = ecs.delete_entities(&defeated_entities) {
+            let mut game_log = ecs.write_resource::<GameLog>();
+            game_log.push_error(&GameError::EntityDeletion {
+                entity: error.entity,
+            });
+        }
+
+        for (position, depth) in hoards {
+            spawn_controller::drop_hoard(ecs, position, depth);
+        }
+
+        for (position, item) in drops {
+            if entity_factory::new_named(ecs, &item, position).is_none() {
+                console::log(format!("Unknown drop '{}'!", item));
+            }
+        }
+
+        if gained_xp > 0 && !player_died {
+            Self::grant_experience(ecs, gained_xp);
+        }
+
+        if let Some(boss_name) = defeated_boss.filter(|_| !player_died) {
+            daily::record(ecs);
+            Self::show_victory_dialog(ecs, &boss_name);
+        }
+
+        player_died
+    }
+
+    /// Registers the victory [DialogInterface], from which the player
+    /// can either keep exploring the dungeon or quit the game.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which the dialog should be registered.
+    /// * `boss_name`: The displayed name of the defeated boss.
+    ///
+    fn show_victory_dialog(ecs: &mut World, boss_name: &str) {
+        DialogInterface::register_dialog(
+            ecs,
+            localization::tr("dialog.victory.title"),
+            Some(localization::tr_with(
+                "dialog.victory.message",
+                &[&boss_name],
+            )),
+            vec![
+                DialogOption {
+                    description: localization::tr("dialog.victory.continue"),
+                    key: VirtualKeyCode::C,
+                    args: vec![],
+                    callback: Box::new(|_, _, _| ()),
+                },
+                DialogOption {
+                    description: localization::tr("dialog.victory.quit"),
+                    key: VirtualKeyCode::Q,
+                    args: vec![],
+                    callback: Box::new(|_, ctx, _| ctx.quit()),
+                },
+            ],
+            false,
+        );
+    }
+
+    /// Grants the `xp` to the player and raises the player's [Statistics]
+    /// for every gained level by [config::LEVEL_UP_HP] and [config::LEVEL_UP_POWER],
+    /// as well as the player's [Charisma] by [config::LEVEL_UP_CHARISMA].
+    /// The player is fully healed on a level up.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] containing the player.
+    /// * `xp`: The xp gained by the player.
+    ///
+    pub fn grant_experience(ecs: &mut World, xp: i32) {
+        let player = *ecs.fetch::<Entity>();
+        let mut experiences = ecs.write_storage::<Experience>();
+        let mut statistics = ecs.write_storage::<Statistics>();
+        let mut charismas = ecs.write_storage::<Charisma>();
+        let mut game_log = ecs.write_resource::<GameLog>();
+
+        let experience = match experiences.get_mut(player) {
+            Some(experience) => experience,
+            None => return,
+        };
+
+        let levels = experience.gain(xp);
+
+        if levels == 0 {
+            return;
+        }
+
+        if let Some(statistic) = statistics.get_mut(player) {
+            statistic.hp_max += levels * config::LEVEL_UP_HP;
+            statistic.power += levels * config::LEVEL_UP_POWER;
+            statistic.hp = statistic.hp_max;
+        }
+
+        if let Some(charisma) = charismas.get_mut(player) {
+            charisma.value += levels * config::LEVEL_UP_CHARISMA;
+        }
+
+        game_log.push(
+            &localization::tr_with("log.level_up", &[&experience.level]),
+            LogCategory::General,
+        );
+    }
+}
+
+impl<'a> System<'a> for DamageSystem {
+    type SystemData = (
+        Read<'a, EventChannel<DamageEvent>>,
+        Write<'a, DamageSources>,
+        WriteStorage<'a, Statistics>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_event_reader::<DamageEvent>(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (damage_events, mut damage_sources, mut statistics) = data;
+
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("DamageSystem has not been set up!");
+
+        for damage in damage_events.read(reader) {
+            if let Some(statistic) = statistics.get_mut(damage.target) {
+                statistic.hp -= damage.amount;
+            }
+
+            if let Some(source) = damage.source {
+                damage_sources.record(damage.target, source);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publish_event;
+    use crate::test_support::{TestWorld, TestWorldBuilder};
+
+    /// Number of attacks published per test, so a single
+    /// missed attack can't make the test fail.
+    const ATTACKS: usize = 5;
+
+    /// Lets the player attack the goblin [ATTACKS] times and resolves
+    /// the resulting damage. The goblin's defense is lowered, so the
+    /// attacks always break through it.
+    fn attack_goblin(goblin_hp: i32) -> (TestWorld, Entity) {
+        let mut world = TestWorldBuilder::new()
+            .with_player(2, 2)
+            .with_monster(3, 2)
+            .build();
+
+        let player = world.player();
+        let goblin = world.monsters[0];
+
+        if let Some(statistic) = world.ecs.write_storage::<Statistics>().get_mut(goblin) {
+            statistic.hp = goblin_hp;
+            statistic.defense = -100;
+        }
+
+        let mut melee_combat = world.setup_system(MeleeCombatSystem::default());
+        let mut damage = world.setup_system(DamageSystem::default());
+
+        for _ in 0..ATTACKS {
+            publish_event(
+                &world.ecs,
+                MeleeAttackEvent {
+                    attacker: player,
+                    target: goblin,
+                },
+            );
+        }
+
+        world.run_system(&mut melee_combat);
+        world.run_system(&mut damage);
+
+        (world, goblin)
+    }
+
+    #[test]
+    fn melee_attack_damages_target() {
+        let (world, goblin) = attack_goblin(1000);
+
+        let hp = world
+            .ecs
+            .read_storage::<Statistics>()
+            .get(goblin)
+            .map(|statistic| statistic.hp)
+            .expect("The goblin has no statistics!");
+
+        assert!(hp < 1000);
+        assert!(!world.messages().is_empty());
+    }
+
+    #[test]
+    fn melee_attack_kills_target() {
+        let (mut world, goblin) = attack_goblin(1);
+
+        let player_died = DamageSystem::clean_up(&mut world.ecs);
+
+        assert!(!player_died);
+        assert!(!world.ecs.is_alive(goblin));
+    }
+}
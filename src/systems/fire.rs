@@ -0,0 +1,185 @@
+//! Systems spreading fire across the flammable tiles of the map.
+
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::{
+    config, localization, register_event_reader, AreaOfEffect, GameLog, IgniteEvent, LogCategory,
+    Map, Name, Position, Regenerates, RunState, Statistics, SystemDispatcherBuilder,
+};
+
+/// Registers the systems of the module with the passed `builder`.
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder.with(
+        FireSpreadSystem::default(),
+        "fire_spread",
+        &["item_use", "spell_cast", "terrain"],
+    )
+}
+
+/// System setting the flammable tiles hit by the [IgniteEvent]s on fire
+/// and spreading the fire across the fire overlay of the [Map].
+///
+/// # Notes
+/// * Runs at the end of the [RunState::MonsterTurn], after all entities
+///   have acted, so every entity standing in the fire takes
+///   [config::FIRE_DAMAGE] once per turn.
+/// * Every turn the fire of a burning tile spreads to each of its
+///   flammable neighbors by the [config::FIRE_SPREAD_CHANCE]. Tiles,
+///   that caught fire during the turn, spread it from the next turn on.
+/// * Tiles burn out after [config::FIRE_DURATION] turns and are
+///   replaced by their [TileType::burnt](crate::TileType::burnt) tile.
+/// * Entities, that [Regenerates], are scorched by the fire of the
+///   events and of the burning tiles, see [Regenerates::scorch].
+///
+#[derive(Default)]
+pub struct FireSpreadSystem {
+    /// Reader for the [IgniteEvent] channel,
+    /// registered during the setup of the system.
+    reader: Option<ReaderId<IgniteEvent>>,
+}
+
+impl<'a> System<'a> for FireSpreadSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, Map>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, GameLog>,
+        Read<'a, EventChannel<IgniteEvent>>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Statistics>,
+        WriteStorage<'a, Regenerates>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_event_reader::<IgniteEvent>(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            run_state,
+            mut map,
+            mut rng,
+            mut game_log,
+            ignite_events,
+            names,
+            positions,
+            mut statistics,
+            mut regenerates,
+        ) = data;
+
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("FireSpreadSystem has not been set up!");
+
+        for event in ignite_events.read(reader) {
+            let area = AreaOfEffect {
+                radius: event.radius,
+            };
+
+            let mut seen = false;
+
+            for y in event.target.y - event.radius..=event.target.y + event.radius {
+                for x in event.target.x - event.radius..=event.target.x + event.radius {
+                    if area.covers(&event.target, &Point::new(x, y))
+                        && map.ignite(x, y)
+                        && map.is_tile_in_fov(x, y)
+                    {
+                        seen = true;
+                    }
+                }
+            }
+
+            // The blast scorches everyone in it, even on tiles, that don't burn
+            for (position, regeneration) in (&positions, &mut regenerates).join() {
+                if area.covers(&event.target, &position.to_point()) {
+                    regeneration.scorch();
+                }
+            }
+
+            // Only fires the player witnesses are reported
+            if seen {
+                game_log.push(
+                    &localization::tr("terrain.fire.ignite"),
+                    LogCategory::Combat,
+                );
+            }
+        }
+
+        if *run_state != RunState::MonsterTurn {
+            return;
+        }
+
+        for (entity, position, statistic, regeneration) in (
+            &entities,
+            &positions,
+            &mut statistics,
+            (&mut regenerates).maybe(),
+        )
+            .join()
+        {
+            if statistic.hp <= 0 || !map.is_burning(position.x, position.y) {
+                continue;
+            }
+
+            if let Some(regeneration) = regeneration {
+                regeneration.scorch();
+            }
+
+            let name = names
+                .get(entity)
+                .map_or_else(|| localization::tr("name.someone"), Name::display);
+
+            statistic.hp -= config::FIRE_DAMAGE;
+            game_log.push(
+                &localization::tr_with("terrain.fire.burn", &[&name, &config::FIRE_DAMAGE]),
+                LogCategory::Combat,
+            );
+        }
+
+        let burning = (0..map.fire.len())
+            .filter(|idx| map.fire[*idx] > 0)
+            .collect::<Vec<_>>();
+
+        // Only the tiles, that were burning at the start of the turn, spread the fire
+        let mut spread = Vec::new();
+
+        for idx in burning.iter().copied() {
+            let (x, y) = map.idx_to_coordinates(idx);
+
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                .iter()
+                .copied()
+            {
+                if rng.range(0, 100) < config::FIRE_SPREAD_CHANCE {
+                    spread.push((nx, ny));
+                }
+            }
+        }
+
+        for idx in burning {
+            map.fire[idx] -= 1;
+
+            if map.fire[idx] == 0 {
+                let (x, y) = map.idx_to_coordinates(idx);
+                let burnt = map.get_tile(x, y).burnt();
+                map.set_tile(x, y, burnt);
+            }
+        }
+
+        for (x, y) in spread {
+            map.ignite(x, y);
+        }
+    }
+}
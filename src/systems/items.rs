@@ -0,0 +1,806 @@
+//! Systems handling the collection, usage and dropping of items.
+
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::exceptions::{GameError, GameResult, RequireComponent};
+use crate::identification::Identification;
+use crate::localization;
+use crate::particles::ParticleBuilder;
+use crate::quests::{Deed, QuestLog};
+use crate::scripting::{ScriptContext, ScriptEffect, ScriptEngine, HOOK_ON_USE};
+use crate::{
+    register_event_reader, AreaOfEffect, CureEvent, Cursed, DamageEvent, DropEvent, EquipEvent,
+    EquipmentSlot, Equippable, Equipped, GameLog, IgniteEvent, LogCategory, Loot, Map, Name,
+    PickupEvent, Position, Scripted, Stackable, Statistics, StatusEffect, StatusEffectEvent,
+    SystemDispatcherBuilder, UseItem, Weight, FOV,
+};
+
+/// Registers the systems of the module with the passed `builder`.
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder
+        .with(ItemCollectionSystem::default(), "item_collection", &[])
+        .with(ItemUseSystem {}, "item_use", &[])
+        .with(ItemDropSystem::default(), "item_drop", &["item_collection"])
+        .with(
+            ItemEquipSystem::default(),
+            "item_equip",
+            &["item_collection"],
+        )
+}
+
+/// System that handles the [PickupEvent]s of all
+/// [Entity] objects and adds the corresponding Item to their
+/// inventory by registering a respective [Loot] component.
+/// Items picked up by the player advance the quests in the [QuestLog].
+///
+/// # Notes
+/// * [Stackable] items join the stack of identical items in the
+///   inventory of the collector, if it already carries one.
+/// * Items, that would take the [Weight] carried by the collector past
+///   its [Statistics::max_load], are left behind. Items taken out of a
+///   container drop to the collector's feet in that case.
+///
+#[derive(Default)]
+pub struct ItemCollectionSystem {
+    /// Reader for the [PickupEvent] channel,
+    /// registered during the setup of the system.
+    reader: Option<ReaderId<PickupEvent>>,
+}
+
+impl<'a> System<'a> for ItemCollectionSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, QuestLog>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, Identification>,
+        Read<'a, EventChannel<PickupEvent>>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Statistics>,
+        ReadStorage<'a, Weight>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Loot>,
+        WriteStorage<'a, Stackable>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_event_reader::<PickupEvent>(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut game_log,
+            mut quest_log,
+            player,
+            identification,
+            pickup_events,
+            names,
+            statistics,
+            weights,
+            mut positions,
+            mut backpack,
+            mut stackables,
+        ) = data;
+
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("ItemCollectionSystem has not been set up!");
+
+        for pickup in pickup_events.read(reader) {
+            let quantity = stackables
+                .get(pickup.item)
+                .map_or(1, |stackable| stackable.quantity);
+
+            let load = weights.get(pickup.item).map_or(0, |weight| weight.value) * quantity;
+            let carried = Weight::carried(pickup.collector, &backpack, &weights, &stackables);
+            let limits = statistics
+                .get(pickup.collector)
+                .map(|statistic| (statistic.carry_capacity(), statistic.max_load()));
+
+            let is_too_heavy =
+                load > 0 && limits.is_some_and(|(_, max_load)| carried + load > max_load);
+
+            if is_too_heavy {
+                if !positions.contains(pickup.item) {
+                    if let Some(position) = positions.get(pickup.collector).copied() {
+                        let _ = positions.insert(pickup.item, position);
+                    }
+                }
+
+                if pickup.collector == *player {
+                    let item_name = names
+                        .get(pickup.item)
+                        .map(|name| identification.display(name))
+                        .unwrap_or_default();
+
+                    game_log.push(
+                        &localization::tr_with("item.too_heavy", &[&item_name]),
+                        LogCategory::Item,
+                    );
+                }
+
+                continue;
+            }
+
+            let stack = Stackable::find_stack(
+                pickup.item,
+                pickup.collector,
+                &entities,
+                &backpack,
+                &names,
+                &stackables,
+            );
+
+            let stored = match stack {
+                Some(stack) => {
+                    if let Some(stackable) = stackables.get_mut(stack) {
+                        stackable.quantity += quantity;
+                    }
+
+                    entities
+                        .delete(pickup.item)
+                        .map_err(|_| GameError::EntityDeletion {
+                            entity: pickup.item,
+                        })
+                }
+                None => backpack
+                    .insert(
+                        pickup.item,
+                        Loot {
+                            owner: pickup.collector,
+                        },
+                    )
+                    .map(|_| ())
+                    .map_err(|_| GameError::component_insertion::<Loot>(pickup.item)),
+            };
+
+            let result = stored.and_then(|_| {
+                positions.remove(pickup.item);
+
+                let collector_name = names.require(pickup.collector)?;
+                let item_name = names.require(pickup.item)?;
+
+                if pickup.collector == *player {
+                    for _ in 0..quantity {
+                        quest_log.record(Deed::Fetch(&item_name.name));
+                    }
+                }
+
+                Ok(localization::tr_with(
+                    "item.picked_up",
+                    &[
+                        &collector_name.display(),
+                        &identification.display(item_name),
+                    ],
+                ))
+            });
+
+            match result {
+                Ok(message) => game_log.push(&message, LogCategory::Item),
+                Err(error) => game_log.push_error(&error),
+            }
+
+            // Crossing the carrying capacity slows the player down
+            let is_burdened = limits
+                .is_some_and(|(capacity, _)| carried <= capacity && carried + load > capacity);
+
+            if is_burdened && pickup.collector == *player {
+                game_log.push(&localization::tr("item.burdened"), LogCategory::Item);
+            }
+        }
+    }
+}
+
+/// System that handles [DropEvent]s
+/// of all [Entity] objects and removes the
+/// corresponding [Item] from their inventory
+/// and set it [Position] to render it on the map.
+///
+/// # Notes
+/// * Worn items are taken off before they are dropped,
+///   unless they are [Cursed], which can't be dropped.
+///
+#[derive(Default)]
+pub struct ItemDropSystem {
+    /// Reader for the [DropEvent] channel,
+    /// registered during the setup of the system.
+    reader: Option<ReaderId<DropEvent>>,
+}
+
+impl<'a> System<'a> for ItemDropSystem {
+    type SystemData = (
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Identification>,
+        Read<'a, EventChannel<DropEvent>>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Cursed>,
+        WriteStorage<'a, Loot>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Equipped>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_event_reader::<DropEvent>(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut game_log,
+            identification,
+            drop_events,
+            names,
+            cursed,
+            mut loot,
+            mut positions,
+            mut equipped,
+        ) = data;
+
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("ItemDropSystem has not been set up!");
+
+        for drop in drop_events.read(reader) {
+            // Cursed items stick to their wearer
+            if equipped.contains(drop.item) && cursed.contains(drop.item) {
+                let owner_name = names
+                    .get(drop.owner)
+                    .map_or_else(|| localization::tr("name.someone"), Name::display);
+                let item_name = names.get(drop.item).map_or_else(
+                    || localization::tr("name.item"),
+                    |name| identification.display(name),
+                );
+
+                game_log.push(
+                    &localization::tr_with("item.cursed.stuck", &[&owner_name, &item_name]),
+                    LogCategory::Item,
+                );
+                continue;
+            }
+
+            equipped.remove(drop.item);
+
+            let result =
+                ItemDropSystem::drop_item(drop, &identification, &names, &mut loot, &mut positions);
+
+            match result {
+                Ok(message) => game_log.push(&message, LogCategory::Item),
+                Err(error) => game_log.push_error(&error),
+            }
+        }
+    }
+}
+
+impl ItemDropSystem {
+    /// Removes the item of the passed `drop` request from the inventory of
+    /// its owner and places it at the owners [Position]. Returns the message
+    /// describing the drop for the [GameLog].
+    ///
+    /// # Arguments
+    /// * `drop`: The [DropEvent] to process.
+    /// * `identification`: The [Identification] resolving the item's name.
+    /// * `names`: The [Name] storage of the `ecs`.
+    /// * `loot`: The [Loot] storage of the `ecs`.
+    /// * `positions`: The [Position] storage of the `ecs`.
+    ///
+    /// # Errors
+    /// * If the owner has no [Position] or the item's [Position] couldn't be set.
+    /// * If the owner or item has no [Name].
+    ///
+    fn drop_item(
+        drop: &DropEvent,
+        identification: &Identification,
+        names: &ReadStorage<Name>,
+        loot: &mut WriteStorage<Loot>,
+        positions: &mut WriteStorage<Position>,
+    ) -> GameResult<String> {
+        let drop_position = *positions.require(drop.owner)?;
+
+        positions
+            .insert(drop.item, drop_position)
+            .map_err(|_| GameError::component_insertion::<Position>(drop.item))?;
+        loot.remove(drop.item);
+
+        let entity_name = names.require(drop.owner)?.display();
+        let item_name = identification.display(names.require(drop.item)?);
+
+        Ok(localization::tr_with(
+            "item.dropped",
+            &[&entity_name, &item_name],
+        ))
+    }
+}
+
+/// System that handles the [EquipEvent]s of all [Entity] objects and
+/// puts the corresponding [Equippable] item on, or takes it off, if
+/// it's already worn.
+///
+/// # Notes
+/// * Putting on an item takes off the items it displaces, i.e. the
+///   item worn in the same slot, the off hand for two-handed weapons
+///   and two-handed weapons for anything worn in the off hand, see
+///   [Equippable::displaces].
+/// * One-handed melee weapons are wielded in the off hand,
+///   if the [EquipEvent] requests it.
+/// * [Cursed] items can't be taken off and lock their slot, until the
+///   curse is removed. The curse is revealed, once the item is worn.
+///
+#[derive(Default)]
+pub struct ItemEquipSystem {
+    /// Reader for the [EquipEvent] channel,
+    /// registered during the setup of the system.
+    reader: Option<ReaderId<EquipEvent>>,
+}
+
+impl<'a> System<'a> for ItemEquipSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Identification>,
+        Read<'a, EventChannel<EquipEvent>>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Equippable>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, Cursed>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_event_reader::<EquipEvent>(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut game_log,
+            identification,
+            equip_events,
+            names,
+            equippables,
+            mut equipped,
+            mut cursed,
+        ) = data;
+
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("ItemEquipSystem has not been set up!");
+
+        let display = |entity: Entity| {
+            names.get(entity).map_or_else(
+                || localization::tr("name.item"),
+                |name| identification.display(name),
+            )
+        };
+
+        for equip in equip_events.read(reader) {
+            let owner_name = names
+                .get(equip.owner)
+                .map_or_else(|| localization::tr("name.someone"), Name::display);
+
+            let equippable = match equippables.get(equip.item) {
+                Some(equippable) => equippable,
+                None => {
+                    game_log.push(
+                        &localization::tr_with("item.not_equippable", &[&display(equip.item)]),
+                        LogCategory::Item,
+                    );
+                    continue;
+                }
+            };
+
+            let slot = if equip.off_hand && equippable.is_off_hand_weapon() {
+                EquipmentSlot::Shield
+            } else {
+                equippable.slot
+            };
+
+            let is_worn = equipped
+                .get(equip.item)
+                .is_some_and(|worn| worn.owner == equip.owner);
+
+            // Taking off the item, if it's already worn, otherwise the items it displaces
+            let worn_items = if is_worn {
+                vec![equip.item]
+            } else {
+                (&entities, &equipped, &equippables)
+                    .join()
+                    .filter(|(_, worn, other)| {
+                        worn.owner == equip.owner && equippable.displaces(slot, other, worn.slot)
+                    })
+                    .map(|(item, _, _)| item)
+                    .collect::<Vec<_>>()
+            };
+
+            if let Some(stuck_item) = worn_items.iter().find(|item| cursed.contains(**item)) {
+                game_log.push(
+                    &localization::tr_with(
+                        "item.cursed.stuck",
+                        &[&owner_name, &display(*stuck_item)],
+                    ),
+                    LogCategory::Item,
+                );
+                continue;
+            }
+
+            for item in worn_items.iter() {
+                equipped.remove(*item);
+                game_log.push(
+                    &localization::tr_with("item.unequipped", &[&owner_name, &display(*item)]),
+                    LogCategory::Item,
+                );
+            }
+
+            if is_worn {
+                continue;
+            }
+
+            let worn = Equipped {
+                owner: equip.owner,
+                slot,
+            };
+
+            if equipped.insert(equip.item, worn).is_err() {
+                game_log.push_error(&GameError::component_insertion::<Equipped>(equip.item));
+                continue;
+            }
+
+            game_log.push(
+                &localization::tr_with("item.equipped", &[&owner_name, &display(equip.item)]),
+                LogCategory::Item,
+            );
+
+            if let Some(curse) = cursed.get_mut(equip.item) {
+                curse.revealed = true;
+                game_log.push(
+                    &localization::tr_with(
+                        "item.cursed.equipped",
+                        &[&display(equip.item), &owner_name],
+                    ),
+                    LogCategory::Item,
+                );
+            }
+        }
+    }
+}
+
+/// System used for processing [UseItem] requests in
+/// the `ecs`. The effect of an item is defined by the
+/// content script referenced through its [Scripted] component.
+///
+/// # Notes
+/// * Healing and damage are shown through particles on the user,
+///   items used on a target burst on the targeted tile. Items with an
+///   [AreaOfEffect] explode on all tiles of the area instead.
+/// * Damage, status effects and cures of items used on a target hit the
+///   entities with [Statistics] on the targeted tile instead of the user.
+///   Items with an [AreaOfEffect] hit every entity in the area around
+///   the target, including the user. Fire is set on the same tiles
+///   through an [IgniteEvent].
+/// * The damage is dealt through a [DamageEvent] and the status effects are
+///   inflicted in the name of the user, so it's credited with the defeat
+///   of its victims.
+/// * Teleports only move the user onto tiles, that aren't blocked.
+///   Teleporting onto a blocked target fails and leaves the user in
+///   place.
+/// * Consuming an item of a [Stackable] stack only uses up one of them.
+/// * Using an unidentified item identifies all items of its kind, see
+///   [Identification]. The messages of the script still use the
+///   appearance of the item, as its nature is revealed afterwards.
+///
+pub struct ItemUseSystem {}
+
+impl<'a> System<'a> for ItemUseSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, Point>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, Identification>,
+        ReadExpect<'a, ScriptEngine>,
+        Write<'a, ParticleBuilder>,
+        Write<'a, EventChannel<DamageEvent>>,
+        Write<'a, EventChannel<StatusEffectEvent>>,
+        Write<'a, EventChannel<CureEvent>>,
+        Write<'a, EventChannel<IgniteEvent>>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Scripted>,
+        ReadStorage<'a, AreaOfEffect>,
+        ReadStorage<'a, Loot>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, FOV>,
+        WriteStorage<'a, UseItem>,
+        WriteStorage<'a, Statistics>,
+        WriteStorage<'a, Cursed>,
+        WriteStorage<'a, Stackable>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            map,
+            player,
+            mut player_position,
+            mut rng,
+            mut game_log,
+            mut identification,
+            script_engine,
+            mut particle_builder,
+            mut damage_events,
+            mut status_effect_events,
+            mut cure_events,
+            mut ignite_events,
+            names,
+            scripts,
+            areas,
+            backpack,
+            mut positions,
+            mut fovs,
+            mut use_items,
+            mut statistics,
+            mut cursed,
+            mut stackables,
+        ) = data;
+
+        for (entity, usage) in (&entities, &use_items).join() {
+            let scripted = match scripts.get(usage.item) {
+                Some(scripted) => scripted,
+                None => continue,
+            };
+
+            let (hp, hp_max) = match statistics.get(entity) {
+                Some(statistic) => (statistic.hp, statistic.hp_max),
+                None => continue,
+            };
+
+            let user_name = names
+                .get(entity)
+                .map_or_else(|| localization::tr("name.someone"), Name::display);
+            let item_name = names.get(usage.item).map_or_else(
+                || localization::tr("name.item"),
+                |name| identification.display(name),
+            );
+
+            let context = ScriptContext::new(&user_name, &item_name, hp, hp_max);
+
+            let effects = match script_engine.run_hook(&scripted.script, HOOK_ON_USE, context) {
+                Ok(effects) => effects,
+                Err(error) => {
+                    game_log.push_error(&error);
+                    continue;
+                }
+            };
+
+            let mut user_position = positions.get(entity).map(Position::to_point);
+            let area = usage.target.zip(areas.get(usage.item));
+
+            if let Some((target, area)) = area {
+                particle_builder.explosion(target, area);
+            } else if let Some(target) = usage.target {
+                particle_builder.burst(target, 1);
+            }
+
+            // Entities on the targeted tile or in the area around it, or only the user
+            let hit_entities = match usage.target {
+                Some(target) => {
+                    let area = area.map_or(AreaOfEffect { radius: 0 }, |(_, area)| area.clone());
+
+                    (&entities, &positions, &statistics)
+                        .join()
+                        .filter(|(_, position, _)| area.covers(&target, &position.to_point()))
+                        .map(|(hit_entity, position, _)| (hit_entity, Some(position.to_point())))
+                        .collect::<Vec<_>>()
+                }
+                None => vec![(entity, user_position)],
+            };
+
+            for effect in effects {
+                match effect {
+                    ScriptEffect::Heal(amount) => {
+                        if let Some(statistic) = statistics.get_mut(entity) {
+                            statistic.hp = i32::min(statistic.hp_max, statistic.hp + amount);
+                        }
+                        if let Some(position) = user_position {
+                            particle_builder.heal(position);
+                        }
+                    }
+                    ScriptEffect::Damage(amount) => {
+                        for (hit_entity, hit_position) in hit_entities.iter() {
+                            damage_events.single_write(DamageEvent {
+                                target: *hit_entity,
+                                amount,
+                                source: Some(entity),
+                            });
+                            if let Some(position) = hit_position {
+                                particle_builder.slash(*position);
+                            }
+                        }
+                    }
+                    ScriptEffect::Inflict(effect) => {
+                        for (hit_entity, _) in hit_entities.iter() {
+                            status_effect_events.single_write(StatusEffectEvent {
+                                target: *hit_entity,
+                                effect: StatusEffect {
+                                    inflicter: Some(entity),
+                                    ..effect
+                                },
+                            });
+                        }
+                    }
+                    ScriptEffect::Cure(kind) => {
+                        for (hit_entity, _) in hit_entities.iter() {
+                            cure_events.single_write(CureEvent {
+                                target: *hit_entity,
+                                kind,
+                            });
+                        }
+                    }
+                    ScriptEffect::Ignite => {
+                        let target = usage.target.or(user_position);
+                        let radius = area.map_or(0, |(_, area)| area.radius);
+
+                        if let Some(target) = target {
+                            ignite_events.single_write(IgniteEvent { target, radius });
+                        }
+                    }
+                    ScriptEffect::Teleport => {
+                        let destination = match usage.target {
+                            Some(target) => Some(target).filter(|target| {
+                                map.check_idx(target.x, target.y)
+                                    && !map.is_tile_blocked(target.x, target.y)
+                            }),
+                            None => map.random_walkable_tile(&mut rng),
+                        };
+
+                        let (destination, position) = match (destination, positions.get_mut(entity))
+                        {
+                            (Some(destination), Some(position)) => (destination, position),
+                            _ => {
+                                game_log.push(
+                                    &localization::tr_with("item.teleport.blocked", &[&user_name]),
+                                    LogCategory::Item,
+                                );
+                                continue;
+                            }
+                        };
+
+                        position.x = destination.x;
+                        position.y = destination.y;
+                        user_position = Some(destination);
+
+                        if entity == *player {
+                            *player_position = destination;
+                        }
+
+                        if let Some(fov) = fovs.get_mut(entity) {
+                            fov.mark_as_dirty();
+                        }
+
+                        particle_builder.teleport(destination);
+                    }
+                    ScriptEffect::Identify => {
+                        let carried = (&backpack, &names)
+                            .join()
+                            .filter(|(loot, _)| loot.owner == entity)
+                            .map(|(_, name)| name);
+
+                        for name in carried {
+                            let unidentified_name = identification.display(name);
+
+                            if identification.identify(&name.name) {
+                                game_log.push(
+                                    &localization::tr_with(
+                                        "item.identified",
+                                        &[&unidentified_name, &name.display()],
+                                    ),
+                                    LogCategory::Item,
+                                );
+                            }
+                        }
+                    }
+                    ScriptEffect::RemoveCurse => {
+                        let carried = (&entities, &backpack, &names)
+                            .join()
+                            .filter(|(_, loot, _)| loot.owner == entity)
+                            .map(|(item, _, name)| (item, name));
+
+                        for (item, name) in carried {
+                            if cursed.remove(item).is_some() {
+                                game_log.push(
+                                    &localization::tr_with(
+                                        "item.curse_removed",
+                                        &[&identification.display(name)],
+                                    ),
+                                    LogCategory::Item,
+                                );
+                            }
+                        }
+                    }
+                    ScriptEffect::Log(message) => game_log.push(&message, LogCategory::Item),
+                    ScriptEffect::Consume => match stackables.get_mut(usage.item) {
+                        // Only a single item of a stack is used up
+                        Some(stackable) if stackable.quantity > 1 => stackable.quantity -= 1,
+                        _ => {
+                            if entities.delete(usage.item).is_err() {
+                                game_log
+                                    .push_error(&GameError::EntityDeletion { entity: usage.item });
+                            }
+                        }
+                    },
+                }
+            }
+
+            // Using an item reveals its kind for the rest of the run
+            if let Some(name) = names.get(usage.item) {
+                if identification.identify(&name.name) {
+                    game_log.push(
+                        &localization::tr_with("item.identified", &[&item_name, &name.display()]),
+                        LogCategory::Item,
+                    );
+                }
+            }
+        }
+
+        use_items.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestWorldBuilder;
+
+    #[test]
+    fn health_potion_heals_user_and_is_consumed() {
+        let mut world = TestWorldBuilder::new()
+            .with_player(2, 2)
+            .with_potion(2, 2)
+            .build();
+
+        let player = world.player();
+        let potion = world.items[0];
+
+        if let Some(statistic) = world.ecs.write_storage::<Statistics>().get_mut(player) {
+            statistic.hp = 10;
+        }
+
+        world
+            .ecs
+            .write_storage::<UseItem>()
+            .insert(
+                player,
+                UseItem {
+                    item: potion,
+                    target: None,
+                },
+            )
+            .expect("Unable to insert the use request!");
+
+        let mut item_use = world.setup_system(ItemUseSystem {});
+        world.run_system(&mut item_use);
+
+        let hp = world
+            .ecs
+            .read_storage::<Statistics>()
+            .get(player)
+            .map(|statistic| statistic.hp)
+            .expect("The player has no statistics!");
+
+        assert_eq!(hp, 18);
+        assert!(!world.ecs.is_alive(potion));
+        assert!(world.ecs.read_storage::<UseItem>().get(player).is_none());
+    }
+}
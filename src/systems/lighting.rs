@@ -0,0 +1,73 @@
+//! Systems computing the light falling onto the tiles of the map.
+
+use rltk::RGB;
+use specs::prelude::*;
+
+use crate::fov::FovAlgorithm;
+use crate::settings::Settings;
+use crate::{pythagoras_distance, LightSource, Map, Position, SystemDispatcherBuilder};
+
+/// Registers the systems of the module with the passed `builder`.
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder.with(LightingSystem {}, "lighting", &["monster_ai"])
+}
+
+/// System computing the [Map::light_levels] from all
+/// [LightSource]s with a [Position] on the map.
+///
+/// # Notes
+/// * A light only reaches the tiles visible from its position,
+///   using the same [FovAlgorithm] as the field of view.
+///   Its intensity fades linearly with the distance to the
+///   light, until it vanishes at the range of the light.
+/// * The light of overlapping sources adds up.
+/// * Runs after the monsters have moved, so glowing
+///   monsters light the tiles they have moved to.
+/// * Carried items have no [Position] and don't emit light.
+///
+pub struct LightingSystem {}
+
+impl<'a> System<'a> for LightingSystem {
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, LightSource>,
+        Option<Read<'a, Settings>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut map, positions, light_sources, settings) = data;
+
+        let algorithm = settings
+            .map_or(FovAlgorithm::default(), |settings| settings.fov_algorithm)
+            .field_of_view();
+        let mut light_levels = vec![RGB::new(); map.tiles.len()];
+
+        for (position, light) in (&positions, &light_sources).join() {
+            let origin = position.to_point();
+            let range = light.range.max(1) as f32;
+
+            for tile in algorithm.compute(origin, light.range, &map) {
+                if !map.check_idx(tile.x, tile.y) {
+                    continue;
+                }
+
+                let intensity = (1.0 - pythagoras_distance(&origin, &tile) / range).max(0.0);
+                let level = &mut light_levels[map.coordinates_to_idx(tile.x, tile.y)];
+
+                *level = RGB::from_f32(
+                    (level.r + light.color.r * intensity).min(1.0),
+                    (level.g + light.color.g * intensity).min(1.0),
+                    (level.b + light.color.b * intensity).min(1.0),
+                );
+            }
+        }
+
+        map.set_light_levels(light_levels);
+    }
+}
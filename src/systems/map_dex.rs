@@ -0,0 +1,295 @@
+//! Systems keeping the tile information of the [Map] up to date.
+
+use std::collections::HashMap;
+
+use specs::prelude::*;
+use specs::world::Index;
+
+use crate::{Collision, Map, Opaque, Position, SystemDispatcherBuilder};
+
+/// Registers the systems of the module with the passed `builder`.
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder.with(MapDexSystem::default(), "map_dex", &["monster_ai"])
+}
+
+/// The state of an [Entity] as it is currently
+/// stored in the spatial index of the [Map].
+#[derive(Clone, Copy, PartialEq)]
+struct IndexedEntity {
+    /// The indexed [Entity].
+    entity: Entity,
+
+    /// X coordinate of the tile containing the entity.
+    x: i32,
+
+    /// Y coordinate of the tile containing the entity.
+    y: i32,
+
+    /// Flag indicating whether or not the
+    /// entity blocks its tile.
+    blocks: bool,
+
+    /// Flag indicating whether or not the
+    /// entity blocks the sight through its tile.
+    opaque: bool,
+}
+
+/// System updating the properties and tile attributes
+/// of the game [Map].
+///
+/// # Notes
+/// * Only entities whose [Position], [Collision] or [Opaque] changed
+///   since the last run are updated in the [Map::tile_contents],
+///   [Map::blocked_tiles] and [Map::opaque_tiles].
+/// * The whole index is rebuilt when a new [Map] is entered.
+/// * The buffers of the system and the [Map::tile_contents] keep their
+///   memory between runs, so updating the index doesn't allocate.
+///
+#[derive(Default)]
+pub struct MapDexSystem {
+    /// Reader for the modification events of the
+    /// [Position] storage, registered during setup.
+    position_reader: Option<ReaderId<ComponentEvent>>,
+
+    /// Reader for the modification events of the
+    /// [Collision] storage, registered during setup.
+    collision_reader: Option<ReaderId<ComponentEvent>>,
+
+    /// Reader for the modification events of the
+    /// [Opaque] storage, registered during setup.
+    opaque_reader: Option<ReaderId<ComponentEvent>>,
+
+    /// All indexed entities by their id.
+    indexed: HashMap<Index, IndexedEntity>,
+
+    /// The depth of the [Map] the index was built for.
+    indexed_depth: Option<i32>,
+
+    /// Buffer collecting the ids of the entities
+    /// changed since the last run.
+    changed: Vec<Index>,
+}
+
+impl MapDexSystem {
+    /// Clears the [Map::tile_contents], [Map::blocked_tiles] and
+    /// [Map::opaque_tiles] and indexes all entities with a [Position] anew.
+    ///
+    /// # Arguments
+    /// * `map`: The [Map] whose index should be rebuilt.
+    /// * `entities`: All entities of the `ecs`.
+    /// * `positions`: The [Position] storage.
+    /// * `collisions`: The [Collision] storage.
+    /// * `opaques`: The [Opaque] storage.
+    ///
+    fn rebuild(
+        &mut self,
+        map: &mut Map,
+        entities: &Entities,
+        positions: &ReadStorage<Position>,
+        collisions: &ReadStorage<Collision>,
+        opaques: &ReadStorage<Opaque>,
+    ) {
+        self.indexed.clear();
+        map.clear_tile_contents();
+        map.refresh_blocked_tiles();
+        map.clear_opaque_tiles();
+
+        for (position, entity) in (positions, entities).join() {
+            self.insert(
+                map,
+                IndexedEntity {
+                    entity,
+                    x: position.x,
+                    y: position.y,
+                    blocks: collisions.contains(entity),
+                    opaque: opaques.contains(entity),
+                },
+            );
+        }
+
+        self.indexed_depth = Some(map.depth);
+    }
+
+    /// Updates the index for the [Entity] with the passed `id` by removing
+    /// its previous entry and inserting it at its current [Position].
+    ///
+    /// # Arguments
+    /// * `map`: The [Map] whose index should be updated.
+    /// * `id`: The id of the changed [Entity].
+    /// * `entities`: All entities of the `ecs`.
+    /// * `positions`: The [Position] storage.
+    /// * `collisions`: The [Collision] storage.
+    /// * `opaques`: The [Opaque] storage.
+    ///
+    fn update(
+        &mut self,
+        map: &mut Map,
+        id: Index,
+        entities: &Entities,
+        positions: &ReadStorage<Position>,
+        collisions: &ReadStorage<Collision>,
+        opaques: &ReadStorage<Opaque>,
+    ) {
+        let entity = entities.entity(id);
+
+        let current = match positions.get(entity) {
+            Some(position) if entities.is_alive(entity) => Some(IndexedEntity {
+                entity,
+                x: position.x,
+                y: position.y,
+                blocks: collisions.contains(entity),
+                opaque: opaques.contains(entity),
+            }),
+            _ => None,
+        };
+
+        // Nothing to do if the entity only has been flagged
+        if current.is_some() && self.indexed.get(&id) == current.as_ref() {
+            return;
+        }
+
+        let previous = self.indexed.remove(&id);
+
+        if let Some(previous) = previous {
+            map.tile_contents_remove(previous.x, previous.y, previous.entity);
+        }
+
+        if let Some(current) = current {
+            self.insert(map, current);
+        }
+
+        if let Some(previous) = previous.filter(|previous| previous.blocks) {
+            refresh_tile_blocked(map, previous.x, previous.y, collisions);
+        }
+
+        if let Some(previous) = previous.filter(|previous| previous.opaque) {
+            refresh_tile_opaque(map, previous.x, previous.y, opaques);
+        }
+    }
+
+    /// Pushes the `indexed` entity to the contents of its tile and marks
+    /// the tile as blocked or opaque if the entity blocks it or the sight.
+    ///
+    /// # Arguments
+    /// * `map`: The [Map] to which the entity should be added.
+    /// * `indexed`: The [IndexedEntity] to add.
+    ///
+    fn insert(&mut self, map: &mut Map, indexed: IndexedEntity) {
+        map.tile_contents_push(indexed.x, indexed.y, indexed.entity);
+
+        if indexed.blocks {
+            map.set_tile_is_blocked(indexed.x, indexed.y, true);
+        }
+
+        if indexed.opaque {
+            map.set_tile_is_opaque(indexed.x, indexed.y, true);
+        }
+
+        self.indexed.insert(indexed.entity.id(), indexed);
+    }
+}
+
+impl<'a> System<'a> for MapDexSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, Map>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Collision>,
+        ReadStorage<'a, Opaque>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.position_reader = Some(world.write_storage::<Position>().register_reader());
+        self.collision_reader = Some(world.write_storage::<Collision>().register_reader());
+        self.opaque_reader = Some(world.write_storage::<Opaque>().register_reader());
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut map, positions, collisions, opaques) = data;
+
+        let position_reader = self
+            .position_reader
+            .as_mut()
+            .expect("MapDexSystem has not been set up!");
+
+        let collision_reader = self
+            .collision_reader
+            .as_mut()
+            .expect("MapDexSystem has not been set up!");
+
+        let opaque_reader = self
+            .opaque_reader
+            .as_mut()
+            .expect("MapDexSystem has not been set up!");
+
+        // Collect the ids of all changed entities
+        let mut changed = std::mem::take(&mut self.changed);
+        changed.clear();
+        changed.extend(
+            positions
+                .channel()
+                .read(position_reader)
+                .chain(collisions.channel().read(collision_reader))
+                .chain(opaques.channel().read(opaque_reader))
+                .map(|event| match event {
+                    ComponentEvent::Inserted(id)
+                    | ComponentEvent::Modified(id)
+                    | ComponentEvent::Removed(id) => *id,
+                }),
+        );
+        changed.sort_unstable();
+        changed.dedup();
+
+        // A new map has been entered, so the whole index is stale
+        if self.indexed_depth != Some(map.depth) {
+            self.rebuild(&mut map, &entities, &positions, &collisions, &opaques);
+        } else {
+            for id in changed.iter() {
+                self.update(&mut map, *id, &entities, &positions, &collisions, &opaques);
+            }
+        }
+
+        self.changed = changed;
+    }
+}
+
+/// Sets the blocked flag of the tile at the `x` and `y` position
+/// based on its [crate::TileType] and the entities it contains.
+///
+/// # Arguments
+/// * `map`: The [Map] containing the tile.
+/// * `x`: X coordinate of the tile.
+/// * `y`: Y coordinate of the tile.
+/// * `collisions`: The [Collision] storage.
+///
+fn refresh_tile_blocked(map: &mut Map, x: i32, y: i32, collisions: &ReadStorage<Collision>) {
+    let blocked = !map.get_tile(x, y).is_walkable()
+        || map
+            .tile_contents_get(x, y)
+            .any(|entity| collisions.contains(entity));
+
+    map.set_tile_is_blocked(x, y, blocked);
+}
+
+/// Sets the opaque flag of the tile at the `x` and `y` position
+/// based on the entities it contains.
+///
+/// # Arguments
+/// * `map`: The [Map] containing the tile.
+/// * `x`: X coordinate of the tile.
+/// * `y`: Y coordinate of the tile.
+/// * `opaques`: The [Opaque] storage.
+///
+fn refresh_tile_opaque(map: &mut Map, x: i32, y: i32, opaques: &ReadStorage<Opaque>) {
+    let opaque = map
+        .tile_contents_get(x, y)
+        .any(|entity| opaques.contains(entity));
+
+    map.set_tile_is_opaque(x, y, opaque);
+}
@@ -0,0 +1,101 @@
+//! Module containing all systems of the game.
+//!
+//! The systems are grouped into subsystems by their feature. Every
+//! subsystem exposes a `register` function, that adds its systems to
+//! the [SystemDispatcherBuilder] and inserts the resources they need,
+//! so new features only have to be added to the [SUBSYSTEMS] list.
+
+use specs::prelude::*;
+
+use super::particles;
+use super::{SystemDispatcher, SystemDispatcherBuilder};
+
+mod view;
+pub use view::FOVSystem;
+
+mod ai;
+pub use ai::MonsterAI;
+
+mod lighting;
+pub use lighting::LightingSystem;
+
+mod map_dex;
+pub use map_dex::MapDexSystem;
+
+mod combat;
+pub use combat::{DamageSystem, MeleeCombatSystem, RangedCombatSystem};
+
+mod items;
+pub use items::{ItemCollectionSystem, ItemDropSystem, ItemEquipSystem, ItemUseSystem};
+
+mod trade;
+pub use trade::TheftSystem;
+
+mod spells;
+pub use spells::SpellCastSystem;
+
+mod status;
+pub use status::StatusEffectSystem;
+
+mod terrain;
+pub use terrain::TerrainSystem;
+
+mod fire;
+pub use fire::FireSpreadSystem;
+
+mod regeneration;
+pub use regeneration::{RegeneratesSystem, RegenerationSystem};
+
+mod turns;
+pub use turns::TurnQueueSystem;
+
+/// Signature of the `register` function every subsystem exposes.
+pub type SubsystemRegistration = fn(&mut World, SystemDispatcherBuilder) -> SystemDispatcherBuilder;
+
+/// The `register` functions of all subsystems in the order
+/// in which their systems are executed.
+///
+/// # Notes
+/// * Systems can only depend on systems of subsystems, that
+///   are registered before them.
+/// * Subsystems insert the resources, that only their systems
+///   own, e.g. the [DamageSources](super::DamageSources), in their
+///   `register` function. Shared resources, e.g. the [Map](super::Map),
+///   are inserted by the game itself.
+///
+pub const SUBSYSTEMS: &[SubsystemRegistration] = &[
+    particles::register,
+    view::register,
+    ai::register,
+    lighting::register,
+    map_dex::register,
+    combat::register,
+    items::register,
+    trade::register,
+    spells::register,
+    status::register,
+    combat::register_damage,
+    terrain::register,
+    fire::register,
+    regeneration::register,
+    turns::register,
+];
+
+/// Creates the [SystemDispatcher] executing all systems of the game,
+/// by letting every subsystem in [SUBSYSTEMS] register its systems.
+///
+/// # Arguments
+/// * `ecs`: The [World] the systems will be executed on.
+///
+/// # Notes
+/// * Systems reading or writing the same resources are never executed
+///   in parallel, regardless of their declared dependencies.
+///
+pub fn build_dispatcher(ecs: &mut World) -> SystemDispatcher {
+    SUBSYSTEMS
+        .iter()
+        .fold(SystemDispatcherBuilder::new(), |builder, register| {
+            register(ecs, builder)
+        })
+        .build()
+}
@@ -0,0 +1,135 @@
+//! Systems restoring the resources of the player and of
+//! regenerating monsters over time.
+
+use specs::prelude::*;
+
+use crate::{
+    config, Mana, Monster, Player, Position, Regenerates, RunState, Statistics,
+    SystemDispatcherBuilder, TurnCounter, FOV,
+};
+
+/// Registers the systems of the module with the passed `builder`.
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder
+        .with(RegenerationSystem {}, "regeneration", &["status_effects"])
+        .with(
+            RegeneratesSystem {},
+            "regenerates",
+            &["status_effects", "fire_spread"],
+        )
+}
+
+/// System restoring [config::REGENERATION_HP] and [config::REGENERATION_MP]
+/// to the player every [config::REGENERATION_INTERVAL] turns.
+///
+/// # Notes
+/// * Runs at the end of the [RunState::MonsterTurn], before
+///   the [TurnCounter] is advanced to the next turn.
+/// * The player doesn't regenerate while in combat, meaning
+///   while a [Monster] is within the player's [FOV].
+///
+pub struct RegenerationSystem {}
+
+impl<'a> System<'a> for RegenerationSystem {
+    type SystemData = (
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, TurnCounter>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, FOV>,
+        WriteStorage<'a, Statistics>,
+        WriteStorage<'a, Mana>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            run_state,
+            turn_counter,
+            players,
+            monsters,
+            positions,
+            fovs,
+            mut statistics,
+            mut manas,
+        ) = data;
+
+        if *run_state != RunState::MonsterTurn
+            || (turn_counter.turn + 1) % config::REGENERATION_INTERVAL != 0
+        {
+            return;
+        }
+
+        for (_, fov, statistic, mana) in (
+            &players,
+            &fovs,
+            (&mut statistics).maybe(),
+            (&mut manas).maybe(),
+        )
+            .join()
+        {
+            let in_combat = (&monsters, &positions)
+                .join()
+                .any(|(_, position)| fov.contains(&position.to_point()));
+
+            if in_combat {
+                continue;
+            }
+
+            // Dead entities are removed by the damage system and stay dead
+            if let Some(statistic) = statistic.filter(|statistic| statistic.hp > 0) {
+                statistic.hp = i32::min(statistic.hp_max, statistic.hp + config::REGENERATION_HP);
+            }
+
+            if let Some(mana) = mana {
+                mana.mp = i32::min(mana.mp_max, mana.mp + config::REGENERATION_MP);
+            }
+        }
+    }
+}
+
+/// System healing all entities, that [Regenerates],
+/// by their `per_turn` hp every turn.
+///
+/// # Notes
+/// * Runs at the end of the [RunState::MonsterTurn], after the fire
+///   has burnt the entities standing in it, see the `FireSpreadSystem`.
+/// * Entities hit by fire don't regenerate, until the
+///   [config::FIRE_REGENERATION_PAUSE] has passed.
+///
+pub struct RegeneratesSystem {}
+
+impl<'a> System<'a> for RegeneratesSystem {
+    type SystemData = (
+        ReadExpect<'a, RunState>,
+        WriteStorage<'a, Regenerates>,
+        WriteStorage<'a, Statistics>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (run_state, mut regenerates, mut statistics) = data;
+
+        if *run_state != RunState::MonsterTurn {
+            return;
+        }
+
+        for (regeneration, statistic) in (&mut regenerates, &mut statistics).join() {
+            // Dead entities are removed by the damage system and stay dead
+            if statistic.hp <= 0 {
+                continue;
+            }
+
+            if regeneration.scorched > 0 {
+                regeneration.scorched -= 1;
+                continue;
+            }
+
+            statistic.hp = i32::min(statistic.hp_max, statistic.hp + regeneration.per_turn);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Systems handling the spells cast by entities.
+
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::localization;
+use crate::particles::ParticleBuilder;
+use crate::raws::Raws;
+use crate::scripting::{ScriptContext, ScriptEffect, ScriptEngine, HOOK_ON_CAST};
+use crate::{
+    AreaOfEffect, CastSpell, DamageEvent, GameLog, IgniteEvent, LogCategory, Mana, Name, Position,
+    Statistics, StatusEffect, StatusEffectEvent, SystemDispatcherBuilder,
+};
+
+/// Registers the systems of the module with the passed `builder`.
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder.with(SpellCastSystem {}, "spell_cast", &[])
+}
+
+/// System used for processing [CastSpell] requests in the `ecs`. Mirrors
+/// the `ItemUseSystem`, the effect of a spell is defined by the content
+/// script referenced through its definition in the [Raws].
+///
+/// # Notes
+/// * The mana cost of the spell is spent before its script runs. Casters
+///   without enough [Mana] fail and keep their mana.
+/// * Healing affects the caster. Damage and status effects of spells cast
+///   on a target hit the entities with [Statistics] on the targeted tile,
+///   or in the area around it, if the spell has a radius.
+/// * The damage is dealt through a [DamageEvent] and the status effects are
+///   inflicted in the name of the caster, so it's credited with the defeat
+///   of its victims.
+/// * Fire is set on the tiles hit by the spell, or under the
+///   caster, through an [IgniteEvent].
+/// * Only healing, damage, status effects, fire and log messages are
+///   supported, other effects requested by the script are ignored.
+///
+pub struct SpellCastSystem {}
+
+impl<'a> System<'a> for SpellCastSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Raws>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, ScriptEngine>,
+        Write<'a, ParticleBuilder>,
+        Write<'a, EventChannel<DamageEvent>>,
+        Write<'a, EventChannel<StatusEffectEvent>>,
+        Write<'a, EventChannel<IgniteEvent>>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, CastSpell>,
+        WriteStorage<'a, Mana>,
+        WriteStorage<'a, Statistics>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            raws,
+            mut game_log,
+            script_engine,
+            mut particle_builder,
+            mut damage_events,
+            mut status_effect_events,
+            mut ignite_events,
+            names,
+            positions,
+            mut cast_spells,
+            mut manas,
+            mut statistics,
+        ) = data;
+
+        for (entity, cast) in (&entities, &cast_spells).join() {
+            let spell = match raws.spell(&cast.spell) {
+                Some(spell) => spell,
+                None => continue,
+            };
+
+            let (hp, hp_max) = match statistics.get(entity) {
+                Some(statistic) => (statistic.hp, statistic.hp_max),
+                None => continue,
+            };
+
+            let caster_name = names
+                .get(entity)
+                .map_or_else(|| localization::tr("name.someone"), Name::display);
+            let spell_name = localization::entity_name(&spell.name);
+
+            match manas.get_mut(entity) {
+                Some(mana) if mana.mp >= spell.mana_cost => mana.mp -= spell.mana_cost,
+                _ => {
+                    game_log.push(
+                        &localization::tr_with("spell.no_mana", &[&caster_name, &spell_name]),
+                        LogCategory::Combat,
+                    );
+                    continue;
+                }
+            }
+
+            let context = ScriptContext::new(&caster_name, &spell_name, hp, hp_max);
+
+            let effects = match script_engine.run_hook(&spell.script, HOOK_ON_CAST, context) {
+                Ok(effects) => effects,
+                Err(error) => {
+                    game_log.push_error(&error);
+                    continue;
+                }
+            };
+
+            let caster_position = positions.get(entity).map(Position::to_point);
+            let area = AreaOfEffect {
+                radius: spell.radius.unwrap_or(0),
+            };
+
+            if let Some(target) = cast.target {
+                if spell.radius.is_some() {
+                    particle_builder.explosion(target, &area);
+                } else {
+                    particle_builder.burst(target, 1);
+                }
+            }
+
+            // Entities on the targeted tile or in the area around it, or only the caster
+            let hit_entities = match cast.target {
+                Some(target) => (&entities, &positions, &statistics)
+                    .join()
+                    .filter(|(_, position, _)| area.covers(&target, &position.to_point()))
+                    .map(|(hit_entity, position, _)| (hit_entity, Some(position.to_point())))
+                    .collect::<Vec<_>>(),
+                None => vec![(entity, caster_position)],
+            };
+
+            for effect in effects {
+                match effect {
+                    ScriptEffect::Heal(amount) => {
+                        if let Some(statistic) = statistics.get_mut(entity) {
+                            statistic.hp = i32::min(statistic.hp_max, statistic.hp + amount);
+                        }
+                        if let Some(position) = caster_position {
+                            particle_builder.heal(position);
+                        }
+                    }
+                    ScriptEffect::Damage(amount) => {
+                        for (hit_entity, hit_position) in hit_entities.iter() {
+                            damage_events.single_write(DamageEvent {
+                                target: *hit_entity,
+                                amount,
+                                source: Some(entity),
+                            });
+                            if let Some(position) = hit_position {
+                                particle_builder.slash(*position);
+                            }
+                        }
+                    }
+                    ScriptEffect::Inflict(effect) => {
+                        for (hit_entity, _) in hit_entities.iter() {
+                            status_effect_events.single_write(StatusEffectEvent {
+                                target: *hit_entity,
+                                effect: StatusEffect {
+                                    inflicter: Some(entity),
+                                    ..effect
+                                },
+                            });
+                        }
+                    }
+                    ScriptEffect::Ignite => {
+                        if let Some(target) = cast.target.or(caster_position) {
+                            ignite_events.single_write(IgniteEvent {
+                                target,
+                                radius: area.radius,
+                            });
+                        }
+                    }
+                    ScriptEffect::Log(message) => game_log.push(&message, LogCategory::Combat),
+                    _ => {}
+                }
+            }
+        }
+
+        cast_spells.clear();
+    }
+}
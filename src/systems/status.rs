@@ -0,0 +1,203 @@
+//! Systems applying and ticking the status effects of the entities.
+
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::{
+    localization, register_event_reader, CureEvent, DamageEvent, GameLog, LogCategory, Name,
+    RunState, Statistics, StatusEffectEvent, StatusEffectKind, StatusEffects,
+    SystemDispatcherBuilder, FOV,
+};
+
+/// Registers the systems of the module with the passed `builder`.
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder.with(
+        StatusEffectSystem::default(),
+        "status_effects",
+        &["melee_combat", "item_use"],
+    )
+}
+
+/// System adding the effects of the [StatusEffectEvent]s to the
+/// [StatusEffects] of their targets and ticking all active effects.
+///
+/// # Notes
+/// * The effects are ticked once per turn, at the end of the
+///   [RunState::MonsterTurn], after all entities have acted.
+///   Poison deals its damage through a [DamageEvent] when it's ticked,
+///   credited to the entity, that inflicted it.
+/// * Effects inflicted during a turn are ticked for
+///   the first time at the end of the next turn.
+/// * The [FOV] of an entity is recalculated, when it's blinded and
+///   when the blindness ends.
+/// * The [CureEvent]s are handled after the [StatusEffectEvent]s,
+///   so a cure also removes an effect inflicted in the same turn.
+///
+#[derive(Default)]
+pub struct StatusEffectSystem {
+    /// Reader for the [StatusEffectEvent] channel,
+    /// registered during the setup of the system.
+    reader: Option<ReaderId<StatusEffectEvent>>,
+
+    /// Reader for the [CureEvent] channel,
+    /// registered during the setup of the system.
+    cure_reader: Option<ReaderId<CureEvent>>,
+}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, RunState>,
+        Read<'a, EventChannel<StatusEffectEvent>>,
+        Read<'a, EventChannel<CureEvent>>,
+        Write<'a, EventChannel<DamageEvent>>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Statistics>,
+        WriteStorage<'a, StatusEffects>,
+        WriteStorage<'a, FOV>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_event_reader::<StatusEffectEvent>(world));
+        self.cure_reader = Some(register_event_reader::<CureEvent>(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut game_log,
+            run_state,
+            status_effect_events,
+            cure_events,
+            mut damage_events,
+            names,
+            statistics,
+            mut status_effects,
+            mut fovs,
+        ) = data;
+
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("StatusEffectSystem has not been set up!");
+        let cure_reader = self
+            .cure_reader
+            .as_mut()
+            .expect("StatusEffectSystem has not been set up!");
+
+        let name_of = |entity: Entity| {
+            names
+                .get(entity)
+                .map_or_else(|| localization::tr("name.someone"), Name::display)
+        };
+
+        if *run_state == RunState::MonsterTurn {
+            let mut cured = Vec::new();
+
+            for (entity, effects) in (&entities, &mut status_effects).join() {
+                let name = name_of(entity);
+
+                for effect in effects.effects.iter_mut() {
+                    if effect.kind == StatusEffectKind::Poison && statistics.contains(entity) {
+                        damage_events.single_write(DamageEvent {
+                            target: entity,
+                            amount: effect.potency,
+                            source: effect.inflicter,
+                        });
+                        game_log.push(
+                            &localization::tr_with(
+                                "status.poison.damage",
+                                &[&name, &effect.potency],
+                            ),
+                            LogCategory::Combat,
+                        );
+                    }
+
+                    effect.duration -= 1;
+                }
+
+                for effect in effects.effects.iter().filter(|effect| effect.duration <= 0) {
+                    let id = format!("status.{}.expired", effect.kind.name());
+                    game_log.push(&localization::tr_with(&id, &[&name]), LogCategory::Combat);
+                    refresh_sight(&mut fovs, entity, effect.kind);
+                }
+
+                effects.effects.retain(|effect| effect.duration > 0);
+
+                if effects.effects.is_empty() {
+                    cured.push(entity);
+                }
+            }
+
+            for entity in cured {
+                status_effects.remove(entity);
+            }
+        }
+
+        for event in status_effect_events.read(reader) {
+            let effects = match status_effects.entry(event.target) {
+                Ok(entry) => entry.or_insert_with(StatusEffects::default),
+                // The target has been removed in the meantime
+                Err(_) => continue,
+            };
+
+            effects.apply(event.effect);
+            refresh_sight(&mut fovs, event.target, event.effect.kind);
+
+            let id = format!("status.{}.applied", event.effect.kind.name());
+            game_log.push(
+                &localization::tr_with(&id, &[&name_of(event.target)]),
+                LogCategory::Combat,
+            );
+        }
+
+        for event in cure_events.read(cure_reader) {
+            let is_cured = status_effects
+                .get_mut(event.target)
+                .is_some_and(|effects| effects.cure(event.kind));
+
+            if !is_cured {
+                continue;
+            }
+
+            if status_effects
+                .get(event.target)
+                .is_some_and(|effects| effects.effects.is_empty())
+            {
+                status_effects.remove(event.target);
+            }
+
+            let id = format!("status.{}.expired", event.kind.name());
+            game_log.push(
+                &localization::tr_with(&id, &[&name_of(event.target)]),
+                LogCategory::Combat,
+            );
+            refresh_sight(&mut fovs, event.target, event.kind);
+        }
+    }
+}
+
+/// Marks the [FOV] of the passed `entity` as dirty, if the
+/// `kind` of effect, that has begun or ended, changes its sight.
+///
+/// # Arguments
+/// * `fovs`: The [FOV] storage of the `ecs`.
+/// * `entity`: The entity suffering from the effect.
+/// * `kind`: The [StatusEffectKind] of the effect.
+///
+fn refresh_sight(fovs: &mut WriteStorage<FOV>, entity: Entity, kind: StatusEffectKind) {
+    if kind != StatusEffectKind::Blindness {
+        return;
+    }
+
+    if let Some(fov) = fovs.get_mut(entity) {
+        fov.mark_as_dirty();
+    }
+}
@@ -0,0 +1,95 @@
+//! Systems applying the effects of hazardous terrain.
+
+use specs::prelude::*;
+
+use crate::{
+    config, localization, GameLog, LogCategory, Map, Name, Player, Position, Regenerates, RunState,
+    Statistics, SystemDispatcherBuilder, TileType,
+};
+
+/// Registers the systems of the module with the passed `builder`.
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder.with(TerrainSystem {}, "terrain", &["monster_ai"])
+}
+
+/// System damaging the entities standing on [TileType::LAVA]
+/// and removing the ones, that fell into a [TileType::CHASM].
+///
+/// # Notes
+/// * Runs at the end of the [RunState::MonsterTurn], after all
+///   entities have acted, so every entity in the lava takes
+///   [config::LAVA_DAMAGE] once per turn. The lava scorches entities,
+///   that [Regenerates], like fire.
+/// * The player doesn't die in a chasm, but falls to the next
+///   level instead, see `player::player_handle_input`.
+///
+pub struct TerrainSystem {}
+
+impl<'a> System<'a> for TerrainSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Statistics>,
+        WriteStorage<'a, Regenerates>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            run_state,
+            map,
+            mut game_log,
+            players,
+            names,
+            positions,
+            mut statistics,
+            mut regenerates,
+        ) = data;
+
+        if *run_state != RunState::MonsterTurn {
+            return;
+        }
+
+        for (entity, position, statistic) in (&entities, &positions, &mut statistics).join() {
+            if statistic.hp <= 0 {
+                continue;
+            }
+
+            let name = names
+                .get(entity)
+                .map_or_else(|| localization::tr("name.someone"), Name::display);
+
+            match map.get_tile(position.x, position.y) {
+                TileType::LAVA => {
+                    if let Some(regeneration) = regenerates.get_mut(entity) {
+                        regeneration.scorch();
+                    }
+
+                    statistic.hp -= config::LAVA_DAMAGE;
+                    game_log.push(
+                        &localization::tr_with("terrain.lava.burn", &[&name, &config::LAVA_DAMAGE]),
+                        LogCategory::Combat,
+                    );
+                }
+                TileType::CHASM if !players.contains(entity) => {
+                    statistic.hp = 0;
+                    game_log.push(
+                        &localization::tr_with("terrain.chasm.fall", &[&name]),
+                        LogCategory::Combat,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
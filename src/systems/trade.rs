@@ -0,0 +1,107 @@
+//! Systems guarding the stock of the shopkeepers.
+
+use rltk::Point;
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::localization;
+use crate::{
+    register_event_reader, ForSale, GameLog, LogCategory, Name, PickupEvent, Position, Shopkeeper,
+    SystemDispatcherBuilder, FOV,
+};
+
+/// Registers the systems of the module with the passed `builder`.
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder.with(TheftSystem::default(), "theft", &["item_collection"])
+}
+
+/// System that handles the [PickupEvent]s of items [ForSale], which
+/// the player takes without paying for them. The items are no longer
+/// for sale afterwards. If the [Shopkeeper] sees the player in its
+/// [FOV], it refuses to trade with the player for the rest of the run.
+///
+/// # Notes
+/// * Items, that were too heavy to be picked up, keep their [Position]
+///   on display and remain for sale, see the
+///   [ItemCollectionSystem](super::ItemCollectionSystem).
+///
+#[derive(Default)]
+pub struct TheftSystem {
+    /// Reader for the [PickupEvent] channel,
+    /// registered during the setup of the system.
+    reader: Option<ReaderId<PickupEvent>>,
+}
+
+impl<'a> System<'a> for TheftSystem {
+    type SystemData = (
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, Point>,
+        Read<'a, EventChannel<PickupEvent>>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, FOV>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Shopkeeper>,
+        WriteStorage<'a, ForSale>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_event_reader::<PickupEvent>(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut game_log,
+            player,
+            player_position,
+            pickup_events,
+            names,
+            fovs,
+            positions,
+            mut shopkeepers,
+            mut for_sale,
+        ) = data;
+
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("TheftSystem has not been set up!");
+
+        for pickup in pickup_events.read(reader) {
+            if positions.contains(pickup.item) {
+                continue;
+            }
+
+            let shopkeeper = match for_sale.remove(pickup.item) {
+                Some(sale) if pickup.collector == *player => sale.shopkeeper,
+                _ => continue,
+            };
+
+            let is_seen = fovs
+                .get(shopkeeper)
+                .is_some_and(|fov| fov.contains(&player_position));
+
+            if !is_seen {
+                game_log.push(&localization::tr("shop.theft_unnoticed"), LogCategory::Item);
+                continue;
+            }
+
+            if let Some(keeper) = shopkeepers.get_mut(shopkeeper) {
+                keeper.robbed = true;
+            }
+
+            let name = names.get(shopkeeper).map(Name::display).unwrap_or_default();
+
+            game_log.push(
+                &localization::tr_with("shop.theft_detected", &[&name]),
+                LogCategory::General,
+            );
+        }
+    }
+}
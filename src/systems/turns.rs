@@ -0,0 +1,122 @@
+//! Systems ordering the turns of the acting entities.
+
+use rltk::Point;
+use specs::prelude::*;
+
+use crate::{
+    pythagoras_distance, Loot, Map, Position, RunState, Sneaking, Stackable, Statistics,
+    SystemDispatcherBuilder, TileType, TurnQueue, Weight, AI,
+};
+
+/// Registers the systems of the module with the passed `builder`
+/// and inserts the [TurnQueue] into the `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    ecs.insert(TurnQueue::default());
+
+    builder.with(TurnQueueSystem {}, "turn_queue", &["monster_ai"])
+}
+
+/// System filling the [TurnQueue] with the entities
+/// acting in the next round, once the current one is over.
+///
+/// # Notes
+/// * A new round starts after every [RunState::MonsterTurn], in which all
+///   monsters have acted, and on [RunState::Ticking], e.g. when a level
+///   has been entered or a game has been loaded.
+/// * The player acts first, followed by the monsters with an [AI] in
+///   the order of their distance to the player, so the monsters close
+///   to the player move out of the way of the ones behind them.
+/// * Entities wading through [TileType::DEEP_WATER] are slowed and
+///   only act in every other round, the player included. The same
+///   applies to [Sneaking] entities and to entities carrying more
+///   [Weight] than their [Statistics::carry_capacity].
+///
+pub struct TurnQueueSystem {}
+
+impl<'a> System<'a> for TurnQueueSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, Point>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, Map>,
+        Write<'a, TurnQueue>,
+        ReadStorage<'a, AI>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Sneaking>,
+        ReadStorage<'a, Statistics>,
+        ReadStorage<'a, Loot>,
+        ReadStorage<'a, Weight>,
+        ReadStorage<'a, Stackable>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            player_position,
+            run_state,
+            map,
+            mut turn_queue,
+            ais,
+            positions,
+            sneaking,
+            statistics,
+            backpack,
+            weights,
+            stackables,
+        ) = data;
+
+        let is_round_over = match *run_state {
+            RunState::Ticking => true,
+            RunState::MonsterTurn => turn_queue.is_empty(),
+            _ => false,
+        };
+
+        if !is_round_over {
+            return;
+        }
+
+        let mut monsters = (&entities, &ais, &positions)
+            .join()
+            .map(|(entity, _, position)| {
+                let distance = pythagoras_distance(&position.to_point(), &player_position);
+                (entity, distance)
+            })
+            .collect::<Vec<_>>();
+
+        monsters.sort_by(|(a, a_distance), (b, b_distance)| {
+            a_distance
+                .total_cmp(b_distance)
+                .then_with(|| a.id().cmp(&b.id()))
+        });
+
+        // Entities in deep water, sneaking and burdened ones sit out every even round
+        let is_slow_round = (turn_queue.round() + 1) % 2 == 0;
+        let is_burdened = |entity: &Entity| {
+            statistics.get(*entity).is_some_and(|statistic| {
+                Weight::carried(*entity, &backpack, &weights, &stackables)
+                    > statistic.carry_capacity()
+            })
+        };
+        let is_slowed = |entity: &Entity| {
+            sneaking.contains(*entity)
+                || is_burdened(entity)
+                || positions.get(*entity).map_or(false, |position| {
+                    map.get_tile(position.x, position.y) == TileType::DEEP_WATER
+                })
+        };
+
+        let order = std::iter::once(*player_entity)
+            .chain(monsters.into_iter().map(|(entity, _)| entity))
+            .filter(|entity| !is_slow_round || !is_slowed(entity))
+            .collect();
+
+        turn_queue.start_round(order);
+    }
+}
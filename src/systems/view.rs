@@ -0,0 +1,113 @@
+//! Systems handling the field of view of all entities.
+
+use specs::prelude::*;
+
+use crate::fov::FovAlgorithm;
+use crate::settings::Settings;
+use crate::{
+    config, Facing, Map, Player, Position, StatusEffectKind, StatusEffects,
+    SystemDispatcherBuilder, FOV,
+};
+
+/// Registers the systems of the module with the passed `builder`.
+///
+/// # Arguments
+/// * `_ecs`: The [World] the systems will be executed on.
+/// * `builder`: The builder to register the systems with.
+///
+pub fn register(_ecs: &mut World, builder: SystemDispatcherBuilder) -> SystemDispatcherBuilder {
+    builder.with(FOVSystem {}, "fov", &[])
+}
+
+/// System that handles the field of view
+/// processing. See the implementation below
+/// for more details.
+///
+/// # Notes
+/// * Entities with a [Facing] only see the tiles in their vision cone.
+/// * Blinded entities only see the tiles within the
+///   [config::BLIND_VISION_RANGE], regardless of their [FOV::range].
+///   The `StatusEffectSystem` marks their [FOV] as dirty, when the
+///   blindness is inflicted and when it ends.
+/// * The field of view is calculated by the [FovAlgorithm]
+///   of the [Settings], or the default one if no [Settings]
+///   are registered. Changing the algorithm recalculates the
+///   field of view of all entities.
+///
+pub struct FOVSystem {}
+
+impl<'a> System<'a> for FOVSystem {
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, FOV>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Facing>,
+        ReadStorage<'a, StatusEffects>,
+        Option<Read<'a, Settings>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        // Get the systems data
+        let (mut map, entities, mut fovs, positions, players, facings, status_effects, settings) =
+            data;
+
+        let algorithm = settings.map_or(FovAlgorithm::default(), |settings| settings.fov_algorithm);
+
+        // Find the entities, fov system and positions.
+        for (entity, fov, position) in (&entities, &mut fovs, &positions).join() {
+            // Switching the algorithm invalidates the [FOV]
+            if fov.algorithm != algorithm {
+                fov.algorithm = algorithm;
+                fov.mark_as_dirty();
+            }
+
+            // If the [FOV] is dirty, calculate new
+            if fov.is_dirty {
+                // Invalidate [FOV] flag
+                fov.mark_as_clean();
+
+                let is_blind = status_effects
+                    .get(entity)
+                    .is_some_and(|effects| effects.has(StatusEffectKind::Blindness));
+                let range = if is_blind {
+                    config::BLIND_VISION_RANGE.min(fov.range)
+                } else {
+                    fov.range
+                };
+
+                // Recalculate the [FOV]
+                fov.content.clear();
+                fov.content =
+                    fov.algorithm
+                        .field_of_view()
+                        .compute(position.to_point(), range, &map);
+                fov.content.retain(|pos| {
+                    pos.x >= 0 && pos.x < map.width && pos.y >= 0 && pos.y < map.height
+                });
+
+                // Limit the [FOV] to the vision cone of entities with a [Facing]
+                if let Some(facing) = facings.get(entity) {
+                    let origin = position.to_point();
+                    fov.content.retain(|pos| facing.is_in_cone(origin, *pos));
+                }
+
+                // Check if the entity is the [Player]
+                let _player = players.get(entity);
+                if let Some(_player) = _player {
+                    map.set_player_blind(is_blind);
+
+                    // Clean map fov tiles
+                    map.reset_tiles_in_fov();
+
+                    // Set the tiles of all fields in the [FOV]
+                    for explored_tile in fov.content.iter() {
+                        map.set_explored_tile(explored_tile.x, explored_tile.y, true);
+                        map.set_tile_in_fov(explored_tile.x, explored_tile.y, true);
+                    }
+                }
+            }
+        }
+    }
+}
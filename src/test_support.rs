@@ -0,0 +1,246 @@
+//! Fixtures for testing systems and game logic in isolation.
+//!
+//! The [TestWorldBuilder] assembles a [World] with all components
+//! registered, a small open map and canned entities, so a single
+//! system can be set up and executed without running the game.
+
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+use super::game_config::{self, GameConfig};
+use super::identification;
+use super::quests::QuestLog;
+use super::{
+    entity_factory, faction, raws, register_components, scripting, spawn_table, GameLog,
+    LevelStatistics, Map, PlayerPathing, PlayerResting, PlayerRunning, Position, Rectangle,
+    RunState, RunStatistics, TurnCounter,
+};
+
+/// Seed of the random number generator registered in the
+/// test worlds, so test runs are reproducible.
+pub const TEST_SEED: u64 = 1337;
+
+/// Width and height of the map of the test worlds.
+const MAP_SIZE: i32 = 10;
+
+/// Builder to assemble a [TestWorld] step by step.
+///
+/// # Notes
+/// * The player is always created last, so the monsters and
+///   items can be placed before it.
+///
+pub struct TestWorldBuilder {
+    /// Position of the player, if one should be created.
+    player: Option<Position>,
+
+    /// Positions of the goblins to create.
+    monsters: Vec<Position>,
+
+    /// Positions of the health potions to create.
+    potions: Vec<Position>,
+}
+
+impl TestWorldBuilder {
+    /// Creates a new [TestWorldBuilder] for a world with a
+    /// `10 x 10` map, no entities and the [RunState::Ticking].
+    pub fn new() -> Self {
+        TestWorldBuilder {
+            player: None,
+            monsters: Vec::new(),
+            potions: Vec::new(),
+        }
+    }
+
+    /// Creates the player at the given coordinates.
+    ///
+    /// # Arguments
+    /// * `x`: The x coordinate of the player.
+    /// * `y`: The y coordinate of the player.
+    ///
+    pub fn with_player(mut self, x: i32, y: i32) -> Self {
+        self.player = Some(Position { x, y });
+        self
+    }
+
+    /// Creates a goblin at the given coordinates.
+    ///
+    /// # Arguments
+    /// * `x`: The x coordinate of the goblin.
+    /// * `y`: The y coordinate of the goblin.
+    ///
+    pub fn with_monster(mut self, x: i32, y: i32) -> Self {
+        self.monsters.push(Position { x, y });
+        self
+    }
+
+    /// Creates a health potion at the given coordinates.
+    ///
+    /// # Arguments
+    /// * `x`: The x coordinate of the potion.
+    /// * `y`: The y coordinate of the potion.
+    ///
+    pub fn with_potion(mut self, x: i32, y: i32) -> Self {
+        self.potions.push(Position { x, y });
+        self
+    }
+
+    /// Consumes the builder and assembles the [TestWorld].
+    pub fn build(self) -> TestWorld {
+        let mut ecs = World::new();
+
+        register_components(&mut ecs);
+
+        ecs.insert(RandomNumberGenerator::seeded(TEST_SEED));
+        scripting::register(&mut ecs);
+        raws::register(&mut ecs);
+        spawn_table::register(&mut ecs);
+        faction::register(&mut ecs);
+        identification::register(&mut ecs);
+        game_config::register(&mut ecs, GameConfig::default());
+        ecs.insert(GameLog::new_empty());
+        ecs.insert(PlayerPathing::new());
+        ecs.insert(PlayerResting::default());
+        ecs.insert(PlayerRunning::default());
+        ecs.insert(TurnCounter::default());
+        ecs.insert(RunStatistics::default());
+        ecs.insert(LevelStatistics::default());
+        ecs.insert(QuestLog::default());
+        ecs.insert(RunState::Ticking);
+        ecs.insert(open_map(MAP_SIZE, MAP_SIZE));
+
+        let monsters = self
+            .monsters
+            .into_iter()
+            .map(|position| spawn(&mut ecs, "Goblin", position))
+            .collect();
+
+        let items = self
+            .potions
+            .into_iter()
+            .map(|position| spawn(&mut ecs, "Health Potion", position))
+            .collect();
+
+        let player = self.player.map(|position| {
+            let player = entity_factory::new_player(&position, &mut ecs);
+
+            ecs.insert(player);
+            ecs.insert(position.to_point());
+
+            player
+        });
+
+        TestWorld {
+            ecs,
+            player,
+            monsters,
+            items,
+        }
+    }
+}
+
+impl Default for TestWorldBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [World] assembled by the [TestWorldBuilder] together
+/// with the entities created for it.
+pub struct TestWorld {
+    /// The assembled `ecs`.
+    pub ecs: World,
+
+    /// The player, if one was created.
+    pub player: Option<Entity>,
+
+    /// The monsters in the order of their creation.
+    pub monsters: Vec<Entity>,
+
+    /// The items in the order of their creation.
+    pub items: Vec<Entity>,
+}
+
+impl TestWorld {
+    /// Sets up the passed `system` with the `ecs` and returns it.
+    /// Systems reading events must be set up before the events
+    /// are published, otherwise they miss them.
+    ///
+    /// # Arguments
+    /// * `system`: The system to set up.
+    ///
+    pub fn setup_system<S>(&mut self, mut system: S) -> S
+    where
+        S: for<'a> System<'a>,
+    {
+        system.setup(&mut self.ecs);
+        system
+    }
+
+    /// Executes the passed `system` once on the `ecs` and
+    /// applies all pending entity changes afterwards.
+    ///
+    /// # Arguments
+    /// * `system`: The system to execute.
+    ///
+    pub fn run_system<S>(&mut self, system: &mut S)
+    where
+        S: for<'a> System<'a>,
+    {
+        system.run_now(&self.ecs);
+        self.ecs.maintain();
+    }
+
+    /// Returns the player [Entity].
+    ///
+    /// # Panics
+    /// * If the world has been built without a player.
+    ///
+    pub fn player(&self) -> Entity {
+        self.player
+            .expect("The test world has been built without a player!")
+    }
+
+    /// Returns all messages pushed to the [GameLog] so far.
+    pub fn messages(&self) -> Vec<String> {
+        self.ecs
+            .fetch::<GameLog>()
+            .iter()
+            .map(|entry| entry.text.clone())
+            .collect()
+    }
+}
+
+/// Creates the entity with the passed `name` at the `position`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the entity should be created.
+/// * `name`: The name of the entity definition, e.g. `"Goblin"`.
+/// * `position`: The [Position] of the entity.
+///
+/// # Panics
+/// * If no entity with the `name` is defined.
+///
+fn spawn(ecs: &mut World, name: &str, position: Position) -> Entity {
+    entity_factory::new_named(ecs, name, position)
+        .unwrap_or_else(|| panic!("No entity named '{}' is defined!", name))
+}
+
+/// Creates a [Map] consisting of a single room, that
+/// covers the whole map except for the surrounding walls.
+///
+/// # Arguments
+/// * `width`: The width of the map.
+/// * `height`: The height of the map.
+///
+fn open_map(width: i32, height: i32) -> Map {
+    let mut map = Map::new(width, height, 1);
+    map.biome = None;
+
+    let room = Rectangle::new(0, 0, width - 2, height - 2);
+    map.draw_room(&room);
+    map.rooms.push(room);
+    map.refresh_blocked_tiles();
+    map.refresh_tile_costs();
+
+    map
+}
@@ -1,5 +1,6 @@
 //! Factory to create entityless tiles
 
+use super::biome::Biome;
 use super::{swatch, Renderable};
 
 /// Factory to produce basic tile
@@ -7,9 +8,9 @@ use super::{swatch, Renderable};
 pub struct TileFactory {}
 
 impl TileFactory {
-    /// Create a new floor tile
-    pub fn new_floor() -> Renderable {
-        let (fg, bg) = swatch::FLOOR.colors();
+    /// Create a new floor tile in the colors of the `biome`
+    pub fn new_floor(biome: Option<Biome>) -> Renderable {
+        let (fg, bg) = biome.map_or(&swatch::FLOOR, |biome| biome.floor()).colors();
 
         Renderable {
             symbol: rltk::to_cp437('.'),
@@ -19,9 +20,9 @@ impl TileFactory {
         }
     }
 
-    /// Create a new wall tile
-    pub fn new_wall() -> Renderable {
-        let (fg, bg) = swatch::WALL.colors();
+    /// Create a new wall tile in the colors of the `biome`
+    pub fn new_wall(biome: Option<Biome>) -> Renderable {
+        let (fg, bg) = biome.map_or(&swatch::WALL, |biome| biome.wall()).colors();
 
         Renderable {
             symbol: rltk::to_cp437('#'),
@@ -30,4 +31,100 @@ impl TileFactory {
             order: -1,
         }
     }
+
+    /// Create a new tile for stairs leading down
+    pub fn new_stairs_down() -> Renderable {
+        let (fg, bg) = swatch::STAIRS_DOWN.colors();
+
+        Renderable {
+            symbol: rltk::to_cp437('>'),
+            fg,
+            bg,
+            order: -1,
+        }
+    }
+
+    /// Create a new deep water tile
+    pub fn new_deep_water() -> Renderable {
+        let (fg, bg) = swatch::DEEP_WATER.colors();
+
+        Renderable {
+            symbol: rltk::to_cp437('≈'),
+            fg,
+            bg,
+            order: -1,
+        }
+    }
+
+    /// Create a new lava tile
+    pub fn new_lava() -> Renderable {
+        let (fg, bg) = swatch::LAVA.colors();
+
+        Renderable {
+            symbol: rltk::to_cp437('≈'),
+            fg,
+            bg,
+            order: -1,
+        }
+    }
+
+    /// Create a new chasm tile
+    pub fn new_chasm() -> Renderable {
+        let (fg, bg) = swatch::CHASM.colors();
+
+        Renderable {
+            symbol: rltk::to_cp437('░'),
+            fg,
+            bg,
+            order: -1,
+        }
+    }
+
+    /// Create a new road tile
+    pub fn new_road() -> Renderable {
+        let (fg, bg) = swatch::ROAD.colors();
+
+        Renderable {
+            symbol: rltk::to_cp437('·'),
+            fg,
+            bg,
+            order: -1,
+        }
+    }
+
+    /// Create a new rubble tile
+    pub fn new_rubble() -> Renderable {
+        let (fg, bg) = swatch::RUBBLE.colors();
+
+        Renderable {
+            symbol: rltk::to_cp437(';'),
+            fg,
+            bg,
+            order: -1,
+        }
+    }
+
+    /// Create a new tile for the remains of a bashed in door
+    pub fn new_broken_door() -> Renderable {
+        let (fg, bg) = swatch::BROKEN_DOOR.colors();
+
+        Renderable {
+            symbol: rltk::to_cp437('\''),
+            fg,
+            bg,
+            order: -1,
+        }
+    }
+
+    /// Create a new bridge tile
+    pub fn new_bridge() -> Renderable {
+        let (fg, bg) = swatch::BRIDGE.colors();
+
+        Renderable {
+            symbol: rltk::to_cp437('='),
+            fg,
+            bg,
+            order: -1,
+        }
+    }
 }
@@ -0,0 +1,314 @@
+//! Module for the touch input of the web build.
+//!
+//! Browsers emulate mouse events for taps, but the emulated press and
+//! release arrive within the same frame, so `rltk` never reports the click.
+//! On `wasm32` targets the touch events of the canvas are therefore handled
+//! directly: the position of the active touch is applied to the mouse
+//! position of the [Rltk] context and a tap is reported as a left click, so
+//! all mouse driven features, e.g. `click-to-move` and the targeting, work
+//! with touch input as well. Holding a touch on the same tile for
+//! [config::LONG_PRESS_MILLIS] is reported as [TouchGesture::LongPress].
+//!
+//! On all other targets touch input is never enabled.
+
+use rltk::Rltk;
+
+#[cfg(target_arch = "wasm32")]
+use super::config;
+
+/// Enum describing the gestures reported by [apply].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+pub enum TouchGesture {
+    /// The player tapped the screen, which is
+    /// reported as left click as well.
+    Tap,
+
+    /// The player held a touch on the same tile.
+    LongPress,
+}
+
+/// Installs the touch handlers on the canvas the game is
+/// rendered to. Does nothing on targets other than `wasm32`.
+///
+/// # Notes
+/// * Errors are reported to the console, the game stays
+///   playable with mouse and keyboard.
+///
+pub fn install() {
+    #[cfg(target_arch = "wasm32")]
+    web::install();
+}
+
+/// Applies the touch input of the current frame to the passed `ctx`
+/// and returns the gesture, that has been completed in the frame.
+///
+/// # Arguments
+/// * `ctx`: The [Rltk] context of the current frame.
+///
+/// # Notes
+/// * Must be called once per frame before the input is read.
+/// * The position of the last touch replaces the mouse position,
+///   until the mouse of the device is moved.
+///
+pub fn apply(ctx: &mut Rltk) -> Option<TouchGesture> {
+    #[cfg(target_arch = "wasm32")]
+    return web::apply(ctx);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = ctx;
+        None
+    }
+}
+
+/// Returns `true` if the player has used touch input,
+/// i.e. the touch controls should be displayed.
+pub fn is_enabled() -> bool {
+    #[cfg(target_arch = "wasm32")]
+    return web::is_enabled();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    false
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::sync::{Mutex, MutexGuard};
+
+    use instant::{Duration, Instant};
+    use rltk::{console, Rltk};
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen::JsCast;
+    use web_sys::{HtmlCanvasElement, TouchEvent};
+
+    use super::{config, TouchGesture};
+
+    /// The id of the canvas element `rltk` renders to.
+    const CANVAS_ID: &str = "canvas";
+
+    /// A touch, that hasn't been lifted yet.
+    struct ActiveTouch {
+        /// The time the touch started.
+        started: Instant,
+
+        /// The tile the touch started on.
+        start_tile: (i32, i32),
+
+        /// Flag indicating whether or not the touch
+        /// has left the tile it started on.
+        moved: bool,
+
+        /// Flag indicating whether or not the touch
+        /// has already been reported as long press.
+        long_pressed: bool,
+    }
+
+    /// The touch input collected by the event handlers.
+    struct TouchState {
+        /// Flag indicating whether or not a touch has been seen.
+        enabled: bool,
+
+        /// The canvas pixel position of the last touch.
+        position: Option<(i32, i32)>,
+
+        /// The mouse position reported by `rltk`, when the
+        /// touch position was last applied. Once it changes,
+        /// the mouse has been moved.
+        mouse_position: Option<(i32, i32)>,
+
+        /// The touch, that hasn't been lifted yet.
+        active: Option<ActiveTouch>,
+
+        /// Flag indicating whether or not a tap has been
+        /// completed since the last frame.
+        tapped: bool,
+    }
+
+    /// The touch input shared between the event handlers and the game loop.
+    static STATE: Mutex<TouchState> = Mutex::new(TouchState {
+        enabled: false,
+        position: None,
+        mouse_position: None,
+        active: None,
+        tapped: false,
+    });
+
+    /// Locks the shared [TouchState].
+    fn state() -> MutexGuard<'static, TouchState> {
+        STATE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns the tile of the passed canvas pixel `position`.
+    ///
+    /// # Arguments
+    /// * `canvas`: The canvas the game is rendered to.
+    /// * `position`: The position in canvas pixels.
+    ///
+    fn to_tile(canvas: &HtmlCanvasElement, position: (i32, i32)) -> (i32, i32) {
+        let tile_width = canvas.width() as i32 / config::WINDOW_WIDTH;
+        let tile_height = canvas.height() as i32 / config::WINDOW_HEIGHT;
+
+        (
+            position.0 / tile_width.max(1),
+            position.1 / tile_height.max(1),
+        )
+    }
+
+    /// Returns the canvas pixel position of the first changed touch of the
+    /// `event`, taking the scaling of the canvas by the page into account.
+    ///
+    /// # Arguments
+    /// * `canvas`: The canvas the game is rendered to.
+    /// * `event`: The touch event.
+    ///
+    fn touch_position(canvas: &HtmlCanvasElement, event: &TouchEvent) -> Option<(i32, i32)> {
+        let touch = event.changed_touches().get(0)?;
+        let rect = canvas.get_bounding_client_rect();
+
+        if rect.width() <= 0.0 || rect.height() <= 0.0 {
+            return None;
+        }
+
+        let x = (touch.client_x() as f64 - rect.left()) * canvas.width() as f64 / rect.width();
+        let y = (touch.client_y() as f64 - rect.top()) * canvas.height() as f64 / rect.height();
+
+        Some((x as i32, y as i32))
+    }
+
+    /// Handles the start of a touch on the `canvas`.
+    fn on_touch_start(canvas: &HtmlCanvasElement, event: &TouchEvent) {
+        if let Some(position) = touch_position(canvas, event) {
+            let mut state = state();
+
+            state.enabled = true;
+            state.position = Some(position);
+            state.active = Some(ActiveTouch {
+                started: Instant::now(),
+                start_tile: to_tile(canvas, position),
+                moved: false,
+                long_pressed: false,
+            });
+        }
+    }
+
+    /// Handles the movement of a touch on the `canvas`.
+    fn on_touch_move(canvas: &HtmlCanvasElement, event: &TouchEvent) {
+        if let Some(position) = touch_position(canvas, event) {
+            let mut state = state();
+            state.position = Some(position);
+
+            if let Some(active) = state.active.as_mut() {
+                active.moved |= to_tile(canvas, position) != active.start_tile;
+            }
+        }
+    }
+
+    /// Handles the end of a touch on the `canvas`. The touch is
+    /// a tap, unless it has been reported as long press.
+    fn on_touch_end(canvas: &HtmlCanvasElement, event: &TouchEvent) {
+        let mut state = state();
+
+        if let Some(position) = touch_position(canvas, event) {
+            state.position = Some(position);
+        }
+
+        if let Some(active) = state.active.take() {
+            state.tapped |= !active.long_pressed;
+        }
+    }
+
+    /// Handles a touch on the `canvas`, that has been cancelled by the browser.
+    fn on_touch_cancel(_canvas: &HtmlCanvasElement, _event: &TouchEvent) {
+        state().active = None;
+    }
+
+    /// Registers the touch handlers on the canvas.
+    pub fn install() {
+        let canvas = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id(CANVAS_ID))
+            .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok());
+
+        let canvas = match canvas {
+            Some(canvas) => canvas,
+            None => {
+                console::log("Unable to find the canvas, touch input is disabled.");
+                return;
+            }
+        };
+
+        let handlers: [(&str, fn(&HtmlCanvasElement, &TouchEvent)); 4] = [
+            ("touchstart", on_touch_start),
+            ("touchmove", on_touch_move),
+            ("touchend", on_touch_end),
+            ("touchcancel", on_touch_cancel),
+        ];
+
+        for (name, handler) in handlers.iter() {
+            let target = canvas.clone();
+            let handler = *handler;
+
+            // Prevent the emulated mouse events and the scrolling of the page
+            let callback = Closure::wrap(Box::new(move |event: TouchEvent| {
+                event.prevent_default();
+                handler(&target, &event);
+            }) as Box<dyn FnMut(TouchEvent)>);
+
+            if canvas
+                .add_event_listener_with_callback(name, callback.as_ref().unchecked_ref())
+                .is_err()
+            {
+                console::log(format!("Unable to register the {} handler.", name));
+            }
+
+            callback.forget();
+        }
+    }
+
+    /// See [super::apply].
+    pub fn apply(ctx: &mut Rltk) -> Option<TouchGesture> {
+        let mut state = state();
+
+        // The mouse has been moved since the last touch, so it takes over again
+        if state
+            .mouse_position
+            .is_some_and(|position| position != ctx.mouse_pos)
+        {
+            state.position = None;
+            state.mouse_position = None;
+        }
+
+        if let Some(position) = state.position {
+            state.mouse_position = Some(ctx.mouse_pos);
+            ctx.mouse_pos = position;
+        }
+
+        if state.tapped {
+            state.tapped = false;
+            ctx.left_click = true;
+            return Some(TouchGesture::Tap);
+        }
+
+        let long_press = Duration::from_millis(config::LONG_PRESS_MILLIS);
+
+        match state.active.as_mut() {
+            Some(active)
+                if !active.moved
+                    && !active.long_pressed
+                    && active.started.elapsed() >= long_press =>
+            {
+                active.long_pressed = true;
+                Some(TouchGesture::LongPress)
+            }
+            _ => None,
+        }
+    }
+
+    /// See [super::is_enabled].
+    pub fn is_enabled() -> bool {
+        state().enabled
+    }
+}
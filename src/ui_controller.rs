@@ -1,47 +1,370 @@
 //! Module containing all UI functionality of the game
 
-use rltk::{Point, Rltk};
+use rltk::{ColorPair, DrawBatch, Point, Rect, Rltk, VirtualKeyCode, RGB};
 use specs::prelude::*;
 
+use super::camera::Camera;
+use super::daily::{self, DailyRun};
+use super::debug_console::{self, DebugConsole};
+use super::diagnostics::Diagnostics;
+use super::game_config::GameConfig;
+use super::identification::Identification;
+use super::keybindings::{self, KeyAction, KeyBindings};
+use super::quests::{QuestLog, QuestState};
+use super::saveload;
+use super::settings::Settings;
+use super::touch;
+use super::valuation::{self, PriceModifiers};
 use super::{
-    config, swatch, timestamp_formatted, GameLog, Map, Name, Player, Position, Statistics,
+    config, i32_to_alpha_key, localization, pythagoras_distance, submit_batch, swatch,
+    virtual_key_code_to_char, virtual_key_code_to_digit, virtual_key_code_to_string, AreaOfEffect,
+    Cursed, Equippable, Equipped, Experience, ForSale, GameLog, Invisible, LevelStatistics, Loot,
+    Mana, Map, Name, Player, Purse, Ranged, RunStatistics, Scripted, Stackable, Statistics,
+    StatusEffectKind, StatusEffects, TurnCounter, Value, Weight, FOV,
 };
 
-/// Draws the ui of the game in the given `ctx`.
+/// The maximum length of the player's name.
+const MAX_NAME_LENGTH: usize = 20;
+
+/// Enum describing the result of the player's
+/// interaction with a menu screen.
+#[derive(PartialEq, Copy, Clone)]
+pub enum MenuResult<T> {
+    /// The player hasn't made a
+    /// selection yet.
+    NoResponse,
+
+    /// The player has closed the
+    /// menu without a selection.
+    Cancel,
+
+    /// The player has selected the
+    /// contained value.
+    Selected(T),
+}
+
+/// Trait for the options of the menu screens
+/// drawn by [draw_main_menu] and [draw_options_menu].
+pub trait MenuOption: Copy + PartialEq + 'static {
+    /// All options of the menu in
+    /// the order of their display.
+    const ALL: &'static [Self];
+
+    /// The option chosen when the player
+    /// presses `ESCAPE`, if there is one.
+    const CANCEL: Option<Self>;
+
+    /// Returns the translated text displayed for the option.
+    ///
+    /// # Arguments
+    /// * `settings`: The player's [Settings], whose values are shown.
+    ///
+    fn description(&self, settings: &Settings) -> String;
+
+    /// Returns `true` if the option can be chosen, `false` if
+    /// it's displayed disabled and skipped by the navigation.
+    fn is_available(&self) -> bool {
+        true
+    }
+}
+
+/// Enum describing the options of the
+/// main menu.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MainMenuSelection {
+    /// Starts a new game.
+    NewGame,
+
+    /// Starts the daily challenge, see [daily].
+    DailyChallenge,
+
+    /// Continues the saved game.
+    LoadGame,
+
+    /// Opens the options menu.
+    Options,
+
+    /// Quits the game.
+    Quit,
+}
+
+impl MenuOption for MainMenuSelection {
+    const ALL: &'static [MainMenuSelection] = &[
+        MainMenuSelection::NewGame,
+        MainMenuSelection::DailyChallenge,
+        MainMenuSelection::LoadGame,
+        MainMenuSelection::Options,
+        MainMenuSelection::Quit,
+    ];
+
+    const CANCEL: Option<MainMenuSelection> = None;
+
+    fn description(&self, _settings: &Settings) -> String {
+        match self {
+            MainMenuSelection::NewGame => localization::tr("menu.new_game"),
+            MainMenuSelection::DailyChallenge => {
+                localization::tr_with("menu.daily_challenge", &[&daily::today()])
+            }
+            MainMenuSelection::LoadGame => localization::tr("menu.load_game"),
+            MainMenuSelection::Options => localization::tr("menu.options"),
+            MainMenuSelection::Quit => localization::tr("menu.quit"),
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        match self {
+            MainMenuSelection::LoadGame => saveload::has_save_game(),
+            _ => true,
+        }
+    }
+}
+
+/// Enum describing the options of the
+/// options menu.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum OptionsMenuSelection {
+    /// Switches to the next available language.
+    Language,
+
+    /// Toggles the reduced motion mode.
+    ReducedMotion,
+
+    /// Switches to the next field of view algorithm.
+    FovAlgorithm,
+
+    /// Opens the key bindings screen.
+    KeyBindings,
+
+    /// Returns to the main menu.
+    Back,
+}
+
+impl MenuOption for OptionsMenuSelection {
+    const ALL: &'static [OptionsMenuSelection] = &[
+        OptionsMenuSelection::Language,
+        OptionsMenuSelection::ReducedMotion,
+        OptionsMenuSelection::FovAlgorithm,
+        OptionsMenuSelection::KeyBindings,
+        OptionsMenuSelection::Back,
+    ];
+
+    const CANCEL: Option<OptionsMenuSelection> = Some(OptionsMenuSelection::Back);
+
+    fn description(&self, settings: &Settings) -> String {
+        match self {
+            OptionsMenuSelection::Language => localization::tr_with(
+                "menu.language",
+                &[&localization::language_name(&localization::language())],
+            ),
+            OptionsMenuSelection::ReducedMotion => {
+                let state = if settings.reduced_motion {
+                    localization::tr("menu.on")
+                } else {
+                    localization::tr("menu.off")
+                };
+
+                localization::tr_with("menu.reduced_motion", &[&state])
+            }
+            OptionsMenuSelection::FovAlgorithm => {
+                localization::tr_with("menu.fov_algorithm", &[&settings.fov_algorithm.name()])
+            }
+            OptionsMenuSelection::KeyBindings => localization::tr("menu.key_bindings"),
+            OptionsMenuSelection::Back => localization::tr("menu.back"),
+        }
+    }
+}
+
+/// Enum describing the result of the player's
+/// interaction with a menu of [MenuOption]s.
+#[derive(PartialEq, Copy, Clone)]
+pub enum MainMenuResult<T> {
+    /// The player is browsing the options,
+    /// `selected` is the highlighted option.
+    NoSelection {
+        /// The highlighted option.
+        selected: T,
+    },
+
+    /// The player has chosen the `selected` option.
+    Selected {
+        /// The chosen option.
+        selected: T,
+    },
+}
+
+/// Enum describing the options of the
+/// game over screen.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum GameOverSelection {
+    /// Starts a new run in place of the finished one.
+    NewRun,
+
+    /// Quits the game.
+    Quit,
+}
+
+/// Enum describing the buttons of the touch controls,
+/// which are displayed once the player used touch input.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TouchButton {
+    /// Opens the inventory.
+    Inventory,
+
+    /// Waits for a turn.
+    Wait,
+
+    /// Descends the stairs the player stands on.
+    Stairs,
+}
+
+impl TouchButton {
+    /// All touch buttons in the order of their display.
+    const ALL: [TouchButton; 3] = [
+        TouchButton::Inventory,
+        TouchButton::Wait,
+        TouchButton::Stairs,
+    ];
+
+    /// Returns the translated label of the button.
+    fn label(&self) -> String {
+        let label = match self {
+            TouchButton::Inventory => localization::tr("touch.inventory"),
+            TouchButton::Wait => localization::tr("touch.wait"),
+            TouchButton::Stairs => localization::tr("touch.stairs"),
+        };
+
+        format!("[ {} ]", label)
+    }
+}
+
+/// Returns the touch buttons with the area they occupy. The buttons
+/// are right aligned in the last row of the window, below the log.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [GameConfig] is registered.
+///
+fn touch_button_layout(ecs: &World) -> Vec<(TouchButton, Rect)> {
+    let game_config = ecs.fetch::<GameConfig>();
+    let y = game_config.window_height - 1;
+    let mut x = game_config.window_width - 1;
+    let mut layout = Vec::new();
+
+    for button in TouchButton::ALL.iter().rev() {
+        let width = button.label().chars().count() as i32;
+        x -= width;
+        layout.push((*button, Rect::with_size(x, y, width, 1)));
+        x -= 1;
+    }
+
+    layout.reverse();
+    layout
+}
+
+/// Returns the touch button at the passed `point`, if
+/// the touch controls are displayed and there is one.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [GameConfig] is registered.
+/// * `point`: The tile to check.
+///
+pub fn touch_button_at(ecs: &World, point: Point) -> Option<TouchButton> {
+    if !touch::is_enabled() {
+        return None;
+    }
+
+    touch_button_layout(ecs)
+        .into_iter()
+        .find(|(_, area)| area.point_in_rect(point))
+        .map(|(button, _)| button)
+}
+
+/// Draws the touch buttons, if the player used touch input.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [GameConfig] is registered.
+/// * `batch`: The [DrawBatch] to which the buttons should be drawn.
+///
+fn draw_touch_buttons(ecs: &World, batch: &mut DrawBatch) {
+    if !touch::is_enabled() {
+        return;
+    }
+
+    let (fg, bg) = swatch::TOUCH_BUTTON.colors();
+
+    for (button, area) in touch_button_layout(ecs).iter() {
+        batch.print_color(
+            Point::new(area.x1, area.y1),
+            button.label(),
+            ColorPair::new(fg, bg),
+        );
+    }
+}
+
+/// Returns the row the player clicked or tapped in
+/// the current frame, if there was a click.
+///
+/// # Arguments
+/// * `ctx`: The [Rltk] context, required to read the mouse.
+///
+fn clicked_row(ctx: &Rltk) -> Option<i32> {
+    if ctx.left_click {
+        return Some(ctx.mouse_point().y);
+    }
+
+    None
+}
+
+/// Draws the ui of the game and submits it to the
+/// [config::LAYER_UI].
 ///
 /// # Arguments
 /// * `ecs`: The `ecs` is needed to read values from the game for display.
-/// * `ctx`: The console context in which the ui should be drawn.
+/// * `ctx`: The console context, required to read the mouse position.
 ///
 /// # See also
 /// * [draw_message_log]
 /// * [draw_messages]
 /// * [draw_player_health]
+/// * [draw_player_mana]
+/// * [draw_player_gold]
 /// * [draw_mouse_cursor]
+/// * [draw_touch_buttons]
 ///
-pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
-    draw_message_log(ctx);
-    draw_messages(ecs, ctx);
-    draw_player_health(ecs, ctx);
-    draw_mouse_cursor(ctx);
+pub fn draw_ui(ecs: &World, ctx: &Rltk) {
+    let mut batch = DrawBatch::new();
+
+    draw_message_log(ecs, &mut batch);
+    draw_messages(ecs, &mut batch);
+    draw_player_health(ecs, &mut batch);
+    draw_player_mana(ecs, &mut batch);
+    draw_player_experience(ecs, &mut batch);
+    draw_level_status(ecs, &mut batch);
+    draw_player_gold(ecs, &mut batch);
+    draw_mouse_cursor(ctx, &mut batch);
+    draw_touch_buttons(ecs, &mut batch);
+
+    submit_batch(&mut batch, config::LAYER_UI);
 }
 
 /// Draws the games message log at the bottom of the
 /// Screen.
 ///
 /// # Arguments
-/// * `ctx`: The [Rltk] context in which the message log
-/// should be drawn.
+/// * `ecs`: The [World] in which the [GameConfig] is registered.
+/// * `batch`: The [DrawBatch] to which the message log
+///   should be drawn.
 ///
-fn draw_message_log(ctx: &mut Rltk) {
-    let (x, y) = (0, config::MAP_HEIGHT);
+fn draw_message_log(ecs: &World, batch: &mut DrawBatch) {
+    let game_config = ecs.fetch::<GameConfig>();
+
+    let (x, y) = (0, game_config.viewport_height());
     let (width, height) = (
-        config::WINDOW_WIDTH - 1,
-        config::WINDOW_WIDTH - config::MAP_HEIGHT - 1,
+        game_config.window_width - 1,
+        game_config.window_height - game_config.viewport_height() - 1,
     );
     let (fg, bg) = swatch::MESSAGE_BOX.colors();
 
-    ctx.draw_box(x, y, width, height, fg, bg);
+    batch.draw_box(Rect::with_size(x, y, width, height), ColorPair::new(fg, bg));
 }
 
 /// Writes the messages which are stored in the [GameLog]
@@ -49,143 +372,2088 @@ fn draw_message_log(ctx: &mut Rltk) {
 ///
 /// # Arguments
 /// * `ecs`: THe [World] in which the [GameLog] is stored.
-/// * `ctx`: The [Rltk] context in which the messages should
-/// be written.
+/// * `batch`: The [DrawBatch] to which the messages should
+///   be written.
 ///
-fn draw_messages(ecs: &World, ctx: &mut Rltk) {
-    let mut game_log = ecs.fetch_mut::<GameLog>();
+fn draw_messages(ecs: &World, batch: &mut DrawBatch) {
+    let game_log = ecs.fetch::<GameLog>();
+    let game_config = ecs.fetch::<GameConfig>();
 
     let x = 2;
-    let mut y = config::MAP_HEIGHT + 1;
+    let first_line = game_config.viewport_height() + 1;
+    let visible_lines = (game_config.window_height - 2 - first_line).max(0) as usize;
+    let (_, bg) = swatch::MESSAGE_BOX.colors();
 
-    game_log.messages_for_each_rev(|message| {
-        if y < config::WINDOW_HEIGHT - 2 {
-            let timestamp = timestamp_formatted();
-            ctx.print(x, y, &format!("{} > {}", timestamp, message));
-            y += 1;
-        }
-    })
+    let entries = game_log.iter_recent(visible_lines);
+
+    for (y, entry) in (first_line..).zip(entries) {
+        batch.print_color(
+            Point::new(x, y),
+            format!("{:>4} > {}", entry.turn, entry.text),
+            ColorPair::new(entry.color, bg),
+        );
+    }
 }
 
 /// Draws the players healh information in form of status
 /// text and a health bar on top of the message log ui.
+/// The bar turns green, while the player is poisoned.
 ///
 /// # Arguments
 /// * `ecs`: The [World] in which the player is stored.
-/// * `ctx`: The [Rltk] context in which the ui should be drawn.
+/// * `batch`: The [DrawBatch] to which the ui should be drawn.
 ///
-fn draw_player_health(ecs: &World, ctx: &mut Rltk) {
+fn draw_player_health(ecs: &World, batch: &mut DrawBatch) {
     let players = ecs.read_storage::<Player>();
     let statistics = ecs.read_storage::<Statistics>();
+    let status_effects = ecs.read_storage::<StatusEffects>();
+    let y = ecs.fetch::<GameConfig>().viewport_height();
 
-    for (_, statistic) in (&players, &statistics).join() {
-        let health = format!(" HP: {} / {} ", statistic.hp, statistic.hp_max);
+    for (_, statistic, effects) in (&players, &statistics, status_effects.maybe()).join() {
+        let health = localization::tr_with("ui.hp", &[&statistic.hp, &statistic.hp_max]);
 
         let (fg, bg) = swatch::PLAYER_HEALTH_TEXT.colors();
 
-        ctx.print_color(12, config::MAP_HEIGHT, fg, bg, &health);
+        batch.print_color(Point::new(12, y), &health, ColorPair::new(fg, bg));
 
-        let (fg, bg) = swatch::PLAYER_HEALTH_BAR.colors();
+        let is_poisoned = effects.is_some_and(|effects| effects.has(StatusEffectKind::Poison));
+        let (fg, bg) = if is_poisoned {
+            swatch::PLAYER_HEALTH_BAR_POISONED.colors()
+        } else {
+            swatch::PLAYER_HEALTH_BAR.colors()
+        };
 
-        ctx.draw_bar_horizontal(
-            28,
-            config::MAP_HEIGHT,
-            50,
+        batch.bar_horizontal(
+            Point::new(28, y),
+            22,
             statistic.hp,
             statistic.hp_max,
-            fg,
-            bg,
+            ColorPair::new(fg, bg),
+        );
+    }
+}
+
+/// Draws the players mana in form of a status text and
+/// a mana bar below the message log ui.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+/// * `batch`: The [DrawBatch] to which the ui should be drawn.
+///
+fn draw_player_mana(ecs: &World, batch: &mut DrawBatch) {
+    let players = ecs.read_storage::<Player>();
+    let manas = ecs.read_storage::<Mana>();
+    let y = ecs.fetch::<GameConfig>().window_height - 1;
+
+    for (_, mana) in (&players, &manas).join() {
+        let text = localization::tr_with("ui.mp", &[&mana.mp, &mana.mp_max]);
+
+        let (fg, bg) = swatch::PLAYER_MANA_TEXT.colors();
+
+        batch.print_color(Point::new(12, y), &text, ColorPair::new(fg, bg));
+
+        let (fg, bg) = swatch::PLAYER_MANA_BAR.colors();
+
+        batch.bar_horizontal(
+            Point::new(28, y),
+            22,
+            mana.mp,
+            mana.mp_max,
+            ColorPair::new(fg, bg),
+        );
+    }
+}
+
+/// Draws the players level and xp in form of status
+/// texts and a xp bar on top of the message log ui,
+/// next to the health information.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+/// * `batch`: The [DrawBatch] to which the ui should be drawn.
+///
+fn draw_player_experience(ecs: &World, batch: &mut DrawBatch) {
+    let players = ecs.read_storage::<Player>();
+    let experiences = ecs.read_storage::<Experience>();
+    let y = ecs.fetch::<GameConfig>().viewport_height();
+
+    for (_, experience) in (&players, &experiences).join() {
+        let level = localization::tr_with("ui.level", &[&experience.level]);
+        let xp = localization::tr_with("ui.xp", &[&experience.xp, &experience.xp_to_next_level()]);
+
+        let (fg, bg) = swatch::PLAYER_EXPERIENCE_TEXT.colors();
+
+        batch.print_color(Point::new(2, y), &level, ColorPair::new(fg, bg));
+        batch.print_color(Point::new(51, y), &xp, ColorPair::new(fg, bg));
+
+        let (fg, bg) = swatch::PLAYER_EXPERIENCE_BAR.colors();
+
+        batch.bar_horizontal(
+            Point::new(68, y),
+            10,
+            experience.xp,
+            experience.xp_to_next_level(),
+            ColorPair::new(fg, bg),
         );
     }
 }
 
+/// Draws the depth of the current level and the
+/// current turn below the message log ui, next
+/// to the mana information.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [LevelStatistics] are stored.
+/// * `batch`: The [DrawBatch] to which the ui should be drawn.
+///
+fn draw_level_status(ecs: &World, batch: &mut DrawBatch) {
+    let level_statistics = ecs.fetch::<LevelStatistics>();
+    let turn_counter = ecs.fetch::<TurnCounter>();
+    let y = ecs.fetch::<GameConfig>().window_height - 1;
+
+    let depth = localization::tr_with("ui.depth", &[&level_statistics.depth]);
+    let turn = localization::tr_with("ui.turn", &[&turn_counter.turn]);
+
+    let (fg, bg) = swatch::LEVEL_STATUS_TEXT.colors();
+
+    batch.print_color(Point::new(51, y), &depth, ColorPair::new(fg, bg));
+    batch.print_color(Point::new(63, y), &turn, ColorPair::new(fg, bg));
+}
+
+/// Draws the gold in the player's [Purse] below
+/// the message log ui, next to the mana information.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+/// * `batch`: The [DrawBatch] to which the ui should be drawn.
+///
+fn draw_player_gold(ecs: &World, batch: &mut DrawBatch) {
+    let players = ecs.read_storage::<Player>();
+    let purses = ecs.read_storage::<Purse>();
+    let y = ecs.fetch::<GameConfig>().window_height - 1;
+
+    for (_, purse) in (&players, &purses).join() {
+        let text = localization::tr_with("ui.gold", &[&purse.gold]);
+
+        let (fg, bg) = swatch::PLAYER_GOLD_TEXT.colors();
+
+        batch.print_color(Point::new(2, y), &text, ColorPair::new(fg, bg));
+    }
+}
+
 /// Sets the background color of the
 /// tile currently focused by the mouse cursor.
 ///
 /// # Arguments
-/// * `ctx`: The [Rltk] context in which the mouse cursor
-/// should be highlighted.
+/// * `ctx`: The [Rltk] context, required to read the mouse position.
+/// * `batch`: The [DrawBatch] to which the mouse cursor
+///   should be drawn.
 ///
 /// # See also
 /// * [swatch::Mouse_Cursor]
 ///
-fn draw_mouse_cursor(ctx: &mut Rltk) {
-    let (x, y) = ctx.mouse_pos();
-    ctx.set_bg(x, y, swatch::MOUSE_CURSOR);
+fn draw_mouse_cursor(ctx: &Rltk, batch: &mut DrawBatch) {
+    batch.set_bg(ctx.mouse_point(), swatch::MOUSE_CURSOR);
+}
+
+/// Returns the lines of the tooltip of the passed `entity`, starting
+/// with its name, followed by its [Statistics] and active
+/// [StatusEffects], if it has any.
+///
+/// # Arguments
+/// * `entity`: The [Entity] under the mouse cursor.
+/// * `name`: The [Name] of the `entity`.
+/// * `identification`: The [Identification] resolving the `name`.
+/// * `statistics`: The storage of the [Statistics] components.
+/// * `status_effects`: The storage of the [StatusEffects] components.
+///
+fn entity_tooltip(
+    entity: Entity,
+    name: &Name,
+    identification: &Identification,
+    statistics: &ReadStorage<Statistics>,
+    status_effects: &ReadStorage<StatusEffects>,
+) -> Vec<String> {
+    let mut lines = vec![identification.display(name)];
+
+    if let Some(stats) = statistics.get(entity) {
+        lines.push(localization::tr_with(
+            "tooltip.hp",
+            &[&stats.hp, &stats.hp_max],
+        ));
+        lines.push(localization::tr_with(
+            "tooltip.attributes",
+            &[&stats.power, &stats.defense],
+        ));
+    }
+
+    if let Some(status_effects) = status_effects.get(entity) {
+        for effect in status_effects.effects.iter() {
+            let status = localization::tr(&format!("status.{}.name", effect.kind.name()));
+            lines.push(localization::tr_with(
+                "tooltip.effect",
+                &[&status, &effect.duration],
+            ));
+        }
+    }
+
+    lines
 }
 
-/// Draws a tooltip displaying the name of all entities
-/// on a tile, when the mouse is hovered over it.
+/// Draws a tooltip box describing all entities on a tile, when
+/// the mouse is hovered over it. Entities with [Statistics] show
+/// their hp, power and defense, followed by their active
+/// [StatusEffects]. If no entity is visible on an explored tile,
+/// its terrain is displayed instead. The tooltip is submitted to
+/// the [config::LAYER_TOOLTIP].
 ///
 /// # Arguments
-/// * `ecs`: The [World] struct, required to read the entities names and positions.
-/// * `ctx`: The [Rltk] context in which the tooltips should be drawn.
+/// * `ecs`: The [World] struct, required to read the map and the entities.
+/// * `ctx`: The [Rltk] context, required to read the mouse position.
+/// * `camera`: The [Camera] translating the mouse position onto the map.
+///
+/// # Notes
+/// * The box is placed on the side of the cursor with more space and
+///   moved up, if it would reach below the viewport.
+/// * Entities hidden from the player aren't listed, see [Invisible::is_hidden].
 ///
-pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
+pub fn draw_tooltips(ecs: &World, ctx: &Rltk, camera: &Camera) {
     let map = ecs.fetch::<Map>();
+    let identification = ecs.fetch::<Identification>();
     let names = ecs.read_storage::<Name>();
-    let positions = ecs.read_storage::<Position>();
+    let statistics = ecs.read_storage::<Statistics>();
+    let status_effects = ecs.read_storage::<StatusEffects>();
+    let invisibles = ecs.read_storage::<Invisible>();
+    let player = *ecs.fetch::<Entity>();
+    let player_position = *ecs.fetch::<Point>();
+
+    let Point { x, y } = ctx.mouse_point();
 
-    let (x, y) = ctx.mouse_pos();
+    let position = match camera.to_world(Point::new(x, y)) {
+        Some(position) => position,
+        None => return,
+    };
 
-    if !map.check_idx(x, y) {
+    if !map.check_idx(position.x, position.y) || !map.is_tile_explored(position.x, position.y) {
         return;
     }
 
     let mut tooltips: Vec<String> = Vec::new();
 
-    for (name, position) in (&names, &positions).join() {
-        if position.is_equal_to_tuple(&(x, y)) && map.is_tile_in_fov(x, y) {
-            tooltips.push(name.name.to_string());
+    if map.is_tile_in_fov(position.x, position.y) {
+        for entity in map.tile_contents_get(position.x, position.y) {
+            if Invisible::is_hidden(
+                &invisibles,
+                &status_effects,
+                player,
+                player_position,
+                entity,
+                position,
+            ) {
+                continue;
+            }
+
+            if let Some(name) = names.get(entity) {
+                tooltips.extend(entity_tooltip(
+                    entity,
+                    name,
+                    &identification,
+                    &statistics,
+                    &status_effects,
+                ));
+            }
         }
     }
 
     if tooltips.is_empty() {
-        return;
+        tooltips.push(map.get_tile(position.x, position.y).description());
     }
 
-    let mut max_width = tooltips
+    let max_width = tooltips
         .iter()
-        .max_by_key(|&element| (element.len(), element))
-        .unwrap()
-        .len() as i32;
+        .map(|tooltip| tooltip.chars().count())
+        .max()
+        .unwrap_or(0) as i32;
 
-    max_width += 3;
+    // The box has a border and a space of padding on both sides
+    let box_width = max_width + 3;
+    let box_height = tooltips.len() as i32 + 1;
+    let box_y = y.min(camera.height - box_height - 1).max(0);
+
+    let (box_x, arrow_position, arrow) = if x > camera.width / 2 {
+        (x - 3 - box_width, Point::new(x - 2, y), "->")
+    } else {
+        (x + 3, Point::new(x + 1, y), "<-")
+    };
 
-    let mut y_position = y;
+    let mut batch = DrawBatch::new();
     let (fg, bg) = swatch::TOOLTIP.colors();
+    let color = ColorPair::new(fg, bg);
+
+    batch.draw_box(Rect::with_size(box_x, box_y, box_width, box_height), color);
+
+    for (line_y, tooltip) in (box_y + 1..).zip(tooltips.iter()) {
+        batch.print_color(Point::new(box_x + 2, line_y), tooltip, color);
+    }
+
+    batch.print_color(arrow_position, arrow, color);
+
+    submit_batch(&mut batch, config::LAYER_TOOLTIP);
+}
+
+/// Draws the performance overlay with the timings of the
+/// [Diagnostics] in the top right corner of the viewport, if the
+/// overlay is enabled. The overlay is submitted to the
+/// [config::LAYER_OVERLAY].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [Diagnostics] are registered.
+///
+pub fn draw_performance_overlay(ecs: &World) {
+    let diagnostics = ecs.fetch::<Diagnostics>();
+
+    if !diagnostics.show_overlay {
+        return;
+    }
+
+    let title = localization::tr("diagnostics.title");
+    let lines = diagnostics.report();
+
+    let width = lines
+        .iter()
+        .chain(std::iter::once(&title))
+        .map(|line| line.len() as i32)
+        .max()
+        .unwrap_or(0)
+        + 3;
+    let height = lines.len() as i32 + 1;
+    let x = ecs.fetch::<GameConfig>().viewport_width() - width - 1;
+    let y = 1;
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::DIALOG_FRAME.colors();
+    batch.draw_box(Rect::with_size(x, y, width, height), ColorPair::new(fg, bg));
+
+    let (fg, bg) = swatch::DIALOG_TITLE.colors();
+    batch.print_color(Point::new(x + 2, y), &title, ColorPair::new(fg, bg));
+
+    let (fg, bg) = swatch::DIALOG_OPTION.colors();
+
+    for (line_y, line) in (y + 1..).zip(lines.iter()) {
+        batch.print_color(Point::new(x + 2, line_y), line, ColorPair::new(fg, bg));
+    }
+
+    submit_batch(&mut batch, config::LAYER_OVERLAY);
+}
+
+/// Draws the main menu of the game to the [config::LAYER_UI]
+/// and handles the player's input on it.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player's [Settings] are registered.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+/// * `selection`: The currently highlighted option.
+///
+pub fn draw_main_menu(
+    ecs: &World,
+    ctx: &Rltk,
+    selection: MainMenuSelection,
+) -> MainMenuResult<MainMenuSelection> {
+    draw_menu(ecs, ctx, config::GAME_NAME, config::GAME_VERSION, selection)
+}
+
+/// Draws the options menu, in which the player changes
+/// the [Settings], to the [config::LAYER_UI] and handles
+/// the player's input on it.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player's [Settings] are registered.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+/// * `selection`: The currently highlighted option.
+///
+pub fn draw_options_menu(
+    ecs: &World,
+    ctx: &Rltk,
+    selection: OptionsMenuSelection,
+) -> MainMenuResult<OptionsMenuSelection> {
+    draw_menu(ecs, ctx, &localization::tr("menu.options"), "", selection)
+}
+
+/// Draws a menu with the `title` and the options of type `T` to
+/// the [config::LAYER_UI] and handles the player's input on it.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player's [Settings] are registered.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+/// * `title`: The title above the options.
+/// * `subtitle`: The line below the `title`.
+/// * `selection`: The currently highlighted option.
+///
+/// # Notes
+/// * Options, that aren't available, can't be highlighted or chosen.
+///
+fn draw_menu<T: MenuOption>(
+    ecs: &World,
+    ctx: &Rltk,
+    title: &str,
+    subtitle: &str,
+    selection: T,
+) -> MainMenuResult<T> {
+    let settings = ecs.fetch::<Settings>();
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::MENU_TITLE.colors();
+    batch.print_color_centered(15, title, ColorPair::new(fg, bg));
+    batch.print_color_centered(16, subtitle, ColorPair::new(fg, bg));
+
+    let mut y = 24;
+    let mut tapped = None;
+
+    for option in T::ALL.iter() {
+        if clicked_row(ctx) == Some(y) && option.is_available() {
+            tapped = Some(*option);
+        }
+
+        let (fg, bg) = if !option.is_available() {
+            swatch::MENU_OPTION_DISABLED.colors()
+        } else if *option == selection {
+            swatch::MENU_OPTION_SELECTED.colors()
+        } else {
+            swatch::MENU_OPTION.colors()
+        };
+
+        batch.print_color_centered(y, option.description(&settings), ColorPair::new(fg, bg));
+        y += 2;
+    }
+
+    submit_batch(&mut batch, config::LAYER_UI);
+
+    if let Some(selected) = tapped {
+        return MainMenuResult::Selected { selected };
+    }
+
+    if let (Some(VirtualKeyCode::Escape), Some(selected)) = (ctx.key, T::CANCEL) {
+        return MainMenuResult::Selected { selected };
+    }
+
+    let position = T::ALL
+        .iter()
+        .position(|option| *option == selection)
+        .unwrap_or(0);
+    let options_count = T::ALL.len();
 
-    if x > 40 {
-        let start_x = x - max_width + 1;
-        let arrow_position = Point::new(x - 2, y);
+    // Steps through the options in the passed direction
+    // until the next available option is found
+    let step = |offset: usize| {
+        (1..=options_count)
+            .map(|distance| T::ALL[(position + distance * offset) % options_count])
+            .find(|option| option.is_available())
+            .unwrap_or(selection)
+    };
 
-        for tooltip in tooltips.iter() {
-            ctx.print_color(start_x, y_position, fg, bg, tooltip);
-            y_position += 1;
+    match ctx.key {
+        Some(VirtualKeyCode::Up) | Some(VirtualKeyCode::W) => MainMenuResult::NoSelection {
+            selected: step(options_count - 1),
+        },
+        Some(VirtualKeyCode::Down) | Some(VirtualKeyCode::S) => {
+            MainMenuResult::NoSelection { selected: step(1) }
         }
+        Some(VirtualKeyCode::Return) if selection.is_available() => MainMenuResult::Selected {
+            selected: selection,
+        },
+        _ => MainMenuResult::NoSelection {
+            selected: selection,
+        },
+    }
+}
+
+/// The state of the key bindings screen, that is
+/// kept between the frames it's displayed in.
+#[derive(PartialEq, Copy, Clone, Default, Debug)]
+pub struct KeyBindingsView {
+    /// The index of the highlighted [KeyAction].
+    pub selection: usize,
+
+    /// Flag indicating whether or not the next pressed
+    /// key is bound to the highlighted [KeyAction].
+    pub capturing: bool,
+}
+
+/// Enum describing the result of the player's
+/// interaction with the key bindings screen.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum KeyBindingsResult {
+    /// The player is browsing the key bindings.
+    Browsing {
+        /// The state of the key bindings screen.
+        view: KeyBindingsView,
+    },
+
+    /// The player has closed the key bindings screen.
+    Closed,
+}
+
+/// Draws the key bindings screen, on which the player rebinds the
+/// keys of the [KeyAction]s, to the [config::LAYER_UI] and handles
+/// the player's input on it.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [KeyBindings] are registered.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+/// * `view`: The current state of the key bindings screen.
+///
+/// # Notes
+/// * The changed [KeyBindings] are written directly into the `ecs`
+///   and stored right away.
+/// * While a key is captured, `ESCAPE` cancels the capture, so it
+///   can't be bound to another action.
+///
+pub fn draw_key_bindings(ecs: &World, ctx: &Rltk, view: KeyBindingsView) -> KeyBindingsResult {
+    let mut key_bindings = ecs.fetch_mut::<KeyBindings>();
+    let mut view = view;
+
+    view.selection = view.selection.min(KeyAction::ALL.len() - 1);
+
+    let first_row = 6;
+    let back_row = ecs.fetch::<GameConfig>().window_height - 4;
+    let action = KeyAction::ALL[view.selection];
 
-        ctx.print_color(
-            arrow_position.x,
-            arrow_position.y,
-            fg,
-            bg,
-            &"->".to_string(),
-        )
+    if view.capturing {
+        match ctx.key {
+            Some(VirtualKeyCode::Escape) => view.capturing = false,
+            Some(key) if keybindings::key_name(key).is_some() => {
+                key_bindings.bind(action, key);
+                key_bindings.save();
+                view.capturing = false;
+            }
+            _ => {}
+        }
     } else {
-        let start_x = x + 3;
-        let arrow_position = Point::new(x + 1, y);
+        if let Some(row) = clicked_row(ctx) {
+            if row == back_row {
+                return KeyBindingsResult::Closed;
+            }
+
+            let index = row - first_row;
+
+            if index >= 0 && (index as usize) < KeyAction::ALL.len() {
+                view.selection = index as usize;
+                view.capturing = true;
+            }
+        }
 
-        for tooltip in tooltips.iter() {
-            ctx.print_color(start_x, y_position, fg, bg, &tooltip);
-            y_position += 1;
+        match ctx.key {
+            Some(VirtualKeyCode::Escape) => return KeyBindingsResult::Closed,
+            Some(VirtualKeyCode::Up) => {
+                view.selection = (view.selection + KeyAction::ALL.len() - 1) % KeyAction::ALL.len()
+            }
+            Some(VirtualKeyCode::Down) => {
+                view.selection = (view.selection + 1) % KeyAction::ALL.len()
+            }
+            Some(VirtualKeyCode::Return) => view.capturing = true,
+            Some(VirtualKeyCode::Back) | Some(VirtualKeyCode::Delete) => {
+                key_bindings.clear(action);
+                key_bindings.save();
+            }
+            Some(VirtualKeyCode::R) => {
+                *key_bindings = KeyBindings::default();
+                key_bindings.save();
+            }
+            _ => {}
         }
+    }
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::MENU_TITLE.colors();
+    batch.print_color_centered(3, localization::tr("keys.title"), ColorPair::new(fg, bg));
+
+    for (y, (index, action)) in (first_row..).zip(KeyAction::ALL.iter().enumerate()) {
+        let (fg, bg) = if index == view.selection {
+            swatch::MENU_OPTION_SELECTED.colors()
+        } else {
+            swatch::MENU_OPTION.colors()
+        };
 
-        ctx.print_color(
-            arrow_position.x,
-            arrow_position.y,
-            fg,
-            bg,
-            &"<-".to_string(),
+        let keys = key_bindings
+            .keys(*action)
+            .iter()
+            .filter_map(|key| keybindings::key_name(*key))
+            .collect::<Vec<_>>();
+
+        let keys = if index == view.selection && view.capturing {
+            localization::tr("keys.capture")
+        } else if keys.is_empty() {
+            localization::tr("keys.unbound")
+        } else {
+            keys.join(", ")
+        };
+
+        batch.print_color(
+            Point::new(10, y),
+            action.description(),
+            ColorPair::new(fg, bg),
         );
+        batch.print_color(Point::new(40, y), keys, ColorPair::new(fg, bg));
+    }
+
+    let (fg, bg) = swatch::MENU_OPTION.colors();
+    batch.print_color_centered(
+        back_row - 2,
+        localization::tr("keys.help"),
+        ColorPair::new(fg, bg),
+    );
+
+    let (fg, bg) = swatch::DIALOG_DISMISS_BUTTON.colors();
+    batch.print_color_centered(
+        back_row,
+        localization::tr("keys.back"),
+        ColorPair::new(fg, bg),
+    );
+
+    submit_batch(&mut batch, config::LAYER_UI);
+
+    KeyBindingsResult::Browsing { view }
+}
+
+/// Draws the character creation screen, on which the player names the
+/// player [Entity], to the [config::LAYER_UI] and handles the player's
+/// input on it.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player [Entity] is stored.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+///
+/// # Notes
+/// * The typed name is written directly into the [Name] component of the player.
+/// * [MenuResult::Selected] is returned once the player confirms a non empty name.
+///
+pub fn draw_character_creation(ecs: &World, ctx: &Rltk) -> MenuResult<()> {
+    let player = ecs.fetch::<Entity>();
+    let mut names = ecs.write_storage::<Name>();
+
+    let name = match names.get_mut(*player) {
+        None => return MenuResult::Cancel,
+        Some(name) => name,
+    };
+
+    // The confirm and back options can be tapped as well
+    let key = match clicked_row(ctx) {
+        Some(30) => Some(VirtualKeyCode::Return),
+        Some(32) => Some(VirtualKeyCode::Escape),
+        _ => ctx.key,
+    };
+
+    match key {
+        Some(VirtualKeyCode::Escape) => return MenuResult::Cancel,
+        Some(VirtualKeyCode::Return) if !name.name.trim().is_empty() => {
+            name.name = name.name.trim().to_string();
+            return MenuResult::Selected(());
+        }
+        Some(VirtualKeyCode::Back) => {
+            name.name.pop();
+        }
+        Some(key) => {
+            if let Some(character) = virtual_key_code_to_char(key, ctx.shift) {
+                if name.name.len() < MAX_NAME_LENGTH {
+                    name.name.push(character);
+                }
+            }
+        }
+        None => {}
+    }
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::MENU_TITLE.colors();
+    batch.print_color_centered(
+        15,
+        localization::tr("creation.title"),
+        ColorPair::new(fg, bg),
+    );
+
+    let (fg, bg) = swatch::MENU_OPTION_SELECTED.colors();
+    batch.print_color_centered(20, format!(" {}_ ", name.name), ColorPair::new(fg, bg));
+
+    let (fg, bg) = swatch::MENU_OPTION.colors();
+    batch.print_color_centered(
+        30,
+        localization::tr("creation.confirm"),
+        ColorPair::new(fg, bg),
+    );
+    batch.print_color_centered(
+        32,
+        localization::tr("creation.back"),
+        ColorPair::new(fg, bg),
+    );
+
+    submit_batch(&mut batch, config::LAYER_UI);
+
+    MenuResult::NoResponse
+}
+
+/// Draws the game over screen, which is shown after the player has
+/// died, with a summary of the run and its score, see
+/// [valuation::run_score], to the [config::LAYER_UI] and
+/// handles the player's input on it.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [RunStatistics] are stored.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+///
+/// # Notes
+/// * Returns [MenuResult::Selected] once the player wants to
+///   start a new run or quit.
+///
+pub fn draw_game_over(ecs: &World, ctx: &Rltk) -> MenuResult<GameOverSelection> {
+    let run_statistics = ecs.fetch::<RunStatistics>();
+    let turn_counter = ecs.fetch::<TurnCounter>();
+    let depth = ecs.fetch::<Map>().depth;
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::MENU_TITLE.colors();
+    batch.print_color_centered(
+        10,
+        localization::tr("game_over.title"),
+        ColorPair::new(fg, bg),
+    );
+
+    let (fg, bg) = swatch::MENU_OPTION.colors();
+    batch.print_color_centered(
+        13,
+        localization::tr("game_over.message"),
+        ColorPair::new(fg, bg),
+    );
+
+    let mut summary = vec![
+        localization::tr_with("game_over.turns", &[&turn_counter.turn]),
+        localization::tr_with("game_over.depth", &[&depth]),
+        localization::tr_with("game_over.kills", &[&run_statistics.kills]),
+        localization::tr_with(
+            "game_over.damage",
+            &[&run_statistics.damage_dealt, &run_statistics.damage_taken],
+        ),
+    ];
+
+    if !run_statistics.uniques_slain.is_empty() {
+        let uniques = run_statistics
+            .uniques_slain
+            .iter()
+            .map(|name| localization::entity_name(name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        summary.push(localization::tr_with("game_over.uniques", &[&uniques]));
+    }
+
+    summary.push(if run_statistics.wizard {
+        localization::tr("game_over.unscored")
+    } else {
+        localization::tr_with("game_over.score", &[&valuation::run_score(ecs)])
+    });
+
+    if let Some(best_score) = ecs
+        .try_fetch::<DailyRun>()
+        .and_then(|daily_run| daily_run.best_score)
+    {
+        summary.push(localization::tr_with(
+            "game_over.daily_best",
+            &[&best_score],
+        ));
+    }
+
+    for (y, line) in (17..).zip(summary.iter()) {
+        batch.print_color_centered(y, line, ColorPair::new(fg, bg));
+    }
+
+    let (fg, bg) = swatch::MENU_OPTION_SELECTED.colors();
+    batch.print_color_centered(
+        24,
+        localization::tr("game_over.new_run"),
+        ColorPair::new(fg, bg),
+    );
+    batch.print_color_centered(
+        26,
+        localization::tr("game_over.quit"),
+        ColorPair::new(fg, bg),
+    );
+
+    submit_batch(&mut batch, config::LAYER_UI);
+
+    match (ctx.key, clicked_row(ctx)) {
+        (Some(VirtualKeyCode::N), _) | (_, Some(24)) => {
+            MenuResult::Selected(GameOverSelection::NewRun)
+        }
+        (Some(VirtualKeyCode::Q), _) | (_, Some(26)) => {
+            MenuResult::Selected(GameOverSelection::Quit)
+        }
+        _ => MenuResult::NoResponse,
+    }
+}
+
+/// Enum describing the categories the items
+/// in the inventory are grouped by.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
+pub enum ItemCategory {
+    /// Items, that are used on their user, e.g. potions.
+    Consumable,
+
+    /// Items, that are used on a target tile.
+    Targeted,
+
+    /// Items, that are worn, e.g. weapons.
+    Equipment,
+
+    /// Items without any effect.
+    Miscellaneous,
+}
+
+impl ItemCategory {
+    /// Returns the category of the passed `item`.
+    ///
+    /// # Arguments
+    /// * `item`: The item [Entity] to categorize.
+    /// * `scripts`: The [Scripted] storage of the `ecs`.
+    /// * `ranged`: The [Ranged] storage of the `ecs`.
+    /// * `equippables`: The [Equippable] storage of the `ecs`.
+    ///
+    fn of(
+        item: Entity,
+        scripts: &ReadStorage<Scripted>,
+        ranged: &ReadStorage<Ranged>,
+        equippables: &ReadStorage<Equippable>,
+    ) -> ItemCategory {
+        if ranged.contains(item) {
+            ItemCategory::Targeted
+        } else if scripts.contains(item) {
+            ItemCategory::Consumable
+        } else if equippables.contains(item) {
+            ItemCategory::Equipment
+        } else {
+            ItemCategory::Miscellaneous
+        }
+    }
+
+    /// Returns the translated title of the category.
+    fn title(&self) -> String {
+        match self {
+            ItemCategory::Consumable => localization::tr("inventory.category.consumable"),
+            ItemCategory::Targeted => localization::tr("inventory.category.targeted"),
+            ItemCategory::Equipment => localization::tr("inventory.category.equipment"),
+            ItemCategory::Miscellaneous => localization::tr("inventory.category.miscellaneous"),
+        }
+    }
+}
+
+/// Enum describing the actions the player can
+/// take on the selected item of the inventory.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum InventoryAction {
+    /// Uses the item.
+    Use,
+
+    /// Drops the item.
+    Drop,
+
+    /// Puts the item on or takes it off.
+    Equip,
+
+    /// Shows the description of the item.
+    Examine,
+}
+
+impl InventoryAction {
+    /// All actions in the order of their display.
+    const ALL: [InventoryAction; 4] = [
+        InventoryAction::Use,
+        InventoryAction::Drop,
+        InventoryAction::Equip,
+        InventoryAction::Examine,
+    ];
+
+    /// Returns the key, that triggers the action.
+    fn key(&self) -> VirtualKeyCode {
+        match self {
+            InventoryAction::Use => VirtualKeyCode::U,
+            InventoryAction::Drop => VirtualKeyCode::D,
+            InventoryAction::Equip => VirtualKeyCode::E,
+            InventoryAction::Examine => VirtualKeyCode::X,
+        }
+    }
+
+    /// Returns the translated text displayed for the action.
+    fn description(&self) -> String {
+        match self {
+            InventoryAction::Use => localization::tr("inventory.action.use"),
+            InventoryAction::Drop => localization::tr("inventory.action.drop"),
+            InventoryAction::Equip => localization::tr("inventory.action.equip"),
+            InventoryAction::Examine => localization::tr("inventory.action.examine"),
+        }
+    }
+}
+
+/// The state of the inventory screen, that is
+/// kept between the frames it's displayed in.
+#[derive(PartialEq, Copy, Clone, Default, Debug)]
+pub struct InventoryView {
+    /// The index of the displayed page.
+    pub page: usize,
+
+    /// The selected item, whose actions are displayed.
+    pub item: Option<Entity>,
+
+    /// Flag indicating whether or not the
+    /// selected item has been examined.
+    pub examined: bool,
+}
+
+impl InventoryView {
+    /// Creates a new [InventoryView] of the passed `page`.
+    ///
+    /// # Arguments
+    /// * `page`: The index of the displayed page.
+    /// * `item`: The selected item, if any.
+    ///
+    fn new(page: usize, item: Option<Entity>) -> Self {
+        InventoryView {
+            page,
+            item,
+            examined: false,
+        }
+    }
+}
+
+/// Enum describing the result of the player's
+/// interaction with the inventory screen.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum InventoryResult {
+    /// The player is browsing the inventory.
+    Browsing {
+        /// The state of the inventory screen.
+        view: InventoryView,
+    },
+
+    /// The player has closed the inventory.
+    Closed,
+
+    /// The player wants to use the `item`.
+    Use {
+        /// The selected item.
+        item: Entity,
+    },
+
+    /// The player wants to drop the `item`.
+    Drop {
+        /// The selected item.
+        item: Entity,
+    },
+
+    /// The player wants to put the `item` on or take it off.
+    Equip {
+        /// The selected item.
+        item: Entity,
+    },
+}
+
+/// Splits the passed `text` into lines of at most `width`
+/// characters. Words longer than the `width` are not split.
+///
+/// # Arguments
+/// * `text`: The text to split.
+/// * `width`: The maximum number of characters of a line.
+///
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + word.chars().count() + 1 > width {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+
+        line.push_str(word);
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Draws the inventory screen of the player to the [config::LAYER_UI]
+/// and handles the player's input on it. The items are grouped by their
+/// [ItemCategory] and split into pages of [config::INVENTORY_PAGE_SIZE]
+/// items. Selecting an item lists the [InventoryAction]s on it.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player and its items are stored.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+/// * `view`: The current state of the inventory screen.
+///
+/// # Notes
+/// * Items are selected by their letter on the page, the actions by
+///   their key. The pages are turned with the arrow or page keys.
+/// * Everything can be tapped as well, the dismiss button at the
+///   bottom of the screen works like `ESCAPE`.
+///
+pub fn draw_inventory(ecs: &World, ctx: &Rltk, view: InventoryView) -> InventoryResult {
+    let entities = ecs.entities();
+    let player = ecs.fetch::<Entity>();
+    let identification = ecs.fetch::<Identification>();
+    let names = ecs.read_storage::<Name>();
+    let backpack = ecs.read_storage::<Loot>();
+    let scripts = ecs.read_storage::<Scripted>();
+    let ranged = ecs.read_storage::<Ranged>();
+    let equippables = ecs.read_storage::<Equippable>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let cursed = ecs.read_storage::<Cursed>();
+    let stackables = ecs.read_storage::<Stackable>();
+    let weights = ecs.read_storage::<Weight>();
+
+    let mut items = (&entities, &backpack, &names)
+        .join()
+        .filter(|item| item.1.owner == *player)
+        .map(|(entity, _, name)| {
+            let mut display_name = identification.display(name);
+
+            if let Some(stackable) = stackables.get(entity) {
+                display_name = stackable.label(display_name);
+            }
+
+            // Curses are only known once the item has been worn
+            if cursed.get(entity).is_some_and(|curse| curse.revealed) {
+                display_name = localization::tr_with("inventory.cursed", &[&display_name]);
+            }
+
+            if equipped.contains(entity) {
+                display_name = localization::tr_with("inventory.equipped", &[&display_name]);
+            }
+
+            (
+                ItemCategory::of(entity, &scripts, &ranged, &equippables),
+                display_name,
+                entity,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    items.sort();
+
+    let page_count = items.len().div_ceil(config::INVENTORY_PAGE_SIZE).max(1);
+    let page = view.page.min(page_count - 1);
+    let page_items = items
+        .iter()
+        .skip(page * config::INVENTORY_PAGE_SIZE)
+        .take(config::INVENTORY_PAGE_SIZE)
+        .collect::<Vec<_>>();
+
+    // The selected item may have been used up in the meantime
+    let selected = page_items
+        .iter()
+        .find(|(_, _, entity)| Some(*entity) == view.item);
+
+    let (width, height) = {
+        let game_config = ecs.fetch::<GameConfig>();
+        (game_config.window_width - 1, game_config.window_height - 1)
+    };
+    let details_x = width / 2 + 2;
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::DIALOG_FRAME.colors();
+    batch.draw_box(Rect::with_size(0, 0, width, height), ColorPair::new(fg, bg));
+
+    let (fg, bg) = swatch::DIALOG_TITLE.colors();
+    batch.print_color(
+        Point::new(2, 0),
+        localization::tr("inventory.title"),
+        ColorPair::new(fg, bg),
+    );
+
+    let page_text = localization::tr_with("inventory.page", &[&(page + 1), &page_count]);
+    batch.print_color(
+        Point::new(width - 2 - page_text.chars().count() as i32, 0),
+        &page_text,
+        ColorPair::new(fg, bg),
+    );
+
+    // The carried weight is shown in the middle of the top border
+    let carried = Weight::carried(*player, &backpack, &weights, &stackables);
+    let capacity = ecs
+        .read_storage::<Statistics>()
+        .get(*player)
+        .map_or(0, Statistics::carry_capacity);
+    let weight_key = if carried > capacity {
+        "inventory.weight.burdened"
+    } else {
+        "inventory.weight"
+    };
+    let weight_text = localization::tr_with(weight_key, &[&carried, &capacity]);
+    batch.print_color(
+        Point::new((width - weight_text.chars().count() as i32) / 2, 0),
+        &weight_text,
+        ColorPair::new(fg, bg),
+    );
+
+    // The list of the items on the page, grouped by their category
+    let mut item_rows: Vec<(i32, Entity)> = Vec::new();
+    let mut category: Option<ItemCategory> = None;
+    let mut y = 2;
+
+    if items.is_empty() {
+        batch.print(Point::new(2, y), localization::tr("inventory.empty"));
+    }
+
+    for (index, (item_category, name, entity)) in page_items.iter().enumerate() {
+        if category != Some(*item_category) {
+            if category.is_some() {
+                y += 1;
+            }
+
+            let (fg, bg) = swatch::MENU_TITLE.colors();
+            batch.print_color(
+                Point::new(2, y),
+                item_category.title(),
+                ColorPair::new(fg, bg),
+            );
+
+            category = Some(*item_category);
+            y += 1;
+        }
+
+        let (fg, bg) = if Some(*entity) == view.item {
+            swatch::MENU_OPTION_SELECTED.colors()
+        } else {
+            swatch::DIALOG_OPTION.colors()
+        };
+        let key_string = virtual_key_code_to_string(i32_to_alpha_key(index as i32));
+
+        batch.print_color(
+            Point::new(4, y),
+            format!("{} - {}", key_string, name),
+            ColorPair::new(fg, bg),
+        );
+
+        item_rows.push((y, *entity));
+        y += 1;
+    }
+
+    // The details and actions of the selected item
+    let mut action_rows: Vec<(i32, InventoryAction)> = Vec::new();
+
+    match selected {
+        None if !items.is_empty() => {
+            batch.print(
+                Point::new(details_x, 2),
+                localization::tr("inventory.prompt"),
+            );
+        }
+        None => {}
+        Some((item_category, name, entity)) => {
+            let (fg, bg) = swatch::DIALOG_TITLE.colors();
+            batch.print_color(Point::new(details_x, 2), name, ColorPair::new(fg, bg));
+            batch.print(Point::new(details_x, 3), item_category.title());
+
+            let (fg, bg) = swatch::DIALOG_OPTION.colors();
+            let mut y = 5;
+
+            for action in InventoryAction::ALL.iter() {
+                batch.print_color(
+                    Point::new(details_x, y),
+                    action.description(),
+                    ColorPair::new(fg, bg),
+                );
+
+                action_rows.push((y, *action));
+                y += 2;
+            }
+
+            if view.examined {
+                // The description would give away the nature of unidentified items
+                let description = match names.get(*entity) {
+                    Some(name) if !identification.is_identified(&name.name) => {
+                        localization::tr("inventory.unidentified")
+                    }
+                    name => name
+                        .and_then(|name| localization::entity_description(&name.name))
+                        .unwrap_or_else(|| localization::tr("inventory.no_description")),
+                };
+                let line_width = (width - details_x - 2) as usize;
+
+                for (line_y, line) in (y + 1..).zip(wrap_text(&description, line_width)) {
+                    batch.print(Point::new(details_x, line_y), line);
+                }
+            }
+        }
+    }
+
+    // The buttons in the bottom border of the screen
+    let (fg, bg) = swatch::DIALOG_DISMISS_BUTTON.colors();
+    let dismiss_text = localization::tr(if selected.is_some() {
+        "inventory.back"
+    } else {
+        "inventory.dismiss"
+    });
+    let dismiss_area = Rect::with_size(2, height, dismiss_text.chars().count() as i32, 1);
+
+    batch.print_color(
+        Point::new(dismiss_area.x1, dismiss_area.y1),
+        &dismiss_text,
+        ColorPair::new(fg, bg),
+    );
+
+    let previous_text = localization::tr("inventory.previous");
+    let previous_area = Rect::with_size(details_x, height, previous_text.chars().count() as i32, 1);
+
+    if page > 0 {
+        batch.print_color(
+            Point::new(previous_area.x1, previous_area.y1),
+            &previous_text,
+            ColorPair::new(fg, bg),
+        );
+    }
+
+    let next_text = localization::tr("inventory.next");
+    let next_width = next_text.chars().count() as i32;
+    let next_area = Rect::with_size(width - 2 - next_width, height, next_width, 1);
+
+    if page + 1 < page_count {
+        batch.print_color(
+            Point::new(next_area.x1, next_area.y1),
+            &next_text,
+            ColorPair::new(fg, bg),
+        );
+    }
+
+    submit_batch(&mut batch, config::LAYER_UI);
+
+    let browse = |view: InventoryView| InventoryResult::Browsing { view };
+    let back = if selected.is_some() {
+        browse(InventoryView::new(page, None))
+    } else {
+        InventoryResult::Closed
+    };
+    let previous_page = browse(InventoryView::new(page.saturating_sub(1), None));
+    let next_page = browse(InventoryView::new((page + 1).min(page_count - 1), None));
+
+    let act = |item: Entity, action: InventoryAction| match action {
+        InventoryAction::Use => InventoryResult::Use { item },
+        InventoryAction::Drop => InventoryResult::Drop { item },
+        InventoryAction::Equip => InventoryResult::Equip { item },
+        InventoryAction::Examine => browse(InventoryView {
+            page,
+            item: Some(item),
+            examined: true,
+        }),
+    };
+
+    let current = browse(InventoryView {
+        page,
+        item: selected.map(|(_, _, entity)| *entity),
+        examined: view.examined,
+    });
+
+    if ctx.left_click {
+        let point = ctx.mouse_point();
+
+        if dismiss_area.point_in_rect(point) {
+            return back;
+        }
+
+        if previous_area.point_in_rect(point) {
+            return previous_page;
+        }
+
+        if next_area.point_in_rect(point) {
+            return next_page;
+        }
+
+        if point.x < details_x {
+            if let Some((_, entity)) = item_rows.iter().find(|(row, _)| *row == point.y) {
+                return browse(InventoryView::new(page, Some(*entity)));
+            }
+        } else if let Some((_, _, entity)) = selected {
+            if let Some((_, action)) = action_rows.iter().find(|(row, _)| *row == point.y) {
+                return act(*entity, *action);
+            }
+        }
+
+        return current;
+    }
+
+    match ctx.key {
+        None => current,
+        Some(VirtualKeyCode::Escape) => back,
+        Some(VirtualKeyCode::Left) | Some(VirtualKeyCode::PageUp) => previous_page,
+        Some(VirtualKeyCode::Right) | Some(VirtualKeyCode::PageDown) => next_page,
+        Some(key) => match selected {
+            Some((_, _, entity)) => InventoryAction::ALL
+                .iter()
+                .find(|action| action.key() == key)
+                .map_or(current, |action| act(*entity, *action)),
+            None => {
+                let selection = rltk::letter_to_option(key);
+
+                if selection >= 0 && (selection as usize) < page_items.len() {
+                    browse(InventoryView::new(
+                        page,
+                        Some(page_items[selection as usize].2),
+                    ))
+                } else {
+                    current
+                }
+            }
+        },
+    }
+}
+
+/// The state of the message log history screen, that
+/// is kept between the frames it's displayed in.
+#[derive(PartialEq, Copy, Clone, Default, Debug)]
+pub struct LogHistoryView {
+    /// The amount of lines scrolled up from the newest message.
+    pub offset: usize,
+}
+
+/// Enum describing the result of the player's
+/// interaction with the message log history screen.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum LogHistoryResult {
+    /// The player is reading the message log history.
+    Browsing {
+        /// The state of the message log history screen.
+        view: LogHistoryView,
+    },
+
+    /// The player has closed the message log history.
+    Closed,
+}
+
+/// Splits all entries of the [GameLog] into the lines of the message
+/// log history, from the oldest to the newest, with their colors.
+///
+/// # Arguments
+/// * `game_log`: The [GameLog] holding the entries.
+/// * `width`: The maximum number of characters of a line.
+///
+fn log_history_lines(game_log: &GameLog, width: usize) -> Vec<(String, RGB)> {
+    let mut lines = Vec::new();
+
+    for entry in game_log.iter() {
+        let prefix = format!("{:>4} > ", entry.turn);
+        let indent = " ".repeat(prefix.chars().count());
+        let text_width = width.saturating_sub(prefix.chars().count()).max(1);
+
+        for (index, line) in wrap_text(&entry.text, text_width).into_iter().enumerate() {
+            let prefix = if index == 0 { &prefix } else { &indent };
+            lines.push((format!("{}{}", prefix, line), entry.color));
+        }
+    }
+
+    lines
+}
+
+/// Draws the message log history, which holds all entries of the
+/// [GameLog], to the [config::LAYER_UI] and handles the player's
+/// input on it.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [GameLog] is stored.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+/// * `view`: The current state of the message log history screen.
+///
+/// # Notes
+/// * The history opens at the newest entries. `UP` and `DOWN` scroll by a
+///   line, `PAGE UP` and `PAGE DOWN` by a page and `HOME` and `END` jump
+///   to the oldest and the newest entry.
+/// * `ESCAPE` or the key of [KeyAction::LogHistory] close the history.
+///
+pub fn draw_log_history(ecs: &World, ctx: &Rltk, view: LogHistoryView) -> LogHistoryResult {
+    let game_log = ecs.fetch::<GameLog>();
+
+    let (width, height) = {
+        let game_config = ecs.fetch::<GameConfig>();
+        (game_config.window_width - 1, game_config.window_height - 1)
+    };
+
+    let first_row = 2;
+    let page_size = (height - first_row - 1).max(1) as usize;
+    let lines = log_history_lines(&game_log, (width - 4).max(1) as usize);
+    let max_offset = lines.len().saturating_sub(page_size);
+
+    let mut view = view;
+    view.offset = view.offset.min(max_offset);
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::DIALOG_FRAME.colors();
+    batch.draw_box(Rect::with_size(0, 0, width, height), ColorPair::new(fg, bg));
+
+    let (fg, bg) = swatch::DIALOG_TITLE.colors();
+    batch.print_color(
+        Point::new(2, 0),
+        localization::tr("history.title"),
+        ColorPair::new(fg, bg),
+    );
+
+    let last_line = lines.len() - view.offset;
+    let first_line = last_line.saturating_sub(page_size);
+
+    let position_text = localization::tr_with(
+        "history.position",
+        &[&(first_line + 1).min(last_line), &last_line, &lines.len()],
+    );
+    batch.print_color(
+        Point::new(width - 2 - position_text.chars().count() as i32, 0),
+        &position_text,
+        ColorPair::new(fg, bg),
+    );
+
+    if lines.is_empty() {
+        batch.print(Point::new(2, first_row), localization::tr("history.empty"));
+    }
+
+    let (_, bg) = swatch::DIALOG_FRAME.colors();
+
+    for (y, (line, color)) in (first_row..).zip(&lines[first_line..last_line]) {
+        batch.print_color(Point::new(2, y), line, ColorPair::new(*color, bg));
+    }
+
+    // The button and the help in the bottom border of the screen
+    let (fg, bg) = swatch::DIALOG_DISMISS_BUTTON.colors();
+    let dismiss_text = localization::tr("history.back");
+    let dismiss_area = Rect::with_size(2, height, dismiss_text.chars().count() as i32, 1);
+
+    batch.print_color(
+        Point::new(dismiss_area.x1, dismiss_area.y1),
+        &dismiss_text,
+        ColorPair::new(fg, bg),
+    );
+
+    let help_text = localization::tr("history.help");
+    batch.print_color(
+        Point::new(width - 2 - help_text.chars().count() as i32, height),
+        &help_text,
+        ColorPair::new(fg, bg),
+    );
+
+    submit_batch(&mut batch, config::LAYER_UI);
+
+    if ctx.left_click && dismiss_area.point_in_rect(ctx.mouse_point()) {
+        return LogHistoryResult::Closed;
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => return LogHistoryResult::Closed,
+        Some(VirtualKeyCode::Up) => view.offset = (view.offset + 1).min(max_offset),
+        Some(VirtualKeyCode::Down) => view.offset = view.offset.saturating_sub(1),
+        Some(VirtualKeyCode::PageUp) => view.offset = (view.offset + page_size).min(max_offset),
+        Some(VirtualKeyCode::PageDown) => view.offset = view.offset.saturating_sub(page_size),
+        Some(VirtualKeyCode::Home) => view.offset = max_offset,
+        Some(VirtualKeyCode::End) => view.offset = 0,
+        Some(key) if ecs.fetch::<KeyBindings>().action(key) == Some(KeyAction::LogHistory) => {
+            return LogHistoryResult::Closed
+        }
+        _ => {}
+    }
+
+    LogHistoryResult::Browsing { view }
+}
+
+/// Highlights all tiles in the `range` of the player, that can be targeted,
+/// in the [config::LAYER_OVERLAY] and handles the selection of a target
+/// tile with the mouse.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player is stored.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+/// * `range`: The maximum distance between the player and the target.
+/// * `radius`: The radius of the [AreaOfEffect] around the target, if any.
+///
+/// # Notes
+/// * Only tiles in the [FOV] of the player can be targeted.
+/// * The tiles of the map hit around a valid target are highlighted as well.
+/// * The selected target is returned in world coordinates.
+///
+pub fn draw_player_ranged_targeting(
+    ecs: &World,
+    ctx: &Rltk,
+    range: i32,
+    radius: Option<i32>,
+) -> MenuResult<Point> {
+    let map = ecs.fetch::<Map>();
+    let camera = Camera::new(ecs);
+    let player = ecs.fetch::<Entity>();
+    let player_position = ecs.fetch::<Point>();
+    let fovs = ecs.read_storage::<FOV>();
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::DIALOG_TITLE.colors();
+    batch.print_color(
+        Point::new(5, 0),
+        localization::tr("ui.targeting"),
+        ColorPair::new(fg, bg),
+    );
+
+    let mut available_tiles: Vec<Point> = Vec::new();
+
+    if let Some(fov) = fovs.get(*player) {
+        for tile in fov.content.iter() {
+            if pythagoras_distance(&player_position, tile) <= range as f32 {
+                if let Some(screen) = camera.to_screen(*tile) {
+                    batch.set_bg(screen, swatch::TARGETING_RANGE);
+                }
+                available_tiles.push(*tile);
+            }
+        }
+    }
+
+    let mouse_point = ctx.mouse_point();
+    let target = camera.to_world(mouse_point);
+    let is_valid_target = target.is_some_and(|target| available_tiles.contains(&target));
+
+    if let (true, Some(target), Some(radius)) = (is_valid_target, target, radius) {
+        let area = AreaOfEffect { radius };
+
+        for y in target.y - radius..=target.y + radius {
+            for x in target.x - radius..=target.x + radius {
+                let tile = Point::new(x, y);
+
+                if !map.check_idx(x, y) || !area.covers(&target, &tile) {
+                    continue;
+                }
+
+                if let Some(screen) = camera.to_screen(tile) {
+                    batch.set_bg(screen, swatch::TARGETING_AREA);
+                }
+            }
+        }
+    }
+
+    if is_valid_target {
+        batch.set_bg(mouse_point, swatch::TARGETING_CURSOR);
+    } else {
+        batch.set_bg(mouse_point, swatch::TARGETING_CURSOR_INVALID);
+    }
+
+    submit_batch(&mut batch, config::LAYER_OVERLAY);
+
+    if ctx.key == Some(VirtualKeyCode::Escape) {
+        return MenuResult::Cancel;
+    }
+
+    if ctx.left_click {
+        if let (true, Some(target)) = (is_valid_target, target) {
+            return MenuResult::Selected(target);
+        }
+        return MenuResult::Cancel;
+    }
+
+    MenuResult::NoResponse
+}
+
+/// Draws the cursor, with which the player picks the tile to teleport to in
+/// the wizard mode, to the [config::LAYER_OVERLAY] and handles the player's
+/// input. Returns the selected tile, once the player has clicked it.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the [Map].
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+///
+/// # Notes
+/// * Every explored tile, that isn't blocked, can be selected,
+///   even if it's out of the player's [FOV].
+/// * The selected target is returned in world coordinates.
+///
+pub fn draw_wizard_teleport(ecs: &World, ctx: &Rltk) -> MenuResult<Point> {
+    let map = ecs.fetch::<Map>();
+    let camera = Camera::new(ecs);
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::DIALOG_TITLE.colors();
+    batch.print_color(
+        Point::new(5, 0),
+        localization::tr("wizard.teleport.targeting"),
+        ColorPair::new(fg, bg),
+    );
+
+    let mouse_point = ctx.mouse_point();
+    let target = camera.to_world(mouse_point).filter(|target| {
+        map.check_idx(target.x, target.y)
+            && map.is_tile_explored(target.x, target.y)
+            && !map.is_tile_blocked(target.x, target.y)
+    });
+
+    if target.is_some() {
+        batch.set_bg(mouse_point, swatch::TARGETING_CURSOR);
+    } else {
+        batch.set_bg(mouse_point, swatch::TARGETING_CURSOR_INVALID);
+    }
+
+    submit_batch(&mut batch, config::LAYER_OVERLAY);
+
+    if ctx.key == Some(VirtualKeyCode::Escape) {
+        return MenuResult::Cancel;
+    }
+
+    if ctx.left_click {
+        return match target {
+            Some(target) => MenuResult::Selected(target),
+            None => MenuResult::Cancel,
+        };
+    }
+
+    MenuResult::NoResponse
+}
+
+/// Enum describing the result of the player's
+/// interaction with the debug console.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum DebugConsoleResult {
+    /// The player is typing a command.
+    Typing,
+
+    /// The player has submitted the typed command.
+    Submitted,
+
+    /// The player has closed the console.
+    Closed,
+}
+
+/// Draws the [DebugConsole] as an overlay at the top of the screen to the
+/// [config::LAYER_DIALOG] and handles the player's input on it.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [DebugConsole] is registered.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+///
+/// # Notes
+/// * The typed command is written directly into the [DebugConsole],
+///   only letters, digits and spaces can be typed.
+/// * The console is closed through the tilde or escape key.
+///
+pub fn draw_debug_console(ecs: &World, ctx: &Rltk) -> DebugConsoleResult {
+    let mut console = ecs.fetch_mut::<DebugConsole>();
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) | Some(VirtualKeyCode::Grave) => {
+            return DebugConsoleResult::Closed
+        }
+        Some(VirtualKeyCode::Return) => return DebugConsoleResult::Submitted,
+        Some(VirtualKeyCode::Back) => {
+            console.input.pop();
+        }
+        Some(key) => {
+            let character =
+                virtual_key_code_to_char(key, false).or_else(|| virtual_key_code_to_digit(key));
+
+            if let Some(character) = character {
+                if console.input.len() < debug_console::MAX_INPUT_LENGTH {
+                    console.input.push(character);
+                }
+            }
+        }
+        None => {}
+    }
+
+    let width = ecs.fetch::<GameConfig>().viewport_width();
+    let height = debug_console::MAX_OUTPUT_LINES as i32 + 3;
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::DIALOG_FRAME.colors();
+    batch.draw_box(
+        Rect::with_size(0, 0, width - 1, height),
+        ColorPair::new(fg, bg),
+    );
+
+    let (fg, bg) = swatch::DIALOG_TITLE.colors();
+    batch.print_color(
+        Point::new(2, 0),
+        localization::tr("console.title"),
+        ColorPair::new(fg, bg),
+    );
+
+    let (fg, bg) = swatch::DIALOG_OPTION.colors();
+    for (y, line) in (1..).zip(console.output.iter()) {
+        batch.print_color(Point::new(2, y), line, ColorPair::new(fg, bg));
+    }
+
+    let (fg, bg) = swatch::MENU_OPTION_SELECTED.colors();
+    batch.print_color(
+        Point::new(2, height - 1),
+        format!("> {}_", console.input),
+        ColorPair::new(fg, bg),
+    );
+
+    submit_batch(&mut batch, config::LAYER_DIALOG);
+
+    DebugConsoleResult::Typing
+}
+
+/// Enum describing the result of the player's
+/// interaction with the quest journal.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum QuestJournalResult {
+    /// The player is reading the quest journal.
+    Open,
+
+    /// The player has closed the quest journal.
+    Closed,
+}
+
+/// Draws the quest journal, which lists all quests of the [QuestLog]
+/// with their objectives and progress, to the [config::LAYER_UI]
+/// and handles the player's input on it.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [QuestLog] is stored.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+///
+/// # Notes
+/// * Active quests are listed before the finished ones, which are grayed out.
+/// * `ESCAPE` or the key of [KeyAction::QuestJournal] close the journal.
+///
+pub fn draw_quest_journal(ecs: &World, ctx: &Rltk) -> QuestJournalResult {
+    let quest_log = ecs.fetch::<QuestLog>();
+
+    let (width, height) = {
+        let game_config = ecs.fetch::<GameConfig>();
+        (game_config.window_width - 1, game_config.window_height - 1)
+    };
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::DIALOG_FRAME.colors();
+    batch.draw_box(Rect::with_size(0, 0, width, height), ColorPair::new(fg, bg));
+
+    let (fg, bg) = swatch::DIALOG_TITLE.colors();
+    batch.print_color(
+        Point::new(2, 0),
+        localization::tr("journal.title"),
+        ColorPair::new(fg, bg),
+    );
+
+    if quest_log.quests().is_empty() {
+        batch.print(Point::new(2, 2), localization::tr("journal.empty"));
+    }
+
+    let mut quests = quest_log.quests().iter().collect::<Vec<_>>();
+    quests.sort_by_key(|quest| quest.state != QuestState::Active);
+
+    let mut y = 2;
+
+    for quest in quests.into_iter().take_while(|_| y < height - 1) {
+        let (pallet, state) = match quest.state {
+            QuestState::Active => (swatch::MENU_OPTION, "journal.active"),
+            QuestState::Completed | QuestState::Rewarded => {
+                (swatch::MENU_OPTION_DISABLED, "journal.completed")
+            }
+        };
+        let (fg, bg) = pallet.colors();
+
+        batch.print_color(
+            Point::new(2, y),
+            format!(
+                "{} ({})",
+                localization::tr(&quest.title),
+                localization::tr(state)
+            ),
+            ColorPair::new(fg, bg),
+        );
+        batch.print_color(
+            Point::new(4, y + 1),
+            format!(
+                "{} {}/{}",
+                quest.objective.description(),
+                quest.progress.min(quest.objective.goal()),
+                quest.objective.goal()
+            ),
+            ColorPair::new(fg, bg),
+        );
+
+        y += 3;
+    }
+
+    // The button in the bottom border of the screen
+    let (fg, bg) = swatch::DIALOG_DISMISS_BUTTON.colors();
+    let dismiss_text = localization::tr("journal.back");
+    let dismiss_area = Rect::with_size(2, height, dismiss_text.chars().count() as i32, 1);
+
+    batch.print_color(
+        Point::new(dismiss_area.x1, dismiss_area.y1),
+        &dismiss_text,
+        ColorPair::new(fg, bg),
+    );
+
+    submit_batch(&mut batch, config::LAYER_UI);
+
+    if ctx.left_click && dismiss_area.point_in_rect(ctx.mouse_point()) {
+        return QuestJournalResult::Closed;
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => QuestJournalResult::Closed,
+        Some(key) if ecs.fetch::<KeyBindings>().action(key) == Some(KeyAction::QuestJournal) => {
+            QuestJournalResult::Closed
+        }
+        _ => QuestJournalResult::Open,
+    }
+}
+
+/// Enum describing the sides of a trade on the shop screen.
+#[derive(PartialEq, Eq, Copy, Clone, Default, Debug)]
+pub enum ShopMode {
+    /// The player buys the stock of the shopkeeper.
+    #[default]
+    Buy,
+
+    /// The player sells the items in the backpack.
+    Sell,
+}
+
+/// The state of the shop screen, that is kept
+/// between the frames it's displayed in.
+#[derive(PartialEq, Copy, Clone, Default, Debug)]
+pub struct ShopView {
+    /// The side of the trade, that is displayed.
+    pub mode: ShopMode,
+}
+
+/// Enum describing the result of the player's
+/// interaction with the shop screen.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ShopResult {
+    /// The player is browsing the shop.
+    Browsing {
+        /// The state of the shop screen.
+        view: ShopView,
+    },
+
+    /// The player has left the shop.
+    Closed,
+
+    /// The player buys the item of the shopkeeper's stock.
+    Buy {
+        /// The item to buy.
+        item: Entity,
+    },
+
+    /// The player sells the item of the backpack.
+    Sell {
+        /// The item to sell.
+        item: Entity,
+    },
+}
+
+/// Draws the shop screen of the passed `shopkeeper` to the
+/// [config::LAYER_UI] and handles the player's input on it.
+/// Depending on the [ShopMode], the stock of the shopkeeper or
+/// the items of the player are listed with their prices.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the player and the shopkeeper are stored.
+/// * `ctx`: The [Rltk] context, required to read the player's input.
+/// * `shopkeeper`: The [Shopkeeper](super::Shopkeeper) the player trades with.
+/// * `view`: The current state of the shop screen.
+///
+/// # Notes
+/// * Items are bought or sold by their letter, `TAB` switches between
+///   buying and selling and `ESCAPE` leaves the shop.
+/// * Worn items and items the shopkeeper isn't interested in can't be sold.
+///
+pub fn draw_shop(ecs: &World, ctx: &Rltk, shopkeeper: Entity, view: ShopView) -> ShopResult {
+    let entities = ecs.entities();
+    let player = ecs.fetch::<Entity>();
+    let modifiers = PriceModifiers::of_player(ecs);
+    let identification = ecs.fetch::<Identification>();
+    let names = ecs.read_storage::<Name>();
+    let values = ecs.read_storage::<Value>();
+    let backpack = ecs.read_storage::<Loot>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let for_sale = ecs.read_storage::<ForSale>();
+    let stackables = ecs.read_storage::<Stackable>();
+    let gold = ecs
+        .read_storage::<Purse>()
+        .get(*player)
+        .map_or(0, |purse| purse.gold);
+
+    let mut items = match view.mode {
+        ShopMode::Buy => (&entities, &for_sale, &names, &values)
+            .join()
+            .filter(|(_, sale, _, _)| sale.shopkeeper == shopkeeper)
+            .map(|(entity, _, name, value)| {
+                let price = modifiers.buy_price(value);
+                (identification.display(name), price, entity)
+            })
+            .collect::<Vec<_>>(),
+        ShopMode::Sell => (&entities, &backpack, &names, &values, !&equipped)
+            .join()
+            .filter(|(_, loot, _, _, _)| loot.owner == *player)
+            .map(|(entity, _, name, value, _)| {
+                let identified = identification.is_identified(&name.name);
+                let price = modifiers.sell_price(value, identified);
+                let display_name = match stackables.get(entity) {
+                    Some(stackable) => stackable.label(identification.display(name)),
+                    None => identification.display(name),
+                };
+
+                (display_name, price, entity)
+            })
+            .collect::<Vec<_>>(),
+    };
+
+    items.sort();
+    items.truncate(26);
+
+    let (width, height) = {
+        let game_config = ecs.fetch::<GameConfig>();
+        (game_config.window_width - 1, game_config.window_height - 1)
+    };
+
+    let mut batch = DrawBatch::new();
+
+    let (fg, bg) = swatch::DIALOG_FRAME.colors();
+    batch.draw_box(Rect::with_size(0, 0, width, height), ColorPair::new(fg, bg));
+
+    let (fg, bg) = swatch::DIALOG_TITLE.colors();
+    let shopkeeper_name = names.get(shopkeeper).map(Name::display).unwrap_or_default();
+    batch.print_color(
+        Point::new(2, 0),
+        localization::tr_with("shop.title", &[&shopkeeper_name]),
+        ColorPair::new(fg, bg),
+    );
+
+    let gold_text = localization::tr_with("shop.gold", &[&gold]);
+    batch.print_color(
+        Point::new(width - 2 - gold_text.chars().count() as i32, 0),
+        &gold_text,
+        ColorPair::new(fg, bg),
+    );
+
+    // The tabs of the two sides of the trade
+    let mut tab_x = 2;
+
+    for (mode, id) in [(ShopMode::Buy, "shop.buy"), (ShopMode::Sell, "shop.sell")] {
+        let (fg, bg) = if mode == view.mode {
+            swatch::MENU_OPTION_SELECTED.colors()
+        } else {
+            swatch::MENU_OPTION.colors()
+        };
+        let text = localization::tr(id);
+
+        batch.print_color(Point::new(tab_x, 2), &text, ColorPair::new(fg, bg));
+        tab_x += text.chars().count() as i32 + 3;
+    }
+
+    let first_row = 4;
+
+    if items.is_empty() {
+        batch.print(
+            Point::new(2, first_row),
+            localization::tr(match view.mode {
+                ShopMode::Buy => "shop.sold_out",
+                ShopMode::Sell => "shop.nothing_to_sell",
+            }),
+        );
+    }
+
+    for (index, (name, price, _)) in items.iter().enumerate() {
+        let y = first_row + index as i32;
+        let affordable = view.mode == ShopMode::Sell || *price <= gold;
+
+        let (fg, bg) = if affordable {
+            swatch::DIALOG_OPTION.colors()
+        } else {
+            swatch::MENU_OPTION_DISABLED.colors()
+        };
+        let key_string = virtual_key_code_to_string(i32_to_alpha_key(index as i32));
+        let price_text = localization::tr_with("shop.price", &[price]);
+
+        batch.print_color(
+            Point::new(4, y),
+            format!("{} - {}", key_string, name),
+            ColorPair::new(fg, bg),
+        );
+        batch.print_color(
+            Point::new(width - 2 - price_text.chars().count() as i32, y),
+            &price_text,
+            ColorPair::new(fg, bg),
+        );
+    }
+
+    // The button and the help in the bottom border of the screen
+    let (fg, bg) = swatch::DIALOG_DISMISS_BUTTON.colors();
+    let dismiss_text = localization::tr("shop.leave");
+    let dismiss_area = Rect::with_size(2, height, dismiss_text.chars().count() as i32, 1);
+
+    batch.print_color(
+        Point::new(dismiss_area.x1, dismiss_area.y1),
+        &dismiss_text,
+        ColorPair::new(fg, bg),
+    );
+
+    let help_text = localization::tr("shop.help");
+    batch.print_color(
+        Point::new(width - 2 - help_text.chars().count() as i32, height),
+        &help_text,
+        ColorPair::new(fg, bg),
+    );
+
+    submit_batch(&mut batch, config::LAYER_UI);
+
+    let switched = ShopResult::Browsing {
+        view: ShopView {
+            mode: match view.mode {
+                ShopMode::Buy => ShopMode::Sell,
+                ShopMode::Sell => ShopMode::Buy,
+            },
+        },
+    };
+
+    let trade = |index: usize| match (items.get(index), view.mode) {
+        (Some((_, _, item)), ShopMode::Buy) => ShopResult::Buy { item: *item },
+        (Some((_, _, item)), ShopMode::Sell) => ShopResult::Sell { item: *item },
+        (None, _) => ShopResult::Browsing { view },
+    };
+
+    if ctx.left_click {
+        let point = ctx.mouse_point();
+
+        if dismiss_area.point_in_rect(point) {
+            return ShopResult::Closed;
+        }
+
+        if point.y == 2 {
+            return switched;
+        }
+
+        if point.y >= first_row {
+            return trade((point.y - first_row) as usize);
+        }
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => ShopResult::Closed,
+        Some(VirtualKeyCode::Tab) => switched,
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+
+            if selection >= 0 {
+                trade(selection as usize)
+            } else {
+                ShopResult::Browsing { view }
+            }
+        }
+        None => ShopResult::Browsing { view },
     }
 }
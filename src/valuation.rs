@@ -0,0 +1,141 @@
+//! Module for the valuation of items and the score of a run.
+//!
+//! Every tradeable item carries a [Value]. What it's worth depends on
+//! whether the player knows what it is, unidentified items are worth only
+//! [config::UNIDENTIFIED_PRICE_PERCENT] of their base price. The prices
+//! of the shopkeepers are adjusted by the [PriceModifiers] of the trade.
+//! The wealth of the player, i.e. the gold and the worth of the carried
+//! items, is part of the score of the run, see [run_score].
+
+use specs::prelude::*;
+
+use super::identification::Identification;
+use super::{config, Charisma, Loot, Map, Name, Purse, RunStatistics, Stackable, Value};
+
+/// Returns the worth of an item with the passed [Value], as far as
+/// it's known to the player.
+///
+/// # Arguments
+/// * `value`: The [Value] of the item.
+/// * `identified`: Flag indicating whether or not the item is identified.
+///
+pub fn worth(value: &Value, identified: bool) -> i32 {
+    if identified {
+        value.base_price
+    } else {
+        value.base_price * config::UNIDENTIFIED_PRICE_PERCENT / 100
+    }
+}
+
+/// The modifiers adjusting the prices of a trade.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PriceModifiers {
+    /// The depth on which the trade takes place.
+    pub depth: i32,
+
+    /// The [Charisma] of the player.
+    pub charisma: i32,
+}
+
+impl PriceModifiers {
+    /// Returns the modifiers of a trade of the player on the current level.
+    ///
+    /// # Arguments
+    /// * `ecs`: The [World] in which the player and the [Map] are stored.
+    ///
+    pub fn of_player(ecs: &World) -> Self {
+        let player = *ecs.fetch::<Entity>();
+
+        PriceModifiers {
+            depth: ecs.fetch::<Map>().depth,
+            charisma: ecs
+                .read_storage::<Charisma>()
+                .get(player)
+                .map_or(0, |charisma| charisma.value),
+        }
+    }
+
+    /// Returns the haggled discount or bonus in percent, see
+    /// [config::CHARISMA_PRICE_PERCENT].
+    fn charisma_percent(&self) -> i32 {
+        (self.charisma * config::CHARISMA_PRICE_PERCENT)
+            .clamp(0, config::MAX_CHARISMA_PRICE_PERCENT)
+    }
+
+    /// Returns the price the shopkeepers sell an item with the passed
+    /// [Value] for. The shopkeepers always know what they sell, so
+    /// the price is derived from the base price.
+    ///
+    /// # Arguments
+    /// * `value`: The [Value] of the item.
+    ///
+    pub fn buy_price(&self, value: &Value) -> i32 {
+        let surcharge = 100 + self.depth.max(0) * config::DEPTH_PRICE_PERCENT;
+        let price = value.base_price * surcharge / 100;
+
+        (price * (100 - self.charisma_percent()) / 100).max(1)
+    }
+
+    /// Returns the price the shopkeepers pay for an item with the passed
+    /// [Value], which is a share of its [worth], see [config::SELL_PRICE_PERCENT].
+    ///
+    /// # Arguments
+    /// * `value`: The [Value] of the item.
+    /// * `identified`: Flag indicating whether or not the item is identified.
+    ///
+    pub fn sell_price(&self, value: &Value, identified: bool) -> i32 {
+        let price = worth(value, identified) * config::SELL_PRICE_PERCENT / 100;
+
+        (price * (100 + self.charisma_percent()) / 100).max(1)
+    }
+}
+
+/// Returns the wealth of the passed `owner`, i.e. the gold in its
+/// [Purse] and the [worth] of all items in its backpack. Every
+/// item of a [Stackable] stack counts.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the `owner` and its items are stored.
+/// * `owner`: The [Entity] owning the wealth.
+///
+pub fn wealth(ecs: &World, owner: Entity) -> i32 {
+    let identification = ecs.fetch::<Identification>();
+    let names = ecs.read_storage::<Name>();
+    let values = ecs.read_storage::<Value>();
+    let backpack = ecs.read_storage::<Loot>();
+    let stackables = ecs.read_storage::<Stackable>();
+
+    let gold = ecs
+        .read_storage::<Purse>()
+        .get(owner)
+        .map_or(0, |purse| purse.gold);
+
+    let items: i32 = (&backpack, &values, &names, stackables.maybe())
+        .join()
+        .filter(|(loot, _, _, _)| loot.owner == owner)
+        .map(|(_, value, name, stackable)| {
+            let quantity = stackable.map_or(1, |stackable| stackable.quantity);
+            worth(value, identification.is_identified(&name.name)) * quantity
+        })
+        .sum();
+
+    gold + items
+}
+
+/// Returns the score of the current run, made up of the reached depth,
+/// the defeated monsters and the [wealth] of the player.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the [RunStatistics].
+///
+/// # See also
+/// * [config::SCORE_PER_DEPTH]
+/// * [config::SCORE_PER_KILL]
+///
+pub fn run_score(ecs: &World) -> i32 {
+    let player = *ecs.fetch::<Entity>();
+    let depth = ecs.fetch::<Map>().depth;
+    let kills = ecs.fetch::<RunStatistics>().kills as i32;
+
+    depth * config::SCORE_PER_DEPTH + kills * config::SCORE_PER_KILL + wealth(ecs, player)
+}
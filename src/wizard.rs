@@ -0,0 +1,730 @@
+//! Module for the wizard mode, which exposes cheats for testing.
+//!
+//! The wizard mode is enabled through the `--wizard` command line flag.
+//! While it's enabled, `CTRL + W` opens the wizard dialog, from which the
+//! player becomes invincible, teleports to a clicked tile, spawns any
+//! entity of the [Raws] or skips to the next level. To inspect the
+//! generated levels, the dialog also toggles whether the whole [Map] is
+//! drawn as explored and whether entities out of the fov are drawn.
+//!
+//! The options of the dialogs request a [WizardCommand], which is executed
+//! by the game in [RunState::Wizard], since the callbacks of a
+//! [DialogInterface] can't change the [World].
+//!
+//! Cheating is never allowed in a [DailyRun], whose result is recorded on
+//! the daily scoreboard. Every other run, in which a cheat has been used,
+//! is marked in its [RunStatistics] and no longer scored.
+
+use rltk::{Point, VirtualKeyCode};
+use specs::prelude::*;
+
+use super::daily::DailyRun;
+use super::particles::ParticleBuilder;
+use super::raws::Raws;
+use super::{entity_factory, localization};
+use super::{
+    i32_to_alpha_key, DialogArgs, DialogInterface, DialogOption, GameLog, LogCategory, Map,
+    Position, RunState, RunStatistics, Statistics, FOV,
+};
+
+/// The amount of entities listed on a single page of the spawn dialog.
+const SPAWN_PAGE_SIZE: usize = 10;
+
+/// Resource holding the state of the wizard mode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WizardMode {
+    /// Flag indicating whether or not the wizard mode
+    /// has been enabled on the command line.
+    pub enabled: bool,
+
+    /// Flag indicating whether or not the player survives all damage.
+    pub invincible: bool,
+
+    /// Flag indicating whether or not every tile of the
+    /// [Map] is drawn, as if it had been explored.
+    pub reveal_map: bool,
+
+    /// Flag indicating whether or not all entities are drawn,
+    /// regardless of the fov and their invisibility.
+    pub show_all_entities: bool,
+
+    /// The command requested through the wizard dialog,
+    /// which is executed in [RunState::Wizard].
+    pub pending: Option<WizardCommand>,
+}
+
+/// Enum describing the commands of the wizard dialogs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WizardCommand {
+    /// Toggles the invincibility of the player.
+    ToggleInvincibility,
+
+    /// Toggles whether or not the whole map is revealed.
+    ToggleRevealMap,
+
+    /// Toggles whether or not all entities are shown.
+    ToggleShowAllEntities,
+
+    /// Lets the player pick the tile to teleport to.
+    Teleport,
+
+    /// Lists the categories of the entities to spawn.
+    ChooseCategory,
+
+    /// Lists a page of the entities of the `category`.
+    Browse {
+        /// The category of the listed entities.
+        category: SpawnCategory,
+
+        /// The index of the listed page, starting at `0`.
+        page: usize,
+    },
+
+    /// Spawns the entity with the name next to the player.
+    Spawn(String),
+
+    /// Descends to the next level.
+    SkipLevel,
+}
+
+/// Enum describing the categories of the entities,
+/// that can be spawned through the wizard mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnCategory {
+    /// The monsters of the dungeon.
+    Monsters,
+
+    /// The unique monsters.
+    Uniques,
+
+    /// All items.
+    Items,
+
+    /// The NPCs of the town.
+    Npcs,
+}
+
+impl SpawnCategory {
+    /// All categories in the order of their display.
+    const ALL: [SpawnCategory; 4] = [
+        SpawnCategory::Monsters,
+        SpawnCategory::Uniques,
+        SpawnCategory::Items,
+        SpawnCategory::Npcs,
+    ];
+
+    /// Returns the key selecting the category.
+    fn key(&self) -> VirtualKeyCode {
+        match self {
+            SpawnCategory::Monsters => VirtualKeyCode::M,
+            SpawnCategory::Uniques => VirtualKeyCode::U,
+            SpawnCategory::Items => VirtualKeyCode::I,
+            SpawnCategory::Npcs => VirtualKeyCode::N,
+        }
+    }
+
+    /// Returns the localized name of the category.
+    fn description(&self) -> String {
+        match self {
+            SpawnCategory::Monsters => localization::tr("wizard.spawn.monsters"),
+            SpawnCategory::Uniques => localization::tr("wizard.spawn.uniques"),
+            SpawnCategory::Items => localization::tr("wizard.spawn.items"),
+            SpawnCategory::Npcs => localization::tr("wizard.spawn.npcs"),
+        }
+    }
+
+    /// Returns the names of all entities of the category in alphabetical order.
+    ///
+    /// # Arguments
+    /// * `raws`: The [Raws] defining the entities.
+    ///
+    fn names(&self, raws: &Raws) -> Vec<String> {
+        match self {
+            SpawnCategory::Monsters => raws.monster_names(),
+            SpawnCategory::Uniques => raws.unique_names(),
+            SpawnCategory::Items => raws.item_names(),
+            SpawnCategory::Npcs => raws.npc_names(),
+        }
+    }
+}
+
+/// Returns `true` if the wizard mode has been enabled
+/// on the command line, `false` otherwise.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [WizardMode] is registered.
+///
+pub fn is_enabled(ecs: &World) -> bool {
+    ecs.try_fetch::<WizardMode>()
+        .is_some_and(|wizard_mode| wizard_mode.enabled)
+}
+
+/// Returns `true` if every tile of the [Map] should be drawn,
+/// as if it had been explored, `false` otherwise.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [WizardMode] is registered.
+///
+/// # Notes
+/// * Always `false` in a [DailyRun].
+///
+pub fn reveals_map(ecs: &World) -> bool {
+    !ecs.has_value::<DailyRun>()
+        && ecs
+            .try_fetch::<WizardMode>()
+            .is_some_and(|wizard_mode| wizard_mode.enabled && wizard_mode.reveal_map)
+}
+
+/// Returns `true` if all entities should be drawn, regardless
+/// of the fov and their invisibility, `false` otherwise.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [WizardMode] is registered.
+///
+/// # Notes
+/// * Always `false` in a [DailyRun].
+///
+pub fn shows_all_entities(ecs: &World) -> bool {
+    !ecs.has_value::<DailyRun>()
+        && ecs
+            .try_fetch::<WizardMode>()
+            .is_some_and(|wizard_mode| wizard_mode.enabled && wizard_mode.show_all_entities)
+}
+
+/// Opens the wizard dialog and returns the next [RunState]. Does nothing
+/// if the wizard mode is disabled. In a [DailyRun] the player is only
+/// told, that cheating isn't allowed.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the dialog should be registered.
+///
+pub fn open(ecs: &mut World) -> RunState {
+    if !is_enabled(ecs) {
+        return RunState::AwaitingInput;
+    }
+
+    if ecs.has_value::<DailyRun>() {
+        ecs.write_resource::<GameLog>()
+            .push(&localization::tr("wizard.scored_run"), LogCategory::General);
+        return RunState::AwaitingInput;
+    }
+
+    let (invincibility, reveal_map, show_all_entities) = {
+        let wizard_mode = ecs.fetch::<WizardMode>();
+
+        (
+            toggle_state(wizard_mode.invincible),
+            toggle_state(wizard_mode.reveal_map),
+            toggle_state(wizard_mode.show_all_entities),
+        )
+    };
+
+    DialogInterface::register_dialog(
+        ecs,
+        localization::tr("wizard.title"),
+        Some(localization::tr("wizard.message")),
+        vec![
+            command_option(
+                localization::tr_with("wizard.invincibility", &[&invincibility]),
+                VirtualKeyCode::I,
+                WizardCommand::ToggleInvincibility,
+            ),
+            command_option(
+                localization::tr("wizard.teleport"),
+                VirtualKeyCode::T,
+                WizardCommand::Teleport,
+            ),
+            command_option(
+                localization::tr("wizard.spawn"),
+                VirtualKeyCode::S,
+                WizardCommand::ChooseCategory,
+            ),
+            command_option(
+                localization::tr("wizard.skip_level"),
+                VirtualKeyCode::L,
+                WizardCommand::SkipLevel,
+            ),
+            command_option(
+                localization::tr_with("wizard.reveal_map", &[&reveal_map]),
+                VirtualKeyCode::R,
+                WizardCommand::ToggleRevealMap,
+            ),
+            command_option(
+                localization::tr_with("wizard.show_all_entities", &[&show_all_entities]),
+                VirtualKeyCode::E,
+                WizardCommand::ToggleShowAllEntities,
+            ),
+        ],
+        true,
+    );
+
+    RunState::Dialog
+}
+
+/// Returns the localized state of a toggle of the wizard dialog.
+///
+/// # Arguments
+/// * `enabled`: Flag indicating whether or not the toggle is on.
+///
+fn toggle_state(enabled: bool) -> String {
+    if enabled {
+        localization::tr("menu.on")
+    } else {
+        localization::tr("menu.off")
+    }
+}
+
+/// Creates the [DialogOption] requesting the passed `command`.
+///
+/// # Arguments
+/// * `description`: The description of the option.
+/// * `key`: The key selecting the option.
+/// * `command`: The [WizardCommand] to execute.
+///
+fn command_option(
+    description: String,
+    key: VirtualKeyCode,
+    command: WizardCommand,
+) -> DialogOption {
+    DialogOption {
+        description,
+        key,
+        args: vec![Box::new(command)],
+        callback: Box::new(|world, _, args| request(world, args)),
+    }
+}
+
+/// Callback of the [DialogOption]s of the wizard dialogs, which stores
+/// the selected [WizardCommand] and requests [RunState::Wizard].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [WizardMode] is registered.
+/// * `args`: The [WizardCommand] of the option.
+///
+fn request(ecs: &World, args: &DialogArgs) {
+    if let Some(command) = args
+        .first()
+        .and_then(|arg| arg.downcast_ref::<WizardCommand>())
+    {
+        ecs.write_resource::<WizardMode>().pending = Some(command.clone());
+        *ecs.write_resource::<RunState>() = RunState::Wizard;
+    }
+}
+
+/// Executes the [WizardCommand] requested through the wizard dialog
+/// and returns the next [RunState].
+///
+/// # Arguments
+/// * `ecs`: The [World] the command is executed on.
+///
+/// # Notes
+/// * Every command marks the run as cheated in its [RunStatistics].
+///
+pub fn execute(ecs: &mut World) -> RunState {
+    let command = match ecs.try_fetch_mut::<WizardMode>() {
+        Some(mut wizard_mode) => wizard_mode.pending.take(),
+        None => None,
+    };
+
+    let command = match command {
+        Some(command) if !ecs.has_value::<DailyRun>() => command,
+        _ => return RunState::AwaitingInput,
+    };
+
+    ecs.write_resource::<RunStatistics>().wizard = true;
+
+    match command {
+        WizardCommand::ToggleInvincibility => {
+            let invincible = {
+                let mut wizard_mode = ecs.write_resource::<WizardMode>();
+                wizard_mode.invincible = !wizard_mode.invincible;
+                wizard_mode.invincible
+            };
+
+            let id = if invincible {
+                "wizard.invincible"
+            } else {
+                "wizard.vulnerable"
+            };
+
+            ecs.write_resource::<GameLog>()
+                .push(&localization::tr(id), LogCategory::General);
+            RunState::AwaitingInput
+        }
+        WizardCommand::ToggleRevealMap => {
+            let mut wizard_mode = ecs.write_resource::<WizardMode>();
+            wizard_mode.reveal_map = !wizard_mode.reveal_map;
+            RunState::AwaitingInput
+        }
+        WizardCommand::ToggleShowAllEntities => {
+            let mut wizard_mode = ecs.write_resource::<WizardMode>();
+            wizard_mode.show_all_entities = !wizard_mode.show_all_entities;
+            RunState::AwaitingInput
+        }
+        WizardCommand::Teleport => RunState::WizardTeleport,
+        WizardCommand::ChooseCategory => {
+            show_categories(ecs);
+            RunState::Dialog
+        }
+        WizardCommand::Browse { category, page } => {
+            show_page(ecs, category, page);
+            RunState::Dialog
+        }
+        WizardCommand::Spawn(name) => {
+            spawn(ecs, &name);
+            RunState::Ticking
+        }
+        WizardCommand::SkipLevel => RunState::NextLevel,
+    }
+}
+
+/// Registers the dialog listing the [SpawnCategory]s.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the dialog should be registered.
+///
+fn show_categories(ecs: &mut World) {
+    let options = SpawnCategory::ALL
+        .iter()
+        .map(|category| {
+            command_option(
+                category.description(),
+                category.key(),
+                WizardCommand::Browse {
+                    category: *category,
+                    page: 0,
+                },
+            )
+        })
+        .collect();
+
+    DialogInterface::register_dialog(
+        ecs,
+        localization::tr("wizard.spawn"),
+        Some(localization::tr("wizard.spawn.category")),
+        options,
+        true,
+    );
+}
+
+/// Registers the dialog listing the passed `page` of the entities
+/// of the `category`, see [SPAWN_PAGE_SIZE].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the dialog should be registered.
+/// * `category`: The [SpawnCategory] of the listed entities.
+/// * `page`: The index of the listed page, starting at `0`.
+///
+/// # Notes
+/// * The entities are selected through the letters `A` to `J`,
+///   the pages are turned through `Y` and `Z`.
+///
+fn show_page(ecs: &mut World, category: SpawnCategory, page: usize) {
+    let names = category.names(&ecs.fetch::<Raws>());
+    let pages = names.len().div_ceil(SPAWN_PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+
+    let mut options = names
+        .iter()
+        .skip(page * SPAWN_PAGE_SIZE)
+        .take(SPAWN_PAGE_SIZE)
+        .enumerate()
+        .map(|(index, name)| {
+            command_option(
+                localization::entity_name(name),
+                i32_to_alpha_key(index as i32),
+                WizardCommand::Spawn(name.clone()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if page > 0 {
+        options.push(command_option(
+            localization::tr("wizard.spawn.previous"),
+            VirtualKeyCode::Y,
+            WizardCommand::Browse {
+                category,
+                page: page - 1,
+            },
+        ));
+    }
+
+    if page + 1 < pages {
+        options.push(command_option(
+            localization::tr("wizard.spawn.next"),
+            VirtualKeyCode::Z,
+            WizardCommand::Browse {
+                category,
+                page: page + 1,
+            },
+        ));
+    }
+
+    DialogInterface::register_dialog(
+        ecs,
+        category.description(),
+        Some(localization::tr_with(
+            "wizard.spawn.page",
+            &[&(page + 1), &pages],
+        )),
+        options,
+        true,
+    );
+}
+
+/// Spawns the entity with the passed `name` on a free tile next
+/// to the player. If all tiles around the player are blocked,
+/// nothing is spawned and the player is informed through the [GameLog].
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the entity should be created.
+/// * `name`: The name of the entity, e.g. `"Goblin"`.
+///
+fn spawn(ecs: &mut World, name: &str) {
+    let spawned = free_tile_next_to_player(ecs).and_then(|tile| {
+        entity_factory::new_named(
+            ecs,
+            name,
+            Position {
+                x: tile.x,
+                y: tile.y,
+            },
+        )
+    });
+
+    let message = match spawned {
+        Some(_) => localization::tr_with("wizard.spawned", &[&localization::entity_name(name)]),
+        None => localization::tr("wizard.no_room"),
+    };
+
+    ecs.write_resource::<GameLog>()
+        .push(&message, LogCategory::General);
+}
+
+/// Returns the first tile next to the player, that isn't blocked,
+/// or `None` if all tiles around the player are blocked.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player and the [Map].
+///
+pub fn free_tile_next_to_player(ecs: &World) -> Option<Point> {
+    let player_position = *ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+
+    (-1..=1)
+        .flat_map(|y| (-1..=1).map(move |x| (x, y)))
+        .filter(|offset| *offset != (0, 0))
+        .map(|(x, y)| Point::new(player_position.x + x, player_position.y + y))
+        .find(|tile| map.check_idx(tile.x, tile.y) && !map.is_tile_blocked(tile.x, tile.y))
+}
+
+/// Teleports the player onto the passed `target` tile and returns
+/// the next [RunState]. Blocked tiles can't be teleported onto.
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player.
+/// * `target`: The tile the player has clicked.
+///
+pub fn teleport(ecs: &mut World, target: Point) -> RunState {
+    let player = *ecs.fetch::<Entity>();
+
+    {
+        let map = ecs.fetch::<Map>();
+
+        if !map.check_idx(target.x, target.y) || map.is_tile_blocked(target.x, target.y) {
+            return RunState::AwaitingInput;
+        }
+    }
+
+    if let Some(position) = ecs.write_storage::<Position>().get_mut(player) {
+        position.x = target.x;
+        position.y = target.y;
+    }
+
+    if let Some(fov) = ecs.write_storage::<FOV>().get_mut(player) {
+        fov.mark_as_dirty();
+    }
+
+    *ecs.write_resource::<Point>() = target;
+
+    if let Some(mut particle_builder) = ecs.try_fetch_mut::<ParticleBuilder>() {
+        particle_builder.teleport(target);
+    }
+
+    RunState::Ticking
+}
+
+/// Restores the health of the player, if the player has been
+/// defeated while invincible. Must be called before the defeated
+/// entities are removed, see [DamageSystem::clean_up](super::DamageSystem::clean_up).
+///
+/// # Arguments
+/// * `ecs`: The [World] containing the player.
+///
+/// # Notes
+/// * Does nothing in a [DailyRun], even if the invincibility
+///   has been toggled on before the run started.
+///
+pub fn protect_player(ecs: &World) {
+    let invincible = ecs
+        .try_fetch::<WizardMode>()
+        .is_some_and(|wizard_mode| wizard_mode.enabled && wizard_mode.invincible);
+
+    if !invincible || ecs.has_value::<DailyRun>() {
+        return;
+    }
+
+    let player = *ecs.fetch::<Entity>();
+
+    if let Some(statistics) = ecs.write_storage::<Statistics>().get_mut(player) {
+        if statistics.hp < 1 {
+            statistics.hp = statistics.hp_max;
+        }
+    }
+}
+
+/// Turns off all toggles of the [WizardMode] and drops the pending
+/// [WizardCommand], so no cheat carries over into the next run.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [WizardMode] is registered.
+///
+pub fn reset(ecs: &mut World) {
+    if let Some(mut wizard_mode) = ecs.try_fetch_mut::<WizardMode>() {
+        *wizard_mode = WizardMode {
+            enabled: wizard_mode.enabled,
+            ..WizardMode::default()
+        };
+    }
+}
+
+/// Registers the [WizardMode] with the passed `ecs`.
+///
+/// # Arguments
+/// * `ecs`: The [World] in which the [WizardMode] should be registered.
+/// * `enabled`: Flag indicating whether or not the wizard mode
+///   has been enabled on the command line.
+///
+pub fn register(ecs: &mut World, enabled: bool) {
+    ecs.insert(WizardMode {
+        enabled,
+        ..WizardMode::default()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::game_config::{self, GameConfig};
+    use super::super::test_support::TEST_SEED;
+    use super::super::{
+        faction, identification, raws, register_components, rng, scripting, spawn_table, State,
+    };
+    use super::*;
+
+    /// Creates a [State] registered like the one of the game,
+    /// with the wizard mode enabled on the command line.
+    fn wizard_state() -> State {
+        let mut state = State::new();
+
+        rng::register(&mut state.ecs, Some(TEST_SEED));
+        scripting::register(&mut state.ecs);
+        game_config::register(&mut state.ecs, GameConfig::default());
+        register(&mut state.ecs, true);
+        raws::register(&mut state.ecs);
+        spawn_table::register(&mut state.ecs);
+        faction::register(&mut state.ecs);
+        identification::register(&mut state.ecs);
+        register_components(&mut state.ecs);
+
+        state
+    }
+
+    /// Requests the passed `command` through the wizard dialog and executes it.
+    fn cheat(state: &mut State, command: WizardCommand) {
+        state.ecs.write_resource::<WizardMode>().pending = Some(command);
+        execute(&mut state.ecs);
+    }
+
+    /// Reduces the health of the player to zero and
+    /// returns the health left after the protection.
+    fn defeat_player(state: &mut State) -> i32 {
+        let player = *state.ecs.fetch::<Entity>();
+
+        state
+            .ecs
+            .write_storage::<Statistics>()
+            .get_mut(player)
+            .unwrap()
+            .hp = 0;
+
+        protect_player(&state.ecs);
+
+        state
+            .ecs
+            .read_storage::<Statistics>()
+            .get(player)
+            .unwrap()
+            .hp
+    }
+
+    #[test]
+    fn invincibility_protects_player() {
+        let mut state = wizard_state();
+        state.new_game(false);
+        cheat(&mut state, WizardCommand::ToggleInvincibility);
+
+        assert!(defeat_player(&mut state) > 0);
+    }
+
+    #[test]
+    fn invincibility_ends_with_the_run() {
+        let mut state = wizard_state();
+        state.new_game(false);
+        cheat(&mut state, WizardCommand::ToggleInvincibility);
+
+        state.new_game(true);
+
+        assert!(!state.ecs.fetch::<WizardMode>().invincible);
+        assert!(defeat_player(&mut state) < 1);
+    }
+
+    #[test]
+    fn invincibility_is_ignored_in_daily_run() {
+        let mut state = wizard_state();
+        state.new_game(true);
+        state.ecs.write_resource::<WizardMode>().invincible = true;
+
+        assert!(defeat_player(&mut state) < 1);
+    }
+
+    #[test]
+    fn revealing_ends_with_the_run() {
+        let mut state = wizard_state();
+        state.new_game(false);
+        cheat(&mut state, WizardCommand::ToggleRevealMap);
+        cheat(&mut state, WizardCommand::ToggleShowAllEntities);
+
+        assert!(reveals_map(&state.ecs));
+        assert!(shows_all_entities(&state.ecs));
+
+        state.new_game(false);
+
+        assert!(!reveals_map(&state.ecs));
+        assert!(!shows_all_entities(&state.ecs));
+    }
+
+    #[test]
+    fn revealing_is_ignored_in_daily_run() {
+        let mut state = wizard_state();
+        state.new_game(true);
+
+        {
+            let mut wizard_mode = state.ecs.write_resource::<WizardMode>();
+            wizard_mode.reveal_map = true;
+            wizard_mode.show_all_entities = true;
+        }
+
+        assert!(!reveals_map(&state.ecs));
+        assert!(!shows_all_entities(&state.ecs));
+    }
+}